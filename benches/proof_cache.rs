@@ -0,0 +1,48 @@
+//! Latency of proving the same leaf of a 1000-leaf L1 deposit tree over and over, as
+//! clients polling for their inclusion proof do, computed from the tree every time and
+//! answered from the `ProofCache` the API serves proofs through.
+//!
+//! Run with `cargo bench --bench proof_cache`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tokio::runtime::Runtime;
+use tree_builder::l1_tree::L1MerkleTreeBuilder;
+use zeroxbridge_sequencer::tree_builder::ProofCache;
+
+const LEAVES: u64 = 1000;
+
+fn leaf(i: u64) -> [u8; 32] {
+    let mut leaf = [0u8; 32];
+    leaf[24..].copy_from_slice(&i.to_be_bytes());
+    leaf
+}
+
+async fn build_tree() -> L1MerkleTreeBuilder {
+    let mut tree = L1MerkleTreeBuilder::new();
+    tree.build_merkle((0..LEAVES).map(leaf).collect())
+        .await
+        .unwrap();
+    tree
+}
+
+fn proof_cache(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let tree = rt.block_on(build_tree());
+    let requested = leaf(LEAVES / 2);
+    let mut group = c.benchmark_group("proof_of_same_leaf_1000_leaves");
+
+    group.bench_function("uncached", |b| {
+        b.iter(|| rt.block_on(tree.get_proof(requested)).unwrap())
+    });
+
+    let cache = ProofCache::default();
+    group.bench_function("cached", |b| {
+        b.iter(|| rt.block_on(cache.prove(&tree, requested)).unwrap())
+    });
+    assert!(cache.stats().hit_ratio() > 0.99);
+
+    group.finish();
+}
+
+criterion_group!(benches, proof_cache);
+criterion_main!(benches);
@@ -0,0 +1,95 @@
+//! Read latency of the L1 deposit tree while the tree builder appends to it.
+//!
+//! Each iteration runs 100 API-like readers (`get_root` and `get_proof`) against one
+//! writer appending a leaf, with the tree behind a `Mutex` as it used to be and behind
+//! the `RwLock` the sequencer now shares it through.
+//!
+//! Run with `cargo bench --bench tree_lock`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::sync::{Mutex, RwLock};
+use tree_builder::l1_tree::L1MerkleTreeBuilder;
+
+const READERS: usize = 100;
+const INITIAL_LEAVES: u64 = 128;
+
+fn leaf(i: u64) -> [u8; 32] {
+    let mut leaf = [0u8; 32];
+    leaf[24..].copy_from_slice(&i.to_be_bytes());
+    leaf
+}
+
+async fn build_tree() -> L1MerkleTreeBuilder {
+    let mut tree = L1MerkleTreeBuilder::new();
+    tree.build_merkle((0..INITIAL_LEAVES).map(leaf).collect())
+        .await
+        .unwrap();
+    tree
+}
+
+async fn read(tree: &L1MerkleTreeBuilder, i: usize) {
+    tree.get_root().await.unwrap();
+    tree.get_proof(leaf(i as u64 % INITIAL_LEAVES))
+        .await
+        .unwrap();
+}
+
+async fn mutex_round(tree: &Arc<Mutex<L1MerkleTreeBuilder>>) {
+    let writer = {
+        let tree = tree.clone();
+        tokio::spawn(async move {
+            let mut tree = tree.lock().await;
+            tree.build_merkle(vec![leaf(u64::MAX)]).await.unwrap();
+        })
+    };
+    let readers: Vec<_> = (0..READERS)
+        .map(|i| {
+            let tree = tree.clone();
+            tokio::spawn(async move { read(&*tree.lock().await, i).await })
+        })
+        .collect();
+
+    writer.await.unwrap();
+    for reader in readers {
+        reader.await.unwrap();
+    }
+}
+
+async fn rwlock_round(tree: &Arc<RwLock<L1MerkleTreeBuilder>>) {
+    let writer = {
+        let tree = tree.clone();
+        tokio::spawn(async move {
+            let mut tree = tree.write().await;
+            tree.build_merkle(vec![leaf(u64::MAX)]).await.unwrap();
+        })
+    };
+    let readers: Vec<_> = (0..READERS)
+        .map(|i| {
+            let tree = tree.clone();
+            tokio::spawn(async move { read(&*tree.read().await, i).await })
+        })
+        .collect();
+
+    writer.await.unwrap();
+    for reader in readers {
+        reader.await.unwrap();
+    }
+}
+
+fn tree_lock(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("tree_100_readers_1_writer");
+
+    let tree = Arc::new(Mutex::new(rt.block_on(build_tree())));
+    group.bench_function("mutex", |b| b.iter(|| rt.block_on(mutex_round(&tree))));
+
+    let tree = Arc::new(RwLock::new(rt.block_on(build_tree())));
+    group.bench_function("rwlock", |b| b.iter(|| rt.block_on(rwlock_round(&tree))));
+
+    group.finish();
+}
+
+criterion_group!(benches, tree_lock);
+criterion_main!(benches);
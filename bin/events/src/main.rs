@@ -0,0 +1,134 @@
+use clap::{Arg, Command};
+use std::path::PathBuf;
+use tracing::{error, info};
+use zeroxbridge_sequencer::config::load_config;
+use zeroxbridge_sequencer::db::database::get_db_pool;
+use zeroxbridge_sequencer::events::backfill_deposit_hashes;
+use zeroxbridge_sequencer::events::l1_event_watcher::RealEthereumProvider;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Initialize logging
+    tracing_subscriber::fmt::init();
+
+    let matches = Command::new("Events")
+        .version("1.0")
+        .about("Maintenance of the ingested L1 events")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("CONFIG_FILE")
+                .help("Path to configuration file")
+                .default_value("config.toml")
+                .global(true)
+                .value_parser(clap::value_parser!(String)),
+        )
+        .subcommand(
+            Command::new("backfill")
+                .about(
+                    "Ingest the DepositHashAppended events of a block range, resuming after \
+                     the last block recorded by the block tracker",
+                )
+                .arg(
+                    Arg::new("network")
+                        .long("network")
+                        .value_name("NAME")
+                        .help("Network whose events are ingested")
+                        .default_value("default")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("from_block")
+                        .long("from-block")
+                        .value_name("BLOCK")
+                        .help("First block to ingest, usually the bridge deployment block")
+                        .required(true)
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("to_block")
+                        .long("to-block")
+                        .value_name("BLOCK")
+                        .help("Last block to ingest (defaults to the latest final block)")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("chunk_size")
+                        .long("chunk-size")
+                        .value_name("BLOCKS")
+                        .help("Blocks fetched, and inserted in one statement, at a time")
+                        .default_value("2000")
+                        .value_parser(clap::value_parser!(u64).range(1..)),
+                ),
+        )
+        .get_matches();
+
+    let config_path = PathBuf::from(matches.get_one::<String>("config").unwrap());
+    let config = load_config(Some(&config_path))?;
+    let pool = get_db_pool(&config.database).await?;
+
+    match matches.subcommand() {
+        Some(("backfill", args)) => {
+            let name = args.get_one::<String>("network").unwrap();
+            let network = config
+                .active_networks()
+                .into_iter()
+                .find(|network| network.name.as_str() == name)
+                .ok_or_else(|| format!("Network {} is not configured", name))?;
+            let provider = RealEthereumProvider::new(network.ethereum.get_rpc_url());
+
+            let to_block = match args.get_one::<u64>("to_block") {
+                Some(to_block) => *to_block,
+                None => provider
+                    .get_block_number()
+                    .await?
+                    .saturating_sub(network.ethereum.confirmations as u64),
+            };
+            let from_block = *args.get_one::<u64>("from_block").unwrap();
+            let chunk_size = *args.get_one::<u64>("chunk_size").unwrap();
+            info!(
+                "Backfilling deposit hashes of network {} from block {} to {} ({} blocks per chunk)",
+                network.name, from_block, to_block, chunk_size
+            );
+
+            let result = backfill_deposit_hashes(
+                &pool,
+                &network.name,
+                &provider,
+                &network.contracts.l1_contract_address,
+                from_block,
+                to_block,
+                chunk_size,
+                |progress| {
+                    info!(
+                        "Blocks {}/{}, {} of {} events inserted, {:.1} blocks/s",
+                        progress.blocks_done,
+                        progress.blocks_total(),
+                        progress.rows_inserted,
+                        progress.rows_fetched,
+                        progress.blocks_per_second()
+                    )
+                },
+            )
+            .await;
+
+            match result {
+                Ok(progress) => {
+                    info!(
+                        "Backfill done: {} blocks, {} events inserted, {} already stored",
+                        progress.blocks_done,
+                        progress.rows_inserted,
+                        progress.rows_fetched - progress.rows_inserted
+                    );
+                    Ok(())
+                }
+                Err(e) => {
+                    error!("Deposit hash backfill failed: {}", e);
+                    Err(e.to_string().into())
+                }
+            }
+        }
+        _ => unreachable!("a subcommand is required"),
+    }
+}
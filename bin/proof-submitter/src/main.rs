@@ -1,10 +1,12 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 use tracing::{error, info};
-use zeroxbridge_sequencer::config::load_config;
-use zeroxbridge_sequencer::db::database::get_db_pool;
-use zeroxbridge_sequencer::relayer::client::ProofSubmissionClient;
+use zeroxbridge_sequencer::proof_submitter::{
+    self, grpc, SubmitterArgs, DEFAULT_HASHER, DEFAULT_LAYOUT, DEFAULT_MEMORY_VERIFICATION,
+    DEFAULT_STONE_VERSION,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -19,7 +21,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .long("calldata_dir")
                 .value_name("PATH")
                 .help("Path to the calldata directory")
-                .required(true)
+                .required_unless_present("grpc_addr")
                 .value_parser(clap::value_parser!(String)),
         )
         .arg(
@@ -27,7 +29,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .long("job_id")
                 .value_name("ID")
                 .help("Proof job ID")
-                .required(true)
+                .required_unless_present("grpc_addr")
                 .value_parser(clap::value_parser!(String)),
         )
         .arg(
@@ -35,7 +37,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .long("layout")
                 .value_name("LAYOUT")
                 .help("Layout parameter")
-                .default_value("recursive_with_poseidon")
+                .default_value(DEFAULT_LAYOUT)
                 .value_parser(clap::value_parser!(String)),
         )
         .arg(
@@ -43,7 +45,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .long("hasher")
                 .value_name("HASHER")
                 .help("Hasher parameter")
-                .default_value("keccak_160_lsb")
+                .default_value(DEFAULT_HASHER)
                 .value_parser(clap::value_parser!(String)),
         )
         .arg(
@@ -51,7 +53,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .long("stone_version")
                 .value_name("VERSION")
                 .help("Stone version parameter")
-                .default_value("stone6")
+                .default_value(DEFAULT_STONE_VERSION)
                 .value_parser(clap::value_parser!(String)),
         )
         .arg(
@@ -59,7 +61,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .long("memory_verification")
                 .value_name("VERIFICATION")
                 .help("Memory verification parameter")
-                .default_value("true")
+                .default_value(DEFAULT_MEMORY_VERIFICATION)
                 .value_parser(clap::value_parser!(String)),
         )
         .arg(
@@ -70,8 +72,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 .default_value("config.toml")
                 .value_parser(clap::value_parser!(String)),
         )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("Validate the calldata without submitting any transaction")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("report")
+                .long("report")
+                .value_name("PATH")
+                .help("Path of the JSON report (defaults to <calldata_dir>/submission_report.json)")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("grpc_addr")
+                .long("grpc_addr")
+                .value_name("ADDR")
+                .help("Serve submissions over gRPC on ADDR instead of submitting once")
+                .value_parser(clap::value_parser!(SocketAddr)),
+        )
         .get_matches();
 
+    let config_path = PathBuf::from(matches.get_one::<String>("config").unwrap());
+    if let Some(addr) = matches.get_one::<SocketAddr>("grpc_addr") {
+        return grpc::serve(*addr, config_path)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>);
+    }
+
     // Parse arguments
     let calldata_dir = PathBuf::from(matches.get_one::<String>("calldata_dir").unwrap());
     let job_id = u64::from_str(matches.get_one::<String>("job_id").unwrap())
@@ -83,7 +112,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         .get_one::<String>("memory_verification")
         .unwrap()
         .clone();
-    let config_path = PathBuf::from(matches.get_one::<String>("config").unwrap());
+    let dry_run = matches.get_flag("dry_run");
+    let report_path = matches
+        .get_one::<String>("report")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| calldata_dir.join("submission_report.json"));
 
     info!("Starting proof submission with parameters:");
     info!("  Calldata directory: {:?}", calldata_dir);
@@ -92,35 +125,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     info!("  Hasher: {}", hasher);
     info!("  Stone version: {}", stone_version);
     info!("  Memory verification: {}", memory_verification);
+    info!("  Dry run: {}", dry_run);
 
-    // Load configuration
-    let config = load_config(Some(&config_path))?;
-    info!("Configuration loaded successfully");
+    let args = SubmitterArgs {
+        calldata_dir,
+        job_id,
+        layout,
+        hasher,
+        stone_version,
+        memory_verification,
+        config_path,
+        dry_run,
+        report_path: Some(report_path),
+    };
 
-    // Initialize database connection
-    let db_pool = get_db_pool(&config.database.get_db_url()).await?;
-    info!("Database connection established");
-
-    // Create proof submission client
-    let client = ProofSubmissionClient::new(db_pool, config)
-        .await
-        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-    info!("Proof submission client initialized");
-
-    // Submit the proof
-    match client
-        .submit_proof(
-            calldata_dir,
-            job_id,
-            layout,
-            hasher,
-            stone_version,
-            memory_verification,
-        )
-        .await
-    {
-        Ok(_) => {
-            info!("Proof submission completed successfully!");
+    match proof_submitter::run(args).await {
+        Ok(report) => {
+            if report.dry_run {
+                info!(
+                    "Calldata is valid, {} transactions would be submitted",
+                    report.stages.len()
+                );
+            } else {
+                info!(
+                    "Proof submission completed successfully! Fact hash: {}",
+                    report.fact_hash.as_deref().unwrap_or("unknown")
+                );
+            }
             Ok(())
         }
         Err(e) => {
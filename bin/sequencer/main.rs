@@ -1,51 +1,228 @@
 mod api;
 mod config;
 mod db;
+mod events;
+mod herodotus;
+mod maintenance;
+mod proof_client;
 mod proof_generator;
 mod queue;
 mod relayer;
+mod simulation;
+mod telemetry;
+mod tree_builder;
+mod webhooks;
 // mod merkle_tree;
 // mod oracle_service;
 
-use crate::relayer::starknet_relayer::{StarknetRelayer, StarknetRelayerConfig};
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+use crate::api::routes::TreeState;
+use crate::config::{
+    load_config, AppConfig, GrpcConfig, HerodotusConfig, JanitorConfig, L1QueueConfig,
+    LimitsConfig, LiveConfig, MerkleConfig, NetworkConfig, NetworkId, QueueConfig, RelayerConfig,
+    WebhookConfig,
+};
+use crate::db::database::get_db_pool;
+use crate::db::migrations::{DatabaseMigrationValidator, MIGRATOR};
+use crate::events::l1_event_watcher::RealEthereumProvider;
+use crate::events::l2_event_watcher::start_l2_event_loop;
+use crate::events::watcher::start_event_loop;
+use crate::herodotus::{HerodotusClient, StateProofPoller, StateProofRequester};
+use crate::maintenance::Janitor;
+use crate::queue::deposit_reconciler::DepositReconciler;
+use crate::queue::l1_queue::{L1QueueProcessor, WithdrawalQueueProcessor};
+use crate::queue::l2_queue::{CachedInclusionProofs, L2QueueProcessor};
+use crate::simulation::{backend_from_config, PipelineBackend};
+use crate::telemetry::{otlp_tracer_provider, telemetry_layer};
+use crate::tree_builder::{
+    L2TreeBuilderClient, L2TreeBuilderConfig, TreeBuilderClient, TreeBuilderConfig,
+};
+use crate::webhooks::WebhookDispatcher;
+use clap::{Arg, ArgAction, Command};
+use sqlx::{Pool, Postgres};
+use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet::providers::Provider;
 use std::env;
 use std::error::Error;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::spawn;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use url::Url;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    // Initialize tracing
+    let args = Command::new("sequencer")
+        .about("ZeroXBridge sequencer")
+        .arg(
+            Arg::new("enable_l2_tree_builder")
+                .long("enable-l2-tree-builder")
+                .help("Append withdrawals to each network's L2 Merkle tree")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("simulate")
+                .long("simulate")
+                .help("Run against the events fixture and stubs of `[simulation]` in config.toml")
+                .action(ArgAction::SetTrue),
+        )
+        .get_matches();
+
+    // Load configuration from environment or config file, refusing to start on settings
+    // services would only fail on later. Errors are logged once tracing is up.
+    let config = load_config(Some(Path::new("config.toml")));
+
+    // Initialize tracing, exporting spans to the collector in `[telemetry]` if set
+    let tracer_provider = match config.as_ref().ok().and_then(|c| c.telemetry.as_ref()) {
+        Some(telemetry) => Some(otlp_tracer_provider(telemetry)?),
+        None => None,
+    };
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
             env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
         ))
         .with(tracing_subscriber::fmt::layer())
+        .with(tracer_provider.as_ref().map(telemetry_layer))
         .init();
 
     info!("Starting ZeroXBridge Sequencer");
 
-    // Load configuration from environment or config file
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let mut config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return Err(e.into());
+        }
+    };
+    if args.get_flag("simulate") {
+        config.simulation.enabled = true;
+    }
+    // Talk to the prover and chains, or to their stubs in simulation mode
+    let backend = backend_from_config(&config.simulation)?;
+    if config.simulation.enabled {
+        warn!("Simulation mode: no L1 node, prover or Starknet account is used");
+    }
+
+    // Services reading their settings on every cycle follow this copy, reloaded from
+    // config.toml on SIGHUP
+    let live_config = LiveConfig::new(config.clone());
+    spawn_config_reloader(live_config.clone())?;
 
     // Create database connection pool
-    let db_pool = PgPoolOptions::new()
-        .max_connections(10)
-        .connect(&database_url)
-        .await?;
+    let db_pool = get_db_pool(&config.database).await?;
 
     // Run database migrations
     info!("Running database migrations");
-    sqlx::migrate!("./migrations").run(&db_pool).await?;
+    MIGRATOR.run(&db_pool).await?;
+
+    // Refuse to start services against a schema they would only fail on later
+    if let Err(e) = DatabaseMigrationValidator::check(&db_pool).await {
+        error!("Database schema is not up to date: {}", e);
+        return Err(Box::new(e));
+    }
 
     // Create and start services
     let db_pool_arc = Arc::new(db_pool);
 
-    // Start the Starknet Relayer service
-    spawn_starknet_relayer(db_pool_arc.clone()).await?;
+    // Each configured L1/L2 pair gets its own set of services sharing the pool
+    let mut trees = Vec::new();
+    for network in config.active_networks() {
+        info!("Starting services for network {}", network.name);
+
+        // Start the Starknet Relayer service
+        spawn_starknet_relayer(
+            backend.as_ref(),
+            db_pool_arc.clone(),
+            &network,
+            &config.relayer,
+            live_config.clone(),
+        )
+        .await?;
+
+        // Start the watchers ingesting the bridge contract's deposit events
+        spawn_l1_event_watchers(
+            backend.as_ref(),
+            db_pool_arc.clone(),
+            &network,
+            &config.limits,
+            &config.l1_queue,
+        )
+        .await?;
+
+        // Start the watcher recording the L2 contract's burn events as pending withdrawals.
+        // The simulation's events fixture only holds L1 logs.
+        if !config.simulation.enabled {
+            spawn_l2_event_watcher(db_pool_arc.clone(), &config, &network).await?;
+        }
+
+        // Start the L1 queue processor promoting confirmed deposits to tree inclusion
+        spawn_l1_queue_processor(
+            backend.as_ref(),
+            db_pool_arc.clone(),
+            &network,
+            &config.l1_queue,
+            &config.queue,
+            live_config.clone(),
+        );
+
+        // Start the reconciler settling deposits submitted before their L1 event
+        spawn_deposit_reconciler(
+            backend.as_ref(),
+            db_pool_arc.clone(),
+            &network,
+            &config.l1_queue,
+        );
+
+        // Start the tree builder and hand proven deposits over to the Starknet relayer
+        // with inclusion proofs from its tree
+        let tree_builder = spawn_tree_builder(db_pool_arc.clone(), &network, &config.merkle);
+        trees.push((
+            network.name.clone(),
+            TreeState::new(tree_builder.tree(), tree_builder.proof_cache())
+                .with_roots(tree_builder.root_feed()),
+        ));
+        spawn_l2_queue_processor(
+            db_pool_arc.clone(),
+            &network,
+            tree_builder,
+            &config.queue,
+            live_config.clone(),
+        );
+
+        // Start the L2 tree builder storing the withdrawals' inclusion proofs if enabled
+        if args.get_flag("enable_l2_tree_builder") {
+            spawn_l2_tree_builder(db_pool_arc.clone(), &network);
+        }
+
+        // Start the poller following Herodotus state proof jobs if enabled. The network's
+        // withdrawal queue processor requests the proofs through it.
+        let state_proofs = spawn_state_proof_poller(
+            db_pool_arc.clone(),
+            &network,
+            &config.herodotus,
+            &config.queue,
+        );
+
+        // Start the processor requesting state proofs for withdrawals and handing them over
+        // to the Ethereum relayer
+        spawn_withdrawal_queue_processor(
+            db_pool_arc.clone(),
+            &network,
+            &config.queue,
+            state_proofs,
+            live_config.clone(),
+        );
+    }
+
+    // Serve the trees' roots and proofs over gRPC if enabled
+    spawn_tree_grpc_server(&config.grpc, trees);
+
+    // Start the janitor cleaning up proof directories and stuck rows
+    spawn_janitor(db_pool_arc.clone(), &config.janitor, &config.queue);
+
+    // Start the dispatcher delivering status changes to the registered webhooks
+    spawn_webhook_dispatcher(db_pool_arc.clone(), &config.webhooks);
 
     // Start other services (API, Queue, Proof Generator, etc.)
     // ...
@@ -56,36 +233,55 @@ async fn main() -> Result<(), Box<dyn Error>> {
     tokio::signal::ctrl_c().await?;
     info!("Shutting down ZeroXBridge Sequencer");
 
+    // Send the spans still buffered
+    if let Some(tracer_provider) = tracer_provider {
+        if let Err(e) = tracer_provider.shutdown() {
+            error!("Failed to export the remaining spans: {}", e);
+        }
+    }
+
     Ok(())
 }
 
-async fn spawn_starknet_relayer(db_pool: Arc<Pool<Postgres>>) -> Result<(), Box<dyn Error>> {
-    // Load Starknet relayer configuration
-    let config = StarknetRelayerConfig {
-        bridge_contract_address: env::var("STARKNET_BRIDGE_CONTRACT")
-            .expect("STARKNET_BRIDGE_CONTRACT must be set"),
-        rpc_url: env::var("STARKNET_RPC_URL").expect("STARKNET_RPC_URL must be set"),
-        private_key: env::var("STARKNET_PRIVATE_KEY").expect("STARKNET_PRIVATE_KEY must be set"),
-        max_retries: env::var("STARKNET_MAX_RETRIES")
-            .unwrap_or_else(|_| "3".to_string())
-            .parse()
-            .expect("STARKNET_MAX_RETRIES must be a valid number"),
-        retry_delay_ms: env::var("STARKNET_RETRY_DELAY_MS")
-            .unwrap_or_else(|_| "5000".to_string())
-            .parse()
-            .expect("STARKNET_RETRY_DELAY_MS must be a valid number"),
-        transaction_timeout_ms: env::var("STARKNET_TX_TIMEOUT_MS")
-            .unwrap_or_else(|_| "60000".to_string())
-            .parse()
-            .expect("STARKNET_TX_TIMEOUT_MS must be a valid number"),
-        account_address: env::var("STARKNET_RETRY_DELAY_MS")
-            .unwrap_or_else(|_| "5000".to_string())
-            .parse()
-            .expect("STARKNET_RETRY_DELAY_MS must be a valid number"),
-    };
+#[cfg(unix)]
+fn spawn_config_reloader(live_config: LiveConfig) -> Result<(), Box<dyn Error>> {
+    use tokio::signal::unix::{signal, SignalKind};
 
+    let mut hangups = signal(SignalKind::hangup())?;
+    spawn(async move {
+        while hangups.recv().await.is_some() {
+            match live_config.reload() {
+                Ok(changed) => info!("Configuration reloaded, changed sections: {:?}", changed),
+                Err(e) => warn!("Configuration reload rejected: {}", e),
+            }
+        }
+    });
+
+    info!("Configuration reloads on SIGHUP enabled");
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn spawn_config_reloader(_live_config: LiveConfig) -> Result<(), Box<dyn Error>> {
+    Ok(())
+}
+
+async fn spawn_starknet_relayer(
+    backend: &dyn PipelineBackend,
+    db_pool: Arc<Pool<Postgres>>,
+    network: &NetworkConfig,
+    relayer_config: &RelayerConfig,
+    live_config: LiveConfig,
+) -> Result<(), Box<dyn Error>> {
     // Initialize the Starknet relayer
-    let relayer = StarknetRelayer::new(db_pool.as_ref().clone(), config)
+    let relayer = backend
+        .starknet_relayer(
+            db_pool.as_ref().clone(),
+            network,
+            relayer_config,
+            Some(live_config),
+        )
         .await
         .map_err(|e| {
             error!("Failed to initialize Starknet relayer: {:?}", e);
@@ -104,3 +300,274 @@ async fn spawn_starknet_relayer(db_pool: Arc<Pool<Postgres>>) -> Result<(), Box<
 
     Ok(())
 }
+
+async fn spawn_l1_event_watchers(
+    backend: &dyn PipelineBackend,
+    db_pool: Arc<Pool<Postgres>>,
+    network: &NetworkConfig,
+    limits: &LimitsConfig,
+    l1_queue: &L1QueueConfig,
+) -> Result<(), Box<dyn Error>> {
+    // Without a block tracker, watching starts at the current block, earlier events are
+    // ingested by the `events` backfill
+    let latest_block = backend
+        .l1_block_source(network)
+        .latest_block_number()
+        .await?;
+    let watchers = backend.l1_event_watchers(network, latest_block, limits);
+
+    let interval = Duration::from_secs(l1_queue.poll_interval_sec);
+    spawn(start_event_loop(
+        db_pool.as_ref().clone(),
+        watchers,
+        interval,
+    ));
+
+    info!("L1 event watchers spawned");
+
+    Ok(())
+}
+
+async fn spawn_l2_event_watcher(
+    db_pool: Arc<Pool<Postgres>>,
+    config: &AppConfig,
+    network: &NetworkConfig,
+) -> Result<(), Box<dyn Error>> {
+    let rpc_url: Url = network.starknet.get_rpc_url().parse()?;
+    let provider = JsonRpcClient::new(HttpTransport::new(rpc_url));
+
+    // Without a block tracker, watching starts at the current block, earlier withdrawals
+    // are submitted through the API
+    let latest_block = provider.block_number().await?;
+
+    let interval = Duration::from_secs(config.queue.process_interval_sec);
+    spawn(start_l2_event_loop(
+        config.for_network(network),
+        db_pool.as_ref().clone(),
+        network.name.clone(),
+        latest_block,
+        provider,
+        interval,
+    ));
+
+    info!("L2 event watcher spawned");
+
+    Ok(())
+}
+
+fn spawn_l1_queue_processor(
+    backend: &dyn PipelineBackend,
+    db_pool: Arc<Pool<Postgres>>,
+    network: &NetworkConfig,
+    l1_queue: &L1QueueConfig,
+    queue: &QueueConfig,
+    live_config: LiveConfig,
+) {
+    let provider = backend.l1_block_source(network);
+    let processor = L1QueueProcessor::new(
+        db_pool.as_ref().clone(),
+        network.name.clone(),
+        l1_queue.clone(),
+        queue.max_retries,
+        provider,
+    )
+    .with_live_config(live_config);
+
+    spawn(async move {
+        processor.start().await;
+    });
+
+    info!("L1 queue processor spawned");
+}
+
+fn spawn_deposit_reconciler(
+    backend: &dyn PipelineBackend,
+    db_pool: Arc<Pool<Postgres>>,
+    network: &NetworkConfig,
+    l1_queue: &L1QueueConfig,
+) {
+    let provider = backend.l1_block_source(network);
+    let reconciler = DepositReconciler::new(
+        db_pool.as_ref().clone(),
+        network.name.clone(),
+        l1_queue.clone(),
+        provider,
+    );
+
+    spawn(async move {
+        reconciler.start().await;
+    });
+
+    info!("Deposit reconciler spawned");
+}
+
+fn spawn_tree_builder(
+    db_pool: Arc<Pool<Postgres>>,
+    network: &NetworkConfig,
+    merkle: &MerkleConfig,
+) -> Arc<TreeBuilderClient> {
+    let tree_builder = Arc::new(TreeBuilderClient::new(
+        db_pool.as_ref().clone(),
+        network.name.clone(),
+        TreeBuilderConfig {
+            proof_cache_size: merkle.cache_size as usize,
+            ..TreeBuilderConfig::default()
+        },
+    ));
+
+    let client = tree_builder.clone();
+    spawn(async move {
+        if let Err(e) = client.start().await {
+            error!("Tree builder stopped with error: {:?}", e);
+        }
+    });
+
+    info!("Tree builder spawned");
+
+    tree_builder
+}
+
+fn spawn_l2_tree_builder(db_pool: Arc<Pool<Postgres>>, network: &NetworkConfig) {
+    let tree_builder = L2TreeBuilderClient::new(
+        db_pool.as_ref().clone(),
+        network.name.clone(),
+        L2TreeBuilderConfig::default(),
+    );
+
+    spawn(async move {
+        if let Err(e) = tree_builder.start().await {
+            error!("L2 tree builder stopped with error: {:?}", e);
+        }
+    });
+
+    info!("L2 tree builder spawned");
+}
+
+#[cfg(feature = "grpc")]
+fn spawn_tree_grpc_server(grpc: &GrpcConfig, trees: Vec<(NetworkId, TreeState)>) {
+    use crate::tree_builder::grpc::{serve, TreeGrpcService};
+
+    if !grpc.enabled {
+        return;
+    }
+
+    let addr = grpc.bind_address;
+    spawn(async move {
+        if let Err(e) = serve(addr, TreeGrpcService::new(trees)).await {
+            error!("Tree gRPC server stopped with error: {:?}", e);
+        }
+    });
+
+    info!("Tree gRPC server spawned");
+}
+
+#[cfg(not(feature = "grpc"))]
+fn spawn_tree_grpc_server(grpc: &GrpcConfig, _trees: Vec<(NetworkId, TreeState)>) {
+    if grpc.enabled {
+        tracing::warn!(
+            "grpc.enabled is set, but the sequencer was built without the `grpc` feature"
+        );
+    }
+}
+
+fn spawn_l2_queue_processor(
+    db_pool: Arc<Pool<Postgres>>,
+    network: &NetworkConfig,
+    tree_builder: Arc<TreeBuilderClient>,
+    queue: &QueueConfig,
+    live_config: LiveConfig,
+) {
+    let processor = L2QueueProcessor::new(
+        db_pool.as_ref().clone(),
+        network.name.clone(),
+        queue.clone(),
+        CachedInclusionProofs {
+            tree: tree_builder.tree(),
+            cache: tree_builder.proof_cache(),
+        },
+    )
+    .with_live_config(live_config);
+
+    spawn(async move {
+        processor.start().await;
+    });
+
+    info!("L2 queue processor spawned");
+}
+
+fn spawn_withdrawal_queue_processor(
+    db_pool: Arc<Pool<Postgres>>,
+    network: &NetworkConfig,
+    queue: &QueueConfig,
+    state_proofs: Option<Arc<dyn StateProofRequester>>,
+    live_config: LiveConfig,
+) {
+    let mut processor = WithdrawalQueueProcessor::new(
+        db_pool.as_ref().clone(),
+        network.name.clone(),
+        queue.clone(),
+    )
+    .with_live_config(live_config);
+    if let Some(state_proofs) = state_proofs {
+        processor = processor.with_state_proofs(state_proofs);
+    }
+
+    spawn(async move {
+        processor.start().await;
+    });
+
+    info!("Withdrawal queue processor spawned");
+}
+
+fn spawn_janitor(db_pool: Arc<Pool<Postgres>>, janitor: &JanitorConfig, queue: &QueueConfig) {
+    let janitor = Janitor::new(db_pool.as_ref().clone(), janitor.clone())
+        .with_deposit_expiry_hours(queue.deposit_expiry_hours);
+
+    spawn(async move {
+        janitor.start().await;
+    });
+
+    info!("Janitor spawned");
+}
+
+fn spawn_state_proof_poller(
+    db_pool: Arc<Pool<Postgres>>,
+    network: &NetworkConfig,
+    herodotus: &HerodotusConfig,
+    queue: &QueueConfig,
+) -> Option<Arc<dyn StateProofRequester>> {
+    if !herodotus.enabled {
+        info!("Herodotus state proofs disabled, withdrawals stay included without one");
+        return None;
+    }
+
+    let provider = RealEthereumProvider::new(network.ethereum.get_rpc_url());
+    let poller = Arc::new(StateProofPoller::new(
+        db_pool.as_ref().clone(),
+        network.name.clone(),
+        HerodotusClient::from_config(herodotus),
+        herodotus.clone(),
+        network.contracts.l1_contract_address.clone(),
+        queue.max_retries,
+        provider,
+    ));
+
+    let runner = poller.clone();
+    spawn(async move {
+        runner.start().await;
+    });
+
+    info!("Herodotus state proof poller spawned");
+
+    Some(poller)
+}
+
+fn spawn_webhook_dispatcher(db_pool: Arc<Pool<Postgres>>, webhooks: &WebhookConfig) {
+    let dispatcher = WebhookDispatcher::new(db_pool.as_ref().clone(), webhooks.clone());
+
+    spawn(async move {
+        dispatcher.start().await;
+    });
+
+    info!("Webhook dispatcher spawned");
+}
@@ -0,0 +1,102 @@
+use clap::{Arg, ArgAction, Command};
+use std::path::PathBuf;
+use tracing::{error, info};
+use zeroxbridge_sequencer::config::{load_config, NetworkId};
+use zeroxbridge_sequencer::db::database::get_db_pool;
+use zeroxbridge_sequencer::tree_builder::{rebuild_tree, TreeRebuildOptions};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Initialize logging
+    tracing_subscriber::fmt::init();
+
+    let matches = Command::new("Tree Admin")
+        .version("1.0")
+        .about("Maintenance of the L1 deposit Merkle tree")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("CONFIG_FILE")
+                .help("Path to configuration file")
+                .default_value("config.toml")
+                .global(true)
+                .value_parser(clap::value_parser!(String)),
+        )
+        .subcommand(
+            Command::new("rebuild")
+                .about(
+                    "Rebuild the tree from the included deposits and report stored leaf \
+                     indexes and roots that disagree with it",
+                )
+                .arg(
+                    Arg::new("network")
+                        .long("network")
+                        .value_name("NAME")
+                        .help("Network whose tree is rebuilt")
+                        .default_value("default")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("fix")
+                        .long("fix")
+                        .help("Repair the inconsistent rows instead of only reporting them")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("batch_size")
+                        .long("batch-size")
+                        .value_name("COUNT")
+                        .help("Deposits checked, and repaired in one transaction, at a time")
+                        .default_value("500")
+                        .value_parser(clap::value_parser!(i64).range(1..)),
+                )
+                .arg(
+                    Arg::new("report")
+                        .long("report")
+                        .value_name("PATH")
+                        .help("Path of the JSON report (printed to stdout when omitted)")
+                        .value_parser(clap::value_parser!(String)),
+                ),
+        )
+        .get_matches();
+
+    let config_path = PathBuf::from(matches.get_one::<String>("config").unwrap());
+    let config = load_config(Some(&config_path))?;
+    let pool = get_db_pool(&config.database).await?;
+
+    match matches.subcommand() {
+        Some(("rebuild", args)) => {
+            let network = NetworkId::new(args.get_one::<String>("network").unwrap().clone());
+            let options = TreeRebuildOptions {
+                batch_size: *args.get_one::<i64>("batch_size").unwrap(),
+                fix: args.get_flag("fix"),
+            };
+            info!(
+                "Rebuilding the tree of network {} (fix: {}, batch size: {})",
+                network, options.fix, options.batch_size
+            );
+
+            let report = match rebuild_tree(&pool, &network, options).await {
+                Ok(report) => report,
+                Err(e) => {
+                    error!("Tree rebuild failed: {:?}", e);
+                    return Err(Box::new(e) as Box<dyn std::error::Error + Send + Sync>);
+                }
+            };
+
+            let json = serde_json::to_string_pretty(&report)?;
+            match args.get_one::<String>("report") {
+                Some(path) => std::fs::write(path, json)?,
+                None => println!("{}", json),
+            }
+            info!(
+                "Checked {} leaves, {} discrepancies",
+                report.leaves_checked,
+                report.discrepancies.len()
+            );
+            Ok(())
+        }
+        _ => unreachable!("a subcommand is required"),
+    }
+}
@@ -0,0 +1,8 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::compile_protos("proto/proof_submitter.proto")?;
+    // The tree service is only built with the `grpc` feature
+    if std::env::var_os("CARGO_FEATURE_GRPC").is_some() {
+        tonic_build::compile_protos("proto/tree.proto")?;
+    }
+    Ok(())
+}
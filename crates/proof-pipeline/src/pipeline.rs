@@ -1,5 +1,5 @@
 use std::{
-    io::{self, Write},
+    io,
     path::{Path, PathBuf},
     process::Command,
 };
@@ -25,6 +25,21 @@ pub struct CalldataArtifacts {
     _temp_dir: Option<TempDir>,
 }
 
+impl CalldataArtifacts {
+    /// Artifacts persisted by an earlier run, read back from `calldata_dir`
+    pub fn from_calldata_dir(
+        calldata_dir: PathBuf,
+        proof_path: PathBuf,
+    ) -> Result<Self, ProofError> {
+        Ok(CalldataArtifacts {
+            fact_hash: extract_fact_hash(&calldata_dir)?,
+            calldata_dir,
+            proof_path,
+            _temp_dir: None,
+        })
+    }
+}
+
 pub struct ProofInputArgs {
     pub sierra_path: PathBuf,
     pub program_inputs: serde_json::Value,
@@ -45,7 +60,7 @@ fn execute_command(
     let output = Command::new(command)
         .args(args)
         .output()
-        .map_err(|e| ProofError::Io(e))?;
+        .map_err(ProofError::Io)?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
@@ -60,19 +75,26 @@ fn execute_command(
     Ok(())
 }
 
-pub fn run_full_stone_pipeline(
-    args: ProofInputArgs,
-) -> Result<CalldataArtifacts, ProofError> {
-    let temp_dir = tempdir().map_err(ProofError::Io)?;
-    let temp_path = temp_dir.path();
-    let target_dir = temp_path.join("target");
-    std::fs::create_dir(&target_dir)?;
+/// Files written by `cairo1-run` that the prover reads
+#[derive(Debug, Clone)]
+pub struct CairoRunOutput {
+    pub public_input: PathBuf,
+    pub private_input: PathBuf,
+}
 
-    // 1. Prepare input file
-    let input_file = temp_path.join("input.json");
-    std::fs::write(&input_file, serde_json::to_vec(&args.program_inputs)?)?;
+/// Runs the program in proof mode with `cairo1-run`, writing the trace and AIR inputs
+/// to `target_dir`
+pub fn run_cairo_program(
+    sierra_path: &Path,
+    program_inputs: &serde_json::Value,
+    layout: &str,
+    target_dir: &Path,
+) -> Result<CairoRunOutput, ProofError> {
+    std::fs::create_dir_all(target_dir)?;
+
+    let input_file = target_dir.join("input.json");
+    std::fs::write(&input_file, serde_json::to_vec(program_inputs)?)?;
 
-    // 2. Execute cairo1-run
     let public_input = target_dir.join("public_input.json");
     let private_input = target_dir.join("private_input.json");
     let trace_file = target_dir.join("trace");
@@ -81,9 +103,9 @@ pub fn run_full_stone_pipeline(
     execute_command(
         "cairo1-run",
         &[
-            args.sierra_path.to_str().unwrap(),
+            sierra_path.to_str().unwrap(),
             "--layout",
-            &args.layout,
+            layout,
             "--arguments-file",
             input_file.to_str().unwrap(),
             "--proof_mode",
@@ -99,19 +121,31 @@ pub fn run_full_stone_pipeline(
         "Cairo execution (cairo1-run)",
     )?;
 
-    // 3. Generate proof with cpu_air_prover
+    Ok(CairoRunOutput {
+        public_input,
+        private_input,
+    })
+}
+
+/// Proves a Cairo run with `cpu_air_prover`, returning the path of the proof
+pub fn generate_proof(
+    run: &CairoRunOutput,
+    prover_parameters: &Path,
+    prover_config: &Path,
+    target_dir: &Path,
+) -> Result<PathBuf, ProofError> {
     let proof_path = target_dir.join("proof.json");
     execute_command(
         "cpu_air_prover",
         &[
             "--parameter_file",
-            args.prover_parameters.to_str().unwrap(),
+            prover_parameters.to_str().unwrap(),
             "--prover_config_file",
-            args.prover_config.to_str().unwrap(),
+            prover_config.to_str().unwrap(),
             "--private_input_file",
-            private_input.to_str().unwrap(),
+            run.private_input.to_str().unwrap(),
             "--public_input_file",
-            public_input.to_str().unwrap(),
+            run.public_input.to_str().unwrap(),
             "--out_file",
             proof_path.to_str().unwrap(),
             "--generate_annotations",
@@ -120,37 +154,85 @@ pub fn run_full_stone_pipeline(
         "Proof generation (cpu_air_prover)",
     )?;
 
-    // 4. Optionally verify proof
-    if args.run_verifier {
-        execute_command(
-            "cpu_air_verifier",
-            &["--in_file", proof_path.to_str().unwrap()],
-            "Proof verification (cpu_air_verifier)",
-        )?;
-    }
+    Ok(proof_path)
+}
 
-    // 5. Prepare calldata with swiftness
-    let calldata_dir = temp_path.join("calldata");
+/// Checks a proof with `cpu_air_verifier`
+pub fn verify_proof(proof_path: &Path) -> Result<(), ProofError> {
+    execute_command(
+        "cpu_air_verifier",
+        &["--in_file", proof_path.to_str().unwrap()],
+        "Proof verification (cpu_air_verifier)",
+    )
+}
+
+/// Splits a proof into Starknet calldata with swiftness, written to `calldata_dir`
+pub fn prepare_calldata(
+    proof_path: &Path,
+    layout: &str,
+    hasher: &str,
+    stone_version: &str,
+    calldata_dir: &Path,
+) -> Result<(), ProofError> {
     execute_command(
         "swiftness",
         &[
             "--proof",
             proof_path.to_str().unwrap(),
             "--layout",
-            &args.layout,
+            layout,
             "--hasher",
-            &args.hasher,
+            hasher,
             "--stone-version",
-            &args.stone_version,
+            stone_version,
             "--out",
             calldata_dir.to_str().unwrap(),
         ],
         "Calldata preparation (swiftness)",
+    )
+}
+
+pub fn run_full_stone_pipeline(
+    args: ProofInputArgs,
+) -> Result<CalldataArtifacts, ProofError> {
+    let temp_dir = tempdir().map_err(ProofError::Io)?;
+    let temp_path = temp_dir.path();
+    let target_dir = temp_path.join("target");
+
+    // 1. Execute cairo1-run
+    let run = run_cairo_program(
+        &args.sierra_path,
+        &args.program_inputs,
+        &args.layout,
+        &target_dir,
+    )?;
+
+    // 2. Generate proof with cpu_air_prover
+    let proof_path = generate_proof(
+        &run,
+        &args.prover_parameters,
+        &args.prover_config,
+        &target_dir,
+    )?;
+
+    // 3. Optionally verify proof
+    if args.run_verifier {
+        verify_proof(&proof_path)?;
+    }
+
+    // 4. Prepare calldata with swiftness
+    let calldata_dir = temp_path.join("calldata");
+    prepare_calldata(
+        &proof_path,
+        &args.layout,
+        &args.hasher,
+        &args.stone_version,
+        &calldata_dir,
     )?;
 
     // Handle temp directory persistence
     let (calldata_dir, proof_path, _temp_dir) = if args.keep_temp_files {
-        let persistent_path = temp_dir.into_path();
+        let persistent_path = temp_dir.keep();
         (
             persistent_path.join("calldata"),
             persistent_path.join("target/proof.json"),
@@ -0,0 +1,118 @@
+//! Cost of the Merkle tree operations the sequencer runs for every deposit and
+//! withdrawal: building the L1 and L2 trees, proving a leaf and verifying its proof.
+//! Everything runs in memory, no database is needed.
+//!
+//! Run with `cargo bench -p tree-builder` from `crates/tree-builder`.
+//!
+//! Baselines are machine dependent, so none are checked in. Record one before a change
+//! with `cargo bench -p tree-builder -- --save-baseline main` and compare with
+//! `cargo bench -p tree-builder -- --baseline main` after it, on the same machine.
+//! Criterion keeps both under `target/criterion/`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use tokio::runtime::Runtime;
+use tree_builder::l1_tree::L1MerkleTreeBuilder;
+use tree_builder::l2_tree::L2MerkleTreeBuilder;
+
+const BUILD_SIZES: [u64; 3] = [100, 1000, 10_000];
+
+/// Leaves of the trees proofs are benchmarked on
+const PROOF_TREE_LEAVES: u64 = 10_000;
+
+fn leaf(i: u64) -> [u8; 32] {
+    let mut leaf = [0u8; 32];
+    leaf[24..].copy_from_slice(&i.to_be_bytes());
+    leaf
+}
+
+fn leaves(count: u64) -> Vec<[u8; 32]> {
+    (0..count).map(leaf).collect()
+}
+
+/// First, middle and last leaf of the proof trees
+fn proved_leaves() -> [(&'static str, [u8; 32]); 3] {
+    [
+        ("first", leaf(0)),
+        ("middle", leaf(PROOF_TREE_LEAVES / 2)),
+        ("last", leaf(PROOF_TREE_LEAVES - 1)),
+    ]
+}
+
+fn l1_tree(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("l1_build_merkle");
+    group.sample_size(10);
+    for size in BUILD_SIZES {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter_batched(
+                || (L1MerkleTreeBuilder::new(), leaves(size)),
+                |(mut tree, leaves)| {
+                    rt.block_on(tree.build_merkle(leaves)).unwrap();
+                    tree
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut tree = L1MerkleTreeBuilder::new();
+    rt.block_on(tree.build_merkle(leaves(PROOF_TREE_LEAVES)))
+        .unwrap();
+
+    let mut group = c.benchmark_group("l1_get_proof_10000_leaves");
+    for (position, leaf) in proved_leaves() {
+        group.bench_function(position, |b| {
+            b.iter(|| rt.block_on(tree.get_proof(leaf)).unwrap())
+        });
+    }
+    group.finish();
+
+    let (_, leaf) = proved_leaves()[1];
+    let proof = rt.block_on(tree.get_proof(leaf)).unwrap().unwrap();
+    c.bench_function("l1_verify_proof_10000_leaves", |b| {
+        b.iter(|| assert!(rt.block_on(tree.verify_proof(proof.clone(), leaf)).unwrap()))
+    });
+}
+
+fn l2_tree(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("l2_build_merkle");
+    group.sample_size(10);
+    for size in BUILD_SIZES {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter_batched(
+                || (L2MerkleTreeBuilder::new(), leaves(size)),
+                |(mut tree, leaves)| {
+                    rt.block_on(tree.build_merkle(leaves)).unwrap();
+                    tree
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut tree = L2MerkleTreeBuilder::new();
+    rt.block_on(tree.build_merkle(leaves(PROOF_TREE_LEAVES)))
+        .unwrap();
+
+    let mut group = c.benchmark_group("l2_get_proof_10000_leaves");
+    for (position, leaf) in proved_leaves() {
+        group.bench_function(position, |b| {
+            b.iter(|| rt.block_on(tree.get_proof(leaf)).unwrap())
+        });
+    }
+    group.finish();
+
+    let (_, leaf) = proved_leaves()[1];
+    let proof = rt.block_on(tree.get_proof(leaf)).unwrap().unwrap();
+    c.bench_function("l2_verify_proof_10000_leaves", |b| {
+        b.iter(|| assert!(rt.block_on(tree.verify_proof(proof.clone(), leaf)).unwrap()))
+    });
+}
+
+criterion_group!(benches, l1_tree, l2_tree);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Runtime};
+use tree_builder::l1_tree::L1MerkleTreeBuilder;
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Builder::new_current_thread().build().unwrap())
+}
+
+/// Splits the input into 32-byte leaves, zero padding the last one
+fn leaves(data: &[u8]) -> Vec<[u8; 32]> {
+    data.chunks(32)
+        .map(|chunk| {
+            let mut leaf = [0u8; 32];
+            leaf[..chunk.len()].copy_from_slice(chunk);
+            leaf
+        })
+        .collect()
+}
+
+fuzz_target!(|data: &[u8]| {
+    runtime().block_on(async {
+        let leaves = leaves(data);
+        let mut tree = L1MerkleTreeBuilder::new();
+        tree.build_merkle(leaves.clone()).await.unwrap();
+        let _ = tree.get_root().await;
+
+        for leaf in leaves {
+            let proof = tree.get_proof(leaf).await.unwrap().unwrap();
+            assert!(tree.verify_proof(proof, leaf).await.unwrap());
+        }
+    });
+});
@@ -0,0 +1,38 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Runtime};
+use tree_builder::l2_tree::L2MerkleTreeBuilder;
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Builder::new_current_thread().build().unwrap())
+}
+
+/// Splits the input into 32-byte leaves, zero padding the last one. Leaves are felt252
+/// commitments, so the top bits are cleared to keep them below the Stark prime.
+fn leaves(data: &[u8]) -> Vec<[u8; 32]> {
+    data.chunks(32)
+        .map(|chunk| {
+            let mut leaf = [0u8; 32];
+            leaf[..chunk.len()].copy_from_slice(chunk);
+            leaf[0] &= 0x07;
+            leaf
+        })
+        .collect()
+}
+
+fuzz_target!(|data: &[u8]| {
+    runtime().block_on(async {
+        let leaves = leaves(data);
+        let mut tree = L2MerkleTreeBuilder::new();
+        tree.build_merkle(leaves.clone()).await.unwrap();
+        let _ = tree.get_root().await;
+
+        for leaf in leaves {
+            let proof = tree.get_proof(leaf).await.unwrap().unwrap();
+            assert!(tree.verify_proof(proof, leaf).await.unwrap());
+        }
+    });
+});
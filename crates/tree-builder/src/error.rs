@@ -1,4 +1,5 @@
 use accumulators::{
+    hasher::HasherError,
     mmr::MMRError,
     store::{InStoreTableError, StoreError},
 };
@@ -12,6 +13,8 @@ pub enum TreeBuilderError {
     StoreError(#[from] StoreError),
     #[error(transparent)]
     TableError(#[from] InStoreTableError),
+    #[error(transparent)]
+    HasherError(#[from] HasherError),
     #[error("Failed to decode hex: {0}")]
     HexError(String),
     #[error("Failed to convert to array: {0}")]
@@ -20,4 +23,10 @@ pub enum TreeBuilderError {
     InvalidLeafHash(String),
     #[error(transparent)]
     FromHexError(#[from] hex::FromHexError),
+    #[error("Snapshot I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid snapshot: {0}")]
+    SnapshotError(#[from] serde_json::Error),
+    #[error("The tree never held {requested} elements, it holds {current}")]
+    InvalidElementsCount { requested: usize, current: usize },
 }
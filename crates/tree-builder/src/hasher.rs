@@ -0,0 +1,134 @@
+use std::{array::TryFromSliceError, fmt::Debug, sync::Arc};
+
+use accumulators::hasher::{keccak::KeccakHasher, stark_poseidon::StarkPoseidonHasher, Hasher};
+
+use crate::{
+    error::TreeBuilderError,
+    types::{HashAlgorithm, Result},
+};
+
+/// Hash function a tree combines its nodes with
+pub trait MerkleHasher: Send + Sync + Debug {
+    fn algorithm(&self) -> HashAlgorithm;
+
+    /// Hasher the underlying MMR is built with
+    fn mmr_hasher(&self) -> Arc<dyn Hasher>;
+
+    /// Hashes two nodes into their parent
+    fn hash_pair(&self, left: &[u8; 32], right: &[u8; 32]) -> Result<[u8; 32]> {
+        let parent = self
+            .mmr_hasher()
+            .hash(vec![encode_hash(left), encode_hash(right)])?;
+        decode_hash(&parent)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PoseidonMerkleHasher;
+
+impl MerkleHasher for PoseidonMerkleHasher {
+    fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Poseidon
+    }
+
+    fn mmr_hasher(&self) -> Arc<dyn Hasher> {
+        Arc::new(StarkPoseidonHasher::new(None))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Keccak256MerkleHasher;
+
+impl MerkleHasher for Keccak256MerkleHasher {
+    fn algorithm(&self) -> HashAlgorithm {
+        HashAlgorithm::Keccak256
+    }
+
+    fn mmr_hasher(&self) -> Arc<dyn Hasher> {
+        Arc::new(KeccakHasher::new())
+    }
+}
+
+/// Picks the hasher at runtime, e.g. from configuration
+impl MerkleHasher for HashAlgorithm {
+    fn algorithm(&self) -> HashAlgorithm {
+        *self
+    }
+
+    fn mmr_hasher(&self) -> Arc<dyn Hasher> {
+        match self {
+            Self::Poseidon => PoseidonMerkleHasher.mmr_hasher(),
+            Self::Keccak256 => Keccak256MerkleHasher.mmr_hasher(),
+        }
+    }
+}
+
+pub(crate) fn encode_hash(hash: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+/// Decodes a hash returned by the MMR, left padding short values to 32 bytes
+pub(crate) fn decode_hash(hex_str: &str) -> Result<[u8; 32]> {
+    let hex_to_decode = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+    // Felts are printed without leading zeros, possibly leaving an odd number of digits
+    let mut bytes = hex::decode(format!("{:0>64}", hex_to_decode))?;
+
+    // Pad with zeros if needed
+    while bytes.len() < 32 {
+        bytes.insert(0, 0);
+    }
+    // Truncate if too long
+    if bytes.len() > 32 {
+        bytes.truncate(32);
+    }
+
+    bytes
+        .as_slice()
+        .try_into()
+        .map_err(|e: TryFromSliceError| TreeBuilderError::ConversionError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(value: u8) -> [u8; 32] {
+        let mut leaf = [0u8; 32];
+        leaf[31] = value;
+        leaf
+    }
+
+    #[test]
+    fn test_hashers_disagree_on_the_same_pair() -> Result<()> {
+        let poseidon = PoseidonMerkleHasher.hash_pair(&leaf(1), &leaf(2))?;
+        let keccak = Keccak256MerkleHasher.hash_pair(&leaf(1), &leaf(2))?;
+
+        assert_ne!(poseidon, keccak);
+        assert_eq!(
+            poseidon,
+            PoseidonMerkleHasher.hash_pair(&leaf(1), &leaf(2))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_algorithm_dispatches_to_its_hasher() -> Result<()> {
+        assert_eq!(
+            HashAlgorithm::Keccak256.hash_pair(&leaf(1), &leaf(2))?,
+            Keccak256MerkleHasher.hash_pair(&leaf(1), &leaf(2))?
+        );
+        assert_eq!(
+            HashAlgorithm::Poseidon.hash_pair(&leaf(1), &leaf(2))?,
+            PoseidonMerkleHasher.hash_pair(&leaf(1), &leaf(2))?
+        );
+        assert_eq!(HashAlgorithm::default(), HashAlgorithm::Poseidon);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_hash_pads_short_values() -> Result<()> {
+        assert_eq!(decode_hash("0x02")?, leaf(2));
+        assert_eq!(decode_hash(&encode_hash(&leaf(7)))?, leaf(7));
+        Ok(())
+    }
+}
@@ -1,16 +1,28 @@
-use std::{array::TryFromSliceError, sync::Arc};
+use std::{array::TryFromSliceError, fs, path::Path, sync::Arc};
 
 use accumulators::{
     hasher::keccak::KeccakHasher,
-    mmr::{Proof, MMR},
+    mmr::{ProofOptions, MMR},
     store::memory::InMemoryStore,
 };
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::{error::TreeBuilderError, types::Result};
 
+pub use accumulators::mmr::Proof;
+
 /// A builder for constructing Merkle trees and generating proofs
 pub struct L1MerkleTreeBuilder {
     mmr: MMR,
+    leaves: Vec<[u8; 32]>,
+}
+
+/// Serialized form of an [`L1MerkleTreeBuilder`].
+///
+/// The leaves, in insertion order, are enough to rebuild every node of the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L1TreeSnapshot {
+    pub leaves: Vec<String>,
 }
 
 impl L1MerkleTreeBuilder {
@@ -32,13 +44,64 @@ impl L1MerkleTreeBuilder {
 
         Self {
             mmr: MMR::new(store_rc, hasher, None),
+            leaves: Vec::new(),
         }
     }
 
+    /// Rebuilds a tree by appending the snapshot's leaves in order
+    pub async fn from_snapshot(snapshot: L1TreeSnapshot) -> Result<Self> {
+        let leaves = snapshot
+            .leaves
+            .iter()
+            .map(|leaf| {
+                if leaf.starts_with("0x") {
+                    Self::decode_hex(leaf)
+                } else {
+                    Err(TreeBuilderError::InvalidLeafHash(leaf.clone()))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut builder = Self::new();
+        builder.build_merkle(leaves).await?;
+        Ok(builder)
+    }
+
+    pub fn snapshot(&self) -> L1TreeSnapshot {
+        L1TreeSnapshot {
+            leaves: self
+                .leaves
+                .iter()
+                .map(|leaf| format!("0x{}", hex::encode(leaf)))
+                .collect(),
+        }
+    }
+
+    /// Writes the tree to `path` as JSON.
+    ///
+    /// The snapshot is written to a temporary file first and renamed into place, so a
+    /// crash mid-write never leaves a truncated snapshot behind.
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&self.snapshot())?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Loads a tree previously written with [`save_to_file`](Self::save_to_file).
+    ///
+    /// Appending to the MMR is async, so unlike serialization this cannot go through
+    /// `Deserialize` and the snapshot is replayed here instead.
+    pub async fn load_from_file(path: &Path) -> Result<Self> {
+        let snapshot: L1TreeSnapshot = serde_json::from_slice(&fs::read(path)?)?;
+        Self::from_snapshot(snapshot).await
+    }
+
     /// Builds a Merkle tree from a list of commitment hashes
     pub async fn build_merkle(&mut self, leaves: Vec<[u8; 32]>) -> Result<()> {
         for leaf in leaves {
             self.mmr.append(format!("0x{}", hex::encode(leaf))).await?;
+            self.leaves.push(leaf);
         }
         Ok(())
     }
@@ -51,13 +114,72 @@ impl L1MerkleTreeBuilder {
         Self::decode_hex(&root)
     }
 
+    /// Gets the Merkle root of the tree as it was when it held `elements_count` elements
+    pub async fn get_root_at(&self, elements_count: usize) -> Result<[u8; 32]> {
+        self.check_elements_count(elements_count).await?;
+        let bag = self.mmr.bag_the_peaks(Some(elements_count)).await?;
+        let root = self.mmr.calculate_root_hash(&bag, elements_count)?;
+        Self::decode_hex(&root)
+    }
+
+    /// Gets the number of leaves appended to the tree
+    pub async fn leaf_count(&self) -> Result<usize> {
+        Ok(self.mmr.leaves_count.get().await?)
+    }
+
+    /// Gets the number of elements of the tree, leaves and inner nodes
+    pub async fn elements_count(&self) -> Result<usize> {
+        Ok(self.mmr.elements_count.get().await?)
+    }
+
+    /// Whether a tree can hold exactly `elements_count` elements. Its peaks are perfect
+    /// binary trees of strictly decreasing height.
+    pub fn is_valid_size(elements_count: usize) -> bool {
+        let mut remaining = elements_count;
+        let mut previous_peak = usize::MAX;
+        while remaining > 0 {
+            let height = usize::BITS - 1 - remaining.saturating_add(1).leading_zeros();
+            let peak = (1usize << height) - 1;
+            if peak >= previous_peak {
+                return false;
+            }
+            remaining -= peak;
+            previous_peak = peak;
+        }
+        true
+    }
+
     /// Generates a Merkle proof for a given leaf
     pub async fn get_proof(&self, leaf: [u8; 32]) -> Result<Option<Proof>> {
         let elements_count = self.mmr.elements_count.get().await?;
-        let leaf_str = format!("0x{}", hex::encode(leaf));
+        match self.element_index(leaf, elements_count).await? {
+            Some(idx) => Ok(Some(self.mmr.get_proof(idx, None).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Generates a Merkle proof for a given leaf against the tree as it was when it held
+    /// `elements_count` elements, `None` if the leaf was only appended later
+    pub async fn get_proof_at(
+        &self,
+        leaf: [u8; 32],
+        elements_count: usize,
+    ) -> Result<Option<Proof>> {
+        self.check_elements_count(elements_count).await?;
+        let Some(idx) = self.element_index(leaf, elements_count).await? else {
+            return Ok(None);
+        };
+
+        let options = ProofOptions {
+            elements_count: Some(elements_count),
+            ..Default::default()
+        };
+        Ok(Some(self.mmr.get_proof(idx, Some(options)).await?))
+    }
 
-        // Find the leaf index by scanning elements
-        let mut leaf_index = None;
+    /// Finds the element index of `leaf` by scanning the first `elements_count` elements
+    async fn element_index(&self, leaf: [u8; 32], elements_count: usize) -> Result<Option<usize>> {
+        let leaf_str = format!("0x{}", hex::encode(leaf));
         for i in 1..=elements_count {
             if let Some(hash) = self
                 .mmr
@@ -66,18 +188,23 @@ impl L1MerkleTreeBuilder {
                 .await?
             {
                 if hash == leaf_str {
-                    leaf_index = Some(i);
-                    break;
+                    return Ok(Some(i));
                 }
             }
         }
+        Ok(None)
+    }
 
-        if let Some(idx) = leaf_index {
-            let proof = self.mmr.get_proof(idx, None).await?;
-            Ok(Some(proof))
-        } else {
-            Ok(None)
+    /// Fails unless the tree held exactly `elements_count` elements at some point
+    async fn check_elements_count(&self, elements_count: usize) -> Result<()> {
+        let current = self.mmr.elements_count.get().await?;
+        if elements_count == 0 || elements_count > current || !Self::is_valid_size(elements_count) {
+            return Err(TreeBuilderError::InvalidElementsCount {
+                requested: elements_count,
+                current,
+            });
         }
+        Ok(())
     }
 
     /// Verifies a Merkle proof for a given leaf
@@ -87,6 +214,12 @@ impl L1MerkleTreeBuilder {
     }
 }
 
+impl Serialize for L1MerkleTreeBuilder {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.snapshot().serialize(serializer)
+    }
+}
+
 impl Default for L1MerkleTreeBuilder {
     fn default() -> Self {
         Self::new()
@@ -127,6 +260,8 @@ mod tests {
             );
         }
 
+        assert_eq!(builder.leaf_count().await?, 2);
+
         // Test non-existent leaf
         let fake_leaf = [99u8; 32];
         assert!(
@@ -136,4 +271,92 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_valid_sizes() {
+        let valid: Vec<usize> = (0..12)
+            .filter(|&size| L1MerkleTreeBuilder::is_valid_size(size))
+            .collect();
+        assert_eq!(valid, [0, 1, 3, 4, 7, 8, 10, 11]);
+    }
+
+    #[tokio::test]
+    async fn test_root_and_proof_at_earlier_size() -> Result<()> {
+        let mut builder = L1MerkleTreeBuilder::new();
+        builder.build_merkle(vec![[1u8; 32], [2u8; 32]]).await?;
+        let elements_count = builder.elements_count().await?;
+        let root = builder.get_root().await?;
+        let proof = builder.get_proof([1u8; 32]).await?.unwrap();
+
+        builder.build_merkle(vec![[3u8; 32], [4u8; 32]]).await?;
+        assert_ne!(builder.get_root().await?, root);
+
+        assert_eq!(builder.get_root_at(elements_count).await?, root);
+        let earlier = builder
+            .get_proof_at([1u8; 32], elements_count)
+            .await?
+            .unwrap();
+        assert_eq!(earlier.siblings_hashes, proof.siblings_hashes);
+        assert_eq!(earlier.peaks_hashes, proof.peaks_hashes);
+        assert_eq!(earlier.elements_count, elements_count);
+
+        // Appended after the tree had that size
+        assert!(builder
+            .get_proof_at([3u8; 32], elements_count)
+            .await?
+            .is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sizes_the_tree_never_had_are_rejected() -> Result<()> {
+        let mut builder = L1MerkleTreeBuilder::new();
+        builder.build_merkle(vec![[1u8; 32], [2u8; 32]]).await?;
+
+        for elements_count in [0, 2, 4] {
+            assert!(matches!(
+                builder.get_root_at(elements_count).await,
+                Err(TreeBuilderError::InvalidElementsCount { current: 3, .. })
+            ));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() -> Result<()> {
+        let mut builder = L1MerkleTreeBuilder::new();
+        let leaves: Vec<[u8; 32]> = (0..100u8).map(|i| [i; 32]).collect();
+        builder.build_merkle(leaves.clone()).await?;
+
+        let json = serde_json::to_string(&builder)?;
+        let snapshot: L1TreeSnapshot = serde_json::from_str(&json)?;
+        assert_eq!(snapshot.leaves.len(), 100);
+
+        let restored = L1MerkleTreeBuilder::from_snapshot(snapshot).await?;
+        assert_eq!(restored.get_root().await?, builder.get_root().await?);
+        assert_eq!(restored.leaf_count().await?, 100);
+
+        let proof = restored.get_proof(leaves[42]).await?.unwrap();
+        assert!(restored.verify_proof(proof, leaves[42]).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("l1_tree.json");
+
+        let mut builder = L1MerkleTreeBuilder::new();
+        builder
+            .build_merkle((0..100u8).map(|i| [i; 32]).collect())
+            .await?;
+        builder.save_to_file(&path)?;
+
+        let restored = L1MerkleTreeBuilder::load_from_file(&path).await?;
+        assert_eq!(restored.get_root().await?, builder.get_root().await?);
+        assert!(!path.with_extension("tmp").exists());
+
+        Ok(())
+    }
 }
@@ -1,53 +1,45 @@
-use std::{array::TryFromSliceError, sync::Arc};
+use accumulators::{mmr::MMR, store::memory::InMemoryStore};
+use std::sync::Arc;
 
-use accumulators::{
-    hasher::stark_poseidon,
-    mmr::{Proof, MMR},
-    store::memory::InMemoryStore,
+use crate::{
+    hasher::{decode_hash, MerkleHasher, PoseidonMerkleHasher},
+    types::{HashAlgorithm, Result},
 };
 
-use crate::{error::TreeBuilderError, types::Result};
+pub use accumulators::mmr::Proof;
 
-/// A builder for constructing Merkle trees and generating proofs
-pub struct L2MerkleTreeBuilder {
+/// A builder for constructing Merkle trees and generating proofs.
+///
+/// Nodes are hashed with Poseidon unless another [`MerkleHasher`] is given to
+/// [`with_hasher`](Self::with_hasher); proofs are verified with the same hasher.
+pub struct L2MerkleTreeBuilder<H: MerkleHasher = PoseidonMerkleHasher> {
     mmr: MMR,
+    hasher: H,
 }
 
 impl L2MerkleTreeBuilder {
-    fn decode_hex(hex_str: &str) -> Result<[u8; 32]> {
-        let hex_to_decode = if hex_str.starts_with("0x") {
-            &hex_str[2..]
-        } else {
-            hex_str
-        };
-        let mut bytes = hex::decode(hex_to_decode)?;
-
-        // Pad with zeros if needed
-        while bytes.len() < 32 {
-            bytes.insert(0, 0);
-        }
-        // Truncate if too long
-        if bytes.len() > 32 {
-            bytes.truncate(32);
-        }
-
-        bytes
-            .as_slice()
-            .try_into()
-            .map_err(|e: TryFromSliceError| TreeBuilderError::ConversionError(e.to_string()))
+    /// Creates a new L2MerkleTreeBuilder instance hashing with Poseidon
+    pub fn new() -> Self {
+        Self::with_hasher(PoseidonMerkleHasher)
     }
+}
 
-    /// Creates a new L2MerkleTreeBuilder instance
-    pub fn new() -> Self {
+impl<H: MerkleHasher> L2MerkleTreeBuilder<H> {
+    /// Creates a new L2MerkleTreeBuilder instance hashing with `hasher`
+    pub fn with_hasher(hasher: H) -> Self {
         let store = InMemoryStore::default();
         let store_rc = Arc::new(store);
-        let hasher = Arc::new(stark_poseidon::StarkPoseidonHasher::new(None));
 
         Self {
-            mmr: MMR::new(store_rc, hasher, None),
+            mmr: MMR::new(store_rc, hasher.mmr_hasher(), None),
+            hasher,
         }
     }
 
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        self.hasher.algorithm()
+    }
+
     /// Builds a Merkle tree from a list of commitment hashes
     pub async fn build_merkle(&mut self, leaves: Vec<[u8; 32]>) -> Result<()> {
         for leaf in leaves {
@@ -61,7 +53,12 @@ impl L2MerkleTreeBuilder {
         let bag = self.mmr.bag_the_peaks(None).await?;
         let elements_count = self.mmr.elements_count.get().await?;
         let root = self.mmr.calculate_root_hash(&bag, elements_count)?;
-        Self::decode_hex(&root)
+        decode_hash(&root)
+    }
+
+    /// Gets the number of leaves appended to the tree
+    pub async fn leaf_count(&self) -> Result<usize> {
+        Ok(self.mmr.leaves_count.get().await?)
     }
 
     /// Generates a Merkle proof for a given leaf
@@ -109,8 +106,20 @@ impl Default for L2MerkleTreeBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::TreeBuilderError;
+    use crate::hasher::Keccak256MerkleHasher;
     use crate::utils::felt252_to_hex;
 
+    fn small_leaves() -> Vec<[u8; 32]> {
+        (1..=5u8)
+            .map(|value| {
+                let mut leaf = [0u8; 32];
+                leaf[31] = value;
+                leaf
+            })
+            .collect()
+    }
+
     #[tokio::test]
     async fn test_basic_tree_operations() -> Result<()> {
         let mut builder = L2MerkleTreeBuilder::new();
@@ -130,6 +139,7 @@ mod tests {
         // Add second leaf
         let leaf2 = [2u8; 32];
         builder.build_merkle(vec![leaf2]).await?;
+        assert_eq!(builder.leaf_count().await?, 2);
 
         // Verify both leaves have valid proofs
         for leaf in [leaf1, leaf2] {
@@ -198,4 +208,53 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_keccak_and_poseidon_trees_have_different_roots() -> Result<()> {
+        let mut poseidon = L2MerkleTreeBuilder::new();
+        let mut keccak = L2MerkleTreeBuilder::with_hasher(Keccak256MerkleHasher);
+        poseidon.build_merkle(small_leaves()).await?;
+        keccak.build_merkle(small_leaves()).await?;
+
+        assert_eq!(poseidon.hash_algorithm(), HashAlgorithm::Poseidon);
+        assert_eq!(keccak.hash_algorithm(), HashAlgorithm::Keccak256);
+        assert_ne!(poseidon.get_root().await?, keccak.get_root().await?);
+
+        // A tree picked at runtime matches the one built with the concrete hasher
+        let mut configured = L2MerkleTreeBuilder::with_hasher(HashAlgorithm::Keccak256);
+        configured.build_merkle(small_leaves()).await?;
+        assert_eq!(configured.get_root().await?, keccak.get_root().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proofs_verify_with_the_tree_hasher() -> Result<()> {
+        let leaf = small_leaves()[2];
+
+        for algorithm in [HashAlgorithm::Poseidon, HashAlgorithm::Keccak256] {
+            let mut builder = L2MerkleTreeBuilder::with_hasher(algorithm);
+            builder.build_merkle(small_leaves()).await?;
+
+            let proof = builder.get_proof(leaf).await?.unwrap();
+            assert!(
+                builder.verify_proof(proof, leaf).await?,
+                "{:?} proof should be valid",
+                algorithm
+            );
+        }
+
+        // A proof built with Keccak256 does not hold against the Poseidon tree
+        let mut poseidon = L2MerkleTreeBuilder::new();
+        let mut keccak = L2MerkleTreeBuilder::with_hasher(Keccak256MerkleHasher);
+        poseidon.build_merkle(small_leaves()).await?;
+        keccak.build_merkle(small_leaves()).await?;
+        let keccak_proof = keccak.get_proof(leaf).await?.unwrap();
+        assert!(!poseidon
+            .verify_proof(keccak_proof, leaf)
+            .await
+            .unwrap_or(false));
+
+        Ok(())
+    }
 }
@@ -1,4 +1,5 @@
 mod error;
+pub mod hasher;
 pub mod l1_tree;
 pub mod l2_tree;
 pub mod types;
@@ -1,3 +1,16 @@
+use serde::{Deserialize, Serialize};
+
 use crate::error::TreeBuilderError;
 
 pub type Result<T> = std::result::Result<T, TreeBuilderError>;
+
+/// Hash function combining the nodes of a tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    /// Stark Poseidon, native to Starknet
+    #[default]
+    Poseidon,
+    /// Keccak256, for roots verified by L1 contracts
+    Keccak256,
+}
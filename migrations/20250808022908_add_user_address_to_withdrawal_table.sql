@@ -1,4 +1,3 @@
 -- Add migration script here
-ALTER TABLE withdrawal
-ADD COLUMN user_address TEXT NOT NULL;
-ß
+ALTER TABLE withdrawals
+ADD COLUMN IF NOT EXISTS user_address TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,12 @@
+-- Create deposit_audit_log table recording operator-driven deposit status changes
+CREATE TABLE IF NOT EXISTS deposit_audit_log (
+    id SERIAL PRIMARY KEY,
+    deposit_id INTEGER NOT NULL REFERENCES deposits (id),
+    old_status TEXT NOT NULL,
+    new_status TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create index on deposit_id for faster lookups
+CREATE INDEX IF NOT EXISTS deposit_audit_log_deposit_id_idx ON deposit_audit_log (deposit_id);
@@ -0,0 +1,11 @@
+-- Create withdrawal_events table recording every withdrawal status change
+CREATE TABLE IF NOT EXISTS withdrawal_events (
+    id SERIAL PRIMARY KEY,
+    withdrawal_id INTEGER NOT NULL REFERENCES withdrawals (id),
+    status TEXT NOT NULL,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create index on withdrawal_id for faster timeline lookups
+CREATE INDEX IF NOT EXISTS withdrawal_events_withdrawal_id_idx ON withdrawal_events (withdrawal_id);
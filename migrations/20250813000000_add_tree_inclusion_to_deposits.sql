@@ -0,0 +1,10 @@
+-- Record where each deposit landed in the L1 Merkle tree
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS leaf_index BIGINT,
+    ADD COLUMN IF NOT EXISTS merkle_root TEXT,
+    ADD COLUMN IF NOT EXISTS included_at TIMESTAMPTZ;
+
+-- A leaf position can only be taken by one deposit
+CREATE UNIQUE INDEX IF NOT EXISTS deposits_leaf_index_uniq
+    ON deposits (leaf_index)
+    WHERE leaf_index IS NOT NULL;
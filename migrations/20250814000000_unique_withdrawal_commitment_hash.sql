@@ -0,0 +1,2 @@
+-- Withdrawals from L2 burn events are merged with API-created rows by commitment hash
+CREATE UNIQUE INDEX IF NOT EXISTS idx_withdrawals_commitment_hash ON withdrawals (commitment_hash);
@@ -0,0 +1,19 @@
+-- Create merkle_roots table keeping the root of the L1 deposit tree at every size
+CREATE TABLE IF NOT EXISTS merkle_roots (
+    id SERIAL PRIMARY KEY,
+    elements_count BIGINT NOT NULL,
+    root TEXT NOT NULL,
+    block_number BIGINT,
+    source TEXT NOT NULL CHECK (source IN ('local', 'onchain')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT merkle_roots_elements_count_source_key UNIQUE (elements_count, source)
+);
+
+COMMENT ON TABLE merkle_roots IS 'Roots of the L1 deposit Merkle tree, computed locally or emitted by the contract';
+COMMENT ON COLUMN merkle_roots.elements_count IS 'Number of leaves in the tree when the root was computed';
+COMMENT ON COLUMN merkle_roots.block_number IS 'L1 block that emitted the root, NULL for local roots';
+COMMENT ON COLUMN merkle_roots.source IS 'local for roots computed by the tree builder, onchain for roots emitted on L1';
+
+-- Tree size the deposit's proof was generated against
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS proof_elements_count BIGINT;
@@ -0,0 +1,3 @@
+-- Deposits submitted before their commitment is seen on L1 are matched against
+-- deposit_hashes by commitment hash
+CREATE INDEX IF NOT EXISTS deposit_hashes_commitment_hash_idx ON deposit_hashes (commitment_hash);
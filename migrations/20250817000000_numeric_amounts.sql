@@ -0,0 +1,5 @@
+-- Amounts are uint256 on L1 and u256 on L2; BIGINT could not hold them all.
+-- NUMERIC(78, 0) fits every value up to 2^256 - 1.
+ALTER TABLE deposits ALTER COLUMN amount TYPE NUMERIC(78, 0);
+ALTER TABLE withdrawals ALTER COLUMN amount TYPE NUMERIC(78, 0);
+ALTER TABLE l2_transactions ALTER COLUMN amount TYPE NUMERIC(78, 0);
@@ -0,0 +1,22 @@
+-- Create herodotus_jobs table linking Herodotus storage proof jobs to the withdrawals
+-- waiting on them
+CREATE TABLE IF NOT EXISTS herodotus_jobs (
+    id SERIAL PRIMARY KEY,
+    withdrawal_id INTEGER NOT NULL REFERENCES withdrawals (id),
+    job_id TEXT NOT NULL UNIQUE,
+    block_number BIGINT NOT NULL,
+    contract_address TEXT NOT NULL,
+    storage_slot TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes on withdrawal_id and status for the poller lookups
+CREATE INDEX IF NOT EXISTS herodotus_jobs_withdrawal_id_idx ON herodotus_jobs (withdrawal_id);
+CREATE INDEX IF NOT EXISTS herodotus_jobs_status_idx ON herodotus_jobs (status);
+
+-- Proof payload returned by Herodotus, relayed with the withdrawal
+ALTER TABLE withdrawals
+    ADD COLUMN IF NOT EXISTS state_proof JSONB;
@@ -0,0 +1,9 @@
+-- Track which proof client worker holds a deposit while its proof is generated, so
+-- replicas never prove the same deposit twice and abandoned claims can be reclaimed
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS claimed_by TEXT,
+    ADD COLUMN IF NOT EXISTS claimed_at TIMESTAMPTZ;
+
+-- Create an index on claimed_at for the janitor looking up stale claims
+CREATE INDEX IF NOT EXISTS deposits_claimed_at_idx ON deposits (claimed_at)
+    WHERE claimed_at IS NOT NULL;
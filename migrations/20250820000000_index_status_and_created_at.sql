@@ -0,0 +1,8 @@
+-- Create indexes on status and created_at for the GET /stats aggregates
+CREATE INDEX IF NOT EXISTS deposits_status_idx ON deposits (status);
+CREATE INDEX IF NOT EXISTS deposits_created_at_idx ON deposits (created_at);
+CREATE INDEX IF NOT EXISTS withdrawals_status_idx ON withdrawals (status);
+CREATE INDEX IF NOT EXISTS withdrawals_created_at_idx ON withdrawals (created_at);
+
+-- Create index on new_status for the time-to-ready lookups in the audit log
+CREATE INDEX IF NOT EXISTS deposit_audit_log_new_status_idx ON deposit_audit_log (new_status);
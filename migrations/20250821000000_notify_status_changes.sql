@@ -0,0 +1,39 @@
+-- Notify listening services when a deposit or L2 transaction reaches a new status, so
+-- they can pick it up straight away instead of waiting for their next poll
+CREATE OR REPLACE FUNCTION notify_status_change() RETURNS TRIGGER AS $$
+DECLARE
+    old_status TEXT;
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        old_status := OLD.status;
+    END IF;
+
+    PERFORM pg_notify(
+        TG_TABLE_NAME || '_status',
+        json_build_object('id', NEW.id, 'status', NEW.status, 'old_status', old_status)::text
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS deposits_status_inserted ON deposits;
+CREATE TRIGGER deposits_status_inserted
+    AFTER INSERT ON deposits
+    FOR EACH ROW EXECUTE FUNCTION notify_status_change();
+
+DROP TRIGGER IF EXISTS deposits_status_changed ON deposits;
+CREATE TRIGGER deposits_status_changed
+    AFTER UPDATE OF status ON deposits
+    FOR EACH ROW WHEN (OLD.status IS DISTINCT FROM NEW.status)
+    EXECUTE FUNCTION notify_status_change();
+
+DROP TRIGGER IF EXISTS l2_transactions_status_inserted ON l2_transactions;
+CREATE TRIGGER l2_transactions_status_inserted
+    AFTER INSERT ON l2_transactions
+    FOR EACH ROW EXECUTE FUNCTION notify_status_change();
+
+DROP TRIGGER IF EXISTS l2_transactions_status_changed ON l2_transactions;
+CREATE TRIGGER l2_transactions_status_changed
+    AFTER UPDATE OF status ON l2_transactions
+    FOR EACH ROW WHEN (OLD.status IS DISTINCT FROM NEW.status)
+    EXECUTE FUNCTION notify_status_change();
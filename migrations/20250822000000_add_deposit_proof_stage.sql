@@ -0,0 +1,4 @@
+-- Last Stone pipeline stage completed for the deposit, so an interrupted proof can
+-- resume from its checkpoint instead of starting over
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS proof_stage TEXT;
@@ -0,0 +1,40 @@
+-- Tag every row with the L1/L2 network pair it belongs to, existing rows stay on the
+-- single network the sequencer ran against before networks were configurable
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE withdrawals
+    ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE l2_transactions
+    ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE deposit_hashes
+    ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE merkle_roots
+    ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE block_trackers
+    ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'default';
+
+-- Each network's watchers keep their own cursors under the same keys
+ALTER TABLE block_trackers DROP CONSTRAINT block_trackers_pkey;
+ALTER TABLE block_trackers ADD PRIMARY KEY (network, key);
+DROP INDEX IF EXISTS idx_block_trackers_key;
+
+-- Each network builds its own tree, so leaf positions and roots repeat across networks
+DROP INDEX IF EXISTS deposits_leaf_index_uniq;
+CREATE UNIQUE INDEX IF NOT EXISTS deposits_network_leaf_index_uniq
+    ON deposits (network, leaf_index)
+    WHERE leaf_index IS NOT NULL;
+ALTER TABLE merkle_roots DROP CONSTRAINT merkle_roots_elements_count_source_key;
+ALTER TABLE merkle_roots
+    ADD CONSTRAINT merkle_roots_network_elements_count_source_key
+    UNIQUE (network, elements_count, source);
+
+-- Services only ever look at the rows of their own network
+CREATE INDEX IF NOT EXISTS deposits_network_status_idx ON deposits (network, status);
+CREATE INDEX IF NOT EXISTS withdrawals_network_status_idx ON withdrawals (network, status);
+CREATE INDEX IF NOT EXISTS l2_transactions_network_status_idx ON l2_transactions (network, status);
+CREATE INDEX IF NOT EXISTS deposit_hashes_network_commitment_hash_idx
+    ON deposit_hashes (network, commitment_hash);
+
+COMMENT ON COLUMN deposits.network IS 'Configured L1/L2 network pair the deposit was made on';
+COMMENT ON COLUMN withdrawals.network IS 'Configured L1/L2 network pair the withdrawal was made on';
+COMMENT ON COLUMN block_trackers.network IS 'Network whose watcher the cursor belongs to';
@@ -0,0 +1,18 @@
+-- Hash of every L1 block the deposit watcher moved its cursor to, compared with the
+-- chain on the next poll to notice re-orgs
+CREATE TABLE IF NOT EXISTS block_hashes (
+    network TEXT NOT NULL DEFAULT 'default',
+    block_number BIGINT NOT NULL,
+    block_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (network, block_number)
+);
+
+COMMENT ON TABLE block_hashes IS 'L1 block hashes seen by the deposit watcher, used to detect re-orgs';
+
+-- L1 block that emitted the deposit event, so deposits of re-orged blocks can be found
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS l1_block_number BIGINT;
+CREATE INDEX IF NOT EXISTS deposits_network_l1_block_number_idx
+    ON deposits (network, l1_block_number)
+    WHERE l1_block_number IS NOT NULL;
@@ -0,0 +1,16 @@
+-- Link each L2 transaction to the deposit it relays, so the L2 queue processor can
+-- never enqueue the same deposit twice
+ALTER TABLE l2_transactions
+    ADD COLUMN IF NOT EXISTS deposit_id INTEGER UNIQUE REFERENCES deposits(id);
+
+-- Starknet keys and transaction hashes are felts, longer than an L1 address
+ALTER TABLE l2_transactions
+    ALTER COLUMN stark_pub_key TYPE TEXT,
+    ALTER COLUMN token_address TYPE TEXT,
+    ALTER COLUMN tx_hash TYPE TEXT;
+
+-- A withdrawal is handed to the Ethereum relayer with a single proof
+CREATE UNIQUE INDEX IF NOT EXISTS withdrawal_proofs_withdrawal_id_uniq
+    ON withdrawal_proofs (withdrawal_id);
+
+COMMENT ON COLUMN l2_transactions.deposit_id IS 'Deposit relayed by the transaction, set by the L2 queue processor';
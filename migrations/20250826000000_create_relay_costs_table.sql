@@ -0,0 +1,20 @@
+-- Fee the relayer account paid for each completed L2 transaction, read from its receipt.
+-- Transactions relayed in one batch share the batch's fee evenly.
+CREATE TABLE IF NOT EXISTS relay_costs (
+    id BIGSERIAL PRIMARY KEY,
+    l2_transaction_id BIGINT NOT NULL UNIQUE REFERENCES l2_transactions(id),
+    network TEXT NOT NULL DEFAULT 'default',
+    tx_hash TEXT NOT NULL,
+    account_address TEXT NOT NULL,
+    actual_fee NUMERIC(78, 0) NOT NULL,
+    -- WEI or FRI
+    fee_unit TEXT NOT NULL,
+    steps BIGINT NOT NULL,
+    l1_gas BIGINT NOT NULL,
+    l1_data_gas BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS relay_costs_created_at_idx ON relay_costs (created_at);
+
+COMMENT ON TABLE relay_costs IS 'Fees paid by the relayer account per relayed L2 transaction';
@@ -0,0 +1,16 @@
+-- Progress of a tree rebuild, so a rebuild interrupted on a large deposits table
+-- resumes after the last batch it checked instead of starting over
+CREATE TABLE IF NOT EXISTS tree_rebuild_checkpoints (
+    network TEXT PRIMARY KEY,
+    fix BOOLEAN NOT NULL,
+    leaves_checked BIGINT NOT NULL,
+    last_leaf_index BIGINT NOT NULL,
+    discrepancies JSONB NOT NULL DEFAULT '[]',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE tree_rebuild_checkpoints IS 'Progress of the tree rebuild running on each network';
+COMMENT ON COLUMN tree_rebuild_checkpoints.fix IS 'Whether the rebuild repairs the rows it finds inconsistent';
+COMMENT ON COLUMN tree_rebuild_checkpoints.leaves_checked IS 'Number of leaves replayed into the rebuilt tree';
+COMMENT ON COLUMN tree_rebuild_checkpoints.last_leaf_index IS 'leaf_index of the last deposit checked, as stored after any fix';
+COMMENT ON COLUMN tree_rebuild_checkpoints.discrepancies IS 'Discrepancies found so far';
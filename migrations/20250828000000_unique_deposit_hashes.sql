@@ -0,0 +1,11 @@
+-- A DepositHashAppended event is stored once per network, so a backfill overlapping
+-- ranges already ingested can insert with ON CONFLICT DO NOTHING
+DELETE FROM deposit_hashes AS duplicate
+USING deposit_hashes AS kept
+WHERE duplicate.network = kept.network
+  AND duplicate.commitment_hash = kept.commitment_hash
+  AND duplicate.block_number = kept.block_number
+  AND duplicate.id > kept.id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS deposit_hashes_network_commitment_hash_block_number_key
+    ON deposit_hashes (network, commitment_hash, block_number);
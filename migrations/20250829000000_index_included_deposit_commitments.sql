@@ -0,0 +1,5 @@
+-- The tree builder looks up a commitment among the included deposits before appending
+-- it, however the hash was written (with or without 0x, upper or lower case)
+CREATE INDEX IF NOT EXISTS deposits_network_included_commitment_idx
+    ON deposits (network, lower(regexp_replace(commitment_hash, '^0x', '')))
+    WHERE leaf_index IS NOT NULL;
@@ -0,0 +1,15 @@
+-- Invoke transactions the Starknet relayer sent, recorded before their confirmation is
+-- awaited so that a relayer restarted in between waits for them instead of sending
+-- the same L2 transactions again. Transactions relayed in one batch share a hash.
+CREATE TABLE IF NOT EXISTS submitted_tx_hashes (
+    id BIGSERIAL PRIMARY KEY,
+    l2_transaction_id BIGINT NOT NULL REFERENCES l2_transactions(id) ON DELETE CASCADE,
+    tx_hash TEXT NOT NULL,
+    -- pending_confirmation, confirmed or failed
+    status TEXT NOT NULL DEFAULT 'pending_confirmation',
+    submitted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS submitted_tx_hashes_pending_idx
+    ON submitted_tx_hashes (l2_transaction_id)
+    WHERE status = 'pending_confirmation';
@@ -0,0 +1,11 @@
+-- Position of a withdrawal's commitment in its network's L2 tree, the root it was
+-- appended under and its inclusion proof against that root
+ALTER TABLE withdrawals
+    ADD COLUMN IF NOT EXISTS leaf_index BIGINT,
+    ADD COLUMN IF NOT EXISTS merkle_root TEXT,
+    ADD COLUMN IF NOT EXISTS proof JSONB;
+
+-- A leaf position of a network's tree can only be taken by one withdrawal
+CREATE UNIQUE INDEX IF NOT EXISTS withdrawals_network_leaf_index_uniq
+    ON withdrawals (network, leaf_index)
+    WHERE leaf_index IS NOT NULL;
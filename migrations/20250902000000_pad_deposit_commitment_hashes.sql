@@ -0,0 +1,11 @@
+-- Deposits recorded from L1 events used to store their commitment hash as unprefixed
+-- hex without leading zeros, which the tree builder cannot decode. Spell them like
+-- commitments submitted through the API, unless that spelling is already taken.
+UPDATE deposits AS d
+SET commitment_hash = '0x' || lpad(lower(d.commitment_hash), 64, '0'),
+    updated_at = NOW()
+WHERE d.commitment_hash ~ '^[0-9a-fA-F]{1,64}$'
+  AND NOT EXISTS (
+      SELECT 1 FROM deposits AS other
+      WHERE other.commitment_hash = '0x' || lpad(lower(d.commitment_hash), 64, '0')
+  );
@@ -0,0 +1,89 @@
+-- Endpoints integrators registered to be notified of deposit and withdrawal status
+-- changes, see `/admin/webhooks`
+CREATE TABLE IF NOT EXISTS webhooks (
+    id SERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    -- Key of the HMAC-SHA256 signature sent in `X-Signature`
+    secret TEXT NOT NULL,
+    -- `deposit.status_changed` and/or `withdrawal.status_changed`
+    event_types TEXT[] NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Outbox of the webhook dispatcher: one row per status change and subscribed webhook,
+-- written in the transaction making the change so none is lost or blocks the pipeline
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    webhook_id INTEGER NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    -- pending, delivered or failed once max_attempts were made
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- HTTP status of the last attempt, NULL when the webhook could not be reached
+    response_status INTEGER,
+    last_error TEXT,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_due_idx
+    ON webhook_deliveries (next_attempt_at)
+    WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS webhook_deliveries_webhook_id_idx
+    ON webhook_deliveries (webhook_id);
+
+-- Queues a delivery of the row's status change to every active webhook subscribed to
+-- `<entity type>.status_changed`, the entity type being the trigger's argument
+CREATE OR REPLACE FUNCTION enqueue_webhook_deliveries() RETURNS TRIGGER AS $$
+DECLARE
+    entity TEXT := TG_ARGV[0];
+    event TEXT := TG_ARGV[0] || '.status_changed';
+    old_status TEXT;
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        old_status := OLD.status;
+    END IF;
+
+    INSERT INTO webhook_deliveries (webhook_id, event_type, payload)
+    SELECT w.id, event, jsonb_build_object(
+        'event', event,
+        'entity_type', entity,
+        'id', NEW.id,
+        'network', NEW.network,
+        'commitment_hash', NEW.commitment_hash,
+        'old_status', old_status,
+        'new_status', NEW.status,
+        'timestamp', NOW()
+    )
+    FROM webhooks w
+    WHERE w.active AND event = ANY (w.event_types);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS deposits_webhook_inserted ON deposits;
+CREATE TRIGGER deposits_webhook_inserted
+    AFTER INSERT ON deposits
+    FOR EACH ROW EXECUTE FUNCTION enqueue_webhook_deliveries('deposit');
+
+DROP TRIGGER IF EXISTS deposits_webhook_status_changed ON deposits;
+CREATE TRIGGER deposits_webhook_status_changed
+    AFTER UPDATE OF status ON deposits
+    FOR EACH ROW WHEN (OLD.status IS DISTINCT FROM NEW.status)
+    EXECUTE FUNCTION enqueue_webhook_deliveries('deposit');
+
+DROP TRIGGER IF EXISTS withdrawals_webhook_inserted ON withdrawals;
+CREATE TRIGGER withdrawals_webhook_inserted
+    AFTER INSERT ON withdrawals
+    FOR EACH ROW EXECUTE FUNCTION enqueue_webhook_deliveries('withdrawal');
+
+DROP TRIGGER IF EXISTS withdrawals_webhook_status_changed ON withdrawals;
+CREATE TRIGGER withdrawals_webhook_status_changed
+    AFTER UPDATE OF status ON withdrawals
+    FOR EACH ROW WHEN (OLD.status IS DISTINCT FROM NEW.status)
+    EXECUTE FUNCTION enqueue_webhook_deliveries('withdrawal');
@@ -0,0 +1,18 @@
+-- How long each stage of a deposit's way through the sequencer took, written by
+-- `StageTimer` once a stage finishes, see `GET /deposits/{id}/timings`
+CREATE TABLE IF NOT EXISTS deposit_timings (
+    id BIGSERIAL PRIMARY KEY,
+    deposit_id INTEGER NOT NULL REFERENCES deposits (id) ON DELETE CASCADE,
+    -- tree_inclusion, cairo_inputs, scarb_build, stone_pipeline, calldata,
+    -- artifact_persistence, relay_queueing, relay_submission or relay_confirmation
+    stage TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    success BOOLEAN NOT NULL,
+    -- Why the stage failed, NULL when it succeeded
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_deposit_timings_deposit_id ON deposit_timings (deposit_id);
+CREATE INDEX IF NOT EXISTS idx_deposit_timings_stage ON deposit_timings (stage, finished_at);
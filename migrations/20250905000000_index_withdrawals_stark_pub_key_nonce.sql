@@ -0,0 +1,3 @@
+-- Looks up the withdrawal taking up each nonce of a key when checking for nonce gaps
+CREATE INDEX IF NOT EXISTS withdrawals_stark_pub_key_nonce_idx
+    ON withdrawals (stark_pub_key, nonce);
@@ -0,0 +1,10 @@
+-- Services paused by an admin through `POST /admin/services/{name}/pause`, checked by
+-- each service at the top of its loop. A service without a row runs.
+CREATE TABLE IF NOT EXISTS service_controls (
+    service_name TEXT PRIMARY KEY,
+    paused BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Why the service was paused or resumed
+    reason TEXT,
+    updated_by TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
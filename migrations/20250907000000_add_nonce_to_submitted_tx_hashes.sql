@@ -0,0 +1,5 @@
+-- Nonce each recorded invoke transaction was signed with. A transaction that is not
+-- accepted in time can still be included until its nonce is taken by another one, so
+-- the relayer only sends its L2 transactions again once the account's nonce moved past
+-- it. Rows recorded before this column existed have no nonce and are waited for.
+ALTER TABLE submitted_tx_hashes ADD COLUMN IF NOT EXISTS nonce TEXT;
@@ -0,0 +1,22 @@
+-- Carry the commitment hash of deposits in their status notifications, so the API can
+-- stream a deposit's status changes without reading the row again
+CREATE OR REPLACE FUNCTION notify_status_change() RETURNS TRIGGER AS $$
+DECLARE
+    old_status TEXT;
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        old_status := OLD.status;
+    END IF;
+
+    PERFORM pg_notify(
+        TG_TABLE_NAME || '_status',
+        json_build_object(
+            'id', NEW.id,
+            'status', NEW.status,
+            'old_status', old_status,
+            'commitment_hash', to_jsonb(NEW) ->> 'commitment_hash'
+        )::text
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,267 @@
+use axum::{
+    body::Body,
+    extract::Path,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use std::ops::Range;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tracing::error;
+
+use crate::api::error::ApiError;
+use crate::config::ProofConfig;
+use crate::db::database::{fetch_deposit_status_by_id, DEPOSIT_STATUS_QUEUED_FOR_RELAY};
+use crate::proof_client::artifact_store::{
+    artifact_store_from_config, ArtifactName, ArtifactStoreError, ProofArtifactStore,
+};
+use crate::proof_client::service::DEPOSIT_STATUS_READY_FOR_RELAY;
+
+/// Statuses of deposits whose proof was generated and saved
+const PROVED_DEPOSIT_STATUSES: &[&str] = &[
+    DEPOSIT_STATUS_READY_FOR_RELAY,
+    DEPOSIT_STATUS_QUEUED_FOR_RELAY,
+    "READY_TO_CLAIM",
+    "PROCESSED",
+];
+
+/// Store the API serves proof artifacts from, built from `[proof]` on the first download
+#[derive(Clone)]
+pub struct ProofArtifacts {
+    config: ProofConfig,
+    store: Arc<OnceCell<Arc<dyn ProofArtifactStore>>>,
+}
+
+impl ProofArtifacts {
+    pub fn new(config: ProofConfig) -> Self {
+        Self {
+            config,
+            store: Arc::new(OnceCell::new()),
+        }
+    }
+
+    async fn store(&self) -> Result<&Arc<dyn ProofArtifactStore>, ArtifactStoreError> {
+        self.store
+            .get_or_try_init(|| artifact_store_from_config(&self.config))
+            .await
+    }
+}
+
+impl Default for ProofArtifacts {
+    fn default() -> Self {
+        Self::new(ProofConfig::default())
+    }
+}
+
+/// A `Range` header that cannot be served from an artifact of the given size
+#[derive(Debug, PartialEq, Eq)]
+struct RangeNotSatisfiable;
+
+/// Downloads an artifact of a deposit's proof.
+///
+/// The artifact is streamed from the proof artifact store as it is read. `Range`
+/// requests for a single byte range are answered with `206 Partial Content`, so
+/// interrupted downloads can resume, and the `ETag` is the SHA-256 recorded when the
+/// artifact was saved.
+#[utoipa::path(
+    get,
+    path = "/proofs/{deposit_id}/artifacts/{name}",
+    tag = "proofs",
+    params(
+        ("deposit_id" = i32, Path, description = "Deposit ID"),
+        ("name" = String, Path, description = "`proof.json`, `fact_hash.txt`, or `calldata/` followed by `initial`, `step{n}` or `final`"),
+        ("Range" = Option<String>, Header, description = "A single byte range, e.g. `bytes=1048576-`"),
+    ),
+    responses(
+        (status = 200, description = "The whole artifact", content_type = "application/octet-stream"),
+        (status = 206, description = "The requested byte range of the artifact", content_type = "application/octet-stream"),
+        (status = 404, description = "Unknown deposit or artifact", body = ApiErrorBody),
+        (status = 409, description = "The deposit's proof has not been generated yet, `details.status` holds its status", body = ApiErrorBody),
+        (status = 416, description = "The range lies outside the artifact", body = ApiErrorBody),
+    )
+)]
+pub async fn download_proof_artifact(
+    Path((deposit_id, name)): Path<(i32, String)>,
+    headers: HeaderMap,
+    Extension(pool): Extension<PgPool>,
+    Extension(artifacts): Extension<ProofArtifacts>,
+) -> Result<Response, ApiError> {
+    let artifact = ArtifactName::parse(&name).ok_or_else(artifact_not_found)?;
+    let status = fetch_deposit_status_by_id(&pool, deposit_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("deposit_not_found", "Deposit not found"))?;
+    if !PROVED_DEPOSIT_STATUSES
+        .iter()
+        .any(|proved| proved.eq_ignore_ascii_case(&status))
+    {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "proof_not_ready",
+            format!("The proof of deposit {} has not been generated", deposit_id),
+        )
+        .with_details(json!({ "status": status })));
+    }
+
+    let store = artifacts.store().await.map_err(store_error)?;
+    let info = store
+        .stat(deposit_id, &artifact)
+        .await
+        .map_err(store_error)?
+        .ok_or_else(artifact_not_found)?;
+    let etag = info.sha256.map(|sha256| format!("\"{}\"", sha256));
+
+    // A range of an artifact that changed since the client's copy would corrupt it
+    let if_range_matches = match headers.get(header::IF_RANGE) {
+        Some(if_range) => etag.as_deref().is_some_and(|etag| if_range == etag),
+        None => true,
+    };
+    let range = match headers.get(header::RANGE).and_then(|r| r.to_str().ok()) {
+        Some(range) if if_range_matches => match parse_range(range, info.size) {
+            Ok(range) => range,
+            Err(RangeNotSatisfiable) => {
+                let mut response = ApiError::new(
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    "range_not_satisfiable",
+                    format!("The artifact is {} bytes long", info.size),
+                )
+                .into_response();
+                response.headers_mut().insert(
+                    header::CONTENT_RANGE,
+                    header_value(format!("bytes */{}", info.size)),
+                );
+                return Ok(response);
+            }
+        },
+        _ => None,
+    };
+
+    let bytes = range.clone().unwrap_or(0..info.size);
+    let stream = store
+        .read(deposit_id, &artifact, bytes.clone())
+        .await
+        .map_err(store_error)?
+        .ok_or_else(artifact_not_found)?;
+
+    let mut response = Response::new(Body::from_stream(stream));
+    let response_headers = response.headers_mut();
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(artifact.content_type()),
+    );
+    response_headers.insert(
+        header::CONTENT_LENGTH,
+        header_value((bytes.end - bytes.start).to_string()),
+    );
+    response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    if let Some(etag) = etag {
+        response_headers.insert(header::ETAG, header_value(etag));
+    }
+    if range.is_some() {
+        response_headers.insert(
+            header::CONTENT_RANGE,
+            header_value(format!(
+                "bytes {}-{}/{}",
+                bytes.start,
+                bytes.end - 1,
+                info.size
+            )),
+        );
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+    }
+    Ok(response)
+}
+
+/// Parses a `Range` header against an artifact of `size` bytes, `None` when the whole
+/// artifact is served instead.
+///
+/// Only single byte ranges are served: headers in another unit, with several ranges or
+/// that do not parse are ignored, as HTTP allows.
+fn parse_range(header: &str, size: u64) -> Result<Option<Range<u64>>, RangeNotSatisfiable> {
+    let Some(spec) = header.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return Ok(None);
+    };
+
+    let range = if first.is_empty() {
+        // The last `last` bytes
+        let Ok(suffix) = last.parse::<u64>() else {
+            return Ok(None);
+        };
+        if suffix == 0 || size == 0 {
+            return Err(RangeNotSatisfiable);
+        }
+        size.saturating_sub(suffix)..size
+    } else {
+        let Ok(first) = first.parse::<u64>() else {
+            return Ok(None);
+        };
+        let last = if last.is_empty() {
+            u64::MAX
+        } else {
+            match last.parse::<u64>() {
+                Ok(last) if last >= first => last,
+                _ => return Ok(None),
+            }
+        };
+        if first >= size {
+            return Err(RangeNotSatisfiable);
+        }
+        first..last.min(size - 1) + 1
+    };
+    Ok(Some(range))
+}
+
+fn artifact_not_found() -> ApiError {
+    ApiError::not_found("artifact_not_found", "Artifact not found")
+}
+
+fn store_error(err: ArtifactStoreError) -> ApiError {
+    error!("Failed to read proof artifact: {}", err);
+    ApiError::internal("Failed to read proof artifact")
+}
+
+fn header_value(value: String) -> HeaderValue {
+    HeaderValue::from_str(&value).expect("formatted header values are visible ASCII")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_ranges_are_clamped_to_the_artifact() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Ok(Some(0..100)));
+        assert_eq!(parse_range("bytes=500-", 1000), Ok(Some(500..1000)));
+        assert_eq!(parse_range("bytes=900-5000", 1000), Ok(Some(900..1000)));
+        assert_eq!(parse_range("bytes=-100", 1000), Ok(Some(900..1000)));
+        assert_eq!(parse_range("bytes=-5000", 1000), Ok(Some(0..1000)));
+    }
+
+    #[test]
+    fn test_ranges_outside_the_artifact_are_not_satisfiable() {
+        assert_eq!(parse_range("bytes=1000-", 1000), Err(RangeNotSatisfiable));
+        assert_eq!(parse_range("bytes=-0", 1000), Err(RangeNotSatisfiable));
+        assert_eq!(parse_range("bytes=0-", 0), Err(RangeNotSatisfiable));
+    }
+
+    #[test]
+    fn test_other_ranges_serve_the_whole_artifact() {
+        for header in [
+            "items=0-1",
+            "bytes=0-1,5-6",
+            "bytes=5-1",
+            "bytes=a-b",
+            "bytes=",
+        ] {
+            assert_eq!(parse_range(header, 1000), Ok(None), "{}", header);
+        }
+    }
+}
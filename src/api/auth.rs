@@ -0,0 +1,321 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+    middleware::Next,
+    response::Response,
+};
+use ethers::types::{Address, Signature, H256};
+use ethers::utils::keccak256;
+use std::collections::HashSet;
+use std::str::FromStr;
+use tracing::error;
+
+use crate::api::error::ApiError;
+use crate::http::body_limit::BodyLimit;
+
+/// Headers carrying the admin signatures of a request
+pub const ADMIN_SIGNATURE_HEADERS: [&str; 3] = ["x-admin-sig-1", "x-admin-sig-2", "x-admin-sig-3"];
+
+/// Number of distinct admin signatures required to authorize a request
+pub const ADMIN_SIGNATURE_THRESHOLD: usize = 2;
+
+/// Bearer token guarding admin endpoints, shared with handlers as an `Extension`
+#[derive(Debug, Clone, Default)]
+pub struct AdminToken(pub Option<String>);
+
+impl AdminToken {
+    /// Checks the `Authorization: Bearer <token>` header against the configured token
+    pub fn authorize(&self, headers: &HeaderMap) -> Result<(), ApiError> {
+        let expected = self.0.as_deref().ok_or_else(|| {
+            ApiError::new(
+                StatusCode::FORBIDDEN,
+                "admin_disabled",
+                "Admin endpoints are disabled",
+            )
+        })?;
+
+        let provided = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                ApiError::new(
+                    StatusCode::UNAUTHORIZED,
+                    "unauthorized",
+                    "Missing bearer token",
+                )
+            })?;
+
+        if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+            return Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                "Invalid bearer token",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// 2-of-3 ECDSA multisig guarding the `/admin` routes.
+///
+/// Every request must carry secp256k1 signatures of its body hash (see
+/// [`admin_request_hash`]) in the `X-Admin-Sig-{1,2,3}` headers, and at least
+/// [`ADMIN_SIGNATURE_THRESHOLD`] of them must recover to distinct configured admin
+/// addresses.
+#[derive(Debug, Clone, Default)]
+pub struct AdminAuth {
+    known_addresses: Vec<Address>,
+}
+
+impl AdminAuth {
+    /// Builds the guard from the configured admin addresses, skipping malformed ones
+    pub fn new(admin_addresses: &[String]) -> Self {
+        let known_addresses = admin_addresses
+            .iter()
+            .filter_map(|address| match Address::from_str(address.trim()) {
+                Ok(address) => Some(address),
+                Err(e) => {
+                    error!("Ignoring invalid admin address {}: {}", address, e);
+                    None
+                }
+            })
+            .collect();
+
+        Self { known_addresses }
+    }
+
+    /// Whether at least [`ADMIN_SIGNATURE_THRESHOLD`] of `sigs` are signatures of
+    /// `body_hash` by distinct addresses in `known_addresses`.
+    ///
+    /// Signatures are 65-byte hex strings (`r || s || v`); malformed ones are ignored.
+    pub fn verify(body_hash: &[u8; 32], sigs: &[String], known_addresses: &[Address]) -> bool {
+        let hash = H256::from(*body_hash);
+
+        let signers: HashSet<Address> = sigs
+            .iter()
+            .filter_map(|sig| Signature::from_str(sig.trim()).ok())
+            .filter_map(|sig| sig.recover(hash).ok())
+            .filter(|signer| known_addresses.contains(signer))
+            .collect();
+
+        signers.len() >= ADMIN_SIGNATURE_THRESHOLD
+    }
+
+    fn authorize(&self, body_hash: &[u8; 32], headers: &HeaderMap) -> Result<(), ApiError> {
+        if self.known_addresses.len() < ADMIN_SIGNATURE_THRESHOLD {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "admin_disabled",
+                "Admin endpoints are disabled",
+            ));
+        }
+
+        let sigs: Vec<String> = ADMIN_SIGNATURE_HEADERS
+            .iter()
+            .filter_map(|name| headers.get(*name))
+            .filter_map(|value| value.to_str().ok())
+            .map(str::to_string)
+            .collect();
+
+        if !Self::verify(body_hash, &sigs, &self.known_addresses) {
+            return Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                format!(
+                    "At least {} valid admin signatures are required",
+                    ADMIN_SIGNATURE_THRESHOLD
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Hash admins sign to authorize a request.
+///
+/// It covers the method and path as well as the body, so signatures collected for
+/// one request cannot be replayed against another admin endpoint: the keccak256 of
+/// `"{METHOD} {path_and_query}\n"` followed by the raw body.
+pub fn admin_request_hash(method: &Method, uri: &Uri, body: &[u8]) -> [u8; 32] {
+    let path = uri
+        .path_and_query()
+        .map_or(uri.path(), |path| path.as_str());
+
+    let mut message = format!("{} {}\n", method, path).into_bytes();
+    message.extend_from_slice(body);
+    keccak256(message)
+}
+
+/// Axum middleware rejecting requests without enough valid admin signatures
+pub async fn require_admin_signatures(
+    State(auth): State<AdminAuth>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let (parts, body) = request.into_parts();
+    let limit = parts
+        .extensions
+        .get::<BodyLimit>()
+        .copied()
+        .unwrap_or_default();
+    let body = axum::body::to_bytes(body, limit.0)
+        .await
+        .map_err(|_| limit.payload_too_large())?;
+
+    let body_hash = admin_request_hash(&parts.method, &parts.uri, &body);
+    auth.authorize(&body_hash, &parts.headers)?;
+
+    Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
+
+// Compare without short-circuiting so response timing does not leak the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use ethers::signers::{LocalWallet, Signer};
+
+    // Hardhat's default development accounts
+    const ADMIN_KEYS: [&str; 3] = [
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+        "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9f804cdab365a",
+    ];
+    const OUTSIDER_KEY: &str = "7c852118294e51e653712a81e05800f419141751be58f605c371e15141b007a6";
+
+    fn headers(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(authorization).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_authorize_accepts_matching_token() {
+        let token = AdminToken(Some("secret".to_string()));
+        assert!(token.authorize(&headers("Bearer secret")).is_ok());
+    }
+
+    #[test]
+    fn test_authorize_rejects_wrong_or_missing_token() {
+        let token = AdminToken(Some("secret".to_string()));
+
+        let err = token.authorize(&headers("Bearer nope")).unwrap_err();
+        assert_eq!(err.status, StatusCode::UNAUTHORIZED);
+
+        let err = token.authorize(&HeaderMap::new()).unwrap_err();
+        assert_eq!(err.status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_authorize_without_configured_token() {
+        let err = AdminToken(None)
+            .authorize(&headers("Bearer secret"))
+            .unwrap_err();
+        assert_eq!(err.status, StatusCode::FORBIDDEN);
+    }
+
+    fn wallet(key: &str) -> LocalWallet {
+        key.parse().unwrap()
+    }
+
+    fn sign(key: &str, hash: &[u8; 32]) -> String {
+        let signature = wallet(key).sign_hash(H256::from(*hash)).unwrap();
+        format!("0x{}", signature)
+    }
+
+    fn admin_addresses() -> Vec<Address> {
+        ADMIN_KEYS.iter().map(|key| wallet(key).address()).collect()
+    }
+
+    fn request_hash() -> [u8; 32] {
+        admin_request_hash(&Method::DELETE, &Uri::from_static("/admin/deposits/1"), b"")
+    }
+
+    #[test]
+    fn test_verify_accepts_two_admin_signatures() {
+        let hash = request_hash();
+        let sigs = vec![sign(ADMIN_KEYS[0], &hash), sign(ADMIN_KEYS[2], &hash)];
+
+        assert!(AdminAuth::verify(&hash, &sigs, &admin_addresses()));
+    }
+
+    #[test]
+    fn test_verify_rejects_single_signature() {
+        let hash = request_hash();
+        let sigs = vec![sign(ADMIN_KEYS[1], &hash)];
+
+        assert!(!AdminAuth::verify(&hash, &sigs, &admin_addresses()));
+    }
+
+    #[test]
+    fn test_verify_counts_each_signer_once() {
+        let hash = request_hash();
+        let sig = sign(ADMIN_KEYS[0], &hash);
+
+        assert!(!AdminAuth::verify(
+            &hash,
+            &[sig.clone(), sig],
+            &admin_addresses()
+        ));
+    }
+
+    #[test]
+    fn test_verify_ignores_unknown_signers() {
+        let hash = request_hash();
+        let sigs = vec![sign(ADMIN_KEYS[0], &hash), sign(OUTSIDER_KEY, &hash)];
+
+        assert!(!AdminAuth::verify(&hash, &sigs, &admin_addresses()));
+    }
+
+    #[test]
+    fn test_verify_ignores_malformed_signatures() {
+        let hash = request_hash();
+        let sigs = vec![
+            "not-a-signature".to_string(),
+            "0x1234".to_string(),
+            sign(ADMIN_KEYS[0], &hash),
+            sign(ADMIN_KEYS[1], &hash),
+        ];
+
+        assert!(AdminAuth::verify(&hash, &sigs, &admin_addresses()));
+        assert!(!AdminAuth::verify(&hash, &sigs[..3], &admin_addresses()));
+    }
+
+    #[test]
+    fn test_signatures_do_not_carry_over_to_other_requests() {
+        let hash = request_hash();
+        let sigs = vec![sign(ADMIN_KEYS[0], &hash), sign(ADMIN_KEYS[1], &hash)];
+        let other =
+            admin_request_hash(&Method::DELETE, &Uri::from_static("/admin/deposits/2"), b"");
+
+        assert!(!AdminAuth::verify(&other, &sigs, &admin_addresses()));
+    }
+
+    #[test]
+    fn test_admin_auth_skips_invalid_addresses() {
+        let auth = AdminAuth::new(&[
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string(),
+            "not-an-address".to_string(),
+        ]);
+        assert_eq!(auth.known_addresses.len(), 1);
+
+        let err = auth
+            .authorize(&request_hash(), &HeaderMap::new())
+            .unwrap_err();
+        assert_eq!(err.status, StatusCode::FORBIDDEN);
+    }
+}
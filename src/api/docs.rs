@@ -0,0 +1,216 @@
+use axum::{response::Html, Json};
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::api::error::{ApiErrorBody, ApiErrorDetails};
+use crate::api::{artifacts, handlers, sse, types, ws};
+use crate::db::database::{
+    BlockTracker, BridgeStats, Deposit, DepositStats, DepositTiming, MerkleRoot, MerkleStats,
+    RelayCostSummary, ServiceControl, StageLatency, TransferStats, WebhookDelivery, Withdrawal,
+};
+use crate::events::deposit_status::DepositStatusEvent;
+use crate::tree_builder::{DiscrepancyField, TreeDiscrepancy, TreeRebuildReport};
+
+/// Declares [`ApiDoc`] with `$handler`s as its paths, and [`DOCUMENTED_HANDLERS`] naming
+/// them, so the list the router checks against is the one the document is built from
+macro_rules! api_doc {
+    ($($module:ident::$handler:ident),* $(,)?) => {
+        /// OpenAPI description of the sequencer API, generated from the handler annotations.
+        #[derive(OpenApi)]
+        #[openapi(
+            info(title = "ZeroXBridge Sequencer API"),
+            paths($($module::$handler),*),
+            components(schemas(
+                Deposit,
+                DepositStats,
+                DepositTiming,
+                BridgeStats,
+                TransferStats,
+                MerkleStats,
+                StageLatency,
+                RelayCostSummary,
+                Withdrawal,
+                MerkleRoot,
+                BlockTracker,
+                ApiErrorBody,
+                ApiErrorDetails,
+                types::DepositRequest,
+                types::DepositResponse,
+                types::CancelDepositResponse,
+                types::RefreshDepositResponse,
+                types::SetBlockTrackerRequest,
+                types::TreeRebuildRequest,
+                types::FillNonceGapRequest,
+                types::FillNonceGapResponse,
+                types::ServiceControlRequest,
+                types::ConfigReloadResponse,
+                ServiceControl,
+                types::ServiceHealth,
+                types::HealthResponse,
+                types::CreateWebhookRequest,
+                types::WebhookResponse,
+                WebhookDelivery,
+                TreeRebuildReport,
+                TreeDiscrepancy,
+                DiscrepancyField,
+                types::CreateWithdrawalRequest,
+                types::StarkSignature,
+                types::WithrawalResponse,
+                types::TimelineEntry,
+                types::WithdrawalStatusResponse,
+                types::PoseidonHashRequest,
+                types::PoseidonHashResponse,
+                types::HashRequest,
+                types::HashResponse,
+                types::InputData,
+                types::NonceType,
+                types::NonceResponse,
+                types::TreeRootResponse,
+                types::TreeProofResponse,
+                types::MerkleRootsResponse,
+                types::LatestMerkleRootsResponse,
+                DepositStatusEvent,
+            )),
+            modifiers(&AdminTokenScheme),
+            tags(
+                (name = "health", description = "Liveness check and paused services"),
+                (name = "deposits", description = "L1 to L2 deposits"),
+                (name = "withdrawals", description = "L2 to L1 withdrawals"),
+                (name = "hashing", description = "Commitment hash helpers"),
+                (name = "tree", description = "L1 deposit Merkle tree"),
+                (name = "proofs", description = "Artifacts of generated deposit proofs"),
+                (name = "stats", description = "Aggregate bridge metrics"),
+                (name = "admin", description = "Operator endpoints guarded by the admin multisig"),
+            )
+        )]
+        pub struct ApiDoc;
+
+        /// Names of the handlers registered in the paths of [`ApiDoc`]
+        pub const DOCUMENTED_HANDLERS: &[&str] = &[$(stringify!($handler)),*];
+    };
+}
+
+api_doc!(
+    handlers::hello_world,
+    handlers::get_health,
+    handlers::handle_deposit_post,
+    handlers::handle_get_pending_deposits,
+    handlers::fetch_user_deposits_handler,
+    handlers::fetch_user_latest_deposit_handler,
+    handlers::get_deposit_by_hash,
+    handlers::get_deposit_stats_handler,
+    handlers::get_bridge_stats_handler,
+    handlers::get_relay_costs_handler,
+    handlers::cancel_deposit,
+    handlers::admin_cancel_deposit,
+    handlers::refresh_deposit,
+    handlers::get_deposit_timings,
+    handlers::get_block_trackers,
+    handlers::set_block_tracker,
+    handlers::rebuild_tree_handler,
+    handlers::fill_nonce_gap,
+    handlers::pause_service,
+    handlers::resume_service,
+    handlers::reload_config,
+    handlers::create_webhook,
+    handlers::get_webhooks,
+    handlers::remove_webhook,
+    handlers::get_webhook_deliveries,
+    handlers::create_withdrawal,
+    handlers::get_pending_withdrawals,
+    handlers::get_all_withdrawals,
+    handlers::get_latest_withdrawal,
+    handlers::get_withdrawal_status,
+    handlers::get_withdrawal_status_by_hash,
+    handlers::compute_poseidon_hash,
+    handlers::compute_hash_handler,
+    handlers::get_next_nonce,
+    handlers::get_tree_root,
+    handlers::get_tree_proof,
+    handlers::get_merkle_roots,
+    handlers::get_latest_merkle_roots,
+    artifacts::download_proof_artifact,
+    ws::deposit_status_ws,
+    sse::deposit_status_sse,
+);
+
+/// Whether `handler` is registered in the paths of [`ApiDoc`]. A `const fn`, so routes can
+/// assert it at compile time.
+pub const fn is_documented(handler: &str) -> bool {
+    let mut i = 0;
+    while i < DOCUMENTED_HANDLERS.len() {
+        if str_eq(DOCUMENTED_HANDLERS[i], handler) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Registers the bearer token and the multisig guarding the admin endpoints
+struct AdminTokenScheme;
+
+impl Modify for AdminTokenScheme {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "admin_token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "admin_multisig",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-Admin-Sig-1",
+                "Signatures of the request hash by admin addresses in X-Admin-Sig-1, \
+                 X-Admin-Sig-2 and X-Admin-Sig-3, at least two of which must be valid",
+            ))),
+        );
+    }
+}
+
+// utoipa-swagger-ui only supports axum 0.7, so the UI is loaded from the swagger-ui-dist bundle
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>ZeroXBridge Sequencer API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
+
+/// Serves the OpenAPI document
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Serves Swagger UI pointed at [`openapi_json`]
+pub async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI_HTML)
+}
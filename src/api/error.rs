@@ -0,0 +1,184 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use utoipa::ToSchema;
+
+use crate::config::{AmountLimitError, ConfigReloadError};
+use crate::http::request_id::current_request_id;
+use crate::utils::{AddressError, BurnDataError, SignatureError};
+
+/// Error returned by API handlers.
+///
+/// Rendered as `{ "error": { "code": "...", "message": "...", "request_id": "..." } }`.
+/// `code` is a stable identifier clients can match on; `message` is meant for humans
+/// and may change. Some errors add machine-readable `details`.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiErrorBody {
+    pub error: ApiErrorDetails,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiErrorDetails {
+    pub code: String,
+    pub message: String,
+    pub request_id: Option<String>,
+    /// Values clients need to recover from the error, such as the server time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Attaches `details` to the error body
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, message)
+    }
+
+    pub fn not_found(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, code, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", message)
+    }
+
+    pub fn invalid_stark_key(message: impl Into<String>) -> Self {
+        Self::bad_request("invalid_stark_key", message)
+    }
+
+    pub fn invalid_commitment_hash(message: impl Into<String>) -> Self {
+        Self::bad_request("invalid_commitment_hash", message)
+    }
+
+    /// Maps errors from inserting a deposit or withdrawal, reporting a clash on the
+    /// unique commitment hash index as `duplicate_commitment`.
+    pub fn from_insert_error(err: sqlx::Error) -> Self {
+        match &err {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => Self::new(
+                StatusCode::CONFLICT,
+                "duplicate_commitment",
+                "A record with this commitment hash already exists",
+            ),
+            _ => Self::from(err),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => Self::not_found("not_found", "Resource not found"),
+            sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+                error!("Database unavailable: {}", err);
+                Self::new(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "db_unavailable",
+                    "Database is unavailable",
+                )
+            }
+            err => {
+                error!("Database error: {}", err);
+                Self::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "db_error",
+                    "Database query failed",
+                )
+            }
+        }
+    }
+}
+
+impl From<AddressError> for ApiError {
+    fn from(err: AddressError) -> Self {
+        Self::invalid_stark_key(err.to_string())
+    }
+}
+
+impl From<BurnDataError> for ApiError {
+    fn from(err: BurnDataError) -> Self {
+        match err {
+            BurnDataError::InvalidCallerHex(_)
+            | BurnDataError::InvalidCallerLength(_)
+            | BurnDataError::CallerNotFieldElement => Self::invalid_stark_key(err.to_string()),
+            BurnDataError::ZeroAmount | BurnDataError::ZeroTimestamp => {
+                Self::bad_request("invalid_burn_data", err.to_string())
+            }
+        }
+    }
+}
+
+impl From<SignatureError> for ApiError {
+    fn from(err: SignatureError) -> Self {
+        Self::new(
+            StatusCode::UNAUTHORIZED,
+            "invalid_signature",
+            err.to_string(),
+        )
+    }
+}
+
+impl From<AmountLimitError> for ApiError {
+    fn from(err: AmountLimitError) -> Self {
+        let code = match err {
+            AmountLimitError::BelowMinimum { .. } => "amount_below_minimum",
+            AmountLimitError::AboveMaximum { .. } => "amount_above_maximum",
+        };
+        Self::bad_request(code, err.to_string())
+    }
+}
+
+impl From<ConfigReloadError> for ApiError {
+    fn from(err: ConfigReloadError) -> Self {
+        match &err {
+            ConfigReloadError::Read { .. } => {
+                Self::bad_request("config_unreadable", err.to_string())
+            }
+            ConfigReloadError::Invalid(errors) => {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                Self::bad_request("invalid_config", "The reloaded configuration is invalid")
+                    .with_details(serde_json::json!({ "errors": errors }))
+            }
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ApiErrorBody {
+            error: ApiErrorDetails {
+                code: self.code.to_string(),
+                message: self.message,
+                request_id: current_request_id(),
+                details: self.details,
+            },
+        };
+
+        (self.status, Json(body)).into_response()
+    }
+}
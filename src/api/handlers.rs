@@ -1,94 +1,99 @@
-use axum::{extract::Query, http::StatusCode, response::IntoResponse, Extension, Json};
-use chrono::Utc;
+use axum::{
+    extract::{Path, Query},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use sqlx::{PgPool, Postgres, Transaction};
-use crate::utils::{compute_poseidon_commitment_hash, BurnData, HashMethod};
+use sqlx::{types::BigDecimal, PgPool, Postgres, Transaction};
+use utoipa::IntoParams;
+use crate::api::auth::AdminToken;
+use crate::api::error::ApiError;
+use crate::api::routes::{
+    HashFreshness, L1Verification, Networks, TreeState, WithdrawalDomain, WithdrawalSignatures,
+};
+use crate::api::stats::{BridgeStatsCache, DepositStatsCache};
+use crate::api::types::{
+    CancelDepositResponse, ConfigReloadResponse, CreateWebhookRequest, CreateWithdrawalRequest,
+    DepositRequest,
+    DepositResponse, FillNonceGapRequest, FillNonceGapResponse, HashRequest, HashResponse,
+    HealthResponse, InputData, LatestMerkleRootsResponse,
+    MerkleRootsResponse, NonceResponse, NonceType, PoseidonHashRequest, PoseidonHashResponse,
+    RefreshDepositResponse, ServiceControlRequest, ServiceHealth, SetBlockTrackerRequest,
+    TimelineEntry, TreeProofResponse,
+    TreeRebuildRequest, TreeRootResponse, WebhookResponse, WithdrawalStatusResponse,
+    WithrawalResponse,
+};
+use crate::config::{LimitsConfig, LiveConfig, NetworkId};
+use crate::events::BLOCK_TRACKER_KEYS;
+use crate::tree_builder::{
+    rebuild_tree, TreeBuilderClientError, TreeRebuildOptions, TreeRebuildReport,
+};
+use crate::utils::{
+    compute_poseidon_commitment_hash_hex, is_valid_felt252, parse_commitment_hash,
+    validate_starknet_address, verify_stark_signature, BurnData, CommitmentParseError, HashMethod,
+    STARK_PRIME_HEX,
+};
 use crate::db::database::{
-    fetch_all_withdrawals_by_user, fetch_latest_withdrawal_by_user, fetch_pending_deposits, get_user_deposits, Withdrawal, insert_deposit_with_l2_hash, Deposit,
-    fetch_pending_withdrawals, insert_deposit, insert_withdrawal, Deposit, Withdrawal, get_or_create_nonce, get_user_latest_deposit, 
+    delete_webhook, fetch_all_withdrawals_by_user, fetch_block_trackers,
+    fetch_deposit_by_commitment_hash, fetch_deposit_hash_block, fetch_deposit_status_by_id,
+    fetch_deposit_timings, fetch_latest_merkle_roots, fetch_service_controls,
+    fetch_latest_withdrawal_by_user, fetch_latest_withdrawal_proof, fetch_next_deposit_nonce,
+    fetch_next_withdrawal_nonce, fetch_pending_deposits, fetch_pending_withdrawals,
+    fetch_webhook_deliveries, fetch_webhooks, fetch_withdrawal_by_commitment_hash,
+    fetch_withdrawal_by_id, fetch_withdrawal_events, fill_withdrawal_nonce_gap,
+    get_avg_proof_generation_time,
+    get_bridge_stats, get_deposit_stats, get_or_create_nonce, get_relay_costs_summary, get_root_at,
+    get_user_deposits, get_user_latest_deposit, insert_deposit_with_l2_hash, insert_webhook,
+    ping_db, refresh_expired_deposit, set_service_paused, soft_delete_deposit,
+    update_last_processed_block, BlockTracker,
+    BridgeStats, Deposit, DepositCancellation, DepositRefresh, DepositStats, DepositTiming,
+    MerkleRoot,
+    RelayCostSummary, RootSource, ServiceControl, WebhookDelivery, Withdrawal, WithdrawalProof,
+    DEPOSIT_STATUS_AWAITING_L1_CONFIRMATION,
 };
+use crate::db::service_controls::PipelineService;
+use crate::webhooks::WEBHOOK_EVENT_TYPES;
 
 use starknet::core::types::Felt;
+use tracing::{info, warn};
 
-// UPDATED: Added l1_token field
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateWithdrawalRequest {
-    pub stark_pub_key: String,
-    pub amount: i64,
-    pub commitment_hash: String,
-    pub l1_token: String, // ADDED: New required field
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NetworkQuery {
+    /// Configured network to use, the first configured one when omitted. Unknown names
+    /// are rejected with `unknown_network`
+    pub network: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DepositRequest {
-    pub stark_pub_key: String,
-    pub amount: i64,
-    pub commitment_hash: String,
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CancelDepositQuery {
+    pub reason: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct DepositResponse {
-    pub deposit_id: i32,
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WithdrawalQuery {
+    pub user_address: Option<String>,
+    pub stark_pub_key: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct WithrawalResponse {
-    pub withdrawal_id: i32,
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NonceQuery {
+    /// Whether to return the next deposit or withdrawal nonce
+    #[serde(rename = "type")]
+    #[param(inline)]
+    pub nonce_type: NonceType,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct PoseidonHashRequest {
-    /// Starknet address of the recipient
-    pub recipient: String,
-    /// USD amount to mint
-    pub amount: u128,
-    /// Transaction nonce
-    pub nonce: u64,
-    /// Block timestamp
-    pub timestamp: u64,
-    /// Optional hash method to use: "batch" or "sequential" (default: "BatchHash")
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WithdrawalStatusQuery {
     #[serde(default)]
-    pub hash_method: Option<String>,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct PoseidonHashResponse {
-    pub commitment_hash: String,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct HashRequest {
-    pub stark_pubkey: String,
-    pub usd_val: u64,
-    pub nonce: u64,
-    pub timestamp: u64,
-}
-
-#[derive(Serialize, Debug)]
-pub struct HashResponse {
-    pub commitment_hash: String,
-    pub input_data: InputData,
-}
-
-#[derive(Serialize, Debug)]
-pub struct InputData {
-    pub stark_pubkey: String,
-    pub usd_val: u64,
-    pub nonce: u64,
-    pub timestamp: u64,
-}
-
-#[derive(Serialize, Debug)]
-pub struct ErrorResponse {
-    pub error: String,
-    pub details: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct WithdrawalQuery {
-    pub user_address: Option<String>,
-    pub stark_pub_key: Option<String>,
+    pub include_proof: bool,
 }
 
 pub enum WithdrawalFetchMode {
@@ -96,112 +101,821 @@ pub enum WithdrawalFetchMode {
     All,
 }
 
+#[utoipa::path(
+    post,
+    path = "/deposit",
+    tag = "deposits",
+    params(NetworkQuery),
+    request_body = DepositRequest,
+    responses(
+        (status = 200, description = "Deposit recorded", body = DepositResponse),
+        (
+            status = 202,
+            description = "Deposit held until its commitment is seen on L1",
+            body = DepositResponse
+        ),
+        (
+            status = 400,
+            description = "Invalid amount, Starknet key, commitment or network, or amount out of limits",
+            body = ApiErrorBody
+        ),
+        (status = 409, description = "Commitment hash already used", body = ApiErrorBody),
+    )
+)]
 pub async fn handle_deposit_post(
     Extension(pool): Extension<PgPool>,
+    Extension(l1_verification): Extension<L1Verification>,
+    Extension(limits): Extension<LimitsConfig>,
+    Extension(networks): Extension<Networks>,
+    Query(query): Query<NetworkQuery>,
     Json(payload): Json<DepositRequest>,
-) -> Result<Json<DepositResponse>, (StatusCode, String)> {
+) -> Result<(StatusCode, Json<DepositResponse>), ApiError> {
+    let network = networks.resolve(query.network.as_deref())?;
     if payload.amount <= 0 || payload.stark_pub_key.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Invalid input".to_string()));
+        return Err(ApiError::bad_request("invalid_input", "Invalid input"));
+    }
+    validate_commitment_hash(&payload.commitment_hash)?;
+    // The commitment becomes a leaf of the L2 tree, where it must fit in a felt
+    if !is_valid_felt252(&payload.commitment_hash) {
+        return Err(ApiError::invalid_commitment_hash(format!(
+            "Commitment hash must be below the Stark prime {}",
+            STARK_PRIME_HEX
+        )));
     }
+    let amount = BigDecimal::from(payload.amount);
+    limits.check_deposit(&amount)?;
 
     // Parse recipient address as Felt (felt252)
-    let recipient_felt = match Felt::from_hex(&payload.stark_pub_key) {
-        Ok(felt) => felt,
-        Err(_) => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                "Invalid Starknet address format. Must be a valid hex format (0x...).".to_string(),
-            ))
-        }
+    let recipient_felt = validate_starknet_address(&payload.stark_pub_key)?;
+
+    // Without verification, the L1 queue matches the commitment with L1 after the fact
+    let status = if l1_verification.0
+        && !commitment_seen_on_l1(&pool, &network, &payload.commitment_hash).await?
+    {
+        DEPOSIT_STATUS_AWAITING_L1_CONFIRMATION
+    } else {
+        "pending"
     };
 
-    let mut tx: Transaction<'_, Postgres> = pool
-        .begin()
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
 
-    let nonce = get_or_create_nonce(&mut tx, &payload.stark_pub_key)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let nonce = get_or_create_nonce(&mut tx, &payload.stark_pub_key).await?;
 
     let timestamp = Utc::now().timestamp() as u64;
 
-    let l2_hash = compute_poseidon_commitment_hash(
+    let l2_hash_hex = compute_poseidon_commitment_hash_hex(
         recipient_felt,
         payload.amount as u128,
         nonce as u64,
         timestamp,
         HashMethod::BatchHash,
     );
-    let l2_hash_hex = format!("0x{:x}", l2_hash);
 
     let deposit_id = insert_deposit_with_l2_hash(
         &mut tx,
+        &network,
         &payload.stark_pub_key,
-        payload.amount,
+        &amount,
         &payload.commitment_hash,
         &l2_hash_hex,
         nonce,
+        status,
     )
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(ApiError::from_insert_error)?;
 
-    tx.commit()
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    Ok(Json(DepositResponse { deposit_id }))
+    tx.commit().await?;
+    let estimated_proof_time_secs = get_avg_proof_generation_time(&pool).await?;
+
+    let code = if status == DEPOSIT_STATUS_AWAITING_L1_CONFIRMATION {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::OK
+    };
+    Ok((
+        code,
+        Json(DepositResponse {
+            deposit_id,
+            status: status.to_string(),
+            estimated_proof_time_secs,
+        }),
+    ))
+}
+
+/// Rejects commitment hashes that are not `0x` followed by 64 hex digits, which would
+/// otherwise be stored as is and only fail once the tree builder decodes them
+fn validate_commitment_hash(commitment_hash: &str) -> Result<(), ApiError> {
+    if !commitment_hash.starts_with("0x") {
+        return Err(ApiError::invalid_commitment_hash(
+            "Commitment hash must start with 0x",
+        ));
+    }
+    match parse_commitment_hash(commitment_hash) {
+        Ok(_) => Ok(()),
+        Err(CommitmentParseError::InvalidHex(_)) => Err(ApiError::invalid_commitment_hash(
+            "Commitment hash must only contain hex digits after 0x",
+        )),
+        Err(_) => Err(ApiError::invalid_commitment_hash(format!(
+            "Commitment hash must be 66 characters long (0x and 64 hex digits), got {}",
+            commitment_hash.len()
+        ))),
+    }
 }
 
+/// Whether a `DepositHashAppended` event for `commitment_hash` has been ingested on
+/// `network`
+async fn commitment_seen_on_l1(
+    pool: &PgPool,
+    network: &NetworkId,
+    commitment_hash: &str,
+) -> Result<bool, ApiError> {
+    let commitment = parse_commitment_hash(commitment_hash).map_err(|_| {
+        ApiError::bad_request(
+            "invalid_commitment",
+            "Commitment hash must be a 32-byte hex string",
+        )
+    })?;
+
+    Ok(fetch_deposit_hash_block(pool, network, &commitment)
+        .await?
+        .is_some())
+}
+
+#[utoipa::path(
+    get,
+    path = "/deposit",
+    tag = "deposits",
+    params(NetworkQuery),
+    responses(
+        (status = 200, description = "Pending deposits", body = [Deposit]),
+        (status = 400, description = "Unknown network", body = ApiErrorBody),
+    )
+)]
 pub async fn handle_get_pending_deposits(
     Extension(pool): Extension<PgPool>,
-) -> Result<Json<Vec<Deposit>>, (StatusCode, String)> {
-    let deposit = fetch_pending_deposits(&pool, 5)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Extension(networks): Extension<Networks>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<Vec<Deposit>>, ApiError> {
+    let network = networks.resolve(query.network.as_deref())?;
+    let deposit = fetch_pending_deposits(&pool, &network, 5).await?;
 
     Ok(Json(deposit))
 }
 
+/// Cancels a deposit that has not yet been included in the Merkle tree.
+///
+/// Admin only: requires `Authorization: Bearer <admin_token>`.
+#[utoipa::path(
+    delete,
+    path = "/deposits/{id}",
+    tag = "deposits",
+    params(("id" = i32, Path, description = "Deposit id"), CancelDepositQuery),
+    responses(
+        (status = 200, description = "Deposit cancelled", body = CancelDepositResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ApiErrorBody),
+        (status = 404, description = "Deposit not found", body = ApiErrorBody),
+        (status = 409, description = "Deposit can no longer be cancelled", body = ApiErrorBody),
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn cancel_deposit(
+    Extension(pool): Extension<PgPool>,
+    Extension(admin_token): Extension<AdminToken>,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    Query(query): Query<CancelDepositQuery>,
+) -> Result<Json<CancelDepositResponse>, ApiError> {
+    admin_token.authorize(&headers)?;
+
+    Ok(Json(
+        cancel_deposit_with_reason(&pool, id, query.reason).await?,
+    ))
+}
+
+/// Cancels a deposit that has not yet been included in the Merkle tree.
+///
+/// Admin only: requires 2 of the 3 `X-Admin-Sig-{1,2,3}` headers to hold signatures by
+/// configured admin addresses.
+#[utoipa::path(
+    delete,
+    path = "/admin/deposits/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Deposit id"), CancelDepositQuery),
+    responses(
+        (status = 200, description = "Deposit cancelled", body = CancelDepositResponse),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+        (status = 404, description = "Deposit not found", body = ApiErrorBody),
+        (status = 409, description = "Deposit can no longer be cancelled", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn admin_cancel_deposit(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<i32>,
+    Query(query): Query<CancelDepositQuery>,
+) -> Result<Json<CancelDepositResponse>, ApiError> {
+    Ok(Json(
+        cancel_deposit_with_reason(&pool, id, query.reason).await?,
+    ))
+}
+
+async fn cancel_deposit_with_reason(
+    pool: &PgPool,
+    id: i32,
+    reason: Option<String>,
+) -> Result<CancelDepositResponse, ApiError> {
+    let reason = reason
+        .filter(|reason| !reason.trim().is_empty())
+        .unwrap_or_else(|| "cancelled by operator".to_string());
+
+    match soft_delete_deposit(pool, id, &reason).await? {
+        DepositCancellation::Cancelled(entry) => Ok(CancelDepositResponse {
+            deposit_id: entry.deposit_id,
+            old_status: entry.old_status,
+            status: entry.new_status,
+        }),
+        DepositCancellation::NotFound => Err(ApiError::not_found(
+            "deposit_not_found",
+            format!("Deposit {} not found", id),
+        )),
+        DepositCancellation::InvalidStatus(status) => Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "deposit_not_cancellable",
+            format!("Deposit {} cannot be cancelled in status '{}'", id, status),
+        )),
+    }
+}
+
+/// Puts an expired deposit back in the queue, for users who sent their L1 transaction
+/// after the deposit expired. The L1 queue then looks for its commitment on L1 again.
+#[utoipa::path(
+    post,
+    path = "/deposits/{id}/refresh",
+    tag = "deposits",
+    params(("id" = i32, Path, description = "Deposit id")),
+    responses(
+        (status = 200, description = "Deposit pending again", body = RefreshDepositResponse),
+        (status = 404, description = "Deposit not found", body = ApiErrorBody),
+        (status = 409, description = "Deposit is not expired", body = ApiErrorBody),
+    )
+)]
+pub async fn refresh_deposit(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<i32>,
+) -> Result<Json<RefreshDepositResponse>, ApiError> {
+    match refresh_expired_deposit(&pool, id, "refreshed by user").await? {
+        DepositRefresh::Refreshed(entry) => Ok(Json(RefreshDepositResponse {
+            deposit_id: entry.deposit_id,
+            old_status: entry.old_status,
+            status: entry.new_status,
+        })),
+        DepositRefresh::NotFound => Err(ApiError::not_found(
+            "deposit_not_found",
+            format!("Deposit {} not found", id),
+        )),
+        DepositRefresh::InvalidStatus(status) => Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "deposit_not_expired",
+            format!("Deposit {} cannot be refreshed in status '{}'", id, status),
+        )),
+    }
+}
+
+/// Lists how long each stage of a deposit took, in the order the stages started.
+///
+/// A stage that ran more than once, e.g. a proof retried after a failure, is listed
+/// once per run; failed runs have `success` false and the reason in `error`.
+#[utoipa::path(
+    get,
+    path = "/deposits/{id}/timings",
+    tag = "deposits",
+    params(("id" = i32, Path, description = "Deposit id")),
+    responses(
+        (status = 200, description = "Timings of the deposit's stages", body = [DepositTiming]),
+        (status = 404, description = "Deposit not found", body = ApiErrorBody),
+    )
+)]
+pub async fn get_deposit_timings(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<DepositTiming>>, ApiError> {
+    if fetch_deposit_status_by_id(&pool, id).await?.is_none() {
+        return Err(ApiError::not_found(
+            "deposit_not_found",
+            format!("Deposit {} not found", id),
+        ));
+    }
+    Ok(Json(fetch_deposit_timings(&pool, id).await?))
+}
+
+/// Lists the cursors of a network's event watchers.
+///
+/// Admin only: requires 2 of the 3 `X-Admin-Sig-{1,2,3}` headers to hold signatures by
+/// configured admin addresses.
+#[utoipa::path(
+    get,
+    path = "/admin/block-tracker",
+    tag = "admin",
+    params(NetworkQuery),
+    responses(
+        (status = 200, description = "Current tracker values", body = [BlockTracker]),
+        (status = 400, description = "Unknown network", body = ApiErrorBody),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn get_block_trackers(
+    Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<Vec<BlockTracker>>, ApiError> {
+    let network = networks.resolve(query.network.as_deref())?;
+    Ok(Json(fetch_block_trackers(&pool, &network).await?))
+}
+
+/// Moves an event watcher's cursor, e.g. to replay a block range after a re-org.
+///
+/// The watcher resumes from the block after `block_number` on its next poll. Admin
+/// only, like [`get_block_trackers`].
+#[utoipa::path(
+    put,
+    path = "/admin/block-tracker",
+    tag = "admin",
+    params(NetworkQuery),
+    request_body = SetBlockTrackerRequest,
+    responses(
+        (status = 200, description = "Tracker values after the update", body = [BlockTracker]),
+        (status = 400, description = "Unknown tracker key or network", body = ApiErrorBody),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn set_block_tracker(
+    Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
+    Query(query): Query<NetworkQuery>,
+    Json(payload): Json<SetBlockTrackerRequest>,
+) -> Result<Json<Vec<BlockTracker>>, ApiError> {
+    let network = networks.resolve(query.network.as_deref())?;
+    if !BLOCK_TRACKER_KEYS.contains(&payload.key.as_str()) {
+        return Err(ApiError::bad_request(
+            "unknown_block_tracker",
+            format!(
+                "Unknown block tracker '{}'. Valid keys are: {}",
+                payload.key,
+                BLOCK_TRACKER_KEYS.join(", ")
+            ),
+        ));
+    }
+    if payload.block_number > i64::MAX as u64 {
+        return Err(ApiError::bad_request(
+            "invalid_block_number",
+            "Block number is too large",
+        ));
+    }
+
+    update_last_processed_block(&pool, &network, &payload.key, payload.block_number).await?;
+    warn!(
+        "Block tracker {} of network {} manually set to {}",
+        payload.key, network, payload.block_number
+    );
+
+    Ok(Json(fetch_block_trackers(&pool, &network).await?))
+}
+
+/// Rebuilds a network's L1 tree from its included deposits and reports every stored
+/// leaf index or root that disagrees with it, repairing them when `fix` is set.
+///
+/// Progress is checkpointed after every batch, so a rebuild cut short by the request
+/// timeout picks up where it stopped when the request is sent again. Admin only, like
+/// [`get_block_trackers`].
+#[utoipa::path(
+    post,
+    path = "/admin/tree/rebuild",
+    tag = "admin",
+    params(NetworkQuery),
+    request_body = TreeRebuildRequest,
+    responses(
+        (status = 200, description = "Discrepancies found, and repaired with `fix`", body = TreeRebuildReport),
+        (status = 400, description = "Invalid batch size or unknown network", body = ApiErrorBody),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn rebuild_tree_handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
+    Query(query): Query<NetworkQuery>,
+    Json(payload): Json<TreeRebuildRequest>,
+) -> Result<Json<TreeRebuildReport>, ApiError> {
+    let network = networks.resolve(query.network.as_deref())?;
+    let mut options = TreeRebuildOptions {
+        fix: payload.fix,
+        ..TreeRebuildOptions::default()
+    };
+    if let Some(batch_size) = payload.batch_size {
+        if batch_size <= 0 {
+            return Err(ApiError::bad_request(
+                "invalid_batch_size",
+                "Batch size must be positive",
+            ));
+        }
+        options.batch_size = batch_size;
+    }
+
+    let report = rebuild_tree(&pool, &network, options)
+        .await
+        .map_err(|e| match e {
+            TreeBuilderClientError::Database(e) => ApiError::from(e),
+            e => tree_error(e),
+        })?;
+    if payload.fix && !report.discrepancies.is_empty() {
+        warn!(
+            "Repaired {} tree discrepancies of network {}",
+            report.discrepancies.len(),
+            network
+        );
+    }
+
+    Ok(Json(report))
+}
+
+/// Takes up the lowest withdrawal nonce of a key that no withdrawal uses with a
+/// tombstone withdrawal of nothing, so the key's later withdrawals are accepted by L1.
+///
+/// Gaps are left by failed or cancelled withdrawals and reported by the janitor. Admin
+/// only, like [`get_block_trackers`].
+#[utoipa::path(
+    post,
+    path = "/admin/withdrawals/fill-nonce-gap",
+    tag = "admin",
+    params(NetworkQuery),
+    request_body = FillNonceGapRequest,
+    responses(
+        (status = 200, description = "Tombstone withdrawal taking up the nonce", body = FillNonceGapResponse),
+        (status = 400, description = "Unknown network", body = ApiErrorBody),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+        (status = 409, description = "The key has no unused nonce", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn fill_nonce_gap(
+    Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
+    Query(query): Query<NetworkQuery>,
+    Json(payload): Json<FillNonceGapRequest>,
+) -> Result<Json<FillNonceGapResponse>, ApiError> {
+    let network = networks.resolve(query.network.as_deref())?;
+    let Some((withdrawal_id, nonce)) =
+        fill_withdrawal_nonce_gap(&pool, &network, &payload.stark_pub_key).await?
+    else {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "no_nonce_gap",
+            format!("{} has no unused withdrawal nonce", payload.stark_pub_key),
+        ));
+    };
+    warn!(
+        "Filled withdrawal nonce {} of {} with tombstone withdrawal {}",
+        nonce, payload.stark_pub_key, withdrawal_id
+    );
+
+    Ok(Json(FillNonceGapResponse {
+        withdrawal_id,
+        nonce,
+    }))
+}
+
+/// Pauses a service, e.g. the Starknet relayer during a bad L2 contract upgrade, without
+/// stopping the others.
+///
+/// The service skips its work from its next loop iteration, at most
+/// [`PAUSE_CACHE_TTL`](crate::db::service_controls::PAUSE_CACHE_TTL) later. Admin only,
+/// like [`get_block_trackers`].
+#[utoipa::path(
+    post,
+    path = "/admin/services/{name}/pause",
+    tag = "admin",
+    params(("name" = String, Path, description = "Service name, e.g. `starknet_relayer`")),
+    request_body = ServiceControlRequest,
+    responses(
+        (status = 200, description = "Paused service", body = ServiceControl),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+        (status = 404, description = "Unknown service", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn pause_service(
+    Extension(pool): Extension<PgPool>,
+    Path(name): Path<String>,
+    Json(payload): Json<ServiceControlRequest>,
+) -> Result<Json<ServiceControl>, ApiError> {
+    let control = set_service_pause(&pool, &name, true, &payload).await?;
+    warn!("Service {} paused by {}", name, payload.updated_by);
+
+    Ok(Json(control))
+}
+
+/// Resumes a service paused by [`pause_service`]. Admin only, like
+/// [`get_block_trackers`].
+#[utoipa::path(
+    post,
+    path = "/admin/services/{name}/resume",
+    tag = "admin",
+    params(("name" = String, Path, description = "Service name, e.g. `starknet_relayer`")),
+    request_body = ServiceControlRequest,
+    responses(
+        (status = 200, description = "Resumed service", body = ServiceControl),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+        (status = 404, description = "Unknown service", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn resume_service(
+    Extension(pool): Extension<PgPool>,
+    Path(name): Path<String>,
+    Json(payload): Json<ServiceControlRequest>,
+) -> Result<Json<ServiceControl>, ApiError> {
+    let control = set_service_pause(&pool, &name, false, &payload).await?;
+    info!("Service {} resumed by {}", name, payload.updated_by);
+
+    Ok(Json(control))
+}
+
+async fn set_service_pause(
+    pool: &PgPool,
+    name: &str,
+    paused: bool,
+    payload: &ServiceControlRequest,
+) -> Result<ServiceControl, ApiError> {
+    let service = PipelineService::from_name(name).ok_or_else(|| {
+        let known: Vec<&str> = PipelineService::ALL
+            .iter()
+            .map(PipelineService::as_str)
+            .collect();
+        ApiError::not_found(
+            "unknown_service",
+            format!(
+                "Unknown service '{}'. Services are: {}",
+                name,
+                known.join(", ")
+            ),
+        )
+    })?;
+    if payload.updated_by.trim().is_empty() {
+        return Err(ApiError::bad_request(
+            "missing_updated_by",
+            "updated_by must name who changes the service",
+        ));
+    }
+
+    Ok(set_service_paused(
+        pool,
+        service.as_str(),
+        paused,
+        payload.reason.as_deref(),
+        &payload.updated_by,
+    )
+    .await?)
+}
+
+/// Reloads the configuration file and swaps it in if it validates, leaving the running
+/// configuration untouched otherwise.
+///
+/// Services of this process reading the live configuration pick the change up on their
+/// next cycle, settings only read at startup still need a restart. A sequencer running
+/// in another process is reloaded with `SIGHUP` instead. Admin only, like
+/// [`get_block_trackers`].
+#[utoipa::path(
+    post,
+    path = "/admin/config/reload",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Reloaded configuration", body = ConfigReloadResponse),
+        (status = 400, description = "Unreadable or invalid configuration", body = ApiErrorBody),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+        (status = 404, description = "This server has no configuration to reload", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn reload_config(
+    live_config: Option<Extension<LiveConfig>>,
+) -> Result<Json<ConfigReloadResponse>, ApiError> {
+    let Some(Extension(live_config)) = live_config else {
+        return Err(ApiError::not_found(
+            "config_reload_unavailable",
+            "This server was not started from a configuration file",
+        ));
+    };
+
+    let changed = live_config.reload().map_err(|e| {
+        warn!("Configuration reload rejected: {}", e);
+        ApiError::from(e)
+    })?;
+    info!("Configuration reloaded, changed sections: {:?}", changed);
+
+    Ok(Json(ConfigReloadResponse { changed }))
+}
+
+/// Minimum length of a webhook's signing secret
+const MIN_WEBHOOK_SECRET_LEN: usize = 16;
+
+/// Deliveries listed by [`get_webhook_deliveries`]
+const WEBHOOK_DELIVERIES_LIMIT: i64 = 100;
+
+/// Registers a webhook the status changes of `event_types` are POSTed to.
+///
+/// Each delivery's body is signed with the webhook's secret in `X-Signature`, and its
+/// id is sent in `X-Webhook-Delivery`. Deliveries are at least once, so receivers should
+/// drop ids they already processed. Admin only, like [`get_block_trackers`].
+#[utoipa::path(
+    post,
+    path = "/admin/webhooks",
+    tag = "admin",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Registered webhook", body = WebhookResponse),
+        (status = 400, description = "Invalid URL, secret or event type", body = ApiErrorBody),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn create_webhook(
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<Json<WebhookResponse>, ApiError> {
+    let url_is_valid = url::Url::parse(&payload.url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host());
+    if !url_is_valid {
+        return Err(ApiError::bad_request(
+            "invalid_webhook_url",
+            "Webhook URL must be an absolute http or https URL",
+        ));
+    }
+    if payload.secret.len() < MIN_WEBHOOK_SECRET_LEN {
+        return Err(ApiError::bad_request(
+            "invalid_webhook_secret",
+            format!(
+                "Webhook secret must be at least {} characters long",
+                MIN_WEBHOOK_SECRET_LEN
+            ),
+        ));
+    }
+    if payload.event_types.is_empty() {
+        return Err(ApiError::bad_request(
+            "invalid_event_type",
+            "At least one event type is required",
+        ));
+    }
+    if let Some(event_type) = payload
+        .event_types
+        .iter()
+        .find(|event_type| !WEBHOOK_EVENT_TYPES.contains(&event_type.as_str()))
+    {
+        return Err(ApiError::bad_request(
+            "invalid_event_type",
+            format!(
+                "Unknown event type '{}'. Valid event types are: {}",
+                event_type,
+                WEBHOOK_EVENT_TYPES.join(", ")
+            ),
+        ));
+    }
+
+    let webhook =
+        insert_webhook(&pool, &payload.url, &payload.secret, &payload.event_types).await?;
+    info!(
+        "Webhook {} registered for {:?} at {}",
+        webhook.id, webhook.event_types, webhook.url
+    );
+
+    Ok(Json(webhook.into()))
+}
+
+/// Lists the registered webhooks, oldest first. Admin only, like [`get_block_trackers`].
+#[utoipa::path(
+    get,
+    path = "/admin/webhooks",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Registered webhooks", body = [WebhookResponse]),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn get_webhooks(
+    Extension(pool): Extension<PgPool>,
+) -> Result<Json<Vec<WebhookResponse>>, ApiError> {
+    let webhooks = fetch_webhooks(&pool).await?;
+    Ok(Json(webhooks.into_iter().map(Into::into).collect()))
+}
+
+/// Deletes a webhook along with its deliveries, including those not yet sent. Admin
+/// only, like [`get_block_trackers`].
+#[utoipa::path(
+    delete,
+    path = "/admin/webhooks/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "Deleted webhook", body = WebhookResponse),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+        (status = 404, description = "Webhook not found", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn remove_webhook(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<i32>,
+) -> Result<Json<WebhookResponse>, ApiError> {
+    let webhook = delete_webhook(&pool, id).await?.ok_or_else(|| {
+        ApiError::not_found("webhook_not_found", format!("Webhook {} not found", id))
+    })?;
+    info!("Webhook {} at {} deleted", webhook.id, webhook.url);
+
+    Ok(Json(webhook.into()))
+}
+
+/// Lists the latest 100 deliveries of a webhook, newest first, with the outcome of
+/// their last attempt. Admin only, like [`get_block_trackers`].
+#[utoipa::path(
+    get,
+    path = "/admin/webhooks/{id}/deliveries",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "Latest deliveries", body = [WebhookDelivery]),
+        (status = 401, description = "Not enough valid admin signatures", body = ApiErrorBody),
+    ),
+    security(("admin_multisig" = []))
+)]
+pub async fn get_webhook_deliveries(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<WebhookDelivery>>, ApiError> {
+    Ok(Json(
+        fetch_webhook_deliveries(&pool, id, WEBHOOK_DELIVERIES_LIMIT).await?,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/withdrawals",
+    tag = "withdrawals",
+    params(NetworkQuery),
+    request_body = CreateWithdrawalRequest,
+    responses(
+        (status = 200, description = "Withdrawal recorded", body = WithrawalResponse),
+        (
+            status = 400,
+            description = "Invalid amount, Starknet key, commitment or network, or amount out of limits",
+            body = ApiErrorBody
+        ),
+        (
+            status = 401,
+            description = "Signature missing, or not a low-s signature of the burn by `stark_pub_key`",
+            body = ApiErrorBody
+        ),
+        (status = 409, description = "Commitment hash already used", body = ApiErrorBody),
+    )
+)]
 pub async fn create_withdrawal(
     Extension(pool): Extension<PgPool>,
+    Extension(limits): Extension<LimitsConfig>,
+    Extension(networks): Extension<Networks>,
+    Extension(domain): Extension<WithdrawalDomain>,
+    Extension(signatures): Extension<WithdrawalSignatures>,
+    Query(query): Query<NetworkQuery>,
     Json(payload): Json<CreateWithdrawalRequest>,
-) -> Result<Json<WithrawalResponse>, (StatusCode, String)> {
+) -> Result<Json<WithrawalResponse>, ApiError> {
     use crate::db::database::{get_and_increment_withdrawal_nonce, insert_withdrawal_v2};
     use crate::utils::BurnData;
 
+    let network = networks.resolve(query.network.as_deref())?;
+
     // Validation logic
     if payload.amount <= 0
         || payload.stark_pub_key.trim().is_empty()
         || payload.l1_token.trim().is_empty()
     {
-        return Err((StatusCode::BAD_REQUEST, "Invalid input".to_string()));
+        return Err(ApiError::bad_request("invalid_input", "Invalid input"));
     }
+    validate_commitment_hash(&payload.commitment_hash)?;
+    let amount = BigDecimal::from(payload.amount);
+    limits.check_withdrawal(&amount)?;
 
-    // Just verify the Starknet address is a valid felt (do not store)
-    if Felt::from_hex(&payload.stark_pub_key).is_err() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Invalid Starknet address format. Must be a valid hex format (0x...).".to_string(),
-        ));
-    };
+    // Just verify the Starknet address is valid (do not store)
+    let stark_key = validate_starknet_address(&payload.stark_pub_key)?;
 
-    let mut tx: Transaction<'_, Postgres> = pool
-        .begin()
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
 
     // Fetch and increment the nonce for the user
-    let nonce = get_and_increment_withdrawal_nonce(&mut tx, &payload.stark_pub_key)
-        .await
-        .map_err(|err| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to get/increment nonce: {:?}", err),
-            )
-        })?;
+    let nonce = get_and_increment_withdrawal_nonce(&mut tx, &payload.stark_pub_key).await?;
 
     // Use current timestamp if not provided (for compatibility, but ideally should be provided)
-    let timestamp = chrono::Utc::now().timestamp() as u64;
+    let timestamp = payload
+        .timestamp
+        .unwrap_or_else(|| chrono::Utc::now().timestamp() as u64);
 
     // Construct BurnData and compute the hash
     let burn_data = BurnData {
@@ -211,61 +925,79 @@ pub async fn create_withdrawal(
         time_stamp: timestamp,
     };
 
-    if BurnData::hex_to_bytes32(&burn_data.caller).is_err() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Invalid stark_pubkey format".to_string(),
-        ));
+    burn_data.validate()?;
+    // Dropping the transaction on failure leaves the nonce unused
+    if signatures.0 {
+        let signature = payload.signature.as_ref().ok_or_else(|| {
+            ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "missing_signature",
+                "Withdrawals must be signed by their stark_pub_key",
+            )
+        })?;
+        verify_stark_signature(
+            &stark_key,
+            &burn_data.signing_hash(&domain.0),
+            &signature.r,
+            &signature.s,
+        )?;
     }
-    let l1_hash = burn_data.hash_to_hex_string();
+    // L1 verifies withdrawals as EIP-712 typed data under the bridge's domain
+    let l1_hash = format!("0x{}", hex::encode(burn_data.eip712_hash(&domain.0)));
 
     // Insert withdrawal with l1_hash and nonce
     let withdrawal_id = insert_withdrawal_v2(
         &mut tx,
+        &network,
         &payload.stark_pub_key,
-        payload.amount,
+        &amount,
         &payload.l1_token,
         &payload.commitment_hash,
         &l1_hash,
         nonce,
     )
     .await
-    .map_err(|err| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("DB Error: {:?}", err),
-        )
-    })?;
+    .map_err(ApiError::from_insert_error)?;
 
-    tx.commit()
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tx.commit().await?;
 
     Ok(Json(WithrawalResponse { withdrawal_id }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/withdrawals",
+    tag = "withdrawals",
+    params(NetworkQuery),
+    responses(
+        (status = 200, description = "Pending withdrawals", body = [Withdrawal]),
+        (status = 400, description = "Unknown network", body = ApiErrorBody),
+    )
+)]
 pub async fn get_pending_withdrawals(
     Extension(pool): Extension<PgPool>,
-) -> Result<Json<Vec<Withdrawal>>, (StatusCode, String)> {
-    match fetch_pending_withdrawals(&pool, 3).await {
-        Ok(withdrawals) => Ok(Json(withdrawals)),
-        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
-    }
+    Extension(networks): Extension<Networks>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<Vec<Withdrawal>>, ApiError> {
+    let network = networks.resolve(query.network.as_deref())?;
+    let withdrawals = fetch_pending_withdrawals(&pool, &network, 3).await?;
+    Ok(Json(withdrawals))
 }
 
 pub async fn fetch_withdrawals_by_identifier(
     pool: &PgPool,
+    network: &NetworkId,
     user_address: Option<String>,
     stark_pub_key: Option<String>,
     mode: WithdrawalFetchMode,
-) -> Result<Vec<Withdrawal>, (StatusCode, String)> {
+) -> Result<Vec<Withdrawal>, ApiError> {
     // one (not two of them at the same time) query parameter must be provided to the endpoint that calls this handler
     if (user_address.is_none() && stark_pub_key.is_none())
         || (user_address.is_some() && stark_pub_key.is_some())
     {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Exactly one of user_address or stark_pub_key must be provided".into(),
+        return Err(ApiError::bad_request(
+            "invalid_identifier",
+            "Exactly one of user_address or stark_pub_key must be provided",
         ));
     }
 
@@ -273,23 +1005,48 @@ pub async fn fetch_withdrawals_by_identifier(
 
     match mode {
         WithdrawalFetchMode::Latest => {
-            let w = fetch_latest_withdrawal_by_user(pool, &identifier)
+            let w = fetch_latest_withdrawal_by_user(pool, network, &identifier)
                 .await
-                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+                .map_err(|e| match e {
+                    sqlx::Error::RowNotFound => ApiError::not_found(
+                        "withdrawal_not_found",
+                        "No withdrawals found for the given user",
+                    ),
+                    e => ApiError::from(e),
+                })?;
             Ok(vec![w])
         }
-        WithdrawalFetchMode::All => fetch_all_withdrawals_by_user(pool, &identifier)
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        WithdrawalFetchMode::All => {
+            Ok(fetch_all_withdrawals_by_user(pool, network, &identifier).await?)
+        }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/withdrawals/latest",
+    tag = "withdrawals",
+    params(WithdrawalQuery, NetworkQuery),
+    responses(
+        (status = 200, description = "Latest withdrawal of the user", body = [Withdrawal]),
+        (
+            status = 400,
+            description = "Not exactly one identifier given, or unknown network",
+            body = ApiErrorBody
+        ),
+        (status = 404, description = "User has no withdrawals", body = ApiErrorBody),
+    )
+)]
 pub async fn get_latest_withdrawal(
     Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
     Query(query): Query<WithdrawalQuery>,
-) -> Result<Json<Vec<Withdrawal>>, (StatusCode, String)> {
+    Query(selected): Query<NetworkQuery>,
+) -> Result<Json<Vec<Withdrawal>>, ApiError> {
+    let network = networks.resolve(selected.network.as_deref())?;
     let withdrawals = fetch_withdrawals_by_identifier(
         &pool,
+        &network,
         query.user_address,
         query.stark_pub_key,
         WithdrawalFetchMode::Latest,
@@ -298,12 +1055,30 @@ pub async fn get_latest_withdrawal(
     Ok(Json(withdrawals))
 }
 
+#[utoipa::path(
+    get,
+    path = "/withdrawals/all",
+    tag = "withdrawals",
+    params(WithdrawalQuery, NetworkQuery),
+    responses(
+        (status = 200, description = "All withdrawals of the user", body = [Withdrawal]),
+        (
+            status = 400,
+            description = "Not exactly one identifier given, or unknown network",
+            body = ApiErrorBody
+        ),
+    )
+)]
 pub async fn get_all_withdrawals(
     Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
     Query(query): Query<WithdrawalQuery>,
-) -> Result<Json<Vec<Withdrawal>>, (StatusCode, String)> {
+    Query(selected): Query<NetworkQuery>,
+) -> Result<Json<Vec<Withdrawal>>, ApiError> {
+    let network = networks.resolve(selected.network.as_deref())?;
     let withdrawals = fetch_withdrawals_by_identifier(
         &pool,
+        &network,
         query.user_address,
         query.stark_pub_key,
         WithdrawalFetchMode::All,
@@ -312,14 +1087,205 @@ pub async fn get_all_withdrawals(
     Ok(Json(withdrawals))
 }
 
+#[utoipa::path(
+    get,
+    path = "/withdrawals/{id}",
+    tag = "withdrawals",
+    params(("id" = i32, Path, description = "Withdrawal id"), WithdrawalStatusQuery),
+    responses(
+        (status = 200, description = "Withdrawal with timeline", body = WithdrawalStatusResponse),
+        (status = 404, description = "Withdrawal not found", body = ApiErrorBody),
+    )
+)]
+pub async fn get_withdrawal_status(
+    Extension(pool): Extension<PgPool>,
+    Path(id): Path<i32>,
+    Query(query): Query<WithdrawalStatusQuery>,
+) -> Result<Json<WithdrawalStatusResponse>, ApiError> {
+    let withdrawal = fetch_withdrawal_by_id(&pool, id).await?.ok_or_else(|| {
+        ApiError::not_found(
+            "withdrawal_not_found",
+            format!("Withdrawal {} not found", id),
+        )
+    })?;
+
+    Ok(Json(
+        build_withdrawal_status(&pool, withdrawal, query.include_proof).await?,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/withdrawals/by-hash/{commitment_hash}",
+    tag = "withdrawals",
+    params(
+        ("commitment_hash" = String, Path, description = "Commitment hash of the withdrawal"),
+        WithdrawalStatusQuery,
+    ),
+    responses(
+        (status = 200, description = "Withdrawal with timeline", body = WithdrawalStatusResponse),
+        (status = 404, description = "Withdrawal not found", body = ApiErrorBody),
+    )
+)]
+pub async fn get_withdrawal_status_by_hash(
+    Extension(pool): Extension<PgPool>,
+    Path(commitment_hash): Path<String>,
+    Query(query): Query<WithdrawalStatusQuery>,
+) -> Result<Json<WithdrawalStatusResponse>, ApiError> {
+    let withdrawal = fetch_withdrawal_by_commitment_hash(&pool, &commitment_hash)
+        .await?
+        .ok_or_else(|| {
+            ApiError::not_found(
+                "withdrawal_not_found",
+                format!("No withdrawal with commitment hash {}", commitment_hash),
+            )
+        })?;
+
+    Ok(Json(
+        build_withdrawal_status(&pool, withdrawal, query.include_proof).await?,
+    ))
+}
+
+async fn build_withdrawal_status(
+    pool: &PgPool,
+    withdrawal: Withdrawal,
+    include_proof: bool,
+) -> Result<WithdrawalStatusResponse, ApiError> {
+    let events = fetch_withdrawal_events(pool, withdrawal.id).await?;
+
+    // Withdrawals are always inserted as 'pending'; later changes come from the event history
+    let mut timeline = vec![TimelineEntry {
+        stage: withdrawal_stage("pending").to_string(),
+        status: "pending".to_string(),
+        timestamp: withdrawal.created_at.map(|t| t.and_utc()),
+        error: None,
+    }];
+    timeline.extend(events.into_iter().map(|event| TimelineEntry {
+        stage: withdrawal_stage(&event.status).to_string(),
+        status: event.status,
+        timestamp: Some(event.created_at),
+        error: event.error_message,
+    }));
+
+    // Rows updated before the history existed only carry their current status
+    if timeline.len() == 1 && !withdrawal.status.eq_ignore_ascii_case("pending") {
+        timeline.push(TimelineEntry {
+            stage: withdrawal_stage(&withdrawal.status).to_string(),
+            status: withdrawal.status.clone(),
+            timestamp: withdrawal.updated_at.map(|t| t.and_utc()),
+            error: None,
+        });
+    }
+
+    let proof = if include_proof {
+        fetch_latest_withdrawal_proof(pool, withdrawal.id)
+            .await?
+            .map(proof_to_json)
+    } else {
+        None
+    };
+
+    Ok(WithdrawalStatusResponse {
+        withdrawal,
+        timeline,
+        proof,
+    })
+}
+
+/// Maps a withdrawal status onto the lifecycle stage it belongs to
+fn withdrawal_stage(status: &str) -> &'static str {
+    match status.to_ascii_lowercase().as_str() {
+        "pending" => "created",
+        "pending_tree_inclusion" | "included" => "tree_inclusion",
+        "pending_proof_generation" | "proof_generated" | "awaiting_state_proof" => {
+            "proof_generation"
+        }
+        "ready_for_relay" | "queued_for_relay" | "relayed" => "l1_relay",
+        "confirmed" | "completed" => "confirmation",
+        "failed" => "failed",
+        _ => "unknown",
+    }
+}
+
+fn proof_to_json(proof: WithdrawalProof) -> serde_json::Value {
+    // Proofs are stored as raw bytes; surface them as JSON when they hold JSON, hex otherwise
+    let decode = |bytes: Option<Vec<u8>>| match bytes {
+        Some(bytes) => serde_json::from_slice(&bytes)
+            .unwrap_or_else(|_| json!(format!("0x{}", hex::encode(bytes)))),
+        None => serde_json::Value::Null,
+    };
+
+    json!({
+        "status": proof.status,
+        "proof_params": decode(proof.proof_params),
+        "proof_data": decode(proof.proof_data),
+        "created_at": proof.created_at,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "health",
+    responses((status = 200, description = "Service is up", body = Object))
+)]
 pub async fn hello_world(
     Extension(_): Extension<PgPool>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     Ok(Json(json!({
         "message": "hello world from zeroxbridge"
     })))
 }
 
+/// Whether each service that can be paused is running or was paused by an admin.
+///
+/// Paused services are reported in their own state rather than as failures, so the
+/// response is a 200 either way. A database that cannot be reached within
+/// `database.connection_timeout_secs` is a 503.
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "State of each service", body = HealthResponse),
+        (status = 503, description = "Database is unavailable", body = ApiErrorBody)
+    )
+)]
+pub async fn get_health(
+    Extension(pool): Extension<PgPool>,
+) -> Result<Json<HealthResponse>, ApiError> {
+    ping_db(&pool).await?;
+    let controls = fetch_service_controls(&pool).await?;
+
+    let services: Vec<ServiceHealth> = PipelineService::ALL
+        .iter()
+        .map(|service| {
+            let paused = controls
+                .iter()
+                .find(|control| control.service_name == service.as_str() && control.paused);
+            let (state, reason) = match paused {
+                Some(control) => ("paused", control.reason.clone()),
+                None => ("running", None),
+            };
+            ServiceHealth {
+                service: service.as_str().to_string(),
+                state: state.to_string(),
+                reason,
+            }
+        })
+        .collect();
+    let status = if services.iter().any(|service| service.state == "paused") {
+        "paused"
+    } else {
+        "ok"
+    };
+
+    Ok(Json(HealthResponse {
+        status: status.to_string(),
+        services,
+    }))
+}
+
 /// Computes a Poseidon commitment hash for deposit transactions
 ///
 /// This endpoint allows users to generate the same hash that the L2 contract
@@ -332,36 +1298,68 @@ pub async fn hello_world(
 /// - nonce: Transaction nonce
 /// - timestamp: Block timestamp
 ///
+/// Unless disabled, the nonce must be the recipient's next deposit nonce, see
+/// `GET /nonce/{stark_pub_key}`, and the timestamp close to server time, since the
+/// contract would never accept the commitment otherwise.
+///
 /// Returns the commitment hash that should be used when making the deposit.
+#[utoipa::path(
+    post,
+    path = "/poseidon/hash",
+    tag = "hashing",
+    request_body = PoseidonHashRequest,
+    responses(
+        (status = 200, description = "Poseidon commitment hash", body = PoseidonHashResponse),
+        (
+            status = 400,
+            description = "Invalid recipient or hash method, or stale timestamp",
+            body = ApiErrorBody
+        ),
+        (
+            status = 409,
+            description = "Nonce is not the recipient's next one",
+            body = ApiErrorBody
+        ),
+    )
+)]
 pub async fn compute_poseidon_hash(
+    Extension(pool): Extension<PgPool>,
+    Extension(freshness): Extension<HashFreshness>,
     Json(payload): Json<PoseidonHashRequest>,
-) -> Result<Json<PoseidonHashResponse>, (StatusCode, String)> {
+) -> Result<Json<PoseidonHashResponse>, ApiError> {
     // Parse recipient address as Felt (felt252)
     let recipient_felt = match Felt::from_hex(&payload.recipient) {
         Ok(felt) => felt,
-        Err(_) => return Err((StatusCode::BAD_REQUEST,
-            "Invalid recipient address format. Must be a valid Starknet address in hex format (0x...).".
-            to_string())
-        ),
+        Err(_) => return Err(ApiError::invalid_stark_key(
+            "Invalid recipient address format. Must be a valid Starknet address in hex format (0x...).",
+        )),
     };
 
     // Determine which hash method to use (default to BatchHash for efficiency)
     let method = match payload.hash_method.as_deref() {
         Some("BatchHash") | Some("batch") | None => HashMethod::BatchHash,
         Some("SequentialPairwise") | Some("sequential") => HashMethod::SequentialPairwise,
-        Some(method) => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                format!(
+        Some(method) => return Err(ApiError::bad_request(
+            "invalid_hash_method",
+            format!(
                 "Invalid hash method: '{}'. Valid options are 'BatchHash' or 'SequentialPairwise'",
                 method
             ),
-            ))
-        }
+        )),
     };
 
+    if freshness.enabled {
+        check_timestamp_freshness(
+            payload.timestamp,
+            Utc::now().timestamp() as u64,
+            freshness.max_skew_sec,
+        )?;
+        let expected = fetch_next_deposit_nonce(&pool, &payload.recipient).await?;
+        check_nonce(payload.nonce, expected)?;
+    }
+
     // Compute the Poseidon hash using the utility function
-    let hash = compute_poseidon_commitment_hash(
+    let hash_hex = compute_poseidon_commitment_hash_hex(
         recipient_felt,
         payload.amount,
         payload.nonce,
@@ -369,30 +1367,52 @@ pub async fn compute_poseidon_hash(
         method,
     );
 
-    // Convert hash to hex string format
-    let hash_hex = format!("0x{:x}", hash);
-
     Ok(Json(PoseidonHashResponse {
         commitment_hash: hash_hex,
     }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/compute-hash",
+    tag = "hashing",
+    request_body = HashRequest,
+    responses(
+        (status = 200, description = "Keccak commitment hash of the burn", body = HashResponse),
+        (
+            status = 400,
+            description = "Burn data is not canonical, or its timestamp is stale",
+            body = ApiErrorBody
+        ),
+        (
+            status = 409,
+            description = "Nonce is not the key's next withdrawal nonce",
+            body = ApiErrorBody
+        ),
+    )
+)]
 pub async fn compute_hash_handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(freshness): Extension<HashFreshness>,
     Json(payload): Json<HashRequest>,
-) -> Result<Json<HashResponse>, impl IntoResponse> {
-    // Validate the Starknet public key format before hashing
+) -> Result<Json<HashResponse>, ApiError> {
     let burn_data = BurnData {
         caller: payload.stark_pubkey.clone(),
         amount: payload.usd_val,
         nonce: payload.nonce,
         time_stamp: payload.timestamp,
     };
-    if BurnData::hex_to_bytes32(&burn_data.caller).is_err() {
-        let error_response = ErrorResponse {
-            error: "Invalid stark_pubkey".to_string(),
-            details: Some("Invalid hex string for caller address".to_string()),
-        };
-        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    // Only hash the canonical encoding of the burn
+    burn_data.validate()?;
+    // Only hash burns the contract can still accept
+    if freshness.enabled {
+        check_timestamp_freshness(
+            burn_data.time_stamp,
+            Utc::now().timestamp() as u64,
+            freshness.max_skew_sec,
+        )?;
+        let expected = fetch_next_withdrawal_nonce(&pool, &burn_data.caller).await?;
+        check_nonce(burn_data.nonce, expected)?;
     }
     // Compute the commitment hash
     let hex_hash = burn_data.hash_to_hex_string();
@@ -409,7 +1429,238 @@ pub async fn compute_hash_handler(
     Ok(Json(response))
 }
 
-#[derive(Serialize, Deserialize)]
+/// Rejects a `timestamp` more than `max_skew_sec` away from `now`, giving the server
+/// time so the client can resync its clock
+fn check_timestamp_freshness(timestamp: u64, now: u64, max_skew_sec: u64) -> Result<(), ApiError> {
+    if timestamp.abs_diff(now) <= max_skew_sec {
+        return Ok(());
+    }
+    Err(ApiError::bad_request(
+        "stale_timestamp",
+        format!(
+            "Timestamp {} is more than {} seconds away from server time {}",
+            timestamp, max_skew_sec, now
+        ),
+    )
+    .with_details(json!({ "server_time": now })))
+}
+
+/// Rejects any `nonce` but the `expected` next one, giving the expected nonce
+fn check_nonce(nonce: u64, expected: i64) -> Result<(), ApiError> {
+    if nonce as i64 == expected {
+        return Ok(());
+    }
+    Err(ApiError::new(
+        StatusCode::CONFLICT,
+        "invalid_nonce",
+        format!("Nonce {} is not the next nonce {}", nonce, expected),
+    )
+    .with_details(json!({ "expected_nonce": expected })))
+}
+
+/// Returns the nonce the next deposit or withdrawal of a Stark key must be hashed with
+#[utoipa::path(
+    get,
+    path = "/nonce/{stark_pub_key}",
+    tag = "hashing",
+    params(("stark_pub_key" = String, Path, description = "Starknet public key"), NonceQuery),
+    responses(
+        (status = 200, description = "Next nonce of the key", body = NonceResponse),
+        (status = 400, description = "Invalid Starknet key or nonce type", body = ApiErrorBody),
+    )
+)]
+pub async fn get_next_nonce(
+    Extension(pool): Extension<PgPool>,
+    Path(stark_pub_key): Path<String>,
+    Query(query): Query<NonceQuery>,
+) -> Result<Json<NonceResponse>, ApiError> {
+    validate_starknet_address(&stark_pub_key)?;
+    let nonce = match query.nonce_type {
+        NonceType::Deposit => fetch_next_deposit_nonce(&pool, &stark_pub_key).await?,
+        NonceType::Withdrawal => fetch_next_withdrawal_nonce(&pool, &stark_pub_key).await?,
+    };
+    Ok(Json(NonceResponse {
+        stark_pub_key,
+        nonce_type: query.nonce_type,
+        nonce: nonce as u64,
+    }))
+}
+
+/// Returns the current root of the in-memory L1 deposit tree
+#[utoipa::path(
+    get,
+    path = "/tree/root",
+    tag = "tree",
+    responses((status = 200, description = "Current root and leaf count", body = TreeRootResponse))
+)]
+pub async fn get_tree_root(
+    Extension(tree_state): Extension<TreeState>,
+) -> Result<Json<TreeRootResponse>, ApiError> {
+    let tree = tree_state.tree.read().await;
+
+    let root = tree.get_root().await.map_err(tree_error)?;
+    let leaf_count = tree.leaf_count().await.map_err(tree_error)?;
+
+    Ok(Json(TreeRootResponse {
+        root: format!("0x{}", hex::encode(root)),
+        leaf_count,
+    }))
+}
+
+/// Returns the inclusion proof of `commitment_hash` against the current root
+#[utoipa::path(
+    get,
+    path = "/tree/proof/{commitment_hash}",
+    tag = "tree",
+    params(("commitment_hash" = String, Path, description = "32-byte commitment hash (0x...)")),
+    responses(
+        (status = 200, description = "Inclusion proof", body = TreeProofResponse),
+        (status = 400, description = "Malformed commitment hash", body = ApiErrorBody),
+        (status = 404, description = "Commitment is not in the tree", body = ApiErrorBody),
+    )
+)]
+pub async fn get_tree_proof(
+    Extension(tree_state): Extension<TreeState>,
+    Path(commitment_hash): Path<String>,
+) -> Result<Json<TreeProofResponse>, ApiError> {
+    let leaf = parse_leaf(&commitment_hash)?;
+    let tree = tree_state.tree.read().await;
+
+    let proof = tree_state
+        .proofs
+        .prove(&tree, leaf)
+        .await
+        .map_err(tree_error)?
+        .ok_or_else(|| {
+            ApiError::not_found(
+                "leaf_not_found",
+                format!("Commitment {} is not in the tree", commitment_hash),
+            )
+        })?;
+
+    Ok(Json(TreeProofResponse {
+        commitment_hash: format!("0x{}", hex::encode(leaf)),
+        leaf_index: proof.leaf_index,
+        siblings: proof.siblings,
+        peaks: proof.peaks,
+        elements_count: proof.elements_count,
+        root: proof.merkle_root,
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MerkleRootQuery {
+    /// Number of leaves in the tree
+    pub count: i64,
+}
+
+/// Returns the local and on-chain roots of the tree holding `count` leaves
+#[utoipa::path(
+    get,
+    path = "/merkle/roots",
+    tag = "tree",
+    params(MerkleRootQuery, NetworkQuery),
+    responses(
+        (status = 200, description = "Roots of the tree size", body = MerkleRootsResponse),
+        (status = 400, description = "Negative tree size or unknown network", body = ApiErrorBody),
+        (status = 404, description = "No root recorded for the tree size", body = ApiErrorBody),
+    )
+)]
+pub async fn get_merkle_roots(
+    Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
+    Query(query): Query<MerkleRootQuery>,
+    Query(selected): Query<NetworkQuery>,
+) -> Result<Json<MerkleRootsResponse>, ApiError> {
+    let network = networks.resolve(selected.network.as_deref())?;
+    if query.count < 0 {
+        return Err(ApiError::bad_request(
+            "invalid_count",
+            "count must not be negative",
+        ));
+    }
+
+    let roots = get_root_at(&pool, &network, query.count).await?;
+    if roots.is_empty() {
+        return Err(ApiError::not_found(
+            "root_not_found",
+            format!("No root recorded for {} elements", query.count),
+        ));
+    }
+
+    let (local, onchain) = split_roots_by_source(roots);
+    let matches = match (&local, &onchain) {
+        (Some(local), Some(onchain)) => Some(local.root.eq_ignore_ascii_case(&onchain.root)),
+        _ => None,
+    };
+
+    Ok(Json(MerkleRootsResponse {
+        elements_count: query.count,
+        local,
+        onchain,
+        matches,
+    }))
+}
+
+/// Returns the root of the largest tree recorded by each source
+#[utoipa::path(
+    get,
+    path = "/merkle/roots/latest",
+    tag = "tree",
+    params(NetworkQuery),
+    responses(
+        (status = 200, description = "Latest roots by source", body = LatestMerkleRootsResponse),
+        (status = 400, description = "Unknown network", body = ApiErrorBody),
+    )
+)]
+pub async fn get_latest_merkle_roots(
+    Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Json<LatestMerkleRootsResponse>, ApiError> {
+    let network = networks.resolve(query.network.as_deref())?;
+    let (local, onchain) = split_roots_by_source(fetch_latest_merkle_roots(&pool, &network).await?);
+
+    Ok(Json(LatestMerkleRootsResponse { local, onchain }))
+}
+
+fn split_roots_by_source(roots: Vec<MerkleRoot>) -> (Option<MerkleRoot>, Option<MerkleRoot>) {
+    let mut local = None;
+    let mut onchain = None;
+    for root in roots {
+        if root.source == RootSource::Local.as_str() {
+            local = Some(root);
+        } else if root.source == RootSource::OnChain.as_str() {
+            onchain = Some(root);
+        }
+    }
+    (local, onchain)
+}
+
+fn parse_leaf(commitment_hash: &str) -> Result<[u8; 32], ApiError> {
+    let invalid = || {
+        ApiError::bad_request(
+            "invalid_commitment_hash",
+            "Commitment hash must be 32 bytes of hex (0x...)",
+        )
+    };
+
+    let bytes = hex::decode(commitment_hash.trim_start_matches("0x")).map_err(|_| invalid())?;
+    bytes.try_into().map_err(|_| invalid())
+}
+
+fn tree_error(err: impl std::fmt::Display) -> ApiError {
+    tracing::error!("Merkle tree error: {}", err);
+    ApiError::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "tree_error",
+        "Failed to read the Merkle tree",
+    )
+}
+
+#[derive(Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FetchDepositQuery {
     pub stark_pub_key: Option<String>,
     pub user_address: Option<String>,
@@ -417,47 +1668,170 @@ pub struct FetchDepositQuery {
 
 // pub struct
 
+#[utoipa::path(
+    get,
+    path = "/deposits/stats",
+    tag = "deposits",
+    responses(
+        (status = 200, description = "Deposit counts by status, today's volume and average proof generation time, refreshed every 30 seconds", body = DepositStats),
+    )
+)]
+pub async fn get_deposit_stats_handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(cache): Extension<DepositStatsCache>,
+) -> Result<Json<DepositStats>, ApiError> {
+    let stats = cache.get_or_load(|| get_deposit_stats(&pool)).await?;
+    Ok(Json(stats))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats",
+    tag = "stats",
+    responses(
+        (status = 200, description = "Deposit and withdrawal counts, volumes and latencies with the deposit tree size, refreshed every `server.stats_cache_ttl_sec` seconds", body = BridgeStats),
+    )
+)]
+pub async fn get_bridge_stats_handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(cache): Extension<BridgeStatsCache>,
+) -> Result<Json<BridgeStats>, ApiError> {
+    let stats = cache.get_or_load(|| get_bridge_stats(&pool)).await?;
+    Ok(Json(stats))
+}
+
+/// Days of relay costs reported when `from` is omitted
+const RELAY_COSTS_DEFAULT_DAYS: i64 = 30;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RelayCostsQuery {
+    /// Start of the period, RFC 3339. Defaults to 30 days before `to`
+    pub from: Option<DateTime<Utc>>,
+    /// End of the period, excluded, RFC 3339. Defaults to now
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats/relay-costs",
+    tag = "stats",
+    params(RelayCostsQuery),
+    responses(
+        (status = 200, description = "Fees paid by the relayer accounts per day, network and fee unit", body = [RelayCostSummary]),
+        (status = 400, description = "`from` is not before `to`", body = ApiErrorBody),
+    )
+)]
+pub async fn get_relay_costs_handler(
+    Extension(pool): Extension<PgPool>,
+    Query(query): Query<RelayCostsQuery>,
+) -> Result<Json<Vec<RelayCostSummary>>, ApiError> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query
+        .from
+        .unwrap_or(to - Duration::days(RELAY_COSTS_DEFAULT_DAYS));
+    if from >= to {
+        return Err(ApiError::bad_request(
+            "invalid_range",
+            "from must be before to",
+        ));
+    }
+
+    Ok(Json(get_relay_costs_summary(&pool, from, to).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/deposits/latest",
+    tag = "deposits",
+    params(FetchDepositQuery, NetworkQuery),
+    responses(
+        (status = 200, description = "Latest deposit of the user", body = Deposit),
+        (status = 400, description = "No identifier given or unknown network", body = ApiErrorBody),
+        (status = 404, description = "User has no deposits", body = ApiErrorBody),
+    )
+)]
 pub async fn fetch_user_latest_deposit_handler(
     Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
     Query(payload): Query<FetchDepositQuery>,
-) -> Result<Json<Deposit>, (StatusCode, String)> {
+    Query(selected): Query<NetworkQuery>,
+) -> Result<Json<Deposit>, ApiError> {
+    let network = networks.resolve(selected.network.as_deref())?;
     let key = extract_user_key(&payload)?;
 
-    let deposit = get_user_latest_deposit(&pool, &key).await;
+    let deposit = get_user_latest_deposit(&pool, &network, &key).await;
 
     match deposit {
         Ok(Some(dp)) => Ok(Json(dp)),
-        Ok(None) => Err((
-            StatusCode::NOT_FOUND,
-            "No deposits found for the given user".to_string(),
+        Ok(None) => Err(ApiError::not_found(
+            "deposit_not_found",
+            "No deposits found for the given user",
         )),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(e) => Err(e.into()),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/deposits/by-hash/{commitment_hash}",
+    tag = "deposits",
+    params(("commitment_hash" = String, Path, description = "Commitment hash of the deposit")),
+    responses(
+        (status = 200, description = "Deposit with its current status", body = Deposit),
+        (status = 404, description = "Deposit not found", body = ApiErrorBody),
+    )
+)]
+pub async fn get_deposit_by_hash(
+    Extension(pool): Extension<PgPool>,
+    Path(commitment_hash): Path<String>,
+) -> Result<Json<Deposit>, ApiError> {
+    let deposit = fetch_deposit_by_commitment_hash(&pool, &commitment_hash)
+        .await?
+        .ok_or_else(|| {
+            ApiError::not_found(
+                "deposit_not_found",
+                format!("No deposit with commitment hash {}", commitment_hash),
+            )
+        })?;
+
+    Ok(Json(deposit))
+}
+
+#[utoipa::path(
+    get,
+    path = "/deposits",
+    tag = "deposits",
+    params(FetchDepositQuery, NetworkQuery),
+    responses(
+        (status = 200, description = "Deposits of the user", body = [Deposit]),
+        (status = 400, description = "No identifier given or unknown network", body = ApiErrorBody),
+    )
+)]
 pub async fn fetch_user_deposits_handler(
     Extension(pool): Extension<PgPool>,
+    Extension(networks): Extension<Networks>,
     Query(payload): Query<FetchDepositQuery>,
-) -> Result<Json<Vec<Deposit>>, (StatusCode, String)> {
+    Query(selected): Query<NetworkQuery>,
+) -> Result<Json<Vec<Deposit>>, ApiError> {
+    let network = networks.resolve(selected.network.as_deref())?;
     let key = extract_user_key(&payload)?;
 
-    let deposit = get_user_deposits(&pool, &key, 2)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let deposit = get_user_deposits(&pool, &network, &key, 2).await?;
 
     Ok(Json(deposit))
 }
 
-fn extract_user_key(payload: &FetchDepositQuery) -> Result<String, (StatusCode, String)> {
+fn extract_user_key(payload: &FetchDepositQuery) -> Result<String, ApiError> {
     match (
         payload.stark_pub_key.as_ref(),
         payload.user_address.as_ref(),
     ) {
         (Some(stark), _) => Ok(stark.trim().to_string()),
         (None, Some(user)) => Ok(user.trim().to_string()),
-        (None, None) => Err((
-            StatusCode::BAD_REQUEST,
-            "Either stark_pub_key or user_address must be provided".into(),
+        (None, None) => Err(ApiError::bad_request(
+            "invalid_identifier",
+            "Either stark_pub_key or user_address must be provided",
         )),
     }
 }
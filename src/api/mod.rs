@@ -1,2 +1,10 @@
+pub mod artifacts;
+pub mod auth;
+pub mod docs;
+pub mod error;
 pub mod handlers;
 pub mod routes;
+pub mod sse;
+pub mod stats;
+pub mod types;
+pub mod ws;
@@ -1,14 +1,47 @@
-use crate::{api::handlers::hello_world, config::AppConfig};
+use crate::{
+    api::artifacts::{download_proof_artifact, ProofArtifacts},
+    api::auth::{require_admin_signatures, AdminAuth, AdminToken},
+    api::docs::{is_documented, openapi_json, swagger_ui},
+    api::error::ApiError,
+    api::handlers::{get_health, hello_world},
+    api::sse::{deposit_status_sse, EventStreamSlots},
+    api::stats::{BridgeStatsCache, DepositStatsCache},
+    api::ws::deposit_status_ws,
+    config::{
+        default_request_timeout_ms, AppConfig, LimitsConfig, LiveConfig, NetworkId, RateLimitConfig,
+    },
+    events::deposit_status::DepositStatusFeed,
+    http::body_limit::{limit_body_size, BodyLimit},
+    http::rate_limiter::{rate_limit, RateLimiter},
+    http::request_id::request_id,
+    http::timeout::handle_timeout_error,
+    tree_builder::{ProofCache, RootFeed},
+    utils::BurnData,
+};
 use axum::{
-    routing::{get, post},
+    error_handling::HandleErrorLayer,
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post},
     Extension, Router,
 };
 use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tower::{timeout::TimeoutLayer, ServiceBuilder};
+use tree_builder::l1_tree::L1MerkleTreeBuilder;
 
 use crate::api::handlers::{
-    compute_hash_handler, compute_poseidon_hash, create_withdrawal, get_all_withdrawals, fetch_user_deposits_handler,
-    get_latest_withdrawal, get_pending_withdrawals, handle_deposit_post, fetch_user_latest_deposit_handler,
-    handle_get_pending_deposits,
+    admin_cancel_deposit, cancel_deposit, compute_hash_handler, compute_poseidon_hash,
+    create_webhook, create_withdrawal, fetch_user_deposits_handler,
+    fetch_user_latest_deposit_handler, fill_nonce_gap, get_all_withdrawals, get_block_trackers,
+    get_bridge_stats_handler, get_deposit_by_hash, get_deposit_stats_handler, get_deposit_timings,
+    get_latest_merkle_roots, get_latest_withdrawal, get_merkle_roots, get_next_nonce,
+    get_pending_withdrawals, get_relay_costs_handler, get_tree_proof, get_tree_root,
+    get_webhook_deliveries, get_webhooks, get_withdrawal_status, get_withdrawal_status_by_hash,
+    handle_deposit_post, handle_get_pending_deposits, pause_service, rebuild_tree_handler,
+    refresh_deposit, reload_config, remove_webhook, resume_service, set_block_tracker,
 };
 
 #[derive(Clone)]
@@ -17,22 +50,375 @@ pub struct AppState {
     pub config: AppConfig,
 }
 
+/// In-memory L1 deposit tree shared between the tree builder and the API, with the
+/// cache of the proofs served from it and the feed of the roots it reaches
+#[derive(Clone, Default)]
+pub struct TreeState {
+    pub tree: Arc<RwLock<L1MerkleTreeBuilder>>,
+    pub proofs: ProofCache,
+    pub roots: RootFeed,
+}
+
+impl TreeState {
+    pub fn new(tree: Arc<RwLock<L1MerkleTreeBuilder>>, proofs: ProofCache) -> Self {
+        Self {
+            tree,
+            proofs,
+            roots: RootFeed::default(),
+        }
+    }
+
+    /// Watches the roots of `roots`, the tree builder's feed, rather than a feed nothing
+    /// publishes to
+    pub fn with_roots(mut self, roots: RootFeed) -> Self {
+        self.roots = roots;
+        self
+    }
+}
+
+/// Whether `POST /deposit` only accepts commitment hashes already seen on L1
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L1Verification(pub bool);
+
+/// EIP-712 domain separator of the L1 bridge, withdrawal commitments are hashed under it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithdrawalDomain(pub [u8; 32]);
+
+/// Whether `POST /withdrawals` requires a signature by the withdrawal's Stark key
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithdrawalSignatures(pub bool);
+
+/// Whether the hashing endpoints require the next nonce of the key and a current
+/// timestamp, and how many seconds the timestamp may drift from server time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashFreshness {
+    pub enabled: bool,
+    pub max_skew_sec: u64,
+}
+
+/// Networks the API serves, requests pick one with `?network=` and fall back to the
+/// first one
+#[derive(Debug, Clone)]
+pub struct Networks(Arc<Vec<NetworkId>>);
+
+impl Networks {
+    pub fn new(networks: Vec<NetworkId>) -> Self {
+        if networks.is_empty() {
+            return Self::default();
+        }
+        Self(Arc::new(networks))
+    }
+
+    /// The network named by a request, or the default one when none is named
+    pub fn resolve(&self, requested: Option<&str>) -> Result<NetworkId, ApiError> {
+        let Some(name) = requested else {
+            return Ok(self.0[0].clone());
+        };
+
+        self.0
+            .iter()
+            .find(|network| network.as_str() == name)
+            .cloned()
+            .ok_or_else(|| {
+                let known: Vec<&str> = self.0.iter().map(NetworkId::as_str).collect();
+                ApiError::bad_request(
+                    "unknown_network",
+                    format!(
+                        "Unknown network '{}'. Configured networks are: {}",
+                        name,
+                        known.join(", ")
+                    ),
+                )
+            })
+    }
+}
+
+impl Default for Networks {
+    fn default() -> Self {
+        Self(Arc::new(vec![NetworkId::default()]))
+    }
+}
+
+/// Builds the API router with the default settings.
+///
+/// Rate limiting keys on the peer address, so the router must be served with
+/// `into_make_service_with_connect_info::<SocketAddr>()`; requests without one are
+/// not limited.
 pub fn create_router(pool: PgPool) -> Router {
-    Router::new()
-        .route("/", get(hello_world))
+    create_router_with_rate_limit(pool, RateLimitConfig::default())
+}
+
+/// Builds the API router with per-IP rate limiting configured by `rate_limit_config`
+pub fn create_router_with_rate_limit(pool: PgPool, rate_limit_config: RateLimitConfig) -> Router {
+    build_router(
+        pool,
+        rate_limit_config,
+        AdminToken::default(),
+        AdminAuth::default(),
+        L1Verification::default(),
+        LimitsConfig::default(),
+        TreeState::default(),
+        BridgeStatsCache::default(),
+        BodyLimit::default(),
+        Duration::from_millis(default_request_timeout_ms()),
+        Networks::default(),
+        WithdrawalDomain::default(),
+        WithdrawalSignatures::default(),
+        HashFreshness::default(),
+        ProofArtifacts::default(),
+        EventStreamSlots::default(),
+        true,
+    )
+}
+
+/// Builds the API router serving the Merkle root and proofs from `tree_state`
+pub fn create_router_with_tree_state(pool: PgPool, tree_state: TreeState) -> Router {
+    build_router(
+        pool,
+        RateLimitConfig::default(),
+        AdminToken::default(),
+        AdminAuth::default(),
+        L1Verification::default(),
+        LimitsConfig::default(),
+        tree_state,
+        BridgeStatsCache::default(),
+        BodyLimit::default(),
+        Duration::from_millis(default_request_timeout_ms()),
+        Networks::default(),
+        WithdrawalDomain::default(),
+        WithdrawalSignatures::default(),
+        HashFreshness::default(),
+        ProofArtifacts::default(),
+        EventStreamSlots::default(),
+        true,
+    )
+}
+
+/// Builds the API router with rate limiting, admin authentication, deposit verification,
+/// amount limits, the stats cache TTL, the body size limit, the request timeout, hash
+/// freshness, the served networks, the proof artifact store and the cap on deposit
+/// status streams taken from `config`
+pub fn create_router_with_config(pool: PgPool, config: &AppConfig) -> Router {
+    create_router_with_live_config(pool, LiveConfig::new(config.clone()))
+}
+
+/// Builds the API router from the current settings of `live_config`, which
+/// `POST /admin/config/reload` reloads. Settings the router itself is built from,
+/// such as rate limits, keep their value until a restart.
+pub fn create_router_with_live_config(pool: PgPool, live_config: LiveConfig) -> Router {
+    let config = live_config.snapshot();
+    build_router(
+        pool,
+        config.server.rate_limit.clone(),
+        AdminToken(config.server.admin_token.clone()),
+        AdminAuth::new(&config.server.admin_addresses),
+        L1Verification(config.server.require_l1_verification),
+        config.limits.clone(),
+        TreeState::default(),
+        BridgeStatsCache::new(Duration::from_secs(config.server.stats_cache_ttl_sec)),
+        BodyLimit(config.server.max_body_bytes),
+        Duration::from_millis(config.server.request_timeout_ms),
+        Networks::new(
+            config
+                .active_networks()
+                .into_iter()
+                .map(|network| network.name)
+                .collect(),
+        ),
+        WithdrawalDomain(
+            BurnData::hex_to_bytes32(&config.relayer.domain_separator)
+                .expect("relayer.domain_separator is validated on load"),
+        ),
+        WithdrawalSignatures(config.server.verify_withdrawal_signatures),
+        HashFreshness {
+            enabled: config.server.check_hash_freshness,
+            max_skew_sec: config.server.hash_timestamp_skew_sec,
+        },
+        ProofArtifacts::new(config.proof.clone()),
+        EventStreamSlots::new(config.server.max_event_streams),
+        config.server.enable_docs,
+    )
+    .layer(Extension(live_config))
+}
+
+/// Names a route's handler, failing to compile unless it is registered in the paths of
+/// [`ApiDoc`](crate::api::docs::ApiDoc), so no route goes undocumented
+macro_rules! documented {
+    ($handler:ident) => {{
+        const _: () = assert!(
+            is_documented(stringify!($handler)),
+            concat!(
+                "`",
+                stringify!($handler),
+                "` is not registered in the OpenAPI paths of ApiDoc"
+            )
+        );
+        $handler
+    }};
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_router(
+    pool: PgPool,
+    rate_limit_config: RateLimitConfig,
+    admin_token: AdminToken,
+    admin_auth: AdminAuth,
+    l1_verification: L1Verification,
+    limits: LimitsConfig,
+    tree_state: TreeState,
+    bridge_stats_cache: BridgeStatsCache,
+    body_limit: BodyLimit,
+    request_timeout: Duration,
+    networks: Networks,
+    withdrawal_domain: WithdrawalDomain,
+    withdrawal_signatures: WithdrawalSignatures,
+    hash_freshness: HashFreshness,
+    proof_artifacts: ProofArtifacts,
+    event_stream_slots: EventStreamSlots,
+    enable_docs: bool,
+) -> Router {
+    let limiter = RateLimiter::new(rate_limit_config);
+    let deposit_status_feed = DepositStatusFeed::new(pool.clone());
+
+    // Every /admin route requires the admin multisig
+    let admin_routes = Router::new()
+        .route(
+            "/admin/deposits/{id}",
+            delete(documented!(admin_cancel_deposit)),
+        )
+        .route(
+            "/admin/block-tracker",
+            get(documented!(get_block_trackers)).put(documented!(set_block_tracker)),
+        )
+        .route(
+            "/admin/tree/rebuild",
+            post(documented!(rebuild_tree_handler)),
+        )
+        .route(
+            "/admin/withdrawals/fill-nonce-gap",
+            post(documented!(fill_nonce_gap)),
+        )
+        .route(
+            "/admin/services/{name}/pause",
+            post(documented!(pause_service)),
+        )
+        .route(
+            "/admin/services/{name}/resume",
+            post(documented!(resume_service)),
+        )
+        .route("/admin/config/reload", post(documented!(reload_config)))
+        .route(
+            "/admin/webhooks",
+            get(documented!(get_webhooks)).post(documented!(create_webhook)),
+        )
+        .route("/admin/webhooks/{id}", delete(documented!(remove_webhook)))
+        .route(
+            "/admin/webhooks/{id}/deliveries",
+            get(documented!(get_webhook_deliveries)),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            admin_auth,
+            require_admin_signatures,
+        ));
+
+    let mut router = Router::new()
+        .route("/", get(documented!(hello_world)))
+        .route("/health", get(documented!(get_health)))
         .route(
             "/deposit",
-            post(handle_deposit_post).get(handle_get_pending_deposits),
+            post(documented!(handle_deposit_post)).get(documented!(handle_get_pending_deposits)),
+        )
+        .route("/deposits", get(documented!(fetch_user_deposits_handler)))
+        .route(
+            "/deposits/latest",
+            get(documented!(fetch_user_latest_deposit_handler)),
+        )
+        .route(
+            "/deposits/stats",
+            get(documented!(get_deposit_stats_handler)),
+        )
+        .route("/stats", get(documented!(get_bridge_stats_handler)))
+        .route(
+            "/stats/relay-costs",
+            get(documented!(get_relay_costs_handler)),
+        )
+        .route("/deposits/{id}", delete(documented!(cancel_deposit)))
+        .route("/deposits/{id}/refresh", post(documented!(refresh_deposit)))
+        .route(
+            "/deposits/{id}/timings",
+            get(documented!(get_deposit_timings)),
+        )
+        .route(
+            "/deposits/by-hash/{commitment_hash}",
+            get(documented!(get_deposit_by_hash)),
+        )
+        .route(
+            "/deposits/{commitment_hash}/events",
+            get(documented!(deposit_status_sse)),
+        )
+        .route(
+            "/ws/deposits/{commitment_hash}",
+            get(documented!(deposit_status_ws)),
         )
-        .route("/deposits", get(fetch_user_deposits_handler))
-        .route("/deposits/latest", get(fetch_user_latest_deposit_handler))
         .route(
             "/withdrawals",
-            post(create_withdrawal).get(get_pending_withdrawals),
+            post(documented!(create_withdrawal)).get(documented!(get_pending_withdrawals)),
+        )
+        .route("/withdrawals/all", get(documented!(get_all_withdrawals)))
+        .route(
+            "/withdrawals/latest",
+            get(documented!(get_latest_withdrawal)),
+        )
+        .route("/withdrawals/{id}", get(documented!(get_withdrawal_status)))
+        .route(
+            "/withdrawals/by-hash/{commitment_hash}",
+            get(documented!(get_withdrawal_status_by_hash)),
+        )
+        .route("/poseidon/hash", post(documented!(compute_poseidon_hash)))
+        .route("/compute-hash", post(documented!(compute_hash_handler)))
+        .route("/nonce/{stark_pub_key}", get(documented!(get_next_nonce)))
+        .route("/tree/root", get(documented!(get_tree_root)))
+        .route(
+            "/tree/proof/{commitment_hash}",
+            get(documented!(get_tree_proof)),
+        )
+        .route("/merkle/roots", get(documented!(get_merkle_roots)))
+        .route(
+            "/merkle/roots/latest",
+            get(documented!(get_latest_merkle_roots)),
         )
-        .route("/withdrawals/all", get(get_all_withdrawals))
-        .route("/withdrawals/latest", get(get_latest_withdrawal))
-        .route("/poseidon/hash", post(compute_poseidon_hash))
-        .route("/compute-hash", post(compute_hash_handler))
+        .route(
+            "/proofs/{deposit_id}/artifacts/{*name}",
+            get(documented!(download_proof_artifact)),
+        )
+        .route("/openapi.json", get(openapi_json));
+    if enable_docs {
+        router = router.route("/docs", get(swagger_ui));
+    }
+
+    router
+        .merge(admin_routes)
         .layer(Extension(pool))
+        .layer(Extension(tree_state))
+        .layer(Extension(admin_token))
+        .layer(Extension(l1_verification))
+        .layer(Extension(limits))
+        .layer(Extension(DepositStatsCache::default()))
+        .layer(Extension(bridge_stats_cache))
+        .layer(Extension(networks))
+        .layer(Extension(withdrawal_domain))
+        .layer(Extension(withdrawal_signatures))
+        .layer(Extension(hash_freshness))
+        .layer(Extension(proof_artifacts))
+        .layer(Extension(event_stream_slots))
+        .layer(Extension(deposit_status_feed))
+        .layer(DefaultBodyLimit::max(body_limit.0))
+        .layer(middleware::from_fn_with_state(body_limit, limit_body_size))
+        .layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(handle_timeout_error))
+                .layer(TimeoutLayer::new(request_timeout)),
+        )
+        .layer(middleware::from_fn_with_state(limiter, rate_limit))
+        .layer(middleware::from_fn(request_id))
 }
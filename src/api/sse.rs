@@ -0,0 +1,137 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use chrono::Utc;
+use futures_util::stream::{self, Stream};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, OwnedSemaphorePermit, Semaphore};
+
+use crate::api::error::ApiError;
+use crate::config::default_max_event_streams;
+use crate::db::database::fetch_deposit_status_by_commitment_hash;
+use crate::events::deposit_status::{
+    is_terminal_deposit_status, next_deposit_status_change, DepositStatusEvent, DepositStatusFeed,
+    DepositStatusUpdate,
+};
+
+/// How often an idle stream sends a comment, so proxies don't close it
+pub const EVENT_STREAM_HEARTBEAT: Duration = Duration::from_secs(15);
+
+/// Name of the events carrying a status change
+pub const STATUS_EVENT: &str = "status";
+/// Name of the last event of a stream, carrying the deposit's terminal status
+pub const TERMINAL_EVENT: &str = "terminal";
+
+/// Deposit status streams that may be open at once, each holding a slot until its
+/// client disconnects or the deposit reaches a terminal status
+#[derive(Debug, Clone)]
+pub struct EventStreamSlots(Arc<Semaphore>);
+
+impl EventStreamSlots {
+    pub fn new(max_streams: usize) -> Self {
+        Self(Arc::new(Semaphore::new(max_streams)))
+    }
+
+    fn acquire(&self) -> Result<OwnedSemaphorePermit, ApiError> {
+        self.0.clone().try_acquire_owned().map_err(|_| {
+            ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "too_many_event_streams",
+                "Too many deposit status streams are open, retry later",
+            )
+        })
+    }
+}
+
+impl Default for EventStreamSlots {
+    fn default() -> Self {
+        Self::new(default_max_event_streams())
+    }
+}
+
+/// Streams the status of a deposit as Server-Sent Events.
+///
+/// The current status is sent as soon as the stream opens, then every change as a
+/// `status` event whose data is a JSON `DepositStatusEvent`. Once the deposit reaches a
+/// terminal status, that status is sent as a `terminal` event and the stream ends.
+///
+/// Changes are read from the database's notifications, so those made by the sequencer
+/// or any other process are streamed too.
+#[utoipa::path(
+    get,
+    path = "/deposits/{commitment_hash}/events",
+    tag = "deposits",
+    params(
+        ("commitment_hash" = String, Path, description = "Commitment hash of the deposit"),
+    ),
+    responses(
+        (status = 200, description = "Stream of status events", body = DepositStatusEvent, content_type = "text/event-stream"),
+        (status = 404, description = "Deposit not found", body = ApiErrorBody),
+        (status = 503, description = "Too many streams are open", body = ApiErrorBody),
+    )
+)]
+pub async fn deposit_status_sse(
+    Path(commitment_hash): Path<String>,
+    Extension(pool): Extension<PgPool>,
+    Extension(slots): Extension<EventStreamSlots>,
+    Extension(feed): Extension<DepositStatusFeed>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    let permit = slots.acquire()?;
+    // Subscribe before reading the status so a change in between is not missed
+    let events = feed.subscribe().await?;
+    let status = fetch_deposit_status_by_commitment_hash(&pool, &commitment_hash)
+        .await?
+        .ok_or_else(|| ApiError::not_found("deposit_not_found", "Deposit not found"))?;
+
+    let stream = StatusStream {
+        pool,
+        events,
+        current: DepositStatusEvent {
+            commitment_hash,
+            old_status: None,
+            new_status: status,
+            timestamp: Utc::now(),
+        },
+        sent_current: false,
+        _permit: permit,
+    };
+    let keep_alive = KeepAlive::new().interval(EVENT_STREAM_HEARTBEAT);
+    Ok(Sse::new(stream.into_events()).keep_alive(keep_alive))
+}
+
+struct StatusStream {
+    pool: PgPool,
+    events: broadcast::Receiver<DepositStatusUpdate>,
+    current: DepositStatusEvent,
+    sent_current: bool,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl StatusStream {
+    fn into_events(self) -> impl Stream<Item = Result<Event, axum::Error>> {
+        stream::unfold(Some(self), |state| async move {
+            let mut state = state?;
+            if state.sent_current {
+                state.current =
+                    next_deposit_status_change(&state.pool, &mut state.events, &state.current)
+                        .await?;
+            }
+            state.sent_current = true;
+
+            let terminal = is_terminal_deposit_status(&state.current.new_status);
+            let name = if terminal {
+                TERMINAL_EVENT
+            } else {
+                STATUS_EVENT
+            };
+            let event = Event::default().event(name).json_data(&state.current);
+            // Ending the stream releases the slot
+            Some((event, (!terminal).then_some(state)))
+        })
+    }
+}
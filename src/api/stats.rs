@@ -0,0 +1,163 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::config::default_stats_cache_ttl_sec;
+use crate::db::database::{BridgeStats, DepositStats};
+
+/// How long `GET /deposits/stats` serves the same aggregates
+pub const DEPOSIT_STATS_TTL: Duration = Duration::from_secs(30);
+
+/// Cache of `GET /deposits/stats`
+pub type DepositStatsCache = StatsCache<DepositStats>;
+
+/// Cache of `GET /stats`, kept for `server.stats_cache_ttl_sec`
+pub type BridgeStatsCache = StatsCache<BridgeStats>;
+
+/// Last aggregates and when they were computed, shared by every request so
+/// dashboards polling an endpoint do not each run the aggregation queries
+#[derive(Clone)]
+pub struct StatsCache<T> {
+    ttl: Duration,
+    entry: Arc<RwLock<Option<(Instant, T)>>>,
+}
+
+impl Default for DepositStatsCache {
+    fn default() -> Self {
+        Self::new(DEPOSIT_STATS_TTL)
+    }
+}
+
+impl Default for BridgeStatsCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(default_stats_cache_ttl_sec()))
+    }
+}
+
+impl<T: Clone> StatsCache<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entry: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Returns the cached stats, calling `load` when they are missing or older than
+    /// the TTL. Failed loads are not cached.
+    pub async fn get_or_load<F, Fut, E>(&self, load: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.get_or_load_at(Instant::now(), load).await
+    }
+
+    async fn get_or_load_at<F, Fut, E>(&self, now: Instant, load: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some(stats) = self.fresh(&*self.entry.read().await, now) {
+            return Ok(stats);
+        }
+
+        let mut entry = self.entry.write().await;
+        // Another request may have refreshed the stats while we waited for the lock
+        if let Some(stats) = self.fresh(&entry, now) {
+            return Ok(stats);
+        }
+
+        let stats = load().await?;
+        *entry = Some((now, stats.clone()));
+        Ok(stats)
+    }
+
+    fn fresh(&self, entry: &Option<(Instant, T)>, now: Instant) -> Option<T> {
+        entry
+            .as_ref()
+            .filter(|(computed_at, _)| now.saturating_duration_since(*computed_at) < self.ttl)
+            .map(|(_, stats)| stats.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::types::BigDecimal;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn stats(pending: i64) -> DepositStats {
+        DepositStats {
+            pending,
+            pending_tree_inclusion: 0,
+            pending_proof_generation: 0,
+            ready_for_relay: 0,
+            failed: 0,
+            cancelled: 0,
+            amount_today: BigDecimal::from(0),
+            avg_proof_generation_secs: None,
+            avg_proof_time_secs: None,
+        }
+    }
+
+    async fn load_counting(
+        cache: &DepositStatsCache,
+        now: Instant,
+        loads: &AtomicUsize,
+    ) -> DepositStats {
+        cache
+            .get_or_load_at(now, || async {
+                let count = loads.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok::<_, ()>(stats(count as i64))
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stats_are_served_from_cache_within_ttl() {
+        let cache = DepositStatsCache::default();
+        let loads = AtomicUsize::new(0);
+        let start = Instant::now();
+
+        assert_eq!(load_counting(&cache, start, &loads).await, stats(1));
+        assert_eq!(
+            load_counting(&cache, start + Duration::from_secs(29), &loads).await,
+            stats(1)
+        );
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stats_are_reloaded_after_ttl() {
+        let cache = DepositStatsCache::default();
+        let loads = AtomicUsize::new(0);
+        let start = Instant::now();
+
+        load_counting(&cache, start, &loads).await;
+        assert_eq!(
+            load_counting(&cache, start + DEPOSIT_STATS_TTL, &loads).await,
+            stats(2)
+        );
+        // The TTL restarts from the reload
+        assert_eq!(
+            load_counting(&cache, start + Duration::from_secs(45), &loads).await,
+            stats(2)
+        );
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_load_is_not_cached() {
+        let cache = DepositStatsCache::default();
+        let loads = AtomicUsize::new(0);
+        let now = Instant::now();
+
+        let result = cache.get_or_load_at(now, || async { Err("db down") }).await;
+        assert_eq!(result, Err("db down"));
+
+        assert_eq!(load_counting(&cache, now, &loads).await, stats(1));
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+}
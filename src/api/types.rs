@@ -0,0 +1,274 @@
+//! Request and response bodies of the sequencer API.
+//!
+//! Shared by the handlers and the [`SequencerClient`](crate::sdk::SequencerClient) so
+//! the two cannot drift apart.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::db::database::{MerkleRoot, Webhook, Withdrawal};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateWithdrawalRequest {
+    pub stark_pub_key: String,
+    pub amount: i64,
+    pub commitment_hash: String,
+    pub l1_token: String,
+    /// Signature by `stark_pub_key` of the burn's signing hash: the EIP-712 hash of
+    /// `BurnData(stark_pub_key, amount, next withdrawal nonce, timestamp)` with its top
+    /// six bits cleared. Required unless the server disables withdrawal signatures.
+    #[serde(default)]
+    pub signature: Option<StarkSignature>,
+    /// Unix timestamp of the burn, defaults to the time of the request. Needed to sign it.
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+}
+
+/// Starknet ECDSA signature with a low `s`, both values `0x`-prefixed hex felts
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StarkSignature {
+    pub r: String,
+    pub s: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DepositRequest {
+    pub stark_pub_key: String,
+    pub amount: i64,
+    pub commitment_hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DepositResponse {
+    pub deposit_id: i32,
+    /// `pending`, or `AWAITING_L1_CONFIRMATION` when the commitment has not been seen on L1
+    pub status: String,
+    /// Average seconds deposits of the last 24 hours took to get their proof, `None` when
+    /// no proof was generated in that time
+    pub estimated_proof_time_secs: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CancelDepositResponse {
+    pub deposit_id: i32,
+    pub old_status: String,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RefreshDepositResponse {
+    pub deposit_id: i32,
+    pub old_status: String,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WithrawalResponse {
+    pub withdrawal_id: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoseidonHashRequest {
+    /// Starknet address of the recipient
+    pub recipient: String,
+    /// USD amount to mint
+    pub amount: u128,
+    /// Transaction nonce
+    pub nonce: u64,
+    /// Block timestamp
+    pub timestamp: u64,
+    /// Optional hash method to use: "batch" or "sequential" (default: "BatchHash")
+    #[serde(default)]
+    pub hash_method: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoseidonHashResponse {
+    pub commitment_hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HashRequest {
+    pub stark_pubkey: String,
+    pub usd_val: u64,
+    pub nonce: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HashResponse {
+    pub commitment_hash: String,
+    pub input_data: InputData,
+}
+
+/// Which of a Stark key's nonces to look up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NonceType {
+    /// Nonce hashed by `POST /poseidon/hash`
+    Deposit,
+    /// Nonce hashed by `POST /compute-hash`
+    Withdrawal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NonceResponse {
+    pub stark_pub_key: String,
+    #[serde(rename = "type")]
+    pub nonce_type: NonceType,
+    /// Nonce the next deposit or withdrawal of the key must be hashed with
+    pub nonce: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InputData {
+    pub stark_pubkey: String,
+    pub usd_val: u64,
+    pub nonce: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TimelineEntry {
+    pub stage: String,
+    pub status: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WithdrawalStatusResponse {
+    pub withdrawal: Withdrawal,
+    pub timeline: Vec<TimelineEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SetBlockTrackerRequest {
+    /// One of the event watchers' tracker keys, e.g. `l1_deposit_events_last_block`
+    pub key: String,
+    /// Last block the watcher should consider processed
+    pub block_number: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TreeRebuildRequest {
+    /// Repair the inconsistent rows instead of only reporting them
+    #[serde(default)]
+    pub fix: bool,
+    /// Deposits checked per batch, 500 when omitted
+    #[serde(default)]
+    pub batch_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FillNonceGapRequest {
+    /// Key whose lowest unused withdrawal nonce is filled
+    pub stark_pub_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FillNonceGapResponse {
+    /// Tombstone withdrawal now taking up the nonce
+    pub withdrawal_id: i32,
+    pub nonce: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ConfigReloadResponse {
+    /// Top-level sections whose settings changed, e.g. `queue`
+    pub changed: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ServiceControlRequest {
+    /// Operator pausing or resuming the service
+    pub updated_by: String,
+    /// Why, shown by `GET /health` while the service is paused
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ServiceHealth {
+    /// Service name, e.g. `proof_client`
+    pub service: String,
+    /// `running` or `paused`
+    pub state: String,
+    /// Why the service was paused
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HealthResponse {
+    /// `ok`, or `paused` while an admin paused some service. Neither is a failure
+    pub status: String,
+    pub services: Vec<ServiceHealth>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// `http` or `https` URL the status changes are POSTed to
+    pub url: String,
+    /// Key of the hex-encoded HMAC-SHA256 of each body sent in `X-Signature`, at least
+    /// 16 characters long
+    pub secret: String,
+    /// `deposit.status_changed` and/or `withdrawal.status_changed`
+    pub event_types: Vec<String>,
+}
+
+/// A registered webhook, without its secret
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WebhookResponse {
+    pub id: i32,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(webhook: Webhook) -> Self {
+        Self {
+            id: webhook.id,
+            url: webhook.url,
+            event_types: webhook.event_types,
+            active: webhook.active,
+            created_at: webhook.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TreeRootResponse {
+    pub root: String,
+    pub leaf_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TreeProofResponse {
+    pub commitment_hash: String,
+    pub leaf_index: usize,
+    pub siblings: Vec<String>,
+    pub peaks: Vec<String>,
+    pub elements_count: usize,
+    pub root: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MerkleRootsResponse {
+    pub elements_count: i64,
+    pub local: Option<MerkleRoot>,
+    pub onchain: Option<MerkleRoot>,
+    /// Whether the local and on-chain roots agree, once both are known
+    pub matches: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LatestMerkleRootsResponse {
+    pub local: Option<MerkleRoot>,
+    pub onchain: Option<MerkleRoot>,
+}
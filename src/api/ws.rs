@@ -0,0 +1,114 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path,
+    },
+    response::Response,
+    Extension,
+};
+use chrono::Utc;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tracing::error;
+
+use crate::api::error::ApiError;
+use crate::db::database::fetch_deposit_status_by_commitment_hash;
+use crate::events::deposit_status::{
+    follow_update, is_terminal_deposit_status, DepositStatusEvent, DepositStatusFeed,
+    DepositStatusUpdate,
+};
+
+/// Streams the status of a deposit over a WebSocket.
+///
+/// The current status is sent as soon as the connection opens, then every change as a
+/// JSON `DepositStatusEvent`. The server closes the socket once the deposit reaches a
+/// terminal status.
+#[utoipa::path(
+    get,
+    path = "/ws/deposits/{commitment_hash}",
+    tag = "deposits",
+    params(
+        ("commitment_hash" = String, Path, description = "Commitment hash of the deposit"),
+    ),
+    responses(
+        (status = 101, description = "Switched to WebSocket, messages are status events", body = DepositStatusEvent),
+        (status = 404, description = "Deposit not found", body = ApiErrorBody),
+    )
+)]
+pub async fn deposit_status_ws(
+    ws: WebSocketUpgrade,
+    Path(commitment_hash): Path<String>,
+    Extension(pool): Extension<PgPool>,
+    Extension(feed): Extension<DepositStatusFeed>,
+) -> Result<Response, ApiError> {
+    // Subscribe before reading the status so a change in between is not missed
+    let events = feed.subscribe().await?;
+    let status = fetch_deposit_status_by_commitment_hash(&pool, &commitment_hash)
+        .await?
+        .ok_or_else(|| ApiError::not_found("deposit_not_found", "Deposit not found"))?;
+
+    Ok(ws.on_upgrade(move |socket| {
+        stream_deposit_status(socket, pool, commitment_hash, status, events)
+    }))
+}
+
+async fn stream_deposit_status(
+    mut socket: WebSocket,
+    pool: PgPool,
+    commitment_hash: String,
+    status: String,
+    mut events: broadcast::Receiver<DepositStatusUpdate>,
+) {
+    let mut event = DepositStatusEvent {
+        commitment_hash,
+        old_status: None,
+        new_status: status,
+        timestamp: Utc::now(),
+    };
+
+    loop {
+        let json = match serde_json::to_string(&event) {
+            Ok(json) => json,
+            Err(e) => {
+                error!("Failed to serialize deposit status event: {}", e);
+                return;
+            }
+        };
+        if socket.send(Message::Text(json.into())).await.is_err() {
+            return;
+        }
+        if is_terminal_deposit_status(&event.new_status) {
+            let _ = socket.send(Message::Close(None)).await;
+            return;
+        }
+
+        event = match next_event(&mut socket, &pool, &mut events, &event).await {
+            Some(next) => next,
+            None => return,
+        };
+    }
+}
+
+/// Waits for the next status change of `current`'s deposit, `None` once the client
+/// disconnects or the status can no longer be followed
+async fn next_event(
+    socket: &mut WebSocket,
+    pool: &PgPool,
+    events: &mut broadcast::Receiver<DepositStatusUpdate>,
+    current: &DepositStatusEvent,
+) -> Option<DepositStatusEvent> {
+    loop {
+        tokio::select! {
+            received = events.recv() => match follow_update(pool, received, current).await {
+                Some(Some(event)) => return Some(event),
+                Some(None) => {}
+                None => return None,
+            },
+            message = socket.recv() => match message {
+                // Pings are answered by axum, other client messages are ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return None,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
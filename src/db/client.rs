@@ -1,8 +1,10 @@
 use anyhow::Result;
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use sqlx::PgPool;
 use std::sync::Arc;
 
 use crate::config::AppConfig;
+use crate::db::database::get_db_pool;
+use crate::db::migrations::MIGRATOR;
 
 pub type DbPool = PgPool;
 
@@ -14,10 +16,7 @@ pub struct DBClient {
 
 impl DBClient {
     pub async fn new(config: &AppConfig) -> Result<Self> {
-        let pool = PgPoolOptions::new()
-            .max_connections(config.database.max_connections)
-            .connect(&config.database.get_db_url())
-            .await?;
+        let pool = get_db_pool(&config.database).await?;
 
         Ok(Self {
             pool: Arc::new(pool),
@@ -25,7 +24,7 @@ impl DBClient {
     }
 
     pub async fn run_migrations(&self) -> Result<()> {
-        sqlx::migrate!("./migrations").run(&*self.pool).await?;
+        MIGRATOR.run(&*self.pool).await?;
         Ok(())
     }
 }
@@ -1,12 +1,20 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::types::BigDecimal;
 use sqlx::{postgres::PgPoolOptions, FromRow, PgConnection, PgPool, Postgres, Transaction};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+use crate::config::{DatabaseConfig, NetworkId};
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct Withdrawal {
     pub id: i32,
     pub stark_pub_key: String,
-    pub amount: i64,
+    /// Serialized as a decimal string, amounts go up to 2^256 - 1
+    #[schema(value_type = String, example = "1000")]
+    pub amount: BigDecimal,
     pub l1_token: String,
     pub l2_tx_id: Option<i32>,
     pub commitment_hash: String,
@@ -17,13 +25,28 @@ pub struct Withdrawal {
     pub retry_count: i32,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    /// Herodotus storage proof of the L2 root, set when the withdrawal leaves
+    /// `AWAITING_STATE_PROOF`
+    #[schema(value_type = Option<Object>)]
+    pub state_proof: Option<serde_json::Value>,
+    /// Configured network the withdrawal was made on
+    pub network: String,
+    /// Leaf of the withdrawal's commitment in the network's L2 tree
+    pub leaf_index: Option<i64>,
+    /// Root of the L2 tree right after the commitment was appended
+    pub merkle_root: Option<String>,
+    /// Inclusion proof of the commitment against `merkle_root`
+    #[schema(value_type = Option<Object>)]
+    pub proof: Option<serde_json::Value>,
 }
 
-#[derive(Debug, FromRow, Serialize, Deserialize)]
+#[derive(Debug, FromRow, Serialize, Deserialize, ToSchema)]
 pub struct Deposit {
     pub id: i32,
     pub stark_pub_key: String,
-    pub amount: i64,
+    /// Serialized as a decimal string, amounts go up to 2^256 - 1
+    #[schema(value_type = String, example = "1000")]
+    pub amount: BigDecimal,
     pub commitment_hash: String,
     pub l2_hash: Option<String>,
     pub nonce: Option<i64>,
@@ -31,6 +54,21 @@ pub struct Deposit {
     pub retry_count: i32,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub leaf_index: Option<i64>,
+    pub merkle_root: Option<String>,
+    pub included_at: Option<DateTime<Utc>>,
+    pub proof_elements_count: Option<i64>,
+    /// Proof client worker generating the deposit's proof, set while `PROOF_IN_PROGRESS`
+    pub claimed_by: Option<String>,
+    /// Last time the claiming worker reported it was still proving the deposit
+    pub claimed_at: Option<DateTime<Utc>>,
+    /// Last Stone pipeline stage completed while proving the deposit
+    pub proof_stage: Option<String>,
+    /// Configured network the deposit was made on
+    pub network: String,
+    /// L1 block that emitted the deposit event, `None` for deposits not seen by the
+    /// L1 watcher
+    pub l1_block_number: Option<i64>,
 }
 
 #[derive(Debug, FromRow, Serialize, Deserialize)]
@@ -42,6 +80,357 @@ pub struct DepositNonce {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DepositAuditLog {
+    pub id: i32,
+    pub deposit_id: i32,
+    pub old_status: String,
+    pub new_status: String,
+    pub reason: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct WithdrawalEvent {
+    pub id: i32,
+    pub withdrawal_id: i32,
+    pub status: String,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct WithdrawalProof {
+    pub id: i32,
+    pub withdrawal_id: Option<i32>,
+    pub proof_params: Option<Vec<u8>>,
+    pub proof_data: Option<Vec<u8>>,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Result of attempting to cancel a deposit with [`soft_delete_deposit`]
+#[derive(Debug)]
+pub enum DepositCancellation {
+    Cancelled(DepositAuditLog),
+    NotFound,
+    /// The deposit has already progressed past a cancellable state
+    InvalidStatus(String),
+}
+
+/// Result of attempting to put an expired deposit back in the queue with
+/// [`refresh_expired_deposit`]
+#[derive(Debug)]
+pub enum DepositRefresh {
+    Refreshed(DepositAuditLog),
+    NotFound,
+    /// The deposit is not `EXPIRED`
+    InvalidStatus(String),
+}
+
+pub const DEPOSIT_STATUS_CANCELLED: &str = "CANCELLED";
+/// Submitted through the API before its commitment hash was seen on L1
+pub const DEPOSIT_STATUS_AWAITING_L1_CONFIRMATION: &str = "AWAITING_L1_CONFIRMATION";
+/// Never matched by an L1 event within the configured TTL, put back in the queue by
+/// `POST /deposits/{id}/refresh`
+pub const DEPOSIT_STATUS_EXPIRED: &str = "EXPIRED";
+/// Seen on L1 with an amount outside the configured limits, kept out of the tree
+/// until an operator reviews it
+pub const DEPOSIT_STATUS_AMOUNT_FLAGGED: &str = "AMOUNT_FLAGGED";
+/// Seen on L1 with a commitment hash at or above the Stark prime, which the L2 tree
+/// cannot hold without reducing it
+pub const DEPOSIT_STATUS_COMMITMENT_FLAGGED: &str = "COMMITMENT_FLAGGED";
+/// Holds the commitment hash of a deposit already in the tree, kept out of it so the
+/// leaf is not appended twice
+pub const DEPOSIT_STATUS_DUPLICATE_COMMITMENT: &str = "DUPLICATE_COMMITMENT";
+/// Holds a commitment hash that is not 32 bytes of hex, taken out of the queue so it
+/// does not hold up the deposits after it
+pub const DEPOSIT_STATUS_INVALID_COMMITMENT: &str = "INVALID_COMMITMENT";
+/// Emitted in an L1 block that was re-orged away, revived if the event shows up again
+pub const DEPOSIT_STATUS_REORGED: &str = "REORGED";
+/// Waiting on a Stone proof
+pub const DEPOSIT_STATUS_PENDING_PROOF_GENERATION: &str = "PENDING_PROOF_GENERATION";
+/// Claimed by a proof client worker that is generating its proof
+pub const DEPOSIT_STATUS_PROOF_IN_PROGRESS: &str = "PROOF_IN_PROGRESS";
+/// Handed over to the Starknet relayer as an `l2_transactions` row
+pub const DEPOSIT_STATUS_QUEUED_FOR_RELAY: &str = "QUEUED_FOR_RELAY";
+
+/// Appended to the network's L2 tree, its inclusion proof stored on the withdrawal
+pub const WITHDRAWAL_STATUS_INCLUDED: &str = "included";
+/// Waiting on a Herodotus storage proof of the L2 root before being relayed to L1
+pub const WITHDRAWAL_STATUS_AWAITING_STATE_PROOF: &str = "AWAITING_STATE_PROOF";
+/// Handed over to the Ethereum relayer with a `withdrawal_proofs` row
+pub const WITHDRAWAL_STATUS_QUEUED_FOR_RELAY: &str = "queued_for_relay";
+/// Withdrawal of nothing taking up the nonce of one that failed or was cancelled, so the
+/// key's nonces stay sequential on L1. Never relayed
+pub const WITHDRAWAL_STATUS_TOMBSTONE: &str = "tombstone";
+
+/// Sent to Starknet, the relayer has not seen it accepted yet
+pub const SUBMITTED_TX_PENDING_CONFIRMATION: &str = "pending_confirmation";
+pub const SUBMITTED_TX_CONFIRMED: &str = "confirmed";
+/// Reverted or never accepted, the L2 transaction may be sent again
+pub const SUBMITTED_TX_FAILED: &str = "failed";
+
+/// Waiting for its next attempt
+pub const WEBHOOK_DELIVERY_PENDING: &str = "pending";
+/// Accepted by the webhook with a 2xx response
+pub const WEBHOOK_DELIVERY_DELIVERED: &str = "delivered";
+/// Given up on after `webhooks.max_attempts` attempts
+pub const WEBHOOK_DELIVERY_FAILED: &str = "failed";
+
+/// Deposits can only be cancelled before they are included in the Merkle tree
+pub fn is_cancellable_deposit_status(status: &str) -> bool {
+    status.eq_ignore_ascii_case("pending")
+        || status == "PENDING_TREE_INCLUSION"
+        || status == DEPOSIT_STATUS_AWAITING_L1_CONFIRMATION
+}
+
+/// Where a recorded Merkle root comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootSource {
+    /// Computed by the sequencer's own tree builder
+    Local,
+    /// Emitted by the L1 contract
+    OnChain,
+}
+
+impl RootSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RootSource::Local => "local",
+            RootSource::OnChain => "onchain",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, ToSchema)]
+pub struct MerkleRoot {
+    pub elements_count: i64,
+    pub root: String,
+    pub block_number: Option<i64>,
+    pub source: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Hash of an L1 block as seen when the deposit watcher moved its cursor to it
+#[derive(Debug, Clone, FromRow)]
+pub struct L1BlockHash {
+    pub block_number: i64,
+    pub block_hash: String,
+}
+
+/// Cursor of an event watcher, the last block it has processed
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, ToSchema)]
+pub struct BlockTracker {
+    pub network: String,
+    pub key: String,
+    pub last_block: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Lowest nonce of a key that no withdrawal takes up, see [`check_nonce_gap`]
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct WithdrawalNonceGap {
+    pub stark_pub_key: String,
+    pub nonce: i64,
+    /// Nonces assigned after `nonce`, whose withdrawals L1 only accepts once it is filled
+    pub waiting: i64,
+}
+
+/// Deposit throughput aggregates served by `GET /deposits/stats`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DepositStats {
+    pub pending: i64,
+    pub pending_tree_inclusion: i64,
+    pub pending_proof_generation: i64,
+    pub ready_for_relay: i64,
+    pub failed: i64,
+    pub cancelled: i64,
+    /// USD amount of the deposits received since midnight, cancelled and failed
+    /// deposits excluded
+    #[schema(value_type = String, example = "1000")]
+    pub amount_today: BigDecimal,
+    /// Average seconds between tree inclusion and the proof being generated, `None`
+    /// until a proof has been generated
+    pub avg_proof_generation_secs: Option<f64>,
+    /// Average seconds from creation to proof of the deposits proven in the last 24
+    /// hours, `None` when there were none
+    pub avg_proof_time_secs: Option<f64>,
+}
+
+/// Bridge-wide aggregates served by `GET /stats`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BridgeStats {
+    pub deposits: TransferStats,
+    pub withdrawals: TransferStats,
+    pub merkle: MerkleStats,
+    /// Latency of each deposit stage over the last 24 hours, by stage
+    pub stage_latencies: BTreeMap<String, StageLatency>,
+}
+
+/// Aggregates of one bridging direction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TransferStats {
+    pub count_by_status: BTreeMap<String, i64>,
+    /// Amount bridged so far, failed, cancelled and expired transfers excluded
+    #[schema(value_type = String, example = "1000")]
+    pub total_amount: BigDecimal,
+    /// Average seconds from creation to `READY_FOR_RELAY`, `None` until one got there
+    pub avg_seconds_to_ready: Option<f64>,
+    /// Average seconds from creation to completion, `None` until one completed
+    pub avg_seconds_to_complete: Option<f64>,
+}
+
+/// Size and root of the L1 deposit tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct MerkleStats {
+    pub leaf_count: i64,
+    /// `None` until the first root is recorded
+    pub latest_root: Option<String>,
+}
+
+/// Fee paid by the relayer account for one completed L2 transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelayCost {
+    pub l2_transaction_id: i64,
+    pub tx_hash: String,
+    pub account_address: String,
+    /// Share of the invoke transaction's fee, in the smallest unit of `fee_unit`
+    pub actual_fee: BigDecimal,
+    /// `WEI` or `FRI`
+    pub fee_unit: String,
+    pub steps: i64,
+    pub l1_gas: i64,
+    pub l1_data_gas: i64,
+}
+
+/// Invoke transaction the Starknet relayer sent for an L2 transaction
+#[derive(Debug, Clone, FromRow)]
+pub struct SubmittedTxHash {
+    pub id: i64,
+    pub l2_transaction_id: i64,
+    pub tx_hash: String,
+    pub status: String,
+    pub submitted_at: DateTime<Utc>,
+    /// Nonce the transaction was signed with, as hex
+    pub nonce: Option<String>,
+}
+
+/// Relay fees of one network paid in one unit on one day, for `GET /stats/relay-costs`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RelayCostSummary {
+    /// UTC day the transactions were completed on
+    pub day: NaiveDate,
+    pub network: String,
+    /// `WEI` or `FRI`
+    pub fee_unit: String,
+    pub transactions: i64,
+    #[schema(value_type = String, example = "4200000000000000")]
+    pub total_fee: BigDecimal,
+    #[schema(value_type = String, example = "1400000000000000")]
+    pub average_fee: BigDecimal,
+}
+
+/// Time a deposit spent in one stage of the pipeline, served by
+/// `GET /deposits/{id}/timings`
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, Deserialize, ToSchema)]
+pub struct DepositTiming {
+    /// `tree_inclusion`, `cairo_inputs`, `scarb_build`, `stone_pipeline`, `calldata`,
+    /// `artifact_persistence`, `relay_queueing`, `relay_submission` or
+    /// `relay_confirmation`
+    pub stage: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: i64,
+    pub success: bool,
+    /// Why the stage failed, `None` when it succeeded
+    pub error: Option<String>,
+}
+
+/// Percentiles of how long a stage took for the deposits that went through it in the
+/// last 24 hours
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StageLatency {
+    /// Successful runs of the stage the percentiles are taken over
+    pub count: i64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// Whether an admin paused a service, see
+/// [`ServicePause`](crate::db::service_controls::ServicePause)
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, Deserialize, ToSchema)]
+pub struct ServiceControl {
+    pub service_name: String,
+    pub paused: bool,
+    /// Why the service was last paused or resumed
+    pub reason: Option<String>,
+    pub updated_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct HerodotusJob {
+    pub id: i32,
+    pub withdrawal_id: i32,
+    pub job_id: String,
+    pub block_number: i64,
+    pub contract_address: String,
+    pub storage_slot: String,
+    /// `pending`, `done` or `failed`
+    pub status: String,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Endpoint notified of status changes, registered through `/admin/webhooks`
+#[derive(Debug, Clone, FromRow)]
+pub struct Webhook {
+    pub id: i32,
+    pub url: String,
+    /// Key of the HMAC-SHA256 signature of every delivery
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One status change to deliver to one webhook, with the outcome of its last attempt
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i32,
+    pub event_type: String,
+    /// Body POSTed to the webhook
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    /// `pending`, `delivered` or `failed`
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    /// HTTP status of the last attempt, `None` when the webhook could not be reached
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Delivery claimed by the webhook dispatcher, with the webhook to send it to
+#[derive(Debug, Clone)]
+pub struct DueWebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i32,
+    pub url: String,
+    pub secret: String,
+    pub payload: serde_json::Value,
+    /// Attempts made, including the one it was claimed for
+    pub attempts: i32,
+}
+
 //Added DepositHashAppended struct with fields matching the event and database schema.
 #[derive(Debug, FromRow, Serialize, Deserialize)]
 pub struct DepositHashAppended {
@@ -58,7 +447,7 @@ pub struct DepositHashAppended {
 pub async fn insert_withdrawal(
     conn: &PgPool,
     stark_pub_key: &str,
-    amount: i64,
+    amount: &BigDecimal,
     commitment_hash: &str,
 ) -> Result<i32, sqlx::Error> {
     let row_id = sqlx::query_scalar!(
@@ -78,10 +467,12 @@ pub async fn insert_withdrawal(
 }
 
 /// Insert a withdrawal with l1_hash and nonce
+#[allow(clippy::too_many_arguments)]
 pub async fn insert_withdrawal_v2(
     conn: &mut Transaction<'_, Postgres>,
+    network: &NetworkId,
     stark_pub_key: &str,
-    amount: i64,
+    amount: &BigDecimal,
     l1_token: &str,
     commitment_hash: &str,
     l1_hash: &str,
@@ -89,8 +480,8 @@ pub async fn insert_withdrawal_v2(
 ) -> Result<i32, sqlx::Error> {
     let row_id = sqlx::query_scalar!(
         r#"
-        INSERT INTO withdrawals (stark_pub_key, amount, l1_token, commitment_hash, l1_hash, nonce, status)
-        VALUES ($1, $2, $3, $4, $5, $6, 'pending')
+        INSERT INTO withdrawals (stark_pub_key, amount, l1_token, commitment_hash, l1_hash, nonce, status, network)
+        VALUES ($1, $2, $3, $4, $5, $6, 'pending', $7)
         RETURNING id
         "#,
         stark_pub_key,
@@ -98,17 +489,53 @@ pub async fn insert_withdrawal_v2(
         l1_token,
         commitment_hash,
         l1_hash,
-        nonce
+        nonce,
+        network.as_str()
     )
     .fetch_one(&mut **conn)
     .await?;
     Ok(row_id)
 }
 
+/// Records a withdrawal seen in an L2 burn event.
+///
+/// A row already created through the API for the same commitment hash keeps its
+/// status and fields; only a missing nonce is filled in.
+pub async fn upsert_withdrawal(
+    conn: &PgPool,
+    network: &NetworkId,
+    stark_pub_key: &str,
+    amount: &BigDecimal,
+    l1_token: &str,
+    commitment_hash: &str,
+    nonce: Option<i64>,
+) -> Result<i32, sqlx::Error> {
+    let row_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO withdrawals (stark_pub_key, amount, l1_token, commitment_hash, nonce, status, network)
+        VALUES ($1, $2, $3, $4, $5, 'pending', $6)
+        ON CONFLICT (commitment_hash) DO UPDATE
+        SET nonce = COALESCE(withdrawals.nonce, EXCLUDED.nonce),
+        updated_at = NOW()
+        RETURNING id
+        "#,
+        stark_pub_key,
+        amount,
+        l1_token,
+        commitment_hash,
+        nonce,
+        network.as_str()
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(row_id)
+}
+
 pub async fn insert_deposit(
     conn: &PgPool,
     stark_pub_key: &str,
-    amount: i64,
+    amount: &BigDecimal,
     commitment_hash: &str,
 ) -> Result<i32, sqlx::Error> {
     let row_id = sqlx::query_scalar!(
@@ -127,48 +554,58 @@ pub async fn insert_deposit(
     Ok(row_id)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn insert_deposit_with_l2_hash(
     tx: &mut Transaction<'_, Postgres>,
+    network: &NetworkId,
     stark_pub_key: &str,
-    amount: i64,
+    amount: &BigDecimal,
     commitment_hash: &str,
     l2_hash: &str,
     nonce: i64,
+    status: &str,
 ) -> Result<i32, sqlx::Error> {
     sqlx::query_scalar!(
         r#"
-        INSERT INTO deposits (stark_pub_key, amount, commitment_hash, l2_hash, nonce, status)
-        VALUES ($1, $2, $3, $4, $5, 'pending')
+        INSERT INTO deposits (stark_pub_key, amount, commitment_hash, l2_hash, nonce, status, network)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
         RETURNING id
         "#,
         stark_pub_key,
         amount,
         commitment_hash,
         l2_hash,
-        nonce
+        nonce,
+        status,
+        network.as_str()
     )
     .fetch_one(&mut **tx)
     .await
 }
 pub async fn upsert_deposit(
     conn: &PgPool,
+    network: &NetworkId,
     stark_pub_key: &str,
-    amount: i64,
+    amount: &BigDecimal,
     commitment_hash: &str,
     status: &str,
+    l1_block_number: Option<i64>,
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
-        INSERT INTO deposits (stark_pub_key, amount, commitment_hash, status)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO deposits (stark_pub_key, amount, commitment_hash, status, network, l1_block_number)
+        VALUES ($1, $2, $3, $4, $5, $6)
         ON CONFLICT (commitment_hash) DO UPDATE
         SET status = EXCLUDED.status,
+        l1_block_number = COALESCE(EXCLUDED.l1_block_number, deposits.l1_block_number),
         updated_at = NOW()
         "#,
         stark_pub_key,
         amount,
         commitment_hash,
         status,
+        network.as_str(),
+        l1_block_number,
     )
     .execute(conn)
     .await?;
@@ -179,19 +616,21 @@ pub async fn upsert_deposit(
 // new function
 pub async fn insert_deposit_hash_event(
     conn: &PgPool,
+    network: &NetworkId,
     event: &DepositHashAppended,
 ) -> Result<i32, sqlx::Error> {
     let row_id = sqlx::query_scalar!(
         r#"
-        INSERT INTO deposit_hashes (index, commitment_hash, root_hash, elements_count, block_number)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO deposit_hashes (index, commitment_hash, root_hash, elements_count, block_number, network)
+        VALUES ($1, $2, $3, $4, $5, $6)
         RETURNING id
         "#,
         event.index,
         event.commitment_hash,
         event.root_hash,
         event.elements_count,
-        event.block_number
+        event.block_number,
+        network.as_str()
     )
     .fetch_one(conn)
     .await?;
@@ -199,19 +638,81 @@ pub async fn insert_deposit_hash_event(
     Ok(row_id)
 }
 
+/// Inserts `events` with a single statement, skipping those already stored for
+/// `network`, and returns the number of rows inserted
+pub async fn insert_deposit_hash_events(
+    conn: &PgPool,
+    network: &NetworkId,
+    events: &[DepositHashAppended],
+) -> Result<u64, sqlx::Error> {
+    let indexes: Vec<i64> = events.iter().map(|event| event.index).collect();
+    let commitment_hashes: Vec<Vec<u8>> = events
+        .iter()
+        .map(|event| event.commitment_hash.clone())
+        .collect();
+    let root_hashes: Vec<Vec<u8>> = events.iter().map(|event| event.root_hash.clone()).collect();
+    let elements_counts: Vec<i64> = events.iter().map(|event| event.elements_count).collect();
+    let block_numbers: Vec<i64> = events.iter().map(|event| event.block_number).collect();
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO deposit_hashes (index, commitment_hash, root_hash, elements_count, block_number, network)
+        SELECT event.index, event.commitment_hash, event.root_hash, event.elements_count, event.block_number, $6
+        FROM UNNEST($1::BIGINT[], $2::BYTEA[], $3::BYTEA[], $4::BIGINT[], $5::BIGINT[])
+            AS event(index, commitment_hash, root_hash, elements_count, block_number)
+        ON CONFLICT (network, commitment_hash, block_number) DO NOTHING
+        "#,
+        &indexes,
+        &commitment_hashes,
+        &root_hashes,
+        &elements_counts,
+        &block_numbers,
+        network.as_str()
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Block number of the `DepositHashAppended` event that appended `commitment_hash` on
+/// `network`'s L1
+pub async fn fetch_deposit_hash_block(
+    conn: &PgPool,
+    network: &NetworkId,
+    commitment_hash: &[u8],
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT block_number
+        FROM deposit_hashes
+        WHERE network = $1 AND commitment_hash = $2
+        ORDER BY block_number ASC
+        LIMIT 1
+        "#,
+        network.as_str(),
+        commitment_hash
+    )
+    .fetch_optional(conn)
+    .await
+}
+
 pub async fn fetch_pending_withdrawals(
     conn: &PgPool,
+    network: &NetworkId,
     max_retries: u32,
 ) -> Result<Vec<Withdrawal>, sqlx::Error> {
     let withdrawals = sqlx::query_as!(
         Withdrawal,
         r#"
         SELECT * FROM withdrawals
-        WHERE status = 'pending'
-        AND retry_count < $1
+        WHERE network = $1
+        AND status = 'pending'
+        AND retry_count < $2
         ORDER BY created_at ASC
         LIMIT 10
         "#,
+        network.as_str(),
         max_retries as i32
     )
     .fetch_all(conn)
@@ -222,6 +723,7 @@ pub async fn fetch_pending_withdrawals(
 
 pub async fn fetch_pending_deposits(
     conn: &PgPool,
+    network: &NetworkId,
     max_retries: u32,
 ) -> Result<Vec<Deposit>, sqlx::Error> {
     let deposits = sqlx::query_as!(
@@ -229,10 +731,11 @@ pub async fn fetch_pending_deposits(
         r#"
         SELECT *
         FROM deposits
-        WHERE status = 'pending' AND retry_count < $1
+        WHERE network = $1 AND status = 'pending' AND retry_count < $2
         ORDER BY created_at ASC
         LIMIT 10
         "#,
+        network.as_str(),
         max_retries as i32
     )
     .fetch_all(conn)
@@ -241,172 +744,1296 @@ pub async fn fetch_pending_deposits(
     Ok(deposits)
 }
 
-pub async fn update_deposit_status(
-    conn: &mut PgConnection,
+/// Claims up to `limit` of `network`'s deposits whose inclusion proof is ready to be
+/// proven with the Stone pipeline, moving them to `PROOF_IN_PROGRESS` under `worker_id`.
+///
+/// Rows locked by another worker's claim are skipped rather than waited on, so
+/// concurrent workers never receive the same deposit.
+pub async fn claim_deposits_for_proof(
+    conn: &PgPool,
+    network: &NetworkId,
+    worker_id: &str,
+    limit: i64,
+    max_retries: u32,
+) -> Result<Vec<Deposit>, sqlx::Error> {
+    let deposits = sqlx::query_as!(
+        Deposit,
+        r#"
+        WITH claimable AS (
+            SELECT id FROM deposits
+            WHERE network = $4
+            AND status = 'PENDING_PROOF_GENERATION'
+            AND retry_count < $3
+            ORDER BY created_at ASC
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE deposits d
+        SET status = 'PROOF_IN_PROGRESS',
+        claimed_by = $1,
+        claimed_at = NOW(),
+        updated_at = NOW()
+        FROM claimable
+        WHERE d.id = claimable.id
+        RETURNING d.*
+        "#,
+        worker_id,
+        limit,
+        max_retries as i32,
+        network.as_str()
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(deposits)
+}
+
+/// Refreshes `worker_id`'s claim on a deposit, returning `false` once the claim was
+/// lost to the janitor or another worker
+pub async fn heartbeat_deposit_claim(
+    conn: &PgPool,
     id: i32,
-    status: &str,
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+    worker_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
         r#"
         UPDATE deposits
-        SET status = $2, updated_at = NOW()
-        WHERE id = $1
+        SET claimed_at = NOW()
+        WHERE id = $1 AND status = 'PROOF_IN_PROGRESS' AND claimed_by = $2
         "#,
         id,
-        status
+        worker_id
     )
     .execute(conn)
     .await?;
 
-    Ok(())
+    Ok(result.rows_affected() > 0)
 }
 
-pub async fn process_deposit_retry(conn: &mut PgConnection, id: i32) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+/// Puts a deposit `worker_id` failed to prove back to `PENDING_PROOF_GENERATION`,
+/// counting the attempt as a retry. Returns `false` if the worker no longer held it.
+pub async fn release_deposit_claim(
+    conn: &mut PgConnection,
+    id: i32,
+    worker_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
         r#"
         UPDATE deposits
-        SET retry_count = retry_count + 1, updated_at = NOW()
-        WHERE id = $1
+        SET status = 'PENDING_PROOF_GENERATION',
+        retry_count = retry_count + 1,
+        claimed_by = NULL,
+        claimed_at = NULL,
+        updated_at = NOW()
+        WHERE id = $1 AND status = 'PROOF_IN_PROGRESS' AND claimed_by = $2
         "#,
-        id
+        id,
+        worker_id
     )
     .execute(conn)
     .await?;
 
-    Ok(())
+    Ok(result.rows_affected() > 0)
 }
 
-pub async fn process_withdrawal_retry(conn: &mut PgConnection, id: i32) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+/// Marks a deposit `worker_id` could not prove as `FAILED`. Returns `false` if the
+/// worker no longer held it, leaving the deposit to its new owner.
+pub async fn fail_deposit_claim(
+    conn: &mut PgConnection,
+    id: i32,
+    worker_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
         r#"
-        UPDATE withdrawals
-        SET retry_count = retry_count + 1,
+        UPDATE deposits
+        SET status = 'FAILED',
+        claimed_by = NULL,
+        claimed_at = NULL,
         updated_at = NOW()
-        WHERE id = $1
+        WHERE id = $1 AND status = 'PROOF_IN_PROGRESS' AND claimed_by = $2
         "#,
-        id
+        id,
+        worker_id
     )
     .execute(conn)
     .await?;
 
-    Ok(())
+    Ok(result.rows_affected() > 0)
 }
 
-// this obtains the most recent with the limit clause
-// in the scond accessor, it'll return all rows
-pub async fn fetch_latest_withdrawal_by_user(
-    pool: &PgPool,
-    identifier: &str,
-) -> Result<Withdrawal, sqlx::Error> {
+/// Fetches up to `limit` of `network`'s deposits waiting to be appended to its L1
+/// Merkle tree, in the order the contract appended them.
+///
+/// Deposits whose `DepositHashAppended` event was not ingested yet are left out. When a
+/// commitment was appended in several blocks, e.g. before and after a reorg, the latest
+/// event counts.
+pub async fn fetch_deposits_with_event_index(
+    conn: &PgPool,
+    network: &NetworkId,
+    limit: i64,
+) -> Result<Vec<EventIndexedDeposit>, sqlx::Error> {
     sqlx::query_as!(
-        Withdrawal,
+        EventIndexedDeposit,
         r#"
-        SELECT * FROM withdrawals
-        WHERE user_address = $1 OR stark_pub_key = $1
-        ORDER BY created_at DESC
-        LIMIT 1
+        SELECT d.id, d.commitment_hash, event.index AS "event_index!"
+        FROM deposits d
+        JOIN LATERAL (
+            SELECT h.index
+            FROM deposit_hashes h
+            WHERE h.network = d.network
+            AND encode(h.commitment_hash, 'hex') = lower(regexp_replace(d.commitment_hash, '^0x', ''))
+            ORDER BY h.block_number DESC
+            LIMIT 1
+        ) event ON TRUE
+        WHERE d.network = $1 AND d.status = 'PENDING_TREE_INCLUSION'
+        ORDER BY event.index ASC, d.id ASC
+        LIMIT $2
         "#,
-        identifier
+        network.as_str(),
+        limit
     )
-    .fetch_one(pool)
+    .fetch_all(conn)
     .await
 }
 
-pub async fn fetch_all_withdrawals_by_user(
-    pool: &PgPool,
-    identifier: &str,
-) -> Result<Vec<Withdrawal>, sqlx::Error> {
+pub async fn fetch_awaiting_l1_confirmation_deposits(
+    conn: &PgPool,
+    network: &NetworkId,
+) -> Result<Vec<Deposit>, sqlx::Error> {
     sqlx::query_as!(
-        Withdrawal,
+        Deposit,
         r#"
-        SELECT * FROM withdrawals
-        WHERE user_address = $1 OR stark_pub_key = $1
-        ORDER BY created_at DESC
+        SELECT *
+        FROM deposits
+        WHERE network = $1 AND status = 'AWAITING_L1_CONFIRMATION'
+        ORDER BY created_at ASC, id ASC
         "#,
-        identifier
+        network.as_str()
     )
-    .fetch_all(pool)
+    .fetch_all(conn)
     .await
 }
 
-pub async fn update_withdrawal_status(
+/// Records a deposit's position in the tree and hands it over to proof generation.
+///
+/// The position is recorded whatever the deposit's status, its leaf is in the tree
+/// either way, but only a deposit still `PENDING_TREE_INCLUSION` is moved to
+/// `PENDING_PROOF_GENERATION`. Returns whether it was.
+pub async fn mark_deposit_included(
     conn: &mut PgConnection,
     id: i32,
-    status: &str,
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+    leaf_index: i64,
+    merkle_root: &str,
+) -> Result<bool, sqlx::Error> {
+    let old_status = sqlx::query_scalar!(
         r#"
-        UPDATE withdrawals
-        SET status = $2,
+        WITH previous AS (
+            SELECT id, status FROM deposits WHERE id = $1 FOR UPDATE
+        )
+        UPDATE deposits d
+        SET status = CASE
+            WHEN previous.status = 'PENDING_TREE_INCLUSION' THEN 'PENDING_PROOF_GENERATION'
+            ELSE d.status
+        END,
+        leaf_index = $2,
+        merkle_root = $3,
+        included_at = NOW(),
         updated_at = NOW()
-        WHERE id = $1
+        FROM previous
+        WHERE d.id = previous.id
+        RETURNING previous.status AS "old_status!"
         "#,
         id,
-        status
+        leaf_index,
+        merkle_root
     )
-    .execute(conn)
+    .fetch_optional(conn)
     .await?;
 
-    Ok(())
+    Ok(old_status.as_deref() == Some("PENDING_TREE_INCLUSION"))
 }
 
-pub async fn update_last_processed_block(
+/// Fetches the commitment hashes of every deposit already in `network`'s tree, in leaf
+/// order
+pub async fn fetch_included_commitments(
     conn: &PgPool,
-    key: &str,
-    block_number: u64,
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+    network: &NetworkId,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
         r#"
-        INSERT INTO block_trackers (key, last_block)
-        VALUES ($1, $2)
-        ON CONFLICT (key) DO UPDATE
-        SET last_block = $2, updated_at = NOW()
+        SELECT commitment_hash
+        FROM deposits
+        WHERE network = $1 AND leaf_index IS NOT NULL
+        ORDER BY leaf_index ASC
         "#,
-        key,
-        block_number as i64
+        network.as_str()
     )
-    .execute(conn)
-    .await?;
-
-    Ok(())
+    .fetch_all(conn)
+    .await
 }
 
-pub async fn get_last_processed_block(
+/// Id of the deposit already in `network`'s tree with `commitment_hash`, compared as
+/// lowercase hex without `0x` so differently written copies of a hash still match
+pub async fn fetch_included_deposit_by_commitment(
     conn: &PgPool,
-    key: &str,
-) -> Result<Option<u64>, sqlx::Error> {
-    let record = sqlx::query!(
+    network: &NetworkId,
+    commitment_hash: &[u8; 32],
+) -> Result<Option<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
         r#"
-        SELECT last_block FROM block_trackers
-        WHERE key = $1
+        SELECT id
+        FROM deposits
+        WHERE network = $1
+        AND leaf_index IS NOT NULL
+        AND lower(regexp_replace(commitment_hash, '^0x', '')) = $2
+        LIMIT 1
         "#,
-        key
+        network.as_str(),
+        hex::encode(commitment_hash)
     )
     .fetch_optional(conn)
-    .await?;
-
-    Ok(record.map(|r| r.last_block as u64))
+    .await
 }
 
-/// Fetches and increments the withdrawal nonce for a user, creating a row if it does not exist
-pub async fn get_and_increment_withdrawal_nonce(
-    conn: &mut Transaction<'_, Postgres>,
-    stark_pub_key: &str,
-) -> Result<i64, sqlx::Error> {
-    // Try to increment and return the new nonce
-    let rec = sqlx::query_scalar!(
+/// Time of the most recent inclusion in `network`'s tree recorded in the database
+pub async fn fetch_last_tree_checkpoint(
+    conn: &PgPool,
+    network: &NetworkId,
+) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar!(
         r#"
-        INSERT INTO withdrawal_nonces (stark_pub_key, nonce, updated_at)
-        VALUES ($1, 1, NOW())
-        ON CONFLICT (stark_pub_key) DO UPDATE
-        SET nonce = withdrawal_nonces.nonce + 1, updated_at = NOW()
-        RETURNING nonce
+        SELECT MAX(included_at)
+        FROM deposits
+        WHERE network = $1
         "#,
-        stark_pub_key
+        network.as_str()
     )
-    .fetch_one(&mut **conn)
-    .await?;
-    Ok(rec)
+    .fetch_one(conn)
+    .await
+}
+
+/// Deposit awaiting tree inclusion, with the leaf index the contract appended its
+/// commitment at
+#[derive(Debug, Clone, FromRow)]
+pub struct EventIndexedDeposit {
+    pub id: i32,
+    pub commitment_hash: String,
+    /// `index` of the deposit's `DepositHashAppended` event
+    pub event_index: i64,
+}
+
+/// Deposit placed in a network's tree, as checked by a tree rebuild
+#[derive(Debug, Clone, FromRow)]
+pub struct IncludedDeposit {
+    pub id: i32,
+    pub commitment_hash: String,
+    pub leaf_index: i64,
+    pub merkle_root: Option<String>,
+}
+
+/// Fetches up to `limit` deposits of `network`'s tree placed after `after_leaf_index`,
+/// in leaf order
+pub async fn fetch_included_deposits_after(
+    conn: &PgPool,
+    network: &NetworkId,
+    after_leaf_index: i64,
+    limit: i64,
+) -> Result<Vec<IncludedDeposit>, sqlx::Error> {
+    sqlx::query_as!(
+        IncludedDeposit,
+        r#"
+        SELECT id, commitment_hash, leaf_index AS "leaf_index!", merkle_root
+        FROM deposits
+        WHERE network = $1 AND leaf_index > $2
+        ORDER BY leaf_index ASC
+        LIMIT $3
+        "#,
+        network.as_str(),
+        after_leaf_index,
+        limit
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Fetches the commitments of `network`'s tree up to `last_leaf_index` included, in
+/// leaf order
+pub async fn fetch_included_commitments_up_to(
+    conn: &PgPool,
+    network: &NetworkId,
+    last_leaf_index: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT commitment_hash
+        FROM deposits
+        WHERE network = $1 AND leaf_index <= $2
+        ORDER BY leaf_index ASC
+        "#,
+        network.as_str(),
+        last_leaf_index
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Corrects where a deposit landed in the tree, leaving its status untouched
+pub async fn repair_deposit_inclusion(
+    conn: &mut PgConnection,
+    id: i32,
+    leaf_index: i64,
+    merkle_root: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET leaf_index = $2, merkle_root = $3, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        leaf_index,
+        merkle_root
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Progress of an interrupted tree rebuild
+#[derive(Debug, Clone, FromRow)]
+pub struct TreeRebuildCheckpoint {
+    pub fix: bool,
+    pub leaves_checked: i64,
+    pub last_leaf_index: i64,
+    /// Discrepancies found so far, as a JSON array
+    pub discrepancies: serde_json::Value,
+}
+
+pub async fn fetch_tree_rebuild_checkpoint(
+    conn: &PgPool,
+    network: &NetworkId,
+) -> Result<Option<TreeRebuildCheckpoint>, sqlx::Error> {
+    sqlx::query_as!(
+        TreeRebuildCheckpoint,
+        r#"
+        SELECT fix, leaves_checked, last_leaf_index, discrepancies
+        FROM tree_rebuild_checkpoints
+        WHERE network = $1
+        "#,
+        network.as_str()
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn save_tree_rebuild_checkpoint(
+    conn: &mut PgConnection,
+    network: &NetworkId,
+    checkpoint: &TreeRebuildCheckpoint,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO tree_rebuild_checkpoints
+            (network, fix, leaves_checked, last_leaf_index, discrepancies)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (network) DO UPDATE
+        SET fix = EXCLUDED.fix,
+        leaves_checked = EXCLUDED.leaves_checked,
+        last_leaf_index = EXCLUDED.last_leaf_index,
+        discrepancies = EXCLUDED.discrepancies,
+        updated_at = NOW()
+        "#,
+        network.as_str(),
+        checkpoint.fix,
+        checkpoint.leaves_checked,
+        checkpoint.last_leaf_index,
+        checkpoint.discrepancies
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_tree_rebuild_checkpoint(
+    conn: &PgPool,
+    network: &NetworkId,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM tree_rebuild_checkpoints WHERE network = $1",
+        network.as_str()
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Records the tree size the deposit's proof was generated against
+pub async fn set_deposit_proof_elements_count(
+    conn: &mut PgConnection,
+    id: i32,
+    elements_count: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET proof_elements_count = $2, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        elements_count
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Records the last Stone pipeline stage completed for the deposit
+pub async fn set_deposit_proof_stage(
+    conn: &PgPool,
+    id: i32,
+    proof_stage: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET proof_stage = $2, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        proof_stage
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Records the root of `network`'s tree holding `elements_count` leaves.
+///
+/// Recording the same size and source again replaces the root, keeping a known block
+/// number when the new one is missing.
+pub async fn insert_merkle_root(
+    conn: &mut PgConnection,
+    network: &NetworkId,
+    elements_count: i64,
+    root: &str,
+    block_number: Option<i64>,
+    source: RootSource,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO merkle_roots (elements_count, root, block_number, source, network)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (network, elements_count, source) DO UPDATE
+        SET root = EXCLUDED.root,
+        block_number = COALESCE(EXCLUDED.block_number, merkle_roots.block_number)
+        "#,
+        elements_count,
+        root,
+        block_number,
+        source.as_str(),
+        network.as_str()
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Fetches every recorded root of `network`'s tree holding `elements_count` leaves, one
+/// per source
+pub async fn get_root_at(
+    conn: &PgPool,
+    network: &NetworkId,
+    elements_count: i64,
+) -> Result<Vec<MerkleRoot>, sqlx::Error> {
+    sqlx::query_as!(
+        MerkleRoot,
+        r#"
+        SELECT elements_count, root, block_number, source, created_at
+        FROM merkle_roots
+        WHERE network = $1 AND elements_count = $2
+        ORDER BY source ASC
+        "#,
+        network.as_str(),
+        elements_count
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Fetches the local roots of `network`'s tree recorded for sizes `from..=to`, keyed by
+/// size
+pub async fn fetch_local_roots_between(
+    conn: &PgPool,
+    network: &NetworkId,
+    from: i64,
+    to: i64,
+) -> Result<BTreeMap<i64, String>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT elements_count, root
+        FROM merkle_roots
+        WHERE network = $1 AND source = 'local' AND elements_count BETWEEN $2 AND $3
+        "#,
+        network.as_str(),
+        from,
+        to
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.elements_count, row.root))
+        .collect())
+}
+
+/// Fetches the root of `network`'s largest tree recorded by each source
+pub async fn fetch_latest_merkle_roots(
+    conn: &PgPool,
+    network: &NetworkId,
+) -> Result<Vec<MerkleRoot>, sqlx::Error> {
+    sqlx::query_as!(
+        MerkleRoot,
+        r#"
+        SELECT DISTINCT ON (source) elements_count, root, block_number, source, created_at
+        FROM merkle_roots
+        WHERE network = $1
+        ORDER BY source ASC, elements_count DESC
+        "#,
+        network.as_str()
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Moves a deposit from `expected` to `status`.
+///
+/// Returns `false`, changing nothing, when the deposit is no longer in `expected`
+/// because another process moved it since it was read. Callers decide what the
+/// conflict means for them instead of overwriting the other change.
+pub async fn update_deposit_status_cas(
+    conn: &mut PgConnection,
+    id: i32,
+    expected: &str,
+    status: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET status = $3, claimed_by = NULL, claimed_at = NULL, updated_at = NOW()
+        WHERE id = $1 AND status = $2
+        "#,
+        id,
+        expected,
+        status
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Sets a deposit's status whatever it currently is.
+///
+/// Only for setting a status by hand: the pipeline moves deposits with
+/// [`update_deposit_status_cas`] so it never overwrites a change it has not seen.
+pub async fn update_deposit_status(
+    conn: &mut PgConnection,
+    id: i32,
+    status: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET status = $2, claimed_by = NULL, claimed_at = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        status
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn process_deposit_retry(conn: &mut PgConnection, id: i32) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET retry_count = retry_count + 1, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn process_withdrawal_retry(conn: &mut PgConnection, id: i32) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE withdrawals
+        SET retry_count = retry_count + 1,
+        updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// this obtains the most recent with the limit clause
+// in the scond accessor, it'll return all rows
+pub async fn fetch_latest_withdrawal_by_user(
+    pool: &PgPool,
+    network: &NetworkId,
+    identifier: &str,
+) -> Result<Withdrawal, sqlx::Error> {
+    sqlx::query_as!(
+        Withdrawal,
+        r#"
+        SELECT * FROM withdrawals
+        WHERE network = $2 AND (user_address = $1 OR stark_pub_key = $1)
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+        identifier,
+        network.as_str()
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn fetch_all_withdrawals_by_user(
+    pool: &PgPool,
+    network: &NetworkId,
+    identifier: &str,
+) -> Result<Vec<Withdrawal>, sqlx::Error> {
+    sqlx::query_as!(
+        Withdrawal,
+        r#"
+        SELECT * FROM withdrawals
+        WHERE network = $2 AND (user_address = $1 OR stark_pub_key = $1)
+        ORDER BY created_at DESC
+        "#,
+        identifier,
+        network.as_str()
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Updates a withdrawal's status and appends the change to its `withdrawal_events` history.
+///
+/// `error` is stored alongside the event, typically when marking the withdrawal as failed.
+pub async fn update_withdrawal_status(
+    conn: &mut PgConnection,
+    id: i32,
+    status: &str,
+    error: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        WITH updated AS (
+            UPDATE withdrawals
+            SET status = $2,
+            updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, status
+        )
+        INSERT INTO withdrawal_events (withdrawal_id, status, error_message)
+        SELECT id, status, $3 FROM updated
+        "#,
+        id,
+        status,
+        error
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Fetches `network`'s pending withdrawals not yet in its L2 tree, oldest first
+pub async fn fetch_withdrawals_pending_tree_inclusion(
+    conn: &PgPool,
+    network: &NetworkId,
+    limit: i64,
+) -> Result<Vec<Withdrawal>, sqlx::Error> {
+    sqlx::query_as!(
+        Withdrawal,
+        r#"
+        SELECT * FROM withdrawals
+        WHERE network = $1 AND status = 'pending' AND leaf_index IS NULL
+        ORDER BY id ASC
+        LIMIT $2
+        "#,
+        network.as_str(),
+        limit
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Fetches the commitment hashes of every withdrawal already in `network`'s L2 tree, in
+/// leaf order
+pub async fn fetch_included_withdrawal_commitments(
+    conn: &PgPool,
+    network: &NetworkId,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT commitment_hash
+        FROM withdrawals
+        WHERE network = $1 AND leaf_index IS NOT NULL
+        ORDER BY leaf_index ASC
+        "#,
+        network.as_str()
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Records a withdrawal's position in the L2 tree with its inclusion proof
+pub async fn mark_withdrawal_included(
+    conn: &mut PgConnection,
+    id: i32,
+    leaf_index: i64,
+    merkle_root: &str,
+    proof: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE withdrawals
+        SET leaf_index = $2,
+        merkle_root = $3,
+        proof = $4,
+        updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        leaf_index,
+        merkle_root,
+        proof
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    update_withdrawal_status(conn, id, WITHDRAWAL_STATUS_INCLUDED, None).await
+}
+
+/// Moves up to `limit` of `network`'s withdrawals included in its L2 tree to
+/// `AWAITING_STATE_PROOF`, oldest first, returning their ids.
+///
+/// The rows stay locked until `conn`'s transaction ends.
+pub async fn await_state_proof_for_included_withdrawals(
+    conn: &mut PgConnection,
+    network: &NetworkId,
+    limit: i64,
+) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        WITH moved AS (
+            UPDATE withdrawals
+            SET status = $3,
+            updated_at = NOW()
+            WHERE id IN (
+                SELECT id FROM withdrawals
+                WHERE network = $1 AND status = $4
+                ORDER BY id ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, status
+        ),
+        events AS (
+            INSERT INTO withdrawal_events (withdrawal_id, status)
+            SELECT id, status FROM moved
+        )
+        SELECT id AS "id!" FROM moved ORDER BY id
+        "#,
+        network.as_str(),
+        limit,
+        WITHDRAWAL_STATUS_AWAITING_STATE_PROOF,
+        WITHDRAWAL_STATUS_INCLUDED
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Fetches the ids of `network`'s withdrawals awaiting a state proof that have no job
+/// in progress
+pub async fn fetch_withdrawals_awaiting_state_proof(
+    conn: &PgPool,
+    network: &NetworkId,
+    max_retries: u32,
+) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT w.id FROM withdrawals w
+        WHERE w.network = $1 AND w.status = 'AWAITING_STATE_PROOF' AND w.retry_count < $2
+        AND NOT EXISTS (
+            SELECT 1 FROM herodotus_jobs j
+            WHERE j.withdrawal_id = w.id AND j.status = 'pending'
+        )
+        ORDER BY w.created_at ASC
+        "#,
+        network.as_str(),
+        max_retries as i32
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn insert_herodotus_job(
+    conn: &mut PgConnection,
+    withdrawal_id: i32,
+    job_id: &str,
+    block_number: i64,
+    contract_address: &str,
+    storage_slot: &str,
+) -> Result<HerodotusJob, sqlx::Error> {
+    sqlx::query_as!(
+        HerodotusJob,
+        r#"
+        INSERT INTO herodotus_jobs (withdrawal_id, job_id, block_number, contract_address, storage_slot)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+        withdrawal_id,
+        job_id,
+        block_number,
+        contract_address,
+        storage_slot
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// Fetches the jobs in progress for `network`'s withdrawals
+pub async fn fetch_pending_herodotus_jobs(
+    conn: &PgPool,
+    network: &NetworkId,
+) -> Result<Vec<HerodotusJob>, sqlx::Error> {
+    sqlx::query_as!(
+        HerodotusJob,
+        r#"
+        SELECT j.* FROM herodotus_jobs j
+        JOIN withdrawals w ON w.id = j.withdrawal_id
+        WHERE w.network = $1 AND j.status = 'pending'
+        ORDER BY j.created_at ASC
+        "#,
+        network.as_str()
+    )
+    .fetch_all(conn)
+    .await
+}
+
+pub async fn fetch_herodotus_jobs(
+    conn: &PgPool,
+    withdrawal_id: i32,
+) -> Result<Vec<HerodotusJob>, sqlx::Error> {
+    sqlx::query_as!(
+        HerodotusJob,
+        r#"
+        SELECT * FROM herodotus_jobs
+        WHERE withdrawal_id = $1
+        ORDER BY created_at ASC
+        "#,
+        withdrawal_id
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Marks a job as done and attaches its proof to the withdrawal, which is then ready
+/// to be relayed
+pub async fn complete_herodotus_job(
+    tx: &mut Transaction<'_, Postgres>,
+    job: &HerodotusJob,
+    proof: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE herodotus_jobs
+        SET status = 'done', updated_at = NOW()
+        WHERE id = $1
+        "#,
+        job.id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE withdrawals
+        SET state_proof = $2
+        WHERE id = $1
+        "#,
+        job.withdrawal_id,
+        proof
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    update_withdrawal_status(tx, job.withdrawal_id, "ready_for_relay", None).await
+}
+
+/// Marks a job as failed and counts the attempt against its withdrawal, returning the
+/// withdrawal's retry count
+pub async fn fail_herodotus_job(
+    tx: &mut Transaction<'_, Postgres>,
+    job: &HerodotusJob,
+    error: &str,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE herodotus_jobs
+        SET status = 'failed', error_message = $2, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        job.id,
+        error
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    increment_withdrawal_retry_count(tx, job.withdrawal_id).await
+}
+
+/// Counts a failed attempt against a withdrawal, returning its new retry count
+pub async fn increment_withdrawal_retry_count(
+    conn: &mut PgConnection,
+    id: i32,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        UPDATE withdrawals
+        SET retry_count = retry_count + 1, updated_at = NOW()
+        WHERE id = $1
+        RETURNING retry_count
+        "#,
+        id
+    )
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn fetch_withdrawal_by_id(
+    pool: &PgPool,
+    id: i32,
+) -> Result<Option<Withdrawal>, sqlx::Error> {
+    sqlx::query_as!(
+        Withdrawal,
+        r#"
+        SELECT * FROM withdrawals
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Current status of the deposit with `commitment_hash`
+pub async fn fetch_deposit_status_by_commitment_hash(
+    pool: &PgPool,
+    commitment_hash: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT status FROM deposits
+        WHERE commitment_hash = $1
+        "#,
+        commitment_hash
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Current status of the deposit with `id`
+pub async fn fetch_deposit_status_by_id(
+    pool: &PgPool,
+    id: i32,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!("SELECT status FROM deposits WHERE id = $1", id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn fetch_deposit_by_commitment_hash(
+    pool: &PgPool,
+    commitment_hash: &str,
+) -> Result<Option<Deposit>, sqlx::Error> {
+    sqlx::query_as!(
+        Deposit,
+        r#"
+        SELECT * FROM deposits
+        WHERE commitment_hash = $1
+        "#,
+        commitment_hash
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn fetch_withdrawal_by_commitment_hash(
+    pool: &PgPool,
+    commitment_hash: &str,
+) -> Result<Option<Withdrawal>, sqlx::Error> {
+    sqlx::query_as!(
+        Withdrawal,
+        r#"
+        SELECT * FROM withdrawals
+        WHERE commitment_hash = $1
+        "#,
+        commitment_hash
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Fetches the status history of a withdrawal, oldest first
+pub async fn fetch_withdrawal_events(
+    pool: &PgPool,
+    withdrawal_id: i32,
+) -> Result<Vec<WithdrawalEvent>, sqlx::Error> {
+    sqlx::query_as!(
+        WithdrawalEvent,
+        r#"
+        SELECT id, withdrawal_id, status, error_message, created_at
+        FROM withdrawal_events
+        WHERE withdrawal_id = $1
+        ORDER BY created_at ASC, id ASC
+        "#,
+        withdrawal_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Fetches the most recently stored proof for a withdrawal
+pub async fn fetch_latest_withdrawal_proof(
+    pool: &PgPool,
+    withdrawal_id: i32,
+) -> Result<Option<WithdrawalProof>, sqlx::Error> {
+    sqlx::query_as!(
+        WithdrawalProof,
+        r#"
+        SELECT * FROM withdrawal_proofs
+        WHERE withdrawal_id = $1
+        ORDER BY created_at DESC, id DESC
+        LIMIT 1
+        "#,
+        withdrawal_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn update_last_processed_block(
+    conn: &PgPool,
+    network: &NetworkId,
+    key: &str,
+    block_number: u64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO block_trackers (network, key, last_block)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (network, key) DO UPDATE
+        SET last_block = $3, updated_at = NOW()
+        "#,
+        network.as_str(),
+        key,
+        block_number as i64
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_last_processed_block(
+    conn: &PgPool,
+    network: &NetworkId,
+    key: &str,
+) -> Result<Option<u64>, sqlx::Error> {
+    let record = sqlx::query!(
+        r#"
+        SELECT last_block FROM block_trackers
+        WHERE network = $1 AND key = $2
+        "#,
+        network.as_str(),
+        key
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(record.map(|r| r.last_block as u64))
+}
+
+/// Records the hash of `network`'s L1 block `block_number`
+pub async fn record_block_hash(
+    conn: &PgPool,
+    network: &NetworkId,
+    block_number: u64,
+    block_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO block_hashes (network, block_number, block_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (network, block_number) DO UPDATE
+        SET block_hash = EXCLUDED.block_hash, created_at = NOW()
+        "#,
+        network.as_str(),
+        block_number as i64,
+        block_hash
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// The `limit` most recent block hashes recorded for `network`, newest first
+pub async fn fetch_recent_block_hashes(
+    conn: &PgPool,
+    network: &NetworkId,
+    limit: i64,
+) -> Result<Vec<L1BlockHash>, sqlx::Error> {
+    sqlx::query_as!(
+        L1BlockHash,
+        r#"
+        SELECT block_number, block_hash FROM block_hashes
+        WHERE network = $1
+        ORDER BY block_number DESC
+        LIMIT $2
+        "#,
+        network.as_str(),
+        limit
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Undoes what `network`'s L1 watcher recorded from `from_block` onwards after a re-org.
+///
+/// Deposits emitted in those blocks are marked `REORGED`, on-chain roots and block
+/// hashes are dropped and the `tracker_key` cursor is moved back so the blocks are
+/// fetched again. Returns the ids of the re-orged deposits.
+pub async fn rollback_l1_blocks(
+    conn: &mut PgConnection,
+    network: &NetworkId,
+    tracker_key: &str,
+    from_block: u64,
+) -> Result<Vec<i32>, sqlx::Error> {
+    let from_block = from_block as i64;
+
+    // Locked, so no deposit can move between being read and re-orged
+    let reorged = sqlx::query!(
+        r#"
+        SELECT id, status FROM deposits
+        WHERE network = $1 AND l1_block_number >= $2 AND status <> $3
+        ORDER BY id
+        FOR UPDATE
+        "#,
+        network.as_str(),
+        from_block,
+        DEPOSIT_STATUS_REORGED
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for deposit in &reorged {
+        update_deposit_status_cas(conn, deposit.id, &deposit.status, DEPOSIT_STATUS_REORGED)
+            .await?;
+    }
+    let reorged: Vec<i32> = reorged.into_iter().map(|deposit| deposit.id).collect();
+
+    sqlx::query!(
+        r#"
+        DELETE FROM merkle_roots
+        WHERE network = $1 AND source = 'onchain' AND block_number >= $2
+        "#,
+        network.as_str(),
+        from_block
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM block_hashes WHERE network = $1 AND block_number >= $2",
+        network.as_str(),
+        from_block
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE block_trackers
+        SET last_block = LEAST(last_block, $3), updated_at = NOW()
+        WHERE network = $1 AND key = $2
+        "#,
+        network.as_str(),
+        tracker_key,
+        (from_block - 1).max(0)
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(reorged)
+}
+
+pub async fn fetch_block_trackers(
+    conn: &PgPool,
+    network: &NetworkId,
+) -> Result<Vec<BlockTracker>, sqlx::Error> {
+    sqlx::query_as!(
+        BlockTracker,
+        r#"
+        SELECT network, key, last_block, updated_at FROM block_trackers
+        WHERE network = $1
+        ORDER BY key
+        "#,
+        network.as_str()
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Fetches and increments the withdrawal nonce for a user, creating a row if it does not exist
+pub async fn get_and_increment_withdrawal_nonce(
+    conn: &mut Transaction<'_, Postgres>,
+    stark_pub_key: &str,
+) -> Result<i64, sqlx::Error> {
+    // Try to increment and return the new nonce
+    let rec = sqlx::query_scalar!(
+        r#"
+        INSERT INTO withdrawal_nonces (stark_pub_key, nonce, updated_at)
+        VALUES ($1, 1, NOW())
+        ON CONFLICT (stark_pub_key) DO UPDATE
+        SET nonce = withdrawal_nonces.nonce + 1, updated_at = NOW()
+        RETURNING nonce
+        "#,
+        stark_pub_key
+    )
+    .fetch_one(&mut **conn)
+    .await?;
+    Ok(rec)
 }
 
 pub async fn get_or_create_nonce(
@@ -418,70 +2045,1400 @@ pub async fn get_or_create_nonce(
     // - subsequent calls increment and return the new value
     let assigned = sqlx::query_scalar!(
         r#"
-        INSERT INTO deposit_nonces (stark_pubkey, current_nonce)
-        VALUES ($1, 0)
-        ON CONFLICT (stark_pubkey) DO UPDATE
-          SET current_nonce = deposit_nonces.current_nonce + 1,
-              updated_at = NOW()
-        RETURNING current_nonce
+        INSERT INTO deposit_nonces (stark_pubkey, current_nonce)
+        VALUES ($1, 0)
+        ON CONFLICT (stark_pubkey) DO UPDATE
+          SET current_nonce = deposit_nonces.current_nonce + 1,
+              updated_at = NOW()
+        RETURNING current_nonce
+        "#,
+        stark_pubkey
+    )
+    .fetch_one(&mut **conn)
+    .await?;
+    Ok(assigned)
+}
+
+/// The nonce [`get_or_create_nonce`] assigns to the next deposit of `stark_pubkey`
+pub async fn fetch_next_deposit_nonce(
+    pool: &PgPool,
+    stark_pubkey: &str,
+) -> Result<i64, sqlx::Error> {
+    let current = sqlx::query_scalar!(
+        "SELECT current_nonce FROM deposit_nonces WHERE stark_pubkey = $1",
+        stark_pubkey
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(current.map_or(0, |nonce| nonce + 1))
+}
+
+/// The nonce [`get_and_increment_withdrawal_nonce`] assigns to the next withdrawal of
+/// `stark_pub_key`
+pub async fn fetch_next_withdrawal_nonce(
+    pool: &PgPool,
+    stark_pub_key: &str,
+) -> Result<i64, sqlx::Error> {
+    let current = sqlx::query_scalar!(
+        "SELECT nonce FROM withdrawal_nonces WHERE stark_pub_key = $1",
+        stark_pub_key
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(current.map_or(1, |nonce| nonce + 1))
+}
+
+/// Lowest nonce assigned to `stark_pub_key` that no withdrawal takes up, because its
+/// withdrawal failed or was cancelled.
+///
+/// L1 accepts a key's withdrawals in nonce order, so the ones after the gap are stuck
+/// until it is filled with [`fill_withdrawal_nonce_gap`].
+pub async fn check_nonce_gap(
+    pool: &PgPool,
+    stark_pub_key: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    lowest_missing_withdrawal_nonce(&mut conn, stark_pub_key).await
+}
+
+async fn lowest_missing_withdrawal_nonce(
+    conn: &mut PgConnection,
+    stark_pub_key: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT MIN(n.nonce) FROM withdrawal_nonces wn
+        CROSS JOIN LATERAL generate_series(1::BIGINT, wn.nonce) AS n(nonce)
+        WHERE wn.stark_pub_key = $1
+        AND NOT EXISTS (
+            SELECT 1 FROM withdrawals w
+            WHERE w.stark_pub_key = wn.stark_pub_key
+            AND w.nonce = n.nonce
+            AND UPPER(w.status) NOT IN ('FAILED', 'CANCELLED')
+        )
+        "#,
+        stark_pub_key
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// The [`check_nonce_gap`] of every key with more than `min_waiting` withdrawals
+/// stuck behind it
+pub async fn fetch_withdrawal_nonce_gaps(
+    pool: &PgPool,
+    min_waiting: i64,
+) -> Result<Vec<WithdrawalNonceGap>, sqlx::Error> {
+    sqlx::query_as!(
+        WithdrawalNonceGap,
+        r#"
+        SELECT
+            wn.stark_pub_key,
+            MIN(n.nonce) AS "nonce!",
+            wn.nonce - MIN(n.nonce) AS "waiting!"
+        FROM withdrawal_nonces wn
+        CROSS JOIN LATERAL generate_series(1::BIGINT, wn.nonce) AS n(nonce)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM withdrawals w
+            WHERE w.stark_pub_key = wn.stark_pub_key
+            AND w.nonce = n.nonce
+            AND UPPER(w.status) NOT IN ('FAILED', 'CANCELLED')
+        )
+        GROUP BY wn.stark_pub_key, wn.nonce
+        HAVING wn.nonce - MIN(n.nonce) > $1
+        ORDER BY wn.stark_pub_key
+        "#,
+        min_waiting
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Takes up the nonce [`check_nonce_gap`] reports for `stark_pub_key` with a
+/// [`WITHDRAWAL_STATUS_TOMBSTONE`] withdrawal, returning its id and nonce, or `None`
+/// when the key has no gap
+pub async fn fill_withdrawal_nonce_gap(
+    pool: &PgPool,
+    network: &NetworkId,
+    stark_pub_key: &str,
+) -> Result<Option<(i32, i64)>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Waits on new withdrawals and other fills of the key, so a gap is filled once
+    sqlx::query!(
+        "SELECT nonce FROM withdrawal_nonces WHERE stark_pub_key = $1 FOR UPDATE",
+        stark_pub_key
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(nonce) = lowest_missing_withdrawal_nonce(&mut tx, stark_pub_key).await? else {
+        return Ok(None);
+    };
+
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO withdrawals (stark_pub_key, amount, l1_token, commitment_hash, nonce, status, network)
+        VALUES ($1, 0, '', $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        stark_pub_key,
+        format!("tombstone:{}:{}", stark_pub_key, nonce),
+        nonce,
+        WITHDRAWAL_STATUS_TOMBSTONE,
+        network.as_str()
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some((id, nonce)))
+}
+
+pub async fn get_user_latest_deposit(
+    conn: &PgPool,
+    network: &NetworkId,
+    addr: &str,
+) -> Result<Option<Deposit>, sqlx::Error> {
+    println!("address gotten is :::{:?}", addr);
+    let deposit = sqlx::query_as!(
+        Deposit,
+        r#"
+            SELECT * 
+            FROM deposits 
+            WHERE stark_pub_key = $1
+            AND network = $2
+            ORDER BY created_at DESC 
+        "#,
+        addr,
+        network.as_str()
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    println!("here is good ::: {:?}", deposit);
+
+    Ok(deposit)
+}
+
+pub async fn get_user_deposits(
+    conn: &PgPool,
+    network: &NetworkId,
+    addr: &str,
+    max_retries: u32,
+) -> Result<Vec<Deposit>, sqlx::Error> {
+    let deposits = sqlx::query_as!(
+        Deposit,
+        r#"
+            SELECT * 
+            FROM deposits 
+            WHERE stark_pub_key = $1
+            AND 
+            retry_count < $2 
+            AND network = $3
+            ORDER BY created_at DESC
+        "#,
+        addr,
+        max_retries as i32,
+        network.as_str()
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(deposits)
+}
+
+/// Marks a deposit as `CANCELLED` and records the change in `deposit_audit_log`
+pub async fn soft_delete_deposit(
+    pool: &PgPool,
+    id: i32,
+    reason: &str,
+) -> Result<DepositCancellation, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let old_status = sqlx::query_scalar!(
+        r#"
+        SELECT status FROM deposits
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let old_status = match old_status {
+        Some(status) => status,
+        None => return Ok(DepositCancellation::NotFound),
+    };
+
+    if !is_cancellable_deposit_status(&old_status) {
+        return Ok(DepositCancellation::InvalidStatus(old_status));
+    }
+
+    update_deposit_status_cas(&mut tx, id, &old_status, DEPOSIT_STATUS_CANCELLED).await?;
+
+    let entry = sqlx::query_as!(
+        DepositAuditLog,
+        r#"
+        INSERT INTO deposit_audit_log (deposit_id, old_status, new_status, reason)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+        id,
+        old_status,
+        DEPOSIT_STATUS_CANCELLED,
+        reason
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(DepositCancellation::Cancelled(entry))
+}
+
+/// Puts an `EXPIRED` deposit back to `pending` with its retries reset, so the L1 queue
+/// looks for its commitment on L1 again, and records the change in `deposit_audit_log`
+pub async fn refresh_expired_deposit(
+    pool: &PgPool,
+    id: i32,
+    reason: &str,
+) -> Result<DepositRefresh, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let deposit = sqlx::query!(
+        r#"
+        SELECT status, commitment_hash FROM deposits
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let deposit = match deposit {
+        Some(deposit) => deposit,
+        None => return Ok(DepositRefresh::NotFound),
+    };
+
+    if deposit.status != DEPOSIT_STATUS_EXPIRED {
+        return Ok(DepositRefresh::InvalidStatus(deposit.status));
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET status = 'pending', retry_count = 0, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let entry = sqlx::query_as!(
+        DepositAuditLog,
+        r#"
+        INSERT INTO deposit_audit_log (deposit_id, old_status, new_status, reason)
+        VALUES ($1, $2, 'pending', $3)
+        RETURNING *
+        "#,
+        id,
+        deposit.status,
+        reason
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(DepositRefresh::Refreshed(entry))
+}
+
+pub async fn fetch_deposit_audit_log(
+    conn: &PgPool,
+    deposit_id: i32,
+) -> Result<Vec<DepositAuditLog>, sqlx::Error> {
+    sqlx::query_as!(
+        DepositAuditLog,
+        r#"
+        SELECT * FROM deposit_audit_log
+        WHERE deposit_id = $1
+        ORDER BY changed_at ASC
+        "#,
+        deposit_id
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Marks a deposit as `READY_FOR_RELAY` once its proof is generated, releasing the
+/// worker's claim and recording the change in `deposit_audit_log` so proof generation
+/// times can be measured.
+///
+/// Only a deposit still `PENDING_PROOF_GENERATION` or `PROOF_IN_PROGRESS` is moved.
+/// Returns whether it was.
+pub async fn mark_deposit_proof_generated(
+    conn: &mut PgConnection,
+    id: i32,
+) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query_scalar!(
+        r#"
+        WITH previous AS (
+            SELECT id, status FROM deposits
+            WHERE id = $1 AND status IN ('PENDING_PROOF_GENERATION', 'PROOF_IN_PROGRESS')
+            FOR UPDATE
+        ),
+        updated AS (
+            UPDATE deposits d
+            SET status = 'READY_FOR_RELAY',
+            claimed_by = NULL,
+            claimed_at = NULL,
+            updated_at = NOW()
+            FROM previous
+            WHERE d.id = previous.id
+            RETURNING d.id, previous.status AS old_status
+        ),
+        logged AS (
+            INSERT INTO deposit_audit_log (deposit_id, old_status, new_status, reason)
+            SELECT id, old_status, 'READY_FOR_RELAY', 'proof generated'
+            FROM updated
+        )
+        SELECT id AS "id!" FROM updated
+        "#,
+        id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(updated.is_some())
+}
+
+/// Fetches up to `limit` of `network`'s deposits whose proof is generated, oldest first
+pub async fn fetch_deposits_ready_for_relay(
+    conn: &PgPool,
+    network: &NetworkId,
+    limit: i64,
+) -> Result<Vec<Deposit>, sqlx::Error> {
+    sqlx::query_as!(
+        Deposit,
+        r#"
+        SELECT * FROM deposits
+        WHERE network = $1 AND status = 'READY_FOR_RELAY'
+        ORDER BY created_at ASC
+        LIMIT $2
+        "#,
+        network.as_str(),
+        limit
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Hands a deposit over to the Starknet relayer: inserts its `ready_for_relay` L2
+/// transaction and moves the deposit to `QUEUED_FOR_RELAY`.
+///
+/// Returns the id of the new L2 transaction, or `None` when the deposit is no longer
+/// `READY_FOR_RELAY` or already has one. The deposit row stays locked until `tx` ends.
+pub async fn queue_deposit_for_relay(
+    tx: &mut Transaction<'_, Postgres>,
+    deposit: &Deposit,
+    token_address: &str,
+    proof_data: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    // Moved first, so a deposit queued or changed by another process is left alone
+    if !update_deposit_status_cas(
+        tx,
+        deposit.id,
+        "READY_FOR_RELAY",
+        DEPOSIT_STATUS_QUEUED_FOR_RELAY,
+    )
+    .await?
+    {
+        return Ok(None);
+    }
+
+    let l2_transaction_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO l2_transactions
+            (deposit_id, network, stark_pub_key, amount, token_address, status, proof_data)
+        VALUES ($1, $2, $3, $4, $5, 'ready_for_relay', $6)
+        ON CONFLICT (deposit_id) DO NOTHING
+        RETURNING id
+        "#,
+        deposit.id,
+        deposit.network,
+        deposit.stark_pub_key,
+        deposit.amount,
+        token_address,
+        proof_data
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(l2_transaction_id)
+}
+
+/// Fetches up to `limit` of `network`'s withdrawals that are ready for relay but not
+/// yet handed to the Ethereum relayer, oldest first
+pub async fn fetch_withdrawals_ready_for_relay(
+    conn: &PgPool,
+    network: &NetworkId,
+    limit: i64,
+) -> Result<Vec<Withdrawal>, sqlx::Error> {
+    sqlx::query_as!(
+        Withdrawal,
+        r#"
+        SELECT * FROM withdrawals
+        WHERE network = $1 AND status = 'ready_for_relay'
+        ORDER BY created_at ASC
+        LIMIT $2
+        "#,
+        network.as_str(),
+        limit
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Hands a withdrawal over to the Ethereum relayer: stores its proof as a `ready`
+/// `withdrawal_proofs` row and moves the withdrawal to `queued_for_relay`.
+///
+/// Returns `false` when the withdrawal is no longer `ready_for_relay` or already has a
+/// proof. The withdrawal row stays locked until `tx` ends.
+pub async fn queue_withdrawal_for_relay(
+    tx: &mut Transaction<'_, Postgres>,
+    withdrawal_id: i32,
+    proof_params: &[u8],
+    proof_data: &[u8],
+) -> Result<bool, sqlx::Error> {
+    let status = sqlx::query_scalar!(
+        "SELECT status FROM withdrawals WHERE id = $1 FOR UPDATE",
+        withdrawal_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+    if status.as_deref() != Some("ready_for_relay") {
+        return Ok(false);
+    }
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO withdrawal_proofs (withdrawal_id, proof_params, proof_data, status)
+        VALUES ($1, $2, $3, 'ready')
+        ON CONFLICT (withdrawal_id) DO NOTHING
+        "#,
+        withdrawal_id,
+        proof_params,
+        proof_data
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    // A withdrawal that already has its proof is only moved along
+    update_withdrawal_status(tx, withdrawal_id, WITHDRAWAL_STATUS_QUEUED_FOR_RELAY, None).await?;
+
+    Ok(inserted.rows_affected() == 1)
+}
+
+/// Aggregates deposit counts by status, today's volume and the average proof
+/// generation time in a single query
+pub async fn get_deposit_stats(pool: &PgPool) -> Result<DepositStats, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE status = 'pending') AS "pending!",
+            COUNT(*) FILTER (WHERE status = 'PENDING_TREE_INCLUSION') AS "pending_tree_inclusion!",
+            COUNT(*) FILTER (
+                WHERE status IN ('PENDING_PROOF_GENERATION', 'PROOF_IN_PROGRESS')
+            ) AS "pending_proof_generation!",
+            COUNT(*) FILTER (WHERE status = 'READY_FOR_RELAY') AS "ready_for_relay!",
+            COUNT(*) FILTER (WHERE status = 'FAILED') AS "failed!",
+            COUNT(*) FILTER (WHERE status = 'CANCELLED') AS "cancelled!",
+            COALESCE(
+                SUM(amount) FILTER (
+                    WHERE created_at >= date_trunc('day', NOW())
+                    AND status NOT IN ('CANCELLED', 'FAILED')
+                ),
+                0
+            ) AS "amount_today!",
+            (
+                SELECT AVG(EXTRACT(EPOCH FROM log.changed_at - d.included_at))::DOUBLE PRECISION
+                FROM deposit_audit_log log
+                JOIN deposits d ON d.id = log.deposit_id
+                WHERE log.old_status IN ('PENDING_PROOF_GENERATION', 'PROOF_IN_PROGRESS')
+                AND log.new_status = 'READY_FOR_RELAY'
+                AND d.included_at IS NOT NULL
+            ) AS avg_proof_generation_secs
+        FROM deposits
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(DepositStats {
+        pending: row.pending,
+        pending_tree_inclusion: row.pending_tree_inclusion,
+        pending_proof_generation: row.pending_proof_generation,
+        ready_for_relay: row.ready_for_relay,
+        failed: row.failed,
+        cancelled: row.cancelled,
+        amount_today: row.amount_today,
+        avg_proof_generation_secs: row.avg_proof_generation_secs,
+        avg_proof_time_secs: get_avg_proof_generation_time(pool).await?,
+    })
+}
+
+/// Average seconds from creation to `READY_FOR_RELAY` of the deposits whose proof was
+/// generated in the last 24 hours, `None` when there were none. New deposits are told
+/// this as their expected wait.
+pub async fn get_avg_proof_generation_time(pool: &PgPool) -> Result<Option<f64>, sqlx::Error> {
+    get_avg_proof_generation_time_at(pool, Utc::now()).await
+}
+
+/// [`get_avg_proof_generation_time`] over the 24 hours before `now`.
+///
+/// Only deposits still `READY_FOR_RELAY` count, as their `updated_at` is when the proof
+/// was generated.
+pub async fn get_avg_proof_generation_time_at(
+    pool: &PgPool,
+    now: DateTime<Utc>,
+) -> Result<Option<f64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT AVG(EXTRACT(EPOCH FROM d.updated_at - d.created_at))::DOUBLE PRECISION
+        FROM deposits d
+        WHERE d.status = 'READY_FOR_RELAY'
+        AND d.updated_at > $1::TIMESTAMPTZ - INTERVAL '24 hours'
+        AND d.updated_at <= $1
+        AND EXISTS (
+            SELECT 1 FROM deposit_audit_log log
+            WHERE log.deposit_id = d.id
+            AND log.old_status IN ('PENDING_PROOF_GENERATION', 'PROOF_IN_PROGRESS')
+            AND log.new_status = 'READY_FOR_RELAY'
+        )
+        "#,
+        now
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Aggregates deposits, withdrawals and the deposit tree for `GET /stats`.
+///
+/// Deposits count as completed once `READY_TO_CLAIM` or `PROCESSED`, statuses they
+/// never leave, so their last update is when they completed. Withdrawals count as
+/// completed once `completed` or `confirmed`, timed from `withdrawal_events`.
+pub async fn get_bridge_stats(pool: &PgPool) -> Result<BridgeStats, sqlx::Error> {
+    let deposit_counts =
+        sqlx::query!(r#"SELECT status, COUNT(*) AS "count!" FROM deposits GROUP BY status"#)
+            .fetch_all(pool)
+            .await?;
+
+    let deposits = sqlx::query!(
+        r#"
+        SELECT
+            COALESCE(
+                SUM(amount) FILTER (
+                    WHERE UPPER(status) NOT IN ('FAILED', 'CANCELLED', 'EXPIRED')
+                ),
+                0
+            ) AS "total_amount!",
+            (
+                SELECT AVG(EXTRACT(EPOCH FROM log.changed_at - d.created_at))::DOUBLE PRECISION
+                FROM deposit_audit_log log
+                JOIN deposits d ON d.id = log.deposit_id
+                WHERE log.new_status = 'READY_FOR_RELAY'
+            ) AS avg_seconds_to_ready,
+            (
+                AVG(EXTRACT(EPOCH FROM updated_at - created_at)) FILTER (
+                    WHERE UPPER(status) IN ('READY_TO_CLAIM', 'PROCESSED')
+                )
+            )::DOUBLE PRECISION AS avg_seconds_to_complete
+        FROM deposits
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let withdrawal_counts =
+        sqlx::query!(r#"SELECT status, COUNT(*) AS "count!" FROM withdrawals GROUP BY status"#)
+            .fetch_all(pool)
+            .await?;
+
+    let withdrawals = sqlx::query!(
+        r#"
+        WITH reached AS (
+            SELECT
+                withdrawal_id,
+                MIN(created_at) FILTER (
+                    WHERE UPPER(status) = 'READY_FOR_RELAY'
+                ) AS ready_at,
+                MIN(created_at) FILTER (
+                    WHERE LOWER(status) IN ('completed', 'confirmed')
+                ) AS completed_at
+            FROM withdrawal_events
+            GROUP BY withdrawal_id
+        )
+        SELECT
+            (
+                SELECT COALESCE(SUM(amount), 0) FROM withdrawals
+                WHERE UPPER(status) NOT IN ('FAILED', 'CANCELLED')
+            ) AS "total_amount!",
+            AVG(
+                EXTRACT(EPOCH FROM r.ready_at - w.created_at::TIMESTAMPTZ)
+            )::DOUBLE PRECISION AS avg_seconds_to_ready,
+            AVG(
+                EXTRACT(EPOCH FROM r.completed_at - w.created_at::TIMESTAMPTZ)
+            )::DOUBLE PRECISION AS avg_seconds_to_complete
+        FROM reached r
+        JOIN withdrawals w ON w.id = r.withdrawal_id
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    // The local tree is the one proofs are generated against, prefer it over the
+    // root seen on chain at the same size
+    let merkle = sqlx::query!(
+        r#"
+        SELECT elements_count, root FROM merkle_roots
+        ORDER BY elements_count DESC, source = 'local' DESC
+        LIMIT 1
+        "#
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(BridgeStats {
+        deposits: TransferStats {
+            count_by_status: deposit_counts
+                .into_iter()
+                .map(|row| (row.status, row.count))
+                .collect(),
+            total_amount: deposits.total_amount,
+            avg_seconds_to_ready: deposits.avg_seconds_to_ready,
+            avg_seconds_to_complete: deposits.avg_seconds_to_complete,
+        },
+        withdrawals: TransferStats {
+            count_by_status: withdrawal_counts
+                .into_iter()
+                .map(|row| (row.status, row.count))
+                .collect(),
+            total_amount: withdrawals.total_amount,
+            avg_seconds_to_ready: withdrawals.avg_seconds_to_ready,
+            avg_seconds_to_complete: withdrawals.avg_seconds_to_complete,
+        },
+        merkle: MerkleStats {
+            leaf_count: merkle.as_ref().map_or(0, |row| row.elements_count),
+            latest_root: merkle.map(|row| row.root),
+        },
+        stage_latencies: get_stage_latencies(pool).await?,
+    })
+}
+
+/// Records the fee paid for relaying an L2 transaction, keeping the first record if
+/// the transaction is recorded twice
+pub async fn insert_relay_cost(
+    pool: &PgPool,
+    network: &NetworkId,
+    cost: &RelayCost,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO relay_costs (
+            l2_transaction_id, network, tx_hash, account_address, actual_fee, fee_unit,
+            steps, l1_gas, l1_data_gas
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (l2_transaction_id) DO NOTHING
+        "#,
+        cost.l2_transaction_id,
+        network.as_str(),
+        cost.tx_hash,
+        cost.account_address,
+        cost.actual_fee,
+        cost.fee_unit,
+        cost.steps,
+        cost.l1_gas,
+        cost.l1_data_gas
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Records that the invoke transaction `tx_hash`, signed with `nonce` and relaying
+/// `l2_transaction_ids`, is about to be sent and awaits confirmation
+pub async fn insert_submitted_tx_hash(
+    pool: &PgPool,
+    l2_transaction_ids: &[i64],
+    tx_hash: &str,
+    nonce: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO submitted_tx_hashes (l2_transaction_id, tx_hash, status, nonce)
+        SELECT id, $2, $3, $4 FROM UNNEST($1::BIGINT[]) AS id
+        "#,
+        l2_transaction_ids,
+        tx_hash,
+        SUBMITTED_TX_PENDING_CONFIRMATION,
+        nonce
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Latest transaction sent for an L2 transaction that is still awaiting confirmation
+pub async fn fetch_pending_submitted_tx_hash(
+    pool: &PgPool,
+    l2_transaction_id: i64,
+) -> Result<Option<SubmittedTxHash>, sqlx::Error> {
+    sqlx::query_as!(
+        SubmittedTxHash,
+        r#"
+        SELECT id, l2_transaction_id, tx_hash, status, submitted_at, nonce
+        FROM submitted_tx_hashes
+        WHERE l2_transaction_id = $1 AND status = $2
+        ORDER BY submitted_at DESC, id DESC
+        LIMIT 1
+        "#,
+        l2_transaction_id,
+        SUBMITTED_TX_PENDING_CONFIRMATION
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Every transaction sent for an L2 transaction that is still awaiting confirmation,
+/// the original and its replacements, oldest first
+pub async fn fetch_pending_submitted_tx_hashes(
+    pool: &PgPool,
+    l2_transaction_id: i64,
+) -> Result<Vec<SubmittedTxHash>, sqlx::Error> {
+    sqlx::query_as!(
+        SubmittedTxHash,
+        r#"
+        SELECT id, l2_transaction_id, tx_hash, status, submitted_at, nonce
+        FROM submitted_tx_hashes
+        WHERE l2_transaction_id = $1 AND status = $2
+        ORDER BY submitted_at ASC, id ASC
         "#,
-        stark_pubkey
+        l2_transaction_id,
+        SUBMITTED_TX_PENDING_CONFIRMATION
     )
-    .fetch_one(&mut **conn)
+    .fetch_all(pool)
+    .await
+}
+
+/// Moves the records of `tx_hash` awaiting confirmation to `status`, for a transaction
+/// the node turned down when it was broadcast
+pub async fn resolve_submitted_tx_hash(
+    pool: &PgPool,
+    tx_hash: &str,
+    status: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE submitted_tx_hashes
+        SET status = $2
+        WHERE tx_hash = $1 AND status = $3
+        "#,
+        tx_hash,
+        status,
+        SUBMITTED_TX_PENDING_CONFIRMATION
+    )
+    .execute(pool)
     .await?;
-    Ok(assigned)
+
+    Ok(result.rows_affected())
 }
 
-pub async fn get_user_latest_deposit(
+/// Moves the transactions awaiting confirmation for `l2_transaction_ids` to `status`,
+/// returning how many were updated
+pub async fn resolve_submitted_tx_hashes(
+    pool: &PgPool,
+    l2_transaction_ids: &[i64],
+    status: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE submitted_tx_hashes
+        SET status = $2
+        WHERE l2_transaction_id = ANY($1) AND status = $3
+        "#,
+        l2_transaction_ids,
+        status,
+        SUBMITTED_TX_PENDING_CONFIRMATION
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Totals and averages the relay fees recorded in `[from, to)` per UTC day, network
+/// and fee unit, oldest day first
+pub async fn get_relay_costs_summary(
+    pool: &PgPool,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<RelayCostSummary>, sqlx::Error> {
+    sqlx::query_as!(
+        RelayCostSummary,
+        r#"
+        SELECT
+            (created_at AT TIME ZONE 'UTC')::DATE AS "day!",
+            network,
+            fee_unit,
+            COUNT(*) AS "transactions!",
+            SUM(actual_fee) AS "total_fee!",
+            AVG(actual_fee) AS "average_fee!"
+        FROM relay_costs
+        WHERE created_at >= $1 AND created_at < $2
+        GROUP BY 1, network, fee_unit
+        ORDER BY 1, network, fee_unit
+        "#,
+        from,
+        to
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Records how long a deposit spent in one stage
+pub async fn insert_deposit_timing(
+    pool: &PgPool,
+    deposit_id: i32,
+    timing: &DepositTiming,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO deposit_timings (
+            deposit_id, stage, started_at, finished_at, duration_ms, success, error
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        deposit_id,
+        timing.stage,
+        timing.started_at,
+        timing.finished_at,
+        timing.duration_ms,
+        timing.success,
+        timing.error
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Timings of a deposit's stages in the order they started
+pub async fn fetch_deposit_timings(
+    pool: &PgPool,
+    deposit_id: i32,
+) -> Result<Vec<DepositTiming>, sqlx::Error> {
+    sqlx::query_as!(
+        DepositTiming,
+        r#"
+        SELECT stage, started_at, finished_at, duration_ms, success, error
+        FROM deposit_timings
+        WHERE deposit_id = $1
+        ORDER BY started_at, id
+        "#,
+        deposit_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// p50 and p95 durations of the successful runs of each stage finished in the last 24
+/// hours
+pub async fn get_stage_latencies(
+    pool: &PgPool,
+) -> Result<BTreeMap<String, StageLatency>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            stage,
+            COUNT(*) AS "count!",
+            PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY duration_ms) AS "p50_ms!",
+            PERCENTILE_CONT(0.95) WITHIN GROUP (ORDER BY duration_ms) AS "p95_ms!"
+        FROM deposit_timings
+        WHERE success AND finished_at > NOW() - INTERVAL '24 hours'
+        GROUP BY stage
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                row.stage,
+                StageLatency {
+                    count: row.count,
+                    p50_ms: row.p50_ms,
+                    p95_ms: row.p95_ms,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Pauses or resumes `service_name`, recording who did it and why
+pub async fn set_service_paused(
+    pool: &PgPool,
+    service_name: &str,
+    paused: bool,
+    reason: Option<&str>,
+    updated_by: &str,
+) -> Result<ServiceControl, sqlx::Error> {
+    sqlx::query_as!(
+        ServiceControl,
+        r#"
+        INSERT INTO service_controls (service_name, paused, reason, updated_by, updated_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        ON CONFLICT (service_name) DO UPDATE
+        SET paused = EXCLUDED.paused,
+            reason = EXCLUDED.reason,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        RETURNING service_name, paused, reason, updated_by, updated_at
+        "#,
+        service_name,
+        paused,
+        reason,
+        updated_by
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Pause state of `service_name`, `None` if it was never paused
+pub async fn fetch_service_control(
+    pool: &PgPool,
+    service_name: &str,
+) -> Result<Option<ServiceControl>, sqlx::Error> {
+    sqlx::query_as!(
+        ServiceControl,
+        r#"
+        SELECT service_name, paused, reason, updated_by, updated_at
+        FROM service_controls
+        WHERE service_name = $1
+        "#,
+        service_name
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Pause state of every service that was ever paused, by name
+pub async fn fetch_service_controls(pool: &PgPool) -> Result<Vec<ServiceControl>, sqlx::Error> {
+    sqlx::query_as!(
+        ServiceControl,
+        r#"
+        SELECT service_name, paused, reason, updated_by, updated_at
+        FROM service_controls
+        ORDER BY service_name
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn fetch_deposit_statuses(
     conn: &PgPool,
-    addr: &str,
-) -> Result<Option<Deposit>, sqlx::Error> {
-    println!("address gotten is :::{:?}", addr);
-    let deposit = sqlx::query_as!(
-        Deposit,
+    ids: &[i32],
+) -> Result<Vec<(i32, String)>, sqlx::Error> {
+    let rows = sqlx::query!(
         r#"
-            SELECT * 
-            FROM deposits 
-            WHERE stark_pub_key = $1
-            ORDER BY created_at DESC 
+        SELECT id, status FROM deposits
+        WHERE id = ANY($1)
         "#,
-        addr
+        ids
     )
-    .fetch_optional(conn)
+    .fetch_all(conn)
     .await?;
 
-    println!("here is good ::: {:?}", deposit);
+    Ok(rows.into_iter().map(|row| (row.id, row.status)).collect())
+}
 
-    Ok(deposit)
+/// Puts deposits that have been `processing` for more than `stale_after_secs` back to
+/// `pending` and counts the attempt as a retry, returning their ids.
+pub async fn reset_stale_processing_deposits(
+    conn: &PgPool,
+    stale_after_secs: i64,
+) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        UPDATE deposits
+        SET status = 'pending',
+        retry_count = retry_count + 1,
+        updated_at = NOW()
+        WHERE status = 'processing'
+        AND updated_at < NOW() - $1::BIGINT * INTERVAL '1 second'
+        RETURNING id
+        "#,
+        stale_after_secs
+    )
+    .fetch_all(conn)
+    .await
 }
 
-pub async fn get_user_deposits(
+/// Puts deposits whose proof client worker stopped sending heartbeats more than
+/// `stale_after_secs` ago back to `PENDING_PROOF_GENERATION`, counting the attempt as a
+/// retry. Returns their ids.
+pub async fn reclaim_abandoned_proof_claims(
     conn: &PgPool,
-    addr: &str,
-    max_retries: u32,
-) -> Result<Vec<Deposit>, sqlx::Error> {
-    let deposits = sqlx::query_as!(
-        Deposit,
+    stale_after_secs: i64,
+) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
         r#"
-            SELECT * 
-            FROM deposits 
-            WHERE stark_pub_key = $1
-            AND 
-            retry_count < $2 
-            ORDER BY created_at DESC
+        UPDATE deposits
+        SET status = 'PENDING_PROOF_GENERATION',
+        retry_count = retry_count + 1,
+        claimed_by = NULL,
+        claimed_at = NULL,
+        updated_at = NOW()
+        WHERE status = 'PROOF_IN_PROGRESS'
+        AND claimed_at < NOW() - $1::BIGINT * INTERVAL '1 second'
+        RETURNING id
         "#,
-        addr,
-        max_retries as i32
+        stale_after_secs
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Expires deposits created more than `older_than_hours` ago that are still waiting in
+/// `pending` or `PENDING_TREE_INCLUSION` although no `DepositHashAppended` event of
+/// their commitment was ever ingested, recording `reason` in `deposit_audit_log`.
+/// Returns their ids.
+///
+/// Deposits with an event are never expired, however long they have been waiting.
+pub async fn expire_unseen_deposits(
+    conn: &PgPool,
+    older_than_hours: i64,
+    reason: &str,
+) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        WITH previous AS (
+            SELECT d.id, d.status FROM deposits d
+            WHERE d.status IN ('pending', 'PENDING_TREE_INCLUSION')
+            AND d.created_at < NOW() - $1::BIGINT * INTERVAL '1 hour'
+            AND d.l1_block_number IS NULL
+            AND NOT EXISTS (
+                SELECT 1 FROM deposit_hashes dh
+                WHERE dh.network = d.network
+                AND encode(dh.commitment_hash, 'hex')
+                    = lower(regexp_replace(d.commitment_hash, '^0x', ''))
+            )
+            FOR UPDATE OF d SKIP LOCKED
+        ),
+        expired AS (
+            UPDATE deposits d
+            SET status = 'EXPIRED', updated_at = NOW()
+            FROM previous
+            WHERE d.id = previous.id
+            RETURNING d.id, previous.status AS old_status
+        ),
+        logged AS (
+            INSERT INTO deposit_audit_log (deposit_id, old_status, new_status, reason)
+            SELECT id, old_status, 'EXPIRED', $2
+            FROM expired
+        )
+        SELECT id AS "id!" FROM expired
+        "#,
+        older_than_hours,
+        reason
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Puts withdrawals that have been `processing` for more than `stale_after_secs` back to
+/// `pending`, counting the attempt as a retry and recording the change in
+/// `withdrawal_events`. Returns their ids.
+pub async fn reset_stale_processing_withdrawals(
+    conn: &PgPool,
+    stale_after_secs: i64,
+) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        WITH reset AS (
+            UPDATE withdrawals
+            SET status = 'pending',
+            retry_count = retry_count + 1,
+            updated_at = NOW()
+            WHERE status = 'processing'
+            AND updated_at < NOW() - $1::BIGINT * INTERVAL '1 second'
+            RETURNING id, status
+        )
+        INSERT INTO withdrawal_events (withdrawal_id, status, error_message)
+        SELECT id, status, 'Reset after being stuck in processing' FROM reset
+        RETURNING withdrawal_id
+        "#,
+        stale_after_secs
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Puts L2 transactions that have been `processing` for more than `stale_after_secs`
+/// back to `ready_for_relay` and counts the attempt as a retry, returning their ids.
+pub async fn reset_stale_processing_l2_transactions(
+    conn: &PgPool,
+    stale_after_secs: i64,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        UPDATE l2_transactions
+        SET status = 'ready_for_relay',
+        retry_count = retry_count + 1,
+        updated_at = NOW()
+        WHERE status = 'processing'
+        AND updated_at < NOW() - $1::BIGINT * INTERVAL '1 second'
+        RETURNING id
+        "#,
+        stale_after_secs
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Drops the proof data of L2 transactions that failed more than `older_than_days` ago,
+/// returning their ids.
+pub async fn clear_failed_l2_transaction_proofs(
+    conn: &PgPool,
+    older_than_days: i64,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        UPDATE l2_transactions
+        SET proof_data = NULL
+        WHERE status = 'failed'
+        AND proof_data IS NOT NULL
+        AND updated_at < NOW() - $1::BIGINT * INTERVAL '1 day'
+        RETURNING id
+        "#,
+        older_than_days
     )
     .fetch_all(conn)
+    .await
+}
+
+/// Registers a webhook notified of the status changes of `event_types`
+pub async fn insert_webhook(
+    pool: &PgPool,
+    url: &str,
+    secret: &str,
+    event_types: &[String],
+) -> Result<Webhook, sqlx::Error> {
+    sqlx::query_as!(
+        Webhook,
+        r#"
+        INSERT INTO webhooks (url, secret, event_types)
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#,
+        url,
+        secret,
+        event_types
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Lists the registered webhooks, oldest first
+pub async fn fetch_webhooks(pool: &PgPool) -> Result<Vec<Webhook>, sqlx::Error> {
+    sqlx::query_as!(Webhook, "SELECT * FROM webhooks ORDER BY id ASC")
+        .fetch_all(pool)
+        .await
+}
+
+/// Deletes a webhook along with its deliveries, returning it unless it did not exist
+pub async fn delete_webhook(pool: &PgPool, id: i32) -> Result<Option<Webhook>, sqlx::Error> {
+    sqlx::query_as!(
+        Webhook,
+        "DELETE FROM webhooks WHERE id = $1 RETURNING *",
+        id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Latest `limit` deliveries of a webhook, newest first
+pub async fn fetch_webhook_deliveries(
+    pool: &PgPool,
+    webhook_id: i32,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+    sqlx::query_as!(
+        WebhookDelivery,
+        r#"
+        SELECT * FROM webhook_deliveries
+        WHERE webhook_id = $1
+        ORDER BY id DESC
+        LIMIT $2
+        "#,
+        webhook_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Claims up to `limit` due deliveries of active webhooks, counting the attempt and
+/// pushing `next_attempt_at` `lease_ms` ahead.
+///
+/// Rows locked by another dispatcher are skipped, and the lease keeps them from being
+/// claimed again while being sent. A delivery whose attempt is never recorded, e.g.
+/// because the dispatcher stopped, is claimed again once its lease runs out.
+pub async fn claim_due_webhook_deliveries(
+    pool: &PgPool,
+    limit: i64,
+    lease_ms: i64,
+) -> Result<Vec<DueWebhookDelivery>, sqlx::Error> {
+    sqlx::query_as!(
+        DueWebhookDelivery,
+        r#"
+        WITH due AS (
+            SELECT d.id FROM webhook_deliveries d
+            JOIN webhooks w ON w.id = d.webhook_id
+            WHERE d.status = $3 AND d.next_attempt_at <= NOW() AND w.active
+            ORDER BY d.next_attempt_at ASC, d.id ASC
+            LIMIT $1
+            FOR UPDATE OF d SKIP LOCKED
+        ),
+        claimed AS (
+            UPDATE webhook_deliveries d
+            SET attempts = d.attempts + 1,
+            next_attempt_at = NOW() + $2::BIGINT * INTERVAL '1 millisecond',
+            updated_at = NOW()
+            FROM due
+            WHERE d.id = due.id
+            RETURNING d.id, d.webhook_id, d.payload, d.attempts
+        )
+        SELECT
+            c.id AS "id!",
+            c.webhook_id AS "webhook_id!",
+            w.url AS "url!",
+            w.secret AS "secret!",
+            c.payload AS "payload!",
+            c.attempts AS "attempts!"
+        FROM claimed c
+        JOIN webhooks w ON w.id = c.webhook_id
+        ORDER BY c.id ASC
+        "#,
+        limit,
+        lease_ms,
+        WEBHOOK_DELIVERY_PENDING
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Records that the webhook accepted a delivery with `response_status`
+pub async fn mark_webhook_delivered(
+    pool: &PgPool,
+    id: i64,
+    response_status: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = $3, response_status = $2, last_error = NULL,
+        delivered_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        response_status,
+        WEBHOOK_DELIVERY_DELIVERED
+    )
+    .execute(pool)
     .await?;
 
-    Ok(deposits)
+    Ok(())
+}
+
+/// Records a failed attempt of a delivery, which is attempted again in `retry_in_ms`
+pub async fn retry_webhook_delivery(
+    pool: &PgPool,
+    id: i64,
+    response_status: Option<i32>,
+    error: &str,
+    retry_in_ms: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE webhook_deliveries
+        SET response_status = $2, last_error = $3,
+        next_attempt_at = NOW() + $4::BIGINT * INTERVAL '1 millisecond',
+        updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        response_status,
+        error,
+        retry_in_ms
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Records the last failed attempt of a delivery, which is not attempted again
+pub async fn fail_webhook_delivery(
+    pool: &PgPool,
+    id: i64,
+    response_status: Option<i32>,
+    error: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = $4, response_status = $2, last_error = $3, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        response_status,
+        error,
+        WEBHOOK_DELIVERY_FAILED
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
 }
 
-pub async fn get_db_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
+/// Pool settings from `[database]`.
+///
+/// Connections are checked before being handed out, so one the server or network
+/// dropped while idle is replaced instead of failing the query that drew it.
+pub fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
     PgPoolOptions::new()
-        .max_connections(10)
-        .connect(database_url)
-        .await
+        .max_connections(config.max_connections)
+        .acquire_timeout(Duration::from_secs(config.connection_timeout_secs))
+        .idle_timeout(Duration::from_secs(config.idle_timeout_secs))
+        .test_before_acquire(true)
+}
+
+pub async fn get_db_pool(config: &DatabaseConfig) -> Result<PgPool, sqlx::Error> {
+    pool_options(config).connect(&config.get_db_url()).await
+}
+
+/// Round trip to the database, failing once no connection can be acquired within
+/// `database.connection_timeout_secs`
+pub async fn ping_db(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT 1").execute(pool).await?;
+    Ok(())
 }
@@ -0,0 +1,71 @@
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Migrations embedded from `./migrations` at build time
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Migration error: {0}")]
+    Migrate(#[from] MigrateError),
+
+    #[error("Database is missing migrations: {}", .0.join(", "))]
+    MissingMigrations(Vec<String>),
+}
+
+/// Verifies the database schema is up to date before services start, so a database
+/// behind the binary fails fast instead of with `ColumnNotFound` errors at runtime
+pub struct DatabaseMigrationValidator;
+
+impl DatabaseMigrationValidator {
+    /// Checks every embedded migration has been applied to `pool`'s database
+    pub async fn check(pool: &PgPool) -> Result<(), MigrationError> {
+        Self::check_against(pool, &MIGRATOR).await
+    }
+
+    /// Checks every migration of `migrator` has been applied, reporting the missing
+    /// ones by file name (`<version>_<description>`) in version order
+    pub async fn check_against(pool: &PgPool, migrator: &Migrator) -> Result<(), MigrationError> {
+        let mut conn = pool.acquire().await?;
+
+        // A database never migrated has no table to list from, and misses everything
+        let has_migrations_table = sqlx::query_scalar!(
+            r#"SELECT to_regclass('_sqlx_migrations') IS NOT NULL AS "exists!""#
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        let applied: HashSet<i64> = if has_migrations_table {
+            conn.list_applied_migrations()
+                .await?
+                .into_iter()
+                .map(|migration| migration.version)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        let missing: Vec<String> = migrator
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .filter(|migration| !applied.contains(&migration.version))
+            .map(|migration| {
+                format!(
+                    "{}_{}",
+                    migration.version,
+                    migration.description.replace(' ', "_")
+                )
+            })
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MigrationError::MissingMigrations(missing))
+        }
+    }
+}
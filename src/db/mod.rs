@@ -1,2 +1,5 @@
 pub mod client;
 pub mod database;
+pub mod migrations;
+pub mod service_controls;
+pub mod timings;
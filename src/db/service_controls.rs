@@ -0,0 +1,143 @@
+//! Pausing individual services without stopping the sequencer, e.g. the Starknet
+//! relayer during a bad L2 contract upgrade while proofs keep being generated.
+//!
+//! Admins pause and resume services through `POST /admin/services/{name}/pause` and
+//! `/resume`, which write `service_controls`. Each service holds a [`ServicePause`] and
+//! skips its work for as long as it reports the service as paused.
+
+use sqlx::PgPool;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::db::database::fetch_service_control;
+
+/// How long a service goes on with the pause state it last read, so its loop does not
+/// query `service_controls` on every tick
+pub const PAUSE_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Long-running services an admin can pause
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineService {
+    TreeBuilder,
+    ProofClient,
+    L1Queue,
+    L2Queue,
+    WithdrawalQueue,
+    StarknetRelayer,
+    EthereumRelayer,
+}
+
+impl PipelineService {
+    pub const ALL: [PipelineService; 7] = [
+        PipelineService::TreeBuilder,
+        PipelineService::ProofClient,
+        PipelineService::L1Queue,
+        PipelineService::L2Queue,
+        PipelineService::WithdrawalQueue,
+        PipelineService::StarknetRelayer,
+        PipelineService::EthereumRelayer,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PipelineService::TreeBuilder => "tree_builder",
+            PipelineService::ProofClient => "proof_client",
+            PipelineService::L1Queue => "l1_queue",
+            PipelineService::L2Queue => "l2_queue",
+            PipelineService::WithdrawalQueue => "withdrawal_queue",
+            PipelineService::StarknetRelayer => "starknet_relayer",
+            PipelineService::EthereumRelayer => "ethereum_relayer",
+        }
+    }
+
+    /// The service named `name` in `service_controls`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|service| service.as_str() == name)
+    }
+}
+
+#[derive(Debug, Default)]
+struct PauseState {
+    paused: bool,
+    checked_at: Option<Instant>,
+}
+
+/// Pause state of one service, read from `service_controls` at most once per
+/// [`PAUSE_CACHE_TTL`].
+///
+/// Pausing and resuming are logged once, by the first check seeing the change. A
+/// failed read keeps the state last read, so a database outage neither pauses nor
+/// resumes anything.
+#[derive(Debug)]
+pub struct ServicePause {
+    db_pool: PgPool,
+    service: PipelineService,
+    state: Mutex<PauseState>,
+}
+
+impl ServicePause {
+    pub fn new(db_pool: PgPool, service: PipelineService) -> Self {
+        Self {
+            db_pool,
+            service,
+            state: Mutex::new(PauseState::default()),
+        }
+    }
+
+    /// Whether the service should skip this iteration of its loop
+    pub async fn is_paused(&self) -> bool {
+        {
+            let state = self.state.lock().unwrap();
+            if state
+                .checked_at
+                .is_some_and(|checked_at| checked_at.elapsed() < PAUSE_CACHE_TTL)
+            {
+                return state.paused;
+            }
+        }
+
+        let control = match fetch_service_control(&self.db_pool, self.service.as_str()).await {
+            Ok(control) => control,
+            Err(e) => {
+                warn!(
+                    "Failed to read whether {} is paused: {}",
+                    self.service.as_str(),
+                    e
+                );
+                return self.state.lock().unwrap().paused;
+            }
+        };
+        let paused = control.as_ref().is_some_and(|control| control.paused);
+
+        let mut state = self.state.lock().unwrap();
+        if paused != state.paused {
+            let reason = control
+                .and_then(|control| control.reason)
+                .unwrap_or_else(|| "no reason given".to_string());
+            if paused {
+                warn!("{} paused: {}", self.service.as_str(), reason);
+            } else {
+                info!("{} resumed: {}", self.service.as_str(), reason);
+            }
+        }
+        state.paused = paused;
+        state.checked_at = Some(Instant::now());
+        paused
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_names_round_trip() {
+        for service in PipelineService::ALL {
+            assert_eq!(PipelineService::from_name(service.as_str()), Some(service));
+        }
+        assert_eq!(PipelineService::from_name("oracle"), None);
+    }
+}
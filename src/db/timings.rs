@@ -0,0 +1,155 @@
+//! How long each stage of a deposit took, recorded in `deposit_timings` so a slow
+//! deposit can be traced to the stage it spent its time in.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Instant;
+use tokio::runtime::Handle;
+use tracing::warn;
+
+use crate::db::database::{insert_deposit_timing, DepositTiming};
+
+/// Error recorded for a stage whose timer was dropped before it finished, because the
+/// stage returned early, panicked or was cancelled
+pub const STAGE_INTERRUPTED: &str = "Stage ended before recording an outcome";
+
+/// Stages of a deposit's way through the sequencer, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DepositStage {
+    /// Appending the commitment to the deposit tree
+    TreeInclusion,
+    /// Generating the program arguments of the deposit's proof
+    CairoInputs,
+    /// Compiling the Cairo program with Scarb
+    ScarbBuild,
+    /// Running the program and proving it with Stone
+    StonePipeline,
+    /// Splitting the proof into calldata
+    Calldata,
+    /// Saving the proof and calldata to the artifact store
+    ArtifactPersistence,
+    /// Queueing the deposit's L2 transaction
+    RelayQueueing,
+    /// Sending the L2 transaction to Starknet
+    RelaySubmission,
+    /// Waiting for Starknet to accept the L2 transaction
+    RelayConfirmation,
+}
+
+impl DepositStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DepositStage::TreeInclusion => "tree_inclusion",
+            DepositStage::CairoInputs => "cairo_inputs",
+            DepositStage::ScarbBuild => "scarb_build",
+            DepositStage::StonePipeline => "stone_pipeline",
+            DepositStage::Calldata => "calldata",
+            DepositStage::ArtifactPersistence => "artifact_persistence",
+            DepositStage::RelayQueueing => "relay_queueing",
+            DepositStage::RelaySubmission => "relay_submission",
+            DepositStage::RelayConfirmation => "relay_confirmation",
+        }
+    }
+}
+
+/// Times one stage of a deposit from [`StageTimer::start`] until its outcome is
+/// recorded.
+///
+/// The timing is written in the background, so the stage never waits on it and a
+/// failed write is only logged. A timer dropped without an outcome records the stage
+/// as failed with [`STAGE_INTERRUPTED`]; call [`StageTimer::discard`] for a stage that
+/// turned out not to run.
+pub struct StageTimer {
+    db_pool: PgPool,
+    deposit_id: i32,
+    stage: DepositStage,
+    started_at: DateTime<Utc>,
+    started: Instant,
+    finished: bool,
+}
+
+impl StageTimer {
+    pub fn start(db_pool: &PgPool, deposit_id: i32, stage: DepositStage) -> Self {
+        Self {
+            db_pool: db_pool.clone(),
+            deposit_id,
+            stage,
+            started_at: Utc::now(),
+            started: Instant::now(),
+            finished: false,
+        }
+    }
+
+    pub fn succeed(mut self) {
+        self.record(None);
+    }
+
+    pub fn fail(mut self, error: impl Display) {
+        self.record(Some(error.to_string()));
+    }
+
+    /// Records whether the stage ended in `result`, which is passed through
+    pub fn finish<T, E: Display>(self, result: Result<T, E>) -> Result<T, E> {
+        match &result {
+            Ok(_) => self.succeed(),
+            Err(e) => self.fail(e),
+        }
+        result
+    }
+
+    /// Drops the timer without recording anything
+    pub fn discard(mut self) {
+        self.finished = true;
+    }
+
+    fn record(&mut self, error: Option<String>) {
+        self.finished = true;
+        let timing = DepositTiming {
+            stage: self.stage.as_str().to_string(),
+            started_at: self.started_at,
+            finished_at: Utc::now(),
+            duration_ms: self.started.elapsed().as_millis() as i64,
+            success: error.is_none(),
+            error,
+        };
+
+        let Ok(runtime) = Handle::try_current() else {
+            warn!(
+                "Dropping the {} timing of deposit {}: no runtime to write it from",
+                timing.stage, self.deposit_id
+            );
+            return;
+        };
+        let db_pool = self.db_pool.clone();
+        let deposit_id = self.deposit_id;
+        runtime.spawn(async move {
+            if let Err(e) = insert_deposit_timing(&db_pool, deposit_id, &timing).await {
+                warn!(
+                    "Failed to record the {} timing of deposit {}: {}",
+                    timing.stage, deposit_id, e
+                );
+            }
+        });
+    }
+}
+
+impl Drop for StageTimer {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.record(Some(STAGE_INTERRUPTED.to_string()));
+        }
+    }
+}
+
+/// Runs `stage` of a deposit, recording how long it took and whether it succeeded
+pub async fn timed<T, E: Display>(
+    db_pool: &PgPool,
+    deposit_id: i32,
+    stage: DepositStage,
+    stage_future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let timer = StageTimer::start(db_pool, deposit_id, stage);
+    timer.finish(stage_future.await)
+}
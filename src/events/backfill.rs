@@ -0,0 +1,141 @@
+use crate::config::NetworkId;
+use crate::db::database::{
+    get_last_processed_block, insert_deposit_hash_events, update_last_processed_block,
+    DepositHashAppended,
+};
+use crate::events::l1_event_watcher::{
+    fetch_events_logs_with_provider, TestEthereumProvider, ZeroXBridge,
+    DEPOSIT_HASH_BLOCK_TRACKER_KEY,
+};
+use alloy::primitives::U256;
+use alloy::rpc::types::Log;
+use alloy::sol_types::SolEvent;
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+
+/// Progress of a deposit hash backfill, reported after every chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackfillProgress {
+    /// First block fetched, after any block already recorded by the block tracker
+    pub from_block: u64,
+    pub to_block: u64,
+    pub blocks_done: u64,
+    pub rows_fetched: u64,
+    /// Rows actually inserted, events already stored are skipped
+    pub rows_inserted: u64,
+    pub elapsed: Duration,
+}
+
+impl BackfillProgress {
+    pub fn blocks_total(&self) -> u64 {
+        (self.to_block + 1).saturating_sub(self.from_block)
+    }
+
+    pub fn blocks_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.blocks_done as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Ingests the `DepositHashAppended` events of blocks `from_block..=to_block` into
+/// `deposit_hashes`, `chunk_size` blocks per log query and insert.
+///
+/// The block tracker is moved to the end of every chunk inserted, so an interrupted
+/// backfill resumes after it. Events already stored, by an earlier run or by the live
+/// watcher, are skipped, which makes overlapping ranges safe to ingest again.
+#[allow(clippy::too_many_arguments)]
+pub async fn backfill_deposit_hashes<P, F>(
+    pool: &PgPool,
+    network: &NetworkId,
+    provider: &P,
+    contract_addr: &str,
+    from_block: u64,
+    to_block: u64,
+    chunk_size: u64,
+    mut on_progress: F,
+) -> Result<BackfillProgress, Box<dyn std::error::Error>>
+where
+    P: TestEthereumProvider,
+    F: FnMut(&BackfillProgress),
+{
+    if chunk_size == 0 {
+        return Err("Backfill chunk size must be at least 1".into());
+    }
+
+    let from_block =
+        match get_last_processed_block(pool, network, DEPOSIT_HASH_BLOCK_TRACKER_KEY).await? {
+            Some(last_block) => from_block.max(last_block + 1),
+            None => from_block,
+        };
+    let started = Instant::now();
+    let mut progress = BackfillProgress {
+        from_block,
+        to_block,
+        blocks_done: 0,
+        rows_fetched: 0,
+        rows_inserted: 0,
+        elapsed: Duration::ZERO,
+    };
+
+    let mut chunk_from = from_block;
+    while chunk_from <= to_block {
+        let chunk_to = chunk_from.saturating_add(chunk_size - 1).min(to_block);
+        let logs = fetch_events_logs_with_provider::<ZeroXBridge::DepositHashAppended, P>(
+            chunk_from,
+            chunk_to,
+            contract_addr,
+            ZeroXBridge::DepositHashAppended::SIGNATURE,
+            provider,
+        )
+        .await?;
+        let events = logs
+            .iter()
+            .map(deposit_hash_row)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if !events.is_empty() {
+            progress.rows_inserted += insert_deposit_hash_events(pool, network, &events).await?;
+        }
+        update_last_processed_block(pool, network, DEPOSIT_HASH_BLOCK_TRACKER_KEY, chunk_to)
+            .await?;
+
+        progress.blocks_done += chunk_to - chunk_from + 1;
+        progress.rows_fetched += events.len() as u64;
+        progress.elapsed = started.elapsed();
+        on_progress(&progress);
+
+        if chunk_to == to_block {
+            break;
+        }
+        chunk_from = chunk_to + 1;
+    }
+
+    Ok(progress)
+}
+
+pub(crate) fn deposit_hash_row(
+    log: &Log<ZeroXBridge::DepositHashAppended>,
+) -> Result<DepositHashAppended, String> {
+    let event = log.data();
+    Ok(DepositHashAppended {
+        id: 0,
+        index: to_i64(event.index, "index")?,
+        commitment_hash: event.commitmentHash.to_be_bytes::<32>().to_vec(),
+        root_hash: event.rootHash.to_be_bytes::<32>().to_vec(),
+        elements_count: to_i64(event.elementsCount, "elementsCount")?,
+        block_number: log
+            .block_number
+            .ok_or("DepositHashAppended log without a block number")? as i64,
+        created_at: None,
+        updated_at: None,
+    })
+}
+
+fn to_i64(value: U256, field: &str) -> Result<i64, String> {
+    i64::try_from(value)
+        .map_err(|_| format!("DepositHashAppended {} {} overflows i64", field, value))
+}
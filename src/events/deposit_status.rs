@@ -0,0 +1,250 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::Mutex;
+use tokio::time::{sleep, timeout};
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use crate::db::database::{
+    fetch_deposit_status_by_commitment_hash, DEPOSIT_STATUS_CANCELLED, DEPOSIT_STATUS_EXPIRED,
+};
+use crate::events::status_notifications::{StatusNotification, DEPOSITS_STATUS_CHANNEL};
+use crate::proof_client::service::DEPOSIT_STATUS_FAILED;
+
+/// Updates kept for subscribers that fall behind before they start missing them
+const DEPOSIT_STATUS_CHANNEL_CAPACITY: usize = 1024;
+
+/// How long the listener waits for a notification before checking whether any
+/// subscriber is left, and before reconnecting after a failure
+const LISTENER_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Deposit statuses that are never left once reached
+const TERMINAL_DEPOSIT_STATUSES: &[&str] = &[
+    "READY_TO_CLAIM",
+    "PROCESSED",
+    DEPOSIT_STATUS_FAILED,
+    DEPOSIT_STATUS_CANCELLED,
+    DEPOSIT_STATUS_EXPIRED,
+];
+
+/// A deposit moving from one status to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DepositStatusEvent {
+    pub commitment_hash: String,
+    /// `None` in the current status sent when a client subscribes
+    pub old_status: Option<String>,
+    pub new_status: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// What subscribers of a [`DepositStatusFeed`] receive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DepositStatusUpdate {
+    Changed(DepositStatusEvent),
+    /// The listener lost its connection, changes made until it reconnected were missed
+    Missed,
+}
+
+/// Status changes of every deposit, whichever process made them, received from the
+/// `deposits_status` notifications of the database and fanned out to the API's
+/// streams.
+///
+/// One listener connection serves every subscriber. It is opened by the first one and
+/// closed once the last one is gone.
+#[derive(Debug, Clone)]
+pub struct DepositStatusFeed {
+    pool: PgPool,
+    sender: broadcast::Sender<DepositStatusUpdate>,
+    /// Whether the listener runs, locked while it is started or stopped
+    listening: Arc<Mutex<bool>>,
+}
+
+impl DepositStatusFeed {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            sender: broadcast::channel(DEPOSIT_STATUS_CHANNEL_CAPACITY).0,
+            listening: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Subscribes to the changes made from now on, listening on the database first if
+    /// nobody is subscribed yet
+    pub async fn subscribe(&self) -> Result<broadcast::Receiver<DepositStatusUpdate>, sqlx::Error> {
+        let mut listening = self.listening.lock().await;
+        if !*listening {
+            let mut listener = PgListener::connect_with(&self.pool).await?;
+            listener.listen(DEPOSITS_STATUS_CHANNEL).await?;
+            tokio::spawn(self.clone().forward(listener));
+            *listening = true;
+        }
+        Ok(self.sender.subscribe())
+    }
+
+    /// Broadcasts the notifications of `listener` until no subscriber is left
+    async fn forward(self, mut listener: PgListener) {
+        loop {
+            match timeout(LISTENER_CHECK_INTERVAL, listener.try_recv()).await {
+                Ok(Ok(Some(notification))) => {
+                    if let Some(event) = status_event(notification.payload()) {
+                        let _ = self.sender.send(DepositStatusUpdate::Changed(event));
+                    }
+                }
+                // The connection was lost, it is reopened on the next call
+                Ok(Ok(None)) => {
+                    let _ = self.sender.send(DepositStatusUpdate::Missed);
+                }
+                Ok(Err(e)) => {
+                    warn!("Failed to listen on {}: {:?}", DEPOSITS_STATUS_CHANNEL, e);
+                    let _ = self.sender.send(DepositStatusUpdate::Missed);
+                    sleep(LISTENER_CHECK_INTERVAL).await;
+                }
+                Err(_) => {}
+            }
+
+            let mut listening = self.listening.lock().await;
+            if self.sender.receiver_count() == 0 {
+                *listening = false;
+                return;
+            }
+        }
+    }
+}
+
+/// The status change a `deposits_status` notification carries, `None` if it can't be
+/// read
+fn status_event(payload: &str) -> Option<DepositStatusEvent> {
+    let notification: StatusNotification = match serde_json::from_str(payload) {
+        Ok(notification) => notification,
+        Err(e) => {
+            warn!(
+                "Ignoring malformed notification on {}: {}",
+                DEPOSITS_STATUS_CHANNEL, e
+            );
+            return None;
+        }
+    };
+
+    Some(DepositStatusEvent {
+        commitment_hash: notification.commitment_hash?,
+        old_status: notification.old_status,
+        new_status: notification.status,
+        timestamp: Utc::now(),
+    })
+}
+
+pub fn is_terminal_deposit_status(status: &str) -> bool {
+    TERMINAL_DEPOSIT_STATUSES
+        .iter()
+        .any(|terminal| terminal.eq_ignore_ascii_case(status))
+}
+
+/// Waits on `events` for the next status change of `current`'s deposit, `None` once the
+/// status can no longer be followed
+pub async fn next_deposit_status_change(
+    pool: &PgPool,
+    events: &mut broadcast::Receiver<DepositStatusUpdate>,
+    current: &DepositStatusEvent,
+) -> Option<DepositStatusEvent> {
+    loop {
+        if let Some(event) = follow_update(pool, events.recv().await, current).await? {
+            return Some(event);
+        }
+    }
+}
+
+/// Applies an update received while following `current`'s deposit: `Some` with the
+/// deposit's next status change if there is one, `None` once the status can no longer
+/// be followed
+pub async fn follow_update(
+    pool: &PgPool,
+    received: Result<DepositStatusUpdate, RecvError>,
+    current: &DepositStatusEvent,
+) -> Option<Option<DepositStatusEvent>> {
+    match received {
+        Ok(DepositStatusUpdate::Changed(event)) => {
+            Some((event.commitment_hash == current.commitment_hash).then_some(event))
+        }
+        Ok(DepositStatusUpdate::Missed) => resync(pool, current).await,
+        Err(RecvError::Lagged(skipped)) => {
+            warn!(
+                "Deposit status stream of {} lagged by {} events",
+                current.commitment_hash, skipped
+            );
+            resync(pool, current).await
+        }
+        Err(RecvError::Closed) => None,
+    }
+}
+
+async fn resync(pool: &PgPool, current: &DepositStatusEvent) -> Option<Option<DepositStatusEvent>> {
+    match status_change_since(pool, current).await {
+        Ok(event) => Some(event),
+        Err(e) => {
+            error!("Failed to read deposit status: {}", e);
+            None
+        }
+    }
+}
+
+/// The change from `current` to the deposit's stored status, `None` while they match.
+///
+/// For subscribers that lagged behind the feed or whose listener reconnected: changes
+/// were missed, so the status is read again instead of guessed.
+pub async fn status_change_since(
+    pool: &PgPool,
+    current: &DepositStatusEvent,
+) -> Result<Option<DepositStatusEvent>, sqlx::Error> {
+    let status = fetch_deposit_status_by_commitment_hash(pool, &current.commitment_hash).await?;
+    Ok(status
+        .filter(|status| *status != current.new_status)
+        .map(|status| DepositStatusEvent {
+            commitment_hash: current.commitment_hash.clone(),
+            old_status: Some(current.new_status.clone()),
+            new_status: status,
+            timestamp: Utc::now(),
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_notifications_are_read_as_status_events() {
+        let payload = json!({
+            "id": 7,
+            "status": "PENDING_TREE_INCLUSION",
+            "old_status": "pending",
+            "commitment_hash": "0xnotified",
+        });
+
+        let event = status_event(&payload.to_string()).unwrap();
+        assert_eq!(event.commitment_hash, "0xnotified");
+        assert_eq!(event.old_status.as_deref(), Some("pending"));
+        assert_eq!(event.new_status, "PENDING_TREE_INCLUSION");
+    }
+
+    #[test]
+    fn test_notifications_without_commitment_hash_are_ignored() {
+        let payload = json!({ "id": 7, "status": "pending", "old_status": null });
+
+        assert_eq!(status_event(&payload.to_string()), None);
+        assert_eq!(status_event("not json"), None);
+    }
+
+    #[test]
+    fn test_terminal_statuses() {
+        assert!(is_terminal_deposit_status("PROCESSED"));
+        assert!(is_terminal_deposit_status("failed"));
+        assert!(is_terminal_deposit_status(DEPOSIT_STATUS_CANCELLED));
+        assert!(!is_terminal_deposit_status("pending"));
+        assert!(!is_terminal_deposit_status("READY_FOR_RELAY"));
+    }
+}
@@ -1,17 +1,26 @@
-use crate::db::database::{get_last_processed_block, update_last_processed_block, upsert_deposit};
+use crate::config::{LimitsConfig, NetworkId};
+use crate::db::database::{
+    insert_deposit_hash_events, insert_merkle_root, upsert_deposit, RootSource,
+    DEPOSIT_STATUS_AMOUNT_FLAGGED, DEPOSIT_STATUS_COMMITMENT_FLAGGED,
+};
+use crate::events::backfill::deposit_hash_row;
+use crate::events::watcher::{AnyEventWatcher, EventRecorder, EventWatcher, EventWatcherError};
+use crate::queue::l1_queue::DEPOSIT_STATUS_PENDING_TREE_INCLUSION;
+use crate::utils::amount::u256_to_amount;
+use crate::utils::{commitment_hash_hex, is_valid_felt252};
 use anyhow::Result;
-use sqlx::PgPool;
+use sqlx::{types::BigDecimal, PgPool};
 use tracing::log::{debug, warn};
 
-use std::str::FromStr;
 use async_trait::async_trait;
+use std::str::FromStr;
+use thiserror::Error;
 
 use alloy::{
-    primitives::Address,
+    primitives::{Address, B256, U256},
     providers::{Provider, ProviderBuilder},
-    rpc::types::{Filter, Log},
+    rpc::types::{BlockNumberOrTag, Filter, Log},
     sol,
-    sol_types::SolEvent,
 };
 
 // Name of the table for storing block tracker
@@ -21,10 +30,28 @@ pub const DEPOSIT_HASH_BLOCK_TRACKER_KEY: &str = "l1_deposit_hash_events_last_bl
 // Trait for testable Ethereum provider
 #[async_trait]
 pub trait TestEthereumProvider: Send + Sync {
-    fn get_logs(&self, filter: &Filter) -> impl std::future::Future<Output = Result<Vec<alloy::rpc::types::Log>, Box<dyn std::error::Error + Send + Sync>>> + Send;
+    fn get_logs(
+        &self,
+        filter: &Filter,
+    ) -> impl std::future::Future<
+        Output = Result<Vec<alloy::rpc::types::Log>, Box<dyn std::error::Error + Send + Sync>>,
+    > + Send;
+
+    fn get_block_number(
+        &self,
+    ) -> impl std::future::Future<Output = Result<u64, Box<dyn std::error::Error + Send + Sync>>> + Send;
+
+    /// Hash of the canonical block `number`, `None` when the chain is not that long
+    fn get_block_hash(
+        &self,
+        number: u64,
+    ) -> impl std::future::Future<
+        Output = Result<Option<B256>, Box<dyn std::error::Error + Send + Sync>>,
+    > + Send;
 }
 
 // Implementation for real Ethereum provider
+#[derive(Clone)]
 pub struct RealEthereumProvider {
     rpc_url: String,
 }
@@ -33,11 +60,22 @@ impl RealEthereumProvider {
     pub fn new(rpc_url: String) -> Self {
         Self { rpc_url }
     }
+
+    /// Number of the latest L1 block
+    pub async fn get_block_number(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let provider = ProviderBuilder::new().connect(&self.rpc_url).await?;
+        Ok(provider.get_block_number().await?)
+    }
 }
 
 #[async_trait]
 impl TestEthereumProvider for RealEthereumProvider {
-    fn get_logs(&self, filter: &Filter) -> impl std::future::Future<Output = Result<Vec<alloy::rpc::types::Log>, Box<dyn std::error::Error + Send + Sync>>> + Send {
+    fn get_logs(
+        &self,
+        filter: &Filter,
+    ) -> impl std::future::Future<
+        Output = Result<Vec<alloy::rpc::types::Log>, Box<dyn std::error::Error + Send + Sync>>,
+    > + Send {
         let rpc_url = self.rpc_url.clone();
         let filter = filter.clone();
         async move {
@@ -46,6 +84,57 @@ impl TestEthereumProvider for RealEthereumProvider {
             Ok(logs)
         }
     }
+
+    fn get_block_number(
+        &self,
+    ) -> impl std::future::Future<Output = Result<u64, Box<dyn std::error::Error + Send + Sync>>> + Send
+    {
+        Self::get_block_number(self)
+    }
+
+    fn get_block_hash(
+        &self,
+        number: u64,
+    ) -> impl std::future::Future<
+        Output = Result<Option<B256>, Box<dyn std::error::Error + Send + Sync>>,
+    > + Send {
+        let rpc_url = self.rpc_url.clone();
+        async move {
+            let provider = ProviderBuilder::new().connect(&rpc_url).await?;
+            let block = provider
+                .get_block_by_number(BlockNumberOrTag::Number(number))
+                .await?;
+            Ok(block.map(|block| block.header.hash))
+        }
+    }
+}
+
+// Lets callers lend a provider to an `EventWatcher` instead of handing it over
+impl<P: TestEthereumProvider> TestEthereumProvider for &P {
+    fn get_logs(
+        &self,
+        filter: &Filter,
+    ) -> impl std::future::Future<
+        Output = Result<Vec<alloy::rpc::types::Log>, Box<dyn std::error::Error + Send + Sync>>,
+    > + Send {
+        (**self).get_logs(filter)
+    }
+
+    fn get_block_number(
+        &self,
+    ) -> impl std::future::Future<Output = Result<u64, Box<dyn std::error::Error + Send + Sync>>> + Send
+    {
+        (**self).get_block_number()
+    }
+
+    fn get_block_hash(
+        &self,
+        number: u64,
+    ) -> impl std::future::Future<
+        Output = Result<Option<B256>, Box<dyn std::error::Error + Send + Sync>>,
+    > + Send {
+        (**self).get_block_hash(number)
+    }
 }
 
 sol! {
@@ -68,84 +157,326 @@ sol! {
             uint256 newRoot,
             uint256 elementCount
         );
+
+        event DepositHashAppended(
+            uint256 index,
+            uint256 commitmentHash,
+            uint256 rootHash,
+            uint256 elementsCount
+        );
     }
 }
 
+/// Fetches and records the deposit events of blocks with at least
+/// `finality_confirmations` confirmations, `ethereum.confirmations` in the config.
+///
+/// Blocks recorded on an earlier call that were re-orged away are rolled back and
+/// fetched again first.
 pub async fn fetch_l1_deposit_events(
     db_pool: &mut PgPool,
+    network: &NetworkId,
     rpc_url: &str,
     from_block: u64,
     contract_addr: &str,
+    limits: &LimitsConfig,
+    finality_confirmations: u64,
 ) -> Result<Vec<Log<ZeroXBridge::DepositEvent>>, Box<dyn std::error::Error>> {
     let provider = RealEthereumProvider::new(rpc_url.to_string());
-    fetch_l1_deposit_events_with_provider(db_pool, from_block, contract_addr, &provider).await
+    fetch_l1_deposit_events_with_provider(
+        db_pool,
+        network,
+        from_block,
+        contract_addr,
+        limits,
+        finality_confirmations,
+        &provider,
+    )
+    .await
 }
 
 pub async fn fetch_l1_deposit_events_with_provider<P: TestEthereumProvider>(
     db_pool: &mut PgPool,
+    network: &NetworkId,
     from_block: u64,
     contract_addr: &str,
+    limits: &LimitsConfig,
+    finality_confirmations: u64,
     provider: &P,
 ) -> Result<Vec<Log<ZeroXBridge::DepositEvent>>, Box<dyn std::error::Error>> {
-    // Load last processed block for DepositEvent
-    let from_block_deposit = match get_last_processed_block(db_pool, BLOCK_TRACKER_KEY).await {
-        Ok(Some(last_block)) => last_block + 1,
-        Ok(None) => from_block,
-        Err(e) => {
-            warn!("Failed to get last processed block for DepositEvent: {}", e);
-            from_block
-        }
-    };
+    let mut watcher = deposit_event_watcher(provider, network, contract_addr, from_block, limits)
+        .with_confirmations(finality_confirmations);
+    Ok(watcher.poll_once(db_pool).await?)
+}
 
-    // Fetch DepositEvent logs
-    let event_name = ZeroXBridge::DepositEvent::SIGNATURE;
-    let deposit_logs =
-        fetch_events_logs_with_provider(from_block_deposit, contract_addr, event_name, provider)
-            .await?;
-
-    // Update last processed block for DepositEvent
-    if let Some(last_log) = deposit_logs.last() {
-        let block_number = last_log.block_number.ok_or("Block number not found")?;
-        if let Err(e) = update_last_processed_block(db_pool, BLOCK_TRACKER_KEY, block_number).await
-        {
-            warn!(
-                "Failed to update last processed block for DepositEvent: {}",
-                e
+/// Watchers of `network`'s deposit and deposit hash events, starting from `from_block`
+/// until they first move their block trackers, for [`start_event_loop`]
+///
+/// [`start_event_loop`]: crate::events::watcher::start_event_loop
+pub fn l1_event_watchers(
+    network: &NetworkId,
+    rpc_url: &str,
+    contract_addr: &str,
+    from_block: u64,
+    finality_confirmations: u64,
+    limits: &LimitsConfig,
+) -> Vec<Box<dyn AnyEventWatcher>> {
+    l1_event_watchers_with_provider(
+        RealEthereumProvider::new(rpc_url.to_string()),
+        network,
+        contract_addr,
+        from_block,
+        finality_confirmations,
+        limits,
+    )
+}
+
+/// Like [`l1_event_watchers`], with both watchers fetching their logs from `provider`
+pub fn l1_event_watchers_with_provider<P: TestEthereumProvider + Clone + 'static>(
+    provider: P,
+    network: &NetworkId,
+    contract_addr: &str,
+    from_block: u64,
+    finality_confirmations: u64,
+    limits: &LimitsConfig,
+) -> Vec<Box<dyn AnyEventWatcher>> {
+    let deposits =
+        deposit_event_watcher(provider.clone(), network, contract_addr, from_block, limits)
+            .with_confirmations(finality_confirmations);
+    let deposit_hashes = EventWatcher::<ZeroXBridge::DepositHashAppended, _>::new(
+        provider,
+        network.clone(),
+        contract_addr,
+        DEPOSIT_HASH_BLOCK_TRACKER_KEY,
+        from_block,
+    )
+    .with_confirmations(finality_confirmations)
+    .with_recorder(DepositHashRecorder);
+
+    vec![Box::new(deposits), Box::new(deposit_hashes)]
+}
+
+/// Deposit events are the only ones rolled back on a re-org, so only their watcher
+/// tracks block hashes
+fn deposit_event_watcher<P: TestEthereumProvider>(
+    provider: P,
+    network: &NetworkId,
+    contract_addr: &str,
+    from_block: u64,
+    limits: &LimitsConfig,
+) -> EventWatcher<ZeroXBridge::DepositEvent, P> {
+    EventWatcher::new(
+        provider,
+        network.clone(),
+        contract_addr,
+        BLOCK_TRACKER_KEY,
+        from_block,
+    )
+    .with_reorg_tracking()
+    .with_recorder(DepositEventRecorder {
+        limits: limits.clone(),
+    })
+}
+
+/// Records deposits and the contract's roots from `DepositEvent`s
+struct DepositEventRecorder {
+    limits: LimitsConfig,
+}
+
+#[async_trait]
+impl EventRecorder<ZeroXBridge::DepositEvent> for DepositEventRecorder {
+    async fn record(
+        &self,
+        pool: &PgPool,
+        network: &NetworkId,
+        logs: &[Log<ZeroXBridge::DepositEvent>],
+    ) -> Result<(), EventWatcherError> {
+        for log in logs {
+            let event: &ZeroXBridge::DepositEvent = log.data();
+
+            debug!(
+                "Recieved DepositEvent: depositId={}, token={:?}, assetType={:?}, usdVal={}, user={:?}, nonce={}, leafIndex={}, commitmentHash={:x}, newRoot={:x}, elementCount={}",
+                event.depositId,
+                event.token,
+                event.assetType,
+                event.usdVal,
+                event.user,
+                event.nonce,
+                event.leafIndex,
+                event.commitmentHash,
+                event.newRoot,
+                event.elementCount
             );
+
+            match DepositEventProcessor::validate(event) {
+                Ok(()) => {
+                    if let Err(e) =
+                        record_deposit_event(pool, network, event, log.block_number, &self.limits)
+                            .await
+                    {
+                        warn!("Failed to upsert deposit: {}", e)
+                    }
+                }
+                Err(e) => warn!(
+                    "Skipping invalid DepositEvent {} in block {:?}: {}",
+                    event.depositId, log.block_number, e
+                ),
+            }
+
+            // Every append emits the contract's new root, keep it to compare with local roots
+            if let Err(e) = record_onchain_root(pool, network, event, log.block_number).await {
+                warn!("Failed to record on-chain root: {}", e)
+            }
         }
+        Ok(())
     }
+}
 
-    for log in &deposit_logs {
-        let event: &ZeroXBridge::DepositEvent = log.data();
-
-        debug!(
-            "Recieved DepositEvent: depositId={}, token={:?}, assetType={:?}, usdVal={}, user={:?}, nonce={}, leafIndex={}, commitmentHash={:x}, newRoot={:x}, elementCount={}",
-            event.depositId,
-            event.token,
-            event.assetType,
-            event.usdVal,
-            event.user,
-            event.nonce,
-            event.leafIndex,
-            event.commitmentHash,
-            event.newRoot,
-            event.elementCount
-        );
+/// Stores `DepositHashAppended` events in `deposit_hashes`, skipping stored ones
+struct DepositHashRecorder;
 
-        if let Err(e) = upsert_deposit(
-            db_pool,
-            &event.user.to_string(),
-            event.usdVal.to_string().parse::<i64>().unwrap_or(0),
-            &format!("{:x}", event.commitmentHash),
-            "PENDING_TREE_INCLUSION",
-        )
-        .await
-        {
-            warn!("Failed to upsert deposit: {}", e)
+#[async_trait]
+impl EventRecorder<ZeroXBridge::DepositHashAppended> for DepositHashRecorder {
+    async fn record(
+        &self,
+        pool: &PgPool,
+        network: &NetworkId,
+        logs: &[Log<ZeroXBridge::DepositHashAppended>],
+    ) -> Result<(), EventWatcherError> {
+        let events = logs
+            .iter()
+            .map(deposit_hash_row)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(EventWatcherError::InvalidEvent)?;
+        if !events.is_empty() {
+            insert_deposit_hash_events(pool, network, &events).await?;
         }
+        Ok(())
     }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EventValidationError {
+    #[error("{0} address is zero")]
+    ZeroAddress(&'static str),
 
-    Ok(deposit_logs)
+    #[error("Amount is zero")]
+    ZeroAmount,
+
+    #[error("Commitment hash is zero")]
+    ZeroCommitmentHash,
+}
+
+/// Checks of the L1 events a deposit is recorded from
+pub struct DepositEventProcessor;
+
+impl DepositEventProcessor {
+    /// Rejects deposit events no real deposit emits, which are skipped rather than
+    /// stored.
+    ///
+    /// ETH deposits carry the zero address as their token, only ERC20 deposits need one.
+    pub fn validate(event: &ZeroXBridge::DepositEvent) -> Result<(), EventValidationError> {
+        if event.assetType == ZeroXBridge::AssetType::ERC20 && event.token == Address::ZERO {
+            return Err(EventValidationError::ZeroAddress("Token"));
+        }
+        if event.user == Address::ZERO {
+            return Err(EventValidationError::ZeroAddress("User"));
+        }
+        if event.usdVal.is_zero() {
+            return Err(EventValidationError::ZeroAmount);
+        }
+        if event.commitmentHash.is_zero() {
+            return Err(EventValidationError::ZeroCommitmentHash);
+        }
+        Ok(())
+    }
+}
+
+/// Records a deposit seen on `network`'s L1, returning the status it was stored with.
+///
+/// Deposits whose amount is outside `limits` are stored as `AMOUNT_FLAGGED` instead of
+/// entering the tree, the funds are locked on L1 either way. Commitments that are not
+/// felt252s are stored as `COMMITMENT_FLAGGED` for the same reason.
+pub async fn record_deposit_event(
+    db_pool: &PgPool,
+    network: &NetworkId,
+    event: &ZeroXBridge::DepositEvent,
+    block_number: Option<u64>,
+    limits: &LimitsConfig,
+) -> Result<&'static str, sqlx::Error> {
+    let amount = uint256_to_amount(event.usdVal);
+    // Spelled like commitments submitted through the API, so both end up in one row
+    let commitment_hash = commitment_hash_hex(&event.commitmentHash.to_be_bytes());
+    let status = if !is_valid_felt252(&commitment_hash) {
+        warn!(
+            "Flagging deposit with commitment {}: not below the Stark prime",
+            commitment_hash
+        );
+        DEPOSIT_STATUS_COMMITMENT_FLAGGED
+    } else {
+        match limits.check_deposit(&amount) {
+            Ok(()) => DEPOSIT_STATUS_PENDING_TREE_INCLUSION,
+            Err(e) => {
+                warn!(
+                    "Flagging deposit with commitment {}: {}",
+                    commitment_hash, e
+                );
+                DEPOSIT_STATUS_AMOUNT_FLAGGED
+            }
+        }
+    };
+
+    upsert_deposit(
+        db_pool,
+        network,
+        &event.user.to_string(),
+        &amount,
+        &commitment_hash,
+        status,
+        block_number.map(|block| block as i64),
+    )
+    .await?;
+    Ok(status)
+}
+
+fn uint256_to_amount(value: U256) -> BigDecimal {
+    let [l0, l1, h0, h1] = value.into_limbs();
+    u256_to_amount(
+        u128::from(l1) << 64 | u128::from(l0),
+        u128::from(h1) << 64 | u128::from(h0),
+    )
+}
+
+/// Element count of a deposit event as a BIGINT, none if it does not fit in one
+fn element_count(value: U256) -> Option<i64> {
+    i64::try_from(u64::try_from(value).ok()?).ok()
+}
+
+/// Records the root a deposit event reports, skipping it if its element count does not
+/// fit in the `merkle_roots` row
+async fn record_onchain_root(
+    db_pool: &PgPool,
+    network: &NetworkId,
+    event: &ZeroXBridge::DepositEvent,
+    block_number: Option<u64>,
+) -> Result<(), sqlx::Error> {
+    let Some(element_count) = element_count(event.elementCount) else {
+        warn!(
+            "Not recording on-chain root {:x}: element count {} is out of range",
+            event.newRoot, event.elementCount
+        );
+        return Ok(());
+    };
+
+    let mut conn = db_pool.acquire().await?;
+    insert_merkle_root(
+        &mut conn,
+        network,
+        element_count,
+        &format!("0x{}", hex::encode(event.newRoot.to_be_bytes::<32>())),
+        block_number.map(|block| block as i64),
+        RootSource::OnChain,
+    )
+    .await
 }
 
 use std::time::Duration;
@@ -154,8 +485,9 @@ use tokio::time::sleep;
 const MAX_RETRIES: usize = 5; // we can update this. i'm not sure if 10 (retries) would be too much
 const INITIAL_BACKOFF_MS: u64 = 500;
 
-async fn fetch_events_logs_with_provider<T, P>(
+pub(crate) async fn fetch_events_logs_with_provider<T, P>(
     from_block: u64,
+    to_block: u64,
     contract_addr: &str,
     event_name: &str,
     provider: &P,
@@ -169,7 +501,8 @@ where
     let filter = Filter::new()
         .address(contract_addr)
         .event(event_name)
-        .from_block(from_block);
+        .from_block(from_block)
+        .to_block(to_block);
 
     let mut retries = 0;
     let mut backoff = INITIAL_BACKOFF_MS;
@@ -249,4 +582,104 @@ mod tests {
         assert_eq!(event.newRoot, new_root);
         assert_eq!(event.elementCount, element_count);
     }
+
+    fn deposit_event() -> ZeroXBridge::DepositEvent {
+        ZeroXBridge::DepositEvent {
+            assetType: ZeroXBridge::AssetType::ERC20,
+            usdVal: U256::from(100_000),
+            nonce: U256::from(1),
+            leafIndex: U256::ZERO,
+            depositId: U256::from(1),
+            token: Address::from([0xaa; 20]),
+            user: Address::from([0xbb; 20]),
+            commitmentHash: U256::from_be_bytes([0xcc; 32]),
+            newRoot: U256::from_be_bytes([0xdd; 32]),
+            elementCount: U256::from(1),
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_deposit() {
+        assert_eq!(DepositEventProcessor::validate(&deposit_event()), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_token_of_erc20_deposit() {
+        let event = ZeroXBridge::DepositEvent {
+            token: Address::ZERO,
+            ..deposit_event()
+        };
+        assert_eq!(
+            DepositEventProcessor::validate(&event),
+            Err(EventValidationError::ZeroAddress("Token"))
+        );
+
+        let eth_deposit = ZeroXBridge::DepositEvent {
+            assetType: ZeroXBridge::AssetType::ETH,
+            ..event
+        };
+        assert_eq!(DepositEventProcessor::validate(&eth_deposit), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_user() {
+        let event = ZeroXBridge::DepositEvent {
+            user: Address::ZERO,
+            ..deposit_event()
+        };
+        assert_eq!(
+            DepositEventProcessor::validate(&event),
+            Err(EventValidationError::ZeroAddress("User"))
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_amount() {
+        let event = ZeroXBridge::DepositEvent {
+            usdVal: U256::ZERO,
+            ..deposit_event()
+        };
+        assert_eq!(
+            DepositEventProcessor::validate(&event),
+            Err(EventValidationError::ZeroAmount)
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_commitment_hash() {
+        let event = ZeroXBridge::DepositEvent {
+            commitmentHash: U256::ZERO,
+            ..deposit_event()
+        };
+        assert_eq!(
+            DepositEventProcessor::validate(&event),
+            Err(EventValidationError::ZeroCommitmentHash)
+        );
+    }
+
+    // Amounts used to be parsed as i64 and stored as 0 when they did not fit
+    #[test]
+    fn test_uint256_amount_is_not_truncated() {
+        assert_eq!(uint256_to_amount(U256::from(1000)), BigDecimal::from(1000));
+        assert_eq!(
+            uint256_to_amount(U256::from(u64::MAX) + U256::from(1)).to_string(),
+            "18446744073709551616"
+        );
+        assert_eq!(
+            uint256_to_amount(U256::MAX).to_string(),
+            U256::MAX.to_string()
+        );
+    }
+
+    // Element counts used to be stored as 0 when they did not fit
+    #[test]
+    fn test_element_count_out_of_range_is_rejected() {
+        assert_eq!(element_count(U256::from(1000)), Some(1000));
+        assert_eq!(element_count(U256::from(i64::MAX as u64)), Some(i64::MAX));
+        assert_eq!(
+            element_count(U256::from(i64::MAX as u64) + U256::from(1)),
+            None
+        );
+        assert_eq!(element_count(U256::MAX), None);
+    }
 }
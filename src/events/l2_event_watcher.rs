@@ -1,17 +1,26 @@
-use crate::config::AppConfig;
-use crate::db::database::{get_last_processed_block, update_last_processed_block};
+use crate::config::{AppConfig, NetworkId};
+use crate::db::database::{
+    get_last_processed_block, update_last_processed_block, upsert_withdrawal,
+};
+use crate::utils::amount::u256_felts_to_amount;
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use starknet::core::types::{BlockId, EventFilter, EventsPage, Felt};
+use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet::providers::Provider;
 use std::time::Duration;
 use tokio::time::sleep;
-use tracing::log::warn;
+use tracing::{debug, error, warn};
 
 const MAX_RETRIES: u32 = 3;
 const RETRY_DELAY_MS: u64 = 1000;
 const DEFAULT_PAGE_SIZE: u64 = 100;
 
+// Name of the block tracker entry for L2 bridge events
+pub const BLOCK_TRACKER_KEY: &str = "l2_events_last_block";
+
 // Event key for BurnEvent (calculated from event name "BurnEvent")
 const BURN_EVENT_KEY: &str = "0x0099de3f38fed0a76764f614c6bc2b958814813685abc1af6deedab612df44f3";
 // Event key for WithdrawalHashAppended
@@ -26,6 +35,8 @@ pub struct CommitmentLog {
     pub user: String,
     pub amount_low: String,
     pub amount_high: String,
+    /// Burn nonce, emitted by contracts newer than the initial deployment
+    pub nonce: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,14 +49,16 @@ pub struct WithdrawalCommitmentLog {
     pub transaction_hash: String,
 }
 
+#[derive(Default)]
 pub struct L2EventResults {
     pub burn_events: Vec<CommitmentLog>,
     pub withdrawal_events: Vec<WithdrawalCommitmentLog>,
 }
 
-pub trait TestProvider {
-    fn block_number(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
-    fn get_events(
+#[async_trait]
+pub trait TestProvider: Send + Sync {
+    async fn block_number(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_events(
         &self,
         filter: EventFilter,
         continuation_token: Option<String>,
@@ -53,24 +66,53 @@ pub trait TestProvider {
     ) -> Result<EventsPage, Box<dyn std::error::Error + Send + Sync>>;
 }
 
+#[async_trait]
+impl TestProvider for JsonRpcClient<HttpTransport> {
+    async fn block_number(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Provider::block_number(self).await?)
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Provider::get_events(self, filter, continuation_token, chunk_size).await?)
+    }
+}
+
 /// This function queries the L2 contract for events and parses both:
 /// - Burn events into `CommitmentLog`
 /// - WithdrawalHashAppended events into `WithdrawalCommitmentLog`
 ///
 /// Events are returned together in a unified `L2EventResults` struct.
 /// Pagination and block tracking are handled to ensure no events are missed.
+///
+/// Only blocks at least `starknet.confirmations` deep are scanned. Burn events are
+/// recorded as pending withdrawals of `network` before its block tracker advances, so a
+/// failed run is picked up again from the same block. `config` holds that network's
+/// sections, see [`AppConfig::for_network`].
 pub async fn fetch_l2_events<P: TestProvider>(
     config: &AppConfig,
     db_pool: &PgPool,
+    network: &NetworkId,
     from_block: u64,
     provider: &P,
 ) -> Result<L2EventResults> {
-    let start_block = match get_last_processed_block(&db_pool, "l2_events_last_block").await {
+    let start_block = match get_last_processed_block(db_pool, network, BLOCK_TRACKER_KEY).await {
         Ok(Some(last)) => last + 1,
         _ => from_block,
     };
 
     let latest_block = get_latest_block_with_retry(provider).await?;
+    let Some(latest_block) = latest_block.checked_sub(config.starknet.confirmations) else {
+        return Ok(L2EventResults::default());
+    };
+    if start_block > latest_block {
+        return Ok(L2EventResults::default());
+    }
+
     let contract_address = Felt::from_hex(&config.contracts.l2_contract_address)?;
 
     let burn_event_key = Felt::from_hex(BURN_EVENT_KEY)?;
@@ -97,10 +139,11 @@ pub async fn fetch_l2_events<P: TestProvider>(
         .await?;
 
         for event in &page.events {
-            let block_number = event.block_number.unwrap_or_else(|| {
-                warn!("Missing block number for event: {:?}", event);
-                0
-            });
+            // Only events of pending blocks lack one, and those are never final
+            let Some(block_number) = event.block_number else {
+                warn!("Skipping event without a block number: {:?}", event);
+                continue;
+            };
 
             if event.keys.contains(&burn_event_key) && event.data.len() >= 4 {
                 burn_events.push(CommitmentLog {
@@ -109,6 +152,7 @@ pub async fn fetch_l2_events<P: TestProvider>(
                     amount_low: event.data[1].to_hex_string(),
                     amount_high: event.data[2].to_hex_string(),
                     commitment_hash: event.data[3].to_hex_string(),
+                    nonce: event.data.get(4).map(Felt::to_hex_string),
                     transaction_hash: event.transaction_hash.to_hex_string(),
                 });
             } else if event.keys.contains(&withdrawal_event_key) && event.data.len() >= 4 {
@@ -131,20 +175,11 @@ pub async fn fetch_l2_events<P: TestProvider>(
         }
     }
 
-    let max_block = std::cmp::max(
-        burn_events
-            .iter()
-            .map(|e| e.block_number)
-            .max()
-            .unwrap_or(start_block),
-        withdrawal_events
-            .iter()
-            .map(|e| e.block_number)
-            .max()
-            .unwrap_or(start_block),
-    );
-
-    update_last_processed_block(&db_pool, "l2_events_last_block", max_block).await?;
+    for burn in &burn_events {
+        record_burn_withdrawal(db_pool, network, burn).await?;
+    }
+
+    update_last_processed_block(db_pool, network, BLOCK_TRACKER_KEY, latest_block).await?;
 
     Ok(L2EventResults {
         burn_events,
@@ -152,9 +187,82 @@ pub async fn fetch_l2_events<P: TestProvider>(
     })
 }
 
+/// Runs [`fetch_l2_events`] every `interval` in an infinite loop.
+pub async fn start_l2_event_loop<P: TestProvider>(
+    config: AppConfig,
+    db_pool: PgPool,
+    network: NetworkId,
+    from_block: u64,
+    provider: P,
+    interval: Duration,
+) {
+    loop {
+        match fetch_l2_events(&config, &db_pool, &network, from_block, &provider).await {
+            Ok(results) => {
+                if !results.burn_events.is_empty() || !results.withdrawal_events.is_empty() {
+                    debug!(
+                        "Recorded {} burn and {} withdrawal hash events of network {}",
+                        results.burn_events.len(),
+                        results.withdrawal_events.len(),
+                        network
+                    );
+                }
+            }
+            Err(e) => error!("L2 event watcher of network {} failed: {}", network, e),
+        }
+
+        sleep(interval).await;
+    }
+}
+
+/// Upserts the pending withdrawal for a burn event, merging with a row created through
+/// the API for the same commitment hash
+pub async fn record_burn_withdrawal(
+    db_pool: &PgPool,
+    network: &NetworkId,
+    burn: &CommitmentLog,
+) -> Result<i32> {
+    let amount = u256_felts_to_amount(&burn.amount_low, &burn.amount_high).ok_or_else(|| {
+        anyhow!(
+            "Burn amount {}:{} of {} is not a valid u256",
+            burn.amount_high,
+            burn.amount_low,
+            burn.commitment_hash
+        )
+    })?;
+    let nonce = match &burn.nonce {
+        Some(nonce) => Some(
+            felt_to_i64(Felt::from_hex(nonce)?)
+                .ok_or_else(|| anyhow!("Burn nonce {} out of range", nonce))?,
+        ),
+        None => None,
+    };
+
+    // Burn events do not name the L1 token; rows created through the API keep theirs
+    let id = upsert_withdrawal(
+        db_pool,
+        network,
+        &burn.user,
+        &amount,
+        "",
+        &burn.commitment_hash,
+        nonce,
+    )
+    .await?;
+    Ok(id)
+}
+
+fn felt_to_i64(felt: Felt) -> Option<i64> {
+    let bytes = felt.to_bytes_be();
+    if bytes[..24].iter().any(|b| *b != 0) {
+        return None;
+    }
+    i64::try_from(u64::from_be_bytes(bytes[24..].try_into().ok()?)).ok()
+}
+
 async fn get_latest_block_with_retry<P: TestProvider>(provider: &P) -> Result<u64> {
     for attempt in 1..=MAX_RETRIES {
-        match provider.block_number() {
+        match provider.block_number().await {
             Ok(block) => return Ok(block),
             Err(e) => {
                 if attempt == MAX_RETRIES {
@@ -182,7 +290,10 @@ async fn fetch_events_with_retry<P: TestProvider>(
     chunk_size: u64,
 ) -> Result<EventsPage> {
     for attempt in 1..=MAX_RETRIES {
-        match provider.get_events(filter.clone(), continuation_token.clone(), chunk_size) {
+        match provider
+            .get_events(filter.clone(), continuation_token.clone(), chunk_size)
+            .await
+        {
             Ok(events) => return Ok(events),
             Err(e) => {
                 if attempt == MAX_RETRIES {
@@ -202,3 +313,16 @@ async fn fetch_events_with_retry<P: TestProvider>(
         MAX_RETRIES
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_felt_to_i64() {
+        assert_eq!(felt_to_i64(Felt::from(0x1000u64)), Some(0x1000));
+        assert_eq!(felt_to_i64(Felt::from(i64::MAX as u64)), Some(i64::MAX));
+        assert_eq!(felt_to_i64(Felt::from(u64::MAX)), None);
+        assert_eq!(felt_to_i64(Felt::from(u128::MAX)), None);
+    }
+}
@@ -1,4 +1,19 @@
+pub mod backfill;
+pub mod deposit_status;
 pub mod l1_event_watcher;
 pub mod l2_event_watcher;
+pub mod status_notifications;
+pub mod watcher;
 
+pub use backfill::{backfill_deposit_hashes, BackfillProgress};
 pub use l2_event_watcher::{fetch_l2_events, CommitmentLog};
+pub use watcher::{
+    start_event_loop, AnyEventWatcher, EventRecorder, EventWatcher, EventWatcherError,
+};
+
+/// `block_trackers` keys holding the event watchers' cursors
+pub const BLOCK_TRACKER_KEYS: &[&str] = &[
+    l1_event_watcher::BLOCK_TRACKER_KEY,
+    l1_event_watcher::DEPOSIT_HASH_BLOCK_TRACKER_KEY,
+    l2_event_watcher::BLOCK_TRACKER_KEY,
+];
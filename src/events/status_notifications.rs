@@ -0,0 +1,197 @@
+use serde::Deserialize;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::time::{sleep, sleep_until, timeout_at, Instant};
+use tracing::{debug, warn};
+
+/// Channel the `deposits` status trigger notifies on
+pub const DEPOSITS_STATUS_CHANNEL: &str = "deposits_status";
+/// Channel the `l2_transactions` status trigger notifies on
+pub const L2_TRANSACTIONS_STATUS_CHANNEL: &str = "l2_transactions_status";
+
+/// How long a wakeup keeps collecting notifications, so a burst of status changes
+/// results in a single processing cycle
+pub const NOTIFY_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Payload sent by the `notify_status_change` trigger
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct StatusNotification {
+    pub id: i64,
+    pub status: String,
+    /// `None` for newly inserted rows
+    pub old_status: Option<String>,
+    /// Set for rows of tables with a `commitment_hash`, such as deposits
+    #[serde(default)]
+    pub commitment_hash: Option<String>,
+}
+
+/// Why a service loop woke up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Wakeup {
+    /// The poll interval elapsed without a relevant notification
+    Tick,
+    /// Rows reached the watched status, in the order they were notified
+    Notified(Vec<i64>),
+}
+
+/// Wakes a service loop as soon as a row reaches the status it processes, falling back
+/// to the regular poll interval.
+///
+/// Services still run their batched fetch when woken, which picks up the notified rows
+/// in the same order as everything else already waiting instead of letting them jump
+/// the queue. If the listener can't connect, waiting degrades to plain polling.
+pub struct StatusWakeup {
+    listener: Option<PgListener>,
+    filter: StatusFilter,
+}
+
+/// Which notifications wake the loop, kept apart from the listener it reads from
+struct StatusFilter {
+    channel: String,
+    status: String,
+    ignored_from: Vec<String>,
+}
+
+impl StatusWakeup {
+    /// Listens on `channel` for rows moving to `status`
+    pub async fn listen(pool: &PgPool, channel: &str, status: &str) -> Self {
+        let listener = match Self::connect(pool, channel).await {
+            Ok(listener) => Some(listener),
+            Err(e) => {
+                warn!(
+                    "Could not listen on {}, falling back to polling: {:?}",
+                    channel, e
+                );
+                None
+            }
+        };
+
+        Self {
+            listener,
+            filter: StatusFilter {
+                channel: channel.to_string(),
+                status: status.to_string(),
+                ignored_from: Vec::new(),
+            },
+        }
+    }
+
+    async fn connect(pool: &PgPool, channel: &str) -> Result<PgListener, sqlx::Error> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(channel).await?;
+        Ok(listener)
+    }
+
+    /// Ignores rows returning to the watched status from `old_status`, such as retries,
+    /// which should wait for the next poll rather than run again straight away
+    pub fn ignoring_transitions_from(mut self, old_status: &str) -> Self {
+        self.filter.ignored_from.push(old_status.to_string());
+        self
+    }
+
+    /// Waits until `interval` elapses or a row reaches the watched status, then coalesces
+    /// whatever else arrives within [`NOTIFY_DEBOUNCE`] into the same wakeup.
+    pub async fn wait(&mut self, interval: Duration) -> Wakeup {
+        let deadline = Instant::now() + interval;
+        let Some(listener) = self.listener.as_mut() else {
+            sleep(interval).await;
+            return Wakeup::Tick;
+        };
+
+        let first = loop {
+            tokio::select! {
+                _ = sleep_until(deadline) => return Wakeup::Tick,
+                received = listener.recv() => match received {
+                    Ok(notification) => {
+                        if let Some(id) = self.filter.matching(notification.payload()) {
+                            break id;
+                        }
+                    }
+                    Err(e) => {
+                        // The listener reconnects on the next call, notifications sent in
+                        // between are covered by the poll
+                        warn!("Lost notifications on {}: {:?}", self.filter.channel, e);
+                        sleep_until(deadline).await;
+                        return Wakeup::Tick;
+                    }
+                },
+            }
+        };
+
+        let mut ids = vec![first];
+        let window = Instant::now() + NOTIFY_DEBOUNCE;
+        while let Ok(Ok(notification)) = timeout_at(window, listener.recv()).await {
+            if let Some(id) = self.filter.matching(notification.payload()) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        Wakeup::Notified(ids)
+    }
+}
+
+impl StatusFilter {
+    fn matching(&self, payload: &str) -> Option<i64> {
+        let notification: StatusNotification = match serde_json::from_str(payload) {
+            Ok(notification) => notification,
+            Err(e) => {
+                warn!("Ignoring malformed notification on {}: {}", self.channel, e);
+                return None;
+            }
+        };
+        debug!("Notification on {}: {:?}", self.channel, notification);
+
+        let ignored = notification
+            .old_status
+            .as_ref()
+            .is_some_and(|old_status| self.ignored_from.contains(old_status));
+        (notification.status == self.status && !ignored).then_some(notification.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filter(ignored_from: &[&str]) -> StatusFilter {
+        StatusFilter {
+            channel: DEPOSITS_STATUS_CHANNEL.to_string(),
+            status: "PENDING_PROOF_GENERATION".to_string(),
+            ignored_from: ignored_from.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn payload(id: i64, status: &str, old_status: Option<&str>) -> String {
+        json!({ "id": id, "status": status, "old_status": old_status }).to_string()
+    }
+
+    #[test]
+    fn test_matches_rows_reaching_the_watched_status() {
+        let filter = filter(&[]);
+        let moved = payload(
+            7,
+            "PENDING_PROOF_GENERATION",
+            Some("PENDING_TREE_INCLUSION"),
+        );
+        let inserted = payload(8, "PENDING_PROOF_GENERATION", None);
+        let other = payload(9, "READY_FOR_RELAY", Some("PENDING_PROOF_GENERATION"));
+
+        assert_eq!(filter.matching(&moved), Some(7));
+        assert_eq!(filter.matching(&inserted), Some(8));
+        assert_eq!(filter.matching(&other), None);
+        assert_eq!(filter.matching("not json"), None);
+    }
+
+    #[test]
+    fn test_ignores_transitions_from_ignored_statuses() {
+        let filter = filter(&["PROOF_IN_PROGRESS"]);
+        let released = payload(7, "PENDING_PROOF_GENERATION", Some("PROOF_IN_PROGRESS"));
+        let inserted = payload(8, "PENDING_PROOF_GENERATION", None);
+
+        assert_eq!(filter.matching(&released), None);
+        assert_eq!(filter.matching(&inserted), Some(8));
+    }
+}
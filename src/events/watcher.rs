@@ -0,0 +1,276 @@
+use crate::config::NetworkId;
+use crate::db::database::{
+    fetch_recent_block_hashes, get_last_processed_block, record_block_hash, rollback_l1_blocks,
+    update_last_processed_block,
+};
+use crate::events::l1_event_watcher::{fetch_events_logs_with_provider, TestEthereumProvider};
+use alloy::rpc::types::Log;
+use alloy::sol_types::SolEvent;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::marker::PhantomData;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::sleep;
+use tracing::{debug, error, warn};
+
+/// Recorded block hashes compared with the chain when looking for the start of a re-org
+const REORG_SEARCH_DEPTH: i64 = 64;
+
+#[derive(Error, Debug)]
+pub enum EventWatcherError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("L1 provider error: {0}")]
+    Provider(String),
+
+    #[error("Final block {0} not found")]
+    BlockNotFound(u64),
+
+    #[error("Invalid event: {0}")]
+    InvalidEvent(String),
+}
+
+/// Stores the logs of one event type, before the watcher's cursor moves past them
+#[async_trait]
+pub trait EventRecorder<E: SolEvent>: Send + Sync {
+    async fn record(
+        &self,
+        pool: &PgPool,
+        network: &NetworkId,
+        logs: &[Log<E>],
+    ) -> Result<(), EventWatcherError>;
+}
+
+/// Polls the final blocks of one network's L1 contract for events of type `E`.
+///
+/// The `block_tracker_key` cursor holds the last block polled, and is only moved once
+/// the logs up to it are recorded, so a failed poll fetches the same blocks again.
+pub struct EventWatcher<E: SolEvent, P: TestEthereumProvider> {
+    provider: P,
+    network: NetworkId,
+    contract_addr: String,
+    block_tracker_key: &'static str,
+    from_block_default: u64,
+    confirmations: u64,
+    recorder: Option<Box<dyn EventRecorder<E>>>,
+    reorg_tracking: bool,
+    _event: PhantomData<fn() -> E>,
+}
+
+impl<E, P> EventWatcher<E, P>
+where
+    E: SolEvent + Send + Sync + 'static,
+    P: TestEthereumProvider,
+{
+    /// Watcher starting from `from_block_default` until its cursor is first moved
+    pub fn new(
+        provider: P,
+        network: NetworkId,
+        contract_addr: impl Into<String>,
+        block_tracker_key: &'static str,
+        from_block_default: u64,
+    ) -> Self {
+        Self {
+            provider,
+            network,
+            contract_addr: contract_addr.into(),
+            block_tracker_key,
+            from_block_default,
+            confirmations: 0,
+            recorder: None,
+            reorg_tracking: false,
+            _event: PhantomData,
+        }
+    }
+
+    /// Only polls blocks with at least `confirmations` blocks on top of them
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    pub fn with_recorder(mut self, recorder: impl EventRecorder<E> + 'static) -> Self {
+        self.recorder = Some(Box::new(recorder));
+        self
+    }
+
+    /// Records the hash of every block the cursor is moved to, and rolls back what was
+    /// recorded from blocks re-orged away before polling them again
+    pub fn with_reorg_tracking(mut self) -> Self {
+        self.reorg_tracking = true;
+        self
+    }
+
+    /// Fetches and records the logs of the final blocks after the cursor, then moves the
+    /// cursor to the last of them
+    pub async fn poll_once(&mut self, pool: &mut PgPool) -> Result<Vec<Log<E>>, EventWatcherError> {
+        if self.reorg_tracking {
+            self.roll_back_reorg(pool).await?;
+        }
+
+        let from_block =
+            match get_last_processed_block(pool, &self.network, self.block_tracker_key).await {
+                Ok(Some(last_block)) => last_block + 1,
+                Ok(None) => self.from_block_default,
+                Err(e) => {
+                    warn!(
+                        "Failed to get last processed block for {}: {}",
+                        self.block_tracker_key, e
+                    );
+                    self.from_block_default
+                }
+            };
+
+        // Only final blocks are fetched, younger ones may still be re-orged away
+        let latest_block = self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| EventWatcherError::Provider(e.to_string()))?;
+        let Some(to_block) = latest_block.checked_sub(self.confirmations) else {
+            return Ok(Vec::new());
+        };
+        if from_block > to_block {
+            return Ok(Vec::new());
+        }
+        let to_block_hash = if self.reorg_tracking {
+            let hash = self
+                .provider
+                .get_block_hash(to_block)
+                .await
+                .map_err(|e| EventWatcherError::Provider(e.to_string()))?
+                .ok_or(EventWatcherError::BlockNotFound(to_block))?;
+            Some(hash)
+        } else {
+            None
+        };
+
+        let logs = fetch_events_logs_with_provider::<E, P>(
+            from_block,
+            to_block,
+            &self.contract_addr,
+            E::SIGNATURE,
+            &self.provider,
+        )
+        .await
+        .map_err(|e| EventWatcherError::Provider(e.to_string()))?;
+        debug!(
+            "Fetched {} {} logs of network {} from blocks {}..={}",
+            logs.len(),
+            E::SIGNATURE,
+            self.network,
+            from_block,
+            to_block
+        );
+
+        if let Some(recorder) = &self.recorder {
+            recorder.record(pool, &self.network, &logs).await?;
+        }
+
+        if let Err(e) =
+            update_last_processed_block(pool, &self.network, self.block_tracker_key, to_block).await
+        {
+            warn!(
+                "Failed to update last processed block for {}: {}",
+                self.block_tracker_key, e
+            );
+        }
+        if let Some(hash) = to_block_hash {
+            if let Err(e) =
+                record_block_hash(pool, &self.network, to_block, &format!("{:#x}", hash)).await
+            {
+                warn!("Failed to record hash of block {}: {}", to_block, e);
+            }
+        }
+
+        Ok(logs)
+    }
+
+    async fn roll_back_reorg(&self, pool: &PgPool) -> Result<(), EventWatcherError> {
+        let Some(reorg_block) = self.find_reorged_block(pool).await? else {
+            return Ok(());
+        };
+
+        let mut tx = pool.begin().await?;
+        let reorged =
+            rollback_l1_blocks(&mut tx, &self.network, self.block_tracker_key, reorg_block).await?;
+        tx.commit().await?;
+        warn!(
+            "L1 re-org on network {} from block {}, re-orged deposits: {:?}",
+            self.network, reorg_block, reorged
+        );
+        Ok(())
+    }
+
+    /// Compares the recorded hashes of the network's tracked blocks with the chain,
+    /// newest first, and returns the first block that has to be fetched again, if any.
+    ///
+    /// Only the blocks the cursor was moved to are recorded, so a re-org is rolled back
+    /// from the block after the newest recorded block still on the chain.
+    async fn find_reorged_block(&self, pool: &PgPool) -> Result<Option<u64>, EventWatcherError> {
+        let recorded = fetch_recent_block_hashes(pool, &self.network, REORG_SEARCH_DEPTH).await?;
+
+        let mut reorged = None;
+        for block in recorded {
+            let block_number = block.block_number as u64;
+            let canonical = self
+                .provider
+                .get_block_hash(block_number)
+                .await
+                .map_err(|e| EventWatcherError::Provider(e.to_string()))?;
+            if canonical.is_some_and(|hash| format!("{:#x}", hash) == block.block_hash) {
+                return Ok(reorged.map(|_| block_number + 1));
+            }
+            reorged = Some(block_number);
+        }
+
+        Ok(reorged)
+    }
+}
+
+/// Event watcher of any event type, so watchers of different events can be polled by
+/// the same [`start_event_loop`]
+#[async_trait]
+pub trait AnyEventWatcher: Send + Sync {
+    /// Block tracker key of the watcher, naming it in logs
+    fn name(&self) -> &'static str;
+
+    /// Polls once, returning the number of logs recorded
+    async fn poll(&mut self, pool: &mut PgPool) -> Result<usize, EventWatcherError>;
+}
+
+#[async_trait]
+impl<E, P> AnyEventWatcher for EventWatcher<E, P>
+where
+    E: SolEvent + Send + Sync + 'static,
+    P: TestEthereumProvider + 'static,
+{
+    fn name(&self) -> &'static str {
+        self.block_tracker_key
+    }
+
+    async fn poll(&mut self, pool: &mut PgPool) -> Result<usize, EventWatcherError> {
+        Ok(self.poll_once(pool).await?.len())
+    }
+}
+
+/// Polls every watcher in turn, every `interval`, until the process stops
+pub async fn start_event_loop(
+    mut pool: PgPool,
+    mut watchers: Vec<Box<dyn AnyEventWatcher>>,
+    interval: Duration,
+) {
+    loop {
+        for watcher in watchers.iter_mut() {
+            match watcher.poll(&mut pool).await {
+                Ok(0) => {}
+                Ok(count) => debug!("{} recorded {} events", watcher.name(), count),
+                Err(e) => error!("Event watcher {} failed: {}", watcher.name(), e),
+            }
+        }
+
+        sleep(interval).await;
+    }
+}
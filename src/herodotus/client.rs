@@ -0,0 +1,234 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::sleep;
+use tracing::warn;
+
+use crate::config::HerodotusConfig;
+
+#[derive(Error, Debug)]
+pub enum HerodotusError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Herodotus API returned {status}: {body}")]
+    Api { status: u16, body: String },
+
+    #[error("Invalid Herodotus response: {0}")]
+    InvalidResponse(String),
+}
+
+impl HerodotusError {
+    /// Whether the request may succeed when sent again: the API could not be reached,
+    /// was rate limited or was temporarily unavailable
+    pub fn is_transient(&self) -> bool {
+        match self {
+            HerodotusError::Http(_) => true,
+            HerodotusError::Api { status, .. } => matches!(status, 429 | 500 | 502 | 503 | 504),
+            HerodotusError::InvalidResponse(_) => false,
+        }
+    }
+}
+
+/// Identifier Herodotus assigns to a storage proof request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobId(pub String);
+
+impl fmt::Display for JobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Progress of a storage proof job
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobStatus {
+    Pending,
+    /// The proof was generated, holds the payload returned by the API
+    Done(Value),
+    Failed(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubmitResponse {
+    internal_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusResponse {
+    query_status: String,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Client of the Herodotus storage proof API
+pub struct HerodotusClient {
+    http: Client,
+    endpoint: String,
+    api_key: String,
+    source_chain_id: String,
+    destination_chain_id: String,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+impl HerodotusClient {
+    pub fn new(config: &HerodotusConfig, api_key: impl Into<String>) -> Self {
+        Self {
+            http: Client::new(),
+            endpoint: config.herodotus_endpoint.trim_end_matches('/').to_string(),
+            api_key: api_key.into(),
+            source_chain_id: config.source_chain_id.clone(),
+            destination_chain_id: config.destination_chain_id.clone(),
+            max_retries: config.max_retries,
+            retry_backoff: Duration::from_millis(config.retry_backoff_ms),
+        }
+    }
+
+    /// Creates a client authenticated with `HERODOTUS_API_KEY`
+    pub fn from_config(config: &HerodotusConfig) -> Self {
+        Self::new(config, config.get_api_key())
+    }
+
+    /// Requests a proof of `slot` in `contract`'s storage at L1 block `block_number`
+    pub async fn request_storage_proof(
+        &self,
+        block_number: u64,
+        contract: &str,
+        slot: &str,
+    ) -> Result<JobId, HerodotusError> {
+        let body = json!({
+            "destinationChainId": self.destination_chain_id,
+            "fee": "0",
+            "data": {
+                &self.source_chain_id: {
+                    format!("block:{}", block_number): {
+                        "accounts": {
+                            contract: { "slots": [slot] }
+                        }
+                    }
+                }
+            }
+        });
+
+        let response: SubmitResponse = self
+            .send(|| {
+                self.http
+                    .post(format!("{}/submit-batch-query", self.endpoint))
+                    .query(&[("apiKey", &self.api_key)])
+                    .json(&body)
+            })
+            .await?;
+
+        Ok(JobId(response.internal_id))
+    }
+
+    /// Fetches the status of a job, with the proof once it is done
+    pub async fn poll_job(&self, job_id: &JobId) -> Result<JobStatus, HerodotusError> {
+        let payload: Value = self
+            .send(|| {
+                self.http
+                    .get(format!("{}/batch-query-status", self.endpoint))
+                    .query(&[("apiKey", &self.api_key), ("batchQueryId", &job_id.0)])
+            })
+            .await?;
+        let status: StatusResponse = serde_json::from_value(payload.clone())
+            .map_err(|e| HerodotusError::InvalidResponse(e.to_string()))?;
+
+        Ok(match status.query_status.to_ascii_uppercase().as_str() {
+            "DONE" => JobStatus::Done(payload),
+            "FAILED" | "CANCELLED" => JobStatus::Failed(
+                status
+                    .error
+                    .unwrap_or_else(|| format!("job {}", status.query_status.to_lowercase())),
+            ),
+            _ => JobStatus::Pending,
+        })
+    }
+
+    /// Sends the request built by `request`, retrying transient failures with an
+    /// exponential backoff until `max_retries` attempts were made
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<T, HerodotusError> {
+        let mut attempts = 0;
+        let mut backoff = self.retry_backoff;
+
+        loop {
+            attempts += 1;
+            let result = match request().send().await {
+                Ok(response) => parse_response(response).await,
+                Err(e) => Err(e.into()),
+            };
+
+            match result {
+                Err(e) if e.is_transient() && attempts < self.max_retries => {
+                    warn!(
+                        "Herodotus request failed (attempt {}/{}): {}. Retrying in {:?}",
+                        attempts, self.max_retries, e, backoff
+                    );
+                    sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+async fn parse_response<T: for<'de> Deserialize<'de>>(
+    response: reqwest::Response,
+) -> Result<T, HerodotusError> {
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(HerodotusError::Api {
+            status: status.as_u16(),
+            body,
+        });
+    }
+
+    serde_json::from_str(&body).map_err(|e| HerodotusError::InvalidResponse(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = HerodotusError::Api {
+            status: 503,
+            body: "maintenance".to_string(),
+        };
+        assert_eq!(err.to_string(), "Herodotus API returned 503: maintenance");
+
+        let err = HerodotusError::InvalidResponse("missing field `internalId`".to_string());
+        assert_eq!(
+            err.to_string(),
+            "Invalid Herodotus response: missing field `internalId`"
+        );
+    }
+
+    #[test]
+    fn test_only_unavailable_or_rate_limited_api_is_transient() {
+        let api = |status| HerodotusError::Api {
+            status,
+            body: String::new(),
+        };
+
+        for status in [429, 500, 502, 503, 504] {
+            assert!(api(status).is_transient(), "{} is transient", status);
+        }
+        for status in [400, 401, 404, 501] {
+            assert!(!api(status).is_transient(), "{} is not transient", status);
+        }
+        assert!(!HerodotusError::InvalidResponse("not json".to_string()).is_transient());
+    }
+}
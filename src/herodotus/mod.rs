@@ -0,0 +1,14 @@
+//! Herodotus storage proofs of the L2 root on L1.
+//!
+//! Withdrawals cannot be finalized on L1 before the L2 root they are included in is
+//! proven there. The withdrawal queue moves withdrawals included in the L2 tree to
+//! `AWAITING_STATE_PROOF` and requests their proof through a [`StateProofRequester`].
+//! [`StateProofPoller`] polls the jobs through [`HerodotusClient`], requests them again
+//! when they fail, and hands the withdrawal over to the Ethereum relayer once the proof
+//! is returned.
+
+pub mod client;
+pub mod poller;
+
+pub use client::{HerodotusClient, HerodotusError, JobId, JobStatus};
+pub use poller::{StateProofError, StateProofPoller, StateProofRequester, StateProofSummary};
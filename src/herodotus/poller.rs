@@ -0,0 +1,321 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+use crate::config::{HerodotusConfig, NetworkId};
+use crate::db::database::{
+    complete_herodotus_job, fail_herodotus_job, fetch_pending_herodotus_jobs,
+    fetch_withdrawals_awaiting_state_proof, increment_withdrawal_retry_count, insert_herodotus_job,
+    update_withdrawal_status, HerodotusJob,
+};
+use crate::herodotus::client::{HerodotusClient, JobId, JobStatus};
+use crate::queue::l1_queue::{L1BlockSource, ValidationError};
+
+#[derive(Error, Debug)]
+pub enum StateProofError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Failed to read the latest L1 block: {0}")]
+    L1Block(#[from] ValidationError),
+}
+
+/// Withdrawals moved by a polling run, by id
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StateProofSummary {
+    /// Withdrawals a storage proof was requested for
+    pub requested: Vec<i32>,
+    /// Withdrawals whose proof arrived, now ready for relay
+    pub completed: Vec<i32>,
+    /// Withdrawals whose job failed and will be requested again
+    pub retried: Vec<i32>,
+    /// Withdrawals that ran out of retries
+    pub failed: Vec<i32>,
+}
+
+/// Requests storage proofs for withdrawals as the withdrawal queue moves them to
+/// `AWAITING_STATE_PROOF`, see [`WithdrawalQueueProcessor::with_state_proofs`].
+///
+/// [`WithdrawalQueueProcessor::with_state_proofs`]: crate::queue::l1_queue::WithdrawalQueueProcessor::with_state_proofs
+#[async_trait]
+pub trait StateProofRequester: Send + Sync {
+    /// Requests a proof for each of `withdrawal_ids`, recording the jobs through `conn`
+    async fn request_state_proofs(
+        &self,
+        conn: &mut PgConnection,
+        withdrawal_ids: &[i32],
+    ) -> Result<StateProofSummary, StateProofError>;
+}
+
+/// Requests Herodotus storage proofs of the L2 root for a network's withdrawals in
+/// `AWAITING_STATE_PROOF` and advances them to `ready_for_relay` once the proof is
+/// returned.
+///
+/// Failed jobs, and jobs still running after `herodotus.job_timeout_sec`, count as a
+/// retry of the withdrawal and are requested again until `max_retries` is reached.
+pub struct StateProofPoller<P: L1BlockSource> {
+    db_pool: PgPool,
+    network: NetworkId,
+    client: HerodotusClient,
+    config: HerodotusConfig,
+    /// The network's L1 contract, whose storage holds the L2 root
+    contract: String,
+    max_retries: u32,
+    provider: P,
+}
+
+impl<P: L1BlockSource> StateProofPoller<P> {
+    pub fn new(
+        db_pool: PgPool,
+        network: NetworkId,
+        client: HerodotusClient,
+        config: HerodotusConfig,
+        contract: impl Into<String>,
+        max_retries: u32,
+        provider: P,
+    ) -> Self {
+        Self {
+            db_pool,
+            network,
+            client,
+            config,
+            contract: contract.into(),
+            max_retries,
+            provider,
+        }
+    }
+
+    /// Runs the poller in an infinite loop.
+    pub async fn start(&self) {
+        info!(
+            "Starting Herodotus state proof poller for network {}",
+            self.network
+        );
+
+        loop {
+            match self.poll().await {
+                Ok(summary) => {
+                    if !summary.completed.is_empty() {
+                        info!(
+                            "State proofs received for withdrawals {:?}",
+                            summary.completed
+                        );
+                    }
+                    if !summary.failed.is_empty() {
+                        warn!(
+                            "Withdrawals failed waiting on a state proof: {:?}",
+                            summary.failed
+                        );
+                    }
+                }
+                Err(e) => error!("State proof polling failed: {:?}", e),
+            }
+            sleep(Duration::from_secs(self.config.poll_interval_sec)).await;
+        }
+    }
+
+    /// Polls the jobs in progress, then requests proofs for withdrawals without one.
+    pub async fn poll(&self) -> Result<StateProofSummary, StateProofError> {
+        let mut summary = StateProofSummary::default();
+
+        for job in fetch_pending_herodotus_jobs(&self.db_pool, &self.network).await? {
+            self.check_job(&job, &mut summary).await?;
+        }
+
+        let withdrawals =
+            fetch_withdrawals_awaiting_state_proof(&self.db_pool, &self.network, self.max_retries)
+                .await?;
+        if withdrawals.is_empty() {
+            return Ok(summary);
+        }
+
+        let mut conn = self.db_pool.acquire().await?;
+        self.request_jobs(&mut conn, &withdrawals, &mut summary)
+            .await?;
+
+        Ok(summary)
+    }
+
+    /// Requests a storage proof of the L2 root at the latest L1 block for each of
+    /// `withdrawal_ids`, recording the jobs through `conn`.
+    ///
+    /// A failed request counts as a retry of the withdrawal, which is requested again on
+    /// the next run.
+    async fn request_jobs(
+        &self,
+        conn: &mut PgConnection,
+        withdrawal_ids: &[i32],
+        summary: &mut StateProofSummary,
+    ) -> Result<(), StateProofError> {
+        let block_number = self.provider.latest_block_number().await?;
+        for &withdrawal_id in withdrawal_ids {
+            match self
+                .client
+                .request_storage_proof(block_number, &self.contract, &self.config.l2_root_slot)
+                .await
+            {
+                Ok(job_id) => {
+                    insert_herodotus_job(
+                        conn,
+                        withdrawal_id,
+                        &job_id.0,
+                        block_number as i64,
+                        &self.contract,
+                        &self.config.l2_root_slot,
+                    )
+                    .await?;
+                    summary.requested.push(withdrawal_id);
+                }
+                Err(e) => {
+                    let retry_count = increment_withdrawal_retry_count(conn, withdrawal_id).await?;
+                    self.record_failure(conn, withdrawal_id, retry_count, &e.to_string(), summary)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_job(
+        &self,
+        job: &HerodotusJob,
+        summary: &mut StateProofSummary,
+    ) -> Result<(), StateProofError> {
+        let status = match self.client.poll_job(&JobId(job.job_id.clone())).await {
+            Ok(status) => status,
+            // The job may still complete, try again on the next run
+            Err(e) => {
+                warn!("Failed to poll Herodotus job {}: {}", job.job_id, e);
+                return Ok(());
+            }
+        };
+
+        let reason = match status {
+            JobStatus::Done(proof) => {
+                let mut tx = self.db_pool.begin().await?;
+                complete_herodotus_job(&mut tx, job, &proof).await?;
+                tx.commit().await?;
+                summary.completed.push(job.withdrawal_id);
+                return Ok(());
+            }
+            JobStatus::Pending if !self.timed_out(job) => return Ok(()),
+            JobStatus::Pending => format!(
+                "job {} still running after {}s",
+                job.job_id, self.config.job_timeout_sec
+            ),
+            JobStatus::Failed(reason) => reason,
+        };
+
+        let mut tx = self.db_pool.begin().await?;
+        let retry_count = fail_herodotus_job(&mut tx, job, &reason).await?;
+        self.record_failure(&mut tx, job.withdrawal_id, retry_count, &reason, summary)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Fails the withdrawal once it has used up its retries
+    async fn record_failure(
+        &self,
+        conn: &mut PgConnection,
+        withdrawal_id: i32,
+        retry_count: i32,
+        reason: &str,
+        summary: &mut StateProofSummary,
+    ) -> Result<(), StateProofError> {
+        if retry_count >= self.max_retries as i32 {
+            error!(
+                "State proof for withdrawal {} failed permanently: {}",
+                withdrawal_id, reason
+            );
+            update_withdrawal_status(
+                conn,
+                withdrawal_id,
+                "failed",
+                Some(&format!("State proof failed: {}", reason)),
+            )
+            .await?;
+            summary.failed.push(withdrawal_id);
+        } else {
+            warn!(
+                "State proof for withdrawal {} failed: {}. Will retry.",
+                withdrawal_id, reason
+            );
+            summary.retried.push(withdrawal_id);
+        }
+        Ok(())
+    }
+
+    fn timed_out(&self, job: &HerodotusJob) -> bool {
+        is_timed_out(job, self.config.job_timeout_sec, Utc::now())
+    }
+}
+
+#[async_trait]
+impl<P: L1BlockSource> StateProofRequester for StateProofPoller<P> {
+    async fn request_state_proofs(
+        &self,
+        conn: &mut PgConnection,
+        withdrawal_ids: &[i32],
+    ) -> Result<StateProofSummary, StateProofError> {
+        let mut summary = StateProofSummary::default();
+        self.request_jobs(conn, withdrawal_ids, &mut summary)
+            .await?;
+        Ok(summary)
+    }
+}
+
+/// Whether `job` has been running for at least `timeout_sec` by `now`
+fn is_timed_out(job: &HerodotusJob, timeout_sec: u64, now: DateTime<Utc>) -> bool {
+    now.signed_duration_since(job.created_at)
+        .to_std()
+        .is_ok_and(|age| age >= Duration::from_secs(timeout_sec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_created_at(created_at: DateTime<Utc>) -> HerodotusJob {
+        HerodotusJob {
+            id: 1,
+            withdrawal_id: 1,
+            job_id: "job".to_string(),
+            block_number: 100,
+            contract_address: "0xbridge".to_string(),
+            storage_slot: "0x0".to_string(),
+            status: "pending".to_string(),
+            error_message: None,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_job_older_than_timeout_is_timed_out() {
+        let now = Utc::now();
+
+        assert!(is_timed_out(
+            &job_created_at(now - chrono::Duration::seconds(60)),
+            60,
+            now
+        ));
+        assert!(!is_timed_out(
+            &job_created_at(now - chrono::Duration::seconds(59)),
+            60,
+            now
+        ));
+        // Clock skew between the database and the sequencer
+        assert!(!is_timed_out(
+            &job_created_at(now + chrono::Duration::seconds(5)),
+            60,
+            now
+        ));
+    }
+}
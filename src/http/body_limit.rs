@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::error::ApiError;
+use crate::config::default_max_body_bytes;
+
+/// Largest request body accepted by the API, set by `server.max_body_bytes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimit(pub usize);
+
+impl Default for BodyLimit {
+    fn default() -> Self {
+        Self(default_max_body_bytes())
+    }
+}
+
+impl BodyLimit {
+    pub fn payload_too_large(&self) -> ApiError {
+        ApiError::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "payload_too_large",
+            format!("Request body must not exceed {} bytes", self.0),
+        )
+    }
+}
+
+/// Axum middleware rejecting requests whose declared `Content-Length` exceeds the
+/// limit before the body is read.
+///
+/// Bodies sent without a length are capped by `DefaultBodyLimit` when extracted; the
+/// limit is also added to the request extensions for middleware reading raw bodies.
+pub async fn limit_body_size(
+    State(limit): State<BodyLimit>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let content_length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+
+    match content_length {
+        Some(length) if length > limit.0 => limit.payload_too_large().into_response(),
+        _ => {
+            request.extensions_mut().insert(limit);
+            next.run(request).await
+        }
+    }
+}
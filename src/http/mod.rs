@@ -1 +1,5 @@
+pub mod body_limit;
 pub mod client;
+pub mod rate_limiter;
+pub mod request_id;
+pub mod timeout;
@@ -0,0 +1,307 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, Once};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::api::error::ApiError;
+use crate::config::RateLimitConfig;
+
+/// Number of independently locked partitions of the bucket map
+const SHARD_COUNT: usize = 16;
+
+/// How often a shard drops buckets of clients that have gone quiet
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Warns once that requests arrive without their peer address
+static MISSING_CONNECT_INFO: Once = Once::new();
+
+/// Token bucket tracked for a single client
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+struct Shard {
+    buckets: HashMap<IpAddr, Bucket>,
+    last_eviction: Instant,
+}
+
+/// Token-bucket rate limiter keyed on the peer IP address.
+///
+/// Buckets are spread over [`SHARD_COUNT`] mutex-guarded maps so concurrent requests
+/// from different clients rarely contend on the same lock.
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    shards: Arc<Vec<Mutex<Shard>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        let now = Instant::now();
+        let shards = (0..SHARD_COUNT)
+            .map(|_| {
+                Mutex::new(Shard {
+                    buckets: HashMap::new(),
+                    last_eviction: now,
+                })
+            })
+            .collect();
+
+        Self {
+            config,
+            shards: Arc::new(shards),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Takes a token from the bucket of `key`.
+    ///
+    /// Returns the time until the next token becomes available when the bucket is empty.
+    pub fn check(&self, key: IpAddr) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    /// Number of clients currently holding a bucket
+    pub fn tracked_clients(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().buckets.len())
+            .sum()
+    }
+
+    fn burst(&self) -> f64 {
+        self.config.burst_size.max(1) as f64
+    }
+
+    // Tokens refilled per second
+    fn rate(&self) -> f64 {
+        self.config.requests_per_minute.max(1) as f64 / 60.0
+    }
+
+    fn shard(&self, key: &IpAddr) -> &Mutex<Shard> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+
+    fn check_at(&self, key: IpAddr, now: Instant) -> Result<(), Duration> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let burst = self.burst();
+        let rate = self.rate();
+
+        let mut shard = self.shard(&key).lock().unwrap();
+        if now.saturating_duration_since(shard.last_eviction) >= EVICTION_INTERVAL {
+            self.evict_idle(&mut shard, now);
+        }
+
+        let bucket = shard.buckets.entry(key).or_insert(Bucket {
+            tokens: burst,
+            last_refill: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate).min(burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+
+    // A bucket idle long enough to refill completely behaves exactly like a fresh one,
+    // so dropping it loses nothing and keeps the map bounded by the active clients
+    fn evict_idle(&self, shard: &mut Shard, now: Instant) {
+        let burst = self.burst();
+        let rate = self.rate();
+
+        shard.buckets.retain(|_, bucket| {
+            let elapsed = now.saturating_duration_since(bucket.last_refill);
+            bucket.tokens + elapsed.as_secs_f64() * rate < burst
+        });
+        shard.last_eviction = now;
+    }
+}
+
+/// Axum middleware rejecting requests with `429 Too Many Requests` once the
+/// caller has exhausted its burst allowance.
+///
+/// Callers are told apart by the peer address the server records when it is started
+/// with `into_make_service_with_connect_info::<SocketAddr>()`. Without it every client
+/// would share one bucket, so requests are let through unlimited, with a warning.
+pub async fn rate_limit(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !limiter.is_enabled() {
+        return next.run(request).await;
+    }
+
+    let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>() else {
+        MISSING_CONNECT_INFO.call_once(|| {
+            warn!(
+                "Rate limiting is skipped: the server was not started with \
+                 into_make_service_with_connect_info::<SocketAddr>()"
+            )
+        });
+        return next.run(request).await;
+    };
+    let client_ip = peer.ip();
+
+    match limiter.check(client_ip) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            warn!("Rate limit exceeded for {}", client_ip);
+            too_many_requests(retry_after)
+        }
+    }
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    // Retry-After is expressed in whole seconds, so round up to avoid an immediate retry
+    let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    let mut response = ApiError::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        "rate_limited",
+        "Too many requests",
+    )
+    .into_response();
+    response.headers_mut().insert(
+        header::RETRY_AFTER,
+        HeaderValue::from(retry_after_secs.max(1)),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn limiter(requests_per_minute: u32, burst_size: u32) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            enabled: true,
+            requests_per_minute,
+            burst_size,
+        })
+    }
+
+    #[test]
+    fn test_burst_is_exhausted() {
+        let limiter = limiter(60, 3);
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check_at(ip, now).is_ok());
+        }
+        assert!(limiter.check_at(ip, now).is_err());
+    }
+
+    #[test]
+    fn test_tokens_refill_over_time() {
+        let limiter = limiter(120, 1);
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let now = Instant::now();
+
+        assert!(limiter.check_at(ip, now).is_ok());
+        let retry_after = limiter.check_at(ip, now).unwrap_err();
+        assert_eq!(retry_after, Duration::from_millis(500));
+
+        assert!(limiter
+            .check_at(ip, now + Duration::from_millis(500))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_clients_have_separate_buckets() {
+        let limiter = limiter(60, 1);
+        let now = Instant::now();
+
+        assert!(limiter
+            .check_at(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), now)
+            .is_ok());
+        assert!(limiter
+            .check_at(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), now)
+            .is_ok());
+        assert!(limiter
+            .check_at(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_idle_clients_are_evicted() {
+        let limiter = limiter(60, 2);
+        let now = Instant::now();
+
+        for i in 0..100 {
+            assert!(limiter
+                .check_at(IpAddr::V4(Ipv4Addr::new(10, 0, 0, i)), now)
+                .is_ok());
+        }
+        assert_eq!(limiter.tracked_clients(), 100);
+
+        let later = now + EVICTION_INTERVAL;
+        for shard in limiter.shards.iter() {
+            limiter.evict_idle(&mut shard.lock().unwrap(), later);
+        }
+        assert_eq!(limiter.tracked_clients(), 0);
+    }
+
+    #[test]
+    fn test_active_clients_survive_eviction() {
+        let limiter = limiter(60, 100);
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let now = Instant::now();
+
+        assert!(limiter.check_at(ip, now).is_ok());
+        assert!(limiter.check_at(ip, now).is_ok());
+
+        // Only one of the two tokens has been refilled a second later
+        let later = now + Duration::from_secs(1);
+        for shard in limiter.shards.iter() {
+            limiter.evict_idle(&mut shard.lock().unwrap(), later);
+        }
+        assert_eq!(limiter.tracked_clients(), 1);
+    }
+
+    #[test]
+    fn test_disabled_limiter_allows_everything() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            enabled: false,
+            requests_per_minute: 1,
+            burst_size: 1,
+        });
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        for _ in 0..10 {
+            assert!(limiter.check(ip).is_ok());
+        }
+        assert_eq!(limiter.tracked_clients(), 0);
+    }
+
+    #[test]
+    fn test_default_is_disabled_in_unit_tests() {
+        assert!(!RateLimitConfig::default().enabled);
+    }
+}
@@ -0,0 +1,18 @@
+use axum::{http::StatusCode, BoxError};
+use tower::timeout::error::Elapsed;
+
+use crate::api::error::ApiError;
+
+/// Turns errors of the timeout layer into API errors, `408 Request Timeout` when the
+/// request took longer than `server.request_timeout_ms`
+pub async fn handle_timeout_error(err: BoxError) -> ApiError {
+    if err.is::<Elapsed>() {
+        ApiError::new(
+            StatusCode::REQUEST_TIMEOUT,
+            "request_timeout",
+            "Request took too long to process",
+        )
+    } else {
+        ApiError::internal(format!("Unhandled middleware error: {}", err))
+    }
+}
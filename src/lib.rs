@@ -2,8 +2,16 @@ pub mod api;
 pub mod config;
 pub mod db;
 pub mod events;
+pub mod herodotus;
 pub mod http;
+pub mod maintenance;
 pub mod proof_client;
+pub mod proof_submitter;
 pub mod queue;
 pub mod relayer;
+pub mod sdk;
+pub mod simulation;
+pub mod telemetry;
+pub mod tree_builder;
 pub mod utils;
+pub mod webhooks;
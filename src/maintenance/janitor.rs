@@ -0,0 +1,357 @@
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+use crate::config::{default_deposit_expiry_hours, JanitorConfig};
+use crate::db::database::{
+    clear_failed_l2_transaction_proofs, expire_unseen_deposits, fetch_deposit_statuses,
+    fetch_withdrawal_nonce_gaps, reclaim_abandoned_proof_claims, reset_stale_processing_deposits,
+    reset_stale_processing_l2_transactions, reset_stale_processing_withdrawals, WithdrawalNonceGap,
+    DEPOSIT_STATUS_CANCELLED,
+};
+use crate::proof_client::service::DEPOSIT_STATUS_FAILED;
+
+/// Deposit statuses after which the proof client's working directory is no longer read
+const FINISHED_DEPOSIT_STATUSES: &[&str] = &[
+    "READY_TO_CLAIM",
+    "PROCESSED",
+    DEPOSIT_STATUS_FAILED,
+    DEPOSIT_STATUS_CANCELLED,
+];
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Error, Debug)]
+pub enum JanitorError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Filesystem error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// What a single janitor run cleaned up
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct JanitorReport {
+    /// Working directories of finished deposits that were deleted
+    pub removed_proof_dirs: Vec<PathBuf>,
+    pub reset_deposits: Vec<i32>,
+    pub reset_withdrawals: Vec<i32>,
+    pub reset_l2_transactions: Vec<i64>,
+    /// Deposits taken back from proof client workers that stopped sending heartbeats
+    pub reclaimed_proof_claims: Vec<i32>,
+    /// Working directories left behind by deposits that no longer exist
+    pub removed_orphaned_dirs: Vec<PathBuf>,
+    /// Failed L2 transactions whose proof data was dropped
+    pub vacuumed_l2_transactions: Vec<i64>,
+    /// Deposits that waited too long for an L1 event that never came
+    pub expired_deposits: Vec<i32>,
+    /// Unused withdrawal nonces with more than the threshold of withdrawals behind them
+    pub withdrawal_nonce_gaps: Vec<WithdrawalNonceGap>,
+}
+
+/// Periodically removes what the proof client and crashed runs leave behind.
+///
+/// Each action can be switched off in [`JanitorConfig`]:
+/// - deleting the `deposit_{id}` working directories of finished deposits,
+/// - putting deposits, withdrawals and L2 transactions stuck in `processing` back to
+///   the status they were picked up from so they are retried, and reclaiming deposits
+///   whose proof client worker stopped refreshing its claim,
+/// - vacuuming dead letters: failed L2 transactions' proof data and working directories
+///   of deposits that no longer exist,
+/// - expiring deposits whose commitment was never seen on L1, e.g. because the user
+///   never sent the L1 transaction,
+/// - alerting on withdrawal nonces left unused by failed or cancelled withdrawals, which
+///   hold back the key's later withdrawals on L1 until filled through
+///   `/admin/withdrawals/fill-nonce-gap`.
+pub struct Janitor {
+    db_pool: PgPool,
+    config: JanitorConfig,
+    deposit_expiry_hours: u64,
+}
+
+impl Janitor {
+    pub fn new(db_pool: PgPool, config: JanitorConfig) -> Self {
+        Self {
+            db_pool,
+            config,
+            deposit_expiry_hours: default_deposit_expiry_hours(),
+        }
+    }
+
+    /// Sets how long deposits never seen on L1 are kept waiting, see
+    /// `queue.deposit_expiry_hours`
+    pub fn with_deposit_expiry_hours(mut self, hours: u64) -> Self {
+        self.deposit_expiry_hours = hours;
+        self
+    }
+
+    /// Runs the janitor in an infinite loop.
+    pub async fn start(&self) {
+        info!("Starting janitor");
+
+        loop {
+            if let Err(e) = self.run_once().await {
+                error!("Janitor run failed: {:?}", e);
+            }
+            sleep(Duration::from_secs(self.config.interval_sec)).await;
+        }
+    }
+
+    /// Runs every enabled cleanup action once.
+    pub async fn run_once(&self) -> Result<JanitorReport, JanitorError> {
+        let mut report = JanitorReport::default();
+
+        if self.config.clean_proof_dirs {
+            report.removed_proof_dirs = self.clean_proof_dirs().await?;
+        }
+        if self.config.reset_stale_processing {
+            self.reset_stale_processing(&mut report).await?;
+        }
+        if self.config.vacuum_dead_letters {
+            self.vacuum_dead_letters(&mut report).await?;
+        }
+        if self.config.expire_unseen_deposits {
+            report.expired_deposits = self.expire_unseen_deposits().await?;
+        }
+        if self.config.check_withdrawal_nonce_gaps {
+            report.withdrawal_nonce_gaps = self.check_withdrawal_nonce_gaps().await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Deletes working directories older than the retention window whose deposit is
+    /// finished.
+    async fn clean_proof_dirs(&self) -> Result<Vec<PathBuf>, JanitorError> {
+        let retention = Duration::from_secs(self.config.proof_dir_retention_sec);
+        let dirs = expired_deposit_dirs(&self.config.artifacts_dir, retention, SystemTime::now())?;
+        let statuses = self.deposit_statuses(&dirs).await?;
+
+        let mut removed = Vec::new();
+        for (deposit_id, path) in dirs {
+            let finished = statuses
+                .get(&deposit_id)
+                .is_some_and(|status| FINISHED_DEPOSIT_STATUSES.contains(&status.as_str()));
+            if finished && remove_dir(&path) {
+                info!(
+                    "Removed working directory {:?} of deposit {}",
+                    path, deposit_id
+                );
+                removed.push(path);
+            }
+        }
+
+        Ok(removed)
+    }
+
+    async fn reset_stale_processing(&self, report: &mut JanitorReport) -> Result<(), JanitorError> {
+        let stale_after = self.config.stale_processing_after_sec as i64;
+
+        report.reset_deposits = reset_stale_processing_deposits(&self.db_pool, stale_after).await?;
+        if !report.reset_deposits.is_empty() {
+            warn!(
+                "Reset deposits stuck in processing: {:?}",
+                report.reset_deposits
+            );
+        }
+
+        report.reset_withdrawals =
+            reset_stale_processing_withdrawals(&self.db_pool, stale_after).await?;
+        if !report.reset_withdrawals.is_empty() {
+            warn!(
+                "Reset withdrawals stuck in processing: {:?}",
+                report.reset_withdrawals
+            );
+        }
+
+        report.reset_l2_transactions =
+            reset_stale_processing_l2_transactions(&self.db_pool, stale_after).await?;
+        if !report.reset_l2_transactions.is_empty() {
+            warn!(
+                "Reset L2 transactions stuck in processing: {:?}",
+                report.reset_l2_transactions
+            );
+        }
+
+        report.reclaimed_proof_claims =
+            reclaim_abandoned_proof_claims(&self.db_pool, stale_after).await?;
+        if !report.reclaimed_proof_claims.is_empty() {
+            warn!(
+                "Reclaimed deposits abandoned by proof client workers: {:?}",
+                report.reclaimed_proof_claims
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn vacuum_dead_letters(&self, report: &mut JanitorReport) -> Result<(), JanitorError> {
+        let retention_days = self.config.dead_letter_retention_days;
+
+        let retention = Duration::from_secs(retention_days * SECONDS_PER_DAY);
+        let dirs = expired_deposit_dirs(&self.config.artifacts_dir, retention, SystemTime::now())?;
+        let statuses = self.deposit_statuses(&dirs).await?;
+
+        for (deposit_id, path) in dirs {
+            if !statuses.contains_key(&deposit_id) && remove_dir(&path) {
+                info!(
+                    "Removed working directory {:?} of unknown deposit {}",
+                    path, deposit_id
+                );
+                report.removed_orphaned_dirs.push(path);
+            }
+        }
+
+        report.vacuumed_l2_transactions =
+            clear_failed_l2_transaction_proofs(&self.db_pool, retention_days as i64).await?;
+        if !report.vacuumed_l2_transactions.is_empty() {
+            info!(
+                "Dropped proof data of failed L2 transactions: {:?}",
+                report.vacuumed_l2_transactions
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn expire_unseen_deposits(&self) -> Result<Vec<i32>, JanitorError> {
+        let reason = format!(
+            "no L1 deposit event within {} hours",
+            self.deposit_expiry_hours
+        );
+        let expired =
+            expire_unseen_deposits(&self.db_pool, self.deposit_expiry_hours as i64, &reason)
+                .await?;
+        if !expired.is_empty() {
+            warn!("Expired deposits never seen on L1: {:?}", expired);
+        }
+
+        Ok(expired)
+    }
+
+    async fn check_withdrawal_nonce_gaps(&self) -> Result<Vec<WithdrawalNonceGap>, JanitorError> {
+        let threshold = self.config.withdrawal_nonce_gap_threshold as i64;
+        let gaps = fetch_withdrawal_nonce_gaps(&self.db_pool, threshold).await?;
+        for gap in &gaps {
+            error!(
+                "Withdrawal nonce {} of {} is unused, {} later withdrawals wait on it",
+                gap.nonce, gap.stark_pub_key, gap.waiting
+            );
+        }
+
+        Ok(gaps)
+    }
+
+    async fn deposit_statuses(
+        &self,
+        dirs: &[(i32, PathBuf)],
+    ) -> Result<HashMap<i32, String>, JanitorError> {
+        if dirs.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let ids: Vec<i32> = dirs.iter().map(|(id, _)| *id).collect();
+        Ok(fetch_deposit_statuses(&self.db_pool, &ids)
+            .await?
+            .into_iter()
+            .collect())
+    }
+}
+
+/// Lists the `deposit_{id}` directories in `artifacts_dir` last modified at least
+/// `retention` before `now`. A missing `artifacts_dir` has no directories.
+fn expired_deposit_dirs(
+    artifacts_dir: &Path,
+    retention: Duration,
+    now: SystemTime,
+) -> io::Result<Vec<(i32, PathBuf)>> {
+    let entries = match fs::read_dir(artifacts_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let Some(deposit_id) = entry.file_name().to_str().and_then(parse_deposit_dir_name) else {
+            continue;
+        };
+
+        let metadata = entry.metadata()?;
+        let age = now.duration_since(metadata.modified()?).unwrap_or_default();
+        if metadata.is_dir() && age >= retention {
+            dirs.push((deposit_id, entry.path()));
+        }
+    }
+
+    Ok(dirs)
+}
+
+fn parse_deposit_dir_name(name: &str) -> Option<i32> {
+    name.strip_prefix("deposit_")?.parse().ok()
+}
+
+// Failing to delete one directory should not stop the rest of the run
+fn remove_dir(path: &Path) -> bool {
+    match fs::remove_dir_all(path) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("Failed to remove {:?}: {}", path, e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_deposit_dir_name() {
+        assert_eq!(parse_deposit_dir_name("deposit_42"), Some(42));
+        assert_eq!(parse_deposit_dir_name("deposit_"), None);
+        assert_eq!(parse_deposit_dir_name("deposit_abc"), None);
+        assert_eq!(parse_deposit_dir_name("withdrawal_42"), None);
+    }
+
+    #[test]
+    fn test_expired_deposit_dirs_skips_fresh_and_unrelated_entries() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("deposit_1")).unwrap();
+        fs::create_dir(dir.path().join("other")).unwrap();
+        fs::write(dir.path().join("deposit_2"), "not a directory").unwrap();
+
+        let retention = Duration::from_secs(60);
+        let now = SystemTime::now();
+        assert!(expired_deposit_dirs(dir.path(), retention, now)
+            .unwrap()
+            .is_empty());
+
+        let later = now + Duration::from_secs(120);
+        assert_eq!(
+            expired_deposit_dirs(dir.path(), retention, later).unwrap(),
+            vec![(1, dir.path().join("deposit_1"))]
+        );
+    }
+
+    #[test]
+    fn test_expired_deposit_dirs_without_artifacts_dir() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("missing");
+
+        assert!(
+            expired_deposit_dirs(&missing, Duration::ZERO, SystemTime::now())
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
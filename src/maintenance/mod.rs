@@ -0,0 +1,3 @@
+pub mod janitor;
+
+pub use janitor::{Janitor, JanitorError, JanitorReport};
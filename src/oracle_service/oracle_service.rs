@@ -1,4 +1,4 @@
-use crate::config::AppConfig;
+use crate::config::LiveConfig;
 use ethers::prelude::*;
 use std::time::Duration;
 use tokio::time::sleep;
@@ -24,7 +24,7 @@ pub async fn initializer() {
     // );
 
     // tokio::spawn(async move {
-    //     sync_tvl(l1_contract, l2_contract, &config)
+    //     sync_tvl(l1_contract, l2_contract, &live_config)
     //         .await
     //         .expect("TVL sync failed");
     // });
@@ -62,19 +62,20 @@ async fn update_l2_tvl(
     Ok(())
 }
 
-/// Sync TVL between L1 and L2
+/// Sync TVL between L1 and L2, taking `[oracle]` from `live_config` on every tick so a
+/// reloaded tolerance or interval applies without a restart
 pub async fn sync_tvl(
     l1_contract: Contract<Provider<Http>>,
     l2_contract: Contract<Provider<Http>>,
-    config: &AppConfig,
+    live_config: &LiveConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let tolerance_percent = config
-        .oracle
-        .tolerance_percent
-        .unwrap_or(DEFAULT_TOLERANCE_PERCENT);
-    let polling_interval = Duration::from_secs(config.oracle.polling_interval_seconds);
-
     loop {
+        let oracle = live_config.snapshot().oracle;
+        let tolerance_percent = oracle
+            .tolerance_percent
+            .unwrap_or(DEFAULT_TOLERANCE_PERCENT);
+        let polling_interval = Duration::from_secs(oracle.polling_interval_seconds);
+
         // Fetch TVL values
         let l1_tvl = fetch_l1_tvl(&l1_contract).await?;
         let l2_tvl = fetch_l2_tvl(&l2_contract).await?;
@@ -0,0 +1,749 @@
+use async_trait::async_trait;
+use aws_sdk_s3::{error::DisplayErrorContext, primitives::ByteStream, Client};
+use bytes::Bytes;
+use futures_util::stream::{self, Stream};
+use proof_pipeline::pipeline::{CalldataArtifacts, ProofError};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt};
+
+use crate::config::{ArtifactStoreKind, ProofConfig};
+use crate::proof_client::checkpoint::ProofCheckpoint;
+use crate::proof_submitter::calldata::{FACT_HASH_FILE, FINAL_FILE, INITIAL_FILE};
+
+/// Name the proof is stored under, next to the calldata files
+const PROOF_FILE: &str = "proof.json";
+
+/// SHA-256 of every saved file of the local store, keyed by its path in the deposit
+/// directory
+const ARTIFACT_HASHES_FILE: &str = "artifact_hashes.json";
+
+/// User metadata holding the SHA-256 of each object saved by the s3 store
+const SHA256_METADATA: &str = "sha256";
+
+/// Size of the chunks artifacts are streamed in
+const STREAM_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Bytes of a saved artifact, read as they are sent
+pub type ArtifactStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
+
+#[derive(Error, Debug)]
+pub enum ArtifactStoreError {
+    #[error("File I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("S3 error: {0}")]
+    S3(String),
+
+    #[error("Stored artifacts are invalid: {0}")]
+    InvalidArtifacts(String),
+
+    #[error("The s3 artifact store requires proof.s3_bucket to be set")]
+    MissingBucket,
+}
+
+impl From<ProofError> for ArtifactStoreError {
+    fn from(err: ProofError) -> Self {
+        match err {
+            ProofError::Io(e) => Self::Io(e),
+            other => Self::InvalidArtifacts(format!("{:?}", other)),
+        }
+    }
+}
+
+/// Where the artifacts of a deposit ended up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactLocation {
+    Local(PathBuf),
+    S3 { bucket: String, prefix: String },
+}
+
+impl fmt::Display for ArtifactLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(path) => write!(f, "{}", path.display()),
+            Self::S3 { bucket, prefix } => write!(f, "s3://{}/{}", bucket, prefix),
+        }
+    }
+}
+
+/// A saved artifact of a deposit that can be downloaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactName {
+    /// The Stone proof, `proof.json`
+    Proof,
+    /// Fact hash registered by the verifier, `fact_hash.txt`
+    FactHash,
+    /// A calldata file, `calldata/initial`, `calldata/step{n}` or `calldata/final`
+    Calldata(String),
+}
+
+impl ArtifactName {
+    /// Parses the name an artifact is downloaded under, `None` for anything else
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            PROOF_FILE => Some(Self::Proof),
+            "fact_hash.txt" => Some(Self::FactHash),
+            _ => {
+                let file = name.strip_prefix("calldata/")?;
+                let is_step = file
+                    .strip_prefix("step")
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+                (file == INITIAL_FILE || file == FINAL_FILE || is_step)
+                    .then(|| Self::Calldata(file.to_string()))
+            }
+        }
+    }
+
+    /// Path of the artifact in the deposit's directory or key prefix
+    pub fn stored_path(&self) -> &str {
+        match self {
+            Self::Proof => PROOF_FILE,
+            Self::FactHash => FACT_HASH_FILE,
+            Self::Calldata(file) => file,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Proof => "application/json",
+            Self::FactHash | Self::Calldata(_) => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+/// Size of a saved artifact and the SHA-256 recorded when it was saved, `None` for
+/// artifacts saved before hashes were recorded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactInfo {
+    pub size: u64,
+    pub sha256: Option<String>,
+}
+
+/// Persists the calldata and proof generated for each deposit.
+///
+/// Every deposit gets its own `deposit_{id}` directory or key prefix holding the
+/// calldata files produced by swiftness and the proof as `proof.json`.
+#[async_trait]
+pub trait ProofArtifactStore: Send + Sync {
+    async fn save(
+        &self,
+        deposit_id: i32,
+        artifacts: &CalldataArtifacts,
+    ) -> Result<ArtifactLocation, ArtifactStoreError>;
+
+    /// Returns the saved artifacts, `None` when no proof was saved for the deposit
+    async fn load(&self, deposit_id: i32) -> Result<Option<CalldataArtifacts>, ArtifactStoreError>;
+
+    /// Saves the pipeline checkpoint of a deposit being proved, returning `false` when
+    /// the store does not keep checkpoints
+    async fn save_checkpoint(
+        &self,
+        _deposit_id: i32,
+        _checkpoint: &ProofCheckpoint,
+    ) -> Result<bool, ArtifactStoreError> {
+        Ok(false)
+    }
+
+    /// Returns the saved pipeline checkpoint, `None` when there is none or the store
+    /// does not keep checkpoints
+    async fn load_checkpoint(
+        &self,
+        _deposit_id: i32,
+    ) -> Result<Option<ProofCheckpoint>, ArtifactStoreError> {
+        Ok(None)
+    }
+
+    /// Describes a saved artifact, `None` when it was not saved or the store does not
+    /// serve downloads
+    async fn stat(
+        &self,
+        _deposit_id: i32,
+        _artifact: &ArtifactName,
+    ) -> Result<Option<ArtifactInfo>, ArtifactStoreError> {
+        Ok(None)
+    }
+
+    /// Streams the bytes in `range` of a saved artifact without reading it into memory,
+    /// `None` when it was not saved or the store does not serve downloads
+    async fn read(
+        &self,
+        _deposit_id: i32,
+        _artifact: &ArtifactName,
+        _range: Range<u64>,
+    ) -> Result<Option<ArtifactStream>, ArtifactStoreError> {
+        Ok(None)
+    }
+}
+
+/// Builds the store selected by `proof.artifact_store`
+pub async fn artifact_store_from_config(
+    config: &ProofConfig,
+) -> Result<Arc<dyn ProofArtifactStore>, ArtifactStoreError> {
+    match config.artifact_store {
+        ArtifactStoreKind::Local => Ok(Arc::new(LocalArtifactStore::new(
+            config.artifacts_dir.clone(),
+        ))),
+        ArtifactStoreKind::S3 => {
+            let bucket = config
+                .s3_bucket
+                .clone()
+                .ok_or(ArtifactStoreError::MissingBucket)?;
+            let aws_config = aws_config::load_from_env().await;
+            Ok(Arc::new(S3ArtifactStore::new(
+                Client::new(&aws_config),
+                bucket,
+                config.s3_prefix.clone(),
+                config.artifacts_dir.clone(),
+            )))
+        }
+    }
+}
+
+/// Keeps artifacts under `{root}/deposit_{id}/` on the local filesystem
+pub struct LocalArtifactStore {
+    root: PathBuf,
+}
+
+impl LocalArtifactStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn deposit_dir(&self, deposit_id: i32) -> PathBuf {
+        deposit_dir(&self.root, deposit_id)
+    }
+}
+
+#[async_trait]
+impl ProofArtifactStore for LocalArtifactStore {
+    async fn save(
+        &self,
+        deposit_id: i32,
+        artifacts: &CalldataArtifacts,
+    ) -> Result<ArtifactLocation, ArtifactStoreError> {
+        let target = self.deposit_dir(deposit_id);
+        copy_dir(&artifacts.calldata_dir, &target)?;
+
+        let mut hashes = BTreeMap::new();
+        for file in relative_files(&artifacts.calldata_dir)? {
+            hashes.insert(key_path(&file), sha256_file(&target.join(&file))?);
+        }
+        hashes.insert(PROOF_FILE.to_string(), sha256_file(&artifacts.proof_path)?);
+        fs::write(
+            target.join(ARTIFACT_HASHES_FILE),
+            serde_json::to_vec_pretty(&hashes).map_err(io::Error::from)?,
+        )?;
+        fs::copy(&artifacts.proof_path, target.join(PROOF_FILE))?;
+
+        Ok(ArtifactLocation::Local(target))
+    }
+
+    async fn load(&self, deposit_id: i32) -> Result<Option<CalldataArtifacts>, ArtifactStoreError> {
+        read_artifacts(&self.deposit_dir(deposit_id))
+    }
+
+    async fn save_checkpoint(
+        &self,
+        deposit_id: i32,
+        checkpoint: &ProofCheckpoint,
+    ) -> Result<bool, ArtifactStoreError> {
+        checkpoint.write_sidecar(&self.deposit_dir(deposit_id))?;
+        Ok(true)
+    }
+
+    async fn load_checkpoint(
+        &self,
+        deposit_id: i32,
+    ) -> Result<Option<ProofCheckpoint>, ArtifactStoreError> {
+        Ok(ProofCheckpoint::read_sidecar(
+            &self.deposit_dir(deposit_id),
+        )?)
+    }
+
+    async fn stat(
+        &self,
+        deposit_id: i32,
+        artifact: &ArtifactName,
+    ) -> Result<Option<ArtifactInfo>, ArtifactStoreError> {
+        let dir = self.deposit_dir(deposit_id);
+        let path = dir.join(artifact.stored_path());
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let hashes_path = dir.join(ARTIFACT_HASHES_FILE);
+        let sha256 = if hashes_path.exists() {
+            let mut hashes: BTreeMap<String, String> =
+                serde_json::from_slice(&fs::read(hashes_path)?).map_err(|e| {
+                    ArtifactStoreError::InvalidArtifacts(format!(
+                        "{} of deposit {}: {}",
+                        ARTIFACT_HASHES_FILE, deposit_id, e
+                    ))
+                })?;
+            hashes.remove(artifact.stored_path())
+        } else {
+            None
+        };
+
+        Ok(Some(ArtifactInfo {
+            size: fs::metadata(path)?.len(),
+            sha256,
+        }))
+    }
+
+    async fn read(
+        &self,
+        deposit_id: i32,
+        artifact: &ArtifactName,
+        range: Range<u64>,
+    ) -> Result<Option<ArtifactStream>, ArtifactStoreError> {
+        let path = self.deposit_dir(deposit_id).join(artifact.stored_path());
+        let mut file = match tokio::fs::File::open(path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        file.seek(io::SeekFrom::Start(range.start)).await?;
+
+        Ok(Some(stream_reader(file.take(range.end - range.start))))
+    }
+}
+
+/// Keeps artifacts in an S3 bucket under `{prefix}/deposit_{id}/`.
+///
+/// Loaded artifacts are downloaded to `{cache_dir}/deposit_{id}/` since the relayer
+/// and proof submitter read calldata from disk. Pipeline checkpoints are not kept in
+/// the bucket, they stay next to the stage outputs on the proving worker's disk.
+pub struct S3ArtifactStore {
+    client: Client,
+    bucket: String,
+    prefix: String,
+    cache_dir: PathBuf,
+}
+
+impl S3ArtifactStore {
+    pub fn new(
+        client: Client,
+        bucket: impl Into<String>,
+        prefix: impl Into<String>,
+        cache_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            client,
+            bucket: bucket.into(),
+            prefix: prefix.into(),
+            cache_dir: cache_dir.into(),
+        }
+    }
+
+    fn deposit_prefix(&self, deposit_id: i32) -> String {
+        let prefix = self.prefix.trim_matches('/');
+        if prefix.is_empty() {
+            format!("deposit_{}", deposit_id)
+        } else {
+            format!("{}/deposit_{}", prefix, deposit_id)
+        }
+    }
+
+    fn artifact_key(&self, deposit_id: i32, artifact: &ArtifactName) -> String {
+        format!(
+            "{}/{}",
+            self.deposit_prefix(deposit_id),
+            artifact.stored_path()
+        )
+    }
+
+    async fn put_file(&self, key: String, path: &Path) -> Result<(), ArtifactStoreError> {
+        let sha256 = sha256_file(path)?;
+        let body = ByteStream::from_path(path)
+            .await
+            .map_err(|e| ArtifactStoreError::S3(e.to_string()))?;
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .metadata(SHA256_METADATA, sha256)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| ArtifactStoreError::S3(DisplayErrorContext(&e).to_string()))?;
+        Ok(())
+    }
+
+    async fn list_keys(&self, prefix: &str) -> Result<Vec<String>, ArtifactStoreError> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+
+        loop {
+            let page = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(|e| ArtifactStoreError::S3(DisplayErrorContext(&e).to_string()))?;
+
+            keys.extend(
+                page.contents()
+                    .iter()
+                    .filter_map(|o| o.key().map(String::from)),
+            );
+
+            match page.next_continuation_token() {
+                Some(token) => continuation_token = Some(token.to_string()),
+                None => return Ok(keys),
+            }
+        }
+    }
+
+    async fn download(&self, key: &str, path: &Path) -> Result<(), ArtifactStoreError> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| ArtifactStoreError::S3(DisplayErrorContext(&e).to_string()))?;
+        let bytes = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| ArtifactStoreError::S3(e.to_string()))?
+            .into_bytes();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProofArtifactStore for S3ArtifactStore {
+    async fn save(
+        &self,
+        deposit_id: i32,
+        artifacts: &CalldataArtifacts,
+    ) -> Result<ArtifactLocation, ArtifactStoreError> {
+        let prefix = self.deposit_prefix(deposit_id);
+
+        for file in relative_files(&artifacts.calldata_dir)? {
+            let key = format!("{}/{}", prefix, key_path(&file));
+            self.put_file(key, &artifacts.calldata_dir.join(&file))
+                .await?;
+        }
+        self.put_file(format!("{}/{}", prefix, PROOF_FILE), &artifacts.proof_path)
+            .await?;
+
+        Ok(ArtifactLocation::S3 {
+            bucket: self.bucket.clone(),
+            prefix,
+        })
+    }
+
+    async fn load(&self, deposit_id: i32) -> Result<Option<CalldataArtifacts>, ArtifactStoreError> {
+        let prefix = format!("{}/", self.deposit_prefix(deposit_id));
+        let keys = self.list_keys(&prefix).await?;
+        if !keys.iter().any(|key| &key[prefix.len()..] == PROOF_FILE) {
+            return Ok(None);
+        }
+
+        let target = deposit_dir(&self.cache_dir, deposit_id);
+        for key in &keys {
+            self.download(key, &target.join(&key[prefix.len()..]))
+                .await?;
+        }
+
+        read_artifacts(&target)
+    }
+
+    async fn stat(
+        &self,
+        deposit_id: i32,
+        artifact: &ArtifactName,
+    ) -> Result<Option<ArtifactInfo>, ArtifactStoreError> {
+        let key = self.artifact_key(deposit_id, artifact);
+        let head = match self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(head) => head,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => return Ok(None),
+            Err(e) => return Err(ArtifactStoreError::S3(DisplayErrorContext(&e).to_string())),
+        };
+
+        Ok(Some(ArtifactInfo {
+            size: head.content_length().unwrap_or_default().max(0) as u64,
+            sha256: head
+                .metadata()
+                .and_then(|metadata| metadata.get(SHA256_METADATA))
+                .cloned(),
+        }))
+    }
+
+    async fn read(
+        &self,
+        deposit_id: i32,
+        artifact: &ArtifactName,
+        range: Range<u64>,
+    ) -> Result<Option<ArtifactStream>, ArtifactStoreError> {
+        let key = self.artifact_key(deposit_id, artifact);
+        let mut request = self.client.get_object().bucket(&self.bucket).key(key);
+        // An empty range cannot be expressed, the empty object is read whole
+        if range.end > range.start {
+            request = request.range(format!("bytes={}-{}", range.start, range.end - 1));
+        }
+        let object = match request.send().await {
+            Ok(object) => object,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+            Err(e) => return Err(ArtifactStoreError::S3(DisplayErrorContext(&e).to_string())),
+        };
+
+        Ok(Some(stream_reader(object.body.into_async_read())))
+    }
+}
+
+/// Streams `reader` in chunks of [`STREAM_CHUNK_BYTES`]
+fn stream_reader(reader: impl AsyncRead + Send + Unpin + 'static) -> ArtifactStream {
+    Box::pin(stream::try_unfold(reader, |mut reader| async move {
+        let mut chunk = vec![0; STREAM_CHUNK_BYTES];
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        chunk.truncate(read);
+        Ok(Some((Bytes::from(chunk), reader)))
+    }))
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn deposit_dir(root: &Path, deposit_id: i32) -> PathBuf {
+    root.join(format!("deposit_{}", deposit_id))
+}
+
+fn read_artifacts(dir: &Path) -> Result<Option<CalldataArtifacts>, ArtifactStoreError> {
+    // The proof is written last, so a directory without one holds no complete proof
+    let proof_path = dir.join(PROOF_FILE);
+    if !proof_path.exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(CalldataArtifacts::from_calldata_dir(
+        dir.to_path_buf(),
+        proof_path,
+    )?))
+}
+
+/// Paths of every file under `dir`, relative to it
+fn relative_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+
+    while let Some(relative) = pending.pop() {
+        for entry in fs::read_dir(dir.join(&relative))? {
+            let entry = entry?;
+            let path = relative.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+// S3 keys always use forward slashes
+fn key_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline_output(dir: &Path) -> CalldataArtifacts {
+        let calldata_dir = dir.join("calldata");
+        fs::create_dir_all(calldata_dir.join("steps")).unwrap();
+        fs::write(calldata_dir.join("initial"), "0x1 0x2").unwrap();
+        fs::write(calldata_dir.join("steps").join("step1"), "0x3").unwrap();
+        fs::write(calldata_dir.join("fact.txt"), "0xfact\n").unwrap();
+
+        let proof_path = dir.join("proof.json");
+        fs::write(&proof_path, "{}").unwrap();
+
+        CalldataArtifacts::from_calldata_dir(calldata_dir, proof_path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_local_store_round_trip() {
+        let pipeline_dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let store = LocalArtifactStore::new(root.path());
+
+        let location = store
+            .save(7, &pipeline_output(pipeline_dir.path()))
+            .await
+            .unwrap();
+        assert_eq!(
+            location,
+            ArtifactLocation::Local(root.path().join("deposit_7"))
+        );
+
+        let loaded = store.load(7).await.unwrap().unwrap();
+        assert_eq!(loaded.calldata_dir, root.path().join("deposit_7"));
+        assert_eq!(loaded.fact_hash.as_deref(), Some("0xfact"));
+        assert_eq!(
+            fs::read_to_string(loaded.calldata_dir.join("steps").join("step1")).unwrap(),
+            "0x3"
+        );
+        assert!(loaded.proof_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_local_store_without_proof_loads_nothing() {
+        let root = tempfile::tempdir().unwrap();
+        let store = LocalArtifactStore::new(root.path());
+        assert!(store.load(1).await.unwrap().is_none());
+
+        // The proof client writes the Cairo inputs to the same directory first
+        fs::create_dir_all(root.path().join("deposit_1")).unwrap();
+        fs::write(root.path().join("deposit_1/input.cairo1.json"), "[]").unwrap();
+        assert!(store.load(1).await.unwrap().is_none());
+    }
+
+    async fn read_all(stream: ArtifactStream) -> Vec<u8> {
+        use futures_util::TryStreamExt;
+        stream
+            .try_fold(Vec::new(), |mut bytes, chunk| async move {
+                bytes.extend_from_slice(&chunk);
+                Ok(bytes)
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_local_store_serves_ranges_of_saved_artifacts() {
+        let pipeline_dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let store = LocalArtifactStore::new(root.path());
+        let artifacts = pipeline_output(pipeline_dir.path());
+        let proof: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&artifacts.proof_path, &proof).unwrap();
+        store.save(7, &artifacts).await.unwrap();
+
+        let info = store.stat(7, &ArtifactName::Proof).await.unwrap().unwrap();
+        assert_eq!(info.size, proof.len() as u64);
+        assert_eq!(info.sha256, Some(hex::encode(Sha256::digest(&proof))));
+
+        let whole = store
+            .read(7, &ArtifactName::Proof, 0..info.size)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(read_all(whole).await, proof);
+        let middle = store
+            .read(7, &ArtifactName::Proof, 70_000..130_001)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(read_all(middle).await, &proof[70_000..130_001]);
+
+        let fact_hash = ArtifactName::FactHash;
+        assert_eq!(store.stat(7, &fact_hash).await.unwrap().unwrap().size, 7);
+        assert!(store.stat(8, &fact_hash).await.unwrap().is_none());
+        assert!(store.read(8, &fact_hash, 0..1).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_artifact_names() {
+        assert_eq!(ArtifactName::parse("proof.json"), Some(ArtifactName::Proof));
+        assert_eq!(
+            ArtifactName::parse("fact_hash.txt").unwrap().stored_path(),
+            "fact.txt"
+        );
+        for file in ["initial", "step1", "step12", "final"] {
+            assert_eq!(
+                ArtifactName::parse(&format!("calldata/{}", file)),
+                Some(ArtifactName::Calldata(file.to_string()))
+            );
+        }
+
+        // Only the artifacts themselves, never other files of the deposit directory
+        for name in [
+            "fact.txt",
+            "checkpoint.json",
+            "calldata/",
+            "calldata/step",
+            "calldata/stepx",
+            "calldata/../checkpoint.json",
+            "calldata/steps/step1",
+        ] {
+            assert_eq!(ArtifactName::parse(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_relative_files_walks_subdirectories() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = pipeline_output(dir.path());
+
+        let mut files: Vec<String> = relative_files(&artifacts.calldata_dir)
+            .unwrap()
+            .iter()
+            .map(|file| key_path(file))
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["fact.txt", "initial", "steps/step1"]);
+    }
+
+    #[test]
+    fn test_location_display() {
+        let location = ArtifactLocation::S3 {
+            bucket: "proofs".to_string(),
+            prefix: "calldata/deposit_3".to_string(),
+        };
+        assert_eq!(location.to_string(), "s3://proofs/calldata/deposit_3");
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+use tracing::info;
+
+use crate::proof_client::input_generator::{Cairo1Input, CAIRO_INPUT_FILE_ENV};
+
+#[derive(Error, Debug)]
+pub enum BuildError {
+    #[error("No Scarb project in this directory: {0}")]
+    ProjectNotFound(PathBuf),
+
+    #[error("Failed to execute Scarb: {0}")]
+    CommandFailed(#[from] std::io::Error),
+
+    #[error("Build failed. Exit code: {0:?}")]
+    BuildFailed(Option<i32>),
+
+    #[error("Invalid Scarb.toml: {0}")]
+    InvalidManifest(String),
+
+    #[error("Output file not found: {0}")]
+    OutputNotFound(PathBuf),
+
+    #[error("Failed to watch sources: {0}")]
+    Watch(String),
+}
+
+/// Builds the Cairo project rooted at `base_dir` with Scarb
+#[derive(Debug, Clone)]
+pub struct CairoBuildManager {
+    base_dir: PathBuf,
+    scarb_command: String,
+}
+
+impl CairoBuildManager {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            scarb_command: "scarb".to_string(),
+        }
+    }
+
+    /// Overrides the Scarb executable used for builds
+    pub fn with_scarb_command(mut self, scarb_command: impl Into<String>) -> Self {
+        self.scarb_command = scarb_command.into();
+        self
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// Runs `scarb build` and returns the path of the generated Sierra file
+    pub fn build_cairo_project(&self) -> Result<PathBuf, BuildError> {
+        self.build_with_env(&HashMap::new())
+    }
+
+    /// Runs `scarb build` with `env_vars` added to its environment, returning the path
+    /// of the generated Sierra file
+    pub fn build_with_env(
+        &self,
+        env_vars: &HashMap<String, String>,
+    ) -> Result<PathBuf, BuildError> {
+        if !self.base_dir.exists() {
+            return Err(BuildError::ProjectNotFound(self.base_dir.clone()));
+        }
+
+        info!("Building Scarb project at {:?}", self.base_dir);
+
+        let status = Command::new(&self.scarb_command)
+            .arg("build")
+            .envs(env_vars)
+            .current_dir(&self.base_dir)
+            .status()?;
+
+        if !status.success() {
+            return Err(BuildError::BuildFailed(status.code()));
+        }
+
+        let output_file = self
+            .base_dir
+            .join("target/dev")
+            .join(format!("{}.sierra.json", self.package_name()?));
+
+        if output_file.exists() {
+            info!("Output file found: {:?}", output_file);
+            Ok(output_file)
+        } else {
+            Err(BuildError::OutputNotFound(output_file))
+        }
+    }
+
+    /// Builds the project with `args` as its program inputs.
+    ///
+    /// The inputs are written to a temporary file named by `CAIRO_INPUT_FILE`, which is
+    /// removed once the build finishes.
+    pub fn build_with_cairo_args(&self, args: &Cairo1Input) -> Result<PathBuf, BuildError> {
+        let mut input_file = tempfile::Builder::new()
+            .prefix("cairo_input_")
+            .suffix(".json")
+            .tempfile()?;
+        serde_json::to_writer(&mut input_file, args).map_err(io::Error::from)?;
+        input_file.flush()?;
+
+        let env_vars = HashMap::from([(
+            CAIRO_INPUT_FILE_ENV.to_string(),
+            input_file.path().to_string_lossy().into_owned(),
+        )]);
+        self.build_with_env(&env_vars)
+    }
+
+    // Read the package name from Scarb.toml to locate the build output
+    fn package_name(&self) -> Result<String, BuildError> {
+        let toml_str = fs::read_to_string(self.base_dir.join("Scarb.toml"))
+            .map_err(|e| BuildError::InvalidManifest(format!("Failed to read: {}", e)))?;
+        let parsed: toml::Value = toml_str
+            .parse()
+            .map_err(|e| BuildError::InvalidManifest(format!("Failed to parse: {}", e)))?;
+
+        parsed
+            .get("package")
+            .and_then(|pkg| pkg.get("name"))
+            .and_then(|name| name.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                BuildError::InvalidManifest("Could not find package.name".to_string())
+            })
+    }
+
+    /// Watches `base_dir/src` and rebuilds the project whenever a source file changes.
+    ///
+    /// Events are debounced by 500ms so an editor save touching several files only
+    /// triggers one build. `callback` receives the new Sierra file path after each
+    /// successful build; failed builds are logged and the watcher keeps running.
+    #[cfg(feature = "dev-watch")]
+    pub fn watch_and_rebuild(
+        &self,
+        callback: impl Fn(PathBuf) + Send + 'static,
+    ) -> Result<std::thread::JoinHandle<()>, BuildError> {
+        use notify::{RecursiveMode, Watcher};
+        use std::sync::mpsc;
+        use std::time::Duration;
+        use tracing::error;
+
+        const DEBOUNCE: Duration = Duration::from_millis(500);
+
+        let src_dir = self.base_dir.join("src");
+        if !src_dir.exists() {
+            return Err(BuildError::ProjectNotFound(src_dir));
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })
+        .map_err(|e| BuildError::Watch(e.to_string()))?;
+        watcher
+            .watch(&src_dir, RecursiveMode::Recursive)
+            .map_err(|e| BuildError::Watch(e.to_string()))?;
+
+        let manager = self.clone();
+        let handle = std::thread::spawn(move || {
+            // The watcher stops emitting events once dropped, so keep it alive here
+            let _watcher = watcher;
+
+            while let Ok(event) = rx.recv() {
+                if let Err(e) = event {
+                    error!("File watcher error: {}", e);
+                    continue;
+                }
+
+                // Wait until no further events arrive within the debounce window
+                while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+                info!("Cairo sources changed, rebuilding {:?}", manager.base_dir);
+                match manager.build_cairo_project() {
+                    Ok(sierra_path) => callback(sierra_path),
+                    Err(e) => error!("Cairo rebuild failed: {}", e),
+                }
+            }
+        });
+
+        Ok(handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof_client::input_generator::{Hash256Limbs, L1MerklePath};
+    use tempfile::TempDir;
+
+    fn create_project(package_name: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("Scarb.toml"),
+            format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", package_name),
+        )
+        .unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("target/dev")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_build_returns_sierra_path() {
+        let dir = create_project("demo");
+        let sierra = dir.path().join("target/dev/demo.sierra.json");
+        fs::write(&sierra, "{}").unwrap();
+
+        let manager = CairoBuildManager::new(dir.path()).with_scarb_command("true");
+
+        assert_eq!(manager.build_cairo_project().unwrap(), sierra);
+    }
+
+    #[test]
+    fn test_build_reports_missing_output() {
+        let dir = create_project("demo");
+        let manager = CairoBuildManager::new(dir.path()).with_scarb_command("true");
+
+        assert!(matches!(
+            manager.build_cairo_project(),
+            Err(BuildError::OutputNotFound(_))
+        ));
+    }
+
+    /// Installs a fake `scarb` that records `CAIRO_INPUT_FILE` and the file it names
+    fn recording_scarb(dir: &Path) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = dir.join("fake-scarb.sh");
+        fs::write(
+            &script,
+            "#!/bin/sh\n\
+             printf '%s' \"$CAIRO_INPUT_FILE\" > input_file_env\n\
+             cat \"$CAIRO_INPUT_FILE\" > input_file_contents\n\
+             touch target/dev/demo.sierra.json\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        script.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_build_with_env_passes_variables_to_scarb() {
+        let dir = create_project("demo");
+        let scarb = recording_scarb(dir.path());
+        fs::write(dir.path().join("inputs.json"), "[1 2 3]").unwrap();
+
+        let manager = CairoBuildManager::new(dir.path()).with_scarb_command(scarb);
+        let env_vars =
+            HashMap::from([(CAIRO_INPUT_FILE_ENV.to_string(), "inputs.json".to_string())]);
+
+        assert_eq!(
+            manager.build_with_env(&env_vars).unwrap(),
+            dir.path().join("target/dev/demo.sierra.json")
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("input_file_env")).unwrap(),
+            "inputs.json"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("input_file_contents")).unwrap(),
+            "[1 2 3]"
+        );
+    }
+
+    #[test]
+    fn test_build_with_cairo_args_writes_inputs_for_the_build() {
+        let dir = create_project("demo");
+        let scarb = recording_scarb(dir.path());
+        let manager = CairoBuildManager::new(dir.path()).with_scarb_command(scarb);
+        let hash = |low| Hash256Limbs { high: 0, low };
+        let args = Cairo1Input::new(&L1MerklePath {
+            root: hash(141516),
+            leaf: hash(12345),
+            leaf_index: 1,
+            mmr_size: 3,
+            siblings: vec![hash(67890)],
+            peaks: vec![hash(141516)],
+        });
+
+        manager.build_with_cairo_args(&args).unwrap();
+
+        let contents = fs::read_to_string(dir.path().join("input_file_contents")).unwrap();
+        assert_eq!(contents, serde_json::to_string(&args).unwrap());
+        // The temporary input file does not outlive the build
+        let input_file = fs::read_to_string(dir.path().join("input_file_env")).unwrap();
+        assert!(!Path::new(&input_file).exists());
+    }
+
+    #[cfg(feature = "dev-watch")]
+    #[test]
+    fn test_watch_and_rebuild_fires_callback() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let dir = create_project("demo");
+        let sierra = dir.path().join("target/dev/demo.sierra.json");
+        fs::write(&sierra, "{}").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let manager = CairoBuildManager::new(dir.path()).with_scarb_command("true");
+        let _handle = manager
+            .watch_and_rebuild(move |path| {
+                let _ = tx.send(path);
+            })
+            .unwrap();
+
+        fs::write(dir.path().join("src/lib.cairo"), "fn main() {}").unwrap();
+
+        let rebuilt = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(rebuilt, sierra);
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the checkpoint file kept next to a deposit's stage outputs
+pub const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// Stages of proving a deposit, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofStage {
+    /// Program arguments generated from the deposit
+    CairoInputs,
+    /// Sierra file compiled by Scarb
+    Build,
+    /// Stone proof of the program run
+    Prove,
+    /// Calldata split from the proof by swiftness
+    Calldata,
+}
+
+impl ProofStage {
+    pub const ALL: [ProofStage; 4] = [
+        ProofStage::CairoInputs,
+        ProofStage::Build,
+        ProofStage::Prove,
+        ProofStage::Calldata,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofStage::CairoInputs => "cairo_inputs",
+            ProofStage::Build => "build",
+            ProofStage::Prove => "prove",
+            ProofStage::Calldata => "calldata",
+        }
+    }
+}
+
+/// Where a completed stage wrote its output and the hash it had then
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageOutput {
+    pub path: PathBuf,
+    pub sha256: String,
+}
+
+/// Outputs of the stages completed so far for one deposit
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofCheckpoint {
+    pub stages: BTreeMap<ProofStage, StageOutput>,
+}
+
+impl ProofCheckpoint {
+    /// Output of `stage` if it is still on disk, non-empty and unchanged since the stage
+    /// completed
+    pub fn valid_output(&self, stage: ProofStage) -> Option<PathBuf> {
+        let output = self.stages.get(&stage)?;
+        match hash_output(&output.path) {
+            Ok(Some(sha256)) if sha256 == output.sha256 => Some(output.path.clone()),
+            _ => None,
+        }
+    }
+
+    /// Records `path` as the output of `stage`, failing if it is missing or empty
+    pub fn record(&mut self, stage: ProofStage, path: &Path) -> io::Result<()> {
+        let sha256 = hash_output(path)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} stage left no output at {:?}", stage.as_str(), path),
+            )
+        })?;
+        self.stages.insert(
+            stage,
+            StageOutput {
+                path: path.to_path_buf(),
+                sha256,
+            },
+        );
+        Ok(())
+    }
+
+    /// Reads the sidecar checkpoint in `dir`, `None` when there is none
+    pub fn read_sidecar(dir: &Path) -> io::Result<Option<Self>> {
+        let path = dir.join(CHECKPOINT_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let checkpoint = serde_json::from_slice(&fs::read(path)?)?;
+        Ok(Some(checkpoint))
+    }
+
+    /// Writes the checkpoint to `dir` as a sidecar JSON file
+    pub fn write_sidecar(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(CHECKPOINT_FILE), serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// SHA-256 of a file, or of the relative paths and contents of every file in a
+/// directory. `None` when nothing, or only empty files, exist at `path`.
+fn hash_output(path: &Path) -> io::Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let mut files = Vec::new();
+    if path.is_dir() {
+        collect_files(path, PathBuf::new(), &mut files)?;
+        files.sort();
+    } else {
+        files.push(PathBuf::new());
+    }
+
+    let mut hasher = Sha256::new();
+    let mut empty = true;
+    for relative in files {
+        let file = if relative.as_os_str().is_empty() {
+            path.to_path_buf()
+        } else {
+            path.join(&relative)
+        };
+        let contents = fs::read(file)?;
+        empty &= contents.is_empty();
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update((contents.len() as u64).to_be_bytes());
+        hasher.update(&contents);
+    }
+
+    Ok((!empty).then(|| hex::encode(hasher.finalize())))
+}
+
+/// Paths of every file under `root`, relative to it
+fn collect_files(root: &Path, relative: PathBuf, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(root.join(&relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_files(root, path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_recorded_output_stays_valid_until_changed() {
+        let dir = TempDir::new().unwrap();
+        let proof = dir.path().join("proof.json");
+        fs::write(&proof, "{\"proof\": 1}").unwrap();
+
+        let mut checkpoint = ProofCheckpoint::default();
+        checkpoint.record(ProofStage::Prove, &proof).unwrap();
+        assert_eq!(
+            checkpoint.valid_output(ProofStage::Prove),
+            Some(proof.clone())
+        );
+        assert_eq!(checkpoint.valid_output(ProofStage::Calldata), None);
+
+        fs::write(&proof, "{\"proof\": 2}").unwrap();
+        assert_eq!(checkpoint.valid_output(ProofStage::Prove), None);
+
+        fs::remove_file(&proof).unwrap();
+        assert_eq!(checkpoint.valid_output(ProofStage::Prove), None);
+    }
+
+    #[test]
+    fn test_directory_outputs_hash_every_file() {
+        let dir = TempDir::new().unwrap();
+        let calldata = dir.path().join("calldata");
+        fs::create_dir_all(calldata.join("steps")).unwrap();
+        fs::write(calldata.join("initial"), "0x1").unwrap();
+        fs::write(calldata.join("steps/step1"), "0x2").unwrap();
+
+        let mut checkpoint = ProofCheckpoint::default();
+        checkpoint.record(ProofStage::Calldata, &calldata).unwrap();
+        assert!(checkpoint.valid_output(ProofStage::Calldata).is_some());
+
+        fs::write(calldata.join("steps/step1"), "0x3").unwrap();
+        assert!(checkpoint.valid_output(ProofStage::Calldata).is_none());
+    }
+
+    #[test]
+    fn test_empty_output_cannot_be_recorded() {
+        let dir = TempDir::new().unwrap();
+        let inputs = dir.path().join("input.cairo1.json");
+        fs::write(&inputs, "").unwrap();
+
+        let mut checkpoint = ProofCheckpoint::default();
+        assert!(checkpoint.record(ProofStage::CairoInputs, &inputs).is_err());
+        assert!(checkpoint
+            .record(ProofStage::CairoInputs, &dir.path().join("missing"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_sidecar_round_trip() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(ProofCheckpoint::read_sidecar(dir.path()).unwrap(), None);
+
+        let inputs = dir.path().join("input.cairo1.json");
+        fs::write(&inputs, "[1]").unwrap();
+        let mut checkpoint = ProofCheckpoint::default();
+        checkpoint.record(ProofStage::CairoInputs, &inputs).unwrap();
+        checkpoint.write_sidecar(dir.path()).unwrap();
+
+        assert_eq!(
+            ProofCheckpoint::read_sidecar(dir.path()).unwrap(),
+            Some(checkpoint)
+        );
+    }
+}
@@ -1,34 +1,133 @@
 use serde::Serialize;
+use std::env;
+use std::ffi::OsString;
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[derive(Serialize)]
-struct Cairo1Input {
-    data: Vec<Vec<u64>>,
+use crate::queue::l2_queue::InclusionProof;
+
+/// Environment variable naming the file the Cairo program inputs are written to and
+/// read from at build time
+pub const CAIRO_INPUT_FILE_ENV: &str = "CAIRO_INPUT_FILE";
+
+/// A 256-bit hash split into the two 128-bit limbs of the Cairo `Hash256` struct.
+///
+/// A felt252 cannot hold every 256-bit value, so hashes are passed to the program as
+/// `high` (the upper 16 bytes, big-endian) followed by `low` (the lower 16 bytes). Both
+/// limbs are below `2^128` and therefore always valid felts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hash256Limbs {
+    pub high: u128,
+    pub low: u128,
+}
+
+impl Hash256Limbs {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        let (high, low) = bytes.split_at(16);
+        Self {
+            high: u128::from_be_bytes(high.try_into().unwrap()),
+            low: u128::from_be_bytes(low.try_into().unwrap()),
+        }
+    }
+
+    /// Parses a hash of up to 64 hex digits, with or without `0x`, left-padding it to
+    /// 32 bytes
+    pub fn from_hex(hex: &str) -> Result<Self, hex::FromHexError> {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+        if digits.len() > 64 {
+            return Err(hex::FromHexError::InvalidStringLength);
+        }
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(format!("{:0>64}", digits), &mut bytes)?;
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
+/// First program argument, selecting the verification of a Keccak MMR proof
+pub const L1_PROOF_MODE: u128 = 1;
+
+/// Inclusion proof of a deposit's commitment in the L1 tree, as the program checks it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L1MerklePath {
+    pub root: Hash256Limbs,
+    pub leaf: Hash256Limbs,
+    /// MMR position of the leaf, starting at 1
+    pub leaf_index: u128,
+    /// Number of MMR elements, leaves and inner nodes alike
+    pub mmr_size: u128,
+    /// Sibling hashes from the leaf up to its peak
+    pub siblings: Vec<Hash256Limbs>,
+    pub peaks: Vec<Hash256Limbs>,
+}
+
+impl L1MerklePath {
+    /// Takes the path of `leaf` out of `proof`, whose hashes are hex strings
+    pub fn from_proof(leaf: [u8; 32], proof: &InclusionProof) -> Result<Self, hex::FromHexError> {
+        let hashes = |hashes: &[String]| {
+            hashes
+                .iter()
+                .map(|hash| Hash256Limbs::from_hex(hash))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Self {
+            root: Hash256Limbs::from_hex(&proof.merkle_root)?,
+            leaf: Hash256Limbs::from_bytes(leaf),
+            leaf_index: proof.leaf_index as u128,
+            mmr_size: proof.elements_count as u128,
+            siblings: hashes(&proof.siblings)?,
+            peaks: hashes(&proof.peaks)?,
+        })
+    }
+}
+
+/// Program arguments in the format `cairo1-run` reads them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Cairo1Input {
+    pub data: Vec<Vec<u128>>,
+}
+
+impl Cairo1Input {
+    /// Lays out the arguments of the program's L1 mode: the mode, root, leaf, leaf
+    /// position and MMR size, then the siblings and the peaks, each preceded by their
+    /// count. Every hash is passed as its `high` and `low` limbs.
+    pub fn new(path: &L1MerklePath) -> Self {
+        let limbs = |hash: &Hash256Limbs| [hash.high, hash.low];
+
+        let mut input_data = vec![L1_PROOF_MODE];
+        input_data.extend(limbs(&path.root));
+        input_data.extend(limbs(&path.leaf));
+        input_data.extend([path.leaf_index, path.mmr_size]);
+        input_data.push(path.siblings.len() as u128);
+        input_data.extend(path.siblings.iter().flat_map(limbs));
+        input_data.push(path.peaks.len() as u128);
+        input_data.extend(path.peaks.iter().flat_map(limbs));
+
+        Self {
+            data: vec![input_data],
+        }
+    }
 }
 
+/// Writes the program inputs proving `path` as JSON and as the `[a b c]` text format,
+/// returning the path of the JSON file.
+///
+/// The JSON file goes to the path in `CAIRO_INPUT_FILE` when set, otherwise to
+/// `input.cairo1.json` in `output_dir`.
 pub fn generate_cairo1_inputs(
-    commitment_hash: u64,
-    proof_array: Vec<u64>,
-    new_root: u64,
+    path: &L1MerklePath,
     output_dir: &str,
-) -> Result<(), std::io::Error> {
-    // Combine inputs into a single array
-    let mut input_data = vec![commitment_hash];
-    input_data.extend(proof_array);
-    input_data.push(new_root);
+) -> Result<PathBuf, std::io::Error> {
+    let json_data = Cairo1Input::new(path);
 
     // Generate JSON file
-    let json_data = Cairo1Input {
-        data: vec![input_data.clone()],
-    };
     let json_string = serde_json::to_string_pretty(&json_data)?;
-    let json_path = Path::new(output_dir).join("input.cairo1.json");
+    let json_path = cairo1_input_path(output_dir, env::var_os(CAIRO_INPUT_FILE_ENV));
     File::create(&json_path)?.write_all(json_string.as_bytes())?;
 
     // Generate TXT file
-    let txt_string = input_data
+    let txt_string = json_data.data[0]
         .iter()
         .map(|x| x.to_string())
         .collect::<Vec<String>>()
@@ -37,7 +136,14 @@ pub fn generate_cairo1_inputs(
     let txt_path = Path::new(output_dir).join("input.cairo1.txt");
     File::create(&txt_path)?.write_all(txt_content.as_bytes())?;
 
-    Ok(())
+    Ok(json_path)
+}
+
+fn cairo1_input_path(output_dir: &str, input_file: Option<OsString>) -> PathBuf {
+    match input_file {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => Path::new(output_dir).join("input.cairo1.json"),
+    }
 }
 
 #[cfg(test)]
@@ -46,37 +152,78 @@ mod tests {
     use std::fs;
     use std::path::Path;
 
+    fn limbs(high: u128, low: u128) -> Hash256Limbs {
+        Hash256Limbs { high, low }
+    }
+
     #[test]
     fn test_generate_cairo1_inputs() {
-        let commitment_hash = 12345;
-        let proof_array = vec![67890, 111213];
-        let new_root = 141516;
+        let path = L1MerklePath {
+            root: limbs(3, 141516),
+            leaf: limbs(1, 12345),
+            leaf_index: 1,
+            mmr_size: 3,
+            siblings: vec![limbs(0, 67890)],
+            peaks: vec![limbs(2, 111213)],
+        };
         let output_dir = "test_output";
 
         // Create temporary output directory
         fs::create_dir_all(output_dir).unwrap();
 
         // Generate files
-        generate_cairo1_inputs(commitment_hash, proof_array.clone(), new_root, output_dir)
-            .expect("Failed to generate files");
+        generate_cairo1_inputs(&path, output_dir).expect("Failed to generate files");
 
         // Verify JSON file
         let json_path = Path::new(output_dir).join("input.cairo1.json");
         let json_content = fs::read_to_string(&json_path).unwrap();
-        let expected_json = r#"{
-  "data": [
-    [12345, 67890, 111213, 141516]
-  ]
-}"#;
-        assert_eq!(json_content.trim(), expected_json.trim());
+        let json: serde_json::Value = serde_json::from_str(&json_content).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "data": [[1, 3, 141516, 1, 12345, 1, 3, 1, 0, 67890, 1, 2, 111213]]
+            })
+        );
 
         // Verify TXT file
         let txt_path = Path::new(output_dir).join("input.cairo1.txt");
         let txt_content = fs::read_to_string(&txt_path).unwrap();
-        let expected_txt = "[12345 67890 111213 141516]";
+        let expected_txt = "[1 3 141516 1 12345 1 3 1 0 67890 1 2 111213]";
         assert_eq!(txt_content, expected_txt);
 
         // Clean up
         fs::remove_dir_all(output_dir).unwrap();
     }
+
+    #[test]
+    fn test_hash_is_split_into_high_and_low_limbs() {
+        let hash = Hash256Limbs::from_hex(
+            "0xffffffffffffffffffffffffffffffff00000000000000000000000000003039",
+        )
+        .unwrap();
+        assert_eq!(hash, limbs(u128::MAX, 12345));
+
+        // Short hashes are left-padded, only filling the low limb
+        assert_eq!(Hash256Limbs::from_hex("0x01").unwrap(), limbs(0, 1));
+        assert_eq!(Hash256Limbs::from_bytes([0u8; 32]), limbs(0, 0));
+
+        assert!(Hash256Limbs::from_hex("0xzz").is_err());
+        assert!(Hash256Limbs::from_hex(&format!("0x{}", "1".repeat(65))).is_err());
+    }
+
+    #[test]
+    fn test_cairo1_input_path_prefers_the_env_var() {
+        assert_eq!(
+            cairo1_input_path("out", Some(OsString::from("/tmp/inputs.json"))),
+            PathBuf::from("/tmp/inputs.json")
+        );
+        assert_eq!(
+            cairo1_input_path("out", None),
+            Path::new("out").join("input.cairo1.json")
+        );
+        assert_eq!(
+            cairo1_input_path("out", Some(OsString::new())),
+            Path::new("out").join("input.cairo1.json")
+        );
+    }
 }
@@ -1,2 +1,6 @@
+pub mod artifact_store;
+pub mod build_manager;
+pub mod checkpoint;
 pub mod input_generator;
 pub mod proof_generator;
+pub mod service;
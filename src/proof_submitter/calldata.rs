@@ -0,0 +1,219 @@
+use serde_json::Value;
+use starknet::core::types::Felt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::debug;
+
+use crate::relayer::proof_submission::ProofSubmissionError;
+
+pub const INITIAL_FILE: &str = "initial";
+pub const FINAL_FILE: &str = "final";
+pub const PROOF_FILE: &str = "proof.json";
+pub const FACT_HASH_FILE: &str = "fact.txt";
+
+/// Calldata written by the proof client for one proof, in submission order
+#[derive(Debug, Clone)]
+pub struct CalldataBundle {
+    pub dir: PathBuf,
+    pub initial: Vec<Felt>,
+    pub steps: Vec<Vec<Felt>>,
+    pub final_calldata: Vec<Felt>,
+    /// Fact hash reported by the verifier, when the pipeline produced one
+    pub fact_hash: Option<Felt>,
+}
+
+impl CalldataBundle {
+    /// Reads and validates a calldata directory.
+    ///
+    /// The directory must contain non-empty `initial` and `final` files, step files
+    /// numbered contiguously from `step1`, and a `proof.json` that parses as JSON.
+    pub fn load(dir: &Path) -> Result<Self, ProofSubmissionError> {
+        if !dir.is_dir() {
+            return Err(ProofSubmissionError::CalldataDirNotFound(
+                dir.display().to_string(),
+            ));
+        }
+
+        let initial = read_required(dir, INITIAL_FILE)?;
+        let final_calldata = read_required(dir, FINAL_FILE)?;
+        let steps = read_steps(dir)?;
+
+        let proof_path = dir.join(PROOF_FILE);
+        if !proof_path.exists() {
+            return Err(ProofSubmissionError::CalldataFileMissing(
+                PROOF_FILE.to_string(),
+            ));
+        }
+        let _: Value = serde_json::from_str(&fs::read_to_string(&proof_path)?)?;
+
+        let fact_path = dir.join(FACT_HASH_FILE);
+        let fact_hash = if fact_path.exists() {
+            let raw = fs::read_to_string(&fact_path)?;
+            let felt = Felt::from_str(raw.trim()).map_err(|e| {
+                ProofSubmissionError::InvalidCalldataFormat(format!(
+                    "Invalid fact hash in {:?}: {}",
+                    fact_path, e
+                ))
+            })?;
+            Some(felt)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            initial,
+            steps,
+            final_calldata,
+            fact_hash,
+        })
+    }
+
+    /// Number of transactions needed to submit the whole proof
+    pub fn transaction_count(&self) -> usize {
+        self.steps.len() + 2
+    }
+}
+
+fn read_required(dir: &Path, name: &str) -> Result<Vec<Felt>, ProofSubmissionError> {
+    let path = dir.join(name);
+    if !path.exists() {
+        return Err(ProofSubmissionError::CalldataFileMissing(name.to_string()));
+    }
+
+    let calldata = read_calldata_file(&path)?;
+    if calldata.is_empty() {
+        return Err(ProofSubmissionError::InvalidCalldataFormat(format!(
+            "{} calldata is empty",
+            name
+        )));
+    }
+    Ok(calldata)
+}
+
+// Step files must run step1..stepN without gaps, otherwise a step would be skipped
+fn read_steps(dir: &Path) -> Result<Vec<Vec<Felt>>, ProofSubmissionError> {
+    let mut numbers = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        let Some(number) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix("step"))
+            .and_then(|n| n.parse::<usize>().ok())
+        else {
+            continue;
+        };
+        numbers.push(number);
+    }
+    numbers.sort_unstable();
+
+    for (expected, number) in (1..).zip(&numbers) {
+        if *number != expected {
+            return Err(ProofSubmissionError::CalldataFileMissing(format!(
+                "step{}",
+                expected
+            )));
+        }
+    }
+
+    numbers
+        .iter()
+        .map(|n| read_required(dir, &format!("step{}", n)))
+        .collect()
+}
+
+/// Reads a calldata file of whitespace separated hex felts
+pub fn read_calldata_file(file_path: &Path) -> Result<Vec<Felt>, ProofSubmissionError> {
+    let content = fs::read_to_string(file_path)?;
+    let calldata = content
+        .split_whitespace()
+        .map(|hex_str| {
+            Felt::from_hex(hex_str).map_err(|e| {
+                ProofSubmissionError::InvalidCalldataFormat(format!(
+                    "Invalid hex value '{}' in file {:?}: {}",
+                    hex_str, file_path, e
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    debug!(
+        "Read {} calldata elements from {:?}",
+        calldata.len(),
+        file_path
+    );
+    Ok(calldata)
+}
+
+/// Encodes a short string (layout, hasher, ...) as a felt, Cairo short string style
+pub fn string_to_felt(input: &str) -> Result<Felt, ProofSubmissionError> {
+    if input.len() > 31 {
+        return Err(ProofSubmissionError::InvalidCalldataFormat(format!(
+            "'{}' does not fit in a felt",
+            input
+        )));
+    }
+    Ok(Felt::from_bytes_be_slice(input.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_bundle(dir: &Path, steps: &[usize]) {
+        fs::write(dir.join(INITIAL_FILE), "0x1 0x2\n0x3").unwrap();
+        fs::write(dir.join(FINAL_FILE), "0x4").unwrap();
+        fs::write(dir.join(PROOF_FILE), "{}").unwrap();
+        for step in steps {
+            fs::write(dir.join(format!("step{}", step)), "0x5").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_load_orders_steps_numerically() {
+        let dir = tempdir().unwrap();
+        write_bundle(dir.path(), &(1..=10).collect::<Vec<_>>());
+        fs::write(dir.path().join("step10"), "0xa").unwrap();
+
+        let bundle = CalldataBundle::load(dir.path()).unwrap();
+
+        assert_eq!(bundle.initial.len(), 3);
+        assert_eq!(bundle.steps.len(), 10);
+        assert_eq!(bundle.steps[9], vec![Felt::from(10u8)]);
+        assert_eq!(bundle.transaction_count(), 12);
+        assert!(bundle.fact_hash.is_none());
+    }
+
+    #[test]
+    fn test_load_rejects_step_gap() {
+        let dir = tempdir().unwrap();
+        write_bundle(dir.path(), &[1, 3]);
+
+        let err = CalldataBundle::load(dir.path()).unwrap_err();
+        assert!(matches!(err, ProofSubmissionError::CalldataFileMissing(name) if name == "step2"));
+    }
+
+    #[test]
+    fn test_load_rejects_empty_final() {
+        let dir = tempdir().unwrap();
+        write_bundle(dir.path(), &[]);
+        fs::write(dir.path().join(FINAL_FILE), "\n").unwrap();
+
+        assert!(matches!(
+            CalldataBundle::load(dir.path()),
+            Err(ProofSubmissionError::InvalidCalldataFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_string_to_felt_matches_hex_encoding() {
+        assert_eq!(
+            string_to_felt("stone6").unwrap(),
+            Felt::from_hex("0x73746f6e6536").unwrap()
+        );
+        assert_eq!(string_to_felt("").unwrap(), Felt::ZERO);
+        assert!(string_to_felt(&"a".repeat(32)).is_err());
+    }
+}
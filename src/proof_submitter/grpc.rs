@@ -0,0 +1,149 @@
+//! gRPC interface of the proof submitter, defined in `proto/proof_submitter.proto`.
+//!
+//! Each `SubmitProof` call runs [`run`](super::run) with the account credentials of the
+//! config file the server was started with. Calldata directories are read from the
+//! server's filesystem, so the server should only listen on trusted interfaces.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tracing::{error, info};
+
+use super::{
+    run, SubmissionReport, SubmitterArgs, DEFAULT_HASHER, DEFAULT_LAYOUT,
+    DEFAULT_MEMORY_VERIFICATION, DEFAULT_STONE_VERSION,
+};
+use crate::relayer::proof_submission::ProofSubmissionError;
+
+pub mod proto {
+    tonic::include_proto!("proof_submitter");
+}
+
+use proto::proof_submitter_server::{ProofSubmitter, ProofSubmitterServer};
+use proto::{StageReport, SubmitProofRequest, SubmitProofResponse};
+
+/// `ProofSubmitter` service submitting with the credentials of `config_path`
+pub struct ProofSubmitterService {
+    config_path: PathBuf,
+}
+
+impl ProofSubmitterService {
+    pub fn new(config_path: PathBuf) -> Self {
+        Self { config_path }
+    }
+
+    fn args(&self, request: SubmitProofRequest) -> SubmitterArgs {
+        SubmitterArgs {
+            calldata_dir: PathBuf::from(request.calldata_dir),
+            job_id: request.job_id,
+            layout: or_default(request.layout, DEFAULT_LAYOUT),
+            hasher: or_default(request.hasher, DEFAULT_HASHER),
+            stone_version: or_default(request.stone_version, DEFAULT_STONE_VERSION),
+            memory_verification: or_default(
+                request.memory_verification,
+                DEFAULT_MEMORY_VERIFICATION,
+            ),
+            config_path: self.config_path.clone(),
+            dry_run: request.dry_run,
+            report_path: None,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl ProofSubmitter for ProofSubmitterService {
+    async fn submit_proof(
+        &self,
+        request: Request<SubmitProofRequest>,
+    ) -> Result<Response<SubmitProofResponse>, Status> {
+        let args = self.args(request.into_inner());
+        info!(
+            "gRPC proof submission of job {} from {:?} (dry run: {})",
+            args.job_id, args.calldata_dir, args.dry_run
+        );
+
+        match run(args).await {
+            Ok(report) => Ok(Response::new(report.into())),
+            Err(e) => {
+                error!("gRPC proof submission failed: {:?}", e);
+                Err(status(e))
+            }
+        }
+    }
+}
+
+/// Serves the `ProofSubmitter` service on `addr` until the process stops
+pub async fn serve(addr: SocketAddr, config_path: PathBuf) -> Result<(), tonic::transport::Error> {
+    info!("Proof submitter gRPC server listening on {}", addr);
+    Server::builder()
+        .add_service(ProofSubmitterServer::new(ProofSubmitterService::new(
+            config_path,
+        )))
+        .serve(addr)
+        .await
+}
+
+/// Serves the `ProofSubmitter` service on an already bound `listener`
+pub async fn serve_with_listener(
+    listener: TcpListener,
+    config_path: PathBuf,
+) -> Result<(), tonic::transport::Error> {
+    Server::builder()
+        .add_service(ProofSubmitterServer::new(ProofSubmitterService::new(
+            config_path,
+        )))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+}
+
+fn or_default(value: String, default: &str) -> String {
+    if value.is_empty() {
+        default.to_string()
+    } else {
+        value
+    }
+}
+
+/// Maps submission errors onto the gRPC codes clients can act on
+fn status(err: ProofSubmissionError) -> Status {
+    let message = err.to_string();
+    match err {
+        ProofSubmissionError::CalldataDirNotFound(_)
+        | ProofSubmissionError::CalldataFileMissing(_) => Status::not_found(message),
+        ProofSubmissionError::InvalidCalldataFormat(_)
+        | ProofSubmissionError::ParseError(_)
+        | ProofSubmissionError::Json(_) => Status::invalid_argument(message),
+        ProofSubmissionError::InvalidConfig(_) | ProofSubmissionError::InvalidContractAddress => {
+            Status::failed_precondition(message)
+        }
+        ProofSubmissionError::TransactionTimeout | ProofSubmissionError::FactNotRegistered(_) => {
+            Status::deadline_exceeded(message)
+        }
+        _ => Status::internal(message),
+    }
+}
+
+impl From<SubmissionReport> for SubmitProofResponse {
+    fn from(report: SubmissionReport) -> Self {
+        Self {
+            job_id: report.job_id,
+            calldata_dir: report.calldata_dir,
+            dry_run: report.dry_run,
+            stages: report
+                .stages
+                .into_iter()
+                .map(|stage| StageReport {
+                    stage: stage.stage,
+                    entrypoint: stage.entrypoint,
+                    calldata_len: stage.calldata_len as u64,
+                    tx_hash: stage.tx_hash,
+                })
+                .collect(),
+            fact_hash: report.fact_hash,
+            fact_registered: report.fact_registered,
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! Submits a STARK proof produced by the proof client to the Starknet verifier.
+//!
+//! Used by the `proof-submitter` binary and reusable from the sequencer: [`run`] reads
+//! the calldata directory written by the proof client, sends the `initial`, `stepN`
+//! and `final` transactions in order, waits for the fact hash to be registered and
+//! returns a [`SubmissionReport`]. [`grpc`] serves the same submission over gRPC.
+
+pub mod calldata;
+pub mod grpc;
+pub mod verifier;
+
+use serde::{Deserialize, Serialize};
+use starknet::core::types::{Call, Felt};
+use starknet::macros::selector;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::config::load_config;
+use crate::relayer::proof_submission::{ProofSubmissionConfig, ProofSubmissionError};
+
+pub use calldata::CalldataBundle;
+pub use verifier::{IntegrityVerifier, ProofVerifier};
+
+/// Proof parameters used when a submission does not name them
+pub const DEFAULT_LAYOUT: &str = "recursive_with_poseidon";
+pub const DEFAULT_HASHER: &str = "keccak_160_lsb";
+pub const DEFAULT_STONE_VERSION: &str = "stone6";
+pub const DEFAULT_MEMORY_VERIFICATION: &str = "true";
+
+/// Inputs of a proof submission, mirroring the `proof-submitter` CLI flags
+#[derive(Debug, Clone)]
+pub struct SubmitterArgs {
+    pub calldata_dir: PathBuf,
+    pub job_id: u64,
+    pub layout: String,
+    pub hasher: String,
+    pub stone_version: String,
+    pub memory_verification: String,
+    /// config.toml holding the Starknet account credentials
+    pub config_path: PathBuf,
+    /// Validate the calldata without sending any transaction
+    pub dry_run: bool,
+    /// Where to write the JSON report, if anywhere
+    pub report_path: Option<PathBuf>,
+}
+
+/// One verifier transaction of a proof submission
+#[derive(Debug, Clone)]
+pub struct ProofStage {
+    pub name: String,
+    pub entrypoint: &'static str,
+    pub selector: Felt,
+    pub calldata: Vec<Felt>,
+}
+
+impl ProofStage {
+    fn to_call(&self, contract_address: Felt) -> Call {
+        Call {
+            to: contract_address,
+            selector: self.selector,
+            calldata: self.calldata.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StageReport {
+    pub stage: String,
+    pub entrypoint: String,
+    pub calldata_len: usize,
+    /// Hash of the accepted transaction, `None` in dry-run mode
+    pub tx_hash: Option<String>,
+}
+
+/// Outcome of a proof submission, written as JSON by the CLI
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubmissionReport {
+    pub job_id: u64,
+    pub calldata_dir: String,
+    pub dry_run: bool,
+    pub stages: Vec<StageReport>,
+    pub fact_hash: Option<String>,
+    pub fact_registered: bool,
+}
+
+impl SubmissionReport {
+    pub fn write_to_file(&self, path: &Path) -> Result<(), ProofSubmissionError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// How long to wait for the verifier to register the fact once the final call lands
+#[derive(Debug, Clone, Copy)]
+pub struct FactPolling {
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for FactPolling {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(2),
+            timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Validates the calldata and, unless `dry_run` is set, submits it to the verifier
+/// configured in `config_path`. The report is written to `report_path` when given.
+pub async fn run(args: SubmitterArgs) -> Result<SubmissionReport, ProofSubmissionError> {
+    let bundle = CalldataBundle::load(&args.calldata_dir)?;
+    let stages = build_stages(&bundle, &args)?;
+
+    let report = if args.dry_run {
+        info!(
+            "Dry run: calldata in {:?} is valid, {} transactions would be sent",
+            args.calldata_dir,
+            stages.len()
+        );
+        build_report(&args, &bundle, &stages, Vec::new(), false)
+    } else {
+        let config = load_config(Some(&args.config_path))
+            .map_err(|e| ProofSubmissionError::InvalidConfig(e.to_string()))?;
+        if config.starknet.try_get_rpc_url().is_err() {
+            return Err(ProofSubmissionError::InvalidConfig(
+                "STARKNET_RPC_URL is not set".to_string(),
+            ));
+        }
+
+        let proof_config = ProofSubmissionConfig::from(config);
+        let polling = FactPolling {
+            timeout: Duration::from_millis(proof_config.transaction_timeout_ms),
+            ..FactPolling::default()
+        };
+        let verifier = IntegrityVerifier::new(proof_config).await?;
+        submit(&verifier, &args, &bundle, &stages, polling).await?
+    };
+
+    if let Some(path) = &args.report_path {
+        report.write_to_file(path)?;
+        info!("Submission report written to {:?}", path);
+    }
+
+    Ok(report)
+}
+
+/// Builds the verifier transactions for `bundle` in submission order
+pub fn build_stages(
+    bundle: &CalldataBundle,
+    args: &SubmitterArgs,
+) -> Result<Vec<ProofStage>, ProofSubmissionError> {
+    let job_id = Felt::from(args.job_id);
+
+    let mut initial = vec![
+        job_id,
+        calldata::string_to_felt(&args.layout)?,
+        calldata::string_to_felt(&args.hasher)?,
+        calldata::string_to_felt(&args.stone_version)?,
+        calldata::string_to_felt(&args.memory_verification)?,
+    ];
+    initial.extend(&bundle.initial);
+
+    let mut stages = vec![ProofStage {
+        name: "initial".to_string(),
+        entrypoint: "verify_proof_initial",
+        selector: selector!("verify_proof_initial"),
+        calldata: initial,
+    }];
+
+    for (i, step) in bundle.steps.iter().enumerate() {
+        let mut calldata = vec![job_id];
+        calldata.extend(step);
+        stages.push(ProofStage {
+            name: format!("step{}", i + 1),
+            entrypoint: "verify_proof_step",
+            selector: selector!("verify_proof_step"),
+            calldata,
+        });
+    }
+
+    let mut final_calldata = vec![job_id];
+    final_calldata.extend(&bundle.final_calldata);
+    stages.push(ProofStage {
+        name: "final".to_string(),
+        entrypoint: "verify_proof_final_and_register_fact",
+        selector: selector!("verify_proof_final_and_register_fact"),
+        calldata: final_calldata,
+    });
+
+    Ok(stages)
+}
+
+/// Sends `stages` one after the other and waits for the bundle's fact to be registered
+pub async fn submit<V: ProofVerifier + ?Sized>(
+    verifier: &V,
+    args: &SubmitterArgs,
+    bundle: &CalldataBundle,
+    stages: &[ProofStage],
+    polling: FactPolling,
+) -> Result<SubmissionReport, ProofSubmissionError> {
+    let mut tx_hashes = Vec::with_capacity(stages.len());
+    for stage in stages {
+        info!("Submitting {} for job_id: {}", stage.name, args.job_id);
+        let tx_hash = verifier
+            .invoke(stage.to_call(verifier.contract_address()))
+            .await?;
+        info!("{} accepted, tx_hash: {:#x}", stage.name, tx_hash);
+        tx_hashes.push(tx_hash);
+    }
+
+    let fact_registered = match bundle.fact_hash {
+        Some(fact_hash) => {
+            wait_for_fact(verifier, fact_hash, polling).await?;
+            true
+        }
+        None => {
+            warn!(
+                "No fact hash in {:?}, skipping fact registration check",
+                bundle.dir
+            );
+            false
+        }
+    };
+
+    Ok(build_report(
+        args,
+        bundle,
+        stages,
+        tx_hashes,
+        fact_registered,
+    ))
+}
+
+async fn wait_for_fact<V: ProofVerifier + ?Sized>(
+    verifier: &V,
+    fact_hash: Felt,
+    polling: FactPolling,
+) -> Result<(), ProofSubmissionError> {
+    let start = Instant::now();
+
+    loop {
+        if verifier.is_fact_registered(fact_hash).await? {
+            info!("Fact {:#x} registered", fact_hash);
+            return Ok(());
+        }
+        if start.elapsed() >= polling.timeout {
+            return Err(ProofSubmissionError::FactNotRegistered(format!(
+                "{:#x}",
+                fact_hash
+            )));
+        }
+        sleep(polling.interval).await;
+    }
+}
+
+fn build_report(
+    args: &SubmitterArgs,
+    bundle: &CalldataBundle,
+    stages: &[ProofStage],
+    tx_hashes: Vec<Felt>,
+    fact_registered: bool,
+) -> SubmissionReport {
+    let mut tx_hashes = tx_hashes.into_iter();
+
+    SubmissionReport {
+        job_id: args.job_id,
+        calldata_dir: bundle.dir.display().to_string(),
+        dry_run: args.dry_run,
+        stages: stages
+            .iter()
+            .map(|stage| StageReport {
+                stage: stage.name.clone(),
+                entrypoint: stage.entrypoint.to_string(),
+                calldata_len: stage.calldata.len(),
+                tx_hash: tx_hashes.next().map(|hash| format!("{:#x}", hash)),
+            })
+            .collect(),
+        fact_hash: bundle.fact_hash.map(|hash| format!("{:#x}", hash)),
+        fact_registered,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    struct MockVerifier {
+        calls: Mutex<Vec<Call>>,
+        fact_checks: AtomicUsize,
+        registered_after: usize,
+    }
+
+    impl MockVerifier {
+        fn new(registered_after: usize) -> Self {
+            Self {
+                calls: Mutex::new(Vec::new()),
+                fact_checks: AtomicUsize::new(0),
+                registered_after,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ProofVerifier for MockVerifier {
+        async fn invoke(&self, call: Call) -> Result<Felt, ProofSubmissionError> {
+            let mut calls = self.calls.lock().unwrap();
+            calls.push(call);
+            Ok(Felt::from(calls.len() as u64 + 0x100))
+        }
+
+        async fn is_fact_registered(&self, _fact_hash: Felt) -> Result<bool, ProofSubmissionError> {
+            let checks = self.fact_checks.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(checks >= self.registered_after)
+        }
+
+        fn contract_address(&self) -> Felt {
+            Felt::from(0x1234u64)
+        }
+    }
+
+    fn args() -> SubmitterArgs {
+        SubmitterArgs {
+            calldata_dir: PathBuf::from("calldata"),
+            job_id: 7,
+            layout: "recursive_with_poseidon".to_string(),
+            hasher: "keccak_160_lsb".to_string(),
+            stone_version: "stone6".to_string(),
+            memory_verification: "true".to_string(),
+            config_path: PathBuf::from("config.toml"),
+            dry_run: false,
+            report_path: None,
+        }
+    }
+
+    fn bundle(fact_hash: Option<Felt>) -> CalldataBundle {
+        CalldataBundle {
+            dir: PathBuf::from("calldata"),
+            initial: vec![Felt::ONE],
+            steps: vec![vec![Felt::TWO], vec![Felt::THREE]],
+            final_calldata: vec![Felt::from(4u8)],
+            fact_hash,
+        }
+    }
+
+    fn fast_polling() -> FactPolling {
+        FactPolling {
+            interval: Duration::from_millis(1),
+            timeout: Duration::from_millis(200),
+        }
+    }
+
+    #[test]
+    fn test_build_stages_prefixes_job_id_and_params() {
+        let stages = build_stages(&bundle(None), &args()).unwrap();
+
+        let names: Vec<_> = stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["initial", "step1", "step2", "final"]);
+        assert_eq!(stages[0].calldata.len(), 6);
+        assert_eq!(stages[0].calldata[0], Felt::from(7u8));
+        assert_eq!(stages[0].calldata[5], Felt::ONE);
+        assert_eq!(stages[2].calldata, vec![Felt::from(7u8), Felt::THREE]);
+        assert_eq!(
+            stages[3].selector,
+            selector!("verify_proof_final_and_register_fact")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_sends_stages_in_order_and_waits_for_fact() {
+        let verifier = MockVerifier::new(3);
+        let args = args();
+        let bundle = bundle(Some(Felt::from(0xfacu64)));
+        let stages = build_stages(&bundle, &args).unwrap();
+
+        let report = submit(&verifier, &args, &bundle, &stages, fast_polling())
+            .await
+            .unwrap();
+
+        let selectors: Vec<_> = verifier
+            .calls
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|call| call.selector)
+            .collect();
+        assert_eq!(
+            selectors,
+            [
+                selector!("verify_proof_initial"),
+                selector!("verify_proof_step"),
+                selector!("verify_proof_step"),
+                selector!("verify_proof_final_and_register_fact"),
+            ]
+        );
+        assert_eq!(verifier.fact_checks.load(Ordering::SeqCst), 3);
+        assert!(report.fact_registered);
+        assert_eq!(report.fact_hash.as_deref(), Some("0xfac"));
+        assert_eq!(report.stages[3].tx_hash.as_deref(), Some("0x104"));
+    }
+
+    #[tokio::test]
+    async fn test_submit_fails_when_fact_is_never_registered() {
+        let verifier = MockVerifier::new(usize::MAX);
+        let args = args();
+        let bundle = bundle(Some(Felt::from(0xfacu64)));
+        let stages = build_stages(&bundle, &args).unwrap();
+
+        let err = submit(&verifier, &args, &bundle, &stages, fast_polling())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, ProofSubmissionError::FactNotRegistered(_)));
+    }
+}
@@ -0,0 +1,182 @@
+use async_trait::async_trait;
+use starknet::accounts::{Account, ConnectedAccount, ExecutionEncoding, SingleOwnerAccount};
+use starknet::core::types::{
+    BlockId, BlockTag, Call, ExecutionResult, Felt, FunctionCall, StarknetError, TransactionReceipt,
+};
+use starknet::macros::selector;
+use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet::providers::{Provider, ProviderError};
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+use url::Url;
+
+use crate::relayer::proof_submission::{ProofSubmissionConfig, ProofSubmissionError};
+use crate::relayer::signer::{signer_from_config, AccountSigner};
+use crate::relayer::starknet_relayer::verify_chain_id;
+
+/// On-chain side of proof submission.
+///
+/// Implemented by [`IntegrityVerifier`] for the real contract; tests substitute a mock
+/// so the submission order and fact polling can be checked without a node.
+#[async_trait]
+pub trait ProofVerifier: Send + Sync {
+    /// Sends `call` and waits until the transaction is accepted, returning its hash
+    async fn invoke(&self, call: Call) -> Result<Felt, ProofSubmissionError>;
+
+    /// Whether the verifier has registered at least one verification for `fact_hash`
+    async fn is_fact_registered(&self, fact_hash: Felt) -> Result<bool, ProofSubmissionError>;
+
+    /// Address of the verifier contract the calls are sent to
+    fn contract_address(&self) -> Felt;
+}
+
+/// Submits proofs to the Integrity verifier contract from the configured account
+pub struct IntegrityVerifier {
+    account: SingleOwnerAccount<JsonRpcClient<HttpTransport>, AccountSigner>,
+    contract_address: Felt,
+    config: ProofSubmissionConfig,
+}
+
+impl IntegrityVerifier {
+    /// Builds the account from `config`, checking the RPC node serves the configured chain
+    pub async fn new(config: ProofSubmissionConfig) -> Result<Self, ProofSubmissionError> {
+        let rpc_url = Url::parse(&config.rpc_url).map_err(|e| {
+            ProofSubmissionError::InvalidConfig(format!(
+                "invalid RPC URL {}: {}",
+                config.rpc_url, e
+            ))
+        })?;
+        let address = Felt::from_hex(&config.account_address).map_err(|e| {
+            ProofSubmissionError::InvalidConfig(format!(
+                "invalid account address {}: {}",
+                config.account_address, e
+            ))
+        })?;
+        let contract_address = Felt::from_hex(&config.contract_address)
+            .map_err(|_| ProofSubmissionError::InvalidContractAddress)?;
+        let signer = signer_from_config(&config.signer, &config.private_key, address)
+            .await
+            .map_err(|e| ProofSubmissionError::InvalidConfig(e.to_string()))?;
+
+        let provider = JsonRpcClient::new(HttpTransport::new(rpc_url));
+        let chain_id = verify_chain_id(&provider, &config.chain_id)
+            .await
+            .map_err(|e| ProofSubmissionError::InvalidConfig(e.to_string()))?;
+
+        let account = SingleOwnerAccount::new(
+            provider,
+            AccountSigner(signer),
+            address,
+            chain_id,
+            ExecutionEncoding::New,
+        );
+
+        Ok(Self {
+            account,
+            contract_address,
+            config,
+        })
+    }
+
+    async fn wait_for_transaction_confirmation(
+        &self,
+        tx_hash: Felt,
+    ) -> Result<(), ProofSubmissionError> {
+        let timeout = Duration::from_millis(self.config.transaction_timeout_ms);
+        let start_time = std::time::Instant::now();
+
+        loop {
+            if start_time.elapsed() > timeout {
+                return Err(ProofSubmissionError::TransactionTimeout);
+            }
+
+            match self
+                .account
+                .provider()
+                .get_transaction_receipt(tx_hash)
+                .await
+            {
+                Ok(receipt) => {
+                    if let TransactionReceipt::Invoke(receipt) = receipt.receipt {
+                        match receipt.execution_result {
+                            ExecutionResult::Succeeded => return Ok(()),
+                            ExecutionResult::Reverted { reason } => {
+                                return Err(ProofSubmissionError::TransactionFailed(
+                                    reason.to_string(),
+                                ));
+                            }
+                        }
+                    }
+                }
+                Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => {
+                    // Transaction not found yet, keep polling
+                }
+                Err(e) => return Err(ProofSubmissionError::Provider(e)),
+            }
+
+            sleep(Duration::from_secs(2)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl ProofVerifier for IntegrityVerifier {
+    async fn invoke(&self, call: Call) -> Result<Felt, ProofSubmissionError> {
+        let max_retries = self.config.max_retries;
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+
+            let result = match self.account.execute_v3(vec![call.clone()]).send().await {
+                Ok(result) => {
+                    info!("Transaction submitted: {:#x}", result.transaction_hash);
+                    self.wait_for_transaction_confirmation(result.transaction_hash)
+                        .await
+                        .map(|_| result.transaction_hash)
+                }
+                Err(e) => Err(ProofSubmissionError::TransactionFailed(e.to_string())),
+            };
+
+            match result {
+                Ok(tx_hash) => return Ok(tx_hash),
+                Err(e) if attempts >= max_retries => {
+                    error!("Transaction failed after {} attempts: {}", attempts, e);
+                    return Err(e);
+                }
+                Err(e) => {
+                    // Linear backoff, matching the other relayers
+                    let delay = Duration::from_millis(self.config.retry_delay_ms * attempts as u64);
+                    warn!(
+                        "Transaction attempt {}/{} failed: {}. Retrying in {:?}",
+                        attempts, max_retries, e, delay
+                    );
+                    sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    async fn is_fact_registered(&self, fact_hash: Felt) -> Result<bool, ProofSubmissionError> {
+        let result = self
+            .account
+            .provider()
+            .call(
+                FunctionCall {
+                    contract_address: self.contract_address,
+                    entry_point_selector: selector!("get_all_verifications_for_fact_hash"),
+                    calldata: vec![fact_hash],
+                },
+                BlockId::Tag(BlockTag::Latest),
+            )
+            .await?;
+
+        // The first felt is the length of the returned verification array
+        Ok(result.first().is_some_and(|len| *len != Felt::ZERO))
+    }
+
+    fn contract_address(&self) -> Felt {
+        self.contract_address
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    config::{L1QueueConfig, NetworkId},
+    db::database::{
+        fetch_awaiting_l1_confirmation_deposits, fetch_deposit_hash_block, Deposit,
+        DEPOSIT_STATUS_EXPIRED, DEPOSIT_STATUS_INVALID_COMMITMENT,
+    },
+    queue::l1_queue::{
+        confirmation_status, move_deposit, ConfirmationStatus, L1BlockSource, ValidationError,
+        DEPOSIT_STATUS_PENDING_TREE_INCLUSION,
+    },
+    utils::parse_commitment_hash,
+};
+
+/// Deposits moved out of `AWAITING_L1_CONFIRMATION` by a reconciliation run
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReconciliationSummary {
+    /// Deposits whose commitment was confirmed on L1, now awaiting tree inclusion
+    pub promoted: Vec<i32>,
+    /// Deposits whose commitment never appeared on L1 within the TTL
+    pub expired: Vec<i32>,
+}
+
+/// Settles deposits accepted through the API before their commitment hash was seen
+/// on L1, see `server.require_l1_verification`.
+///
+/// Once the L1 watcher has ingested the matching `DepositHashAppended` event and it has
+/// enough confirmations, the deposit goes to tree inclusion. Deposits still unmatched
+/// after `l1_queue.awaiting_confirmation_ttl_sec` are expired.
+pub struct DepositReconciler<P: L1BlockSource> {
+    db_pool: PgPool,
+    network: NetworkId,
+    config: L1QueueConfig,
+    provider: P,
+}
+
+impl<P: L1BlockSource> DepositReconciler<P> {
+    pub fn new(db_pool: PgPool, network: NetworkId, config: L1QueueConfig, provider: P) -> Self {
+        Self {
+            db_pool,
+            network,
+            config,
+            provider,
+        }
+    }
+
+    /// Runs the reconciler in an infinite loop.
+    pub async fn start(&self) {
+        info!("Starting deposit reconciler for network {}", self.network);
+
+        loop {
+            match self.reconcile().await {
+                Ok(summary) => {
+                    if !summary.promoted.is_empty() {
+                        info!(
+                            "Deposits confirmed on L1 after submission: {:?}",
+                            summary.promoted
+                        );
+                    }
+                    if !summary.expired.is_empty() {
+                        warn!("Deposits never seen on L1: {:?}", summary.expired);
+                    }
+                }
+                Err(e) => error!("Deposit reconciliation failed: {:?}", e),
+            }
+            sleep(Duration::from_secs(self.config.poll_interval_sec)).await;
+        }
+    }
+
+    /// Promotes or expires every deposit awaiting L1 confirmation once.
+    pub async fn reconcile(&self) -> Result<ReconciliationSummary, ValidationError> {
+        let mut summary = ReconciliationSummary::default();
+
+        let deposits =
+            fetch_awaiting_l1_confirmation_deposits(&self.db_pool, &self.network).await?;
+        if deposits.is_empty() {
+            return Ok(summary);
+        }
+
+        let latest_block = self.provider.latest_block_number().await?;
+        let ttl = Duration::from_secs(self.config.awaiting_confirmation_ttl_sec);
+        let now = Utc::now();
+
+        for deposit in deposits {
+            let mut conn = self.db_pool.acquire().await?;
+
+            match self.check_confirmation(&deposit, latest_block).await {
+                Ok(ConfirmationStatus::Confirmed) => {
+                    if move_deposit(&mut conn, &deposit, DEPOSIT_STATUS_PENDING_TREE_INCLUSION)
+                        .await?
+                    {
+                        summary.promoted.push(deposit.id);
+                    }
+                }
+
+                // The event arrived, so the TTL no longer applies
+                Ok(ConfirmationStatus::Pending { confirmations }) => {
+                    debug!(
+                        "Deposit {} has {}/{} confirmations",
+                        deposit.id, confirmations, self.config.confirmation_blocks
+                    );
+                }
+
+                Ok(ConfirmationStatus::NotFound) if is_expired(deposit.created_at, ttl, now) => {
+                    if move_deposit(&mut conn, &deposit, DEPOSIT_STATUS_EXPIRED).await? {
+                        summary.expired.push(deposit.id);
+                    }
+                }
+
+                Ok(ConfirmationStatus::NotFound) => {}
+
+                // The API checks the format, so this can only come from an older row
+                Err(ValidationError::InvalidCommitment) => {
+                    error!(
+                        "Deposit {} has an invalid commitment hash {}. Taking it out of the queue.",
+                        deposit.id, deposit.commitment_hash
+                    );
+                    move_deposit(&mut conn, &deposit, DEPOSIT_STATUS_INVALID_COMMITMENT).await?;
+                }
+
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    async fn check_confirmation(
+        &self,
+        deposit: &Deposit,
+        latest_block: u64,
+    ) -> Result<ConfirmationStatus, ValidationError> {
+        let commitment = parse_commitment_hash(&deposit.commitment_hash)?;
+        let event_block =
+            fetch_deposit_hash_block(&self.db_pool, &self.network, &commitment).await?;
+
+        Ok(confirmation_status(
+            event_block,
+            latest_block,
+            self.config.confirmation_blocks,
+        ))
+    }
+}
+
+/// Whether a deposit created at `created_at` has waited at least `ttl` by `now`.
+/// Rows without a creation time never expire.
+fn is_expired(created_at: Option<DateTime<Utc>>, ttl: Duration, now: DateTime<Utc>) -> bool {
+    let Some(created_at) = created_at else {
+        return false;
+    };
+
+    now.signed_duration_since(created_at)
+        .to_std()
+        .is_ok_and(|age| age >= ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deposit_older_than_ttl_is_expired() {
+        let now = Utc::now();
+        let ttl = Duration::from_secs(60);
+
+        assert!(is_expired(
+            Some(now - chrono::Duration::seconds(61)),
+            ttl,
+            now
+        ));
+        assert!(is_expired(
+            Some(now - chrono::Duration::seconds(60)),
+            ttl,
+            now
+        ));
+    }
+
+    #[test]
+    fn test_recent_deposit_is_not_expired() {
+        let now = Utc::now();
+        let ttl = Duration::from_secs(60);
+
+        assert!(!is_expired(
+            Some(now - chrono::Duration::seconds(59)),
+            ttl,
+            now
+        ));
+        // Clock skew between the database and the sequencer
+        assert!(!is_expired(
+            Some(now + chrono::Duration::seconds(5)),
+            ttl,
+            now
+        ));
+        assert!(!is_expired(None, ttl, now));
+    }
+}
@@ -1,13 +1,31 @@
-use sqlx::PgPool;
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
-use tracing::{error, info, trace, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::{
-    config::QueueConfig,
-    db::database::{fetch_pending_deposits, process_deposit_retry, update_deposit_status, Deposit},
+    config::{L1QueueConfig, LiveConfig, NetworkId, QueueConfig},
+    db::database::{
+        await_state_proof_for_included_withdrawals, fetch_deposit_hash_block,
+        fetch_pending_deposits, fetch_withdrawals_ready_for_relay, process_deposit_retry,
+        queue_withdrawal_for_relay, update_deposit_status_cas, update_withdrawal_status, Deposit,
+        Withdrawal, DEPOSIT_STATUS_INVALID_COMMITMENT,
+    },
+    db::service_controls::{PipelineService, ServicePause},
+    events::l1_event_watcher::RealEthereumProvider,
+    herodotus::{StateProofError, StateProofRequester},
+    proof_client::service::DEPOSIT_STATUS_FAILED,
+    utils::{parse_commitment_hash, CommitmentParseError},
 };
 
+pub const DEPOSIT_STATUS_PENDING_TREE_INCLUSION: &str = "PENDING_TREE_INCLUSION";
+
+/// Maximum number of withdrawals handed over to the Ethereum relayer per poll
+const RELAY_BATCH_SIZE: i64 = 10;
+
 #[derive(Debug, thiserror::Error)]
 pub enum ValidationError {
     #[error("Database error: {0}")]
@@ -19,103 +37,523 @@ pub enum ValidationError {
     #[error("Invalid deposit commitment format")]
     InvalidCommitment,
 
-    #[error("Commitment not yet found on L1")]
-    CommitmentPending,
+    #[error("Invalid state proof: {0}")]
+    InvalidStateProof(String),
+}
+
+impl From<CommitmentParseError> for ValidationError {
+    fn from(_: CommitmentParseError) -> Self {
+        Self::InvalidCommitment
+    }
+}
+
+/// Source of the latest L1 block number, used to count confirmations
+#[async_trait]
+pub trait L1BlockSource: Send + Sync {
+    async fn latest_block_number(&self) -> Result<u64, ValidationError>;
+}
+
+#[async_trait]
+impl L1BlockSource for RealEthereumProvider {
+    async fn latest_block_number(&self) -> Result<u64, ValidationError> {
+        self.get_block_number()
+            .await
+            .map_err(|e| ValidationError::Rpc(e.to_string()))
+    }
+}
+
+// Lets services share a source picked at runtime, e.g. by the pipeline backend
+#[async_trait]
+impl<S: L1BlockSource + ?Sized> L1BlockSource for Arc<S> {
+    async fn latest_block_number(&self) -> Result<u64, ValidationError> {
+        (**self).latest_block_number().await
+    }
+}
 
-    #[error("Commitment not found after max retries")]
-    MaxRetriesExceeded,
+/// Where a deposit's commitment stands on L1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmationStatus {
+    /// No `DepositHashAppended` event has been recorded for the commitment yet
+    NotFound,
+    /// The commitment was appended but is not buried deep enough yet
+    Pending {
+        confirmations: u64,
+    },
+    Confirmed,
 }
 
-/// L1 Queue structure to process deposits.
-pub struct L1Queue {
+/// Moves `deposit` to `status` if it is still in the status it was read in, returning
+/// whether it was. A deposit another process moved in the meantime is left as is.
+pub(crate) async fn move_deposit(
+    conn: &mut PgConnection,
+    deposit: &Deposit,
+    status: &str,
+) -> Result<bool, sqlx::Error> {
+    let moved = update_deposit_status_cas(conn, deposit.id, &deposit.status, status).await?;
+    if !moved {
+        warn!(
+            "Deposit {} left {} before it could be moved to {}",
+            deposit.id, deposit.status, status
+        );
+    }
+    Ok(moved)
+}
+
+/// Compares the block the commitment was appended in with the latest L1 block.
+pub fn confirmation_status(
+    event_block: Option<i64>,
+    latest_block: u64,
+    confirmation_blocks: u32,
+) -> ConfirmationStatus {
+    let Some(event_block) = event_block else {
+        return ConfirmationStatus::NotFound;
+    };
+
+    let confirmations = latest_block.saturating_sub(event_block.max(0) as u64);
+    if confirmations >= u64::from(confirmation_blocks) {
+        ConfirmationStatus::Confirmed
+    } else {
+        ConfirmationStatus::Pending { confirmations }
+    }
+}
+
+/// Promotes pending deposits to tree inclusion once their commitment hash has been
+/// appended on L1 and has enough confirmations.
+pub struct L1QueueProcessor<P: L1BlockSource> {
     db_pool: PgPool,
-    config: QueueConfig,
+    network: NetworkId,
+    config: L1QueueConfig,
+    max_retries: u32,
+    provider: P,
+    pause: ServicePause,
+    live_config: Option<LiveConfig>,
 }
 
-impl L1Queue {
-    pub fn new(db_pool: PgPool, config: QueueConfig) -> Self {
-        Self { db_pool, config }
+impl<P: L1BlockSource> L1QueueProcessor<P> {
+    pub fn new(
+        db_pool: PgPool,
+        network: NetworkId,
+        config: L1QueueConfig,
+        max_retries: u32,
+        provider: P,
+    ) -> Self {
+        Self {
+            pause: ServicePause::new(db_pool.clone(), PipelineService::L1Queue),
+            db_pool,
+            network,
+            config,
+            max_retries,
+            provider,
+            live_config: None,
+        }
+    }
+
+    /// Takes `[l1_queue]` and `queue.max_retries` from `live_config` on every cycle
+    /// instead of the settings the processor was created with, so a reloaded
+    /// confirmation depth or retry limit applies without a restart
+    pub fn with_live_config(mut self, live_config: LiveConfig) -> Self {
+        self.live_config = Some(live_config);
+        self
+    }
+
+    fn settings(&self) -> (L1QueueConfig, u32) {
+        match &self.live_config {
+            Some(live_config) => {
+                let config = live_config.snapshot();
+                (config.l1_queue, config.queue.max_retries)
+            }
+            None => (self.config.clone(), self.max_retries),
+        }
     }
 
-    /// Runs the L1 queue processor in an infinite loop.
-    pub async fn run(&self) {
+    /// Runs the L1 queue processor in an infinite loop, skipping cycles while it is
+    /// paused.
+    pub async fn start(&self) {
+        info!("Starting L1 queue processor for network {}", self.network);
+
         loop {
-            match self.process_deposits().await {
-                Ok(_) => info!("Completed deposit processing cycle"),
-                Err(e) => error!("Deposit processing cycle failed: {:?}", e),
+            if !self.pause.is_paused().await {
+                match self.process_deposits().await {
+                    Ok(confirmed) if confirmed > 0 => {
+                        info!("Queued {} deposits for tree inclusion", confirmed)
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("Deposit processing cycle failed: {:?}", e),
+                }
             }
-            sleep(Duration::from_secs(self.config.process_interval_sec)).await;
+            let (config, _) = self.settings();
+            sleep(Duration::from_secs(config.poll_interval_sec)).await;
         }
     }
 
-    /// Processes pending deposit requests.
-    async fn process_deposits(&self) -> Result<(), sqlx::Error> {
-        let deposits = fetch_pending_deposits(&self.db_pool, self.config.max_retries).await?;
+    /// Processes pending deposits, returning how many were confirmed on L1.
+    pub async fn process_deposits(&self) -> Result<usize, ValidationError> {
+        let (config, max_retries) = self.settings();
+        let deposits = fetch_pending_deposits(&self.db_pool, &self.network, max_retries).await?;
+        if deposits.is_empty() {
+            return Ok(0);
+        }
+
+        let latest_block = self.provider.latest_block_number().await?;
+        let mut confirmed = 0;
 
         for deposit in deposits {
-            let mut tx = self.db_pool.begin().await?;
+            let mut conn = self.db_pool.acquire().await?;
 
-            // Small delay to prevent hammering chain for each deposit
-            sleep(Duration::from_secs(self.config.initial_retry_delay_sec)).await;
+            match self
+                .check_confirmation(&deposit, latest_block, config.confirmation_blocks)
+                .await
+            {
+                Ok(ConfirmationStatus::Confirmed) => {
+                    info!("Deposit {} confirmed on L1", deposit.id);
+                    if move_deposit(&mut conn, &deposit, DEPOSIT_STATUS_PENDING_TREE_INCLUSION)
+                        .await?
+                    {
+                        confirmed += 1;
+                    }
+                }
 
-            match self.validate_deposit(&deposit).await {
-                Ok(()) => {
-                    info!("Deposit {} validated successfully", deposit.id);
-                    update_deposit_status(&mut tx, deposit.id, "processed").await?;
+                // Waiting for confirmations is expected and does not use up a retry
+                Ok(ConfirmationStatus::Pending { confirmations }) => {
+                    debug!(
+                        "Deposit {} has {}/{} confirmations",
+                        deposit.id, confirmations, config.confirmation_blocks
+                    );
                 }
 
-                Err(ValidationError::CommitmentPending) => {
+                Ok(ConfirmationStatus::NotFound)
+                    if deposit.retry_count + 1 < max_retries as i32 =>
+                {
                     warn!("Deposit {} not yet found on L1. Will retry.", deposit.id);
-                    process_deposit_retry(&mut tx, deposit.id).await?;
-                    sleep(Duration::from_secs(self.config.retry_delay_seconds.into())).await;
+                    process_deposit_retry(&mut conn, deposit.id).await?;
                 }
 
-                Err(ValidationError::MaxRetriesExceeded) => {
+                Ok(ConfirmationStatus::NotFound) => {
                     error!(
-                        "Deposit {} failed after max retries. Marking as failed.",
+                        "Deposit {} not found on L1 after max retries. Marking as failed.",
                         deposit.id
                     );
-                    update_deposit_status(&mut tx, deposit.id, "failed").await?;
+                    move_deposit(&mut conn, &deposit, DEPOSIT_STATUS_FAILED).await?;
                 }
 
-                Err(e) => {
-                    warn!("Deposit {} hit an error: {:?}. Will retry.", deposit.id, e);
-                    process_deposit_retry(&mut tx, deposit.id).await?;
-                    sleep(Duration::from_secs(self.config.retry_delay_seconds.into())).await;
+                Err(ValidationError::InvalidCommitment) => {
+                    error!(
+                        "Deposit {} has an invalid commitment hash {}. Taking it out of the queue.",
+                        deposit.id, deposit.commitment_hash
+                    );
+                    move_deposit(&mut conn, &deposit, DEPOSIT_STATUS_INVALID_COMMITMENT).await?;
                 }
+
+                Err(e) => return Err(e),
             }
+        }
 
-            tx.commit().await?;
+        Ok(confirmed)
+    }
+
+    /// Looks up the `DepositHashAppended` event of the deposit's commitment and counts
+    /// its confirmations against the `confirmation_blocks` it needs.
+    async fn check_confirmation(
+        &self,
+        deposit: &Deposit,
+        latest_block: u64,
+        confirmation_blocks: u32,
+    ) -> Result<ConfirmationStatus, ValidationError> {
+        let commitment = parse_commitment_hash(&deposit.commitment_hash)?;
+        let event_block =
+            fetch_deposit_hash_block(&self.db_pool, &self.network, &commitment).await?;
+
+        Ok(confirmation_status(
+            event_block,
+            latest_block,
+            confirmation_blocks,
+        ))
+    }
+}
+
+/// Hands a network's withdrawals whose state proof arrived over to the Ethereum relayer
+/// with a `ready` `withdrawal_proofs` row, moving them to `queued_for_relay`.
+///
+/// With [`with_state_proofs`](Self::with_state_proofs), withdrawals included in the L2
+/// tree are first moved to `AWAITING_STATE_PROOF` and their proof is requested.
+pub struct WithdrawalQueueProcessor {
+    db_pool: PgPool,
+    network: NetworkId,
+    config: QueueConfig,
+    pause: ServicePause,
+    state_proofs: Option<Arc<dyn StateProofRequester>>,
+    live_config: Option<LiveConfig>,
+}
+
+impl WithdrawalQueueProcessor {
+    pub fn new(db_pool: PgPool, network: NetworkId, config: QueueConfig) -> Self {
+        Self {
+            pause: ServicePause::new(db_pool.clone(), PipelineService::WithdrawalQueue),
+            db_pool,
+            network,
+            config,
+            state_proofs: None,
+            live_config: None,
+        }
+    }
+
+    /// Requests state proofs through `state_proofs` for withdrawals included in the L2
+    /// tree. Without it they stay `included`.
+    pub fn with_state_proofs(mut self, state_proofs: Arc<dyn StateProofRequester>) -> Self {
+        self.state_proofs = Some(state_proofs);
+        self
+    }
+
+    /// Takes `[queue]` from `live_config` on every cycle instead of the config the
+    /// processor was created with, so a reloaded interval applies without a restart
+    pub fn with_live_config(mut self, live_config: LiveConfig) -> Self {
+        self.live_config = Some(live_config);
+        self
+    }
+
+    fn queue_config(&self) -> QueueConfig {
+        match &self.live_config {
+            Some(live_config) => live_config.snapshot().queue,
+            None => self.config.clone(),
         }
+    }
+
+    /// Runs the withdrawal queue processor in an infinite loop, skipping cycles while it
+    /// is paused.
+    pub async fn start(&self) {
+        info!(
+            "Starting withdrawal queue processor for network {}",
+            self.network
+        );
 
-        Ok(())
+        loop {
+            if !self.pause.is_paused().await {
+                match self.request_state_proofs().await {
+                    Ok(requested) if requested > 0 => {
+                        info!("Requested state proofs for {} withdrawals", requested)
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("Withdrawal state proof request cycle failed: {:?}", e),
+                }
+                match self.process_ready_withdrawals().await {
+                    Ok(queued) if queued > 0 => info!("Queued {} withdrawals for relay", queued),
+                    Ok(_) => {}
+                    Err(e) => error!("Withdrawal relay queueing cycle failed: {:?}", e),
+                }
+            }
+            let interval = Duration::from_secs(self.queue_config().process_interval_sec);
+            sleep(interval).await;
+        }
     }
 
-    /// Validates the deposit by verifying commitment existence
-    async fn validate_deposit(&self, deposit: &Deposit) -> Result<(), ValidationError> {
-        let commitment_exists = self
-            .check_l1_commitment(deposit.commitment_hash.clone())
+    /// Moves the network's withdrawals included in the L2 tree to `AWAITING_STATE_PROOF`
+    /// and requests their storage proofs, returning how many were requested.
+    ///
+    /// Both happen in one transaction, so the state proof poller never sees a withdrawal
+    /// awaiting its proof before the job is recorded. It requests the proofs whose
+    /// request failed here again.
+    pub async fn request_state_proofs(&self) -> Result<usize, StateProofError> {
+        let Some(state_proofs) = &self.state_proofs else {
+            return Ok(0);
+        };
+
+        let mut tx = self.db_pool.begin().await?;
+        let withdrawal_ids =
+            await_state_proof_for_included_withdrawals(&mut tx, &self.network, RELAY_BATCH_SIZE)
+                .await?;
+        if withdrawal_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let summary = state_proofs
+            .request_state_proofs(&mut tx, &withdrawal_ids)
             .await?;
+        tx.commit().await?;
+
+        Ok(summary.requested.len())
+    }
 
-        let max_retries_i32 = self.config.max_retries as i32;
+    /// Queues withdrawals in `ready_for_relay`, returning how many proofs were stored.
+    pub async fn process_ready_withdrawals(&self) -> Result<usize, ValidationError> {
+        let withdrawals =
+            fetch_withdrawals_ready_for_relay(&self.db_pool, &self.network, RELAY_BATCH_SIZE)
+                .await?;
+        let mut queued = 0;
 
-        if !commitment_exists {
-            if deposit.retry_count + 1 >= max_retries_i32 {
-                return Err(ValidationError::MaxRetriesExceeded);
-            } else {
-                return Err(ValidationError::CommitmentPending);
+        for withdrawal in withdrawals {
+            let mut tx = self.db_pool.begin().await?;
+
+            match encode_withdrawal_proof(&withdrawal) {
+                Ok((proof_params, proof)) => {
+                    if queue_withdrawal_for_relay(&mut tx, withdrawal.id, &proof_params, &proof)
+                        .await?
+                    {
+                        debug!("Queued withdrawal {} for relay", withdrawal.id);
+                        queued += 1;
+                    } else {
+                        debug!("Withdrawal {} was already queued for relay", withdrawal.id);
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "Withdrawal {} cannot be relayed: {}. Marking as failed.",
+                        withdrawal.id, e
+                    );
+                    update_withdrawal_status(
+                        &mut tx,
+                        withdrawal.id,
+                        "failed",
+                        Some(&e.to_string()),
+                    )
+                    .await?;
+                }
             }
+
+            tx.commit().await?;
         }
 
-        Ok(())
+        Ok(queued)
+    }
+}
+
+/// Encodes a withdrawal as the `proof_params` and `proof` the Ethereum relayer passes to
+/// `unlock_funds_with_proof`, as concatenated 32-byte big-endian words.
+///
+/// The params hold the withdrawal's commitment and the proof the nodes of the Herodotus
+/// storage proof of the L2 root.
+pub fn encode_withdrawal_proof(
+    withdrawal: &Withdrawal,
+) -> Result<(Vec<u8>, Vec<u8>), ValidationError> {
+    let state_proof = withdrawal
+        .state_proof
+        .as_ref()
+        .ok_or_else(|| ValidationError::InvalidStateProof("no state proof".to_string()))?;
+    let proof_params = commitment_to_bytes32(&withdrawal.commitment_hash)?.to_vec();
+
+    Ok((proof_params, encode_state_proof(state_proof)?))
+}
+
+/// Encodes the `proof.storageProof` nodes of a Herodotus payload as 32-byte words
+pub fn encode_state_proof(state_proof: &Value) -> Result<Vec<u8>, ValidationError> {
+    let nodes = state_proof
+        .pointer("/proof/storageProof")
+        .and_then(Value::as_array)
+        .ok_or_else(|| ValidationError::InvalidStateProof("missing storageProof".to_string()))?;
+
+    let mut words = Vec::with_capacity(nodes.len() * 32);
+    for node in nodes {
+        let word = node
+            .as_str()
+            .and_then(|node| commitment_to_bytes32(node).ok())
+            .ok_or_else(|| {
+                ValidationError::InvalidStateProof(format!("invalid storage proof node {}", node))
+            })?;
+        words.extend_from_slice(&word);
+    }
+
+    Ok(words)
+}
+
+/// Converts hex whose leading zeros may be left out, like a withdrawal's commitment hash
+/// as emitted on L2, to a 32-byte word.
+///
+/// Deposits' commitment hashes are never unpadded, see [`parse_commitment_hash`].
+pub fn commitment_to_bytes32(commitment_hash: &str) -> Result<[u8; 32], ValidationError> {
+    let digits = commitment_hash.trim_start_matches("0x");
+    if digits.is_empty() || digits.len() > 64 {
+        return Err(ValidationError::InvalidCommitment);
     }
 
-    async fn check_l1_commitment(&self, commitment_hash: String) -> Result<bool, ValidationError> {
-        // Check l1 event logs for commitment hash
-        // Assuming we have a function `get_event_logs` that fetches event logs from L1
-        // we store last index so we don't have to fetch all logs every time
+    let bytes =
+        hex::decode(format!("{:0>64}", digits)).map_err(|_| ValidationError::InvalidCommitment)?;
+    bytes
+        .try_into()
+        .map_err(|_| ValidationError::InvalidCommitment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_commitment_is_not_found() {
+        assert_eq!(
+            confirmation_status(None, 100, 12),
+            ConfirmationStatus::NotFound
+        );
+    }
+
+    #[test]
+    fn test_commitment_below_confirmation_depth_is_pending() {
+        assert_eq!(
+            confirmation_status(Some(95), 100, 12),
+            ConfirmationStatus::Pending { confirmations: 5 }
+        );
+        // The watcher may be ahead of the provider the latest block was read from
+        assert_eq!(
+            confirmation_status(Some(101), 100, 12),
+            ConfirmationStatus::Pending { confirmations: 0 }
+        );
+    }
+
+    #[test]
+    fn test_commitment_at_confirmation_depth_is_confirmed() {
+        assert_eq!(
+            confirmation_status(Some(88), 100, 12),
+            ConfirmationStatus::Confirmed
+        );
+        assert_eq!(
+            confirmation_status(Some(10), 100, 12),
+            ConfirmationStatus::Confirmed
+        );
+        assert_eq!(
+            confirmation_status(Some(100), 100, 0),
+            ConfirmationStatus::Confirmed
+        );
+    }
+
+    #[test]
+    fn test_commitment_to_bytes32_pads_unprefixed_hex() {
+        let mut expected = [0u8; 32];
+        expected[30..].copy_from_slice(&[0x0a, 0xbc]);
+
+        assert_eq!(commitment_to_bytes32("abc").unwrap(), expected);
+        assert_eq!(commitment_to_bytes32("0xabc").unwrap(), expected);
+        assert_eq!(commitment_to_bytes32(&"ff".repeat(32)).unwrap(), [0xff; 32]);
+    }
+
+    #[test]
+    fn test_state_proof_nodes_are_encoded_as_words() {
+        let payload = serde_json::json!({
+            "queryStatus": "DONE",
+            "proof": { "storageProof": ["0xabc", "0x01"] }
+        });
+
+        let words = encode_state_proof(&payload).unwrap();
+        assert_eq!(words.len(), 64);
+        assert_eq!(&words[30..32], &[0x0a, 0xbc]);
+        assert_eq!(words[63], 0x01);
+    }
+
+    #[test]
+    fn test_malformed_state_proof_is_rejected() {
+        for payload in [
+            serde_json::json!({ "queryStatus": "DONE" }),
+            serde_json::json!({ "proof": { "storageProof": "0xabc" } }),
+            serde_json::json!({ "proof": { "storageProof": ["0xzz"] } }),
+            serde_json::json!({ "proof": { "storageProof": [1] } }),
+        ] {
+            assert!(matches!(
+                encode_state_proof(&payload),
+                Err(ValidationError::InvalidStateProof(_))
+            ));
+        }
+    }
 
-        trace!("Checking L1 commitment for hash: {}", commitment_hash);
-        Ok(true)
+    #[test]
+    fn test_commitment_to_bytes32_rejects_invalid_hex() {
+        assert!(commitment_to_bytes32("").is_err());
+        assert!(commitment_to_bytes32("0xzz").is_err());
+        assert!(commitment_to_bytes32(&"ff".repeat(33)).is_err());
     }
 }
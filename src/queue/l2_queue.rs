@@ -1,16 +1,40 @@
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Postgres};
+use serde_json::json;
+use sqlx::{types::BigDecimal, PgPool, Pool, Postgres};
+use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
+use tokio::sync::RwLock;
 use tokio::time::sleep;
-use tracing::{error, info, trace, warn};
+use tracing::{debug, error, info, trace, warn};
+use tree_builder::l1_tree::L1MerkleTreeBuilder;
+
+use crate::{
+    config::{self, LiveConfig, NetworkId},
+    db::database::{
+        fetch_deposits_ready_for_relay, queue_deposit_for_relay, DEPOSIT_STATUS_INVALID_COMMITMENT,
+    },
+    db::service_controls::{PipelineService, ServicePause},
+    db::timings::{timed, DepositStage},
+    events::status_notifications::{StatusWakeup, Wakeup, DEPOSITS_STATUS_CHANNEL},
+    queue::l1_queue::move_deposit,
+    tree_builder::proof_cache::{inclusion_proof, ProofCache},
+    utils::parse_commitment_hash,
+};
+
+/// Deposits don't record the L1 token, the bridge mints the same L2 token for all of them
+pub const RELAYED_DEPOSIT_TOKEN_ADDRESS: &str = "0x0";
+
+/// Maximum number of deposits handed over to the Starknet relayer per poll
+const RELAY_BATCH_SIZE: i64 = 10;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct L2Transaction {
     pub id: i64,
     pub stark_pub_key: String,
-    pub amount: i64,
+    pub amount: BigDecimal,
     pub token_address: String,
     pub status: String,
     pub created_at: DateTime<Utc>,
@@ -19,6 +43,10 @@ pub struct L2Transaction {
     pub error: Option<String>,
     pub proof_data: Option<String>,
     pub retry_count: i32,
+    /// Configured network the transaction was made on
+    pub network: String,
+    /// Deposit the transaction relays, `None` for rows not created by [`L2QueueProcessor`]
+    pub deposit_id: Option<i32>,
 }
 
 #[derive(Debug, Error)]
@@ -40,6 +68,9 @@ pub enum L2QueueError {
 
     #[error("Max retries exceeded")]
     MaxRetriesExceeded,
+
+    #[error("Merkle tree error: {0}")]
+    Tree(String),
 }
 
 pub struct QueueConfig {
@@ -245,3 +276,212 @@ impl L2Queue {
         Ok(transactions)
     }
 }
+
+/// Inclusion proof of a deposit's commitment against the current root of the L1 tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub leaf_index: usize,
+    pub siblings: Vec<String>,
+    pub peaks: Vec<String>,
+    pub elements_count: usize,
+    pub merkle_root: String,
+}
+
+/// Source of the inclusion proofs relayed with deposits
+#[async_trait]
+pub trait InclusionProofSource: Send + Sync {
+    /// Proves `commitment` against the current root, `None` while it is not in the tree
+    async fn inclusion_proof(
+        &self,
+        commitment: [u8; 32],
+    ) -> Result<Option<InclusionProof>, L2QueueError>;
+}
+
+#[async_trait]
+impl InclusionProofSource for Arc<RwLock<L1MerkleTreeBuilder>> {
+    async fn inclusion_proof(
+        &self,
+        commitment: [u8; 32],
+    ) -> Result<Option<InclusionProof>, L2QueueError> {
+        let tree = self.read().await;
+        let root = tree.get_root().await.map_err(tree_error)?;
+
+        inclusion_proof(&tree, commitment, root)
+            .await
+            .map_err(tree_error)
+    }
+}
+
+/// Proofs of a tree served through the tree builder's proof cache
+pub struct CachedInclusionProofs {
+    pub tree: Arc<RwLock<L1MerkleTreeBuilder>>,
+    pub cache: ProofCache,
+}
+
+#[async_trait]
+impl InclusionProofSource for CachedInclusionProofs {
+    async fn inclusion_proof(
+        &self,
+        commitment: [u8; 32],
+    ) -> Result<Option<InclusionProof>, L2QueueError> {
+        let tree = self.tree.read().await;
+
+        self.cache
+            .prove(&tree, commitment)
+            .await
+            .map_err(tree_error)
+    }
+}
+
+/// Builds the `proof_data` the Starknet relayer reads from an L2 transaction.
+///
+/// The L1 address and signature of the depositor are not collected yet, so `eth_address`,
+/// `r`, `s` and `y_parity` are zero placeholders.
+pub fn relay_proof_data(commitment: [u8; 32], proof: &InclusionProof) -> String {
+    json!({
+        "proof": proof.siblings,
+        "peaks": proof.peaks,
+        "leaf_index": proof.leaf_index,
+        "elements_count": proof.elements_count,
+        "merkle_root": proof.merkle_root,
+        "commitment_hash": format!("0x{}", hex::encode(commitment)),
+        "eth_address": "0x0",
+        "r": "0x0",
+        "s": "0x0",
+        "y_parity": false,
+    })
+    .to_string()
+}
+
+/// Hands a network's deposits whose proof is generated over to the Starknet relayer as
+/// `ready_for_relay` L2 transactions, moving them to `QUEUED_FOR_RELAY`.
+pub struct L2QueueProcessor<S: InclusionProofSource> {
+    db_pool: PgPool,
+    network: NetworkId,
+    config: config::QueueConfig,
+    proofs: S,
+    pause: ServicePause,
+    live_config: Option<LiveConfig>,
+}
+
+impl<S: InclusionProofSource> L2QueueProcessor<S> {
+    pub fn new(
+        db_pool: PgPool,
+        network: NetworkId,
+        config: config::QueueConfig,
+        proofs: S,
+    ) -> Self {
+        Self {
+            pause: ServicePause::new(db_pool.clone(), PipelineService::L2Queue),
+            db_pool,
+            network,
+            config,
+            proofs,
+            live_config: None,
+        }
+    }
+
+    /// Takes `[queue]` from `live_config` on every cycle instead of the config the
+    /// processor was created with, so a reloaded interval applies without a restart
+    pub fn with_live_config(mut self, live_config: LiveConfig) -> Self {
+        self.live_config = Some(live_config);
+        self
+    }
+
+    fn queue_config(&self) -> config::QueueConfig {
+        match &self.live_config {
+            Some(live_config) => live_config.snapshot().queue,
+            None => self.config.clone(),
+        }
+    }
+
+    /// Runs the L2 queue processor in an infinite loop, queueing nothing while it is
+    /// paused.
+    pub async fn start(&self) {
+        info!("Starting L2 queue processor for network {}", self.network);
+
+        let mut wakeup =
+            StatusWakeup::listen(&self.db_pool, DEPOSITS_STATUS_CHANNEL, "READY_FOR_RELAY").await;
+
+        loop {
+            if !self.pause.is_paused().await {
+                match self.process_ready_deposits().await {
+                    Ok(queued) if queued > 0 => info!("Queued {} deposits for relay", queued),
+                    Ok(_) => {}
+                    Err(e) => error!("Deposit relay queueing cycle failed: {:?}", e),
+                }
+            }
+
+            let interval = Duration::from_secs(self.queue_config().process_interval_sec);
+            if let Wakeup::Notified(ids) = wakeup.wait(interval).await {
+                debug!("Deposits {:?} are ready for relay", ids);
+            }
+        }
+    }
+
+    /// Queues deposits in `READY_FOR_RELAY`, returning how many L2 transactions were
+    /// created.
+    pub async fn process_ready_deposits(&self) -> Result<usize, L2QueueError> {
+        let deposits =
+            fetch_deposits_ready_for_relay(&self.db_pool, &self.network, RELAY_BATCH_SIZE).await?;
+        let mut queued = 0;
+
+        for deposit in deposits {
+            let commitment = match parse_commitment_hash(&deposit.commitment_hash) {
+                Ok(commitment) => commitment,
+                Err(e) => {
+                    error!(
+                        "Taking deposit {} out of the relay queue: {}",
+                        deposit.id, e
+                    );
+                    let mut conn = self.db_pool.acquire().await?;
+                    move_deposit(&mut conn, &deposit, DEPOSIT_STATUS_INVALID_COMMITMENT).await?;
+                    continue;
+                }
+            };
+
+            // The tree may not have caught up yet after a restart
+            let Some(proof) = self.proofs.inclusion_proof(commitment).await? else {
+                warn!(
+                    "Deposit {} is not in the tree of {} yet. Will retry.",
+                    deposit.id, self.network
+                );
+                continue;
+            };
+
+            let proof_data = relay_proof_data(commitment, &proof);
+            let queueing = async {
+                let mut tx = self.db_pool.begin().await?;
+                let id = queue_deposit_for_relay(
+                    &mut tx,
+                    &deposit,
+                    RELAYED_DEPOSIT_TOKEN_ADDRESS,
+                    &proof_data,
+                )
+                .await?;
+                tx.commit().await?;
+                Ok::<_, sqlx::Error>(id)
+            };
+            match timed(
+                &self.db_pool,
+                deposit.id,
+                DepositStage::RelayQueueing,
+                queueing,
+            )
+            .await?
+            {
+                Some(id) => {
+                    debug!("Queued deposit {} as L2 transaction {}", deposit.id, id);
+                    queued += 1;
+                }
+                None => debug!("Deposit {} was already queued for relay", deposit.id),
+            }
+        }
+
+        Ok(queued)
+    }
+}
+
+fn tree_error(err: impl std::fmt::Display) -> L2QueueError {
+    L2QueueError::Tree(err.to_string())
+}
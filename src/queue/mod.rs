@@ -1,2 +1,3 @@
+pub mod deposit_reconciler;
 pub mod l1_queue;
 pub mod l2_queue;
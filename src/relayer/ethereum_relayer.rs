@@ -1,9 +1,13 @@
-use crate::config::RelayerConfig;
+use crate::config::{NetworkId, RelayerConfig};
+use crate::db::database::update_withdrawal_status;
+use crate::db::service_controls::{PipelineService, ServicePause};
+use crate::relayer::gas_oracle::{GasFees, GasPriceOracle};
+use crate::utils::amount::amount_to_u256;
 use alloy_json_rpc::RpcError;
 use alloy_primitives::{hex, Address, U256};
 use alloy_rpc_client::{ClientBuilder, RpcClient};
 use alloy_sol_types::{sol, SolCall};
-use sqlx::{PgConnection, PgPool};
+use sqlx::{types::BigDecimal, PgConnection, PgPool};
 use std::time::Duration;
 use thiserror::Error;
 use tokio::time::sleep;
@@ -44,7 +48,7 @@ pub enum RelayerError {
 pub struct WithdrawalWithProof {
     pub withdrawal_id: i32,
     pub stark_pub_key: String,
-    pub amount: i64,
+    pub amount: BigDecimal,
     pub l2_tx_id: String,
     pub commitment_hash: String,
     pub proof_params: Vec<u8>,
@@ -54,39 +58,49 @@ pub struct WithdrawalWithProof {
 /// Relayer for sending L1 transactions to Ethereum
 pub struct EthereumRelayer {
     db_pool: PgPool,
+    network: NetworkId,
     client: RpcClient,
+    gas_oracle: GasPriceOracle<RpcClient>,
     contract_address: Address,
     config: RelayerConfig,
+    pause: ServicePause,
 }
 
 impl EthereumRelayer {
     /// Create a new Ethereum relayer
     pub async fn new(
         db_pool: PgPool,
+        network: NetworkId,
         ethereum_rpc_url: Url,
         contract_address: &str,
         config: RelayerConfig,
     ) -> Result<Self, RelayerError> {
         let client = ClientBuilder::default().http(ethereum_rpc_url);
+        let gas_oracle = GasPriceOracle::new(client.clone(), &config.gas_price);
 
         let contract_address = contract_address
             .parse::<Address>()
             .map_err(|e| RelayerError::ContractError(format!("Invalid contract address: {}", e)))?;
 
         Ok(Self {
+            pause: ServicePause::new(db_pool.clone(), PipelineService::EthereumRelayer),
             db_pool,
+            network,
             client,
+            gas_oracle,
             contract_address,
             config,
         })
     }
 
-    /// Run the relayer in an infinite loop
+    /// Run the relayer in an infinite loop, relaying nothing while it is paused
     pub async fn run(&self) {
         loop {
-            match self.process_relay_transactions().await {
-                Ok(_) => info!("Completed relay processing cycle"),
-                Err(e) => error!("Relay processing cycle failed: {:?}", e),
+            if !self.pause.is_paused().await {
+                match self.process_relay_transactions().await {
+                    Ok(_) => info!("Completed relay processing cycle"),
+                    Err(e) => error!("Relay processing cycle failed: {:?}", e),
+                }
             }
             sleep(Duration::from_secs(self.config.retry_delay_seconds.into())).await;
         }
@@ -105,7 +119,7 @@ impl EthereumRelayer {
                         "Successfully relayed transaction for withdrawal {}",
                         withdrawal.withdrawal_id
                     );
-                    self.update_withdrawal_status(&mut tx, withdrawal.withdrawal_id, "relayed")
+                    update_withdrawal_status(&mut tx, withdrawal.withdrawal_id, "relayed", None)
                         .await?;
                 }
                 Err(e) => {
@@ -115,8 +129,13 @@ impl EthereumRelayer {
                             "Max retries reached for withdrawal {}. Marking as failed: {:?}",
                             withdrawal.withdrawal_id, e
                         );
-                        self.update_withdrawal_status(&mut tx, withdrawal.withdrawal_id, "failed")
-                            .await?;
+                        update_withdrawal_status(
+                            &mut tx,
+                            withdrawal.withdrawal_id,
+                            "failed",
+                            Some(&e.to_string()),
+                        )
+                        .await?;
                     } else {
                         warn!(
                             "Failed to relay transaction for withdrawal {}. Will retry: {:?}",
@@ -163,11 +182,13 @@ impl EthereumRelayer {
                   dp.proof_params, dp.proof_data 
             FROM withdrawals d
             JOIN withdrawal_proofs dp ON d.id = dp.withdrawal_id
-            WHERE d.status = 'ready_for_relay' AND d.retry_count < $1 AND dp.status = 'ready'
+            WHERE d.network = $2 AND d.status = 'queued_for_relay' AND d.retry_count < $1
+                AND dp.status = 'ready'
             ORDER BY d.created_at ASC
             LIMIT 10
             "#,
-            self.config.max_retries as i32
+            self.config.max_retries as i32,
+            self.network.as_str()
         )
         .fetch_all(&self.db_pool)
         .await?;
@@ -188,28 +209,6 @@ impl EthereumRelayer {
         Ok(withdrawals_with_proofs)
     }
 
-    /// Update the status of a withdrawal
-    async fn update_withdrawal_status(
-        &self,
-        conn: &mut PgConnection,
-        id: i32,
-        status: &str,
-    ) -> Result<(), RelayerError> {
-        sqlx::query!(
-            r#"
-            UPDATE withdrawals 
-            SET status = $2, updated_at = NOW()
-            WHERE id = $1
-            "#,
-            id,
-            status
-        )
-        .execute(conn)
-        .await?;
-
-        Ok(())
-    }
-
     /// Increment the retry count for a withdrawal
     async fn increment_retry_count(
         &self,
@@ -288,11 +287,7 @@ impl EthereumRelayer {
 
         let from = accounts[0];
 
-        let gas_price: U256 = self
-            .client
-            .request_noparams("eth_gasPrice")
-            .await
-            .map_err(|e| RelayerError::RpcError(e.to_string()))?;
+        let fees = self.gas_oracle.fees().await?;
 
         let nonce: U256 = self
             .client
@@ -307,7 +302,9 @@ impl EthereumRelayer {
             .map_err(|e| RelayerError::ContractError(format!("Invalid stark pub key: {}", e)))?;
 
         // Parse amount
-        let amount = U256::from(withdrawal.amount);
+        let (low, high) = amount_to_u256(&withdrawal.amount)
+            .map_err(|e| RelayerError::ContractError(e.to_string()))?;
+        let amount = (U256::from(high) << 128) | U256::from(low);
 
         // Parse L2 TX ID, defaulting to 0 if empty
         let l2_tx_id = if withdrawal.l2_tx_id.is_empty() {
@@ -346,14 +343,26 @@ impl EthereumRelayer {
 
         let call_data = call.abi_encode();
 
-        let tx_params = serde_json::json!({
+        let mut tx_params = serde_json::json!({
             "from": from,
             "to": self.contract_address,
             "gas": format!("0x{:x}", self.config.gas_limit),
-            "gasPrice": format!("0x{:x}", gas_price),
             "nonce": format!("0x{:x}", nonce),
             "data": format!("0x{}", hex::encode(&call_data)),
         });
+        match fees {
+            GasFees::Legacy { gas_price } => {
+                tx_params["gasPrice"] = format!("0x{:x}", gas_price).into();
+            }
+            GasFees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                tx_params["maxFeePerGas"] = format!("0x{:x}", max_fee_per_gas).into();
+                tx_params["maxPriorityFeePerGas"] =
+                    format!("0x{:x}", max_priority_fee_per_gas).into();
+            }
+        }
 
         let tx_hash: String = self
             .client
@@ -0,0 +1,260 @@
+use alloy_primitives::U128;
+use alloy_rpc_client::RpcClient;
+use async_trait::async_trait;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::{GasPriceConfig, GasPriceStrategy};
+use crate::relayer::ethereum_relayer::RelayerError;
+
+/// Source of the gas prices suggested by an Ethereum node
+#[async_trait]
+pub trait GasPriceProvider: Send + Sync {
+    async fn get_gas_price(&self) -> Result<u128, RelayerError>;
+    async fn get_max_priority_fee_per_gas(&self) -> Result<u128, RelayerError>;
+}
+
+#[async_trait]
+impl GasPriceProvider for RpcClient {
+    async fn get_gas_price(&self) -> Result<u128, RelayerError> {
+        let price: U128 = self
+            .request_noparams("eth_gasPrice")
+            .await
+            .map_err(|e| RelayerError::RpcError(e.to_string()))?;
+        Ok(price.to())
+    }
+
+    async fn get_max_priority_fee_per_gas(&self) -> Result<u128, RelayerError> {
+        let fee: U128 = self
+            .request_noparams("eth_maxPriorityFeePerGas")
+            .await
+            .map_err(|e| RelayerError::RpcError(e.to_string()))?;
+        Ok(fee.to())
+    }
+}
+
+/// Fee fields of a transaction priced by the oracle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasFees {
+    Legacy {
+        gas_price: u128,
+    },
+    Eip1559 {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+}
+
+impl GasFees {
+    /// Most the transaction may pay per unit of gas
+    pub fn max_price_per_gas(&self) -> u128 {
+        match self {
+            Self::Legacy { gas_price } => *gas_price,
+            Self::Eip1559 {
+                max_fee_per_gas, ..
+            } => *max_fee_per_gas,
+        }
+    }
+}
+
+/// Prices relayed transactions from the node's suggestions and the configured
+/// [`GasPriceStrategy`].
+///
+/// Fetched prices are reused for `cache_ttl_secs`, so a batch of withdrawals costs a
+/// single pair of RPC calls.
+pub struct GasPriceOracle<P> {
+    provider: P,
+    strategy: GasPriceStrategy,
+    cache_ttl: Duration,
+    base_fee: Mutex<Option<(Instant, u128)>>,
+    priority_fee: Mutex<Option<(Instant, u128)>>,
+}
+
+impl<P: GasPriceProvider> GasPriceOracle<P> {
+    pub fn new(provider: P, config: &GasPriceConfig) -> Self {
+        Self {
+            provider,
+            strategy: config.strategy,
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+            base_fee: Mutex::new(None),
+            priority_fee: Mutex::new(None),
+        }
+    }
+
+    /// Gas price suggested by the node, before any multiplier
+    pub async fn get_base_fee(&self) -> Result<u128, RelayerError> {
+        if let Some(price) = self.cached(&self.base_fee) {
+            return Ok(price);
+        }
+        let price = self.provider.get_gas_price().await?;
+        *self.base_fee.lock().unwrap() = Some((Instant::now(), price));
+        Ok(price)
+    }
+
+    /// Priority fee suggested by the node, before any multiplier
+    pub async fn get_priority_fee(&self) -> Result<u128, RelayerError> {
+        if let Some(fee) = self.cached(&self.priority_fee) {
+            return Ok(fee);
+        }
+        let fee = self.provider.get_max_priority_fee_per_gas().await?;
+        *self.priority_fee.lock().unwrap() = Some((Instant::now(), fee));
+        Ok(fee)
+    }
+
+    /// Fees to send the next transaction with
+    pub async fn fees(&self) -> Result<GasFees, RelayerError> {
+        match self.strategy {
+            GasPriceStrategy::Legacy(multiplier) => Ok(GasFees::Legacy {
+                gas_price: apply_multiplier(self.get_base_fee().await?, multiplier),
+            }),
+            GasPriceStrategy::Eip1559 {
+                max_fee_multiplier,
+                priority_fee_multiplier,
+            } => {
+                let priority_fee =
+                    apply_multiplier(self.get_priority_fee().await?, priority_fee_multiplier);
+                let base_fee = apply_multiplier(self.get_base_fee().await?, max_fee_multiplier);
+                Ok(GasFees::Eip1559 {
+                    max_fee_per_gas: base_fee.saturating_add(priority_fee),
+                    max_priority_fee_per_gas: priority_fee,
+                })
+            }
+        }
+    }
+
+    /// Most a transaction using `gas_limit` gas may cost, in wei
+    pub async fn estimate_tx_cost(&self, gas_limit: u64) -> Result<u128, RelayerError> {
+        let fees = self.fees().await?;
+        Ok(fees
+            .max_price_per_gas()
+            .saturating_mul(u128::from(gas_limit)))
+    }
+
+    fn cached(&self, entry: &Mutex<Option<(Instant, u128)>>) -> Option<u128> {
+        match *entry.lock().unwrap() {
+            Some((fetched_at, value)) if fetched_at.elapsed() < self.cache_ttl => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Scales a price in wei, rounding up so a multiplier never lowers it below the product
+fn apply_multiplier(value: u128, multiplier: f64) -> u128 {
+    (value as f64 * multiplier).ceil() as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+    const GWEI: u128 = 1_000_000_000;
+
+    struct MockGasPriceProvider {
+        gas_price: AtomicU64,
+        priority_fee: AtomicU64,
+        fetches: AtomicUsize,
+    }
+
+    impl MockGasPriceProvider {
+        fn new(gas_price: u128, priority_fee: u128) -> Self {
+            Self {
+                gas_price: AtomicU64::new(gas_price as u64),
+                priority_fee: AtomicU64::new(priority_fee as u64),
+                fetches: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl GasPriceProvider for MockGasPriceProvider {
+        async fn get_gas_price(&self) -> Result<u128, RelayerError> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(self.gas_price.load(Ordering::SeqCst).into())
+        }
+
+        async fn get_max_priority_fee_per_gas(&self) -> Result<u128, RelayerError> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(self.priority_fee.load(Ordering::SeqCst).into())
+        }
+    }
+
+    fn oracle(
+        strategy: GasPriceStrategy,
+        cache_ttl_secs: u64,
+    ) -> GasPriceOracle<MockGasPriceProvider> {
+        let config = GasPriceConfig {
+            strategy,
+            cache_ttl_secs,
+        };
+        GasPriceOracle::new(MockGasPriceProvider::new(30 * GWEI, 2 * GWEI), &config)
+    }
+
+    #[tokio::test]
+    async fn test_legacy_multiplier_is_applied() {
+        let oracle = oracle(GasPriceStrategy::Legacy(1.5), 12);
+
+        assert_eq!(oracle.get_base_fee().await.unwrap(), 30 * GWEI);
+        assert_eq!(
+            oracle.fees().await.unwrap(),
+            GasFees::Legacy {
+                gas_price: 45 * GWEI
+            }
+        );
+        assert_eq!(
+            oracle.estimate_tx_cost(100_000).await.unwrap(),
+            45 * GWEI * 100_000
+        );
+    }
+
+    #[tokio::test]
+    async fn test_eip1559_multipliers_are_applied() {
+        let oracle = oracle(
+            GasPriceStrategy::Eip1559 {
+                max_fee_multiplier: 2.0,
+                priority_fee_multiplier: 1.5,
+            },
+            12,
+        );
+
+        assert_eq!(oracle.get_priority_fee().await.unwrap(), 2 * GWEI);
+        // 30 gwei doubled plus the 3 gwei priority fee
+        assert_eq!(
+            oracle.fees().await.unwrap(),
+            GasFees::Eip1559 {
+                max_fee_per_gas: 63 * GWEI,
+                max_priority_fee_per_gas: 3 * GWEI,
+            }
+        );
+        assert_eq!(
+            oracle.estimate_tx_cost(21_000).await.unwrap(),
+            63 * GWEI * 21_000
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prices_are_cached_for_the_ttl() {
+        let oracle = oracle(GasPriceStrategy::Legacy(1.0), 60);
+        oracle.get_base_fee().await.unwrap();
+
+        oracle
+            .provider
+            .gas_price
+            .store((40 * GWEI) as u64, Ordering::SeqCst);
+        assert_eq!(oracle.get_base_fee().await.unwrap(), 30 * GWEI);
+        assert_eq!(oracle.provider.fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_prices_are_fetched_again() {
+        let oracle = oracle(GasPriceStrategy::Legacy(1.0), 0);
+        oracle.get_base_fee().await.unwrap();
+
+        oracle
+            .provider
+            .gas_price
+            .store((40 * GWEI) as u64, Ordering::SeqCst);
+        assert_eq!(oracle.get_base_fee().await.unwrap(), 40 * GWEI);
+        assert_eq!(oracle.provider.fetches.load(Ordering::SeqCst), 2);
+    }
+}
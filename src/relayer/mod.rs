@@ -1,4 +1,8 @@
 pub mod client;
 pub mod ethereum_relayer;
+pub mod gas_oracle;
+pub mod nonce_manager;
 pub mod proof_submission;
+pub mod signer;
 pub mod starknet_relayer;
+pub mod submission;
@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use starknet::core::types::{BlockId, BlockTag, Felt};
+use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet::providers::{Provider, ProviderError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::info;
+
+use crate::relayer::starknet_relayer::StarknetRelayerError;
+
+/// Source of the nonce the Starknet node expects next from an account
+#[async_trait]
+pub trait NonceSource: Send + Sync {
+    async fn fetch_nonce(&self, account_address: Felt) -> Result<Felt, ProviderError>;
+}
+
+#[async_trait]
+impl NonceSource for JsonRpcClient<HttpTransport> {
+    async fn fetch_nonce(&self, account_address: Felt) -> Result<Felt, ProviderError> {
+        self.get_nonce(BlockId::Tag(BlockTag::Pending), account_address)
+            .await
+    }
+}
+
+/// Hands out account nonces locally so transactions submitted concurrently, before
+/// the previous one is included, do not reuse the same nonce.
+///
+/// The counter is read from the pending block at startup and re-synced whenever the
+/// node rejects a nonce.
+pub struct NonceManager {
+    account_address: Felt,
+    next: AtomicU64,
+}
+
+impl NonceManager {
+    /// Starts from the account's nonce in the pending block
+    pub async fn new<S: NonceSource + ?Sized>(
+        source: &S,
+        account_address: Felt,
+    ) -> Result<Self, StarknetRelayerError> {
+        let nonce = fetch_nonce(source, account_address).await?;
+        info!(
+            "Starknet account {:#x} starts at nonce {}",
+            account_address, nonce
+        );
+
+        Ok(Self {
+            account_address,
+            next: AtomicU64::new(nonce),
+        })
+    }
+
+    /// Reserves the next nonce
+    pub fn next(&self) -> u64 {
+        self.next.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Resets the counter to the account's nonce in the pending block
+    pub async fn sync<S: NonceSource + ?Sized>(
+        &self,
+        source: &S,
+    ) -> Result<u64, StarknetRelayerError> {
+        let nonce = fetch_nonce(source, self.account_address).await?;
+        self.next.store(nonce, Ordering::SeqCst);
+        info!(
+            "Re-synced Starknet account {:#x} to nonce {}",
+            self.account_address, nonce
+        );
+        Ok(nonce)
+    }
+}
+
+async fn fetch_nonce<S: NonceSource + ?Sized>(
+    source: &S,
+    account_address: Felt,
+) -> Result<u64, StarknetRelayerError> {
+    let nonce = source.fetch_nonce(account_address).await?;
+    u64::try_from(nonce).map_err(|_| StarknetRelayerError::InvalidNonce(format!("{:#x}", nonce)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    struct MockNonceSource {
+        nonce: AtomicU64,
+        fetches: AtomicUsize,
+    }
+
+    impl MockNonceSource {
+        fn new(nonce: u64) -> Self {
+            Self {
+                nonce: AtomicU64::new(nonce),
+                fetches: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl NonceSource for MockNonceSource {
+        async fn fetch_nonce(&self, _account_address: Felt) -> Result<Felt, ProviderError> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(Felt::from(self.nonce.load(Ordering::SeqCst)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_submissions_get_sequential_nonces() {
+        let source = MockNonceSource::new(7);
+        let manager = Arc::new(NonceManager::new(&source, Felt::ONE).await.unwrap());
+
+        let tasks: Vec<_> = (0..50)
+            .map(|_| {
+                let manager = manager.clone();
+                tokio::spawn(async move { manager.next() })
+            })
+            .collect();
+        let mut nonces = Vec::new();
+        for task in tasks {
+            nonces.push(task.await.unwrap());
+        }
+
+        let unique: HashSet<_> = nonces.iter().collect();
+        assert_eq!(unique.len(), 50);
+        nonces.sort();
+        assert_eq!(nonces, (7..57).collect::<Vec<_>>());
+        assert_eq!(manager.next(), 57);
+        // Nonces are handed out without asking the node again
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sync_resets_to_provider_nonce() {
+        let source = MockNonceSource::new(3);
+        let manager = NonceManager::new(&source, Felt::ONE).await.unwrap();
+        manager.next();
+        manager.next();
+
+        // A transaction sent with a reserved nonce never reached the node
+        source.nonce.store(4, Ordering::SeqCst);
+        assert_eq!(manager.sync(&source).await.unwrap(), 4);
+        assert_eq!(manager.next(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_nonce_above_u64_is_rejected() {
+        struct HugeNonce;
+
+        #[async_trait]
+        impl NonceSource for HugeNonce {
+            async fn fetch_nonce(&self, _account_address: Felt) -> Result<Felt, ProviderError> {
+                Ok(Felt::from(u128::MAX))
+            }
+        }
+
+        assert!(matches!(
+            NonceManager::new(&HugeNonce, Felt::ONE).await,
+            Err(StarknetRelayerError::InvalidNonce(_))
+        ));
+    }
+}
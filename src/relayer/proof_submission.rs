@@ -1,20 +1,14 @@
-use crate::config::AppConfig;
+use crate::config::{AppConfig, SignerConfig};
+use crate::proof_submitter::calldata::{read_calldata_file, string_to_felt};
+use crate::proof_submitter::{IntegrityVerifier, ProofVerifier};
 use serde_json::Value;
 use sqlx::{Pool, Postgres};
-use starknet::accounts::{Account, ConnectedAccount, ExecutionEncoding, SingleOwnerAccount};
-use starknet::core::chain_id::MAINNET;
-use starknet::core::types::{Call, ExecutionResult, Felt, TransactionReceipt};
-use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
-use starknet::providers::{Provider, ProviderError};
-use starknet::signers::{LocalWallet, SigningKey};
+use starknet::core::types::{Call, Felt};
+use starknet::providers::ProviderError;
 use std::collections::HashMap;
-use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
 use thiserror::Error;
-use tokio::time::sleep;
-use tracing::{debug, error, info, warn};
-use url::Url;
+use tracing::{error, info, warn};
 
 #[derive(Error, Debug)]
 pub enum ProofSubmissionError {
@@ -53,14 +47,22 @@ pub enum ProofSubmissionError {
 
     #[error("Invalid calldata format: {0}")]
     InvalidCalldataFormat(String),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Fact hash not registered: {0}")]
+    FactNotRegistered(String),
 }
 
 #[derive(Debug, Clone)]
 pub struct ProofSubmissionConfig {
     pub contract_address: String,
     pub rpc_url: String,
+    pub chain_id: String,
     pub account_address: String,
     pub private_key: String,
+    pub signer: SignerConfig,
     pub max_retries: u32,
     pub retry_delay_ms: u64,
     pub transaction_timeout_ms: u64,
@@ -71,8 +73,10 @@ impl From<AppConfig> for ProofSubmissionConfig {
         Self {
             contract_address: config.starknet.contract_address.clone(),
             rpc_url: config.starknet.get_rpc_url(),
+            chain_id: config.starknet.chain_id.clone(),
             account_address: config.starknet.account_address.clone(),
             private_key: config.starknet.private_key.clone(),
+            signer: config.starknet.signer.clone(),
             max_retries: config.starknet.max_retries.unwrap_or(5),
             retry_delay_ms: config.starknet.retry_delay_ms.unwrap_or(5000),
             transaction_timeout_ms: config.starknet.transaction_timeout_ms.unwrap_or(300000),
@@ -99,8 +103,7 @@ pub struct ProofJob {
 /// Main struct for handling proof submission to Starknet
 pub struct ProofSubmissionRelayer {
     db_pool: Pool<Postgres>,
-    config: ProofSubmissionConfig,
-    account: SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>,
+    verifier: IntegrityVerifier,
 }
 
 impl ProofSubmissionRelayer {
@@ -109,20 +112,9 @@ impl ProofSubmissionRelayer {
         db_pool: Pool<Postgres>,
         config: ProofSubmissionConfig,
     ) -> Result<Self, ProofSubmissionError> {
-        let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(&config.rpc_url).unwrap()));
-        let signer: LocalWallet = LocalWallet::from(SigningKey::from_secret_scalar(
-            Felt::from_hex(&config.private_key).unwrap(),
-        ));
-        let chain_id = MAINNET;
-        let address = Felt::from_hex(&config.account_address).unwrap();
-        let account =
-            SingleOwnerAccount::new(provider, signer, address, chain_id, ExecutionEncoding::New);
-
-        Ok(Self {
-            db_pool,
-            config,
-            account,
-        })
+        let verifier = IntegrityVerifier::new(config).await?;
+
+        Ok(Self { db_pool, verifier })
     }
 
     /// Main entry point for submitting proofs from a calldata directory
@@ -242,14 +234,14 @@ impl ProofSubmissionRelayer {
             ));
         }
 
-        let initial_calldata = self.read_calldata_file(&initial_file)?;
+        let initial_calldata = read_calldata_file(&initial_file)?;
 
         // Build calldata for verify_proof_initial
         let mut calldata = vec![Felt::from(proof_job.job_id as u64)];
-        calldata.push(self.string_to_felt(&proof_job.layout));
-        calldata.push(self.string_to_felt(&proof_job.hasher));
-        calldata.push(self.string_to_felt(&proof_job.stone_version));
-        calldata.push(self.string_to_felt(&proof_job.memory_verification));
+        calldata.push(string_to_felt(&proof_job.layout)?);
+        calldata.push(string_to_felt(&proof_job.hasher)?);
+        calldata.push(string_to_felt(&proof_job.stone_version)?);
+        calldata.push(string_to_felt(&proof_job.memory_verification)?);
         calldata.extend(initial_calldata);
 
         let tx_hash = self
@@ -302,7 +294,7 @@ impl ProofSubmissionRelayer {
                 step_num, proof_job.job_id
             );
 
-            let step_calldata = self.read_calldata_file(&step_file)?;
+            let step_calldata = read_calldata_file(&step_file)?;
 
             // Build calldata for verify_proof_step
             let mut calldata = vec![Felt::from(proof_job.job_id as u64)];
@@ -348,7 +340,7 @@ impl ProofSubmissionRelayer {
             ));
         }
 
-        let final_calldata = self.read_calldata_file(&final_file)?;
+        let final_calldata = read_calldata_file(&final_file)?;
 
         // Build calldata for verify_proof_final_and_register_fact
         let mut calldata = vec![Felt::from(proof_job.job_id as u64)];
@@ -374,16 +366,13 @@ impl ProofSubmissionRelayer {
         Ok(())
     }
 
-    /// Submit a contract call with retry logic
+    /// Submit a contract call to the verifier, retrying until it is accepted
     async fn submit_contract_call(
         &self,
         function_name: &str,
         calldata: Vec<Felt>,
         proof_job: &ProofJob,
     ) -> Result<Felt, ProofSubmissionError> {
-        let contract_address = Felt::from_hex(&self.config.contract_address)
-            .map_err(|_| ProofSubmissionError::InvalidContractAddress)?;
-
         let selector = match function_name {
             "verify_proof_initial" => starknet::macros::selector!("verify_proof_initial"),
             "verify_proof_step" => starknet::macros::selector!("verify_proof_step"),
@@ -399,168 +388,22 @@ impl ProofSubmissionRelayer {
         };
 
         let call = Call {
-            to: contract_address,
+            to: self.verifier.contract_address(),
             selector,
             calldata,
         };
 
-        let mut attempts = 0;
-        let max_retries = self.config.max_retries;
-
-        loop {
-            attempts += 1;
-
-            info!(
-                "Submitting {} (attempt {}/{}) for job_id: {}",
-                function_name, attempts, max_retries, proof_job.job_id
-            );
-
-            match self.account.execute_v3(vec![call.clone()]).send().await {
-                Ok(result) => {
-                    info!(
-                        "Transaction submitted successfully: {} for job_id: {}, tx_hash: {}",
-                        function_name, proof_job.job_id, result.transaction_hash
-                    );
-
-                    // Wait for confirmation
-                    match self
-                        .wait_for_transaction_confirmation(result.transaction_hash)
-                        .await
-                    {
-                        Ok(_) => {
-                            info!(
-                                "Transaction confirmed: {} for job_id: {}, tx_hash: {}",
-                                function_name, proof_job.job_id, result.transaction_hash
-                            );
-                            return Ok(result.transaction_hash);
-                        }
-                        Err(e) => {
-                            error!(
-                                "Transaction confirmation failed: {} for job_id: {}, tx_hash: {}, error: {:?}",
-                                function_name, proof_job.job_id, result.transaction_hash, e
-                            );
-
-                            if attempts >= max_retries {
-                                return Err(e);
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    error!(
-                        "Transaction submission failed: {} for job_id: {} (attempt {}/{}), error: {:?}",
-                        function_name, proof_job.job_id, attempts, max_retries, e
-                    );
-
-                    if attempts >= max_retries {
-                        return Err(ProofSubmissionError::TransactionFailed(format!(
-                            "Failed after {} attempts: {}",
-                            max_retries, e
-                        )));
-                    }
-                }
-            }
-
-            // Exponential backoff
-            let delay = Duration::from_millis(self.config.retry_delay_ms * attempts as u64);
-            warn!(
-                "Retrying {} for job_id: {} in {:?}",
-                function_name, proof_job.job_id, delay
-            );
-            sleep(delay).await;
-        }
-    }
-
-    /// Wait for transaction confirmation
-    async fn wait_for_transaction_confirmation(
-        &self,
-        tx_hash: Felt,
-    ) -> Result<(), ProofSubmissionError> {
-        let timeout = Duration::from_millis(self.config.transaction_timeout_ms);
-        let start_time = std::time::Instant::now();
-
-        loop {
-            if start_time.elapsed() > timeout {
-                return Err(ProofSubmissionError::TransactionTimeout);
-            }
-
-            match self
-                .account
-                .provider()
-                .get_transaction_receipt(tx_hash)
-                .await
-            {
-                Ok(receipt) => {
-                    match receipt.receipt {
-                        TransactionReceipt::Invoke(receipt) => match receipt.execution_result {
-                            ExecutionResult::Succeeded => return Ok(()),
-                            ExecutionResult::Reverted { reason } => {
-                                return Err(ProofSubmissionError::TransactionFailed(
-                                    reason.to_string(),
-                                ));
-                            }
-                        },
-                        _ => {
-                            // Other receipt types, keep polling
-                        }
-                    }
-                }
-                Err(ProviderError::StarknetError(
-                    starknet::core::types::StarknetError::TransactionHashNotFound,
-                )) => {
-                    // Transaction not found yet, keep polling
-                }
-                Err(e) => return Err(ProofSubmissionError::Provider(e)),
-            }
-
-            sleep(Duration::from_secs(2)).await;
-        }
-    }
-
-    /// Read calldata from a file and parse it
-    fn read_calldata_file(&self, file_path: &Path) -> Result<Vec<Felt>, ProofSubmissionError> {
-        let content = fs::read_to_string(file_path)?;
-        let mut calldata = Vec::new();
-
-        for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-
-            // Split by whitespace and parse each hex value
-            for hex_str in line.split_whitespace() {
-                let felt = Felt::from_hex(hex_str).map_err(|e| {
-                    ProofSubmissionError::InvalidCalldataFormat(format!(
-                        "Invalid hex value '{}' in file {:?}: {}",
-                        hex_str, file_path, e
-                    ))
-                })?;
-                calldata.push(felt);
-            }
-        }
-
-        debug!(
-            "Read {} calldata elements from {:?}",
-            calldata.len(),
-            file_path
+        info!(
+            "Submitting {} for job_id: {}",
+            function_name, proof_job.job_id
         );
-        Ok(calldata)
-    }
 
-    /// Convert string to Felt (hex encoding)
-    fn string_to_felt(&self, input: &str) -> Felt {
-        let hex_string = self.string_to_hex(input);
-        Felt::from_hex(&hex_string).unwrap()
-    }
-
-    /// Convert string to hex representation
-    fn string_to_hex(&self, input: &str) -> String {
-        let mut hex_string = String::from("0x");
-        for byte in input.bytes() {
-            hex_string.push_str(&format!("{:02x}", byte));
-        }
-        hex_string
+        self.verifier.invoke(call).await.inspect_err(|e| {
+            error!(
+                "Transaction failed: {} for job_id: {}, error: {:?}",
+                function_name, proof_job.job_id, e
+            )
+        })
     }
 
     /// Create or get existing proof job from database
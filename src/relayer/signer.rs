@@ -0,0 +1,296 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::crypto::Signature;
+use starknet::core::types::Felt;
+use starknet::signers::{Signer, SignerInteractivityContext, SigningKey, VerifyingKey};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+use crate::config::{SignerConfig, SignerKind};
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    // The key itself is never echoed back into logs
+    #[error("Invalid private key: {0}")]
+    InvalidPrivateKey(String),
+
+    #[error("starknet.signer.{0} must be set for the {1} signer")]
+    MissingSetting(&'static str, &'static str),
+
+    #[error("Signing failed: {0}")]
+    Signing(String),
+
+    #[error("Signer I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("External signer returned error {code}: {message}")]
+    External { code: i64, message: String },
+
+    #[error("Invalid external signer response: {0}")]
+    InvalidResponse(String),
+
+    #[error("KMS error: {0}")]
+    Kms(String),
+
+    #[error("The kms signer needs the sequencer built with the `kms` feature")]
+    KmsDisabled,
+}
+
+/// Signs the transactions of a Starknet account.
+///
+/// Implemented by [`LocalSigner`] for a key held in memory and [`ExternalSigner`] for a
+/// signer daemon holding the key, so the relayer never needs to see it.
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    /// Signs `hash`, e.g. a transaction hash, with the account's Stark key
+    async fn sign_hash(&self, hash: Felt) -> Result<Signature, SignerError>;
+
+    /// Public key the account verifies signatures against
+    async fn public_key(&self) -> Result<Felt, SignerError>;
+
+    /// Address of the account the signer signs for
+    fn address(&self) -> Felt;
+}
+
+/// Builds the signer selected by `starknet.signer` for the account at `address`.
+/// `private_key` is only used by the local signer.
+pub async fn signer_from_config(
+    config: &SignerConfig,
+    private_key: &str,
+    address: Felt,
+) -> Result<Arc<dyn TransactionSigner>, SignerError> {
+    match config.kind {
+        SignerKind::Local => Ok(Arc::new(LocalSigner::from_hex(private_key, address)?)),
+        SignerKind::External => {
+            let socket_path = config
+                .socket_path
+                .clone()
+                .ok_or(SignerError::MissingSetting("socket_path", "external"))?;
+            Ok(Arc::new(ExternalSigner::new(socket_path, address)))
+        }
+        SignerKind::Kms => {
+            let key_id = config
+                .kms_key_id
+                .as_deref()
+                .ok_or(SignerError::MissingSetting("kms_key_id", "kms"))?;
+            let ciphertext_path = config
+                .kms_ciphertext_path
+                .as_deref()
+                .ok_or(SignerError::MissingSetting("kms_ciphertext_path", "kms"))?;
+            Ok(Arc::new(
+                decrypt_kms_key(key_id, ciphertext_path, address).await?,
+            ))
+        }
+    }
+}
+
+/// Signs with a Stark key held in memory
+pub struct LocalSigner {
+    key: SigningKey,
+    address: Felt,
+}
+
+impl LocalSigner {
+    pub fn new(key: SigningKey, address: Felt) -> Self {
+        Self { key, address }
+    }
+
+    /// Parses a `0x`-prefixed hex private key, rejecting zero
+    pub fn from_hex(private_key: &str, address: Felt) -> Result<Self, SignerError> {
+        let scalar = Felt::from_hex(private_key)
+            .map_err(|e| SignerError::InvalidPrivateKey(e.to_string()))?;
+        if scalar == Felt::ZERO {
+            return Err(SignerError::InvalidPrivateKey(
+                "private key must not be zero".to_string(),
+            ));
+        }
+        Ok(Self::new(SigningKey::from_secret_scalar(scalar), address))
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for LocalSigner {
+    async fn sign_hash(&self, hash: Felt) -> Result<Signature, SignerError> {
+        self.key
+            .sign(&hash)
+            .map_err(|e| SignerError::Signing(e.to_string()))
+    }
+
+    async fn public_key(&self) -> Result<Felt, SignerError> {
+        Ok(self.key.verifying_key().scalar())
+    }
+
+    fn address(&self) -> Felt {
+        self.address
+    }
+}
+
+/// Signs through a daemon listening on a unix socket.
+///
+/// Every request is a JSON-RPC 2.0 call on its own connection, written as one line and
+/// answered with one line:
+///
+/// - `starknet_signHash` with `{"address", "hash"}` returns `{"r", "s"}`
+/// - `starknet_getPublicKey` with `{"address"}` returns the public key
+///
+/// All values are `0x`-prefixed hex felts.
+pub struct ExternalSigner {
+    socket_path: PathBuf,
+    address: Felt,
+    next_id: AtomicU64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignatureResult {
+    r: Felt,
+    s: Felt,
+}
+
+impl ExternalSigner {
+    pub fn new(socket_path: impl Into<PathBuf>, address: Felt) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+            address,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    async fn call<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, SignerError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+
+        let mut stream = UnixStream::connect(&self.socket_path).await?;
+        stream
+            .write_all(format!("{}\n", request).as_bytes())
+            .await?;
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).await?;
+
+        let response: RpcResponse<T> =
+            serde_json::from_str(&line).map_err(|e| SignerError::InvalidResponse(e.to_string()))?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(SignerError::External {
+                code: error.code,
+                message: error.message,
+            }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(SignerError::InvalidResponse(
+                "neither result nor error".to_string(),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for ExternalSigner {
+    async fn sign_hash(&self, hash: Felt) -> Result<Signature, SignerError> {
+        let signature: SignatureResult = self
+            .call(
+                "starknet_signHash",
+                json!({ "address": self.address, "hash": hash }),
+            )
+            .await?;
+        Ok(Signature {
+            r: signature.r,
+            s: signature.s,
+        })
+    }
+
+    async fn public_key(&self) -> Result<Felt, SignerError> {
+        self.call("starknet_getPublicKey", json!({ "address": self.address }))
+            .await
+    }
+
+    fn address(&self) -> Felt {
+        self.address
+    }
+}
+
+/// Decrypts the private key in `ciphertext_path` with the KMS key `key_id` into a
+/// [`LocalSigner`].
+///
+/// KMS offers no keys on the Stark curve, so it cannot sign for a Starknet account
+/// itself; the key is kept encrypted at rest instead and only decrypted into memory.
+/// The ciphertext is the raw `CiphertextBlob` of `aws kms encrypt` over the hex key.
+/// Credentials and region come from the AWS environment.
+#[cfg(feature = "kms")]
+async fn decrypt_kms_key(
+    key_id: &str,
+    ciphertext_path: &Path,
+    address: Felt,
+) -> Result<LocalSigner, SignerError> {
+    use aws_sdk_kms::{error::DisplayErrorContext, primitives::Blob, Client};
+
+    let ciphertext = tokio::fs::read(ciphertext_path).await?;
+    let aws_config = aws_config::load_from_env().await;
+    let output = Client::new(&aws_config)
+        .decrypt()
+        .key_id(key_id)
+        .ciphertext_blob(Blob::new(ciphertext))
+        .send()
+        .await
+        .map_err(|e| SignerError::Kms(DisplayErrorContext(&e).to_string()))?;
+
+    let plaintext = output
+        .plaintext()
+        .ok_or_else(|| SignerError::Kms("decryption returned no plaintext".to_string()))?;
+    let private_key = std::str::from_utf8(plaintext.as_ref())
+        .map_err(|_| SignerError::InvalidPrivateKey("not UTF-8 hex".to_string()))?;
+    LocalSigner::from_hex(private_key.trim(), address)
+}
+
+#[cfg(not(feature = "kms"))]
+async fn decrypt_kms_key(
+    _key_id: &str,
+    _ciphertext_path: &Path,
+    _address: Felt,
+) -> Result<LocalSigner, SignerError> {
+    Err(SignerError::KmsDisabled)
+}
+
+/// Lets a [`TransactionSigner`] sign for starknet-rs accounts
+#[derive(Clone)]
+pub struct AccountSigner(pub Arc<dyn TransactionSigner>);
+
+#[async_trait]
+impl Signer for AccountSigner {
+    type GetPublicKeyError = SignerError;
+    type SignError = SignerError;
+
+    async fn get_public_key(&self) -> Result<VerifyingKey, Self::GetPublicKeyError> {
+        Ok(VerifyingKey::from_scalar(self.0.public_key().await?))
+    }
+
+    async fn sign_hash(&self, hash: &Felt) -> Result<Signature, Self::SignError> {
+        self.0.sign_hash(*hash).await
+    }
+
+    fn is_interactive(&self, _context: SignerInteractivityContext<'_>) -> bool {
+        false
+    }
+}
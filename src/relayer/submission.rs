@@ -0,0 +1,795 @@
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use sqlx::PgPool;
+use starknet::accounts::{Account, AccountError, ConnectedAccount, SingleOwnerAccount};
+use starknet::core::types::{
+    Call, ExecutionResult, FeeEstimate, Felt, InvokeTransactionReceipt, StarknetError,
+    TransactionReceipt,
+};
+use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet::providers::{Provider, ProviderError};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::config::StarknetNonceConfig;
+use crate::db::database::{
+    fetch_pending_submitted_tx_hashes, insert_submitted_tx_hash, resolve_submitted_tx_hash,
+    resolve_submitted_tx_hashes, SUBMITTED_TX_FAILED,
+};
+use crate::relayer::nonce_manager::{NonceManager, NonceSource};
+use crate::relayer::signer::AccountSigner;
+use crate::relayer::starknet_relayer::StarknetRelayerError;
+
+/// Gas price multiplier starknet-rs applies to estimates unless told otherwise
+pub const BASE_GAS_PRICE_MULTIPLIER: f64 = 1.5;
+
+/// Gas amount multiplier starknet-rs applies to estimates unless told otherwise
+const GAS_ESTIMATE_MULTIPLIER: f64 = 1.5;
+
+/// Invoke transaction signed for a nonce, whose hash is known before it is broadcast
+pub struct PreparedInvoke<'a> {
+    pub tx_hash: Felt,
+    broadcast: BoxFuture<'a, Result<(), StarknetRelayerError>>,
+}
+
+impl<'a> PreparedInvoke<'a> {
+    pub fn new(
+        tx_hash: Felt,
+        broadcast: impl Future<Output = Result<(), StarknetRelayerError>> + Send + 'a,
+    ) -> Self {
+        Self {
+            tx_hash,
+            broadcast: Box::pin(broadcast),
+        }
+    }
+
+    /// Sends the transaction to the node
+    pub async fn broadcast(self) -> Result<(), StarknetRelayerError> {
+        self.broadcast.await
+    }
+}
+
+/// Account submitting invoke transactions and looking up their receipts
+#[async_trait]
+pub trait InvokeSubmitter: NonceSource {
+    fn account_address(&self) -> Felt;
+
+    /// Prepares `calls` with `nonce`, scaling the estimated gas price by
+    /// `gas_price_multiplier`, without broadcasting them
+    async fn prepare<'a>(
+        &'a self,
+        calls: &[Call],
+        nonce: Felt,
+        gas_price_multiplier: f64,
+    ) -> Result<PreparedInvoke<'a>, StarknetRelayerError>;
+
+    /// Receipt of a succeeded transaction, `None` while the node does not know it yet
+    async fn receipt(
+        &self,
+        tx_hash: Felt,
+    ) -> Result<Option<InvokeTransactionReceipt>, StarknetRelayerError>;
+}
+
+type RelayerAccount = SingleOwnerAccount<JsonRpcClient<HttpTransport>, AccountSigner>;
+
+#[async_trait]
+impl NonceSource for RelayerAccount {
+    async fn fetch_nonce(&self, account_address: Felt) -> Result<Felt, ProviderError> {
+        self.provider().fetch_nonce(account_address).await
+    }
+}
+
+#[async_trait]
+impl InvokeSubmitter for RelayerAccount {
+    fn account_address(&self) -> Felt {
+        self.address()
+    }
+
+    async fn prepare<'a>(
+        &'a self,
+        calls: &[Call],
+        nonce: Felt,
+        gas_price_multiplier: f64,
+    ) -> Result<PreparedInvoke<'a>, StarknetRelayerError> {
+        let execution = self.execute_v3(calls.to_vec()).nonce(nonce);
+        // Transactions are only prepared with their gas settings given, estimated here as
+        // starknet-rs does when sending
+        let estimate = execution.estimate_fee().await.map_err(account_error)?;
+        let (gas, gas_price) = gas_settings(&estimate, gas_price_multiplier)?;
+        let prepared = execution
+            .gas(gas)
+            .gas_price(gas_price)
+            .prepared()
+            .map_err(|_| {
+                StarknetRelayerError::TransactionFailed("Transaction not prepared".to_string())
+            })?;
+        let tx_hash = prepared.transaction_hash(false);
+
+        Ok(PreparedInvoke::new(tx_hash, async move {
+            prepared.send().await.map(|_| ()).map_err(account_error)
+        }))
+    }
+
+    async fn receipt(
+        &self,
+        tx_hash: Felt,
+    ) -> Result<Option<InvokeTransactionReceipt>, StarknetRelayerError> {
+        match self.provider().get_transaction_receipt(tx_hash).await {
+            Ok(receipt) => match receipt.receipt {
+                TransactionReceipt::Invoke(receipt) => match receipt.execution_result {
+                    ExecutionResult::Succeeded => Ok(Some(receipt)),
+                    ExecutionResult::Reverted { reason } => {
+                        Err(StarknetRelayerError::TransactionFailed(reason))
+                    }
+                },
+                // Other receipt types — keep polling
+                _ => Ok(None),
+            },
+            Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => Ok(None),
+            Err(e) => Err(StarknetRelayerError::Provider(e)),
+        }
+    }
+}
+
+/// Gas amount and price of a transaction estimated at `estimate`, scaling the price by
+/// `gas_price_multiplier`
+fn gas_settings(
+    estimate: &FeeEstimate,
+    gas_price_multiplier: f64,
+) -> Result<(u64, u128), StarknetRelayerError> {
+    let out_of_range = || {
+        StarknetRelayerError::TransactionFailed(format!(
+            "Fee estimate out of range: {} at gas price {}",
+            estimate.overall_fee, estimate.gas_price
+        ))
+    };
+    let overall_fee = u64::try_from(estimate.overall_fee).map_err(|_| out_of_range())?;
+    let gas_price = u64::try_from(estimate.gas_price)
+        .map_err(|_| out_of_range())?
+        .max(1);
+
+    let gas = (overall_fee.div_ceil(gas_price) as f64 * GAS_ESTIMATE_MULTIPLIER) as u64;
+    let gas_price = (gas_price as f64 * gas_price_multiplier) as u128;
+    Ok((gas, gas_price))
+}
+
+fn account_error<S>(error: AccountError<S>) -> StarknetRelayerError
+where
+    AccountError<S>: std::fmt::Display,
+{
+    match error {
+        AccountError::Provider(e) => StarknetRelayerError::Provider(e),
+        e => StarknetRelayerError::TransactionFailed(format!("Failed to send transaction: {}", e)),
+    }
+}
+
+/// Calls sent in one invoke transaction, with the nonce they hold
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub calls: Vec<Call>,
+    pub nonce: Felt,
+    pub tx_hash: Felt,
+}
+
+/// Where the transactions sent for some L2 transactions are recorded
+#[derive(Debug, Clone, Copy)]
+pub struct SubmissionRecord<'a> {
+    pub pool: &'a PgPool,
+    pub l2_transaction_ids: &'a [i64],
+}
+
+/// Whether the node rejected a transaction for its nonce
+pub fn is_invalid_nonce(error: &StarknetRelayerError) -> bool {
+    matches!(
+        error,
+        StarknetRelayerError::Provider(ProviderError::StarknetError(
+            StarknetError::InvalidTransactionNonce
+        ))
+    )
+}
+
+/// Whether a failed broadcast may still have reached the node, e.g. when the connection
+/// dropped before its answer came back. The node answered any other failure, turning
+/// the transaction down.
+fn may_have_been_sent(error: &StarknetRelayerError) -> bool {
+    matches!(
+        error,
+        StarknetRelayerError::Provider(ProviderError::Other(_))
+    )
+}
+
+/// Prepares `calls` with `nonce`, records the resulting hash in `record`, and only then
+/// broadcasts them, so a relayer stopping at any point knows what it may have sent.
+///
+/// A transaction the node turned down has its record marked failed. One that may have
+/// reached it despite the error stays recorded, and is reported as
+/// [`StarknetRelayerError::Unconfirmed`].
+async fn send_prepared<S: InvokeSubmitter + ?Sized>(
+    submitter: &S,
+    calls: &[Call],
+    nonce: Felt,
+    gas_price_multiplier: f64,
+    record: Option<SubmissionRecord<'_>>,
+) -> Result<Felt, StarknetRelayerError> {
+    let prepared = submitter
+        .prepare(calls, nonce, gas_price_multiplier)
+        .await?;
+    let tx_hash = prepared.tx_hash;
+    let recorded_hash = format!("{:#x}", tx_hash);
+
+    if let Some(record) = record {
+        insert_submitted_tx_hash(
+            record.pool,
+            record.l2_transaction_ids,
+            &recorded_hash,
+            &format!("{:#x}", nonce),
+        )
+        .await?;
+    }
+
+    match prepared.broadcast().await {
+        Ok(()) => Ok(tx_hash),
+        Err(e) if may_have_been_sent(&e) => {
+            warn!(
+                "Broadcast of transaction {} failed, it may still have been sent: {:?}",
+                recorded_hash, e
+            );
+            Err(StarknetRelayerError::Unconfirmed(tx_hash))
+        }
+        Err(e) => {
+            if let Some(record) = record {
+                if let Err(db_error) =
+                    resolve_submitted_tx_hash(record.pool, &recorded_hash, SUBMITTED_TX_FAILED)
+                        .await
+                {
+                    warn!(
+                        "Failed to mark rejected transaction {} failed: {:?}",
+                        recorded_hash, db_error
+                    );
+                }
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Sends `calls` in a new transaction.
+///
+/// With a [`NonceManager`] the nonce is handed out locally, and re-synced from the node
+/// and retried once if the node rejects it. Without one, the account's nonce in the
+/// pending block is read for this transaction alone.
+pub async fn submit<S: InvokeSubmitter + ?Sized>(
+    submitter: &S,
+    nonces: Option<&NonceManager>,
+    calls: Vec<Call>,
+) -> Result<Submission, StarknetRelayerError> {
+    submit_with_record(submitter, nonces, calls, None).await
+}
+
+/// Sends `calls` relaying `l2_transaction_ids` like [`submit`], recording the hash in
+/// `submitted_tx_hashes` before the transaction is broadcast.
+///
+/// The hash is computed from the signed transaction, so the record exists before
+/// anything leaves the relayer: if it stops before completing the L2 transactions, it
+/// waits for that transaction on restart instead of sending them again. Nothing is sent
+/// if the record cannot be written.
+pub async fn submit_recorded<S: InvokeSubmitter + ?Sized>(
+    pool: &PgPool,
+    submitter: &S,
+    nonces: Option<&NonceManager>,
+    l2_transaction_ids: &[i64],
+    calls: Vec<Call>,
+) -> Result<Submission, StarknetRelayerError> {
+    let record = SubmissionRecord {
+        pool,
+        l2_transaction_ids,
+    };
+    submit_with_record(submitter, nonces, calls, Some(record)).await
+}
+
+async fn submit_with_record<S: InvokeSubmitter + ?Sized>(
+    submitter: &S,
+    nonces: Option<&NonceManager>,
+    calls: Vec<Call>,
+    record: Option<SubmissionRecord<'_>>,
+) -> Result<Submission, StarknetRelayerError> {
+    let Some(nonces) = nonces else {
+        let nonce = submitter.fetch_nonce(submitter.account_address()).await?;
+        let tx_hash =
+            send_prepared(submitter, &calls, nonce, BASE_GAS_PRICE_MULTIPLIER, record).await?;
+        return Ok(Submission {
+            calls,
+            nonce,
+            tx_hash,
+        });
+    };
+
+    let mut resynced = false;
+    loop {
+        let nonce = Felt::from(nonces.next());
+        match send_prepared(submitter, &calls, nonce, BASE_GAS_PRICE_MULTIPLIER, record).await {
+            Ok(tx_hash) => {
+                return Ok(Submission {
+                    calls,
+                    nonce,
+                    tx_hash,
+                })
+            }
+            Err(e) if is_invalid_nonce(&e) && !resynced => {
+                warn!("Nonce {} rejected, re-syncing from the provider", nonce);
+                nonces.sync(submitter).await?;
+                resynced = true;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// What became of the transactions an interrupted run sent for an L2 transaction
+#[derive(Debug)]
+pub enum Resumed {
+    /// Nothing is awaiting confirmation: nothing was sent, or what was sent reverted or
+    /// lost its nonce to another transaction. The L2 transaction can be sent again.
+    NotSent,
+    /// One of the sent transactions was accepted, with its hash and receipt
+    Confirmed(Felt, Box<InvokeTransactionReceipt>),
+    /// The latest sent transaction was not accepted in time but can still be included,
+    /// so it is waited for again later
+    Pending(Felt),
+}
+
+/// Waits for the transactions an interrupted run sent for `l2_transaction_id`, if any.
+///
+/// An accepted transaction leaves the records pending until the caller has completed
+/// the L2 transaction. One that reverted has its records marked failed. When none is
+/// accepted within `timeout`, the account's nonce is checked: the L2 transaction is
+/// only sent again once another transaction took the nonce they were signed with,
+/// since until then any of them can still be included.
+pub async fn resume_submission<S: InvokeSubmitter + ?Sized>(
+    pool: &PgPool,
+    submitter: &S,
+    l2_transaction_id: i64,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<Resumed, StarknetRelayerError> {
+    let submitted = fetch_pending_submitted_tx_hashes(pool, l2_transaction_id).await?;
+    let Some(latest) = submitted.last() else {
+        return Ok(Resumed::NotSent);
+    };
+    let hashes = submitted
+        .iter()
+        .map(|submitted| Felt::from_hex(&submitted.tx_hash))
+        .collect::<Result<Vec<_>, _>>()?;
+    let latest_hash = Felt::from_hex(&latest.tx_hash)?;
+    info!(
+        "Transaction {} was sent as {} before the relayer stopped, waiting for it",
+        l2_transaction_id, latest.tx_hash
+    );
+
+    match wait_for_any_receipt(submitter, &hashes, timeout, poll_interval).await {
+        Ok((tx_hash, receipt)) => return Ok(Resumed::Confirmed(tx_hash, Box::new(receipt))),
+        Err(e) if e.is_rejected() => {
+            warn!(
+                "Transaction {} sent for {} reverted, sending it again: {:?}",
+                latest.tx_hash, l2_transaction_id, e
+            );
+            resolve_submitted_tx_hashes(pool, &[l2_transaction_id], SUBMITTED_TX_FAILED).await?;
+            return Ok(Resumed::NotSent);
+        }
+        Err(e) => warn!(
+            "Transaction {} sent for {} not accepted yet: {:?}",
+            latest.tx_hash, l2_transaction_id, e
+        ),
+    }
+
+    // Records predating the nonce column are waited for until resolved by hand
+    let Some(nonce) = latest.nonce.as_deref() else {
+        return Ok(Resumed::Pending(latest_hash));
+    };
+    let nonce = Felt::from_hex(nonce)?;
+    let account_nonce = submitter.fetch_nonce(submitter.account_address()).await?;
+    if account_nonce <= nonce {
+        return Ok(Resumed::Pending(latest_hash));
+    }
+
+    // The nonce is used: by one of the sent transactions, or by another one that
+    // leaves none of them includable
+    for &tx_hash in &hashes {
+        match submitter.receipt(tx_hash).await {
+            Ok(Some(receipt)) => return Ok(Resumed::Confirmed(tx_hash, Box::new(receipt))),
+            Ok(None) => {}
+            Err(e) if e.is_rejected() => break,
+            Err(e) => return Err(e),
+        }
+    }
+    warn!(
+        "Nonce {:#x} of transaction {} was used by another transaction, sending {} again",
+        nonce, latest.tx_hash, l2_transaction_id
+    );
+    resolve_submitted_tx_hashes(pool, &[l2_transaction_id], SUBMITTED_TX_FAILED).await?;
+    Ok(Resumed::NotSent)
+}
+
+/// Polls the receipt of `tx_hash` every `poll_interval` until it is accepted, failing
+/// once `timeout` has passed
+pub async fn wait_for_receipt<S: InvokeSubmitter + ?Sized>(
+    submitter: &S,
+    tx_hash: Felt,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<InvokeTransactionReceipt, StarknetRelayerError> {
+    wait_for_any_receipt(submitter, &[tx_hash], timeout, poll_interval)
+        .await
+        .map(|(_, receipt)| receipt)
+}
+
+/// Polls the receipts of `tx_hashes`, transactions sharing a nonce, every
+/// `poll_interval` until one is accepted, failing once `timeout` has passed
+async fn wait_for_any_receipt<S: InvokeSubmitter + ?Sized>(
+    submitter: &S,
+    tx_hashes: &[Felt],
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<(Felt, InvokeTransactionReceipt), StarknetRelayerError> {
+    let start_time = Instant::now();
+
+    loop {
+        if start_time.elapsed() > timeout {
+            return Err(StarknetRelayerError::TimeoutError(
+                "Transaction confirmation timed out.".to_string(),
+            ));
+        }
+
+        for &tx_hash in tx_hashes {
+            if let Some(receipt) = submitter.receipt(tx_hash).await? {
+                return Ok((tx_hash, receipt));
+            }
+        }
+
+        sleep(poll_interval).await;
+    }
+}
+
+/// Resubmission of transactions the mempool holds on to without including them
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplacementPolicy {
+    /// Time without acceptance after which the last transaction is replaced
+    pub stuck_after: Duration,
+    pub max_replacements: u32,
+    pub fee_bump_percent: u32,
+    /// Time between two receipt lookups
+    pub poll_interval: Duration,
+}
+
+impl ReplacementPolicy {
+    /// The policy of `config`, `None` when replacement is disabled
+    pub fn from_config(config: &StarknetNonceConfig) -> Option<Self> {
+        config.replace_after_ms.map(|replace_after_ms| Self {
+            stuck_after: Duration::from_millis(replace_after_ms),
+            max_replacements: config.max_replacements,
+            fee_bump_percent: config.fee_bump_percent,
+            poll_interval: Duration::from_secs(2),
+        })
+    }
+
+    /// Gas price multiplier of the `replacement`th resubmission, compounding the bump
+    pub fn gas_price_multiplier(&self, replacement: u32) -> f64 {
+        let bump = 1.0 + f64::from(self.fee_bump_percent) / 100.0;
+        BASE_GAS_PRICE_MULTIPLIER * bump.powi(replacement as i32)
+    }
+}
+
+/// Waits for `submission` to be accepted, resubmitting its calls with the same nonce
+/// and a bumped fee whenever no transaction sent so far is accepted within
+/// `policy.stuck_after`.
+///
+/// Only one transaction per nonce can be included, so whichever is accepted first is
+/// returned with its receipt. Replacements are recorded in `record` like the original,
+/// so a restarted relayer waits for all of them. Fails with
+/// [`StarknetRelayerError::TransactionStuck`] once the last replacement is stuck too.
+pub async fn confirm_or_replace<S: InvokeSubmitter + ?Sized>(
+    submitter: &S,
+    submission: &Submission,
+    policy: &ReplacementPolicy,
+    record: Option<SubmissionRecord<'_>>,
+) -> Result<(Felt, InvokeTransactionReceipt), StarknetRelayerError> {
+    let mut hashes = vec![submission.tx_hash];
+    let mut replacements = 0;
+    let mut sent_at = Instant::now();
+
+    loop {
+        for &tx_hash in &hashes {
+            if let Some(receipt) = submitter.receipt(tx_hash).await? {
+                return Ok((tx_hash, receipt));
+            }
+        }
+
+        if sent_at.elapsed() >= policy.stuck_after {
+            if replacements >= policy.max_replacements {
+                return Err(StarknetRelayerError::TransactionStuck {
+                    nonce: submission.nonce,
+                    replacements,
+                });
+            }
+            replacements += 1;
+
+            let multiplier = policy.gas_price_multiplier(replacements);
+            match send_prepared(
+                submitter,
+                &submission.calls,
+                submission.nonce,
+                multiplier,
+                record,
+            )
+            .await
+            {
+                // A replacement that may have reached the node is waited for as well
+                Ok(tx_hash) | Err(StarknetRelayerError::Unconfirmed(tx_hash)) => {
+                    info!(
+                        "Replaced stuck transaction {:#x} with {:#x} (nonce {}, gas price x{:.2})",
+                        hashes[hashes.len() - 1],
+                        tx_hash,
+                        submission.nonce,
+                        multiplier
+                    );
+                    hashes.push(tx_hash);
+                }
+                // The nonce was used up by one of the sent transactions, whose receipt
+                // shows up on a later poll
+                Err(e) => warn!(
+                    "Replacement {} of nonce {} was not sent: {}",
+                    replacements, submission.nonce, e
+                ),
+            }
+            sent_at = Instant::now();
+        }
+
+        sleep(policy.poll_interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+
+    /// Node accepting transactions from the `accepted_from`th sent one on, holding
+    /// every earlier one in its mempool forever
+    struct MockAccount {
+        nonce: AtomicU64,
+        accepted_from: Option<usize>,
+        sent: Mutex<Vec<(u64, f64)>>,
+    }
+
+    impl MockAccount {
+        fn new(nonce: u64, accepted_from: Option<usize>) -> Self {
+            Self {
+                nonce: AtomicU64::new(nonce),
+                accepted_from,
+                sent: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn sent(&self) -> Vec<(u64, f64)> {
+            self.sent.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl NonceSource for MockAccount {
+        async fn fetch_nonce(&self, _account_address: Felt) -> Result<Felt, ProviderError> {
+            Ok(Felt::from(self.nonce.load(Ordering::SeqCst)))
+        }
+    }
+
+    #[async_trait]
+    impl InvokeSubmitter for MockAccount {
+        fn account_address(&self) -> Felt {
+            Felt::ONE
+        }
+
+        async fn prepare<'a>(
+            &'a self,
+            _calls: &[Call],
+            nonce: Felt,
+            gas_price_multiplier: f64,
+        ) -> Result<PreparedInvoke<'a>, StarknetRelayerError> {
+            // Hashes are the 1-based position of the transaction
+            let tx_hash = Felt::from(self.sent().len() + 1);
+            let nonce = u64::try_from(nonce).unwrap();
+
+            Ok(PreparedInvoke::new(tx_hash, async move {
+                if nonce < self.nonce.load(Ordering::SeqCst) {
+                    return Err(StarknetRelayerError::Provider(
+                        ProviderError::StarknetError(StarknetError::InvalidTransactionNonce),
+                    ));
+                }
+                self.sent
+                    .lock()
+                    .unwrap()
+                    .push((nonce, gas_price_multiplier));
+                Ok(())
+            }))
+        }
+
+        async fn receipt(
+            &self,
+            tx_hash: Felt,
+        ) -> Result<Option<InvokeTransactionReceipt>, StarknetRelayerError> {
+            let position = u64::try_from(tx_hash).unwrap() as usize - 1;
+            Ok(self
+                .accepted_from
+                .filter(|accepted_from| position >= *accepted_from)
+                .map(|_| receipt(tx_hash)))
+        }
+    }
+
+    fn receipt(tx_hash: Felt) -> InvokeTransactionReceipt {
+        serde_json::from_value(serde_json::json!({
+            "type": "INVOKE",
+            "transaction_hash": format!("{:#x}", tx_hash),
+            "actual_fee": { "amount": "0x1", "unit": "FRI" },
+            "finality_status": "ACCEPTED_ON_L2",
+            "messages_sent": [],
+            "events": [],
+            "execution_resources": {
+                "steps": 1,
+                "data_availability": { "l1_gas": 0, "l1_data_gas": 0 }
+            },
+            "execution_status": "SUCCEEDED"
+        }))
+        .unwrap()
+    }
+
+    fn policy(max_replacements: u32) -> ReplacementPolicy {
+        ReplacementPolicy {
+            stuck_after: Duration::from_millis(20),
+            max_replacements,
+            fee_bump_percent: 25,
+            poll_interval: Duration::from_millis(5),
+        }
+    }
+
+    async fn submission(account: &MockAccount) -> Submission {
+        submit(account, None, Vec::new()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rejected_nonce_is_resynced_and_retried() {
+        let account = MockAccount::new(3, Some(0));
+        let nonces = NonceManager::new(&account, Felt::ONE).await.unwrap();
+        // Two transactions were sent with nonces the manager never handed out
+        account.nonce.store(5, Ordering::SeqCst);
+
+        let submission = submit(&account, Some(&nonces), Vec::new()).await.unwrap();
+
+        assert_eq!(submission.nonce, Felt::from(5u64));
+        assert_eq!(account.sent(), vec![(5, BASE_GAS_PRICE_MULTIPLIER)]);
+        assert_eq!(nonces.next(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_nonce_is_resynced_only_once() {
+        struct AlwaysTooLow(MockAccount);
+
+        #[async_trait]
+        impl NonceSource for AlwaysTooLow {
+            async fn fetch_nonce(&self, _account_address: Felt) -> Result<Felt, ProviderError> {
+                // The node keeps reporting a nonce it then rejects
+                Ok(Felt::ZERO)
+            }
+        }
+
+        #[async_trait]
+        impl InvokeSubmitter for AlwaysTooLow {
+            fn account_address(&self) -> Felt {
+                Felt::ONE
+            }
+
+            async fn prepare<'a>(
+                &'a self,
+                calls: &[Call],
+                nonce: Felt,
+                gas_price_multiplier: f64,
+            ) -> Result<PreparedInvoke<'a>, StarknetRelayerError> {
+                self.0.prepare(calls, nonce, gas_price_multiplier).await
+            }
+
+            async fn receipt(
+                &self,
+                tx_hash: Felt,
+            ) -> Result<Option<InvokeTransactionReceipt>, StarknetRelayerError> {
+                self.0.receipt(tx_hash).await
+            }
+        }
+
+        let account = AlwaysTooLow(MockAccount::new(1, None));
+        let nonces = NonceManager::new(&account, Felt::ONE).await.unwrap();
+
+        let result = submit(&account, Some(&nonces), Vec::new()).await;
+
+        assert!(matches!(result, Err(ref e) if is_invalid_nonce(e)));
+        assert!(account.0.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unmanaged_nonce_is_read_for_every_transaction() {
+        let account = MockAccount::new(9, Some(0));
+
+        assert_eq!(submission(&account).await.nonce, Felt::from(9u64));
+        account.nonce.store(10, Ordering::SeqCst);
+        assert_eq!(submission(&account).await.nonce, Felt::from(10u64));
+    }
+
+    #[tokio::test]
+    async fn test_stuck_transaction_is_replaced_with_same_nonce_and_higher_fee() {
+        let account = MockAccount::new(4, Some(2));
+        let submission = submission(&account).await;
+
+        let (tx_hash, receipt) = confirm_or_replace(&account, &submission, &policy(3), None)
+            .await
+            .unwrap();
+
+        assert_eq!(tx_hash, Felt::from(3u64));
+        assert_eq!(receipt.transaction_hash, tx_hash);
+        let sent = account.sent();
+        assert_eq!(sent.len(), 3);
+        assert!(sent.iter().all(|(nonce, _)| *nonce == 4));
+        assert!(sent.windows(2).all(|pair| pair[1].1 > pair[0].1));
+        assert_eq!(sent[2].1, policy(3).gas_price_multiplier(2));
+    }
+
+    #[tokio::test]
+    async fn test_accepted_transaction_is_not_replaced() {
+        let account = MockAccount::new(4, Some(0));
+        let submission = submission(&account).await;
+
+        let (tx_hash, _) = confirm_or_replace(&account, &submission, &policy(3), None)
+            .await
+            .unwrap();
+
+        assert_eq!(tx_hash, submission.tx_hash);
+        assert_eq!(account.sent().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_pending_forever_is_given_up_on() {
+        let account = MockAccount::new(4, None);
+        let submission = submission(&account).await;
+
+        let result = confirm_or_replace(&account, &submission, &policy(2), None).await;
+
+        assert!(matches!(
+            result,
+            Err(StarknetRelayerError::TransactionStuck {
+                replacements: 2,
+                ..
+            })
+        ));
+        // The original transaction and two replacements
+        assert_eq!(account.sent().len(), 3);
+    }
+
+    #[test]
+    fn test_gas_price_multiplier_compounds_the_bump() {
+        let policy = policy(3);
+
+        assert_eq!(policy.gas_price_multiplier(0), BASE_GAS_PRICE_MULTIPLIER);
+        assert!((policy.gas_price_multiplier(1) - 1.875).abs() < 1e-9);
+        assert!((policy.gas_price_multiplier(2) - 2.34375).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_replacement_is_disabled_without_a_delay() {
+        let mut config = StarknetNonceConfig::default();
+        assert_eq!(ReplacementPolicy::from_config(&config), None);
+
+        config.replace_after_ms = Some(90_000);
+        let policy = ReplacementPolicy::from_config(&config).unwrap();
+        assert_eq!(policy.stuck_after, Duration::from_secs(90));
+        assert_eq!(policy.max_replacements, 3);
+    }
+}
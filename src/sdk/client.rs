@@ -0,0 +1,234 @@
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::sleep;
+
+use crate::api::error::ApiErrorBody;
+use crate::api::types::{
+    CreateWithdrawalRequest, DepositRequest, DepositResponse, HashRequest, HashResponse,
+    NonceResponse, NonceType, TreeProofResponse, WithrawalResponse,
+};
+use crate::db::database::Deposit;
+
+#[derive(Debug, Error)]
+pub enum SdkError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The sequencer rejected the request with its structured error body
+    #[error("Sequencer returned {status} ({code}): {message}")]
+    Api {
+        status: u16,
+        code: String,
+        message: String,
+        request_id: Option<String>,
+    },
+
+    #[error("Invalid sequencer response ({status}): {body}")]
+    InvalidResponse { status: u16, body: String },
+}
+
+impl SdkError {
+    /// Stable code of an error returned by the sequencer, e.g. `duplicate_commitment`
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::Api { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// Time allowed for each attempt of a request
+    pub timeout: Duration,
+    /// Attempts made after the first one when the sequencer cannot be reached, times out
+    /// or answers with 429 or a 5xx status
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled after every attempt
+    pub retry_delay: Duration,
+    /// Network sent as `?network=` with every request, the sequencer's first configured
+    /// network when `None`
+    pub network: Option<String>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_retries: 2,
+            retry_delay: Duration::from_millis(500),
+            network: None,
+        }
+    }
+}
+
+/// Client of the sequencer API.
+///
+/// Retried deposits and withdrawals may be answered with `duplicate_commitment` when an
+/// earlier attempt was recorded but its response was lost.
+pub struct SequencerClient {
+    http: Client,
+    base_url: String,
+    config: ClientConfig,
+}
+
+impl SequencerClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_config(base_url, ClientConfig::default())
+    }
+
+    pub fn with_config(base_url: impl Into<String>, config: ClientConfig) -> Self {
+        Self {
+            http: Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            config,
+        }
+    }
+
+    /// Records a deposit through `POST /deposit`
+    pub async fn create_deposit(
+        &self,
+        request: &DepositRequest,
+    ) -> Result<DepositResponse, SdkError> {
+        self.send(|| self.request(Method::POST, "/deposit").json(request))
+            .await
+    }
+
+    /// Computes the commitment hash of a burn through `POST /compute-hash`
+    pub async fn compute_commitment_hash(
+        &self,
+        request: &HashRequest,
+    ) -> Result<HashResponse, SdkError> {
+        self.send(|| self.request(Method::POST, "/compute-hash").json(request))
+            .await
+    }
+
+    /// Fetches the nonce the next deposit or withdrawal of `stark_pub_key` must be hashed
+    /// with, through `GET /nonce/{stark_pub_key}`
+    pub async fn get_next_nonce(
+        &self,
+        stark_pub_key: &str,
+        nonce_type: NonceType,
+    ) -> Result<NonceResponse, SdkError> {
+        let path = format!("/nonce/{}", stark_pub_key);
+        self.send(|| {
+            self.request(Method::GET, &path)
+                .query(&[("type", nonce_type)])
+        })
+        .await
+    }
+
+    /// Fetches the deposit with `commitment_hash`, including its current status
+    pub async fn get_deposit_status(&self, commitment_hash: &str) -> Result<Deposit, SdkError> {
+        let path = format!("/deposits/by-hash/{}", commitment_hash);
+        self.send(|| self.request(Method::GET, &path)).await
+    }
+
+    /// Fetches the inclusion proof of `commitment_hash` in the L1 deposit tree
+    pub async fn get_merkle_proof(
+        &self,
+        commitment_hash: &str,
+    ) -> Result<TreeProofResponse, SdkError> {
+        let path = format!("/tree/proof/{}", commitment_hash);
+        self.send(|| self.request(Method::GET, &path)).await
+    }
+
+    /// Records a withdrawal through `POST /withdrawals`
+    pub async fn create_withdrawal(
+        &self,
+        request: &CreateWithdrawalRequest,
+    ) -> Result<WithrawalResponse, SdkError> {
+        self.send(|| self.request(Method::POST, "/withdrawals").json(request))
+            .await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path))
+            .timeout(self.config.timeout);
+
+        match &self.config.network {
+            Some(network) => request.query(&[("network", network)]),
+            None => request,
+        }
+    }
+
+    /// Sends the request built by `build`, building it again for every retry
+    async fn send<T: DeserializeOwned>(
+        &self,
+        build: impl Fn() -> RequestBuilder,
+    ) -> Result<T, SdkError> {
+        let mut delay = self.config.retry_delay;
+        let mut attempt = 0;
+
+        loop {
+            let result = build().send().await;
+            let retryable = match &result {
+                Ok(response) => is_retryable_status(response.status()),
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+            if !retryable || attempt >= self.config.max_retries {
+                return parse_response(result?).await;
+            }
+
+            attempt += 1;
+            sleep(delay).await;
+            delay *= 2;
+        }
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+async fn parse_response<T: DeserializeOwned>(response: Response) -> Result<T, SdkError> {
+    let status = response.status();
+    let body = response.text().await?;
+    let invalid = |body: String| SdkError::InvalidResponse {
+        status: status.as_u16(),
+        body,
+    };
+
+    if status.is_success() {
+        return serde_json::from_str(&body).map_err(|_| invalid(body));
+    }
+
+    match serde_json::from_str::<ApiErrorBody>(&body) {
+        Ok(ApiErrorBody { error }) => Err(SdkError::Api {
+            status: status.as_u16(),
+            code: error.code,
+            message: error.message,
+            request_id: error.request_id,
+        }),
+        Err(_) => Err(invalid(body)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limits_and_server_errors_are_retried() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn test_client_errors_are_not_retried() {
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::CONFLICT));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_base_url_trailing_slash_is_dropped() {
+        let client = SequencerClient::new("http://localhost:8080/");
+        assert_eq!(client.base_url, "http://localhost:8080");
+    }
+}
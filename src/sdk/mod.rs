@@ -0,0 +1,9 @@
+//! Typed async client of the sequencer API.
+//!
+//! [`SequencerClient`] sends the same request and response bodies the handlers use,
+//! taken from [`crate::api::types`], and maps the structured error body of failed
+//! requests onto [`SdkError::Api`].
+
+pub mod client;
+
+pub use client::{ClientConfig, SdkError, SequencerClient};
@@ -0,0 +1,276 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::config::{
+    LimitsConfig, LiveConfig, NetworkConfig, ProverConfig, RelayerConfig, SimulationConfig,
+};
+use crate::events::l1_event_watcher::{l1_event_watchers, RealEthereumProvider};
+use crate::events::AnyEventWatcher;
+use crate::proof_client::service::{ProofGenerationError, ProofStages, StoneProofStages};
+use crate::queue::l1_queue::L1BlockSource;
+use crate::relayer::starknet_relayer::{
+    StarknetRelayer, StarknetRelayerError, StarknetRelayerService,
+};
+
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    #[error("Simulation mode needs the sequencer built with the `simulation` feature")]
+    Disabled,
+
+    #[error("simulation.events_fixture must be set in simulation mode")]
+    MissingFixture,
+
+    #[error("Events fixture I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid events fixture: {0}")]
+    InvalidFixture(String),
+}
+
+/// Does the work of the pipeline's services that leaves the process: proving deposits,
+/// reading L1 and sending L2 transactions.
+///
+/// Services are built from the backend's parts, so simulation mode swaps the backend
+/// rather than changing the services' code paths.
+#[async_trait]
+pub trait PipelineBackend: Send + Sync {
+    /// Stages proving deposits with `prover`, building the Cairo project in
+    /// `cairo_project_dir`
+    fn proof_stages(
+        &self,
+        db_pool: PgPool,
+        prover: ProverConfig,
+        cairo_project_dir: PathBuf,
+    ) -> Result<Arc<dyn ProofStages>, ProofGenerationError>;
+
+    /// Relayer of `network`'s L2 transactions, following its retry limit in
+    /// `live_config` if given
+    async fn starknet_relayer(
+        &self,
+        db_pool: PgPool,
+        network: &NetworkConfig,
+        relayer: &RelayerConfig,
+        live_config: Option<LiveConfig>,
+    ) -> Result<Arc<dyn StarknetRelayerService>, StarknetRelayerError>;
+
+    /// Source of `network`'s latest L1 block, which confirmations are counted from
+    fn l1_block_source(&self, network: &NetworkConfig) -> Arc<dyn L1BlockSource>;
+
+    /// Watchers of `network`'s deposit events, starting from `from_block` until they
+    /// first move their block trackers
+    fn l1_event_watchers(
+        &self,
+        network: &NetworkConfig,
+        from_block: u64,
+        limits: &LimitsConfig,
+    ) -> Vec<Box<dyn AnyEventWatcher>>;
+}
+
+/// Picks the backend of `simulation`: the real one unless simulation is enabled
+pub fn backend_from_config(
+    simulation: &SimulationConfig,
+) -> Result<Arc<dyn PipelineBackend>, SimulationError> {
+    if !simulation.enabled {
+        return Ok(Arc::new(RealBackend));
+    }
+    simulated_backend(simulation)
+}
+
+#[cfg(feature = "simulation")]
+fn simulated_backend(
+    simulation: &SimulationConfig,
+) -> Result<Arc<dyn PipelineBackend>, SimulationError> {
+    Ok(Arc::new(SimulatedBackend::from_config(simulation)?))
+}
+
+#[cfg(not(feature = "simulation"))]
+fn simulated_backend(
+    _simulation: &SimulationConfig,
+) -> Result<Arc<dyn PipelineBackend>, SimulationError> {
+    Err(SimulationError::Disabled)
+}
+
+/// Proves with Scarb and the Stone toolchain, and talks to the networks' nodes
+pub struct RealBackend;
+
+#[async_trait]
+impl PipelineBackend for RealBackend {
+    fn proof_stages(
+        &self,
+        db_pool: PgPool,
+        prover: ProverConfig,
+        cairo_project_dir: PathBuf,
+    ) -> Result<Arc<dyn ProofStages>, ProofGenerationError> {
+        Ok(Arc::new(StoneProofStages::new(
+            db_pool,
+            prover,
+            cairo_project_dir,
+        )?))
+    }
+
+    async fn starknet_relayer(
+        &self,
+        db_pool: PgPool,
+        network: &NetworkConfig,
+        relayer: &RelayerConfig,
+        live_config: Option<LiveConfig>,
+    ) -> Result<Arc<dyn StarknetRelayerService>, StarknetRelayerError> {
+        let mut relayer = StarknetRelayer::from_network_config(network, relayer, db_pool).await?;
+        if let Some(live_config) = live_config {
+            relayer = relayer.with_live_config(live_config);
+        }
+        Ok(Arc::new(relayer))
+    }
+
+    fn l1_block_source(&self, network: &NetworkConfig) -> Arc<dyn L1BlockSource> {
+        Arc::new(RealEthereumProvider::new(network.ethereum.get_rpc_url()))
+    }
+
+    fn l1_event_watchers(
+        &self,
+        network: &NetworkConfig,
+        from_block: u64,
+        limits: &LimitsConfig,
+    ) -> Vec<Box<dyn AnyEventWatcher>> {
+        l1_event_watchers(
+            &network.name,
+            &network.ethereum.get_rpc_url(),
+            &network.contracts.l1_contract_address,
+            from_block,
+            network.ethereum.confirmations.into(),
+            limits,
+        )
+    }
+}
+
+#[cfg(feature = "simulation")]
+pub use simulated::SimulatedBackend;
+
+#[cfg(feature = "simulation")]
+mod simulated {
+    use super::*;
+    use crate::events::l1_event_watcher::l1_event_watchers_with_provider;
+    use crate::simulation::{FixtureEthereumProvider, SimulatedProofStages, SimulatedRelayer};
+    use std::time::Duration;
+
+    /// Reads L1 from a fixture, proves with synthetic artifacts and only logs the L2
+    /// transactions it completes
+    pub struct SimulatedBackend {
+        events: FixtureEthereumProvider,
+        relay_delay: Duration,
+    }
+
+    impl SimulatedBackend {
+        pub fn new(events: FixtureEthereumProvider, relay_delay: Duration) -> Self {
+            Self {
+                events,
+                relay_delay,
+            }
+        }
+
+        /// Creates the backend of `simulation`, reading its events fixture
+        pub fn from_config(simulation: &SimulationConfig) -> Result<Self, SimulationError> {
+            let fixture = simulation
+                .events_fixture
+                .as_deref()
+                .ok_or(SimulationError::MissingFixture)?;
+            Ok(Self::new(
+                FixtureEthereumProvider::from_file(fixture)?,
+                Duration::from_millis(simulation.relay_delay_ms),
+            ))
+        }
+    }
+
+    #[async_trait]
+    impl PipelineBackend for SimulatedBackend {
+        fn proof_stages(
+            &self,
+            db_pool: PgPool,
+            _prover: ProverConfig,
+            _cairo_project_dir: PathBuf,
+        ) -> Result<Arc<dyn ProofStages>, ProofGenerationError> {
+            Ok(Arc::new(SimulatedProofStages::new(db_pool)))
+        }
+
+        async fn starknet_relayer(
+            &self,
+            db_pool: PgPool,
+            network: &NetworkConfig,
+            _relayer: &RelayerConfig,
+            live_config: Option<LiveConfig>,
+        ) -> Result<Arc<dyn StarknetRelayerService>, StarknetRelayerError> {
+            let mut relayer = SimulatedRelayer::new(
+                db_pool,
+                network.name.clone(),
+                network.contracts.l2_contract_address.clone(),
+                self.relay_delay,
+                network.starknet.max_retries.unwrap_or(3),
+            );
+            if let Some(live_config) = live_config {
+                relayer = relayer.with_live_config(live_config);
+            }
+            Ok(Arc::new(relayer))
+        }
+
+        fn l1_block_source(&self, _network: &NetworkConfig) -> Arc<dyn L1BlockSource> {
+            Arc::new(self.events.clone())
+        }
+
+        fn l1_event_watchers(
+            &self,
+            network: &NetworkConfig,
+            from_block: u64,
+            limits: &LimitsConfig,
+        ) -> Vec<Box<dyn AnyEventWatcher>> {
+            l1_event_watchers_with_provider(
+                self.events.clone(),
+                &network.name,
+                &network.contracts.l1_contract_address,
+                from_block,
+                network.ethereum.confirmations.into(),
+                limits,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_real_backend_unless_simulation_is_enabled() {
+        assert!(backend_from_config(&SimulationConfig::default()).is_ok());
+    }
+
+    #[cfg(not(feature = "simulation"))]
+    #[test]
+    fn test_simulation_needs_the_feature() {
+        let simulation = SimulationConfig {
+            enabled: true,
+            ..SimulationConfig::default()
+        };
+
+        assert!(matches!(
+            backend_from_config(&simulation),
+            Err(SimulationError::Disabled)
+        ));
+    }
+
+    #[cfg(feature = "simulation")]
+    #[test]
+    fn test_simulation_needs_an_events_fixture() {
+        let simulation = SimulationConfig {
+            enabled: true,
+            ..SimulationConfig::default()
+        };
+
+        assert!(matches!(
+            backend_from_config(&simulation),
+            Err(SimulationError::MissingFixture)
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use alloy::primitives::{keccak256, B256};
+use alloy::rpc::types::{Filter, FilteredParams, Log};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::events::l1_event_watcher::TestEthereumProvider;
+use crate::queue::l1_queue::{L1BlockSource, ValidationError};
+use crate::simulation::SimulationError;
+
+/// L1 chain as stored in a fixture file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventsFixture {
+    /// Latest block of the chain, which confirmations are counted from
+    pub latest_block: u64,
+    /// Logs of the chain, in the format of `eth_getLogs` results, e.g. as printed by
+    /// `cast logs --json`
+    pub logs: Vec<Log>,
+}
+
+/// L1 provider serving the logs of an [`EventsFixture`] instead of an RPC endpoint.
+///
+/// Blocks up to the fixture's latest have hashes derived from their number, so the
+/// chain never re-orgs.
+#[derive(Debug, Clone)]
+pub struct FixtureEthereumProvider {
+    fixture: Arc<EventsFixture>,
+}
+
+impl FixtureEthereumProvider {
+    pub fn new(fixture: EventsFixture) -> Self {
+        Self {
+            fixture: Arc::new(fixture),
+        }
+    }
+
+    /// Reads the fixture in the JSON file at `path`
+    pub fn from_file(path: &Path) -> Result<Self, SimulationError> {
+        let fixture = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| SimulationError::InvalidFixture(format!("{}: {}", path.display(), e)))?;
+        Ok(Self::new(fixture))
+    }
+
+    fn logs_matching(&self, filter: &Filter) -> Vec<Log> {
+        let params = FilteredParams::new(Some(filter.clone()));
+        self.fixture
+            .logs
+            .iter()
+            .filter(|log| {
+                log.block_number
+                    .is_some_and(|block| params.filter_block_range(block))
+                    && params.filter_address(&log.address())
+                    && params.filter_topics(log.topics())
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+impl TestEthereumProvider for FixtureEthereumProvider {
+    fn get_logs(
+        &self,
+        filter: &Filter,
+    ) -> impl std::future::Future<Output = Result<Vec<Log>, Box<dyn std::error::Error + Send + Sync>>>
+           + Send {
+        let logs = self.logs_matching(filter);
+        async move { Ok(logs) }
+    }
+
+    fn get_block_number(
+        &self,
+    ) -> impl std::future::Future<Output = Result<u64, Box<dyn std::error::Error + Send + Sync>>> + Send
+    {
+        let latest_block = self.fixture.latest_block;
+        async move { Ok(latest_block) }
+    }
+
+    fn get_block_hash(
+        &self,
+        number: u64,
+    ) -> impl std::future::Future<
+        Output = Result<Option<B256>, Box<dyn std::error::Error + Send + Sync>>,
+    > + Send {
+        let hash = (number <= self.fixture.latest_block).then(|| keccak256(number.to_be_bytes()));
+        async move { Ok(hash) }
+    }
+}
+
+#[async_trait]
+impl L1BlockSource for FixtureEthereumProvider {
+    async fn latest_block_number(&self) -> Result<u64, ValidationError> {
+        Ok(self.fixture.latest_block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::l1_event_watcher::ZeroXBridge;
+    use alloy::primitives::{Address, U256};
+    use alloy::sol_types::SolEvent;
+
+    const CONTRACT: Address = Address::repeat_byte(0xaa);
+
+    fn deposit_hash_log(address: Address, block_number: u64) -> Log {
+        let event = ZeroXBridge::DepositHashAppended {
+            index: U256::ZERO,
+            commitmentHash: U256::from(block_number),
+            rootHash: U256::ZERO,
+            elementsCount: U256::from(1),
+        };
+        Log {
+            inner: alloy::primitives::Log {
+                address,
+                data: event.encode_log_data(),
+            },
+            block_number: Some(block_number),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logs_are_filtered_like_an_rpc_endpoint_would() {
+        let provider = FixtureEthereumProvider::new(EventsFixture {
+            latest_block: 20,
+            logs: vec![
+                deposit_hash_log(CONTRACT, 5),
+                deposit_hash_log(CONTRACT, 15),
+                deposit_hash_log(Address::repeat_byte(0xbb), 5),
+            ],
+        });
+        let filter = Filter::new()
+            .address(CONTRACT)
+            .event(ZeroXBridge::DepositHashAppended::SIGNATURE)
+            .from_block(0)
+            .to_block(10);
+
+        let logs = provider.get_logs(&filter).await.unwrap();
+        assert_eq!(logs, vec![deposit_hash_log(CONTRACT, 5)]);
+
+        let other_event = filter.event(ZeroXBridge::DepositEvent::SIGNATURE);
+        assert!(provider.get_logs(&other_event).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fixture_round_trips_through_json() {
+        let fixture = EventsFixture {
+            latest_block: 20,
+            logs: vec![deposit_hash_log(CONTRACT, 5)],
+        };
+        let provider = FixtureEthereumProvider::new(
+            serde_json::from_str(&serde_json::to_string(&fixture).unwrap()).unwrap(),
+        );
+
+        assert_eq!(provider.get_block_number().await.unwrap(), 20);
+        assert_eq!(
+            provider.get_logs(&Filter::new()).await.unwrap(),
+            fixture.logs
+        );
+        assert!(provider.get_block_hash(20).await.unwrap().is_some());
+        assert_eq!(provider.get_block_hash(21).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,26 @@
+//! Simulation mode, running the whole pipeline without a prover or chains.
+//!
+//! The work of the pipeline's services that leaves the process goes through a
+//! [`PipelineBackend`]. [`RealBackend`] hands it to Scarb, the Stone toolchain and the
+//! L1 and L2 nodes like the services always have. In builds with the `simulation`
+//! feature, [`SimulatedBackend`] replaces them with stubs instead: L1 events are read
+//! from a [`FixtureEthereumProvider`], proofs are written by [`SimulatedProofStages`]
+//! and L2 transactions are completed by a [`SimulatedRelayer`] that only logs them.
+
+pub mod backend;
+#[cfg(feature = "simulation")]
+pub mod fixture;
+#[cfg(feature = "simulation")]
+pub mod proof_stages;
+#[cfg(feature = "simulation")]
+pub mod relayer;
+
+#[cfg(feature = "simulation")]
+pub use backend::SimulatedBackend;
+pub use backend::{backend_from_config, PipelineBackend, RealBackend, SimulationError};
+#[cfg(feature = "simulation")]
+pub use fixture::{EventsFixture, FixtureEthereumProvider};
+#[cfg(feature = "simulation")]
+pub use proof_stages::SimulatedProofStages;
+#[cfg(feature = "simulation")]
+pub use relayer::SimulatedRelayer;
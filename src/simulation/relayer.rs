@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use starknet::core::types::{Call, Felt};
+use starknet_crypto::poseidon_hash_many;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, error, info};
+
+use crate::config::{LiveConfig, NetworkId};
+use crate::db::service_controls::{PipelineService, ServicePause};
+use crate::events::status_notifications::{StatusWakeup, Wakeup, L2_TRANSACTIONS_STATUS_CHANNEL};
+use crate::queue::l2_queue::L2Transaction;
+use crate::relayer::starknet_relayer::{
+    build_relay_calls, fetch_ready_l2_transactions, mark_completed, relay_transactions,
+    ProcessingReport, StarknetRelayerError, StarknetRelayerService, TransactionRelay,
+};
+
+/// Relayer logging the calls it would send to Starknet, then completing each transaction
+/// with a fake hash after `relay_delay`.
+///
+/// The calls are built like [`StarknetRelayer`] builds them, so malformed proof data
+/// fails the same way.
+///
+/// [`StarknetRelayer`]: crate::relayer::starknet_relayer::StarknetRelayer
+pub struct SimulatedRelayer {
+    db_pool: PgPool,
+    network: NetworkId,
+    bridge_contract_address: String,
+    relay_delay: Duration,
+    max_retries: u32,
+    pause: ServicePause,
+    live_config: Option<LiveConfig>,
+}
+
+impl SimulatedRelayer {
+    pub fn new(
+        db_pool: PgPool,
+        network: NetworkId,
+        bridge_contract_address: impl Into<String>,
+        relay_delay: Duration,
+        max_retries: u32,
+    ) -> Self {
+        Self {
+            pause: ServicePause::new(db_pool.clone(), PipelineService::StarknetRelayer),
+            db_pool,
+            network,
+            bridge_contract_address: bridge_contract_address.into(),
+            relay_delay,
+            max_retries,
+            live_config: None,
+        }
+    }
+
+    /// Takes the network's `starknet.max_retries` from `live_config` on every cycle,
+    /// like the real relayer
+    pub fn with_live_config(mut self, live_config: LiveConfig) -> Self {
+        self.live_config = Some(live_config);
+        self
+    }
+
+    fn max_retries(&self) -> u32 {
+        self.live_config
+            .as_ref()
+            .and_then(|live_config| live_config.network(&self.network))
+            .map(|network| network.starknet.max_retries.unwrap_or(3))
+            .unwrap_or(self.max_retries)
+    }
+}
+
+#[async_trait]
+impl TransactionRelay for SimulatedRelayer {
+    async fn relay_transaction(&self, tx: &mut L2Transaction) -> Result<(), StarknetRelayerError> {
+        let proof_data = tx
+            .proof_data
+            .as_deref()
+            .ok_or(StarknetRelayerError::ProofDataMissing)?;
+        let calls = build_relay_calls(&self.bridge_contract_address, tx, proof_data)?;
+        for call in &calls {
+            info!(
+                network = %self.network,
+                transaction = tx.id,
+                to = %call.to.to_hex_string(),
+                selector = %call.selector.to_hex_string(),
+                calldata = ?call.calldata.iter().map(Felt::to_hex_string).collect::<Vec<_>>(),
+                "Simulating Starknet call"
+            );
+        }
+
+        sleep(self.relay_delay).await;
+        let tx_hash = simulated_tx_hash(tx.id, &calls).to_hex_string();
+        mark_completed(&self.db_pool, tx.id, &tx_hash).await?;
+        info!(
+            "Transaction {} completed by simulation (hash: {})",
+            tx.id, tx_hash
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StarknetRelayerService for SimulatedRelayer {
+    async fn start(&self) -> Result<(), StarknetRelayerError> {
+        info!(
+            "Starting simulated Starknet relayer for network {}",
+            self.network
+        );
+
+        let mut wakeup = StatusWakeup::listen(
+            &self.db_pool,
+            L2_TRANSACTIONS_STATUS_CHANNEL,
+            "ready_for_relay",
+        )
+        .await;
+
+        // Paused like the Starknet relayer it stands in for
+        loop {
+            if !self.pause.is_paused().await {
+                match self.process_pending_transactions().await {
+                    Ok(report) if report.is_empty() => {}
+                    Ok(report) => info!(
+                        network = %self.network,
+                        succeeded = ?report.succeeded,
+                        retried = ?report.retried,
+                        failed = report.failed.len(),
+                        "Simulated Starknet transactions"
+                    ),
+                    Err(e) => error!("Error simulating Starknet transactions: {:?}", e),
+                }
+            }
+
+            if let Wakeup::Notified(ids) = wakeup.wait(Duration::from_secs(10)).await {
+                debug!("L2 transactions {:?} are ready for relay", ids);
+            }
+        }
+    }
+
+    async fn process_pending_transactions(&self) -> Result<ProcessingReport, StarknetRelayerError> {
+        let transactions = fetch_ready_l2_transactions(&self.db_pool, &self.network).await?;
+        relay_transactions(&self.db_pool, self, transactions, self.max_retries()).await
+    }
+}
+
+/// Hash standing in for the transaction sending `calls` for L2 transaction `id`
+fn simulated_tx_hash(id: i64, calls: &[Call]) -> Felt {
+    let mut elements = vec![Felt::from(id)];
+    for call in calls {
+        elements.extend([call.to, call.selector]);
+        elements.extend(&call.calldata);
+    }
+    poseidon_hash_many(&elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet::macros::selector;
+
+    fn call(calldata: Vec<Felt>) -> Call {
+        Call {
+            to: Felt::from(0x456u64),
+            selector: selector!("process_withdrawal"),
+            calldata,
+        }
+    }
+
+    #[test]
+    fn test_simulated_hashes_differ_per_transaction_and_calldata() {
+        let calls = vec![call(vec![Felt::ONE, Felt::TWO])];
+
+        assert_eq!(simulated_tx_hash(1, &calls), simulated_tx_hash(1, &calls));
+        assert_ne!(simulated_tx_hash(1, &calls), simulated_tx_hash(2, &calls));
+        assert_ne!(
+            simulated_tx_hash(1, &calls),
+            simulated_tx_hash(1, &[call(vec![Felt::ONE, Felt::THREE])])
+        );
+    }
+}
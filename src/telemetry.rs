@@ -0,0 +1,54 @@
+//! Export of the sequencer's tracing spans to an OpenTelemetry collector, set up from
+//! `[telemetry]`.
+
+use opentelemetry::trace::{TraceError, TracerProvider as _};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::runtime;
+use opentelemetry_sdk::trace::{Builder, Sampler, Tracer, TracerProvider};
+use opentelemetry_sdk::Resource;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::TelemetryConfig;
+
+/// Instrumentation scope the sequencer's spans are recorded under
+const TRACER_NAME: &str = "zeroxbridge_sequencer";
+
+/// Provider exporting spans to `config.otlp_endpoint` over OTLP/gRPC, in batches.
+///
+/// Spans still buffered are only sent when the provider is shut down, so call
+/// [`TracerProvider::shutdown`] before exiting.
+pub fn otlp_tracer_provider(config: &TelemetryConfig) -> Result<TracerProvider, TraceError> {
+    let exporter = SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(config.otlp_endpoint.clone())
+        .build()?;
+
+    Ok(tracer_provider_builder(config)
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .build())
+}
+
+/// Provider reporting spans as `config.service_name` and keeping
+/// `config.sampling_ratio` of the traces, still without an exporter
+pub fn tracer_provider_builder(config: &TelemetryConfig) -> Builder {
+    // Child spans follow the decision made for the root of their trace
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(config.sampling_ratio)));
+
+    TracerProvider::builder()
+        .with_sampler(sampler)
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            config.service_name.clone(),
+        )]))
+}
+
+/// Layer handing the spans of the subscriber it is added to over to `provider`
+pub fn telemetry_layer<S>(provider: &TracerProvider) -> OpenTelemetryLayer<S, Tracer>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer(TRACER_NAME))
+}
@@ -0,0 +1,449 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use tokio::sync::{Mutex, Notify, RwLock};
+use tracing::{debug, error, info, warn};
+use tree_builder::l1_tree::L1MerkleTreeBuilder;
+
+use crate::config::NetworkId;
+use crate::db::database::{
+    fetch_deposits_with_event_index, fetch_included_commitments,
+    fetch_included_deposit_by_commitment, fetch_last_tree_checkpoint, insert_merkle_root,
+    mark_deposit_included, update_deposit_status_cas, EventIndexedDeposit, RootSource,
+    DEPOSIT_STATUS_DUPLICATE_COMMITMENT, DEPOSIT_STATUS_INVALID_COMMITMENT,
+};
+use crate::db::service_controls::{PipelineService, ServicePause};
+use crate::db::timings::{DepositStage, StageTimer};
+use crate::events::status_notifications::{StatusWakeup, Wakeup, DEPOSITS_STATUS_CHANNEL};
+use crate::queue::l1_queue::DEPOSIT_STATUS_PENDING_TREE_INCLUSION;
+use crate::tree_builder::proof_cache::{ProofCache, DEFAULT_PROOF_CACHE_SIZE};
+use crate::tree_builder::root_feed::{RootFeed, TreeRoot};
+use crate::utils::{parse_commitment_hash, CommitmentParseError};
+
+#[derive(Error, Debug)]
+pub enum TreeBuilderClientError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Merkle tree error: {0}")]
+    Tree(String),
+
+    #[error("Invalid commitment hash: {0}")]
+    InvalidCommitment(String),
+
+    #[error("Snapshot error: {0}")]
+    Snapshot(String),
+
+    #[error("Rebuild checkpoint error: {0}")]
+    Checkpoint(String),
+
+    #[error("The tree never held {requested} elements, it holds {current}")]
+    InvalidElementsCount { requested: usize, current: usize },
+}
+
+impl From<CommitmentParseError> for TreeBuilderClientError {
+    fn from(err: CommitmentParseError) -> Self {
+        Self::InvalidCommitment(err.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeBuilderConfig {
+    /// Seconds to wait between two polls for deposits awaiting inclusion
+    pub process_interval_sec: u64,
+    /// Maximum number of deposits appended per poll
+    pub batch_size: i64,
+    /// File the tree is saved to on shutdown and restored from on startup
+    pub snapshot_path: Option<PathBuf>,
+    /// Maximum number of inclusion proofs kept in the proof cache
+    pub proof_cache_size: usize,
+}
+
+impl Default for TreeBuilderConfig {
+    fn default() -> Self {
+        Self {
+            process_interval_sec: 5,
+            batch_size: 50,
+            snapshot_path: None,
+            proof_cache_size: DEFAULT_PROOF_CACHE_SIZE,
+        }
+    }
+}
+
+/// Appends one network's deposits awaiting tree inclusion to its in-memory L1 Merkle
+/// tree and records their leaf index and the resulting root, both on the deposit and in
+/// the root history.
+pub struct TreeBuilderClient {
+    db_pool: PgPool,
+    network: NetworkId,
+    config: TreeBuilderConfig,
+    tree: Arc<RwLock<L1MerkleTreeBuilder>>,
+    /// Leaves of the tree, only changed while the tree's write lock is held
+    processed_commitments: Mutex<HashSet<[u8; 32]>>,
+    proofs: ProofCache,
+    roots: RootFeed,
+    shutdown: Arc<Notify>,
+    pause: ServicePause,
+}
+
+impl TreeBuilderClient {
+    pub fn new(db_pool: PgPool, network: NetworkId, config: TreeBuilderConfig) -> Self {
+        Self {
+            pause: ServicePause::new(db_pool.clone(), PipelineService::TreeBuilder),
+            db_pool,
+            network,
+            tree: Arc::new(RwLock::new(L1MerkleTreeBuilder::new())),
+            processed_commitments: Mutex::new(HashSet::new()),
+            proofs: ProofCache::new(config.proof_cache_size),
+            roots: RootFeed::default(),
+            config,
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Shared handle to the tree, e.g. for the API's `TreeState`
+    pub fn tree(&self) -> Arc<RwLock<L1MerkleTreeBuilder>> {
+        self.tree.clone()
+    }
+
+    /// Cache of the inclusion proofs served from [`tree`](Self::tree)
+    pub fn proof_cache(&self) -> ProofCache {
+        self.proofs.clone()
+    }
+
+    /// Feed of the roots reached after each batch of deposits is appended
+    pub fn root_feed(&self) -> RootFeed {
+        self.roots.clone()
+    }
+
+    /// Rebuilds the tree, then appends pending deposits until [`stop`](Self::stop) is called,
+    /// appending nothing while the tree builder is paused.
+    pub async fn start(&self) -> Result<(), TreeBuilderClientError> {
+        info!("Starting tree builder client for network {}", self.network);
+        let leaves = self.rebuild_tree_on_startup().await?;
+        info!("Tree of {} rebuilt with {} leaves", self.network, leaves);
+
+        let interval = Duration::from_secs(self.config.process_interval_sec);
+        let mut wakeup = StatusWakeup::listen(
+            &self.db_pool,
+            DEPOSITS_STATUS_CHANNEL,
+            "PENDING_TREE_INCLUSION",
+        )
+        .await;
+
+        loop {
+            if !self.pause.is_paused().await {
+                match self.process_pending_deposits().await {
+                    Ok(included) if included > 0 => {
+                        info!("Included {} deposits in the tree", included)
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("Tree inclusion cycle failed: {:?}", e),
+                }
+            }
+
+            tokio::select! {
+                woken = wakeup.wait(interval) => {
+                    if let Wakeup::Notified(ids) = woken {
+                        debug!("Deposits {:?} are waiting on tree inclusion", ids);
+                    }
+                }
+                _ = self.shutdown.notified() => {
+                    info!("Tree builder client stopped");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Stops the processing loop and saves a snapshot of the tree if configured.
+    pub async fn stop(&self) -> Result<(), TreeBuilderClientError> {
+        self.shutdown.notify_one();
+
+        if let Some(path) = &self.config.snapshot_path {
+            let tree = self.tree.read().await;
+            tree.save_to_file(path)
+                .map_err(|e| TreeBuilderClientError::Snapshot(e.to_string()))?;
+            info!("Saved tree snapshot to {:?}", path);
+        }
+
+        Ok(())
+    }
+
+    /// Restores the tree from the snapshot file when it is at least as recent as the last
+    /// inclusion recorded in the database, and replays the included deposits otherwise.
+    ///
+    /// Returns the number of leaves in the rebuilt tree.
+    pub async fn rebuild_tree_on_startup(&self) -> Result<usize, TreeBuilderClientError> {
+        // Loaded even when the snapshot is used, pending deposits are checked against it
+        let leaves = fetch_included_commitments(&self.db_pool, &self.network)
+            .await?
+            .iter()
+            .map(String::as_str)
+            .map(parse_commitment_hash)
+            .collect::<Result<Vec<_>, _>>()?;
+        let processed: HashSet<[u8; 32]> = leaves.iter().copied().collect();
+        if processed.len() < leaves.len() {
+            error!(
+                "Tree of {} holds {} duplicate leaves",
+                self.network,
+                leaves.len() - processed.len()
+            );
+        }
+
+        if let Some(path) = &self.config.snapshot_path {
+            if let Some(tree) = self.load_fresh_snapshot(path).await? {
+                let count = tree.leaf_count().await.map_err(tree_error)?;
+                let mut current = self.tree.write().await;
+                *current = tree;
+                *self.processed_commitments.lock().await = processed;
+                self.proofs.clear();
+                info!("Restored tree from snapshot {:?}", path);
+                return Ok(count);
+            }
+        }
+
+        let count = leaves.len();
+        let mut tree = L1MerkleTreeBuilder::new();
+        tree.build_merkle(leaves).await.map_err(tree_error)?;
+        let mut current = self.tree.write().await;
+        *current = tree;
+        *self.processed_commitments.lock().await = processed;
+        self.proofs.clear();
+
+        Ok(count)
+    }
+
+    async fn load_fresh_snapshot(
+        &self,
+        path: &Path,
+    ) -> Result<Option<L1MerkleTreeBuilder>, TreeBuilderClientError> {
+        let modified = match fs::metadata(path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(_) => return Ok(None),
+        };
+
+        let last_checkpoint = fetch_last_tree_checkpoint(&self.db_pool, &self.network).await?;
+        if !snapshot_is_fresh(modified, last_checkpoint) {
+            info!(
+                "Tree snapshot {:?} is stale, replaying from the database",
+                path
+            );
+            return Ok(None);
+        }
+
+        match L1MerkleTreeBuilder::load_from_file(path).await {
+            Ok(tree) => Ok(Some(tree)),
+            Err(e) => {
+                warn!("Failed to load tree snapshot {:?}: {}", path, e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Appends pending deposits to the tree at the leaf index their `DepositHashAppended`
+    /// event gave them on L1, returning how many were included.
+    ///
+    /// Deposits are appended in event index order. Once the next deposit's index is past
+    /// the tree's next leaf, it and every deposit after it wait in the queue until the
+    /// deposits filling the gap are ready.
+    pub async fn process_pending_deposits(&self) -> Result<usize, TreeBuilderClientError> {
+        let deposits =
+            fetch_deposits_with_event_index(&self.db_pool, &self.network, self.config.batch_size)
+                .await?;
+        if deposits.is_empty() {
+            return Ok(0);
+        }
+
+        let mut included = 0;
+        let mut last_root = None;
+
+        for deposit in deposits {
+            let leaf = match parse_commitment_hash(&deposit.commitment_hash) {
+                Ok(leaf) => leaf,
+                Err(e) => {
+                    error!("Taking deposit {} out of the queue: {}", deposit.id, e);
+                    self.take_out_of_queue(deposit.id, DEPOSIT_STATUS_INVALID_COMMITMENT)
+                        .await?;
+                    continue;
+                }
+            };
+
+            if self.processed_commitments.lock().await.contains(&leaf) {
+                self.skip_duplicate(&deposit).await?;
+                continue;
+            }
+
+            // Included by another process, or written differently than the included copy
+            if let Some(included_id) =
+                fetch_included_deposit_by_commitment(&self.db_pool, &self.network, &leaf).await?
+            {
+                error!(
+                    "Data integrity error: deposit {} repeats the commitment {} of included deposit {}",
+                    deposit.id, deposit.commitment_hash, included_id
+                );
+                self.flag_duplicate(deposit.id).await?;
+                continue;
+            }
+
+            let timer = StageTimer::start(&self.db_pool, deposit.id, DepositStage::TreeInclusion);
+            // Readers of the API are only held up while the leaf is appended, not while
+            // the inclusion is written to the database
+            let (leaf_index, root) = {
+                let mut tree = self.tree.write().await;
+                let mut processed = self.processed_commitments.lock().await;
+                // Appended by a concurrent call since the check above
+                if processed.contains(&leaf) {
+                    drop(processed);
+                    drop(tree);
+                    timer.discard();
+                    self.skip_duplicate(&deposit).await?;
+                    continue;
+                }
+                let leaf_index = tree.leaf_count().await.map_err(tree_error)? as i64;
+                match deposit.event_index.cmp(&leaf_index) {
+                    Ordering::Equal => {}
+                    Ordering::Greater => {
+                        debug!(
+                            "Deposit {} was appended at index {} on L1, waiting for the leaves from index {}",
+                            deposit.id, deposit.event_index, leaf_index
+                        );
+                        timer.discard();
+                        break;
+                    }
+                    Ordering::Less => {
+                        error!(
+                            "Data integrity error: deposit {} was appended at index {} on L1, but the tree of {} already holds {} leaves",
+                            deposit.id, deposit.event_index, self.network, leaf_index
+                        );
+                        timer.fail(format!(
+                            "Appended at index {} on L1, but the tree already holds {} leaves",
+                            deposit.event_index, leaf_index
+                        ));
+                        continue;
+                    }
+                }
+                tree.build_merkle(vec![leaf]).await.map_err(tree_error)?;
+                processed.insert(leaf);
+                let root = TreeRoot {
+                    root: tree.get_root().await.map_err(tree_error)?,
+                    leaf_count: leaf_index as usize + 1,
+                    elements_count: tree.elements_count().await.map_err(tree_error)?,
+                };
+                (leaf_index, root)
+            };
+            last_root = Some(root);
+            let root = format!("0x{}", hex::encode(root.root));
+
+            let recorded = async {
+                let mut tx = self.db_pool.begin().await?;
+                if !mark_deposit_included(&mut tx, deposit.id, leaf_index, &root).await? {
+                    warn!(
+                        "Deposit {} left {} before its inclusion was recorded, keeping its status",
+                        deposit.id, DEPOSIT_STATUS_PENDING_TREE_INCLUSION
+                    );
+                }
+                insert_merkle_root(
+                    &mut tx,
+                    &self.network,
+                    leaf_index + 1,
+                    &root,
+                    None,
+                    RootSource::Local,
+                )
+                .await?;
+                tx.commit().await
+            }
+            .await;
+            timer.finish(recorded)?;
+            included += 1;
+        }
+
+        if let Some(root) = last_root {
+            self.roots.publish(root);
+        }
+        Ok(included)
+    }
+
+    async fn skip_duplicate(
+        &self,
+        deposit: &EventIndexedDeposit,
+    ) -> Result<(), TreeBuilderClientError> {
+        warn!(
+            "Skipping deposit {}: commitment {} is already in the tree",
+            deposit.id, deposit.commitment_hash
+        );
+        self.flag_duplicate(deposit.id).await
+    }
+
+    /// Takes a deposit repeating an included commitment out of the inclusion queue
+    async fn flag_duplicate(&self, deposit_id: i32) -> Result<(), TreeBuilderClientError> {
+        self.take_out_of_queue(deposit_id, DEPOSIT_STATUS_DUPLICATE_COMMITMENT)
+            .await
+    }
+
+    /// Moves a deposit waiting for inclusion to `status`, unless another process moved
+    /// it since it was fetched
+    async fn take_out_of_queue(
+        &self,
+        deposit_id: i32,
+        status: &str,
+    ) -> Result<(), TreeBuilderClientError> {
+        let mut conn = self.db_pool.acquire().await?;
+        if !update_deposit_status_cas(
+            &mut conn,
+            deposit_id,
+            DEPOSIT_STATUS_PENDING_TREE_INCLUSION,
+            status,
+        )
+        .await?
+        {
+            warn!(
+                "Deposit {} left {} before it could be moved to {}",
+                deposit_id, DEPOSIT_STATUS_PENDING_TREE_INCLUSION, status
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Whether a snapshot written at `modified` already contains every inclusion up to
+/// `last_checkpoint`
+fn snapshot_is_fresh(modified: SystemTime, last_checkpoint: Option<DateTime<Utc>>) -> bool {
+    match last_checkpoint {
+        Some(checkpoint) => DateTime::<Utc>::from(modified) >= checkpoint,
+        None => true,
+    }
+}
+
+pub(crate) fn tree_error(err: impl std::fmt::Display) -> TreeBuilderClientError {
+    TreeBuilderClientError::Tree(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_newer_than_checkpoint_is_fresh() {
+        let checkpoint = Utc::now();
+        let modified = SystemTime::from(checkpoint) + Duration::from_secs(10);
+
+        assert!(snapshot_is_fresh(modified, Some(checkpoint)));
+        assert!(snapshot_is_fresh(modified, None));
+    }
+
+    #[test]
+    fn test_snapshot_older_than_checkpoint_is_stale() {
+        let checkpoint = Utc::now();
+        let modified = SystemTime::from(checkpoint) - Duration::from_secs(10);
+
+        assert!(!snapshot_is_fresh(modified, Some(checkpoint)));
+    }
+}
@@ -0,0 +1,200 @@
+//! gRPC interface of the tree builder, defined in `proto/tree.proto`.
+//!
+//! The `TreeService` answers from the [`TreeState`] of each network, the same in-memory
+//! trees, proof caches and root feeds the HTTP API reads, so a proof never differs
+//! between the two.
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tracing::{error, info, warn};
+
+use super::client::{tree_error, TreeBuilderClientError};
+use super::root_feed::TreeRoot;
+use crate::api::routes::TreeState;
+use crate::config::NetworkId;
+use crate::queue::l2_queue::InclusionProof;
+use crate::utils::parse_commitment_hash;
+
+pub mod proto {
+    tonic::include_proto!("tree");
+}
+
+use proto::tree_service_server::{TreeService, TreeServiceServer};
+use proto::{
+    GetProofAtCountRequest, GetProofRequest, GetRootRequest, ProofResponse, RootResponse,
+    WatchRootsRequest,
+};
+
+/// `TreeService` serving the trees of the networks it was created with
+#[derive(Clone)]
+pub struct TreeGrpcService {
+    trees: Vec<(NetworkId, TreeState)>,
+}
+
+impl TreeGrpcService {
+    /// Serves `trees`, requests without a network get the first one
+    pub fn new(trees: Vec<(NetworkId, TreeState)>) -> Self {
+        Self { trees }
+    }
+
+    fn tree_state(&self, network: &str) -> Result<&TreeState, Status> {
+        let found = if network.is_empty() {
+            self.trees.first()
+        } else {
+            self.trees.iter().find(|(id, _)| id.as_str() == network)
+        };
+        found
+            .map(|(_, tree_state)| tree_state)
+            .ok_or_else(|| Status::invalid_argument(format!("Unknown network '{}'", network)))
+    }
+}
+
+type RootStream = Pin<Box<dyn Stream<Item = Result<RootResponse, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl TreeService for TreeGrpcService {
+    async fn get_root(
+        &self,
+        request: Request<GetRootRequest>,
+    ) -> Result<Response<RootResponse>, Status> {
+        let tree_state = self.tree_state(&request.get_ref().network)?;
+        let tree = tree_state.tree.read().await;
+
+        let root = TreeRoot {
+            root: tree.get_root().await.map_err(|e| status(tree_error(e)))?,
+            leaf_count: tree.leaf_count().await.map_err(|e| status(tree_error(e)))?,
+            elements_count: tree
+                .elements_count()
+                .await
+                .map_err(|e| status(tree_error(e)))?,
+        };
+        Ok(Response::new(root.into()))
+    }
+
+    async fn get_proof(
+        &self,
+        request: Request<GetProofRequest>,
+    ) -> Result<Response<ProofResponse>, Status> {
+        let request = request.into_inner();
+        let tree_state = self.tree_state(&request.network)?;
+        let leaf = parse_commitment_hash(&request.commitment_hash).map_err(|e| status(e.into()))?;
+        let tree = tree_state.tree.read().await;
+
+        let proof = tree_state.proofs.prove(&tree, leaf).await.map_err(status)?;
+        proof_response(leaf, proof)
+    }
+
+    async fn get_proof_at_count(
+        &self,
+        request: Request<GetProofAtCountRequest>,
+    ) -> Result<Response<ProofResponse>, Status> {
+        let request = request.into_inner();
+        let tree_state = self.tree_state(&request.network)?;
+        let leaf = parse_commitment_hash(&request.commitment_hash).map_err(|e| status(e.into()))?;
+        let elements_count = usize::try_from(request.elements_count)
+            .map_err(|_| Status::out_of_range("elements_count is too large"))?;
+        let tree = tree_state.tree.read().await;
+
+        let proof = tree_state
+            .proofs
+            .prove_at(&tree, leaf, elements_count)
+            .await
+            .map_err(status)?;
+        proof_response(leaf, proof)
+    }
+
+    type WatchRootsStream = RootStream;
+
+    async fn watch_roots(
+        &self,
+        request: Request<WatchRootsRequest>,
+    ) -> Result<Response<Self::WatchRootsStream>, Status> {
+        let network = request.into_inner().network;
+        let tree_state = self.tree_state(&network)?;
+
+        // Roots a slow client missed are skipped, the next one supersedes them
+        let roots =
+            BroadcastStream::new(tree_state.roots.subscribe()).filter_map(move |root| match root {
+                Ok(root) => Some(Ok(root.into())),
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    warn!(
+                        "WatchRoots client of network '{}' skipped {} roots",
+                        network, skipped
+                    );
+                    None
+                }
+            });
+        Ok(Response::new(Box::pin(roots)))
+    }
+}
+
+/// Serves the `TreeService` on `addr` until the process stops
+pub async fn serve(
+    addr: SocketAddr,
+    service: TreeGrpcService,
+) -> Result<(), tonic::transport::Error> {
+    info!("Tree gRPC server listening on {}", addr);
+    Server::builder()
+        .add_service(TreeServiceServer::new(service))
+        .serve(addr)
+        .await
+}
+
+/// Serves the `TreeService` on an already bound `listener`
+pub async fn serve_with_listener(
+    listener: TcpListener,
+    service: TreeGrpcService,
+) -> Result<(), tonic::transport::Error> {
+    Server::builder()
+        .add_service(TreeServiceServer::new(service))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+}
+
+fn proof_response(
+    leaf: [u8; 32],
+    proof: Option<InclusionProof>,
+) -> Result<Response<ProofResponse>, Status> {
+    let commitment_hash = format!("0x{}", hex::encode(leaf));
+    let proof = proof.ok_or_else(|| {
+        Status::not_found(format!("Commitment {} is not in the tree", commitment_hash))
+    })?;
+
+    Ok(Response::new(ProofResponse {
+        commitment_hash,
+        leaf_index: proof.leaf_index as u64,
+        siblings: proof.siblings,
+        peaks: proof.peaks,
+        elements_count: proof.elements_count as u64,
+        root: proof.merkle_root,
+    }))
+}
+
+/// Maps tree builder errors onto the gRPC codes clients can act on
+fn status(err: TreeBuilderClientError) -> Status {
+    let message = err.to_string();
+    match err {
+        TreeBuilderClientError::InvalidCommitment(_) => Status::invalid_argument(message),
+        TreeBuilderClientError::InvalidElementsCount { .. } => Status::out_of_range(message),
+        err => {
+            error!("Tree gRPC request failed: {}", err);
+            Status::internal(message)
+        }
+    }
+}
+
+impl From<TreeRoot> for RootResponse {
+    fn from(root: TreeRoot) -> Self {
+        Self {
+            root: format!("0x{}", hex::encode(root.root)),
+            leaf_count: root.leaf_count as u64,
+            elements_count: root.elements_count as u64,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use starknet::core::types::Felt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Notify, RwLock};
+use tracing::{error, info};
+use tree_builder::l2_tree::{L2MerkleTreeBuilder, Proof};
+
+use crate::config::NetworkId;
+use crate::db::database::{
+    fetch_included_withdrawal_commitments, fetch_withdrawals_pending_tree_inclusion,
+    mark_withdrawal_included,
+};
+use crate::tree_builder::client::{tree_error, TreeBuilderClientError};
+
+#[derive(Debug, Clone)]
+pub struct L2TreeBuilderConfig {
+    /// Seconds to wait between two polls for withdrawals awaiting inclusion
+    pub process_interval_sec: u64,
+    /// Maximum number of withdrawals appended per poll
+    pub batch_size: i64,
+}
+
+impl Default for L2TreeBuilderConfig {
+    fn default() -> Self {
+        Self {
+            process_interval_sec: 5,
+            batch_size: 50,
+        }
+    }
+}
+
+/// Inclusion proof of a withdrawal's commitment in the L2 tree, as stored in
+/// `withdrawals.proof`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalInclusionProof {
+    pub leaf_index: i64,
+    /// Position of the leaf among all nodes of the tree
+    pub element_index: usize,
+    pub element_hash: String,
+    pub siblings: Vec<String>,
+    pub peaks: Vec<String>,
+    /// Nodes of the tree the proof was made against
+    pub elements_count: usize,
+    pub root: String,
+}
+
+impl WithdrawalInclusionProof {
+    fn new(proof: Proof, leaf_index: i64, root: [u8; 32]) -> Self {
+        Self {
+            leaf_index,
+            element_index: proof.element_index,
+            element_hash: proof.element_hash,
+            siblings: proof.siblings_hashes,
+            peaks: proof.peaks_hashes,
+            elements_count: proof.elements_count,
+            root: format!("0x{}", hex::encode(root)),
+        }
+    }
+
+    /// The proof in the form [`L2MerkleTreeBuilder::verify_proof`] takes
+    pub fn into_proof(self) -> Proof {
+        Proof {
+            element_index: self.element_index,
+            element_hash: self.element_hash,
+            siblings_hashes: self.siblings,
+            peaks_hashes: self.peaks,
+            elements_count: self.elements_count,
+        }
+    }
+}
+
+/// Appends one network's pending withdrawals to its in-memory L2 Merkle tree, hashed
+/// with Poseidon, and records their leaf index, the resulting root and their inclusion
+/// proof on the withdrawal, which moves on to `included`.
+pub struct L2TreeBuilderClient {
+    db_pool: PgPool,
+    network: NetworkId,
+    config: L2TreeBuilderConfig,
+    tree: Arc<RwLock<L2MerkleTreeBuilder>>,
+    shutdown: Arc<Notify>,
+}
+
+impl L2TreeBuilderClient {
+    pub fn new(db_pool: PgPool, network: NetworkId, config: L2TreeBuilderConfig) -> Self {
+        Self {
+            db_pool,
+            network,
+            config,
+            tree: Arc::new(RwLock::new(L2MerkleTreeBuilder::new())),
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Shared handle to the tree
+    pub fn tree(&self) -> Arc<RwLock<L2MerkleTreeBuilder>> {
+        self.tree.clone()
+    }
+
+    /// Rebuilds the tree, then appends pending withdrawals until [`stop`](Self::stop) is
+    /// called.
+    pub async fn start(&self) -> Result<(), TreeBuilderClientError> {
+        info!(
+            "Starting L2 tree builder client for network {}",
+            self.network
+        );
+        let leaves = self.rebuild_tree_on_startup().await?;
+        info!("L2 tree of {} rebuilt with {} leaves", self.network, leaves);
+
+        let interval = Duration::from_secs(self.config.process_interval_sec);
+        loop {
+            match self.process_pending_withdrawals().await {
+                Ok(included) if included > 0 => {
+                    info!("Included {} withdrawals in the L2 tree", included)
+                }
+                Ok(_) => {}
+                Err(e) => error!("L2 tree inclusion cycle failed: {:?}", e),
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = self.shutdown.notified() => {
+                    info!("L2 tree builder client stopped");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    pub fn stop(&self) {
+        self.shutdown.notify_one();
+    }
+
+    /// Replays the withdrawals already included in the database into a new tree.
+    ///
+    /// Returns the number of leaves in the rebuilt tree.
+    pub async fn rebuild_tree_on_startup(&self) -> Result<usize, TreeBuilderClientError> {
+        let leaves = fetch_included_withdrawal_commitments(&self.db_pool, &self.network)
+            .await?
+            .iter()
+            .map(String::as_str)
+            .map(parse_withdrawal_commitment)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let count = leaves.len();
+        let mut tree = L2MerkleTreeBuilder::new();
+        tree.build_merkle(leaves).await.map_err(tree_error)?;
+        *self.tree.write().await = tree;
+
+        Ok(count)
+    }
+
+    /// Appends pending withdrawals to the tree in the order they were recorded, returning
+    /// how many were included.
+    pub async fn process_pending_withdrawals(&self) -> Result<usize, TreeBuilderClientError> {
+        let withdrawals = fetch_withdrawals_pending_tree_inclusion(
+            &self.db_pool,
+            &self.network,
+            self.config.batch_size,
+        )
+        .await?;
+
+        let mut included = 0;
+        for withdrawal in withdrawals {
+            let leaf = match parse_withdrawal_commitment(&withdrawal.commitment_hash) {
+                Ok(leaf) => leaf,
+                Err(e) => {
+                    error!("Skipping withdrawal {}: {}", withdrawal.id, e);
+                    continue;
+                }
+            };
+
+            let (leaf_index, root, proof) = {
+                let mut tree = self.tree.write().await;
+                let leaf_index = tree.leaf_count().await.map_err(tree_error)? as i64;
+                tree.build_merkle(vec![leaf]).await.map_err(tree_error)?;
+                let root = tree.get_root().await.map_err(tree_error)?;
+                let proof = tree
+                    .get_proof(leaf)
+                    .await
+                    .map_err(tree_error)?
+                    .ok_or_else(|| {
+                        tree_error(format!(
+                            "Appended commitment {} has no proof",
+                            withdrawal.commitment_hash
+                        ))
+                    })?;
+                (leaf_index, root, proof)
+            };
+            let proof = WithdrawalInclusionProof::new(proof, leaf_index, root);
+            let proof_json = serde_json::to_value(&proof).map_err(tree_error)?;
+
+            let mut tx = self.db_pool.begin().await?;
+            mark_withdrawal_included(&mut tx, withdrawal.id, leaf_index, &proof.root, &proof_json)
+                .await?;
+            tx.commit().await?;
+            included += 1;
+        }
+
+        Ok(included)
+    }
+}
+
+/// Decodes a withdrawal's commitment hash, a felt252 as emitted on L2, into the leaf it
+/// is in the tree. Leading zeros may be left out.
+pub fn parse_withdrawal_commitment(
+    commitment_hash: &str,
+) -> Result<[u8; 32], TreeBuilderClientError> {
+    Felt::from_hex(commitment_hash)
+        .map(|felt| felt.to_bytes_be())
+        .map_err(|_| TreeBuilderClientError::InvalidCommitment(commitment_hash.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_withdrawal_commitment_pads_short_felts() {
+        let mut expected = [0u8; 32];
+        expected[30] = 0x12;
+        expected[31] = 0x34;
+        assert_eq!(parse_withdrawal_commitment("0x1234").unwrap(), expected);
+        assert!(parse_withdrawal_commitment("0xzz").is_err());
+    }
+}
@@ -0,0 +1,16 @@
+pub mod client;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod l2_client;
+pub mod proof_cache;
+pub mod rebuild;
+pub mod root_feed;
+
+pub use client::{TreeBuilderClient, TreeBuilderClientError, TreeBuilderConfig};
+pub use l2_client::{L2TreeBuilderClient, L2TreeBuilderConfig, WithdrawalInclusionProof};
+pub use proof_cache::{ProofCache, ProofCacheStats};
+pub use rebuild::{
+    rebuild_tree, DiscrepancyField, TreeDiscrepancy, TreeRebuild, TreeRebuildOptions,
+    TreeRebuildReport,
+};
+pub use root_feed::{RootFeed, TreeRoot};
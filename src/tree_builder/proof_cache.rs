@@ -0,0 +1,284 @@
+use lru::LruCache;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tree_builder::l1_tree::{L1MerkleTreeBuilder, Proof};
+
+use super::client::{tree_error, TreeBuilderClientError};
+use crate::queue::l2_queue::InclusionProof;
+
+/// Proofs kept when `merkle.cache_size` is not configured
+pub const DEFAULT_PROOF_CACHE_SIZE: usize = 1000;
+
+/// Merkle root and leaf an inclusion proof was computed for
+type ProofKey = ([u8; 32], [u8; 32]);
+
+/// Bounded LRU of the inclusion proofs served from a network's tree.
+///
+/// Entries are keyed by the root they prove against, so appending a leaf never makes
+/// one wrong, it only stops it from being looked up. Clones share the same entries and
+/// counters.
+#[derive(Clone)]
+pub struct ProofCache {
+    entries: Arc<Mutex<LruCache<ProofKey, InclusionProof>>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+/// Counters of a [`ProofCache`] since it was created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+impl ProofCacheStats {
+    /// Share of lookups answered from the cache, 0 before the first lookup
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+impl ProofCache {
+    /// Cache holding up to `capacity` proofs, at least one
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Arc::new(Mutex::new(LruCache::new(capacity))),
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Returns the cached proof of `leaf` against `root`, calling `prove` on a miss.
+    ///
+    /// A leaf `prove` does not find is not cached, it may be appended later.
+    pub async fn get_or_try_insert_with<F, Fut, E>(
+        &self,
+        root: [u8; 32],
+        leaf: [u8; 32],
+        prove: F,
+    ) -> Result<Option<InclusionProof>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Option<InclusionProof>, E>>,
+    {
+        let key = (root, leaf);
+        if let Some(proof) = self.entries.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(proof.clone()));
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let proof = prove().await?;
+        if let Some(proof) = &proof {
+            self.entries.lock().unwrap().put(key, proof.clone());
+        }
+        Ok(proof)
+    }
+
+    /// Proves `leaf` against the current root of `tree`, computing the proof on a miss
+    pub async fn prove(
+        &self,
+        tree: &L1MerkleTreeBuilder,
+        leaf: [u8; 32],
+    ) -> Result<Option<InclusionProof>, TreeBuilderClientError> {
+        let root = tree.get_root().await.map_err(tree_error)?;
+        self.get_or_try_insert_with(root, leaf, || inclusion_proof(tree, leaf, root))
+            .await
+    }
+
+    /// Proves `leaf` against the root `tree` had when it held `elements_count` elements,
+    /// computing the proof on a miss
+    pub async fn prove_at(
+        &self,
+        tree: &L1MerkleTreeBuilder,
+        leaf: [u8; 32],
+        elements_count: usize,
+    ) -> Result<Option<InclusionProof>, TreeBuilderClientError> {
+        let current = tree.elements_count().await.map_err(tree_error)?;
+        if elements_count == 0
+            || elements_count > current
+            || !L1MerkleTreeBuilder::is_valid_size(elements_count)
+        {
+            return Err(TreeBuilderClientError::InvalidElementsCount {
+                requested: elements_count,
+                current,
+            });
+        }
+
+        let root = tree.get_root_at(elements_count).await.map_err(tree_error)?;
+        self.get_or_try_insert_with(root, leaf, || async {
+            let proof = tree
+                .get_proof_at(leaf, elements_count)
+                .await
+                .map_err(tree_error)?;
+            Ok(proof.map(|proof| into_inclusion_proof(proof, root)))
+        })
+        .await
+    }
+
+    /// Drops every entry, for when the tree is replaced rather than appended to
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn stats(&self) -> ProofCacheStats {
+        let entries = self.entries.lock().unwrap();
+        ProofCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: entries.len(),
+            capacity: entries.cap().get(),
+        }
+    }
+}
+
+impl Default for ProofCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROOF_CACHE_SIZE)
+    }
+}
+
+/// Computes the inclusion proof of `leaf` in `tree`, whose current root is `root`
+pub async fn inclusion_proof(
+    tree: &L1MerkleTreeBuilder,
+    leaf: [u8; 32],
+    root: [u8; 32],
+) -> Result<Option<InclusionProof>, TreeBuilderClientError> {
+    let proof = tree.get_proof(leaf).await.map_err(tree_error)?;
+    Ok(proof.map(|proof| into_inclusion_proof(proof, root)))
+}
+
+fn into_inclusion_proof(proof: Proof, root: [u8; 32]) -> InclusionProof {
+    InclusionProof {
+        leaf_index: proof.element_index,
+        siblings: proof.siblings_hashes,
+        peaks: proof.peaks_hashes,
+        elements_count: proof.elements_count,
+        merkle_root: format!("0x{}", hex::encode(root)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::sync::atomic::AtomicUsize;
+
+    fn proof(leaf_index: usize) -> InclusionProof {
+        InclusionProof {
+            leaf_index,
+            siblings: Vec::new(),
+            peaks: Vec::new(),
+            elements_count: leaf_index,
+            merkle_root: "0x0".to_string(),
+        }
+    }
+
+    /// Looks `leaf` up under `root`, counting the proofs computed in `computed`
+    async fn lookup(
+        cache: &ProofCache,
+        root: u8,
+        leaf: u8,
+        computed: &AtomicUsize,
+    ) -> Option<InclusionProof> {
+        cache
+            .get_or_try_insert_with([root; 32], [leaf; 32], || async {
+                computed.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, Infallible>(Some(proof(leaf as usize)))
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hit_does_not_compute_the_proof_again() {
+        let cache = ProofCache::new(4);
+        let computed = AtomicUsize::new(0);
+
+        assert_eq!(lookup(&cache, 1, 7, &computed).await, Some(proof(7)));
+        assert_eq!(lookup(&cache, 1, 7, &computed).await, Some(proof(7)));
+
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_hit_ratio_counts_every_lookup() {
+        let cache = ProofCache::new(4);
+        let computed = AtomicUsize::new(0);
+        assert_eq!(cache.stats().hit_ratio(), 0.0);
+
+        for _ in 0..4 {
+            lookup(&cache, 1, 7, &computed).await;
+        }
+
+        assert_eq!(cache.stats().hit_ratio(), 0.75);
+    }
+
+    #[tokio::test]
+    async fn test_new_root_misses() {
+        let cache = ProofCache::new(4);
+        let computed = AtomicUsize::new(0);
+
+        lookup(&cache, 1, 7, &computed).await;
+        lookup(&cache, 2, 7, &computed).await;
+
+        assert_eq!(computed.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_proof_is_evicted() {
+        let cache = ProofCache::new(2);
+        let computed = AtomicUsize::new(0);
+
+        lookup(&cache, 1, 1, &computed).await;
+        lookup(&cache, 1, 2, &computed).await;
+        lookup(&cache, 1, 1, &computed).await;
+        lookup(&cache, 1, 3, &computed).await;
+        assert_eq!(computed.load(Ordering::SeqCst), 3);
+
+        // Leaf 2 was the least recently used when leaf 3 came in
+        lookup(&cache, 1, 1, &computed).await;
+        assert_eq!(computed.load(Ordering::SeqCst), 3);
+        lookup(&cache, 1, 2, &computed).await;
+        assert_eq!(computed.load(Ordering::SeqCst), 4);
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[tokio::test]
+    async fn test_missing_leaf_is_not_cached() {
+        let cache = ProofCache::new(2);
+
+        let found = cache
+            .get_or_try_insert_with([1; 32], [1; 32], || async { Ok::<_, Infallible>(None) })
+            .await
+            .unwrap();
+
+        assert_eq!(found, None);
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_clear_drops_every_proof() {
+        let cache = ProofCache::new(4);
+        let computed = AtomicUsize::new(0);
+        lookup(&cache, 1, 1, &computed).await;
+        lookup(&cache, 1, 2, &computed).await;
+
+        cache.clear();
+
+        assert_eq!(cache.stats().entries, 0);
+        lookup(&cache, 1, 1, &computed).await;
+        assert_eq!(computed.load(Ordering::SeqCst), 3);
+    }
+}
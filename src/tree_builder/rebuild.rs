@@ -0,0 +1,400 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::{debug, info, warn};
+use tree_builder::l1_tree::L1MerkleTreeBuilder;
+use utoipa::ToSchema;
+
+use super::client::{tree_error, TreeBuilderClientError};
+use crate::config::NetworkId;
+use crate::db::database::{
+    delete_tree_rebuild_checkpoint, fetch_included_commitments_up_to,
+    fetch_included_deposits_after, fetch_local_roots_between, fetch_tree_rebuild_checkpoint,
+    insert_merkle_root, repair_deposit_inclusion, save_tree_rebuild_checkpoint, IncludedDeposit,
+    RootSource, TreeRebuildCheckpoint,
+};
+use crate::utils::parse_commitment_hash;
+
+/// Stored value of a deposit that disagrees with the rebuilt tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancyField {
+    /// `deposits.leaf_index`, the deposit's position in the tree
+    LeafIndex,
+    /// `deposits.merkle_root`, the root once the deposit was appended
+    MerkleRoot,
+    /// The local `merkle_roots` entry for the tree ending with the deposit
+    LocalRoot,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TreeDiscrepancy {
+    pub deposit_id: i32,
+    pub field: DiscrepancyField,
+    /// Value found in the database, `None` when it is missing
+    pub stored: Option<String>,
+    pub recomputed: String,
+}
+
+/// Outcome of a tree rebuild
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TreeRebuildReport {
+    pub network: String,
+    /// Whether the discrepancies were repaired
+    pub fix: bool,
+    pub leaves_checked: i64,
+    /// Root of the rebuilt tree, `None` when no deposit is included
+    pub root: Option<String>,
+    pub discrepancies: Vec<TreeDiscrepancy>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeRebuildOptions {
+    /// Deposits checked, and repaired in one transaction, at a time
+    pub batch_size: i64,
+    /// Repair the discrepancies found instead of only reporting them
+    pub fix: bool,
+}
+
+impl Default for TreeRebuildOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 500,
+            fix: false,
+        }
+    }
+}
+
+/// Rebuilds a network's L1 tree off to the side from its included deposits, checking
+/// the leaf index and roots stored for every deposit against it.
+///
+/// Each batch is repaired in its own transaction together with the checkpoint, so the
+/// live [`TreeBuilderClient`](super::TreeBuilderClient) keeps including deposits while
+/// a rebuild runs, and a rebuild interrupted midway resumes after its last batch.
+pub struct TreeRebuild {
+    pool: PgPool,
+    network: NetworkId,
+    options: TreeRebuildOptions,
+    tree: L1MerkleTreeBuilder,
+    leaves_checked: i64,
+    last_leaf_index: i64,
+    discrepancies: Vec<TreeDiscrepancy>,
+}
+
+impl TreeRebuild {
+    /// Prepares a rebuild of `network`'s tree, picking up the checkpoint of an
+    /// interrupted rebuild that had the same `fix` setting.
+    pub async fn resume(
+        pool: PgPool,
+        network: NetworkId,
+        options: TreeRebuildOptions,
+    ) -> Result<Self, TreeBuilderClientError> {
+        let mut rebuild = Self {
+            pool,
+            network,
+            options,
+            tree: L1MerkleTreeBuilder::new(),
+            leaves_checked: 0,
+            last_leaf_index: -1,
+            discrepancies: Vec::new(),
+        };
+
+        match fetch_tree_rebuild_checkpoint(&rebuild.pool, &rebuild.network).await? {
+            Some(checkpoint) if checkpoint.fix == rebuild.options.fix => {
+                rebuild.restore(checkpoint).await?;
+            }
+            Some(_) => info!(
+                "Starting the rebuild of {} over, its checkpoint was left by a rebuild with fix = {}",
+                rebuild.network, !rebuild.options.fix
+            ),
+            None => {}
+        }
+
+        Ok(rebuild)
+    }
+
+    /// Replays the leaves checked before the checkpoint into the tree
+    async fn restore(
+        &mut self,
+        checkpoint: TreeRebuildCheckpoint,
+    ) -> Result<(), TreeBuilderClientError> {
+        let leaves =
+            fetch_included_commitments_up_to(&self.pool, &self.network, checkpoint.last_leaf_index)
+                .await?
+                .iter()
+                .map(String::as_str)
+                .map(parse_commitment_hash)
+                .collect::<Result<Vec<_>, _>>()?;
+
+        // Deposits renumbered since the checkpoint was taken invalidate it
+        if leaves.len() as i64 != checkpoint.leaves_checked {
+            warn!(
+                "Rebuild checkpoint of {} expected {} leaves up to index {}, found {}; starting over",
+                self.network,
+                checkpoint.leaves_checked,
+                checkpoint.last_leaf_index,
+                leaves.len()
+            );
+            return Ok(());
+        }
+
+        self.discrepancies = serde_json::from_value(checkpoint.discrepancies)
+            .map_err(|e| TreeBuilderClientError::Checkpoint(e.to_string()))?;
+        self.tree.build_merkle(leaves).await.map_err(tree_error)?;
+        self.leaves_checked = checkpoint.leaves_checked;
+        self.last_leaf_index = checkpoint.last_leaf_index;
+        info!(
+            "Resuming the rebuild of {} after {} leaves",
+            self.network, self.leaves_checked
+        );
+        Ok(())
+    }
+
+    /// Checks, and repairs if requested, the next batch of deposits.
+    ///
+    /// Returns `false` once every included deposit has been checked.
+    pub async fn step(&mut self) -> Result<bool, TreeBuilderClientError> {
+        let deposits = fetch_included_deposits_after(
+            &self.pool,
+            &self.network,
+            self.last_leaf_index,
+            self.options.batch_size,
+        )
+        .await?;
+        if deposits.is_empty() {
+            return Ok(false);
+        }
+
+        let first_count = self.leaves_checked + 1;
+        let local_roots = fetch_local_roots_between(
+            &self.pool,
+            &self.network,
+            first_count,
+            first_count + deposits.len() as i64 - 1,
+        )
+        .await?;
+
+        let mut checked = Vec::with_capacity(deposits.len());
+        for deposit in &deposits {
+            let leaf = parse_commitment_hash(&deposit.commitment_hash)?;
+            self.tree
+                .build_merkle(vec![leaf])
+                .await
+                .map_err(tree_error)?;
+            let position = self.leaves_checked;
+            self.leaves_checked += 1;
+
+            let root = format!(
+                "0x{}",
+                hex::encode(self.tree.get_root().await.map_err(tree_error)?)
+            );
+            let local_root = local_roots.get(&self.leaves_checked).map(String::as_str);
+            let found = check_deposit(deposit, position, &root, local_root);
+            checked.push((deposit, position, root, found));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for (deposit, position, root, found) in &checked {
+            if self.options.fix {
+                if found
+                    .iter()
+                    .any(|discrepancy| discrepancy.field != DiscrepancyField::LocalRoot)
+                {
+                    repair_deposit_inclusion(&mut tx, deposit.id, *position, root).await?;
+                }
+                if found
+                    .iter()
+                    .any(|discrepancy| discrepancy.field == DiscrepancyField::LocalRoot)
+                {
+                    insert_merkle_root(
+                        &mut tx,
+                        &self.network,
+                        position + 1,
+                        root,
+                        None,
+                        RootSource::Local,
+                    )
+                    .await?;
+                }
+            }
+            for discrepancy in found {
+                warn!(
+                    "Deposit {} of {} has {:?} {:?}, the rebuilt tree gives {}",
+                    discrepancy.deposit_id,
+                    self.network,
+                    discrepancy.field,
+                    discrepancy.stored,
+                    discrepancy.recomputed
+                );
+            }
+        }
+
+        // Repaired rows now sit at their position, the others where they were found
+        let (last_deposit, last_position, _, _) = checked.last().expect("batch is not empty");
+        self.last_leaf_index = if self.options.fix {
+            *last_position
+        } else {
+            last_deposit.leaf_index
+        };
+        self.discrepancies
+            .extend(checked.into_iter().flat_map(|(_, _, _, found)| found));
+
+        let checkpoint = TreeRebuildCheckpoint {
+            fix: self.options.fix,
+            leaves_checked: self.leaves_checked,
+            last_leaf_index: self.last_leaf_index,
+            discrepancies: serde_json::to_value(&self.discrepancies)
+                .map_err(|e| TreeBuilderClientError::Checkpoint(e.to_string()))?,
+        };
+        save_tree_rebuild_checkpoint(&mut tx, &self.network, &checkpoint).await?;
+        tx.commit().await?;
+
+        debug!(
+            "Rebuild of {} checked {} leaves",
+            self.network, self.leaves_checked
+        );
+        Ok(true)
+    }
+
+    /// Checks the remaining deposits, then clears the checkpoint.
+    pub async fn run(mut self) -> Result<TreeRebuildReport, TreeBuilderClientError> {
+        while self.step().await? {}
+        delete_tree_rebuild_checkpoint(&self.pool, &self.network).await?;
+
+        let root = match self.leaves_checked {
+            0 => None,
+            _ => Some(format!(
+                "0x{}",
+                hex::encode(self.tree.get_root().await.map_err(tree_error)?)
+            )),
+        };
+        let outcome = if self.options.fix {
+            "repaired"
+        } else {
+            "found"
+        };
+        info!(
+            "Rebuilt the tree of {} from {} leaves, {} discrepancies {}",
+            self.network,
+            self.leaves_checked,
+            self.discrepancies.len(),
+            outcome
+        );
+
+        Ok(TreeRebuildReport {
+            network: self.network.to_string(),
+            fix: self.options.fix,
+            leaves_checked: self.leaves_checked,
+            root,
+            discrepancies: self.discrepancies,
+        })
+    }
+}
+
+/// Rebuilds `network`'s tree to completion, resuming an interrupted rebuild.
+///
+/// Without `fix` nothing but the checkpoint is written.
+pub async fn rebuild_tree(
+    pool: &PgPool,
+    network: &NetworkId,
+    options: TreeRebuildOptions,
+) -> Result<TreeRebuildReport, TreeBuilderClientError> {
+    TreeRebuild::resume(pool.clone(), network.clone(), options)
+        .await?
+        .run()
+        .await
+}
+
+/// Compares what is stored for the deposit at `position` with the rebuilt tree, whose
+/// root once it is appended is `root`
+fn check_deposit(
+    deposit: &IncludedDeposit,
+    position: i64,
+    root: &str,
+    local_root: Option<&str>,
+) -> Vec<TreeDiscrepancy> {
+    let discrepancy = |field, stored: Option<&str>, recomputed: &str| TreeDiscrepancy {
+        deposit_id: deposit.id,
+        field,
+        stored: stored.map(str::to_string),
+        recomputed: recomputed.to_string(),
+    };
+    let matches_root = |stored: Option<&str>| stored.is_some_and(|r| r.eq_ignore_ascii_case(root));
+
+    let mut found = Vec::new();
+    if deposit.leaf_index != position {
+        found.push(discrepancy(
+            DiscrepancyField::LeafIndex,
+            Some(&deposit.leaf_index.to_string()),
+            &position.to_string(),
+        ));
+    }
+    if !matches_root(deposit.merkle_root.as_deref()) {
+        found.push(discrepancy(
+            DiscrepancyField::MerkleRoot,
+            deposit.merkle_root.as_deref(),
+            root,
+        ));
+    }
+    if !matches_root(local_root) {
+        found.push(discrepancy(DiscrepancyField::LocalRoot, local_root, root));
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT: &str = "0xabcdef";
+
+    fn deposit(leaf_index: i64, merkle_root: Option<&str>) -> IncludedDeposit {
+        IncludedDeposit {
+            id: 7,
+            commitment_hash: format!("0x{}", "01".repeat(32)),
+            leaf_index,
+            merkle_root: merkle_root.map(str::to_string),
+        }
+    }
+
+    fn fields(found: &[TreeDiscrepancy]) -> Vec<DiscrepancyField> {
+        found.iter().map(|discrepancy| discrepancy.field).collect()
+    }
+
+    #[test]
+    fn test_consistent_deposit_has_no_discrepancy() {
+        let found = check_deposit(&deposit(3, Some("0xABCDEF")), 3, ROOT, Some(ROOT));
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_wrong_root_is_reported() {
+        let found = check_deposit(&deposit(3, Some("0x1234")), 3, ROOT, Some(ROOT));
+        assert_eq!(
+            found,
+            vec![TreeDiscrepancy {
+                deposit_id: 7,
+                field: DiscrepancyField::MerkleRoot,
+                stored: Some("0x1234".to_string()),
+                recomputed: ROOT.to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_missing_values_are_reported() {
+        let found = check_deposit(&deposit(3, None), 3, ROOT, None);
+        assert_eq!(
+            fields(&found),
+            vec![DiscrepancyField::MerkleRoot, DiscrepancyField::LocalRoot]
+        );
+        assert!(found.iter().all(|discrepancy| discrepancy.stored.is_none()));
+    }
+
+    #[test]
+    fn test_leaf_index_gap_is_reported() {
+        let found = check_deposit(&deposit(5, Some(ROOT)), 3, ROOT, Some(ROOT));
+        assert_eq!(fields(&found), vec![DiscrepancyField::LeafIndex]);
+        assert_eq!(found[0].stored.as_deref(), Some("5"));
+        assert_eq!(found[0].recomputed, "3");
+    }
+}
@@ -0,0 +1,45 @@
+use tokio::sync::broadcast;
+
+/// Roots kept for subscribers that fall behind before the oldest is dropped
+pub const DEFAULT_ROOT_FEED_CAPACITY: usize = 64;
+
+/// Root of a network's tree after the tree builder appended a batch of deposits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeRoot {
+    pub root: [u8; 32],
+    pub leaf_count: usize,
+    /// Leaves and inner nodes, the size inclusion proofs against this root refer to
+    pub elements_count: usize,
+}
+
+/// Broadcasts every root the tree builder reaches, e.g. to gRPC `WatchRoots` streams.
+///
+/// Publishing without subscribers drops the root. Clones share the same subscribers.
+#[derive(Clone)]
+pub struct RootFeed {
+    sender: broadcast::Sender<TreeRoot>,
+}
+
+impl RootFeed {
+    /// Feed holding up to `capacity` roots for slow subscribers, at least one
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Receives the roots published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<TreeRoot> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, root: TreeRoot) {
+        // Only fails when nobody is subscribed
+        let _ = self.sender.send(root);
+    }
+}
+
+impl Default for RootFeed {
+    fn default() -> Self {
+        Self::new(DEFAULT_ROOT_FEED_CAPACITY)
+    }
+}
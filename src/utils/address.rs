@@ -0,0 +1,67 @@
+use starknet_crypto::Felt;
+use thiserror::Error;
+
+/// Starknet contract addresses are non-zero felts below `2^251`, a stricter range than
+/// the field itself.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    #[error("Starknet address {0} is not a valid hex felt (0x...)")]
+    InvalidHex(String),
+
+    #[error("Starknet address cannot be zero")]
+    Zero,
+
+    #[error("Starknet address {0} is not below 2^251")]
+    OutOfRange(String),
+}
+
+/// Parses `hex` as a Starknet address, rejecting zero and values of `2^251` or more.
+pub fn validate_starknet_address(hex: &str) -> Result<Felt, AddressError> {
+    let address = Felt::from_hex(hex).map_err(|_| AddressError::InvalidHex(hex.to_string()))?;
+    if address == Felt::ZERO {
+        return Err(AddressError::Zero);
+    }
+    // 2^251 is the lowest value with bit 251 set, i.e. 0x08 in the leading byte
+    if address.to_bytes_be()[0] >= 0x08 {
+        return Err(AddressError::OutOfRange(hex.to_string()));
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_POW_251: &str = "0x800000000000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_valid_addresses_are_parsed() {
+        assert_eq!(validate_starknet_address("0x123"), Ok(Felt::from(0x123u64)));
+        let max = "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+        assert_eq!(
+            validate_starknet_address(max),
+            Ok(Felt::from_hex(TWO_POW_251).unwrap() - Felt::ONE)
+        );
+    }
+
+    #[test]
+    fn test_two_pow_251_is_rejected() {
+        assert_eq!(
+            validate_starknet_address(TWO_POW_251),
+            Err(AddressError::OutOfRange(TWO_POW_251.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_zero_is_rejected() {
+        assert_eq!(validate_starknet_address("0x0"), Err(AddressError::Zero));
+    }
+
+    #[test]
+    fn test_malformed_hex_is_rejected() {
+        assert_eq!(
+            validate_starknet_address("0xtest123"),
+            Err(AddressError::InvalidHex("0xtest123".to_string()))
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use bigdecimal::num_bigint::{BigInt, Sign};
+use sqlx::types::BigDecimal;
+use starknet_crypto::Felt;
+use thiserror::Error;
+
+/// Amounts are `uint256` on L1 and `u256` on L2; stored as `NUMERIC(78, 0)` they are
+/// never truncated, but not every `BigDecimal` is a valid amount.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AmountError {
+    #[error("Amount {0} is negative")]
+    Negative(BigDecimal),
+
+    #[error("Amount {0} is not a whole number")]
+    Fractional(BigDecimal),
+
+    #[error("Amount {0} does not fit in a u256")]
+    TooLarge(BigDecimal),
+}
+
+/// Builds an amount from the two 128-bit halves of a `u256`.
+pub fn u256_to_amount(low: u128, high: u128) -> BigDecimal {
+    let value = (BigInt::from(high) << 128u32) + low;
+    BigDecimal::new(value, 0)
+}
+
+/// Splits an amount into the `(low, high)` 128-bit halves of a `u256`.
+pub fn amount_to_u256(amount: &BigDecimal) -> Result<(u128, u128), AmountError> {
+    if amount.sign() == Sign::Minus {
+        return Err(AmountError::Negative(amount.clone()));
+    }
+    if !amount.is_integer() {
+        return Err(AmountError::Fractional(amount.clone()));
+    }
+
+    let (value, _) = amount.with_scale(0).into_bigint_and_exponent();
+    let (_, digits) = value.to_bytes_be();
+    if digits.len() > 32 {
+        return Err(AmountError::TooLarge(amount.clone()));
+    }
+
+    let mut bytes = [0u8; 32];
+    bytes[32 - digits.len()..].copy_from_slice(&digits);
+    let high = u128::from_be_bytes(bytes[..16].try_into().unwrap());
+    let low = u128::from_be_bytes(bytes[16..].try_into().unwrap());
+    Ok((low, high))
+}
+
+/// Encodes an amount as Cairo serializes a `u256`: the low felt, then the high felt.
+pub fn amount_to_u256_felts(amount: &BigDecimal) -> Result<[Felt; 2], AmountError> {
+    let (low, high) = amount_to_u256(amount)?;
+    Ok([Felt::from(low), Felt::from(high)])
+}
+
+/// Reads an amount emitted as `u256` low/high felts, `None` when either half is not
+/// valid hex or does not fit in 128 bits.
+pub fn u256_felts_to_amount(low: &str, high: &str) -> Option<BigDecimal> {
+    Some(u256_to_amount(felt_to_u128(low)?, felt_to_u128(high)?))
+}
+
+/// Reads a felt, e.g. a fee paid on Starknet, as a whole amount
+pub fn felt_to_amount(felt: &Felt) -> BigDecimal {
+    BigDecimal::new(BigInt::from_bytes_be(Sign::Plus, &felt.to_bytes_be()), 0)
+}
+
+fn felt_to_u128(hex: &str) -> Option<u128> {
+    let bytes = Felt::from_hex(hex).ok()?.to_bytes_be();
+    if bytes[..16].iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(u128::from_be_bytes(bytes[16..].try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn amount(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_amount_round_trips_through_u256_halves() {
+        for (low, high) in [
+            (0, 0),
+            (1000, 0),
+            (u128::MAX, 0),
+            (5, 1),
+            (u128::MAX, u128::MAX),
+        ] {
+            assert_eq!(
+                amount_to_u256(&u256_to_amount(low, high)).unwrap(),
+                (low, high)
+            );
+        }
+    }
+
+    #[test]
+    fn test_amount_above_u128_uses_high_half() {
+        // 2^128 + 7
+        let value = amount("340282366920938463463374607431768211463");
+        assert_eq!(amount_to_u256(&value).unwrap(), (7, 1));
+        assert_eq!(
+            amount_to_u256_felts(&value).unwrap(),
+            [Felt::from(7u8), Felt::from(1u8)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_amounts_are_rejected() {
+        assert!(matches!(
+            amount_to_u256(&amount("-1")),
+            Err(AmountError::Negative(_))
+        ));
+        assert!(matches!(
+            amount_to_u256(&amount("1.5")),
+            Err(AmountError::Fractional(_))
+        ));
+        // 2^256
+        let too_large = amount(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936",
+        );
+        assert!(matches!(
+            amount_to_u256(&too_large),
+            Err(AmountError::TooLarge(_))
+        ));
+        // A trailing zero fraction is still a whole number
+        assert_eq!(amount_to_u256(&amount("12.000")).unwrap(), (12, 0));
+    }
+
+    #[test]
+    fn test_u256_felts_to_amount() {
+        assert_eq!(u256_felts_to_amount("0x1000", "0x0"), Some(amount("4096")));
+        assert_eq!(
+            u256_felts_to_amount("0xffffffffffffffff", "0x1"),
+            Some(amount("340282366920938463481821351505477763071"))
+        );
+        // Each half of a u256 is at most 128 bits
+        assert_eq!(
+            u256_felts_to_amount(&format!("0x1{}", "0".repeat(32)), "0x0"),
+            None
+        );
+        assert_eq!(u256_felts_to_amount("zz", "0x0"), None);
+    }
+}
@@ -0,0 +1,123 @@
+use thiserror::Error;
+
+/// Deposit commitment hashes are stored as `0x` and 64 hex digits, the 32 bytes of the
+/// leaf they are in the L1 tree.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CommitmentParseError {
+    #[error("Commitment hash is empty")]
+    Empty,
+
+    #[error("Commitment hash {0} contains non-hex digits")]
+    InvalidHex(String),
+
+    #[error("Commitment hash {0} has an odd number of hex digits")]
+    OddLength(String),
+
+    #[error("Commitment hash {hash} is {bytes} bytes long, not 32")]
+    WrongLength { hash: String, bytes: usize },
+}
+
+/// Decodes a deposit's commitment hash, with or without `0x`, into its 32 bytes.
+///
+/// Leading zeros may not be left out, so every commitment has exactly one spelling up
+/// to the case of its digits.
+pub fn parse_commitment_hash(commitment_hash: &str) -> Result<[u8; 32], CommitmentParseError> {
+    let digits = commitment_hash
+        .strip_prefix("0x")
+        .unwrap_or(commitment_hash);
+    if digits.is_empty() {
+        return Err(CommitmentParseError::Empty);
+    }
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(CommitmentParseError::InvalidHex(
+            commitment_hash.to_string(),
+        ));
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(CommitmentParseError::OddLength(commitment_hash.to_string()));
+    }
+
+    // Only pairs of hex digits are left, which always decode
+    let bytes = hex::decode(digits).unwrap();
+    let len = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| CommitmentParseError::WrongLength {
+            hash: commitment_hash.to_string(),
+            bytes: len,
+        })
+}
+
+/// Formats 32 bytes as a deposit's commitment hash, the inverse of
+/// [`parse_commitment_hash`]
+pub fn commitment_hash_hex(commitment: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(commitment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_hash_is_parsed_with_or_without_prefix() {
+        let digits = "ab".repeat(32);
+        assert_eq!(
+            parse_commitment_hash(&format!("0x{}", digits)),
+            Ok([0xab; 32])
+        );
+        assert_eq!(parse_commitment_hash(&digits), Ok([0xab; 32]));
+        assert_eq!(parse_commitment_hash(&"AB".repeat(32)), Ok([0xab; 32]));
+    }
+
+    #[test]
+    fn test_empty_commitment_hash_is_rejected() {
+        assert_eq!(parse_commitment_hash(""), Err(CommitmentParseError::Empty));
+        assert_eq!(
+            parse_commitment_hash("0x"),
+            Err(CommitmentParseError::Empty)
+        );
+    }
+
+    #[test]
+    fn test_odd_length_commitment_hash_is_rejected() {
+        let hash = format!("0x{}", "a".repeat(63));
+        assert_eq!(
+            parse_commitment_hash(&hash),
+            Err(CommitmentParseError::OddLength(hash))
+        );
+    }
+
+    #[test]
+    fn test_non_hex_commitment_hash_is_rejected() {
+        let hash = format!("0x{}zz", "ab".repeat(31));
+        assert_eq!(
+            parse_commitment_hash(&hash),
+            Err(CommitmentParseError::InvalidHex(hash))
+        );
+        assert!(matches!(
+            parse_commitment_hash("0x0x12"),
+            Err(CommitmentParseError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_commitment_hash_of_other_than_32_bytes_is_rejected() {
+        for bytes in [1, 31, 33] {
+            let hash = format!("0x{}", "ab".repeat(bytes));
+            assert_eq!(
+                parse_commitment_hash(&hash),
+                Err(CommitmentParseError::WrongLength { hash, bytes })
+            );
+        }
+    }
+
+    #[test]
+    fn test_formatted_commitment_hash_parses_back() {
+        let mut commitment = [0u8; 32];
+        commitment[31] = 0x0a;
+
+        let hash = commitment_hash_hex(&commitment);
+        assert_eq!(hash, format!("0x{}0a", "0".repeat(62)));
+        assert_eq!(parse_commitment_hash(&hash), Ok(commitment));
+    }
+}
@@ -0,0 +1,72 @@
+/// The Stark field prime `2^251 + 17 * 2^192 + 1`. Values hashed into the L2 tree or
+/// passed as calldata must be strictly below it, or Starknet silently reduces them.
+pub const STARK_PRIME_HEX: &str =
+    "0x0800000000000011000000000000000000000000000000000000000000000001";
+
+/// Big-endian bytes of [`STARK_PRIME_HEX`]
+const STARK_PRIME: [u8; 32] = [
+    0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// Returns whether `hex` (with or without `0x`) is a felt252, i.e. below the Stark prime.
+///
+/// Unlike `Felt::from_hex`, which reduces out-of-range values modulo the prime, this
+/// rejects them, so two distinct 256-bit hashes can never map to the same felt.
+pub fn is_valid_felt252(hex: &str) -> bool {
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return false;
+    }
+    let digits = digits.trim_start_matches('0');
+    if digits.len() > 64 {
+        return false;
+    }
+
+    let padded = format!("{:0>64}", digits);
+    let mut bytes = [0u8; 32];
+    for (byte, pair) in bytes.iter_mut().zip(padded.as_bytes().chunks(2)) {
+        // Both digits were checked above
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap();
+    }
+    bytes < STARK_PRIME
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_below_the_prime_are_felts() {
+        assert!(is_valid_felt252("0x0"));
+        assert!(is_valid_felt252("0x123"));
+        assert!(is_valid_felt252("abc"));
+        // The largest felt, P - 1
+        assert!(is_valid_felt252(
+            "0x0800000000000011000000000000000000000000000000000000000000000000"
+        ));
+        assert!(is_valid_felt252(
+            "0x07ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+        ));
+    }
+
+    #[test]
+    fn test_prime_and_above_are_rejected() {
+        assert!(!is_valid_felt252(STARK_PRIME_HEX));
+        assert!(!is_valid_felt252(
+            "0x0800000000000011000000000000000000000000000000000000000000000002"
+        ));
+        assert!(!is_valid_felt252(&format!("0x{}", "f".repeat(64))));
+        // Leading zeros do not count towards the 64 digits
+        assert!(!is_valid_felt252(&format!("0x00{}", &STARK_PRIME_HEX[2..])));
+        assert!(is_valid_felt252(&format!("0x{}1", "0".repeat(64))));
+        assert!(!is_valid_felt252(&format!("0x1{}", "0".repeat(64))));
+    }
+
+    #[test]
+    fn test_malformed_hex_is_rejected() {
+        assert!(!is_valid_felt252(""));
+        assert!(!is_valid_felt252("0x"));
+        assert!(!is_valid_felt252("0xzz"));
+    }
+}
@@ -1,7 +1,40 @@
 use sha3::{Digest, Keccak256};
 use starknet_crypto::{poseidon_hash, Felt, PoseidonHasher};
+use thiserror::Error;
+
+/// Ways [`BurnData`] can deviate from its canonical form
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BurnDataError {
+    #[error("Caller is not a hex string: {0}")]
+    InvalidCallerHex(String),
+
+    #[error("Caller must be exactly 64 hex digits (32 bytes), got {0}")]
+    InvalidCallerLength(usize),
+
+    #[error("Caller is not a valid Starknet field element")]
+    CallerNotFieldElement,
+
+    #[error("Amount must be greater than zero")]
+    ZeroAmount,
+
+    #[error("Timestamp must be greater than zero")]
+    ZeroTimestamp,
+}
+
+/// EIP-712 type of [`BurnData`], hashed into the type hash prefixing its encoded fields
+pub const BURN_DATA_EIP712_TYPE: &str =
+    "BurnData(bytes32 caller,uint256 amount,uint256 nonce,uint256 timeStamp)";
 
 /// Data structure representing the burn data to be hashed
+///
+/// The commitment hash is computed over a canonical 128-byte encoding:
+/// - `caller`: 32 bytes, given as exactly 64 hex digits (optionally `0x`-prefixed) so it is
+///   zero-padded by the client rather than by us, and below the Starknet field prime so
+///   no two callers can refer to the same L2 address
+/// - `amount`, `nonce`, `time_stamp`: each a big-endian `u64` left-padded to a 32-byte
+///   word, as `abi.encodePacked` lays out a `uint256`
+///
+/// Call [`validate`](Self::validate) before hashing untrusted data.
 #[derive(Debug, Clone)]
 pub struct BurnData {
     pub caller: String,  // stark_pubkey (user's starknet address)
@@ -20,6 +53,30 @@ impl BurnData {
         }
     }
 
+    /// Checks that the data is in canonical form, so that a commitment hash can only be
+    /// produced by one encoding of the burn.
+    pub fn validate(&self) -> Result<(), BurnDataError> {
+        let digits = self.caller.strip_prefix("0x").unwrap_or(&self.caller);
+        if digits.len() != 64 {
+            return Err(BurnDataError::InvalidCallerLength(digits.len()));
+        }
+        let caller = Self::hex_to_bytes32(digits)
+            .map_err(|_| BurnDataError::InvalidCallerHex(self.caller.clone()))?;
+
+        // Values at or above the field prime would be reduced on Starknet
+        if Felt::from_bytes_be(&caller).to_bytes_be() != caller {
+            return Err(BurnDataError::CallerNotFieldElement);
+        }
+        if self.amount == 0 {
+            return Err(BurnDataError::ZeroAmount);
+        }
+        if self.time_stamp == 0 {
+            return Err(BurnDataError::ZeroTimestamp);
+        }
+
+        Ok(())
+    }
+
     /// Computes the Keccak256 commitment hash for burn/withdrawal data
     /// This replicates Solidity's keccak256(abi.encodePacked(...)) behavior
     ///
@@ -32,6 +89,42 @@ impl BurnData {
         Self::keccak256(&packed)
     }
 
+    /// Computes the EIP-712 `hashStruct` of the burn. Every field is a static 32-byte
+    /// word, so this is the Solidity
+    /// `keccak256(abi.encode(BURN_DATA_TYPEHASH, caller, amount, nonce, timeStamp))`
+    pub fn eip712_struct_hash(&self) -> [u8; 32] {
+        let caller_hex =
+            Self::hex_to_bytes32(&self.caller).expect("Invalid hex string for caller address");
+        let mut encoded = Vec::with_capacity(160);
+        encoded.extend_from_slice(&Self::keccak256(BURN_DATA_EIP712_TYPE.as_bytes()));
+        encoded.extend_from_slice(&Self::encode_packed(
+            &caller_hex,
+            self.amount,
+            self.nonce,
+            self.time_stamp,
+        ));
+        Self::keccak256(&encoded)
+    }
+
+    /// Computes the EIP-712 typed data hash of the burn that L1 verifiers check:
+    /// `keccak256("\x19\x01" ‖ domain_separator ‖ hashStruct(burn))`
+    pub fn eip712_hash(&self, domain_separator: &[u8; 32]) -> [u8; 32] {
+        let mut encoded = Vec::with_capacity(66);
+        encoded.extend_from_slice(b"\x19\x01");
+        encoded.extend_from_slice(domain_separator);
+        encoded.extend_from_slice(&self.eip712_struct_hash());
+        Self::keccak256(&encoded)
+    }
+
+    /// The [`eip712_hash`](Self::eip712_hash) as the felt the caller's Stark key signs,
+    /// its top six bits cleared like `starknet_keccak` does, as ECDSA messages must be
+    /// below 2^251
+    pub fn signing_hash(&self, domain_separator: &[u8; 32]) -> Felt {
+        let mut hash = self.eip712_hash(domain_separator);
+        hash[0] &= 0x03;
+        Felt::from_bytes_be(&hash)
+    }
+
     /// Convert hash bytes to hex string for easy display/comparison
     pub fn hash_to_hex_string(&self) -> String {
         let hash = self.compute_commitment_hash();
@@ -113,6 +206,7 @@ impl MintData {
 }
 
 /// Poseidon hash methods that can be used for computing commitment hashes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HashMethod {
     /// Uses the stateful hasher to hash all elements at once (recommended for efficiency)
     BatchHash,
@@ -174,6 +268,18 @@ pub fn compute_poseidon_commitment_hash(
     }
 }
 
+/// Same as [`compute_poseidon_commitment_hash`], formatted as a `0x`-prefixed hex string
+pub fn compute_poseidon_commitment_hash_hex(
+    recipient: Felt,
+    amount: u128,
+    nonce: u64,
+    timestamp: u64,
+    method: HashMethod,
+) -> String {
+    let hash = compute_poseidon_commitment_hash(recipient, amount, nonce, timestamp, method);
+    format!("0x{:x}", hash)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,4 +439,42 @@ mod tests {
         let expected = "0x2b6876060a11edcc5dde925cda8fad185f34564e35802fa40ee8ead2f9acb06f";
         assert_eq!(hex_hash, expected);
     }
+
+    #[test]
+    fn test_eip712_hash_depends_on_the_domain() {
+        let data = BurnData::new(
+            "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7".to_string(),
+            50000,
+            123,
+            1672531200,
+        );
+        assert_eq!(
+            hex::encode(BurnData::keccak256(BURN_DATA_EIP712_TYPE.as_bytes())),
+            "294193963847b4729761b66c8c8278f2ae70ebc4fbe396e2909a26235bfe2d81"
+        );
+        // The typed data hash is never the plain packed commitment
+        assert_ne!(data.eip712_hash(&[0u8; 32]), data.compute_commitment_hash());
+        assert_ne!(data.eip712_hash(&[0u8; 32]), data.eip712_hash(&[1u8; 32]));
+    }
+
+    #[test]
+    fn test_signing_hash_is_the_masked_eip712_hash() {
+        let data = BurnData::new(
+            "0x05044aa873d8b61be2711b0ce9409e63d1cc6c5d7e12547d01ddf77cdbee2b9c".to_string(),
+            5000,
+            1,
+            1700000000,
+        );
+        let domain_separator = [0x11; 32];
+
+        assert_eq!(
+            hex::encode(data.eip712_hash(&domain_separator)),
+            "ef50d596e6b5a8dca9274c3f420aaa6e3c366ae48599e373d35f9cd839bc3bf3"
+        );
+        assert_eq!(
+            data.signing_hash(&domain_separator),
+            Felt::from_hex("0x0350d596e6b5a8dca9274c3f420aaa6e3c366ae48599e373d35f9cd839bc3bf3")
+                .unwrap()
+        );
+    }
 }
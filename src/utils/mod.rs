@@ -1,3 +1,17 @@
+pub mod address;
+pub mod amount;
+pub mod commitment;
+pub mod felt;
 pub mod hash;
+pub mod signature;
+#[cfg(test)]
+mod test_vectors;
 
-pub use hash::{compute_poseidon_commitment_hash, BurnData, HashMethod, MintData};
+pub use address::{validate_starknet_address, AddressError};
+pub use commitment::{commitment_hash_hex, parse_commitment_hash, CommitmentParseError};
+pub use felt::{is_valid_felt252, STARK_PRIME_HEX};
+pub use hash::{
+    compute_poseidon_commitment_hash, compute_poseidon_commitment_hash_hex, BurnData,
+    BurnDataError, HashMethod, MintData, BURN_DATA_EIP712_TYPE,
+};
+pub use signature::{verify_stark_signature, SignatureError};
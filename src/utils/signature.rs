@@ -0,0 +1,111 @@
+use starknet_crypto::{verify, Felt};
+use thiserror::Error;
+
+use crate::utils::is_valid_felt252;
+
+/// Half the order of the Stark curve. `(r, s)` and `(r, n - s)` sign the same message,
+/// so only the low `s` is accepted, leaving each message a single valid signature.
+const STARK_CURVE_HALF_ORDER: Felt =
+    Felt::from_hex_unchecked("0x4000000000000087fffffffffffffffdbc08936e573d9190f335120d6e32697");
+
+/// Ways a Stark signature can fail verification
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error("Signature value '{0}' is not a hex felt")]
+    Malformed(String),
+
+    #[error("Signature s is above half the curve order, sign with n - s instead")]
+    HighS,
+
+    #[error("Signature does not match the Stark public key")]
+    Invalid,
+}
+
+/// Verifies `(r, s)`, given as hex felts, is a low-s ECDSA signature of `message` by
+/// the Stark key `public_key`
+pub fn verify_stark_signature(
+    public_key: &Felt,
+    message: &Felt,
+    r: &str,
+    s: &str,
+) -> Result<(), SignatureError> {
+    let r = parse_felt(r)?;
+    let s = parse_felt(s)?;
+    if s > STARK_CURVE_HALF_ORDER {
+        return Err(SignatureError::HighS);
+    }
+
+    match verify(public_key, message, &r, &s) {
+        Ok(true) => Ok(()),
+        // Also out of range `r` or `s`, and keys off the curve
+        Ok(false) | Err(_) => Err(SignatureError::Invalid),
+    }
+}
+
+/// Parses a hex felt, rejecting values `Felt::from_hex` would reduce modulo the prime
+/// so a signature has a single encoding
+fn parse_felt(value: &str) -> Result<Felt, SignatureError> {
+    if !value.starts_with("0x") || !is_valid_felt252(value) {
+        return Err(SignatureError::Malformed(value.to_string()));
+    }
+    Felt::from_hex(value).map_err(|_| SignatureError::Malformed(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signature by the private key 0x4a7e5f1c9b3d2e8f60a1b2c3d4e5f60718293a4b5c6d7e8f9a0b1c2d3e4f5a6
+    const PUBLIC_KEY: &str = "0x05044aa873d8b61be2711b0ce9409e63d1cc6c5d7e12547d01ddf77cdbee2b9c";
+    const MESSAGE: &str = "0x0350d596e6b5a8dca9274c3f420aaa6e3c366ae48599e373d35f9cd839bc3bf3";
+    const R: &str = "0x003f413c84dae839871b6c878ecdd5e29a94bcdac4b55d86ed228104fe5daa95";
+    const LOW_S: &str = "0x00d2563f991f212f19b570bd9ca109a01184ddfe3546188bace6f3c558fbf971";
+    const HIGH_S: &str = "0x072da9c066e0dee1e64a8f42635ef65fa5fc346f95a199a6717fae7c54ca53be";
+
+    fn felt(hex: &str) -> Felt {
+        Felt::from_hex(hex).unwrap()
+    }
+
+    #[test]
+    fn test_valid_signature_is_accepted() {
+        assert_eq!(
+            verify_stark_signature(&felt(PUBLIC_KEY), &felt(MESSAGE), R, LOW_S),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_tampered_message_and_key_are_rejected() {
+        let tampered = felt(MESSAGE) + Felt::ONE;
+        assert_eq!(
+            verify_stark_signature(&felt(PUBLIC_KEY), &tampered, R, LOW_S),
+            Err(SignatureError::Invalid)
+        );
+
+        let other_key = felt(PUBLIC_KEY) + Felt::ONE;
+        assert_eq!(
+            verify_stark_signature(&other_key, &felt(MESSAGE), R, LOW_S),
+            Err(SignatureError::Invalid)
+        );
+    }
+
+    #[test]
+    fn test_high_s_signature_is_rejected() {
+        // (r, n - s) passes plain ECDSA verification of the same message
+        assert!(verify(&felt(PUBLIC_KEY), &felt(MESSAGE), &felt(R), &felt(HIGH_S)).unwrap());
+        assert_eq!(
+            verify_stark_signature(&felt(PUBLIC_KEY), &felt(MESSAGE), R, HIGH_S),
+            Err(SignatureError::HighS)
+        );
+    }
+
+    #[test]
+    fn test_malformed_values_are_rejected() {
+        for value in ["", "0x", "3f413c", "0xzz", &format!("0x{}", "f".repeat(64))] {
+            assert_eq!(
+                verify_stark_signature(&felt(PUBLIC_KEY), &felt(MESSAGE), value, LOW_S),
+                Err(SignatureError::Malformed(value.to_string()))
+            );
+        }
+    }
+}
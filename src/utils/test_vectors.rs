@@ -0,0 +1,232 @@
+//! Known-answer vectors for [`compute_poseidon_commitment_hash`].
+//!
+//! Expected hashes were produced with the reference Starknet Poseidon (Hades) permutation from
+//! `cairo-lang`, i.e. what `poseidon_hash_many` and `poseidon_hash` return inside a Cairo
+//! contract. A mismatch here means the sequencer no longer agrees with L2 on commitment hashes.
+
+use starknet_crypto::Felt;
+
+use super::hash::{
+    compute_poseidon_commitment_hash, compute_poseidon_commitment_hash_hex, HashMethod,
+};
+
+/// `(recipient, amount, nonce, timestamp, method, expected_hash)`
+type TestVector = (&'static str, u128, u64, u64, HashMethod, &'static str);
+
+const TEST_VECTORS: &[TestVector] = &[
+    (
+        "0x0",
+        0,
+        0,
+        0,
+        HashMethod::BatchHash,
+        "0x5c4def9d0323f31f80e90c55fa780591ed2e2fee266491c0bd891aedac38935",
+    ),
+    (
+        "0x0",
+        0,
+        0,
+        0,
+        HashMethod::SequentialPairwise,
+        "0x3a8c68f3cdd48133f9025139ae7390a8bfc76b3597df00588b95b59fe1ad5e7",
+    ),
+    (
+        "0x1",
+        1,
+        1,
+        1,
+        HashMethod::BatchHash,
+        "0x285866bbb7690e2708de4b410785ecb9a1af8badfd83f9fa09c0c941b3db8d2",
+    ),
+    (
+        "0x1",
+        1,
+        1,
+        1,
+        HashMethod::SequentialPairwise,
+        "0x67fad30ecd29245a1b856f3ee429cf53c0f6365a2c8d3590bcad12692f81d74",
+    ),
+    (
+        "0x6ee7c7a561ae5c39e3a2866e8e208ed8ebe45da686e2929622102c80834b771",
+        1000000,
+        42,
+        1650000000,
+        HashMethod::BatchHash,
+        "0x490734213785de16eee9aa32e772113213db36a9464118dc5a22f036f40850e",
+    ),
+    (
+        "0x6ee7c7a561ae5c39e3a2866e8e208ed8ebe45da686e2929622102c80834b771",
+        1000000,
+        42,
+        1650000000,
+        HashMethod::SequentialPairwise,
+        "0x31dbf4c9ba3877aa3220423661ad37a95bfbd850d32466bbe5ac2d82f8c291a",
+    ),
+    (
+        "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
+        50000,
+        123,
+        1672531200,
+        HashMethod::BatchHash,
+        "0x36a3ffde34b0eec0688d159b2d6db02d47d8cf316d09a9393a63634b12f2f2",
+    ),
+    (
+        "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
+        50000,
+        123,
+        1672531200,
+        HashMethod::SequentialPairwise,
+        "0x53a857f79caf62453069451e5d6dbb9f7a485b9445e579f466e397c8831bb05",
+    ),
+    (
+        "0x101010101010101010101010101010101010101010101010101010101010101",
+        1000,
+        42,
+        1640995200,
+        HashMethod::BatchHash,
+        "0x1ca520959e5c40b42a9d1506999b2dcf649eae0db8c086b5e59f588d7ae806c",
+    ),
+    (
+        "0x101010101010101010101010101010101010101010101010101010101010101",
+        1000,
+        42,
+        1640995200,
+        HashMethod::SequentialPairwise,
+        "0x39d0374612dfd01434013a971d7c696115ed03f3e8e29f77d5b9cbb20145a37",
+    ),
+    (
+        "0x800000000000011000000000000000000000000000000000000000000000000",
+        340282366920938463463374607431768211455,
+        18446744073709551615,
+        18446744073709551615,
+        HashMethod::BatchHash,
+        "0x5203b2e2bdf77c8eb380bef8e94bebda4a9594bd7a8fdf903d8acacb06eca",
+    ),
+    (
+        "0x800000000000011000000000000000000000000000000000000000000000000",
+        340282366920938463463374607431768211455,
+        18446744073709551615,
+        18446744073709551615,
+        HashMethod::SequentialPairwise,
+        "0x5918183393c1b6d560689c4bfc461a2a5b4d1401ead726c13fef7dd0542308a",
+    ),
+    (
+        "0x1234567890abcdef",
+        999,
+        0,
+        1704067200,
+        HashMethod::BatchHash,
+        "0x6055c21ae37a23b5d454379f7751e65859e9faa31c9c0608a3c13583eae8d94",
+    ),
+    (
+        "0x1234567890abcdef",
+        999,
+        0,
+        1704067200,
+        HashMethod::SequentialPairwise,
+        "0x6c9b0fde5c72c23c1955de17e26c9cc0cdfae4d1d6c702d75a3f0b8d779c4b",
+    ),
+    (
+        "0x53c91253bc9682c04929ca02ed00b3e423f6710d2ee7e0d5ebb06f3ecf368a8",
+        123456789012345678901234567890,
+        7,
+        1717171717,
+        HashMethod::BatchHash,
+        "0x64d7d3bba4eb21c5c03cc47cdba91d6256b089029bbba77f80e37c4602e28ad",
+    ),
+    (
+        "0x53c91253bc9682c04929ca02ed00b3e423f6710d2ee7e0d5ebb06f3ecf368a8",
+        123456789012345678901234567890,
+        7,
+        1717171717,
+        HashMethod::SequentialPairwise,
+        "0x135338f0efcac9cc46521ecc382e3a4b699bddde0d0bc888eacb1894842bc29",
+    ),
+    (
+        "0x4718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d",
+        250000000000000000000,
+        3,
+        1735689600,
+        HashMethod::BatchHash,
+        "0x67a7cebb76fb17784d6a36c2d6db73f9e67f4a28e099fc2fddd10fb98f1da77",
+    ),
+    (
+        "0x4718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d",
+        250000000000000000000,
+        3,
+        1735689600,
+        HashMethod::SequentialPairwise,
+        "0x7cb75732a6a6663deb334fae375cf9e1aeb5235df2c3c811bb60b035b6dcae1",
+    ),
+    (
+        "0x18ee7c7a561ae5c39e3a2866e8e208ed8ebe45da686e2929622102c80834b7",
+        42,
+        100,
+        1650000001,
+        HashMethod::BatchHash,
+        "0x1962b9cf076a74f8a37e358f0e0d9df0565567bc6ebf927248f52dc5cc433d8",
+    ),
+    (
+        "0x18ee7c7a561ae5c39e3a2866e8e208ed8ebe45da686e2929622102c80834b7",
+        42,
+        100,
+        1650000001,
+        HashMethod::SequentialPairwise,
+        "0x5f288157fc273cd6e1d1b16ccbfe80f6b15bf55830ad3f2f5b48f34c0fc8093",
+    ),
+    (
+        "0xdead",
+        1,
+        18446744073709551615,
+        0,
+        HashMethod::BatchHash,
+        "0x47fc01bb62ecd7ba88f60a1b05d26322c49aa25bb3028b185e0d76492bd2e5",
+    ),
+    (
+        "0xdead",
+        1,
+        18446744073709551615,
+        0,
+        HashMethod::SequentialPairwise,
+        "0x3d17a9e8730c6700143273c3fb7744519c9c486ae1938efc5171e5a46a088b4",
+    ),
+    (
+        "0x6ee7c7a561ae5c39e3a2866e8e208ed8ebe45da686e2929622102c80834b771",
+        1000000,
+        43,
+        1650000000,
+        HashMethod::BatchHash,
+        "0x7802161101476d287cf75e68db327cc939b35dbc5f21d2112c7ea6642392233",
+    ),
+    (
+        "0x6ee7c7a561ae5c39e3a2866e8e208ed8ebe45da686e2929622102c80834b771",
+        1000000,
+        43,
+        1650000000,
+        HashMethod::SequentialPairwise,
+        "0x3718e1e888c60d66d2131f37eb95f7f5749f2a20f84092d786492ebd8de835d",
+    ),
+];
+
+#[test]
+fn validate_all_test_vectors() {
+    assert!(TEST_VECTORS.len() >= 20);
+
+    for (i, &(recipient, amount, nonce, timestamp, method, expected)) in
+        TEST_VECTORS.iter().enumerate()
+    {
+        let recipient = Felt::from_hex(recipient).unwrap();
+        let expected_felt = Felt::from_hex(expected).unwrap();
+
+        let hash = compute_poseidon_commitment_hash(recipient, amount, nonce, timestamp, method);
+        assert_eq!(
+            hash, expected_felt,
+            "test vector {} ({:?}) mismatch",
+            i, method
+        );
+
+        let hash_hex =
+            compute_poseidon_commitment_hash_hex(recipient, amount, nonce, timestamp, method);
+        assert_eq!(hash_hex, expected, "test vector {} hex mismatch", i);
+    }
+}
@@ -0,0 +1,221 @@
+use reqwest::{header, Client};
+use sqlx::PgPool;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+use crate::config::WebhookConfig;
+use crate::db::database::{
+    claim_due_webhook_deliveries, fail_webhook_delivery, mark_webhook_delivered,
+    retry_webhook_delivery, DueWebhookDelivery,
+};
+use crate::webhooks::signature::{sign_payload, DELIVERY_HEADER, SIGNATURE_HEADER};
+
+/// Milliseconds a claimed delivery stays hidden from other dispatchers on top of the
+/// request timeout, so it is only claimed again once its attempt surely ended
+const CLAIM_MARGIN_MS: u64 = 60_000;
+
+/// Characters of a refusing webhook's response body kept in `last_error`
+const MAX_ERROR_BODY_CHARS: usize = 500;
+
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Deliveries attempted by a single dispatch run
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DispatchReport {
+    pub delivered: Vec<i64>,
+    /// Failed attempts of deliveries that are tried again later
+    pub retried: Vec<i64>,
+    /// Deliveries given up on after their last attempt failed
+    pub failed: Vec<i64>,
+}
+
+impl DispatchReport {
+    pub fn is_empty(&self) -> bool {
+        self.delivered.is_empty() && self.retried.is_empty() && self.failed.is_empty()
+    }
+}
+
+/// Why an attempt failed, with the webhook's HTTP status when it answered
+struct AttemptFailure {
+    status: Option<u16>,
+    error: String,
+}
+
+/// Sends the deliveries queued in `webhook_deliveries` to their webhooks.
+///
+/// Deliveries are at least once: a delivery is only marked delivered after its webhook
+/// answered with a 2xx, and one whose outcome could not be recorded is sent again. Its
+/// id is sent in [`DELIVERY_HEADER`] so receivers can drop redeliveries.
+pub struct WebhookDispatcher {
+    db_pool: PgPool,
+    http: Client,
+    config: WebhookConfig,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db_pool: PgPool, config: WebhookConfig) -> Self {
+        Self {
+            db_pool,
+            http: Client::new(),
+            config,
+        }
+    }
+
+    /// Runs the dispatcher in an infinite loop.
+    pub async fn start(&self) {
+        info!("Starting webhook dispatcher");
+
+        loop {
+            match self.dispatch_due().await {
+                Ok(report) if report.is_empty() => {}
+                Ok(report) => info!(
+                    delivered = ?report.delivered,
+                    retried = ?report.retried,
+                    failed = ?report.failed,
+                    "Dispatched webhook deliveries"
+                ),
+                Err(e) => error!("Webhook dispatch failed: {:?}", e),
+            }
+            sleep(Duration::from_secs(self.config.poll_interval_sec)).await;
+        }
+    }
+
+    /// Attempts up to `webhooks.batch_size` due deliveries once, recording the outcome
+    /// of each.
+    pub async fn dispatch_due(&self) -> Result<DispatchReport, WebhookError> {
+        let lease_ms = self.config.request_timeout_ms + CLAIM_MARGIN_MS;
+        let deliveries = claim_due_webhook_deliveries(
+            &self.db_pool,
+            self.config.batch_size as i64,
+            lease_ms as i64,
+        )
+        .await?;
+
+        let mut report = DispatchReport::default();
+        for delivery in deliveries {
+            match self.send(&delivery).await {
+                Ok(status) => {
+                    mark_webhook_delivered(&self.db_pool, delivery.id, i32::from(status)).await?;
+                    report.delivered.push(delivery.id);
+                }
+
+                Err(failure) if delivery.attempts < self.config.max_attempts as i32 => {
+                    let retry_in = retry_backoff(&self.config, delivery.attempts);
+                    warn!(
+                        "Delivery {} to webhook {} failed (attempt {}/{}): {}. Retrying in {:?}",
+                        delivery.id,
+                        delivery.webhook_id,
+                        delivery.attempts,
+                        self.config.max_attempts,
+                        failure.error,
+                        retry_in
+                    );
+                    retry_webhook_delivery(
+                        &self.db_pool,
+                        delivery.id,
+                        failure.status.map(i32::from),
+                        &failure.error,
+                        retry_in.as_millis() as i64,
+                    )
+                    .await?;
+                    report.retried.push(delivery.id);
+                }
+
+                Err(failure) => {
+                    error!(
+                        "Delivery {} to webhook {} failed after {} attempts: {}",
+                        delivery.id, delivery.webhook_id, delivery.attempts, failure.error
+                    );
+                    fail_webhook_delivery(
+                        &self.db_pool,
+                        delivery.id,
+                        failure.status.map(i32::from),
+                        &failure.error,
+                    )
+                    .await?;
+                    report.failed.push(delivery.id);
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// POSTs a delivery's payload signed with its webhook's secret, returning the
+    /// webhook's 2xx status
+    async fn send(&self, delivery: &DueWebhookDelivery) -> Result<u16, AttemptFailure> {
+        let body = delivery.payload.to_string();
+        let response = self
+            .http
+            .post(&delivery.url)
+            .timeout(Duration::from_millis(self.config.request_timeout_ms))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                sign_payload(&delivery.secret, body.as_bytes()),
+            )
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AttemptFailure {
+                status: None,
+                error: e.to_string(),
+            })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(status.as_u16());
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        Err(AttemptFailure {
+            status: Some(status.as_u16()),
+            error: format!(
+                "Webhook answered {}: {}",
+                status,
+                body.chars().take(MAX_ERROR_BODY_CHARS).collect::<String>()
+            ),
+        })
+    }
+}
+
+/// Wait before the attempt following the `attempts`th: `retry_backoff_ms` doubled after
+/// every attempt but the first, at most `max_retry_backoff_ms`
+fn retry_backoff(config: &WebhookConfig, attempts: i32) -> Duration {
+    let doublings = (attempts.max(1) - 1).min(32) as u32;
+    let backoff_ms = config
+        .retry_backoff_ms
+        .saturating_mul(1 << doublings)
+        .min(config.max_retry_backoff_ms);
+    Duration::from_millis(backoff_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_backoff_doubles_up_to_the_cap() {
+        let config = WebhookConfig {
+            retry_backoff_ms: 1_000,
+            max_retry_backoff_ms: 5_000,
+            ..WebhookConfig::default()
+        };
+
+        let backoffs: Vec<u64> = (1..=5)
+            .map(|attempts| retry_backoff(&config, attempts).as_millis() as u64)
+            .collect();
+        assert_eq!(backoffs, vec![1_000, 2_000, 4_000, 5_000, 5_000]);
+        assert_eq!(
+            retry_backoff(&config, i32::MAX),
+            Duration::from_millis(5_000)
+        );
+    }
+}
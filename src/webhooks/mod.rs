@@ -0,0 +1,21 @@
+//! Webhook notifications of deposit and withdrawal status changes.
+//!
+//! Every status change of a deposit or withdrawal queues a delivery for each active
+//! webhook subscribed to its event type, written by a trigger in the transaction making
+//! the change. [`WebhookDispatcher`] sends the due deliveries separately from the
+//! pipeline, signed with [`sign_payload`], and retries failed ones with an exponential
+//! backoff until `webhooks.max_attempts` attempts were made.
+
+pub mod dispatcher;
+pub mod signature;
+
+pub use dispatcher::{DispatchReport, WebhookDispatcher};
+pub use signature::{sign_payload, verify_signature, DELIVERY_HEADER, SIGNATURE_HEADER};
+
+/// Status changes of deposits
+pub const DEPOSIT_STATUS_CHANGED: &str = "deposit.status_changed";
+/// Status changes of withdrawals
+pub const WITHDRAWAL_STATUS_CHANGED: &str = "withdrawal.status_changed";
+
+/// Event types webhooks can subscribe to
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &[DEPOSIT_STATUS_CHANGED, WITHDRAWAL_STATUS_CHANGED];
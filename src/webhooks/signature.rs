@@ -0,0 +1,54 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Header holding the hex-encoded HMAC-SHA256 of the delivery's body
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+/// Header holding the delivery's id, the same on every attempt so receivers can tell
+/// redeliveries apart
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// Signs a delivery's body with the webhook's secret, as sent in [`SIGNATURE_HEADER`]
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    hex::encode(mac(secret, body).finalize().into_bytes())
+}
+
+/// Whether `signature` is the signature of `body` under `secret`, compared in constant
+/// time
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    match hex::decode(signature) {
+        Ok(signature) => mac(secret, body).verify_slice(&signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+fn mac(secret: &str, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_matches_rfc_4231() {
+        // Test case 2 of RFC 4231
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_signature_is_verified() {
+        let body = br#"{"id":1,"new_status":"PROCESSED"}"#;
+        let signature = sign_payload("secret", body);
+
+        assert!(verify_signature("secret", body, &signature));
+        assert!(!verify_signature("other secret", body, &signature));
+        assert!(!verify_signature("secret", b"{}", &signature));
+        assert!(!verify_signature("secret", body, "not hex"));
+    }
+}
@@ -0,0 +1,121 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode, Uri},
+    Router,
+};
+use ethers::signers::LocalWallet;
+use ethers::types::H256;
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::auth::{admin_request_hash, ADMIN_SIGNATURE_HEADERS};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::db::database::{fetch_deposit_audit_log, insert_deposit};
+
+// Keys of the admin addresses configured in `create_test_config`
+const ADMIN_KEYS: [&str; 3] = [
+    "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+    "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9f804cdab365a",
+];
+const OUTSIDER_KEY: &str = "7c852118294e51e653712a81e05800f419141751be58f605c371e15141b007a6";
+
+async fn setup() -> (Router, PgPool) {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    (router, app.db.clone())
+}
+
+async fn create_deposit(pool: &PgPool) -> i32 {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    insert_deposit(
+        pool,
+        "0xadmin123",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+    )
+    .await
+    .unwrap()
+}
+
+fn signed_cancel_request(id: i32, keys: &[&str]) -> Request<Body> {
+    let uri: Uri = format!("/admin/deposits/{}?reason=duplicate", id)
+        .parse()
+        .unwrap();
+    let hash = H256::from(admin_request_hash(&Method::DELETE, &uri, b""));
+
+    let mut request = Request::builder().method(Method::DELETE).uri(uri);
+    for (header, key) in ADMIN_SIGNATURE_HEADERS.iter().zip(keys) {
+        let wallet: LocalWallet = key.parse().unwrap();
+        let signature = wallet.sign_hash(hash).unwrap();
+        request = request.header(*header, format!("0x{}", signature));
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_cancel_with_two_signatures() {
+    let (router, pool) = setup().await;
+    let id = create_deposit(&pool).await;
+
+    let response = router
+        .oneshot(signed_cancel_request(id, &[ADMIN_KEYS[0], ADMIN_KEYS[2]]))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let audit_log = fetch_deposit_audit_log(&pool, id).await.unwrap();
+    assert_eq!(audit_log.len(), 1);
+    assert_eq!(audit_log[0].new_status, "CANCELLED");
+    assert_eq!(audit_log[0].reason, "duplicate");
+}
+
+#[tokio::test]
+async fn test_admin_cancel_with_one_signature_is_rejected() {
+    let (router, pool) = setup().await;
+    let id = create_deposit(&pool).await;
+
+    let response = router
+        .oneshot(signed_cancel_request(id, &[ADMIN_KEYS[1]]))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let audit_log = fetch_deposit_audit_log(&pool, id).await.unwrap();
+    assert!(audit_log.is_empty());
+}
+
+#[tokio::test]
+async fn test_admin_cancel_with_outsider_signature_is_rejected() {
+    let (router, pool) = setup().await;
+    let id = create_deposit(&pool).await;
+
+    let response = router
+        .oneshot(signed_cancel_request(id, &[ADMIN_KEYS[0], OUTSIDER_KEY]))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_admin_signatures_are_bound_to_the_request() {
+    let (router, pool) = setup().await;
+    let id = create_deposit(&pool).await;
+    let other = create_deposit(&pool).await;
+
+    // Signatures collected for one deposit must not cancel another
+    let mut request = signed_cancel_request(id, &[ADMIN_KEYS[0], ADMIN_KEYS[1]]);
+    *request.uri_mut() = format!("/admin/deposits/{}?reason=duplicate", other)
+        .parse()
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let audit_log = fetch_deposit_audit_log(&pool, other).await.unwrap();
+    assert!(audit_log.is_empty());
+}
@@ -0,0 +1,189 @@
+#[path = "utils.rs"]
+mod utils;
+
+use alloy_primitives::{Address, U256};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+use sqlx::{types::BigDecimal, PgPool};
+use std::str::FromStr;
+use tower::ServiceExt;
+use utils::{create_test_app, random_commitment};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::{LimitsConfig, NetworkId};
+use zeroxbridge_sequencer::events::l1_event_watcher::{record_deposit_event, ZeroXBridge};
+use zeroxbridge_sequencer::utils::commitment_hash_hex;
+
+const MIN_AMOUNT: i64 = 10;
+const MAX_AMOUNT: i64 = 1_000;
+
+fn limits() -> LimitsConfig {
+    LimitsConfig {
+        min_deposit_amount: Some(BigDecimal::from(MIN_AMOUNT)),
+        max_deposit_amount: Some(BigDecimal::from(MAX_AMOUNT)),
+        min_withdrawal_amount: Some(BigDecimal::from(MIN_AMOUNT)),
+        max_withdrawal_amount: Some(BigDecimal::from(MAX_AMOUNT)),
+    }
+}
+
+async fn setup() -> (Router, PgPool) {
+    let app = create_test_app().await;
+    let mut config = app.config.clone();
+    config.limits = limits();
+    let router = create_router_with_config(app.db.clone(), &config);
+    (router, app.db.clone())
+}
+
+async fn post(router: Router, uri: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn post_deposit(router: Router, amount: i64) -> (StatusCode, Value) {
+    let body = json!({
+        "stark_pub_key": "0x123",
+        "amount": amount,
+        "commitment_hash": random_commitment()
+    });
+    post(router, "/deposit", body).await
+}
+
+async fn post_withdrawal(router: Router, amount: i64) -> (StatusCode, Value) {
+    let body = json!({
+        "stark_pub_key": format!("0x{:064x}", 0xabc123),
+        "amount": amount,
+        "commitment_hash": random_commitment(),
+        "l1_token": "0xtoken"
+    });
+    post(router, "/withdrawals", body).await
+}
+
+fn deposit_event(usd_val: U256) -> ZeroXBridge::DepositEvent {
+    ZeroXBridge::DepositEvent {
+        assetType: ZeroXBridge::AssetType::ETH,
+        usdVal: usd_val,
+        nonce: U256::from(1),
+        leafIndex: U256::from(0),
+        depositId: U256::from(1),
+        token: Address::from([0x00; 20]),
+        user: Address::from([0xab; 20]),
+        // Shifted below 2^251 so only the amount decides whether the deposit is flagged
+        commitmentHash: U256::from_be_bytes(rand::random::<[u8; 32]>()) >> 5,
+        newRoot: U256::from(0x5678),
+        elementCount: U256::from(1),
+    }
+}
+
+#[tokio::test]
+async fn test_deposit_amount_at_bounds_is_accepted() {
+    let (router, _) = setup().await;
+
+    let (status, _) = post_deposit(router.clone(), MIN_AMOUNT).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = post_deposit(router, MAX_AMOUNT).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_deposit_amount_out_of_bounds_is_rejected() {
+    let (router, _) = setup().await;
+
+    let (status, body) = post_deposit(router.clone(), MAX_AMOUNT + 1).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "amount_above_maximum");
+    assert_eq!(
+        body["error"]["message"],
+        "Amount 1001 is above the maximum deposit amount of 1000"
+    );
+
+    let (status, body) = post_deposit(router, MIN_AMOUNT - 1).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "amount_below_minimum");
+}
+
+#[tokio::test]
+async fn test_withdrawal_amount_limits() {
+    let (router, _) = setup().await;
+
+    let (status, _) = post_withdrawal(router.clone(), MAX_AMOUNT).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = post_withdrawal(router.clone(), MAX_AMOUNT + 1).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "amount_above_maximum");
+
+    let (status, body) = post_withdrawal(router, MIN_AMOUNT - 1).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "amount_below_minimum");
+}
+
+#[tokio::test]
+async fn test_over_limit_deposit_event_is_flagged() {
+    let app = create_test_app().await;
+    // 2^70, which no longer fits the BIGINT amounts used to be stored in
+    let usd_val = U256::from(1) << 70;
+    let event = deposit_event(usd_val);
+
+    let status = record_deposit_event(&app.db, &NetworkId::default(), &event, None, &limits())
+        .await
+        .unwrap();
+    assert_eq!(status, "AMOUNT_FLAGGED");
+
+    let row = sqlx::query!(
+        "SELECT amount, status FROM deposits WHERE commitment_hash = $1",
+        commitment_hash_hex(&event.commitmentHash.to_be_bytes())
+    )
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(row.status, "AMOUNT_FLAGGED");
+    assert_eq!(
+        row.amount,
+        BigDecimal::from_str("1180591620717411303424").unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_event_within_limits_enters_tree() {
+    let app = create_test_app().await;
+    let event = deposit_event(U256::from(MAX_AMOUNT));
+
+    let status = record_deposit_event(&app.db, &NetworkId::default(), &event, None, &limits())
+        .await
+        .unwrap();
+    assert_eq!(status, "PENDING_TREE_INCLUSION");
+}
+
+#[tokio::test]
+async fn test_deposit_event_commitment_outside_felt_range_is_flagged() {
+    let app = create_test_app().await;
+    let prime =
+        U256::from_str("0x0800000000000011000000000000000000000000000000000000000000000001")
+            .unwrap();
+
+    for commitment_hash in [prime, prime + U256::from(rand::random::<u64>())] {
+        let event = ZeroXBridge::DepositEvent {
+            commitmentHash: commitment_hash,
+            ..deposit_event(U256::from(MAX_AMOUNT))
+        };
+        let status = record_deposit_event(&app.db, &NetworkId::default(), &event, None, &limits())
+            .await
+            .unwrap();
+        assert_eq!(status, "COMMITMENT_FLAGGED");
+    }
+}
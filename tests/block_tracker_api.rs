@@ -0,0 +1,126 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode, Uri},
+    Router,
+};
+use ethers::signers::LocalWallet;
+use ethers::types::H256;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use tower::ServiceExt;
+use utils::{create_test_app, ADMIN_KEYS};
+use zeroxbridge_sequencer::api::auth::{admin_request_hash, ADMIN_SIGNATURE_HEADERS};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::get_last_processed_block;
+use zeroxbridge_sequencer::events::l1_event_watcher::DEPOSIT_HASH_BLOCK_TRACKER_KEY;
+
+async fn setup() -> (Router, PgPool) {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    (router, app.db.clone())
+}
+
+fn admin_request(method: Method, body: Option<Value>) -> Request<Body> {
+    let uri = Uri::from_static("/admin/block-tracker");
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let hash = H256::from(admin_request_hash(&method, &uri, body.as_bytes()));
+
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    for (header, key) in ADMIN_SIGNATURE_HEADERS.iter().zip(ADMIN_KEYS) {
+        let wallet: LocalWallet = key.parse().unwrap();
+        let signature = wallet.sign_hash(hash).unwrap();
+        request = request.header(*header, format!("0x{}", signature));
+    }
+    request.body(Body::from(body)).unwrap()
+}
+
+async fn body_json(response: axum::response::Response) -> Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_set_block_tracker_updates_db() {
+    let (router, pool) = setup().await;
+    let block_number = 1_000_000 + rand::random::<u32>() as u64;
+
+    let response = router
+        .clone()
+        .oneshot(admin_request(
+            Method::PUT,
+            Some(json!({
+                "key": DEPOSIT_HASH_BLOCK_TRACKER_KEY,
+                "block_number": block_number
+            })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let stored =
+        get_last_processed_block(&pool, &NetworkId::default(), DEPOSIT_HASH_BLOCK_TRACKER_KEY)
+            .await
+            .unwrap();
+    assert_eq!(stored, Some(block_number));
+
+    let response = router
+        .oneshot(admin_request(Method::GET, None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let trackers = body_json(response).await;
+    let tracker = trackers
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|tracker| tracker["key"] == DEPOSIT_HASH_BLOCK_TRACKER_KEY)
+        .expect("tracker should be listed");
+    assert_eq!(tracker["last_block"], block_number);
+}
+
+#[tokio::test]
+async fn test_set_unknown_block_tracker_is_rejected() {
+    let (router, pool) = setup().await;
+
+    let response = router
+        .oneshot(admin_request(
+            Method::PUT,
+            Some(json!({ "key": "not_a_tracker", "block_number": 1 })),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = body_json(response).await;
+    assert_eq!(body["error"]["code"], "unknown_block_tracker");
+    assert_eq!(
+        get_last_processed_block(&pool, &NetworkId::default(), "not_a_tracker")
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_block_tracker_requires_admin_signatures() {
+    let (router, _) = setup().await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/admin/block-tracker")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
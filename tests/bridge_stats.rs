@@ -0,0 +1,182 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{
+    get_bridge_stats, insert_deposit, insert_merkle_root, mark_deposit_proof_generated,
+    update_withdrawal_status, upsert_withdrawal, RootSource,
+};
+
+fn commitment_hash() -> String {
+    format!("0x{}", Uuid::new_v4().simple())
+}
+
+async fn create_deposit(pool: &PgPool, status: &str, amount: i64, secs_ago: i64) -> i32 {
+    let id = insert_deposit(
+        pool,
+        "0xbridgestats",
+        &BigDecimal::from(amount),
+        &commitment_hash(),
+    )
+    .await
+    .unwrap();
+
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET status = $2, created_at = NOW() - $3::BIGINT * INTERVAL '1 second'
+        WHERE id = $1
+        "#,
+        id,
+        status,
+        secs_ago
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    id
+}
+
+async fn create_withdrawal(pool: &PgPool, statuses: &[&str], secs_ago: i64) -> i32 {
+    let id = upsert_withdrawal(
+        pool,
+        &NetworkId::default(),
+        "0xbridgestats",
+        &BigDecimal::from(500),
+        "",
+        &commitment_hash(),
+        None,
+    )
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE withdrawals SET created_at = NOW() - $2::BIGINT * INTERVAL '1 second' WHERE id = $1",
+        id,
+        secs_ago
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    for status in statuses {
+        update_withdrawal_status(&mut conn, id, status, None)
+            .await
+            .unwrap();
+    }
+    id
+}
+
+async fn get_stats(router: Router) -> Value {
+    let request = Request::builder()
+        .method("GET")
+        .uri("/stats")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_stats_aggregate_deposits_withdrawals_and_tree() {
+    let app = create_test_app().await;
+
+    let ready = create_deposit(&app.db, "PENDING_PROOF_GENERATION", 1000, 60).await;
+    let mut conn = app.db.acquire().await.unwrap();
+    mark_deposit_proof_generated(&mut conn, ready)
+        .await
+        .unwrap();
+    create_deposit(&app.db, "PROCESSED", 2000, 120).await;
+    create_deposit(&app.db, "CANCELLED", 4000, 0).await;
+    create_withdrawal(&app.db, &["ready_for_relay", "completed"], 90).await;
+    create_withdrawal(&app.db, &["failed"], 0).await;
+
+    // Other tests record trees up to i64::MAX, so the root is at a size nobody else uses
+    // and only checked when it is the largest
+    let elements_count = (Uuid::new_v4().as_u128() % 1_000_000_000_000) as i64 + 1_000_000;
+    let root = commitment_hash();
+    insert_merkle_root(
+        &mut conn,
+        &NetworkId::default(),
+        elements_count,
+        &root,
+        None,
+        RootSource::Local,
+    )
+    .await
+    .unwrap();
+
+    let stats = get_bridge_stats(&app.db).await.unwrap();
+
+    let deposits = &stats.deposits;
+    assert!(deposits.count_by_status["READY_FOR_RELAY"] >= 1);
+    assert!(deposits.count_by_status["PROCESSED"] >= 1);
+    assert!(deposits.count_by_status["CANCELLED"] >= 1);
+    assert!(deposits.total_amount >= 3000);
+    assert!(deposits.avg_seconds_to_ready.unwrap() > 0.0);
+    assert!(deposits.avg_seconds_to_complete.unwrap() > 0.0);
+
+    let withdrawals = &stats.withdrawals;
+    assert!(withdrawals.count_by_status["completed"] >= 1);
+    assert!(withdrawals.count_by_status["failed"] >= 1);
+    assert!(withdrawals.total_amount >= 500);
+    assert!(withdrawals.avg_seconds_to_ready.unwrap() > 0.0);
+    assert!(withdrawals.avg_seconds_to_complete.unwrap() > 0.0);
+
+    assert!(stats.merkle.leaf_count >= elements_count);
+    if stats.merkle.leaf_count == elements_count {
+        assert_eq!(stats.merkle.latest_root.as_deref(), Some(root.as_str()));
+    }
+}
+
+#[tokio::test]
+async fn test_stats_endpoint_is_cached_for_the_configured_ttl() {
+    let app = create_test_app().await;
+    let mut config = app.config.clone();
+    config.server.stats_cache_ttl_sec = 60;
+    let router = create_router_with_config(app.db.clone(), &config);
+
+    let before = get_stats(router.clone()).await;
+    for key in [
+        "count_by_status",
+        "total_amount",
+        "avg_seconds_to_ready",
+        "avg_seconds_to_complete",
+    ] {
+        assert!(before["deposits"].get(key).is_some(), "{} is missing", key);
+        assert!(
+            before["withdrawals"].get(key).is_some(),
+            "{} is missing",
+            key
+        );
+    }
+    assert!(before["merkle"]["leaf_count"].is_i64());
+
+    // A status no other test uses shows whether the aggregates were recomputed
+    let status = format!("STATS_{}", Uuid::new_v4().simple());
+    create_deposit(&app.db, &status, 1000, 0).await;
+
+    let cached = get_stats(router).await;
+    assert_eq!(cached, before);
+    assert!(cached["deposits"]["count_by_status"].get(&status).is_none());
+
+    config.server.stats_cache_ttl_sec = 0;
+    let uncached = get_stats(create_router_with_config(app.db.clone(), &config)).await;
+    assert_eq!(uncached["deposits"]["count_by_status"][&status], 1);
+}
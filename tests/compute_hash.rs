@@ -39,3 +39,46 @@ fn test_commitment_hash_solidity_compatibility() {
     let expected = "0x2b6876060a11edcc5dde925cda8fad185f34564e35802fa40ee8ead2f9acb06f";
     assert_eq!(hex_hash, expected);
 }
+
+// Domain separator of name "ZeroXBridge", version "1", chain id 1 and verifying contract
+// 0xcccccccccccccccccccccccccccccccccccccccc, i.e. keccak256(abi.encode(
+//     keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"),
+//     keccak256("ZeroXBridge"), keccak256("1"), 1, verifyingContract
+// ))
+const DOMAIN_SEPARATOR: &str = "0x5b0ec0791eba5a681496feac973518035f115d7ed395a6862f31d43639b2b805";
+
+fn domain_separator() -> [u8; 32] {
+    BurnData::hex_to_bytes32(DOMAIN_SEPARATOR).unwrap()
+}
+
+#[test]
+fn test_eip712_hash_solidity_compatibility() {
+    // Expected values from Solidity, with
+    // BURN_DATA_TYPEHASH = keccak256("BurnData(bytes32 caller,uint256 amount,uint256 nonce,uint256 timeStamp)"):
+    //   structHash = keccak256(abi.encode(BURN_DATA_TYPEHASH, caller, amount, nonce, timeStamp))
+    //   digest = keccak256(abi.encodePacked("\x19\x01", DOMAIN_SEPARATOR, structHash))
+    let vectors = [
+        (
+            "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
+            50000u64,
+            123u64,
+            1672531200u64,
+            "31d6c8f37a08810f25d238376d4a99edf0fd70efd3a07b0567430d6ced737c83",
+            "c4b6cb57aa4c00a743586c1d56a625c5243e9ac8da85e8eb5dab876023742804",
+        ),
+        (
+            "0x0101010101010101010101010101010101010101010101010101010101010101",
+            1000u64,
+            42u64,
+            1640995200u64,
+            "eeeea8018bcec356a369681e39e27dc74f39bdd2b6c9a1e1c9b22e32d1ae69a2",
+            "9409cf6edb332bcc2f5efabd0fdcd066c7833f93f1b2a0ba00b66f0b4ea92f4b",
+        ),
+    ];
+
+    for (caller, amount, nonce, time_stamp, struct_hash, digest) in vectors {
+        let data = BurnData::new(caller.to_string(), amount, nonce, time_stamp);
+        assert_eq!(hex::encode(data.eip712_struct_hash()), struct_hash);
+        assert_eq!(hex::encode(data.eip712_hash(&domain_separator())), digest);
+    }
+}
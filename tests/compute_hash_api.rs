@@ -1,14 +1,13 @@
-use axum::body::Bytes;
-use axum::{
-    body::Body,
-    http::{Request, StatusCode},
-};
-use serde_json::json;
-use tower::ServiceExt; // for `oneshot`
+use sha3::{Digest, Keccak256};
+use zeroxbridge_sequencer::utils::{BurnData, BurnDataError};
 
-use zeroxbridge_sequencer::api::routes::create_router;
+// 0x049d...4dc7 with its leading zero dropped, as Starknet addresses are often written
+const UNPADDED_CALLER: &str = "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+const CANONICAL_CALLER: &str = "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
 
-use zeroxbridge_sequencer::utils::BurnData;
+fn burn_data(caller: &str) -> BurnData {
+    BurnData::new(caller.to_string(), 50000, 123, 1672531200)
+}
 
 #[tokio::test]
 async fn test_compute_hash_api_success() {
@@ -29,6 +28,15 @@ fn test_compute_hash_api_invalid_pubkey() {
     let invalid_pubkey = "not_a_hex_pubkey";
     let result = BurnData::hex_to_bytes32(invalid_pubkey);
     assert!(result.is_err(), "Expected error for invalid hex pubkey");
+
+    assert_eq!(
+        burn_data(invalid_pubkey).validate(),
+        Err(BurnDataError::InvalidCallerLength(16))
+    );
+    assert!(matches!(
+        burn_data(&format!("0x{}", "zz".repeat(32))).validate(),
+        Err(BurnDataError::InvalidCallerHex(_))
+    ));
 }
 
 #[test]
@@ -43,3 +51,62 @@ fn test_utils_reference_solidity_compatibility() {
     let expected = "0x2b6876060a11edcc5dde925cda8fad185f34564e35802fa40ee8ead2f9acb06f";
     assert_eq!(hex_hash, expected);
 }
+
+#[test]
+fn test_canonical_encoding() {
+    let data = burn_data(CANONICAL_CALLER);
+    data.validate().unwrap();
+
+    // caller || amount || nonce || time_stamp, integers as big-endian uint256 words
+    let mut packed = hex::decode(&CANONICAL_CALLER[2..]).unwrap();
+    for value in [50000u64, 123, 1672531200] {
+        packed.extend_from_slice(&[0u8; 24]);
+        packed.extend_from_slice(&value.to_be_bytes());
+    }
+    assert_eq!(packed.len(), 128);
+
+    let expected = format!("0x{}", hex::encode(Keccak256::digest(&packed)));
+    assert_eq!(data.hash_to_hex_string(), expected);
+}
+
+#[test]
+fn test_unpadded_caller_is_rejected() {
+    assert_eq!(
+        burn_data(UNPADDED_CALLER).validate(),
+        Err(BurnDataError::InvalidCallerLength(63))
+    );
+    assert_eq!(
+        burn_data(&format!("0x00{}", &CANONICAL_CALLER[2..])).validate(),
+        Err(BurnDataError::InvalidCallerLength(66))
+    );
+
+    // The prefix is optional, the 64 digits are not
+    burn_data(&CANONICAL_CALLER[2..]).validate().unwrap();
+}
+
+#[test]
+fn test_caller_must_be_a_field_element() {
+    let prime = "0x0800000000000011000000000000000000000000000000000000000000000001";
+    let largest_felt = "0x0800000000000011000000000000000000000000000000000000000000000000";
+
+    assert_eq!(
+        burn_data(prime).validate(),
+        Err(BurnDataError::CallerNotFieldElement)
+    );
+    assert_eq!(
+        burn_data(&format!("0x{}", "ff".repeat(32))).validate(),
+        Err(BurnDataError::CallerNotFieldElement)
+    );
+    burn_data(largest_felt).validate().unwrap();
+}
+
+#[test]
+fn test_zero_amount_and_timestamp_are_rejected() {
+    let mut data = burn_data(CANONICAL_CALLER);
+    data.amount = 0;
+    assert_eq!(data.validate(), Err(BurnDataError::ZeroAmount));
+
+    let mut data = burn_data(CANONICAL_CALLER);
+    data.time_stamp = 0;
+    assert_eq!(data.validate(), Err(BurnDataError::ZeroTimestamp));
+}
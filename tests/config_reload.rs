@@ -0,0 +1,171 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode, Uri},
+    Router,
+};
+use ethers::signers::LocalWallet;
+use ethers::types::H256;
+use serde_json::{json, Value};
+use sqlx::types::BigDecimal;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use utils::{append_on_l1, create_test_app, random_commitment, unique_network, ADMIN_KEYS};
+use zeroxbridge_sequencer::api::auth::{admin_request_hash, ADMIN_SIGNATURE_HEADERS};
+use zeroxbridge_sequencer::api::routes::create_router_with_live_config;
+use zeroxbridge_sequencer::config::LiveConfig;
+use zeroxbridge_sequencer::db::database::upsert_deposit;
+use zeroxbridge_sequencer::queue::l1_queue::{L1BlockSource, L1QueueProcessor, ValidationError};
+
+struct FixedBlock(u64);
+
+#[async_trait]
+impl L1BlockSource for FixedBlock {
+    async fn latest_block_number(&self) -> Result<u64, ValidationError> {
+        Ok(self.0)
+    }
+}
+
+/// Writes the repository's `config.toml` with `queue.process_interval_sec` changed
+fn write_config(dir: &TempDir, process_interval_sec: u64) -> PathBuf {
+    write_config_replacing(
+        dir,
+        "process_interval_sec = 5\n",
+        &format!("process_interval_sec = {}\n", process_interval_sec),
+    )
+}
+
+/// Writes the repository's `config.toml` with `from` replaced by `to`
+fn write_config_replacing(dir: &TempDir, from: &str, to: &str) -> PathBuf {
+    let config = include_str!("../config.toml");
+    assert!(config.contains(from), "config.toml has no {:?}", from);
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, config.replace(from, to)).unwrap();
+    path
+}
+
+/// POSTs to `/admin/config/reload` with the admins' signatures
+async fn reload(router: &Router) -> (StatusCode, Value) {
+    let method = Method::POST;
+    let uri: Uri = "/admin/config/reload".parse().unwrap();
+    let hash = H256::from(admin_request_hash(&method, &uri, b""));
+
+    let mut request = Request::builder().method(method).uri(uri);
+    for (header, key) in ADMIN_SIGNATURE_HEADERS.iter().zip(ADMIN_KEYS) {
+        let wallet: LocalWallet = key.parse().unwrap();
+        let signature = wallet.sign_hash(hash).unwrap();
+        request = request.header(*header, format!("0x{}", signature));
+    }
+
+    let response = router
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_reload_swaps_in_the_new_config() {
+    let app = create_test_app().await;
+    let dir = TempDir::new().unwrap();
+    let live_config = LiveConfig::new(app.config.clone()).with_path(write_config(&dir, 7));
+    let router = create_router_with_live_config(app.db.clone(), live_config.clone());
+
+    let (status, body) = reload(&router).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["changed"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("queue")));
+    assert_eq!(live_config.snapshot().queue.process_interval_sec, 7);
+
+    // Reloading the same file again changes nothing
+    let (status, body) = reload(&router).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["changed"], json!([]));
+}
+
+#[tokio::test]
+async fn test_invalid_config_is_rejected_and_not_swapped_in() {
+    let app = create_test_app().await;
+    let dir = TempDir::new().unwrap();
+    let live_config = LiveConfig::new(app.config.clone()).with_path(write_config(&dir, 0));
+    let router = create_router_with_live_config(app.db.clone(), live_config.clone());
+
+    let (status, body) = reload(&router).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_config");
+    assert_eq!(
+        body["error"]["details"]["errors"],
+        json!(["queue.process_interval_sec must be greater than 0"])
+    );
+    assert_eq!(live_config.snapshot(), app.config);
+}
+
+#[tokio::test]
+async fn test_missing_config_file_is_rejected() {
+    let app = create_test_app().await;
+    let dir = TempDir::new().unwrap();
+    let live_config =
+        LiveConfig::new(app.config.clone()).with_path(dir.path().join("missing.toml"));
+    let router = create_router_with_live_config(app.db.clone(), live_config.clone());
+
+    let (status, body) = reload(&router).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "config_unreadable");
+    assert_eq!(live_config.snapshot(), app.config);
+}
+
+#[tokio::test]
+async fn test_reloaded_confirmation_depth_applies_to_the_running_l1_queue() {
+    let app = create_test_app().await;
+    let dir = TempDir::new().unwrap();
+    let path = write_config_replacing(
+        &dir,
+        "confirmation_blocks = 12 ",
+        "confirmation_blocks = 3 ",
+    );
+    let live_config = LiveConfig::new(app.config.clone()).with_path(path);
+    let router = create_router_with_live_config(app.db.clone(), live_config.clone());
+
+    // A pending deposit whose commitment was appended 5 blocks ago
+    let network = unique_network("reload");
+    let commitment_hash = random_commitment();
+    upsert_deposit(
+        &app.db,
+        &network,
+        "0xconfigreload",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+        "pending",
+        None,
+    )
+    .await
+    .unwrap();
+    append_on_l1(&app.db, &network, &commitment_hash, 0).await;
+
+    let processor = L1QueueProcessor::new(
+        app.db.clone(),
+        network,
+        app.config.l1_queue.clone(),
+        app.config.queue.max_retries,
+        FixedBlock(105),
+    )
+    .with_live_config(live_config);
+
+    // 12 confirmations are needed at startup
+    assert_eq!(processor.process_deposits().await.unwrap(), 0);
+
+    let (status, body) = reload(&router).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(processor.process_deposits().await.unwrap(), 1);
+}
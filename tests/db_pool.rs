@@ -0,0 +1,74 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use serde_json::Value;
+use tower::ServiceExt;
+use utils::{create_test_app, create_test_config};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::DatabaseConfig;
+use zeroxbridge_sequencer::db::database::{get_db_pool, ping_db};
+
+async fn single_connection_config() -> DatabaseConfig {
+    DatabaseConfig {
+        max_connections: 1,
+        connection_timeout_secs: 1,
+        ..create_test_app().await.config.database.clone()
+    }
+}
+
+async fn backend_pid(pool: &sqlx::PgPool) -> i32 {
+    sqlx::query_scalar!(r#"SELECT pg_backend_pid() AS "pid!""#)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_pool_replaces_terminated_connection() {
+    let config = single_connection_config().await;
+    let pool = get_db_pool(&config).await.unwrap();
+    let pid = backend_pid(&pool).await;
+
+    // The server closes the only connection while it sits idle in the pool
+    let admin = create_test_app().await.db.clone();
+    // Waits up to 5s for the backend to be gone, rather than only signalling it
+    let terminated: bool = sqlx::query_scalar!(
+        r#"SELECT pg_terminate_backend($1, 5000) AS "terminated!""#,
+        pid
+    )
+    .fetch_one(&admin)
+    .await
+    .unwrap();
+    assert!(terminated);
+
+    ping_db(&pool).await.unwrap();
+    assert_ne!(backend_pid(&pool).await, pid);
+}
+
+#[tokio::test]
+async fn test_exhausted_pool_times_out() {
+    let pool = get_db_pool(&single_connection_config().await)
+        .await
+        .unwrap();
+    let _held = pool.acquire().await.unwrap();
+
+    let err = ping_db(&pool).await.unwrap_err();
+    assert!(matches!(err, sqlx::Error::PoolTimedOut), "{}", err);
+
+    let router = create_router_with_config(pool.clone(), &create_test_config());
+    let request = Request::builder()
+        .uri("/health")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "db_unavailable");
+}
@@ -1,21 +1,58 @@
-#[path = "utils.rs"]
-mod utils;
+#[path = "harness/mod.rs"]
+mod harness;
 
-use std::usize;
 
 use axum::{
     body::Body,
     http::{Request, StatusCode},
+    Router,
 };
+use harness::utils::random_commitment;
+use harness::Harness;
 use serde_json::json;
 use tower::ServiceExt;
-use utils::create_test_app;
-use zeroxbridge_sequencer::api::routes::create_router;
+
+use zeroxbridge_sequencer::utils::{is_valid_felt252, validate_starknet_address, STARK_PRIME_HEX};
+
+async fn post_deposit(router: Router, commitment_hash: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/deposit")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "stark_pub_key": "0x123",
+                "amount": 1000,
+                "commitment_hash": commitment_hash
+            })
+            .to_string(),
+        ))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn assert_commitment_rejected(commitment_hash: &str, reason: &str) {
+    let harness = Harness::new().launch().await;
+    let router = harness.router();
+
+    let (status, body) = post_deposit(router, commitment_hash).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_commitment_hash");
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(message.contains(reason), "unexpected message: {}", message);
+}
 
 #[tokio::test]
 async fn test_hello_world() {
-    let app = create_test_app().await;
-    let router = create_router(app.db.clone());
+    let harness = Harness::new().launch().await;
+    let router = harness.router();
 
     let request = Request::builder()
         .method("GET")
@@ -39,8 +76,68 @@ async fn test_hello_world() {
 
 #[tokio::test]
 async fn test_post_valid_deposit() {
-    let app = create_test_app().await;
-    let router = create_router(app.db.clone());
+    let harness = Harness::new().launch().await;
+    let router = harness.router();
+
+    let (status, parsed) = post_deposit(router, &random_commitment()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert!(parsed.get("deposit_id").is_some());
+    // Null until some deposit got its proof in the last 24 hours
+    let estimate = parsed.get("estimated_proof_time_secs").unwrap();
+    assert!(estimate.is_null() || estimate.as_f64().unwrap() >= 0.0);
+}
+
+#[tokio::test]
+async fn test_deposit_commitment_hash_without_prefix() {
+    assert_commitment_rejected(&"ab".repeat(33), "start with 0x").await;
+}
+
+#[tokio::test]
+async fn test_deposit_commitment_hash_with_wrong_length() {
+    assert_commitment_rejected(&format!("0x{}", "ab".repeat(31)), "66 characters").await;
+    assert_commitment_rejected(&format!("0x{}", "ab".repeat(33)), "66 characters").await;
+}
+
+#[tokio::test]
+async fn test_deposit_commitment_hash_with_non_hex_digits() {
+    assert_commitment_rejected(&format!("0x{}zz", "ab".repeat(31)), "hex digits").await;
+}
+
+#[tokio::test]
+async fn test_deposit_commitment_hash_at_or_above_the_stark_prime() {
+    assert_commitment_rejected(STARK_PRIME_HEX, "Stark prime").await;
+    assert_commitment_rejected(
+        "0x0800000000000011000000000000000000000000000000000000000000000002",
+        "Stark prime",
+    )
+    .await;
+    assert_commitment_rejected(&format!("0x{}", "ff".repeat(32)), "Stark prime").await;
+}
+
+#[tokio::test]
+async fn test_deposit_commitment_hash_just_below_the_stark_prime() {
+    let harness = Harness::new().launch().await;
+    let router = harness.router();
+    // Close to the largest felt, P - 1, with random low digits to stay unique across runs
+    let commitment_hash = format!(
+        "0x0800000000000010{}{:016x}",
+        "f".repeat(32),
+        rand::random::<u64>()
+    );
+    assert!(is_valid_felt252(&commitment_hash));
+
+    let (status, _) = post_deposit(router, &commitment_hash).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_deposit_stark_pub_key_out_of_address_range() {
+    let harness = Harness::new().launch().await;
+    let router = harness.router();
+    // 2^251 is a valid felt but not a valid Starknet address
+    let stark_pub_key = format!("0x8{}", "0".repeat(62));
+    assert!(validate_starknet_address(&stark_pub_key).is_err());
 
     let request = Request::builder()
         .method("POST")
@@ -48,30 +145,27 @@ async fn test_post_valid_deposit() {
         .header("content-type", "application/json")
         .body(Body::from(
             json!({
-                "stark_pub_key": "0xuser123",
+                "stark_pub_key": stark_pub_key,
                 "amount": 1000,
-                "commitment_hash": "0xcommitment123"
+                "commitment_hash": random_commitment(),
             })
             .to_string(),
         ))
         .unwrap();
 
     let response = router.oneshot(request).await.unwrap();
-    println!("Status: {:?}", response.status());
-    assert_eq!(response.status(), StatusCode::OK);
-
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     let body = axum::body::to_bytes(response.into_body(), usize::MAX)
         .await
         .unwrap();
-    let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
-
-    assert!(parsed.get("deposit_id").is_some());
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "invalid_stark_key");
 }
 
 #[tokio::test]
 async fn test_deposit_missing_commitment_hash() {
-    let app = create_test_app().await;
-    let router = create_router(app.db.clone());
+    let harness = Harness::new().launch().await;
+    let router = harness.router();
 
     let request = Request::builder()
         .method("POST")
@@ -103,8 +197,8 @@ async fn test_deposit_missing_commitment_hash() {
 
 #[tokio::test]
 async fn test_semantic_invalid_deposit() {
-    let app = create_test_app().await;
-    let router = create_router(app.db.clone());
+    let harness = Harness::new().launch().await;
+    let router = harness.router();
 
     let request = Request::builder()
         .method("POST")
@@ -140,26 +234,9 @@ async fn test_semantic_invalid_deposit() {
 
 #[tokio::test]
 async fn test_get_pending_deposits() {
-    let app = create_test_app().await;
-    let router = create_router(app.db.clone());
-
-    // First create a test deposit
-    let post_request = Request::builder()
-        .method("POST")
-        .uri("/deposit")
-        .header("content-type", "application/json")
-        .body(Body::from(
-            json!({
-                "stark_pub_key": "0xtest123",
-                "amount": 500
-            })
-            .to_string(),
-        ))
-        .unwrap();
-
-    let _ = router.clone().oneshot(post_request).await.unwrap();
+    let harness = Harness::new().with_deposit("0x123", 500).launch().await;
+    let router = harness.router();
 
-    // Then test the GET endpoint
     let get_request = Request::builder()
         .method("GET")
         .uri("/deposit")
@@ -0,0 +1,96 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::db::database::{
+    fetch_deposit_audit_log, insert_deposit, update_deposit_status,
+};
+
+async fn setup() -> (Router, PgPool) {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    (router, app.db.clone())
+}
+
+async fn create_deposit(pool: &PgPool) -> i32 {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    insert_deposit(
+        pool,
+        "0xcancel123",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+    )
+    .await
+    .unwrap()
+}
+
+fn cancel_request(id: i32, token: &str) -> Request<Body> {
+    Request::builder()
+        .method("DELETE")
+        .uri(format!("/deposits/{}?reason=invalid%20amount", id))
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_cancel_pending_deposit_writes_audit_log() {
+    let (router, pool) = setup().await;
+    let id = create_deposit(&pool).await;
+
+    let response = router
+        .oneshot(cancel_request(id, "test-admin-token"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let audit_log = fetch_deposit_audit_log(&pool, id).await.unwrap();
+    assert_eq!(audit_log.len(), 1);
+    assert_eq!(audit_log[0].old_status, "pending");
+    assert_eq!(audit_log[0].new_status, "CANCELLED");
+    assert_eq!(audit_log[0].reason, "invalid amount");
+}
+
+#[tokio::test]
+async fn test_cancel_processed_deposit_is_rejected() {
+    let (router, pool) = setup().await;
+    let id = create_deposit(&pool).await;
+
+    let mut conn = pool.acquire().await.unwrap();
+    update_deposit_status(&mut conn, id, "PROCESSED")
+        .await
+        .unwrap();
+
+    let response = router
+        .oneshot(cancel_request(id, "test-admin-token"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let audit_log = fetch_deposit_audit_log(&pool, id).await.unwrap();
+    assert!(audit_log.is_empty());
+}
+
+#[tokio::test]
+async fn test_cancel_deposit_requires_admin_token() {
+    let (router, pool) = setup().await;
+    let id = create_deposit(&pool).await;
+
+    let response = router
+        .oneshot(cancel_request(id, "wrong-token"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let audit_log = fetch_deposit_audit_log(&pool, id).await.unwrap();
+    assert!(audit_log.is_empty());
+}
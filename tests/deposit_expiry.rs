@@ -0,0 +1,177 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::api::types::RefreshDepositResponse;
+use zeroxbridge_sequencer::config::{JanitorConfig, NetworkId};
+use zeroxbridge_sequencer::db::database::{
+    fetch_deposit_audit_log, insert_deposit, insert_deposit_hash_event, update_deposit_status,
+    DepositHashAppended,
+};
+use zeroxbridge_sequencer::maintenance::Janitor;
+
+const EXPIRY_HOURS: i64 = 72;
+
+// Only the expiry runs, the other janitor actions are covered by tests/janitor.rs
+fn janitor(pool: &PgPool) -> Janitor {
+    let config = JanitorConfig {
+        clean_proof_dirs: false,
+        reset_stale_processing: false,
+        vacuum_dead_letters: false,
+        expire_unseen_deposits: true,
+        check_withdrawal_nonce_gaps: false,
+        ..JanitorConfig::default()
+    };
+    Janitor::new(pool.clone(), config).with_deposit_expiry_hours(EXPIRY_HOURS as u64)
+}
+
+/// Creates a deposit in `status` submitted `age_hours` ago, returning its id and
+/// commitment
+async fn create_deposit(pool: &PgPool, status: &str, age_hours: i64) -> (i32, Vec<u8>) {
+    let commitment = Uuid::new_v4().as_bytes().to_vec();
+    let id = insert_deposit(
+        pool,
+        "0xexpiry",
+        &BigDecimal::from(1000),
+        &format!("0x{}", hex::encode(&commitment)),
+    )
+    .await
+    .unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    update_deposit_status(&mut conn, id, status).await.unwrap();
+    sqlx::query!(
+        "UPDATE deposits SET created_at = NOW() - $2::BIGINT * INTERVAL '1 hour' WHERE id = $1",
+        id,
+        age_hours
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    (id, commitment)
+}
+
+async fn record_l1_event(pool: &PgPool, commitment: Vec<u8>) {
+    let event = DepositHashAppended {
+        id: 0,
+        index: 0,
+        commitment_hash: commitment,
+        root_hash: vec![0; 32],
+        elements_count: 1,
+        block_number: 100,
+        created_at: None,
+        updated_at: None,
+    };
+    insert_deposit_hash_event(pool, &NetworkId::default(), &event)
+        .await
+        .unwrap();
+}
+
+async fn status(pool: &PgPool, id: i32) -> String {
+    sqlx::query_scalar!("SELECT status FROM deposits WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+fn refresh_request(id: i32) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/deposits/{}/refresh", id))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_deposits_never_seen_on_l1_expire_after_the_ttl() {
+    let app = create_test_app().await;
+    let (pending, _) = create_deposit(&app.db, "pending", EXPIRY_HOURS + 1).await;
+    let (queued, _) = create_deposit(&app.db, "PENDING_TREE_INCLUSION", EXPIRY_HOURS + 1).await;
+    let (recent, _) = create_deposit(&app.db, "pending", EXPIRY_HOURS - 1).await;
+
+    let report = janitor(&app.db).run_once().await.unwrap();
+
+    assert!(report.expired_deposits.contains(&pending));
+    assert!(report.expired_deposits.contains(&queued));
+    assert!(!report.expired_deposits.contains(&recent));
+    assert_eq!(status(&app.db, queued).await, "EXPIRED");
+    assert_eq!(status(&app.db, recent).await, "pending");
+
+    let audit_log = fetch_deposit_audit_log(&app.db, queued).await.unwrap();
+    assert_eq!(audit_log.len(), 1);
+    assert_eq!(audit_log[0].old_status, "PENDING_TREE_INCLUSION");
+    assert_eq!(audit_log[0].new_status, "EXPIRED");
+    assert_eq!(audit_log[0].reason, "no L1 deposit event within 72 hours");
+}
+
+#[tokio::test]
+async fn test_deposits_seen_on_l1_never_expire() {
+    let app = create_test_app().await;
+    let (confirmed, commitment) =
+        create_deposit(&app.db, "PENDING_TREE_INCLUSION", 10 * EXPIRY_HOURS).await;
+    record_l1_event(&app.db, commitment).await;
+
+    let report = janitor(&app.db).run_once().await.unwrap();
+
+    assert!(!report.expired_deposits.contains(&confirmed));
+    assert_eq!(status(&app.db, confirmed).await, "PENDING_TREE_INCLUSION");
+}
+
+#[tokio::test]
+async fn test_refreshed_deposit_goes_back_to_the_l1_queue() {
+    let app = create_test_app().await;
+    let (id, commitment) = create_deposit(&app.db, "pending", EXPIRY_HOURS + 1).await;
+    janitor(&app.db).run_once().await.unwrap();
+    assert_eq!(status(&app.db, id).await, "EXPIRED");
+    sqlx::query!("UPDATE deposits SET retry_count = 3 WHERE id = $1", id)
+        .execute(&app.db)
+        .await
+        .unwrap();
+
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    let response = router.oneshot(refresh_request(id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let refreshed: RefreshDepositResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(refreshed.old_status, "EXPIRED");
+    assert_eq!(refreshed.status, "pending");
+
+    let retry_count: i32 =
+        sqlx::query_scalar!("SELECT retry_count FROM deposits WHERE id = $1", id)
+            .fetch_one(&app.db)
+            .await
+            .unwrap();
+    assert_eq!(retry_count, 0);
+    let audit_log = fetch_deposit_audit_log(&app.db, id).await.unwrap();
+    assert_eq!(audit_log.last().unwrap().reason, "refreshed by user");
+
+    // Now that the L1 transaction was made, the deposit is not expired again
+    record_l1_event(&app.db, commitment).await;
+    let report = janitor(&app.db).run_once().await.unwrap();
+    assert!(!report.expired_deposits.contains(&id));
+    assert_eq!(status(&app.db, id).await, "pending");
+}
+
+#[tokio::test]
+async fn test_refresh_rejects_deposits_that_are_not_expired() {
+    let app = create_test_app().await;
+    let (id, _) = create_deposit(&app.db, "pending", 0).await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+
+    let response = router.clone().oneshot(refresh_request(id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = router.oneshot(refresh_request(i32::MAX)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
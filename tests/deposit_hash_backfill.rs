@@ -0,0 +1,220 @@
+#[path = "utils.rs"]
+mod utils;
+
+use alloy::primitives::{B256, U256};
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol_types::SolEvent;
+use sqlx::PgPool;
+use std::error::Error;
+use std::future::Future;
+use std::sync::Mutex;
+use utils::{create_test_app, unique_network};
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{
+    get_last_processed_block, insert_deposit_hash_event, DepositHashAppended,
+};
+use zeroxbridge_sequencer::events::l1_event_watcher::{
+    TestEthereumProvider, ZeroXBridge, DEPOSIT_HASH_BLOCK_TRACKER_KEY,
+};
+use zeroxbridge_sequencer::events::{backfill_deposit_hashes, BackfillProgress};
+
+const CONTRACT: &str = "0x00000000000000000000000000000000000000aa";
+
+/// L1 history holding one `DepositHashAppended` event every ten blocks
+struct MockHistory {
+    logs: Vec<Log>,
+    log_queries: Mutex<Vec<(u64, u64)>>,
+}
+
+impl MockHistory {
+    /// Events at blocks 5, 15, ..., up to `latest`
+    fn new(latest: u64) -> Self {
+        let logs = (5..=latest)
+            .step_by(10)
+            .enumerate()
+            .map(|(index, block_number)| {
+                let event = ZeroXBridge::DepositHashAppended {
+                    index: U256::from(index),
+                    commitmentHash: U256::from_be_bytes(rand::random::<[u8; 32]>()) >> 5,
+                    rootHash: U256::from_be_bytes(rand::random::<[u8; 32]>()),
+                    elementsCount: U256::from(index + 1),
+                };
+                Log {
+                    inner: alloy::primitives::Log {
+                        address: CONTRACT.parse().unwrap(),
+                        data: event.encode_log_data(),
+                    },
+                    block_number: Some(block_number),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        Self {
+            logs,
+            log_queries: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn event(&self, position: usize) -> DepositHashAppended {
+        let log = &self.logs[position];
+        let event = ZeroXBridge::DepositHashAppended::decode_log_data(log.data()).unwrap();
+        DepositHashAppended {
+            id: 0,
+            index: position as i64,
+            commitment_hash: event.commitmentHash.to_be_bytes::<32>().to_vec(),
+            root_hash: event.rootHash.to_be_bytes::<32>().to_vec(),
+            elements_count: position as i64 + 1,
+            block_number: log.block_number.unwrap() as i64,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn log_queries(&self) -> Vec<(u64, u64)> {
+        self.log_queries.lock().unwrap().clone()
+    }
+}
+
+impl TestEthereumProvider for MockHistory {
+    fn get_logs(
+        &self,
+        filter: &Filter,
+    ) -> impl Future<Output = Result<Vec<Log>, Box<dyn Error + Send + Sync>>> + Send {
+        let from = filter.get_from_block().unwrap_or(0);
+        let to = filter.get_to_block().unwrap_or(u64::MAX);
+        self.log_queries.lock().unwrap().push((from, to));
+        let logs: Vec<Log> = self
+            .logs
+            .iter()
+            .filter(|log| (from..=to).contains(&log.block_number.unwrap()))
+            .cloned()
+            .collect();
+        async move { Ok(logs) }
+    }
+
+    async fn get_block_number(&self) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        Ok(0)
+    }
+
+    async fn get_block_hash(
+        &self,
+        _number: u64,
+    ) -> Result<Option<B256>, Box<dyn Error + Send + Sync>> {
+        Ok(None)
+    }
+}
+
+async fn backfill(
+    pool: &PgPool,
+    network: &NetworkId,
+    history: &MockHistory,
+    from_block: u64,
+    to_block: u64,
+) -> (BackfillProgress, Vec<BackfillProgress>) {
+    let mut reports = Vec::new();
+    let progress = backfill_deposit_hashes(
+        pool,
+        network,
+        history,
+        CONTRACT,
+        from_block,
+        to_block,
+        10,
+        |progress| reports.push(progress.clone()),
+    )
+    .await
+    .unwrap();
+    (progress, reports)
+}
+
+async fn forget_tracker(pool: &PgPool, network: &NetworkId) {
+    sqlx::query!(
+        "DELETE FROM block_trackers WHERE network = $1",
+        network.as_str()
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Stored events of `network` as (block number, index), one entry per row
+async fn stored_events(pool: &PgPool, network: &NetworkId) -> Vec<(i64, i64)> {
+    sqlx::query!(
+        "SELECT block_number, index FROM deposit_hashes WHERE network = $1 ORDER BY block_number",
+        network.as_str()
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|row| (row.block_number, row.index))
+    .collect()
+}
+
+#[tokio::test]
+async fn test_overlapping_backfills_store_each_event_once() {
+    let app = create_test_app().await;
+    let network = unique_network("backfill");
+    let history = MockHistory::new(99);
+
+    let (progress, _) = backfill(&app.db, &network, &history, 0, 59).await;
+    assert_eq!((progress.rows_fetched, progress.rows_inserted), (6, 6));
+
+    // Without the tracker the second range starts inside the first one
+    forget_tracker(&app.db, &network).await;
+    let (progress, _) = backfill(&app.db, &network, &history, 40, 99).await;
+    assert_eq!((progress.rows_fetched, progress.rows_inserted), (6, 4));
+
+    forget_tracker(&app.db, &network).await;
+    let (progress, _) = backfill(&app.db, &network, &history, 0, 99).await;
+    assert_eq!((progress.rows_fetched, progress.rows_inserted), (10, 0));
+
+    let expected: Vec<(i64, i64)> = (0..10).map(|index| (index * 10 + 5, index)).collect();
+    assert_eq!(stored_events(&app.db, &network).await, expected);
+}
+
+#[tokio::test]
+async fn test_backfill_reports_every_chunk_and_resumes_after_tracked_block() {
+    let app = create_test_app().await;
+    let network = unique_network("backfill");
+    let history = MockHistory::new(99);
+
+    let (progress, reports) = backfill(&app.db, &network, &history, 0, 44).await;
+    assert_eq!(progress.blocks_total(), 45);
+    assert_eq!(progress.blocks_done, 45);
+    let done: Vec<_> = reports
+        .iter()
+        .map(|report| (report.blocks_done, report.rows_inserted))
+        .collect();
+    assert_eq!(done, vec![(10, 1), (20, 2), (30, 3), (40, 4), (45, 4)]);
+    assert_eq!(
+        get_last_processed_block(&app.db, &network, DEPOSIT_HASH_BLOCK_TRACKER_KEY)
+            .await
+            .unwrap(),
+        Some(44)
+    );
+
+    // A second run asked for the whole range only fetches what is left
+    let (progress, _) = backfill(&app.db, &network, &history, 0, 99).await;
+    assert_eq!(progress.from_block, 45);
+    assert_eq!(progress.blocks_done, 55);
+    assert_eq!(progress.rows_inserted, 6);
+    assert_eq!(history.log_queries()[5], (45, 54));
+    assert_eq!(history.log_queries().len(), 11);
+    assert_eq!(stored_events(&app.db, &network).await.len(), 10);
+}
+
+#[tokio::test]
+async fn test_events_stored_by_the_live_watcher_are_skipped() {
+    let app = create_test_app().await;
+    let network = unique_network("backfill");
+    let history = MockHistory::new(49);
+    insert_deposit_hash_event(&app.db, &network, &history.event(2))
+        .await
+        .unwrap();
+
+    let (progress, _) = backfill(&app.db, &network, &history, 0, 49).await;
+
+    assert_eq!((progress.rows_fetched, progress.rows_inserted), (5, 4));
+    assert_eq!(stored_events(&app.db, &network).await.len(), 5);
+}
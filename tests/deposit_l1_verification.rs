@@ -0,0 +1,206 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use tower::ServiceExt;
+use utils::{create_test_app, random_commitment};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::{L1QueueConfig, NetworkId};
+use zeroxbridge_sequencer::db::database::{insert_deposit_hash_event, DepositHashAppended};
+use zeroxbridge_sequencer::queue::deposit_reconciler::DepositReconciler;
+use zeroxbridge_sequencer::queue::l1_queue::{
+    commitment_to_bytes32, L1BlockSource, ValidationError,
+};
+
+const EVENT_BLOCK: i64 = 100;
+const TTL_SECS: u64 = 60 * 60;
+
+struct FixedBlock(u64);
+
+#[async_trait]
+impl L1BlockSource for FixedBlock {
+    async fn latest_block_number(&self) -> Result<u64, ValidationError> {
+        Ok(self.0)
+    }
+}
+
+async fn setup() -> (Router, PgPool) {
+    let app = create_test_app().await;
+    let mut config = app.config.clone();
+    config.server.require_l1_verification = true;
+    let router = create_router_with_config(app.db.clone(), &config);
+    (router, app.db.clone())
+}
+
+fn reconciler(pool: &PgPool, latest_block: u64) -> DepositReconciler<FixedBlock> {
+    let config = L1QueueConfig {
+        confirmation_blocks: 12,
+        awaiting_confirmation_ttl_sec: TTL_SECS,
+        ..L1QueueConfig::default()
+    };
+    DepositReconciler::new(
+        pool.clone(),
+        NetworkId::default(),
+        config,
+        FixedBlock(latest_block),
+    )
+}
+
+async fn post_deposit(router: Router, commitment_hash: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/deposit")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "stark_pub_key": "0x11e71f1ca7e",
+                "amount": 1000,
+                "commitment_hash": commitment_hash
+            })
+            .to_string(),
+        ))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn record_deposit_hash_event(pool: &PgPool, commitment_hash: &str) {
+    let event = DepositHashAppended {
+        id: 0,
+        index: 0,
+        commitment_hash: commitment_to_bytes32(commitment_hash).unwrap().to_vec(),
+        root_hash: vec![0; 32],
+        elements_count: 1,
+        block_number: EVENT_BLOCK,
+        created_at: None,
+        updated_at: None,
+    };
+    insert_deposit_hash_event(pool, &NetworkId::default(), &event)
+        .await
+        .unwrap();
+}
+
+async fn deposit_status(pool: &PgPool, id: i32) -> String {
+    sqlx::query_scalar!("SELECT status FROM deposits WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+fn deposit_id(body: &Value) -> i32 {
+    body["deposit_id"].as_i64().unwrap() as i32
+}
+
+#[tokio::test]
+async fn test_deposit_submitted_before_l1_event_awaits_confirmation() {
+    let (router, pool) = setup().await;
+
+    let (status, body) = post_deposit(router, &random_commitment()).await;
+
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(body["status"], "AWAITING_L1_CONFIRMATION");
+    assert_eq!(
+        deposit_status(&pool, deposit_id(&body)).await,
+        "AWAITING_L1_CONFIRMATION"
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_seen_on_l1_is_accepted() {
+    let (router, pool) = setup().await;
+    let commitment_hash = random_commitment();
+    record_deposit_hash_event(&pool, &commitment_hash).await;
+
+    let (status, body) = post_deposit(router, &commitment_hash).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "pending");
+    assert_eq!(deposit_status(&pool, deposit_id(&body)).await, "pending");
+}
+
+#[tokio::test]
+async fn test_invalid_commitment_is_rejected() {
+    let (router, _) = setup().await;
+
+    let (status, body) = post_deposit(router, "0xnothex").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_commitment_hash");
+}
+
+#[tokio::test]
+async fn test_late_l1_event_promotes_awaiting_deposit() {
+    let (router, pool) = setup().await;
+    let commitment_hash = random_commitment();
+
+    let (status, body) = post_deposit(router, &commitment_hash).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let id = deposit_id(&body);
+
+    let summary = reconciler(&pool, EVENT_BLOCK as u64 + 12)
+        .reconcile()
+        .await
+        .unwrap();
+    assert!(!summary.promoted.contains(&id));
+
+    record_deposit_hash_event(&pool, &commitment_hash).await;
+
+    // Not enough confirmations yet
+    let summary = reconciler(&pool, EVENT_BLOCK as u64 + 1)
+        .reconcile()
+        .await
+        .unwrap();
+    assert!(!summary.promoted.contains(&id));
+    assert_eq!(deposit_status(&pool, id).await, "AWAITING_L1_CONFIRMATION");
+
+    let summary = reconciler(&pool, EVENT_BLOCK as u64 + 12)
+        .reconcile()
+        .await
+        .unwrap();
+    assert!(summary.promoted.contains(&id));
+    assert_eq!(deposit_status(&pool, id).await, "PENDING_TREE_INCLUSION");
+}
+
+#[tokio::test]
+async fn test_awaiting_deposit_expires_after_ttl() {
+    let (router, pool) = setup().await;
+
+    let (_, body) = post_deposit(router.clone(), &random_commitment()).await;
+    let stale = deposit_id(&body);
+    sqlx::query!(
+        "UPDATE deposits SET created_at = NOW() - $2::BIGINT * INTERVAL '1 second' WHERE id = $1",
+        stale,
+        2 * TTL_SECS as i64
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (_, body) = post_deposit(router, &random_commitment()).await;
+    let fresh = deposit_id(&body);
+
+    let summary = reconciler(&pool, EVENT_BLOCK as u64)
+        .reconcile()
+        .await
+        .unwrap();
+
+    assert!(summary.expired.contains(&stale));
+    assert!(!summary.expired.contains(&fresh));
+    assert_eq!(deposit_status(&pool, stale).await, "EXPIRED");
+    assert_eq!(
+        deposit_status(&pool, fresh).await,
+        "AWAITING_L1_CONFIRMATION"
+    );
+}
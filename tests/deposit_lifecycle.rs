@@ -0,0 +1,65 @@
+#[path = "harness/mod.rs"]
+mod harness;
+
+use harness::Harness;
+use starknet::core::types::Felt;
+use zeroxbridge_sequencer::db::database::{
+    DEPOSIT_STATUS_PENDING_PROOF_GENERATION, DEPOSIT_STATUS_QUEUED_FOR_RELAY,
+};
+
+#[tokio::test]
+async fn test_deposit_is_relayed_to_starknet() {
+    let mut harness = Harness::new()
+        .with_deposit("0x123", 1000)
+        .start_l1_queue()
+        .start_tree_builder()
+        .start_proof_client_simulated()
+        .start_l2_queue()
+        .start_relayer()
+        .launch()
+        .await;
+
+    harness.settle().await;
+
+    let deposit = harness.deposits()[0].clone();
+    assert_eq!(
+        harness.deposit_status(&deposit).await,
+        DEPOSIT_STATUS_QUEUED_FOR_RELAY
+    );
+    let transaction = sqlx::query!(
+        "SELECT status, tx_hash FROM l2_transactions WHERE deposit_id = $1",
+        deposit.id
+    )
+    .fetch_one(harness.db())
+    .await
+    .unwrap();
+    assert_eq!(transaction.status, "completed");
+
+    // One transaction, sent to the L2 bridge
+    let sent = harness.starknet().sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(transaction.tx_hash, Some(Felt::ONE.to_hex_string()));
+    let bridge = Felt::from_hex(&harness.config().contracts.l2_contract_address).unwrap();
+    assert!(sent[0].iter().all(|call| call.to == bridge));
+}
+
+#[tokio::test]
+async fn test_deposits_wait_for_the_services_that_are_not_started() {
+    let mut harness = Harness::new()
+        .with_deposit("0x123", 1000)
+        .with_deposit("0x456", 2000)
+        .start_l1_queue()
+        .start_tree_builder()
+        .launch()
+        .await;
+
+    harness.settle().await;
+
+    for deposit in harness.deposits().to_vec() {
+        assert_eq!(
+            harness.deposit_status(&deposit).await,
+            DEPOSIT_STATUS_PENDING_PROOF_GENERATION
+        );
+    }
+    assert!(harness.starknet().sent_transactions().is_empty());
+}
@@ -0,0 +1,174 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::db::database::{
+    fetch_deposit_audit_log, get_avg_proof_generation_time_at, get_deposit_stats, insert_deposit,
+    mark_deposit_proof_generated,
+};
+
+async fn create_included_deposit(pool: &PgPool, secs_ago: i64) -> i32 {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    let id = insert_deposit(
+        pool,
+        "0xstats123",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+    )
+    .await
+    .unwrap();
+
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET status = 'PENDING_PROOF_GENERATION',
+        included_at = NOW() - $2::BIGINT * INTERVAL '1 second'
+        WHERE id = $1
+        "#,
+        id,
+        secs_ago
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    id
+}
+
+fn at(timestamp: &str) -> DateTime<Utc> {
+    timestamp.parse().unwrap()
+}
+
+/// Sets a deposit's timestamps, far in the past so deposits of other tests never
+/// fall in the same 24 hours
+async fn set_timestamps(pool: &PgPool, id: i32, created_at: &str, updated_at: &str) {
+    sqlx::query!(
+        "UPDATE deposits SET created_at = $2, updated_at = $3 WHERE id = $1",
+        id,
+        at(created_at),
+        at(updated_at)
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn create_proven_deposit(pool: &PgPool, created_at: &str, updated_at: &str) -> i32 {
+    let id = create_included_deposit(pool, 10).await;
+    let mut conn = pool.acquire().await.unwrap();
+    mark_deposit_proof_generated(&mut conn, id).await.unwrap();
+    set_timestamps(pool, id, created_at, updated_at).await;
+    id
+}
+
+#[tokio::test]
+async fn test_avg_proof_time_covers_deposits_proven_in_the_last_24_hours() {
+    let app = create_test_app().await;
+    // Proven 100 and 300 seconds after creation
+    create_proven_deposit(&app.db, "2001-03-01T10:00:00Z", "2001-03-01T10:01:40Z").await;
+    create_proven_deposit(&app.db, "2001-03-01T12:00:00Z", "2001-03-01T12:05:00Z").await;
+    // Proven more than 24 hours earlier
+    create_proven_deposit(&app.db, "2001-02-27T10:00:00Z", "2001-02-27T11:00:00Z").await;
+    // Ready without its proof having been generated
+    let id = create_included_deposit(&app.db, 10).await;
+    sqlx::query!(
+        "UPDATE deposits SET status = 'READY_FOR_RELAY' WHERE id = $1",
+        id
+    )
+    .execute(&app.db)
+    .await
+    .unwrap();
+    set_timestamps(&app.db, id, "2001-03-01T09:00:00Z", "2001-03-01T13:00:00Z").await;
+
+    let avg = get_avg_proof_generation_time_at(&app.db, at("2001-03-02T00:00:00Z"))
+        .await
+        .unwrap();
+    assert_eq!(avg, Some(200.0));
+}
+
+#[tokio::test]
+async fn test_avg_proof_time_is_none_without_proven_deposits() {
+    let app = create_test_app().await;
+
+    let avg = get_avg_proof_generation_time_at(&app.db, at("1990-01-01T00:00:00Z"))
+        .await
+        .unwrap();
+    assert_eq!(avg, None);
+}
+
+#[tokio::test]
+async fn test_generated_proof_is_recorded_in_audit_log() {
+    let app = create_test_app().await;
+    let id = create_included_deposit(&app.db, 90).await;
+
+    let mut conn = app.db.acquire().await.unwrap();
+    mark_deposit_proof_generated(&mut conn, id).await.unwrap();
+
+    let log = fetch_deposit_audit_log(&app.db, id).await.unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].old_status, "PENDING_PROOF_GENERATION");
+    assert_eq!(log[0].new_status, "READY_FOR_RELAY");
+
+    let stats = get_deposit_stats(&app.db).await.unwrap();
+    assert!(stats.ready_for_relay >= 1);
+    assert!(stats.avg_proof_generation_secs.unwrap() > 0.0);
+    assert!(stats.amount_today >= 1000);
+}
+
+#[tokio::test]
+async fn test_proof_of_deposit_in_other_status_is_not_recorded() {
+    let app = create_test_app().await;
+    let id = create_included_deposit(&app.db, 10).await;
+    sqlx::query!("UPDATE deposits SET status = 'CANCELLED' WHERE id = $1", id)
+        .execute(&app.db)
+        .await
+        .unwrap();
+
+    let mut conn = app.db.acquire().await.unwrap();
+    mark_deposit_proof_generated(&mut conn, id).await.unwrap();
+
+    assert!(fetch_deposit_audit_log(&app.db, id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_stats_endpoint_returns_aggregates() {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("/deposits/stats")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let stats: Value = serde_json::from_slice(&body).unwrap();
+    for key in [
+        "pending",
+        "pending_tree_inclusion",
+        "pending_proof_generation",
+        "ready_for_relay",
+        "failed",
+        "cancelled",
+    ] {
+        assert!(stats[key].is_i64(), "{} is missing", key);
+    }
+    assert!(stats["amount_today"].is_string());
+    assert!(stats.get("avg_proof_time_secs").is_some());
+}
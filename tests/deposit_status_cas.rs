@@ -0,0 +1,145 @@
+#[path = "utils.rs"]
+mod utils;
+
+use sqlx::{types::BigDecimal, PgPool};
+use utils::{create_test_app, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{
+    fail_deposit_claim, insert_deposit, mark_deposit_included, update_deposit_status,
+    update_deposit_status_cas, upsert_deposit,
+};
+
+async fn create_deposit(pool: &PgPool) -> i32 {
+    insert_deposit(
+        pool,
+        "0xstatuscas",
+        &BigDecimal::from(1000),
+        &format!("0x{}", Uuid::new_v4().simple()),
+    )
+    .await
+    .unwrap()
+}
+
+/// Deposit of `network` created in `status`, so its leaf indexes cannot clash with
+/// those of other tests
+async fn create_network_deposit(pool: &PgPool, network: &NetworkId, status: &str) -> i32 {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    upsert_deposit(
+        pool,
+        network,
+        "0xstatuscas",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+        status,
+        None,
+    )
+    .await
+    .unwrap();
+    sqlx::query_scalar!(
+        "SELECT id FROM deposits WHERE commitment_hash = $1",
+        commitment_hash
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+async fn deposit_status(pool: &PgPool, id: i32) -> String {
+    sqlx::query_scalar!("SELECT status FROM deposits WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn set_status(pool: &PgPool, id: i32, status: &str) {
+    let mut conn = pool.acquire().await.unwrap();
+    update_deposit_status(&mut conn, id, status).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_concurrent_updates_from_the_same_status_have_one_winner() {
+    let app = create_test_app().await;
+
+    for _ in 0..10 {
+        let id = create_deposit(&app.db).await;
+        let mut first = app.db.acquire().await.unwrap();
+        let mut second = app.db.acquire().await.unwrap();
+
+        let (expired, failed) = tokio::join!(
+            update_deposit_status_cas(&mut first, id, "pending", "EXPIRED"),
+            update_deposit_status_cas(&mut second, id, "pending", "FAILED"),
+        );
+        let (expired, failed) = (expired.unwrap(), failed.unwrap());
+
+        assert!(expired != failed, "exactly one update wins");
+        let winner = if expired { "EXPIRED" } else { "FAILED" };
+        assert_eq!(deposit_status(&app.db, id).await, winner);
+    }
+}
+
+#[tokio::test]
+async fn test_update_from_another_status_changes_nothing() {
+    let app = create_test_app().await;
+    let id = create_deposit(&app.db).await;
+    set_status(&app.db, id, "PROCESSED").await;
+
+    let mut conn = app.db.acquire().await.unwrap();
+    assert!(
+        !update_deposit_status_cas(&mut conn, id, "pending", "FAILED")
+            .await
+            .unwrap()
+    );
+    assert_eq!(deposit_status(&app.db, id).await, "PROCESSED");
+}
+
+#[tokio::test]
+async fn test_inclusion_does_not_send_a_completed_deposit_back_to_proving() {
+    let app = create_test_app().await;
+    let network = unique_network("cas");
+    let id = create_network_deposit(&app.db, &network, "PROCESSED").await;
+
+    let mut conn = app.db.acquire().await.unwrap();
+    assert!(!mark_deposit_included(&mut conn, id, 7, "0xroot")
+        .await
+        .unwrap());
+    assert_eq!(deposit_status(&app.db, id).await, "PROCESSED");
+    let leaf_index = sqlx::query_scalar!("SELECT leaf_index FROM deposits WHERE id = $1", id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+    assert_eq!(leaf_index, Some(7));
+
+    let waiting = create_network_deposit(&app.db, &network, "PENDING_TREE_INCLUSION").await;
+    assert!(mark_deposit_included(&mut conn, waiting, 8, "0xroot")
+        .await
+        .unwrap());
+    assert_eq!(
+        deposit_status(&app.db, waiting).await,
+        "PENDING_PROOF_GENERATION"
+    );
+}
+
+#[tokio::test]
+async fn test_worker_that_lost_its_claim_cannot_fail_the_deposit() {
+    let app = create_test_app().await;
+    let id = create_deposit(&app.db).await;
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET status = 'PROOF_IN_PROGRESS', claimed_by = 'worker-b', claimed_at = NOW()
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let mut conn = app.db.acquire().await.unwrap();
+    assert!(!fail_deposit_claim(&mut conn, id, "worker-a").await.unwrap());
+    assert_eq!(deposit_status(&app.db, id).await, "PROOF_IN_PROGRESS");
+
+    assert!(fail_deposit_claim(&mut conn, id, "worker-b").await.unwrap());
+    assert_eq!(deposit_status(&app.db, id).await, "FAILED");
+}
@@ -0,0 +1,180 @@
+#[path = "utils.rs"]
+mod utils;
+
+use futures_util::StreamExt;
+use reqwest_eventsource::{Error as EventSourceError, Event, EventSource};
+use sqlx::{types::BigDecimal, PgPool};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::time::timeout;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::api::sse::{STATUS_EVENT, TERMINAL_EVENT};
+use zeroxbridge_sequencer::config::AppConfig;
+use zeroxbridge_sequencer::db::database::{insert_deposit, update_deposit_status};
+use zeroxbridge_sequencer::events::deposit_status::DepositStatusEvent;
+
+async fn serve(pool: PgPool, config: &AppConfig) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = create_router_with_config(pool, config);
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+    addr
+}
+
+async fn create_deposit(pool: &PgPool) -> (i32, String) {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    let id = insert_deposit(pool, "0xsse123", &BigDecimal::from(1000), &commitment_hash)
+        .await
+        .unwrap();
+    (id, commitment_hash)
+}
+
+async fn set_status(pool: &PgPool, id: i32, status: &str) {
+    let mut conn = pool.acquire().await.unwrap();
+    update_deposit_status(&mut conn, id, status).await.unwrap();
+}
+
+fn subscribe(addr: SocketAddr, commitment_hash: &str) -> EventSource {
+    EventSource::get(format!(
+        "http://{}/deposits/{}/events",
+        addr, commitment_hash
+    ))
+}
+
+async fn next(source: &mut EventSource) -> Result<Event, EventSourceError> {
+    timeout(Duration::from_secs(5), source.next())
+        .await
+        .expect("no event within 5s")
+        .expect("event source closed")
+}
+
+/// Next message of the stream, as its event name and status event
+async fn next_event(source: &mut EventSource) -> (String, DepositStatusEvent) {
+    loop {
+        match next(source).await.unwrap() {
+            Event::Open => {}
+            Event::Message(message) => {
+                return (message.event, serde_json::from_str(&message.data).unwrap())
+            }
+        }
+    }
+}
+
+async fn assert_ended(source: &mut EventSource) {
+    match next(source).await {
+        Err(EventSourceError::StreamEnded) => source.close(),
+        other => panic!("expected the stream to end, got {:?}", other),
+    }
+}
+
+async fn assert_rejected(source: &mut EventSource, expected: u16) {
+    match next(source).await {
+        Err(EventSourceError::InvalidStatusCode(status, _)) => {
+            assert_eq!(status.as_u16(), expected);
+            source.close();
+        }
+        other => panic!("expected a {} response, got {:?}", expected, other),
+    }
+}
+
+#[tokio::test]
+async fn test_status_changes_are_streamed_until_terminal() {
+    let app = create_test_app().await;
+    let addr = serve(app.db.clone(), &app.config).await;
+    let (id, commitment_hash) = create_deposit(&app.db).await;
+    let (other_id, _) = create_deposit(&app.db).await;
+
+    let mut source = subscribe(addr, &commitment_hash);
+
+    let (name, event) = next_event(&mut source).await;
+    assert_eq!(name, STATUS_EVENT);
+    assert_eq!(event.commitment_hash, commitment_hash);
+    assert_eq!(event.old_status, None);
+    assert_eq!(event.new_status, "pending");
+
+    // Changes to other deposits are not forwarded
+    set_status(&app.db, other_id, "PENDING_TREE_INCLUSION").await;
+    set_status(&app.db, id, "PENDING_TREE_INCLUSION").await;
+
+    let (name, event) = next_event(&mut source).await;
+    assert_eq!(name, STATUS_EVENT);
+    assert_eq!(event.commitment_hash, commitment_hash);
+    assert_eq!(event.old_status.as_deref(), Some("pending"));
+    assert_eq!(event.new_status, "PENDING_TREE_INCLUSION");
+
+    set_status(&app.db, id, "PROCESSED").await;
+
+    let (name, event) = next_event(&mut source).await;
+    assert_eq!(name, TERMINAL_EVENT);
+    assert_eq!(event.old_status.as_deref(), Some("PENDING_TREE_INCLUSION"));
+    assert_eq!(event.new_status, "PROCESSED");
+    assert_ended(&mut source).await;
+}
+
+#[tokio::test]
+async fn test_changes_made_by_another_process_are_streamed() {
+    let app = create_test_app().await;
+    let addr = serve(app.db.clone(), &app.config).await;
+    let (id, commitment_hash) = create_deposit(&app.db).await;
+
+    let mut source = subscribe(addr, &commitment_hash);
+    next_event(&mut source).await;
+
+    // A separate pool, like the one of the sequencer's pipeline
+    let sequencer = PgPool::connect(&app.config.database.get_db_url())
+        .await
+        .unwrap();
+    set_status(&sequencer, id, "PENDING_TREE_INCLUSION").await;
+
+    let (name, event) = next_event(&mut source).await;
+    assert_eq!(name, STATUS_EVENT);
+    assert_eq!(event.commitment_hash, commitment_hash);
+    assert_eq!(event.old_status.as_deref(), Some("pending"));
+    assert_eq!(event.new_status, "PENDING_TREE_INCLUSION");
+    source.close();
+}
+
+#[tokio::test]
+async fn test_unknown_deposit_is_not_found() {
+    let app = create_test_app().await;
+    let addr = serve(app.db.clone(), &app.config).await;
+
+    let mut source = subscribe(addr, "0xunknown");
+    assert_rejected(&mut source, 404).await;
+}
+
+#[tokio::test]
+async fn test_streams_beyond_the_cap_are_rejected_until_one_ends() {
+    let app = create_test_app().await;
+    let mut config = app.config.clone();
+    config.server.max_event_streams = 1;
+    let addr = serve(app.db.clone(), &config).await;
+    let (id, commitment_hash) = create_deposit(&app.db).await;
+
+    let mut first = subscribe(addr, &commitment_hash);
+    next_event(&mut first).await;
+
+    let mut second = subscribe(addr, &commitment_hash);
+    assert_rejected(&mut second, 503).await;
+
+    set_status(&app.db, id, "PROCESSED").await;
+    let (name, _) = next_event(&mut first).await;
+    assert_eq!(name, TERMINAL_EVENT);
+    assert_ended(&mut first).await;
+
+    // The finished stream gave its slot back
+    let mut third = subscribe(addr, &commitment_hash);
+    let (name, event) = next_event(&mut third).await;
+    assert_eq!(name, TERMINAL_EVENT);
+    assert_eq!(event.new_status, "PROCESSED");
+    assert_ended(&mut third).await;
+}
@@ -0,0 +1,139 @@
+#[path = "utils.rs"]
+mod utils;
+
+use futures_util::StreamExt;
+use sqlx::{types::BigDecimal, PgPool};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_tungstenite::{
+    connect_async, tungstenite::Error as WsError, tungstenite::Message, MaybeTlsStream,
+    WebSocketStream,
+};
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::{create_router_with_config, AppState};
+use zeroxbridge_sequencer::db::database::{
+    insert_deposit, update_deposit_status, DEPOSIT_STATUS_CANCELLED,
+};
+use zeroxbridge_sequencer::events::deposit_status::DepositStatusEvent;
+use zeroxbridge_sequencer::proof_client::service::DEPOSIT_STATUS_FAILED;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn serve(app: &Arc<AppState>) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+    addr
+}
+
+async fn create_deposit(pool: &PgPool) -> (i32, String) {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    let id = insert_deposit(pool, "0xws123", &BigDecimal::from(1000), &commitment_hash)
+        .await
+        .unwrap();
+    (id, commitment_hash)
+}
+
+async fn set_status(pool: &PgPool, id: i32, status: &str) {
+    let mut conn = pool.acquire().await.unwrap();
+    update_deposit_status(&mut conn, id, status).await.unwrap();
+}
+
+async fn connect(addr: SocketAddr, commitment_hash: &str) -> Socket {
+    let (socket, _) = connect_async(format!("ws://{}/ws/deposits/{}", addr, commitment_hash))
+        .await
+        .unwrap();
+    socket
+}
+
+async fn next_message(socket: &mut Socket) -> Option<Message> {
+    timeout(Duration::from_secs(5), socket.next())
+        .await
+        .expect("no message within 5s")
+        .map(|message| message.unwrap())
+}
+
+async fn next_event(socket: &mut Socket) -> DepositStatusEvent {
+    let message = next_message(socket).await.expect("socket closed");
+    serde_json::from_str(message.to_text().unwrap()).unwrap()
+}
+
+async fn assert_closed(socket: &mut Socket) {
+    match next_message(socket).await {
+        None | Some(Message::Close(_)) => {}
+        Some(message) => panic!("expected the socket to close, got {:?}", message),
+    }
+}
+
+#[tokio::test]
+async fn test_status_changes_are_streamed_until_terminal() {
+    let app = create_test_app().await;
+    let addr = serve(&app).await;
+    let (id, commitment_hash) = create_deposit(&app.db).await;
+    let (other_id, _) = create_deposit(&app.db).await;
+
+    let mut socket = connect(addr, &commitment_hash).await;
+
+    let event = next_event(&mut socket).await;
+    assert_eq!(event.commitment_hash, commitment_hash);
+    assert_eq!(event.old_status, None);
+    assert_eq!(event.new_status, "pending");
+
+    // Changes to other deposits are not forwarded
+    set_status(&app.db, other_id, "PENDING_TREE_INCLUSION").await;
+    set_status(&app.db, id, "PENDING_TREE_INCLUSION").await;
+
+    let event = next_event(&mut socket).await;
+    assert_eq!(event.commitment_hash, commitment_hash);
+    assert_eq!(event.old_status.as_deref(), Some("pending"));
+    assert_eq!(event.new_status, "PENDING_TREE_INCLUSION");
+
+    set_status(&app.db, id, DEPOSIT_STATUS_FAILED).await;
+
+    let event = next_event(&mut socket).await;
+    assert_eq!(event.old_status.as_deref(), Some("PENDING_TREE_INCLUSION"));
+    assert_eq!(event.new_status, DEPOSIT_STATUS_FAILED);
+    assert_closed(&mut socket).await;
+}
+
+#[tokio::test]
+async fn test_deposit_in_terminal_status_closes_after_current_status() {
+    let app = create_test_app().await;
+    let addr = serve(&app).await;
+    let (id, commitment_hash) = create_deposit(&app.db).await;
+    set_status(&app.db, id, DEPOSIT_STATUS_CANCELLED).await;
+
+    let mut socket = connect(addr, &commitment_hash).await;
+
+    let event = next_event(&mut socket).await;
+    assert_eq!(event.old_status, None);
+    assert_eq!(event.new_status, DEPOSIT_STATUS_CANCELLED);
+    assert_closed(&mut socket).await;
+}
+
+#[tokio::test]
+async fn test_unknown_deposit_is_not_upgraded() {
+    let app = create_test_app().await;
+    let addr = serve(&app).await;
+
+    let err = connect_async(format!("ws://{}/ws/deposits/0xunknown", addr))
+        .await
+        .unwrap_err();
+
+    match err {
+        WsError::Http(response) => assert_eq!(response.status(), 404),
+        other => panic!("expected a 404 response, got {:?}", other),
+    }
+}
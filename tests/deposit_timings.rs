@@ -0,0 +1,294 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use sqlx::{types::BigDecimal, PgPool};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use utils::{create_test_app, random_commitment, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router;
+use zeroxbridge_sequencer::db::database::{
+    insert_deposit, insert_deposit_hash_event, upsert_deposit, Deposit, DepositHashAppended,
+};
+use zeroxbridge_sequencer::db::timings::{DepositStage, StageTimer, STAGE_INTERRUPTED};
+use zeroxbridge_sequencer::proof_client::artifact_store::LocalArtifactStore;
+use zeroxbridge_sequencer::proof_client::service::{
+    DepositProver, ProofGenerationError, ProofStages, StoneDepositProver,
+};
+use zeroxbridge_sequencer::queue::l2_queue::L2QueueProcessor;
+use zeroxbridge_sequencer::tree_builder::{TreeBuilderClient, TreeBuilderConfig};
+use zeroxbridge_sequencer::utils::parse_commitment_hash;
+
+/// Writes placeholder outputs, failing the Scarb build when asked to
+#[derive(Default)]
+struct StubStages {
+    fail_build: bool,
+}
+
+#[async_trait]
+impl ProofStages for StubStages {
+    async fn cairo_inputs(
+        &self,
+        deposit: &Deposit,
+        work_dir: &Path,
+    ) -> Result<PathBuf, ProofGenerationError> {
+        let path = work_dir.join("input.cairo1.json");
+        fs::write(&path, format!("{{\"data\": [[{}]]}}", deposit.id))?;
+        Ok(path)
+    }
+
+    async fn build(&self, work_dir: &Path) -> Result<PathBuf, ProofGenerationError> {
+        if self.fail_build {
+            return Err(ProofGenerationError::StonePipeline(
+                "scarb: could not compile".to_string(),
+            ));
+        }
+        let path = work_dir.join("program.sierra.json");
+        fs::write(&path, "{}")?;
+        Ok(path)
+    }
+
+    async fn prove(
+        &self,
+        _sierra_path: &Path,
+        _inputs_path: &Path,
+        work_dir: &Path,
+    ) -> Result<PathBuf, ProofGenerationError> {
+        let path = work_dir.join("proof.json");
+        fs::write(&path, "{\"proof\": true}")?;
+        Ok(path)
+    }
+
+    async fn calldata(
+        &self,
+        _proof_path: &Path,
+        work_dir: &Path,
+    ) -> Result<PathBuf, ProofGenerationError> {
+        let calldata_dir = work_dir.join("calldata");
+        fs::create_dir_all(&calldata_dir)?;
+        fs::write(calldata_dir.join("initial"), "0x1 0x2")?;
+        fs::write(calldata_dir.join("fact.txt"), "0xfact")?;
+        Ok(calldata_dir)
+    }
+}
+
+fn prover(pool: &PgPool, stages: StubStages, dir: &TempDir) -> StoneDepositProver {
+    StoneDepositProver::with_stages(
+        pool.clone(),
+        Arc::new(stages),
+        Arc::new(LocalArtifactStore::new(dir.path().join("artifacts"))),
+        dir.path().join("work"),
+    )
+}
+
+async fn fetch_deposit(pool: &PgPool, id: i32) -> Deposit {
+    sqlx::query_as!(Deposit, "SELECT * FROM deposits WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn get_json(router: &Router, uri: String) -> (StatusCode, Value) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+/// Timings of deposit `id` once `count` of them were written, which happens in the
+/// background
+async fn wait_for_timings(router: &Router, id: i32, count: usize) -> Vec<Value> {
+    for _ in 0..50 {
+        let (status, body) = get_json(router, format!("/deposits/{}/timings", id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let timings = body.as_array().unwrap().clone();
+        if timings.len() >= count {
+            assert_eq!(timings.len(), count, "{:?}", timings);
+            return timings;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("deposit {} did not get {} timings", id, count);
+}
+
+fn stages(timings: &[Value]) -> Vec<&str> {
+    timings
+        .iter()
+        .map(|timing| timing["stage"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_stages_of_a_deposit_are_timed_in_order() {
+    let app = create_test_app().await;
+    let router = create_router(app.db.clone());
+    let dir = TempDir::new().unwrap();
+    let network = unique_network("timings");
+    let commitment_hash = random_commitment();
+
+    upsert_deposit(
+        &app.db,
+        &network,
+        "0xtimings",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+        "PENDING_TREE_INCLUSION",
+        None,
+    )
+    .await
+    .unwrap();
+    let id = sqlx::query_scalar!(
+        "SELECT id FROM deposits WHERE commitment_hash = $1",
+        commitment_hash
+    )
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    let event = DepositHashAppended {
+        id: 0,
+        index: 0,
+        commitment_hash: parse_commitment_hash(&commitment_hash).unwrap().to_vec(),
+        root_hash: vec![0; 32],
+        elements_count: 1,
+        block_number: 100,
+        created_at: None,
+        updated_at: None,
+    };
+    insert_deposit_hash_event(&app.db, &network, &event)
+        .await
+        .unwrap();
+
+    let tree_builder = TreeBuilderClient::new(
+        app.db.clone(),
+        network.clone(),
+        TreeBuilderConfig::default(),
+    );
+    assert_eq!(tree_builder.process_pending_deposits().await.unwrap(), 1);
+
+    let deposit = fetch_deposit(&app.db, id).await;
+    prover(&app.db, StubStages::default(), &dir)
+        .prove(&deposit)
+        .await
+        .unwrap();
+
+    sqlx::query!(
+        "UPDATE deposits SET status = 'READY_FOR_RELAY' WHERE id = $1",
+        id
+    )
+    .execute(&app.db)
+    .await
+    .unwrap();
+    let queue = L2QueueProcessor::new(
+        app.db.clone(),
+        network,
+        app.config.queue.clone(),
+        tree_builder.tree(),
+    );
+    assert_eq!(queue.process_ready_deposits().await.unwrap(), 1);
+
+    let timings = wait_for_timings(&router, id, 7).await;
+    assert_eq!(
+        stages(&timings),
+        vec![
+            "tree_inclusion",
+            "cairo_inputs",
+            "scarb_build",
+            "stone_pipeline",
+            "calldata",
+            "artifact_persistence",
+            "relay_queueing",
+        ]
+    );
+    for timing in &timings {
+        assert_eq!(timing["success"], true, "{}", timing);
+        assert!(timing["error"].is_null(), "{}", timing);
+        assert!(timing["duration_ms"].as_i64().unwrap() >= 0);
+    }
+
+    let (status, stats) = get_json(&router, "/stats".to_string()).await;
+    assert_eq!(status, StatusCode::OK);
+    let latency = &stats["stage_latencies"]["stone_pipeline"];
+    assert!(latency["count"].as_i64().unwrap() >= 1, "{}", stats);
+    assert!(latency["p50_ms"].as_f64().unwrap() <= latency["p95_ms"].as_f64().unwrap());
+}
+
+#[tokio::test]
+async fn test_failed_stage_is_timed_with_its_error() {
+    let app = create_test_app().await;
+    let router = create_router(app.db.clone());
+    let dir = TempDir::new().unwrap();
+    let id = insert_deposit(
+        &app.db,
+        "0xtimings",
+        &BigDecimal::from(1000),
+        &format!("0x{}", Uuid::new_v4().simple()),
+    )
+    .await
+    .unwrap();
+
+    let deposit = fetch_deposit(&app.db, id).await;
+    let stages_failing = StubStages { fail_build: true };
+    assert!(prover(&app.db, stages_failing, &dir)
+        .prove(&deposit)
+        .await
+        .is_err());
+
+    let timings = wait_for_timings(&router, id, 2).await;
+    assert_eq!(stages(&timings), vec!["cairo_inputs", "scarb_build"]);
+    assert_eq!(timings[0]["success"], true);
+    assert_eq!(timings[1]["success"], false);
+    assert_eq!(
+        timings[1]["error"],
+        "Stone pipeline failed: scarb: could not compile"
+    );
+}
+
+#[tokio::test]
+async fn test_dropped_timer_is_recorded_as_interrupted() {
+    let app = create_test_app().await;
+    let router = create_router(app.db.clone());
+    let id = insert_deposit(
+        &app.db,
+        "0xtimings",
+        &BigDecimal::from(1000),
+        &format!("0x{}", Uuid::new_v4().simple()),
+    )
+    .await
+    .unwrap();
+
+    drop(StageTimer::start(
+        &app.db,
+        id,
+        DepositStage::RelaySubmission,
+    ));
+    StageTimer::start(&app.db, id, DepositStage::RelayConfirmation).discard();
+
+    let timings = wait_for_timings(&router, id, 1).await;
+    assert_eq!(stages(&timings), vec!["relay_submission"]);
+    assert_eq!(timings[0]["success"], false);
+    assert_eq!(timings[0]["error"], STAGE_INTERRUPTED);
+}
+
+#[tokio::test]
+async fn test_timings_of_unknown_deposit_are_not_found() {
+    let app = create_test_app().await;
+    let router = create_router(app.db.clone());
+
+    let (status, body) = get_json(&router, "/deposits/-1/timings".to_string()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "deposit_not_found");
+}
@@ -0,0 +1,224 @@
+#[path = "utils.rs"]
+mod utils;
+
+use alloy::primitives::{B256, U256};
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol_types::SolEvent;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::error::Error;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use utils::{create_test_app, unique_network};
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::get_last_processed_block;
+use zeroxbridge_sequencer::events::l1_event_watcher::{
+    TestEthereumProvider, ZeroXBridge, DEPOSIT_HASH_BLOCK_TRACKER_KEY,
+};
+use zeroxbridge_sequencer::events::{EventRecorder, EventWatcher, EventWatcherError};
+
+const CONTRACT: &str = "0x00000000000000000000000000000000000000aa";
+const CONFIRMATIONS: u64 = 5;
+
+/// L1 chain kept in memory, emitting `DepositHashAppended` events
+#[derive(Default)]
+struct MockChain {
+    latest: Mutex<u64>,
+    logs: Mutex<Vec<Log>>,
+    log_queries: Mutex<Vec<(u64, u64)>>,
+}
+
+impl MockChain {
+    fn new(latest: u64) -> Self {
+        let chain = Self::default();
+        chain.mine_to(latest);
+        chain
+    }
+
+    fn mine_to(&self, latest: u64) {
+        *self.latest.lock().unwrap() = latest;
+    }
+
+    fn emit(&self, block_number: u64) {
+        let mut logs = self.logs.lock().unwrap();
+        let event = ZeroXBridge::DepositHashAppended {
+            index: U256::from(logs.len()),
+            commitmentHash: U256::from_be_bytes(rand::random::<[u8; 32]>()) >> 5,
+            rootHash: U256::from_be_bytes(rand::random::<[u8; 32]>()),
+            elementsCount: U256::from(logs.len() + 1),
+        };
+        logs.push(Log {
+            inner: alloy::primitives::Log {
+                address: CONTRACT.parse().unwrap(),
+                data: event.encode_log_data(),
+            },
+            block_number: Some(block_number),
+            ..Default::default()
+        });
+    }
+
+    fn log_queries(&self) -> Vec<(u64, u64)> {
+        self.log_queries.lock().unwrap().clone()
+    }
+}
+
+impl TestEthereumProvider for MockChain {
+    fn get_logs(
+        &self,
+        filter: &Filter,
+    ) -> impl Future<Output = Result<Vec<Log>, Box<dyn Error + Send + Sync>>> + Send {
+        let from = filter.get_from_block().unwrap_or(0);
+        let to = filter.get_to_block().unwrap_or(u64::MAX);
+        self.log_queries.lock().unwrap().push((from, to));
+        let logs: Vec<Log> = self
+            .logs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|log| (from..=to).contains(&log.block_number.unwrap()))
+            .cloned()
+            .collect();
+        async move { Ok(logs) }
+    }
+
+    fn get_block_number(
+        &self,
+    ) -> impl Future<Output = Result<u64, Box<dyn Error + Send + Sync>>> + Send {
+        let latest = *self.latest.lock().unwrap();
+        async move { Ok(latest) }
+    }
+
+    async fn get_block_hash(
+        &self,
+        _number: u64,
+    ) -> Result<Option<B256>, Box<dyn Error + Send + Sync>> {
+        Ok(None)
+    }
+}
+
+/// Keeps the block numbers of the logs it is handed, or fails while `failing` is set
+#[derive(Clone, Default)]
+struct MockRecorder {
+    blocks: Arc<Mutex<Vec<u64>>>,
+    failing: Arc<AtomicBool>,
+}
+
+impl MockRecorder {
+    fn blocks(&self) -> Vec<u64> {
+        self.blocks.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl EventRecorder<ZeroXBridge::DepositHashAppended> for MockRecorder {
+    async fn record(
+        &self,
+        _pool: &PgPool,
+        _network: &NetworkId,
+        logs: &[Log<ZeroXBridge::DepositHashAppended>],
+    ) -> Result<(), EventWatcherError> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(EventWatcherError::InvalidEvent("recorder failing".into()));
+        }
+        let mut blocks = self.blocks.lock().unwrap();
+        blocks.extend(logs.iter().map(|log| log.block_number.unwrap()));
+        Ok(())
+    }
+}
+
+fn watcher<'a>(
+    chain: &'a MockChain,
+    network: &NetworkId,
+    recorder: &MockRecorder,
+    from_block_default: u64,
+) -> EventWatcher<ZeroXBridge::DepositHashAppended, &'a MockChain> {
+    EventWatcher::new(
+        chain,
+        network.clone(),
+        CONTRACT,
+        DEPOSIT_HASH_BLOCK_TRACKER_KEY,
+        from_block_default,
+    )
+    .with_confirmations(CONFIRMATIONS)
+    .with_recorder(recorder.clone())
+}
+
+async fn tracker(pool: &PgPool, network: &NetworkId) -> Option<u64> {
+    get_last_processed_block(pool, network, DEPOSIT_HASH_BLOCK_TRACKER_KEY)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_watcher_advances_the_block_tracker_to_the_last_final_block() {
+    let app = create_test_app().await;
+    let mut pool = app.db.clone();
+    let network = unique_network("watcher");
+    let chain = MockChain::new(20);
+    chain.emit(5);
+    chain.emit(12);
+    let recorder = MockRecorder::default();
+    let mut watcher = watcher(&chain, &network, &recorder, 10);
+
+    // Without a tracker, polling starts at the default block
+    let logs = watcher.poll_once(&mut pool).await.unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(chain.log_queries(), vec![(10, 15)]);
+    assert_eq!(recorder.blocks(), vec![12]);
+    assert_eq!(tracker(&app.db, &network).await, Some(15));
+
+    // The next poll starts after the tracked block
+    chain.emit(18);
+    chain.emit(24);
+    chain.mine_to(28);
+    watcher.poll_once(&mut pool).await.unwrap();
+    assert_eq!(chain.log_queries(), vec![(10, 15), (16, 23)]);
+    assert_eq!(recorder.blocks(), vec![12, 18]);
+    assert_eq!(tracker(&app.db, &network).await, Some(23));
+}
+
+#[tokio::test]
+async fn test_watcher_waits_for_final_blocks() {
+    let app = create_test_app().await;
+    let mut pool = app.db.clone();
+    let network = unique_network("watcher");
+    let chain = MockChain::new(CONFIRMATIONS - 1);
+    let recorder = MockRecorder::default();
+    let mut watcher = watcher(&chain, &network, &recorder, 0);
+
+    // No block is final yet
+    assert!(watcher.poll_once(&mut pool).await.unwrap().is_empty());
+    assert!(chain.log_queries().is_empty());
+    assert_eq!(tracker(&app.db, &network).await, None);
+
+    chain.mine_to(CONFIRMATIONS + 10);
+    watcher.poll_once(&mut pool).await.unwrap();
+    assert_eq!(tracker(&app.db, &network).await, Some(10));
+
+    // Nothing new became final since
+    watcher.poll_once(&mut pool).await.unwrap();
+    assert_eq!(chain.log_queries(), vec![(0, 10)]);
+}
+
+#[tokio::test]
+async fn test_failed_recording_keeps_the_block_tracker() {
+    let app = create_test_app().await;
+    let mut pool = app.db.clone();
+    let network = unique_network("watcher");
+    let chain = MockChain::new(20);
+    chain.emit(8);
+    let recorder = MockRecorder::default();
+    recorder.failing.store(true, Ordering::SeqCst);
+    let mut watcher = watcher(&chain, &network, &recorder, 0);
+
+    assert!(watcher.poll_once(&mut pool).await.is_err());
+    assert_eq!(tracker(&app.db, &network).await, None);
+
+    // The same blocks are fetched again once recording works
+    recorder.failing.store(false, Ordering::SeqCst);
+    watcher.poll_once(&mut pool).await.unwrap();
+    assert_eq!(chain.log_queries(), vec![(0, 15), (0, 15)]);
+    assert_eq!(recorder.blocks(), vec![8]);
+    assert_eq!(tracker(&app.db, &network).await, Some(15));
+}
@@ -0,0 +1,185 @@
+//! In-memory stand-ins for the L1 node and the Starknet account the relayer sends with
+
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy::rpc::types::{Filter, FilteredParams, Log};
+use alloy::sol_types::SolEvent;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use starknet::core::types::{Call, Felt};
+use std::sync::{Arc, Mutex};
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::events::l1_event_watcher::{TestEthereumProvider, ZeroXBridge};
+use zeroxbridge_sequencer::queue::l1_queue::{L1BlockSource, ValidationError};
+use zeroxbridge_sequencer::queue::l2_queue::L2Transaction;
+use zeroxbridge_sequencer::relayer::starknet_relayer::{
+    build_relay_calls, fetch_ready_l2_transactions, mark_completed, relay_transactions,
+    CallExecutor, ProcessingReport, StarknetRelayerError, TransactionRelay,
+};
+
+#[derive(Debug, Default)]
+struct Chain {
+    latest_block: u64,
+    logs: Vec<Log>,
+    deposit_hashes: u64,
+}
+
+/// L1 node serving the logs appended to it, shared by the event watchers and the L1
+/// queue of a harness.
+///
+/// Blocks are only mined when asked to, and have hashes derived from their number, so
+/// the chain never re-orgs.
+#[derive(Debug, Clone, Default)]
+pub struct MockEthereumProvider {
+    chain: Arc<Mutex<Chain>>,
+}
+
+impl MockEthereumProvider {
+    /// Emits `DepositHashAppended` for `commitment_hash` from `contract` in a new block,
+    /// returning its number
+    pub fn append_deposit_hash(&self, contract: Address, commitment_hash: &str) -> u64 {
+        let commitment =
+            U256::from_str_radix(commitment_hash.trim_start_matches("0x"), 16).unwrap();
+
+        let mut chain = self.chain.lock().unwrap();
+        chain.latest_block += 1;
+        let event = ZeroXBridge::DepositHashAppended {
+            index: U256::from(chain.deposit_hashes),
+            commitmentHash: commitment,
+            // The sequencer builds its own tree and never reads L1's root
+            rootHash: U256::ZERO,
+            elementsCount: U256::from(chain.deposit_hashes + 1),
+        };
+        let block = chain.latest_block;
+        chain.logs.push(Log {
+            inner: alloy::primitives::Log {
+                address: contract,
+                data: event.encode_log_data(),
+            },
+            block_number: Some(block),
+            ..Default::default()
+        });
+        chain.deposit_hashes += 1;
+        block
+    }
+
+    /// Mines `blocks` empty blocks
+    pub fn mine(&self, blocks: u64) {
+        self.chain.lock().unwrap().latest_block += blocks;
+    }
+
+    fn logs_matching(&self, filter: &Filter) -> Vec<Log> {
+        let params = FilteredParams::new(Some(filter.clone()));
+        self.chain
+            .lock()
+            .unwrap()
+            .logs
+            .iter()
+            .filter(|log| {
+                log.block_number
+                    .is_some_and(|block| params.filter_block_range(block))
+                    && params.filter_address(&log.address())
+                    && params.filter_topics(log.topics())
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn latest_block(&self) -> u64 {
+        self.chain.lock().unwrap().latest_block
+    }
+}
+
+impl TestEthereumProvider for MockEthereumProvider {
+    fn get_logs(
+        &self,
+        filter: &Filter,
+    ) -> impl std::future::Future<Output = Result<Vec<Log>, Box<dyn std::error::Error + Send + Sync>>>
+           + Send {
+        let logs = self.logs_matching(filter);
+        async move { Ok(logs) }
+    }
+
+    fn get_block_number(
+        &self,
+    ) -> impl std::future::Future<Output = Result<u64, Box<dyn std::error::Error + Send + Sync>>> + Send
+    {
+        let latest_block = self.latest_block();
+        async move { Ok(latest_block) }
+    }
+
+    fn get_block_hash(
+        &self,
+        number: u64,
+    ) -> impl std::future::Future<
+        Output = Result<Option<B256>, Box<dyn std::error::Error + Send + Sync>>,
+    > + Send {
+        let hash = (number <= self.latest_block()).then(|| keccak256(number.to_be_bytes()));
+        async move { Ok(hash) }
+    }
+}
+
+#[async_trait]
+impl L1BlockSource for MockEthereumProvider {
+    async fn latest_block_number(&self) -> Result<u64, ValidationError> {
+        Ok(self.latest_block())
+    }
+}
+
+/// Starknet account accepting every transaction the relayer sends, and keeping their
+/// calls to check.
+///
+/// Transactions are confirmed as soon as they are sent, with hashes counting up from 1.
+#[derive(Debug, Clone, Default)]
+pub struct MockStarknetAccount {
+    sent: Arc<Mutex<Vec<Vec<Call>>>>,
+}
+
+impl MockStarknetAccount {
+    /// Calls of each transaction sent so far, in the order they were sent
+    pub fn sent_transactions(&self) -> Vec<Vec<Call>> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl CallExecutor for MockStarknetAccount {
+    async fn execute_and_confirm(&self, calls: Vec<Call>) -> Result<Felt, StarknetRelayerError> {
+        let mut sent = self.sent.lock().unwrap();
+        sent.push(calls);
+        Ok(Felt::from(sent.len()))
+    }
+}
+
+/// Relays a network's L2 transactions through a [`MockStarknetAccount`], building their
+/// calls and classifying failures like the Starknet relayer
+pub struct MockRelayer {
+    pub db_pool: PgPool,
+    pub network: NetworkId,
+    pub bridge_contract_address: String,
+    pub account: MockStarknetAccount,
+    pub max_retries: u32,
+}
+
+impl MockRelayer {
+    /// Relays the transactions ready now, once
+    pub async fn process_pending_transactions(
+        &self,
+    ) -> Result<ProcessingReport, StarknetRelayerError> {
+        let transactions = fetch_ready_l2_transactions(&self.db_pool, &self.network).await?;
+        relay_transactions(&self.db_pool, self, transactions, self.max_retries).await
+    }
+}
+
+#[async_trait]
+impl TransactionRelay for MockRelayer {
+    async fn relay_transaction(&self, tx: &mut L2Transaction) -> Result<(), StarknetRelayerError> {
+        let proof_data = tx
+            .proof_data
+            .as_deref()
+            .ok_or(StarknetRelayerError::ProofDataMissing)?;
+        let calls = build_relay_calls(&self.bridge_contract_address, tx, proof_data)?;
+        let tx_hash = self.account.execute_and_confirm(calls).await?;
+        mark_completed(&self.db_pool, tx.id, &tx_hash.to_hex_string()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,368 @@
+//! End-to-end harness running the deposit pipeline against a throwaway Postgres and
+//! mock chains, needing nothing but Docker.
+//!
+//! Tests describe the deposits they start from and the services they run:
+//!
+//! ```ignore
+//! let mut harness = Harness::new()
+//!     .with_deposit("0x123", 1000)
+//!     .start_l1_queue()
+//!     .start_tree_builder()
+//!     .start_proof_client_simulated()
+//!     .launch()
+//!     .await;
+//! harness.settle().await;
+//! ```
+//!
+//! Services never run in the background. [`RunningHarness::tick`] runs one pass of each
+//! started service in pipeline order, so a test sees the same states on every run.
+
+// Each test file uses its own part of the harness
+#![allow(dead_code)]
+
+mod chains;
+#[path = "../utils.rs"]
+pub mod utils;
+
+use async_trait::async_trait;
+use axum::{body::Body, http::Request, Router};
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+use tree_builder::l1_tree::L1MerkleTreeBuilder;
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::AppConfig;
+use zeroxbridge_sequencer::db::database::{fetch_deposit_status_by_id, get_db_pool, Deposit};
+use zeroxbridge_sequencer::db::migrations::MIGRATOR;
+use zeroxbridge_sequencer::events::l1_event_watcher::l1_event_watchers_with_provider;
+use zeroxbridge_sequencer::events::AnyEventWatcher;
+use zeroxbridge_sequencer::proof_client::service::{
+    DepositProver, ProofClientService, ProofGenerationError,
+};
+use zeroxbridge_sequencer::queue::l1_queue::L1QueueProcessor;
+use zeroxbridge_sequencer::queue::l2_queue::L2QueueProcessor;
+use zeroxbridge_sequencer::tree_builder::{TreeBuilderClient, TreeBuilderConfig};
+
+pub use chains::{MockEthereumProvider, MockRelayer, MockStarknetAccount};
+use utils::postgres::TestDatabase;
+use utils::random_commitment;
+
+/// Bridge contract the mock L1 emits deposit events from
+pub const L1_CONTRACT: &str = "0x00000000000000000000000000000000000000aa";
+
+/// Blocks both the event watchers and the L1 queue wait for on top of a deposit's
+const CONFIRMATIONS: u32 = 3;
+
+/// Passes [`RunningHarness::settle`] runs before deciding the pipeline never goes idle
+const MAX_TICKS: usize = 20;
+
+/// Deposit submitted through the API when the harness is launched
+#[derive(Debug, Clone)]
+pub struct HarnessDeposit {
+    pub id: i32,
+    pub stark_pub_key: String,
+    pub commitment_hash: String,
+}
+
+/// Marks every deposit proved without running a prover
+struct SimulatedProver;
+
+#[async_trait]
+impl DepositProver for SimulatedProver {
+    async fn prove(&self, _deposit: &Deposit) -> Result<(), ProofGenerationError> {
+        Ok(())
+    }
+}
+
+/// What a test starts from, launched with [`Harness::launch`]
+#[derive(Debug, Default)]
+pub struct Harness {
+    deposits: Vec<(String, u64)>,
+    l1_queue: bool,
+    tree_builder: bool,
+    proof_client: bool,
+    l2_queue: bool,
+    relayer: bool,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Submits a deposit of `amount` to `stark_pub_key` through the API once launched,
+    /// and appends its commitment on L1 with enough confirmations
+    pub fn with_deposit(mut self, stark_pub_key: &str, amount: u64) -> Self {
+        self.deposits.push((stark_pub_key.to_string(), amount));
+        self
+    }
+
+    /// Watches the mock L1 and confirms deposits whose commitment it appended
+    pub fn start_l1_queue(mut self) -> Self {
+        self.l1_queue = true;
+        self
+    }
+
+    pub fn start_tree_builder(mut self) -> Self {
+        self.tree_builder = true;
+        self
+    }
+
+    /// Runs the proof client with a prover that succeeds at once
+    pub fn start_proof_client_simulated(mut self) -> Self {
+        self.proof_client = true;
+        self
+    }
+
+    /// Turns proved deposits into L2 transactions, with inclusion proofs from the tree
+    /// builder's tree
+    pub fn start_l2_queue(mut self) -> Self {
+        self.l2_queue = true;
+        self
+    }
+
+    /// Relays L2 transactions through the harness's [`MockStarknetAccount`]
+    pub fn start_relayer(mut self) -> Self {
+        self.relayer = true;
+        self
+    }
+
+    /// Starts Postgres, migrates it and submits the deposits
+    pub async fn launch(self) -> RunningHarness {
+        let database = TestDatabase::start().await;
+
+        let mut config = utils::create_test_config();
+        config.database.url = Some(database.url().to_string());
+        config.contracts.l1_contract_address = L1_CONTRACT.to_string();
+        config.ethereum.confirmations = CONFIRMATIONS;
+        config.l1_queue.confirmation_blocks = CONFIRMATIONS;
+
+        let db = get_db_pool(&config.database)
+            .await
+            .expect("Failed to connect to the harness database");
+        MIGRATOR
+            .run(&db)
+            .await
+            .expect("Failed to migrate the harness database");
+
+        let network = config.active_networks().remove(0);
+        let ethereum = MockEthereumProvider::default();
+        let starknet = MockStarknetAccount::default();
+
+        let mut watchers = Vec::new();
+        let mut l1_queue = None;
+        if self.l1_queue {
+            watchers = l1_event_watchers_with_provider(
+                ethereum.clone(),
+                &network.name,
+                L1_CONTRACT,
+                0,
+                CONFIRMATIONS.into(),
+                &config.limits,
+            );
+            l1_queue = Some(L1QueueProcessor::new(
+                db.clone(),
+                network.name.clone(),
+                config.l1_queue.clone(),
+                config.queue.max_retries,
+                ethereum.clone(),
+            ));
+        }
+
+        let tree_builder = TreeBuilderClient::new(
+            db.clone(),
+            network.name.clone(),
+            TreeBuilderConfig::default(),
+        );
+        let l2_queue = self.l2_queue.then(|| {
+            L2QueueProcessor::new(
+                db.clone(),
+                network.name.clone(),
+                config.queue.clone(),
+                tree_builder.tree(),
+            )
+        });
+        let proof_client = self.proof_client.then(|| {
+            ProofClientService::with_prover(
+                db.clone(),
+                network.name.clone(),
+                config.queue.clone(),
+                "harness",
+                Arc::new(SimulatedProver),
+            )
+        });
+        let relayer = self.relayer.then(|| MockRelayer {
+            db_pool: db.clone(),
+            network: network.name.clone(),
+            bridge_contract_address: network.contracts.l2_contract_address.clone(),
+            account: starknet.clone(),
+            max_retries: network.starknet.max_retries.unwrap_or(3),
+        });
+
+        let mut harness = RunningHarness {
+            db,
+            config,
+            ethereum,
+            starknet,
+            deposits: Vec::new(),
+            watchers,
+            l1_queue,
+            tree_builder: self.tree_builder.then_some(tree_builder),
+            proof_client,
+            l2_queue,
+            relayer,
+            _database: database,
+        };
+        for (stark_pub_key, amount) in &self.deposits {
+            harness.submit_deposit(stark_pub_key, *amount).await;
+        }
+        harness
+    }
+}
+
+/// Launched [`Harness`], whose Postgres container is removed when it is dropped
+pub struct RunningHarness {
+    db: PgPool,
+    config: AppConfig,
+    ethereum: MockEthereumProvider,
+    starknet: MockStarknetAccount,
+    deposits: Vec<HarnessDeposit>,
+    watchers: Vec<Box<dyn AnyEventWatcher>>,
+    l1_queue: Option<L1QueueProcessor<MockEthereumProvider>>,
+    tree_builder: Option<TreeBuilderClient>,
+    proof_client: Option<ProofClientService>,
+    l2_queue: Option<L2QueueProcessor<Arc<RwLock<L1MerkleTreeBuilder>>>>,
+    relayer: Option<MockRelayer>,
+    // Dropped last, once nothing uses the database any more
+    _database: TestDatabase,
+}
+
+impl RunningHarness {
+    pub fn db(&self) -> &PgPool {
+        &self.db
+    }
+
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    /// API of the sequencer, on the harness's database
+    pub fn router(&self) -> Router {
+        create_router_with_config(self.db.clone(), &self.config)
+    }
+
+    pub fn ethereum(&self) -> &MockEthereumProvider {
+        &self.ethereum
+    }
+
+    pub fn starknet(&self) -> &MockStarknetAccount {
+        &self.starknet
+    }
+
+    /// Deposits submitted so far, in the order they were submitted
+    pub fn deposits(&self) -> &[HarnessDeposit] {
+        &self.deposits
+    }
+
+    /// Submits a deposit through `POST /deposit`, then appends its commitment on L1 and
+    /// mines the blocks confirming it
+    pub async fn submit_deposit(&mut self, stark_pub_key: &str, amount: u64) -> HarnessDeposit {
+        let commitment_hash = random_commitment();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/deposit")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({
+                    "stark_pub_key": stark_pub_key,
+                    "amount": amount,
+                    "commitment_hash": commitment_hash,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = self.router().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let id = body["deposit_id"]
+            .as_i64()
+            .unwrap_or_else(|| panic!("Deposit was not accepted: {}", body));
+
+        self.ethereum
+            .append_deposit_hash(L1_CONTRACT.parse().unwrap(), &commitment_hash);
+        self.ethereum.mine(CONFIRMATIONS.into());
+
+        let deposit = HarnessDeposit {
+            id: id as i32,
+            stark_pub_key: stark_pub_key.to_string(),
+            commitment_hash,
+        };
+        self.deposits.push(deposit.clone());
+        deposit
+    }
+
+    pub async fn deposit_status(&self, deposit: &HarnessDeposit) -> String {
+        fetch_deposit_status_by_id(&self.db, deposit.id)
+            .await
+            .unwrap()
+            .expect("Deposit not found")
+    }
+
+    /// Runs one pass of each started service in pipeline order, returning how many
+    /// events, deposits and transactions they moved along
+    pub async fn tick(&mut self) -> usize {
+        let mut progress = 0;
+
+        let mut db = self.db.clone();
+        for watcher in &mut self.watchers {
+            progress += watcher
+                .poll(&mut db)
+                .await
+                .expect("L1 event watcher failed");
+        }
+        if let Some(l1_queue) = &self.l1_queue {
+            progress += l1_queue.process_deposits().await.expect("L1 queue failed");
+        }
+        if let Some(tree_builder) = &self.tree_builder {
+            progress += tree_builder
+                .process_pending_deposits()
+                .await
+                .expect("Tree builder failed");
+        }
+        if let Some(proof_client) = &self.proof_client {
+            progress += proof_client
+                .process_pending_deposits()
+                .await
+                .expect("Proof client failed");
+        }
+        if let Some(l2_queue) = &self.l2_queue {
+            progress += l2_queue
+                .process_ready_deposits()
+                .await
+                .expect("L2 queue failed");
+        }
+        if let Some(relayer) = &self.relayer {
+            let report = relayer
+                .process_pending_transactions()
+                .await
+                .expect("Relayer failed");
+            progress += report.succeeded.len() + report.retried.len() + report.failed.len();
+        }
+
+        progress
+    }
+
+    /// Ticks until a whole pass moves nothing along
+    pub async fn settle(&mut self) {
+        for _ in 0..MAX_TICKS {
+            if self.tick().await == 0 {
+                return;
+            }
+        }
+        panic!("The pipeline was still busy after {} ticks", MAX_TICKS);
+    }
+}
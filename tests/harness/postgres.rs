@@ -0,0 +1,73 @@
+//! Postgres of a harness, started in a throwaway container so tests need nothing but
+//! Docker
+
+use std::process::Command;
+use std::sync::{Mutex, Once};
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+use testcontainers_modules::testcontainers::{ContainerAsync, ImageExt};
+
+/// Major version the sequencer is deployed against
+const POSTGRES_TAG: &str = "16-alpine";
+
+/// Containers kept by [`TestDatabase::keep_until_exit`]
+static KEPT_CONTAINERS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Running Postgres container, stopped and removed when dropped
+pub struct TestDatabase {
+    container: ContainerAsync<Postgres>,
+    url: String,
+}
+
+impl TestDatabase {
+    pub async fn start() -> Self {
+        let container = Postgres::default()
+            .with_tag(POSTGRES_TAG)
+            .start()
+            .await
+            .expect("Failed to start Postgres, is Docker running?");
+        let host = container.get_host().await.unwrap();
+        let port = container.get_host_port_ipv4(5432).await.unwrap();
+
+        Self {
+            container,
+            url: format!("postgres://postgres:postgres@{}:{}/postgres", host, port),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Keeps the container running past the runtime that started it, returning its
+    /// URL. It is removed when the process exits instead, as for a database shared by
+    /// every test of a binary.
+    pub fn keep_until_exit(self) -> String {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(|| {
+            // SAFETY: registers a plain function taking no arguments
+            unsafe { libc::atexit(remove_kept_containers) };
+        });
+
+        let url = self.url.clone();
+        KEPT_CONTAINERS
+            .lock()
+            .unwrap()
+            .push(self.container.id().to_string());
+        // Dropping would remove the container right away
+        std::mem::forget(self);
+        url
+    }
+}
+
+extern "C" fn remove_kept_containers() {
+    let Ok(ids) = KEPT_CONTAINERS.lock() else {
+        return;
+    };
+    for id in ids.iter() {
+        // The process is exiting, there is nothing left to report a failure to
+        let _ = Command::new("docker")
+            .args(["rm", "--force", "--volumes", id])
+            .output();
+    }
+}
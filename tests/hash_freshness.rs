@@ -0,0 +1,230 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use chrono::Utc;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use tower::ServiceExt;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+
+// Keys and hashes of the Keccak and Poseidon test vectors
+const VECTOR_CALLER: &str = "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+const VECTOR_RECIPIENT: &str = "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+const VECTOR_TIMESTAMP: u64 = 1672531200;
+const KECCAK_VECTOR_HASH: &str =
+    "0x2b6876060a11edcc5dde925cda8fad185f34564e35802fa40ee8ead2f9acb06f";
+const POSEIDON_VECTOR_HASH: &str =
+    "0x36a3ffde34b0eec0688d159b2d6db02d47d8cf316d09a9393a63634b12f2f2";
+
+/// Router checking hash freshness, with timestamps allowed `max_skew_sec` of drift
+async fn router(max_skew_sec: u64) -> (PgPool, Router) {
+    let app = create_test_app().await;
+    let mut config = app.config.clone();
+    config.server.check_hash_freshness = true;
+    config.server.hash_timestamp_skew_sec = max_skew_sec;
+    (
+        app.db.clone(),
+        create_router_with_config(app.db.clone(), &config),
+    )
+}
+
+// Keys are fresh per test run, so they have no nonces yet. Zero-padded, as burn callers
+// must be.
+fn unique_stark_key() -> String {
+    format!("0x{:0>64}", Uuid::new_v4().simple().to_string())
+}
+
+fn now() -> u64 {
+    Utc::now().timestamp() as u64
+}
+
+async fn send(router: Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn post(router: Router, uri: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    send(router, request).await
+}
+
+async fn compute_hash(
+    router: Router,
+    stark_pubkey: &str,
+    nonce: u64,
+    timestamp: u64,
+) -> (StatusCode, Value) {
+    let body = json!({
+        "stark_pubkey": stark_pubkey,
+        "usd_val": 50000,
+        "nonce": nonce,
+        "timestamp": timestamp,
+    });
+    post(router, "/compute-hash", body).await
+}
+
+async fn poseidon_hash(
+    router: Router,
+    recipient: &str,
+    nonce: u64,
+    timestamp: u64,
+) -> (StatusCode, Value) {
+    let body = json!({
+        "recipient": recipient,
+        "amount": 50000,
+        "nonce": nonce,
+        "timestamp": timestamp,
+    });
+    post(router, "/poseidon/hash", body).await
+}
+
+async fn next_nonce(router: Router, stark_pub_key: &str, nonce_type: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri(format!("/nonce/{}?type={}", stark_pub_key, nonce_type))
+        .body(Body::empty())
+        .unwrap();
+    send(router, request).await
+}
+
+#[tokio::test]
+async fn test_skewed_timestamps_are_rejected_with_server_time() {
+    let (_, router) = router(600).await;
+    let key = unique_stark_key();
+
+    for timestamp in [now() - 3600, now() + 3600] {
+        let (status, body) = compute_hash(router.clone(), &key, 1, timestamp).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "stale_timestamp");
+        let server_time = body["error"]["details"]["server_time"].as_u64().unwrap();
+        assert!(server_time.abs_diff(now()) <= 5);
+
+        let (status, body) = poseidon_hash(router.clone(), &key, 0, timestamp).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "stale_timestamp");
+        assert!(body["error"]["details"]["server_time"].is_u64());
+    }
+
+    // Within the window is fresh enough
+    let (status, _) = compute_hash(router, &key, 1, now() - 300).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_stale_nonce_is_rejected_with_expected_nonce() {
+    let (pool, router) = router(600).await;
+    let key = unique_stark_key();
+    sqlx::query!(
+        "INSERT INTO withdrawal_nonces (stark_pub_key, nonce) VALUES ($1, 4)",
+        key
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "INSERT INTO deposit_nonces (stark_pubkey, current_nonce) VALUES ($1, 2)",
+        key
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (status, body) = compute_hash(router.clone(), &key, 4, now()).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["error"]["code"], "invalid_nonce");
+    assert_eq!(body["error"]["details"]["expected_nonce"], 5);
+
+    let (status, body) = poseidon_hash(router.clone(), &key, 2, now()).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["error"]["code"], "invalid_nonce");
+    assert_eq!(body["error"]["details"]["expected_nonce"], 3);
+
+    assert_eq!(
+        compute_hash(router.clone(), &key, 5, now()).await.0,
+        StatusCode::OK
+    );
+    assert_eq!(
+        poseidon_hash(router, &key, 3, now()).await.0,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_nonce_endpoint_returns_next_nonces() {
+    let (pool, router) = router(600).await;
+    let key = unique_stark_key();
+
+    // First deposits are hashed with nonce 0, first withdrawals with 1
+    let (status, body) = next_nonce(router.clone(), &key, "deposit").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({ "stark_pub_key": key, "type": "deposit", "nonce": 0 })
+    );
+    let (_, body) = next_nonce(router.clone(), &key, "withdrawal").await;
+    assert_eq!(body["nonce"], 1);
+
+    sqlx::query!(
+        "INSERT INTO withdrawal_nonces (stark_pub_key, nonce) VALUES ($1, 7)",
+        key
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let (_, body) = next_nonce(router.clone(), &key, "withdrawal").await;
+    assert_eq!(body["nonce"], 8);
+
+    let (status, body) = next_nonce(router, "not_a_key", "deposit").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_stark_key");
+}
+
+#[tokio::test]
+async fn test_fresh_hashes_match_the_test_vectors() {
+    // The vectors' timestamp is long past, so any drift is allowed
+    let (pool, router) = router(u64::MAX).await;
+    // Nonce 123 of the vectors is the next one of both keys
+    sqlx::query!(
+        r#"
+        INSERT INTO withdrawal_nonces (stark_pub_key, nonce) VALUES ($1, 122)
+        ON CONFLICT (stark_pub_key) DO UPDATE SET nonce = 122
+        "#,
+        VECTOR_CALLER
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"
+        INSERT INTO deposit_nonces (stark_pubkey, current_nonce) VALUES ($1, 122)
+        ON CONFLICT (stark_pubkey) DO UPDATE SET current_nonce = 122
+        "#,
+        VECTOR_RECIPIENT
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (status, body) = compute_hash(router.clone(), VECTOR_CALLER, 123, VECTOR_TIMESTAMP).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["commitment_hash"], KECCAK_VECTOR_HASH);
+
+    let (status, body) = poseidon_hash(router, VECTOR_RECIPIENT, 123, VECTOR_TIMESTAMP).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["commitment_hash"], POSEIDON_VECTOR_HASH);
+}
@@ -2,7 +2,6 @@ use anyhow::Result;
 use mockito::{mock, Matcher};
 use std::env;
 use std::fs;
-use tokio;
 use zeroxbridge_sequencer::http::client::submit_sharp_proof_job;
 
 fn setup_dummy_files() -> Result<()> {
@@ -0,0 +1,453 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use mockito::{mock, Matcher, Mock};
+use serde_json::json;
+use sqlx::{types::BigDecimal, PgPool};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use utils::{create_test_app, random_commitment, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::config::{HerodotusConfig, NetworkId};
+use zeroxbridge_sequencer::db::database::{
+    fetch_herodotus_jobs, fetch_withdrawal_by_id, update_withdrawal_status, upsert_withdrawal,
+    WITHDRAWAL_STATUS_AWAITING_STATE_PROOF, WITHDRAWAL_STATUS_INCLUDED,
+};
+use zeroxbridge_sequencer::herodotus::{
+    HerodotusClient, HerodotusError, JobId, JobStatus, StateProofPoller,
+};
+use zeroxbridge_sequencer::queue::l1_queue::{
+    L1BlockSource, ValidationError, WithdrawalQueueProcessor,
+};
+use zeroxbridge_sequencer::tree_builder::{L2TreeBuilderClient, L2TreeBuilderConfig};
+
+const API_KEY: &str = "test_herodotus_key";
+const BRIDGE: &str = "0x00000000000000000000000000000000000b1d9e";
+const LATEST_BLOCK: u64 = 6_000_000;
+
+// The poller picks up every withdrawal of its network awaiting a state proof, so tests
+// running it must not interleave
+static POLLER_LOCK: Mutex<()> = Mutex::const_new(());
+
+struct FixedBlock(u64);
+
+#[async_trait]
+impl L1BlockSource for FixedBlock {
+    async fn latest_block_number(&self) -> Result<u64, ValidationError> {
+        Ok(self.0)
+    }
+}
+
+// Each test mocks the API under its own path on the shared mock server
+fn config(prefix: &str) -> HerodotusConfig {
+    HerodotusConfig {
+        herodotus_endpoint: format!("{}/{}", mockito::server_url(), prefix),
+        ..HerodotusConfig::default()
+    }
+}
+
+fn mock_submit(prefix: &str) -> Mock {
+    mock("POST", format!("/{}/submit-batch-query", prefix).as_str())
+        .match_query(Matcher::UrlEncoded("apiKey".into(), API_KEY.into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body_from_fn(|w| write!(w, r#"{{"internalId":"{}"}}"#, Uuid::new_v4()))
+        .create()
+}
+
+fn mock_status(prefix: &str, body: serde_json::Value) -> Mock {
+    mock("GET", format!("/{}/batch-query-status", prefix).as_str())
+        .match_query(Matcher::UrlEncoded("apiKey".into(), API_KEY.into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(body.to_string())
+        .create()
+}
+
+fn poller(
+    pool: &PgPool,
+    network: &NetworkId,
+    config: HerodotusConfig,
+) -> StateProofPoller<FixedBlock> {
+    StateProofPoller::new(
+        pool.clone(),
+        network.clone(),
+        HerodotusClient::new(&config, API_KEY),
+        config,
+        BRIDGE,
+        2,
+        FixedBlock(LATEST_BLOCK),
+    )
+}
+
+/// Fails the jobs and withdrawals awaiting a state proof left by earlier runs, so only
+/// the test's own are polled
+async fn fail_leftovers(pool: &PgPool) {
+    sqlx::query!("UPDATE herodotus_jobs SET status = 'failed' WHERE status = 'pending'")
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query!(
+        "UPDATE withdrawals SET status = 'failed' WHERE status = $1",
+        WITHDRAWAL_STATUS_AWAITING_STATE_PROOF
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Creates a withdrawal of the default network awaiting its state proof
+async fn awaiting_withdrawal(pool: &PgPool) -> i32 {
+    fail_leftovers(pool).await;
+
+    let id = upsert_withdrawal(
+        pool,
+        &NetworkId::default(),
+        "0xstateproof",
+        &BigDecimal::from(1000),
+        "",
+        &random_commitment(),
+        None,
+    )
+    .await
+    .unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    update_withdrawal_status(&mut conn, id, WITHDRAWAL_STATUS_AWAITING_STATE_PROOF, None)
+        .await
+        .unwrap();
+    id
+}
+
+async fn withdrawal_status(pool: &PgPool, id: i32) -> String {
+    fetch_withdrawal_by_id(pool, id)
+        .await
+        .unwrap()
+        .unwrap()
+        .status
+}
+
+#[tokio::test]
+async fn test_request_storage_proof_returns_job_id() {
+    let m = mock("POST", "/request/submit-batch-query")
+        .match_query(Matcher::UrlEncoded("apiKey".into(), API_KEY.into()))
+        .match_body(Matcher::PartialJson(json!({
+            "destinationChainId": "SN_SEPOLIA",
+            "data": {
+                "11155111": {
+                    "block:6000000": {
+                        "accounts": { BRIDGE: { "slots": ["0x0"] } }
+                    }
+                }
+            }
+        })))
+        .with_status(200)
+        .with_body(r#"{"internalId":"01J0STORAGEPROOF"}"#)
+        .create();
+
+    let client = HerodotusClient::new(&config("request"), API_KEY);
+    let job_id = client
+        .request_storage_proof(LATEST_BLOCK, BRIDGE, "0x0")
+        .await
+        .unwrap();
+
+    assert_eq!(job_id, JobId("01J0STORAGEPROOF".to_string()));
+    m.assert();
+}
+
+#[tokio::test]
+async fn test_poll_job_pending_then_done() {
+    let client = HerodotusClient::new(&config("pending-then-done"), API_KEY);
+    let job_id = JobId("job-1".to_string());
+
+    let pending = mock_status("pending-then-done", json!({ "queryStatus": "IN_PROGRESS" }));
+    assert_eq!(client.poll_job(&job_id).await.unwrap(), JobStatus::Pending);
+    drop(pending);
+
+    let payload = json!({ "queryStatus": "DONE", "proof": { "storageProof": ["0xabc"] } });
+    let _done = mock_status("pending-then-done", payload.clone());
+    assert_eq!(
+        client.poll_job(&job_id).await.unwrap(),
+        JobStatus::Done(payload)
+    );
+}
+
+#[tokio::test]
+async fn test_poll_job_failed() {
+    let client = HerodotusClient::new(&config("failed"), API_KEY);
+    let _m = mock_status(
+        "failed",
+        json!({ "queryStatus": "FAILED", "error": "block not indexed" }),
+    );
+
+    assert_eq!(
+        client.poll_job(&JobId("job-2".to_string())).await.unwrap(),
+        JobStatus::Failed("block not indexed".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_api_error_is_reported() {
+    let _m = mock("POST", "/unauthorized/submit-batch-query")
+        .match_query(Matcher::Any)
+        .with_status(401)
+        .with_body("invalid api key")
+        .create();
+
+    let client = HerodotusClient::new(&config("unauthorized"), "bad_key");
+    let err = client
+        .request_storage_proof(LATEST_BLOCK, BRIDGE, "0x0")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, HerodotusError::Api { status: 401, .. }));
+}
+
+#[tokio::test]
+async fn test_unavailable_api_is_retried() {
+    let config = HerodotusConfig {
+        retry_backoff_ms: 0,
+        ..config("unavailable")
+    };
+    // Answers the first two attempts, before the mock returning the job does
+    let unavailable = mock("POST", "/unavailable/submit-batch-query")
+        .match_query(Matcher::Any)
+        .with_status(503)
+        .expect(2)
+        .create();
+    let _submit = mock_submit("unavailable");
+
+    let client = HerodotusClient::new(&config, API_KEY);
+    assert!(client
+        .request_storage_proof(LATEST_BLOCK, BRIDGE, "0x0")
+        .await
+        .is_ok());
+    unavailable.assert();
+}
+
+#[tokio::test]
+async fn test_retries_stop_after_max_retries() {
+    let config = HerodotusConfig {
+        max_retries: 2,
+        retry_backoff_ms: 0,
+        ..config("rate-limited")
+    };
+    let rate_limited = mock("GET", "/rate-limited/batch-query-status")
+        .match_query(Matcher::Any)
+        .with_status(429)
+        .with_body("slow down")
+        .expect(2)
+        .create();
+
+    let client = HerodotusClient::new(&config, API_KEY);
+    let err = client
+        .poll_job(&JobId("job-3".to_string()))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, HerodotusError::Api { status: 429, .. }));
+    rate_limited.assert();
+}
+
+#[tokio::test]
+async fn test_rejected_request_is_not_retried() {
+    let rejected = mock("POST", "/rejected/submit-batch-query")
+        .match_query(Matcher::Any)
+        .with_status(400)
+        .with_body("unknown chain")
+        .expect(1)
+        .create();
+
+    let client = HerodotusClient::new(&config("rejected"), API_KEY);
+    let err = client
+        .request_storage_proof(LATEST_BLOCK, BRIDGE, "0x0")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, HerodotusError::Api { status: 400, .. }));
+    rejected.assert();
+}
+
+#[tokio::test]
+async fn test_withdrawal_advances_once_proof_is_returned() {
+    let _guard = POLLER_LOCK.lock().await;
+    let app = create_test_app().await;
+    let id = awaiting_withdrawal(&app.db).await;
+    let poller = poller(&app.db, &NetworkId::default(), config("poller-success"));
+
+    let _submit = mock_submit("poller-success");
+    let summary = poller.poll().await.unwrap();
+    assert_eq!(summary.requested, vec![id]);
+
+    let jobs = fetch_herodotus_jobs(&app.db, id).await.unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].block_number, LATEST_BLOCK as i64);
+    assert_eq!(jobs[0].contract_address, BRIDGE);
+
+    let pending = mock_status("poller-success", json!({ "queryStatus": "IN_PROGRESS" }));
+    let summary = poller.poll().await.unwrap();
+    assert!(summary.completed.is_empty());
+    assert!(summary.requested.is_empty());
+    assert_eq!(
+        withdrawal_status(&app.db, id).await,
+        WITHDRAWAL_STATUS_AWAITING_STATE_PROOF
+    );
+    drop(pending);
+
+    let payload = json!({ "queryStatus": "DONE", "proof": { "storageProof": ["0xabc"] } });
+    let _done = mock_status("poller-success", payload.clone());
+    let summary = poller.poll().await.unwrap();
+    assert_eq!(summary.completed, vec![id]);
+
+    let withdrawal = fetch_withdrawal_by_id(&app.db, id).await.unwrap().unwrap();
+    assert_eq!(withdrawal.status, "ready_for_relay");
+    assert_eq!(withdrawal.state_proof, Some(payload));
+    assert_eq!(
+        fetch_herodotus_jobs(&app.db, id).await.unwrap()[0].status,
+        "done"
+    );
+}
+
+#[tokio::test]
+async fn test_included_withdrawal_is_held_for_its_state_proof() {
+    let _guard = POLLER_LOCK.lock().await;
+    let app = create_test_app().await;
+    fail_leftovers(&app.db).await;
+    let network = unique_network("state-proof");
+    let id = upsert_withdrawal(
+        &app.db,
+        &network,
+        "0xstateproof",
+        &BigDecimal::from(1000),
+        "",
+        &random_commitment(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let tree_builder = L2TreeBuilderClient::new(
+        app.db.clone(),
+        network.clone(),
+        L2TreeBuilderConfig::default(),
+    );
+    assert_eq!(tree_builder.process_pending_withdrawals().await.unwrap(), 1);
+    assert_eq!(
+        withdrawal_status(&app.db, id).await,
+        WITHDRAWAL_STATUS_INCLUDED
+    );
+
+    let poller = Arc::new(poller(&app.db, &network, config("handoff")));
+    let processor =
+        WithdrawalQueueProcessor::new(app.db.clone(), network.clone(), app.config.queue.clone())
+            .with_state_proofs(poller.clone());
+
+    let _submit = mock_submit("handoff");
+    assert_eq!(processor.request_state_proofs().await.unwrap(), 1);
+    assert_eq!(
+        withdrawal_status(&app.db, id).await,
+        WITHDRAWAL_STATUS_AWAITING_STATE_PROOF
+    );
+    assert_eq!(fetch_herodotus_jobs(&app.db, id).await.unwrap().len(), 1);
+    assert_eq!(processor.request_state_proofs().await.unwrap(), 0);
+
+    // The poller follows the job instead of requesting another one
+    let pending = mock_status("handoff", json!({ "queryStatus": "IN_PROGRESS" }));
+    assert!(poller.poll().await.unwrap().requested.is_empty());
+    drop(pending);
+
+    let payload = json!({ "queryStatus": "DONE", "proof": { "storageProof": ["0xabc"] } });
+    let _done = mock_status("handoff", payload);
+    assert_eq!(poller.poll().await.unwrap().completed, vec![id]);
+
+    assert_eq!(processor.process_ready_withdrawals().await.unwrap(), 1);
+    assert_eq!(withdrawal_status(&app.db, id).await, "queued_for_relay");
+}
+
+#[tokio::test]
+async fn test_failed_jobs_are_retried_until_max_retries() {
+    let _guard = POLLER_LOCK.lock().await;
+    let app = create_test_app().await;
+    let id = awaiting_withdrawal(&app.db).await;
+    let poller = poller(&app.db, &NetworkId::default(), config("poller-failure"));
+
+    let _submit = mock_submit("poller-failure");
+    let _failed = mock_status(
+        "poller-failure",
+        json!({ "queryStatus": "FAILED", "error": "block not indexed" }),
+    );
+
+    poller.poll().await.unwrap();
+
+    // The failed job counts as a retry and a new one is requested
+    let summary = poller.poll().await.unwrap();
+    assert_eq!(summary.retried, vec![id]);
+    assert_eq!(summary.requested, vec![id]);
+
+    let summary = poller.poll().await.unwrap();
+    assert_eq!(summary.failed, vec![id]);
+    assert!(summary.requested.is_empty());
+    assert_eq!(withdrawal_status(&app.db, id).await, "failed");
+
+    let jobs = fetch_herodotus_jobs(&app.db, id).await.unwrap();
+    assert_eq!(jobs.len(), 2);
+    assert!(jobs.iter().all(|job| job.status == "failed"));
+    assert_eq!(jobs[0].error_message.as_deref(), Some("block not indexed"));
+}
+
+#[tokio::test]
+async fn test_timed_out_job_is_retried() {
+    let _guard = POLLER_LOCK.lock().await;
+    let app = create_test_app().await;
+    let id = awaiting_withdrawal(&app.db).await;
+    let config = HerodotusConfig {
+        job_timeout_sec: 0,
+        ..config("poller-timeout")
+    };
+    let poller = poller(&app.db, &NetworkId::default(), config);
+
+    let _submit = mock_submit("poller-timeout");
+    let _pending = mock_status("poller-timeout", json!({ "queryStatus": "IN_PROGRESS" }));
+
+    poller.poll().await.unwrap();
+    let summary = poller.poll().await.unwrap();
+    assert_eq!(summary.retried, vec![id]);
+
+    let jobs = fetch_herodotus_jobs(&app.db, id).await.unwrap();
+    assert_eq!(jobs[0].status, "failed");
+    assert!(jobs[0]
+        .error_message
+        .as_deref()
+        .unwrap()
+        .contains("still running"));
+}
+
+#[tokio::test]
+async fn test_poller_leaves_other_networks_withdrawals_alone() {
+    let _guard = POLLER_LOCK.lock().await;
+    let app = create_test_app().await;
+    let id = awaiting_withdrawal(&app.db).await;
+    let network = unique_network("state-proof");
+    let other = poller(&app.db, &network, config("other-network"));
+
+    let submit = mock("POST", "/other-network/submit-batch-query")
+        .match_query(Matcher::Any)
+        .expect(0)
+        .create();
+    let summary = other.poll().await.unwrap();
+    assert!(summary.requested.is_empty());
+    assert!(fetch_herodotus_jobs(&app.db, id).await.unwrap().is_empty());
+    submit.assert();
+
+    // Nor does it follow the jobs of the default network's poller
+    let _submit = mock_submit("own-network");
+    let own = poller(&app.db, &NetworkId::default(), config("own-network"));
+    assert_eq!(own.poll().await.unwrap().requested, vec![id]);
+    let status = mock("GET", "/other-network/batch-query-status")
+        .match_query(Matcher::Any)
+        .expect(0)
+        .create();
+    let summary = other.poll().await.unwrap();
+    assert!(summary.requested.is_empty() && summary.retried.is_empty());
+    status.assert();
+}
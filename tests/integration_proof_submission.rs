@@ -100,15 +100,12 @@ async fn test_calldata_parsing() {
     let test_calldata = "0x123 0x456 0x789";
     let expected_values = vec!["0x123", "0x456", "0x789"];
 
-    let parsed: Vec<&str> = test_calldata.trim().split_whitespace().collect();
+    let parsed: Vec<&str> = test_calldata.split_whitespace().collect();
     assert_eq!(parsed, expected_values);
 
     // Test with empty lines and extra whitespace
     let test_calldata_with_whitespace = "  0x123  0x456  0x789  ";
-    let parsed_clean: Vec<&str> = test_calldata_with_whitespace
-        .trim()
-        .split_whitespace()
-        .collect();
+    let parsed_clean: Vec<&str> = test_calldata_with_whitespace.split_whitespace().collect();
     assert_eq!(parsed_clean, expected_values);
 }
 
@@ -124,7 +121,7 @@ fn string_to_hex(input: &str) -> String {
 #[test]
 fn test_transaction_sequencing() {
     // Test that the transaction sequence is correct
-    let expected_sequence = vec![
+    let expected_sequence = [
         "verify_proof_initial",
         "verify_proof_step",
         "verify_proof_step",
@@ -142,7 +139,7 @@ fn test_transaction_sequencing() {
 #[test]
 fn test_status_transitions() {
     // Test the expected status transitions
-    let expected_statuses = vec![
+    let expected_statuses = [
         "processing",
         "initial_submitted",
         "step1_submitted",
@@ -0,0 +1,159 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use sqlx::{types::BigDecimal, PgPool};
+use utils::{append_on_l1, create_test_app, random_commitment, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::config::{L1QueueConfig, NetworkId};
+use zeroxbridge_sequencer::db::database::{upsert_deposit, DEPOSIT_STATUS_INVALID_COMMITMENT};
+use zeroxbridge_sequencer::queue::l1_queue::{L1BlockSource, L1QueueProcessor, ValidationError};
+use zeroxbridge_sequencer::queue::l2_queue::L2QueueProcessor;
+use zeroxbridge_sequencer::tree_builder::{TreeBuilderClient, TreeBuilderConfig};
+
+struct FixedBlock(u64);
+
+#[async_trait]
+impl L1BlockSource for FixedBlock {
+    async fn latest_block_number(&self) -> Result<u64, ValidationError> {
+        Ok(self.0)
+    }
+}
+
+async fn create_deposit(
+    pool: &PgPool,
+    network: &NetworkId,
+    commitment_hash: &str,
+    status: &str,
+) -> i32 {
+    upsert_deposit(
+        pool,
+        network,
+        "0xinvalidcommitments",
+        &BigDecimal::from(1000),
+        commitment_hash,
+        status,
+        None,
+    )
+    .await
+    .unwrap();
+
+    sqlx::query_scalar!(
+        "SELECT id FROM deposits WHERE commitment_hash = $1",
+        commitment_hash
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+async fn deposit_status(pool: &PgPool, id: i32) -> String {
+    sqlx::query_scalar!("SELECT status FROM deposits WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+/// Commitment hashes the parser rejects, each unique so they fit the unique index
+fn malformed_commitments() -> Vec<String> {
+    let unique = Uuid::new_v4().simple().to_string();
+    vec![
+        // Odd number of digits
+        format!("0x{}1", unique),
+        // 31 and 33 bytes
+        format!("0x{}{}", unique, "ab".repeat(15)),
+        format!("0x{}{}", unique, "ab".repeat(17)),
+        // Non-hex digits
+        format!("0x{}zz", unique),
+    ]
+}
+
+#[tokio::test]
+async fn test_malformed_deposits_do_not_block_l1_confirmation() {
+    let app = create_test_app().await;
+    let network = unique_network("invalid");
+
+    // Created first, so they come first in the batch
+    let mut malformed = Vec::new();
+    for commitment_hash in malformed_commitments() {
+        malformed.push(create_deposit(&app.db, &network, &commitment_hash, "pending").await);
+    }
+    let good_commitment = random_commitment();
+    let good = create_deposit(&app.db, &network, &good_commitment, "pending").await;
+    append_on_l1(&app.db, &network, &good_commitment, 0).await;
+
+    let processor = L1QueueProcessor::new(
+        app.db.clone(),
+        network,
+        L1QueueConfig {
+            confirmation_blocks: 12,
+            poll_interval_sec: 1,
+            awaiting_confirmation_ttl_sec: 60,
+        },
+        3,
+        FixedBlock(200),
+    );
+    assert_eq!(processor.process_deposits().await.unwrap(), 1);
+
+    assert_eq!(
+        deposit_status(&app.db, good).await,
+        "PENDING_TREE_INCLUSION"
+    );
+    for id in malformed {
+        assert_eq!(
+            deposit_status(&app.db, id).await,
+            DEPOSIT_STATUS_INVALID_COMMITMENT
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_deposit_does_not_block_relay_queueing() {
+    let app = create_test_app().await;
+    let network = unique_network("invalid");
+    let tree_builder = TreeBuilderClient::new(
+        app.db.clone(),
+        network.clone(),
+        TreeBuilderConfig::default(),
+    );
+
+    // 16 bytes long, and created first so it comes first in the batch
+    let malformed = create_deposit(
+        &app.db,
+        &network,
+        &format!("0x{}", Uuid::new_v4().simple()),
+        "READY_FOR_RELAY",
+    )
+    .await;
+    let good_commitment = random_commitment();
+    let good = create_deposit(
+        &app.db,
+        &network,
+        &good_commitment,
+        "PENDING_TREE_INCLUSION",
+    )
+    .await;
+    append_on_l1(&app.db, &network, &good_commitment, 0).await;
+    assert_eq!(tree_builder.process_pending_deposits().await.unwrap(), 1);
+    sqlx::query!(
+        "UPDATE deposits SET status = 'READY_FOR_RELAY' WHERE id = $1",
+        good
+    )
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let processor = L2QueueProcessor::new(
+        app.db.clone(),
+        network,
+        app.config.queue.clone(),
+        tree_builder.tree(),
+    );
+    assert_eq!(processor.process_ready_deposits().await.unwrap(), 1);
+
+    assert_eq!(deposit_status(&app.db, good).await, "QUEUED_FOR_RELAY");
+    assert_eq!(
+        deposit_status(&app.db, malformed).await,
+        DEPOSIT_STATUS_INVALID_COMMITMENT
+    );
+}
@@ -0,0 +1,322 @@
+#[path = "utils.rs"]
+mod utils;
+
+use sqlx::{types::BigDecimal, PgPool};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::config::{JanitorConfig, NetworkId};
+use zeroxbridge_sequencer::db::database::{
+    insert_deposit, update_deposit_status, update_withdrawal_status, upsert_withdrawal,
+};
+use zeroxbridge_sequencer::maintenance::Janitor;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+fn janitor_config(artifacts_dir: &Path) -> JanitorConfig {
+    JanitorConfig {
+        artifacts_dir: artifacts_dir.to_path_buf(),
+        clean_proof_dirs: false,
+        proof_dir_retention_sec: HOUR,
+        reset_stale_processing: false,
+        stale_processing_after_sec: HOUR,
+        vacuum_dead_letters: false,
+        dead_letter_retention_days: 30,
+        expire_unseen_deposits: false,
+        check_withdrawal_nonce_gaps: false,
+        ..JanitorConfig::default()
+    }
+}
+
+fn create_deposit_dir(artifacts_dir: &Path, deposit_id: i32, age: Duration) -> PathBuf {
+    let path = artifacts_dir.join(format!("deposit_{}", deposit_id));
+    fs::create_dir_all(&path).unwrap();
+    fs::write(path.join("proof.json"), "{}").unwrap();
+    fs::File::open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() - age)
+        .unwrap();
+    path
+}
+
+async fn create_deposit(pool: &PgPool, status: &str) -> i32 {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    let id = insert_deposit(pool, "0xjanitor", &BigDecimal::from(1000), &commitment_hash)
+        .await
+        .unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    update_deposit_status(&mut conn, id, status).await.unwrap();
+    id
+}
+
+async fn create_processing_withdrawal(pool: &PgPool) -> i32 {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    let id = upsert_withdrawal(
+        pool,
+        &NetworkId::default(),
+        "0xjanitor",
+        &BigDecimal::from(1000),
+        "0xtoken",
+        &commitment_hash,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    update_withdrawal_status(&mut conn, id, "processing", None)
+        .await
+        .unwrap();
+    id
+}
+
+async fn create_l2_transaction(pool: &PgPool, status: &str, age_secs: i64) -> i64 {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO l2_transactions (stark_pub_key, amount, token_address, status, proof_data, updated_at)
+        VALUES ('0xjanitor', 1000, '0xtoken', $1, 'proof', NOW() - $2::BIGINT * INTERVAL '1 second')
+        RETURNING id
+        "#,
+        status,
+        age_secs
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+async fn backdate_deposit(pool: &PgPool, id: i32, age_secs: i64) {
+    sqlx::query!(
+        "UPDATE deposits SET updated_at = NOW() - $2::BIGINT * INTERVAL '1 second' WHERE id = $1",
+        id,
+        age_secs
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn backdate_withdrawal(pool: &PgPool, id: i32, age_secs: i64) {
+    sqlx::query!(
+        "UPDATE withdrawals SET updated_at = NOW() - $2::BIGINT * INTERVAL '1 second' WHERE id = $1",
+        id,
+        age_secs
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn create_proof_claim(pool: &PgPool, worker_id: &str, age_secs: i64) -> i32 {
+    let id = create_deposit(pool, "PROOF_IN_PROGRESS").await;
+    sqlx::query!(
+        r#"
+        UPDATE deposits
+        SET claimed_by = $2, claimed_at = NOW() - $3::BIGINT * INTERVAL '1 second'
+        WHERE id = $1
+        "#,
+        id,
+        worker_id,
+        age_secs
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    id
+}
+
+#[tokio::test]
+async fn test_janitor_removes_expired_dirs_of_finished_deposits() {
+    let app = create_test_app().await;
+    let artifacts = TempDir::new().unwrap();
+    let old = Duration::from_secs(2 * HOUR);
+
+    let failed = create_deposit(&app.db, "FAILED").await;
+    let relaying = create_deposit(&app.db, "READY_FOR_RELAY").await;
+    let recent = create_deposit(&app.db, "FAILED").await;
+
+    let failed_dir = create_deposit_dir(artifacts.path(), failed, old);
+    let relaying_dir = create_deposit_dir(artifacts.path(), relaying, old);
+    let recent_dir = create_deposit_dir(artifacts.path(), recent, Duration::ZERO);
+
+    let config = JanitorConfig {
+        clean_proof_dirs: true,
+        ..janitor_config(artifacts.path())
+    };
+    let report = Janitor::new(app.db.clone(), config)
+        .run_once()
+        .await
+        .unwrap();
+
+    assert_eq!(report.removed_proof_dirs, vec![failed_dir.clone()]);
+    assert!(!failed_dir.exists());
+    // Calldata is still needed until the deposit is relayed
+    assert!(relaying_dir.exists());
+    assert!(recent_dir.exists());
+}
+
+#[tokio::test]
+async fn test_janitor_resets_stale_processing_rows() {
+    let app = create_test_app().await;
+    let artifacts = TempDir::new().unwrap();
+    let stale_age = 2 * HOUR as i64;
+
+    let stale_deposit = create_deposit(&app.db, "processing").await;
+    backdate_deposit(&app.db, stale_deposit, stale_age).await;
+    let fresh_deposit = create_deposit(&app.db, "processing").await;
+
+    let stale_withdrawal = create_processing_withdrawal(&app.db).await;
+    backdate_withdrawal(&app.db, stale_withdrawal, stale_age).await;
+    let fresh_withdrawal = create_processing_withdrawal(&app.db).await;
+
+    let stale_tx = create_l2_transaction(&app.db, "processing", stale_age).await;
+    let fresh_tx = create_l2_transaction(&app.db, "processing", 0).await;
+
+    let config = JanitorConfig {
+        reset_stale_processing: true,
+        ..janitor_config(artifacts.path())
+    };
+    let report = Janitor::new(app.db.clone(), config)
+        .run_once()
+        .await
+        .unwrap();
+
+    assert!(report.reset_deposits.contains(&stale_deposit));
+    assert!(!report.reset_deposits.contains(&fresh_deposit));
+    assert!(report.reset_withdrawals.contains(&stale_withdrawal));
+    assert!(!report.reset_withdrawals.contains(&fresh_withdrawal));
+    assert!(report.reset_l2_transactions.contains(&stale_tx));
+    assert!(!report.reset_l2_transactions.contains(&fresh_tx));
+
+    let deposits = sqlx::query!(
+        "SELECT id, status, retry_count FROM deposits WHERE id = ANY($1) ORDER BY id",
+        &[stale_deposit, fresh_deposit][..]
+    )
+    .fetch_all(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(
+        (deposits[0].status.as_str(), deposits[0].retry_count),
+        ("pending", 1)
+    );
+    assert_eq!(
+        (deposits[1].status.as_str(), deposits[1].retry_count),
+        ("processing", 0)
+    );
+
+    let withdrawals = sqlx::query!(
+        "SELECT id, status, retry_count FROM withdrawals WHERE id = ANY($1) ORDER BY id",
+        &[stale_withdrawal, fresh_withdrawal][..]
+    )
+    .fetch_all(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(
+        (withdrawals[0].status.as_str(), withdrawals[0].retry_count),
+        ("pending", 1)
+    );
+    assert_eq!(
+        (withdrawals[1].status.as_str(), withdrawals[1].retry_count),
+        ("processing", 0)
+    );
+
+    let transactions = sqlx::query!(
+        "SELECT id, status, retry_count FROM l2_transactions WHERE id = ANY($1) ORDER BY id",
+        &[stale_tx, fresh_tx][..]
+    )
+    .fetch_all(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(
+        (transactions[0].status.as_str(), transactions[0].retry_count),
+        ("ready_for_relay", 1)
+    );
+    assert_eq!(
+        (transactions[1].status.as_str(), transactions[1].retry_count),
+        ("processing", 0)
+    );
+}
+
+#[tokio::test]
+async fn test_janitor_reclaims_abandoned_proof_claims() {
+    let app = create_test_app().await;
+    let artifacts = TempDir::new().unwrap();
+
+    let abandoned = create_proof_claim(&app.db, "crashed-worker", 2 * HOUR as i64).await;
+    let alive = create_proof_claim(&app.db, "live-worker", 0).await;
+
+    let config = JanitorConfig {
+        reset_stale_processing: true,
+        ..janitor_config(artifacts.path())
+    };
+    let report = Janitor::new(app.db.clone(), config)
+        .run_once()
+        .await
+        .unwrap();
+
+    assert!(report.reclaimed_proof_claims.contains(&abandoned));
+    assert!(!report.reclaimed_proof_claims.contains(&alive));
+
+    let deposits = sqlx::query!(
+        "SELECT id, status, retry_count, claimed_by FROM deposits WHERE id = ANY($1) ORDER BY id",
+        &[abandoned, alive][..]
+    )
+    .fetch_all(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(
+        (deposits[0].status.as_str(), deposits[0].retry_count),
+        ("PENDING_PROOF_GENERATION", 1)
+    );
+    assert_eq!(deposits[0].claimed_by, None);
+    assert_eq!(deposits[1].status, "PROOF_IN_PROGRESS");
+    assert_eq!(deposits[1].claimed_by.as_deref(), Some("live-worker"));
+}
+
+#[tokio::test]
+async fn test_janitor_vacuums_dead_letters() {
+    let app = create_test_app().await;
+    let artifacts = TempDir::new().unwrap();
+    let expired = Duration::from_secs(31 * DAY);
+
+    // No deposit will ever get this id in the test database
+    let orphaned_dir = create_deposit_dir(artifacts.path(), i32::MAX, expired);
+    let recent_orphaned_dir = create_deposit_dir(artifacts.path(), i32::MAX - 1, Duration::ZERO);
+    let known = create_deposit(&app.db, "PENDING_PROOF_GENERATION").await;
+    let known_dir = create_deposit_dir(artifacts.path(), known, expired);
+
+    let failed_tx = create_l2_transaction(&app.db, "failed", expired.as_secs() as i64).await;
+    let recent_failed_tx = create_l2_transaction(&app.db, "failed", 0).await;
+
+    let config = JanitorConfig {
+        vacuum_dead_letters: true,
+        ..janitor_config(artifacts.path())
+    };
+    let report = Janitor::new(app.db.clone(), config)
+        .run_once()
+        .await
+        .unwrap();
+
+    assert_eq!(report.removed_orphaned_dirs, vec![orphaned_dir.clone()]);
+    assert!(!orphaned_dir.exists());
+    assert!(recent_orphaned_dir.exists());
+    assert!(known_dir.exists());
+
+    assert!(report.vacuumed_l2_transactions.contains(&failed_tx));
+    assert!(!report.vacuumed_l2_transactions.contains(&recent_failed_tx));
+
+    let transactions = sqlx::query!(
+        "SELECT id, proof_data FROM l2_transactions WHERE id = ANY($1) ORDER BY id",
+        &[failed_tx, recent_failed_tx][..]
+    )
+    .fetch_all(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(transactions[0].proof_data, None);
+    assert_eq!(transactions[1].proof_data.as_deref(), Some("proof"));
+}
@@ -0,0 +1,241 @@
+#[path = "utils.rs"]
+mod utils;
+
+use alloy::primitives::{Address, B256, U256};
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol_types::SolEvent;
+use std::collections::HashMap;
+use std::error::Error;
+use std::future::Future;
+use std::sync::Mutex;
+use utils::{create_test_app, unique_network};
+use zeroxbridge_sequencer::config::{LimitsConfig, NetworkId};
+use zeroxbridge_sequencer::db::database::get_last_processed_block;
+use zeroxbridge_sequencer::events::l1_event_watcher::{
+    fetch_l1_deposit_events_with_provider, TestEthereumProvider, ZeroXBridge, BLOCK_TRACKER_KEY,
+};
+use zeroxbridge_sequencer::utils::commitment_hash_hex;
+
+const CONTRACT: &str = "0x00000000000000000000000000000000000000aa";
+const CONFIRMATIONS: u64 = 5;
+
+/// L1 chain kept in memory whose blocks can be replaced to simulate a re-org
+#[derive(Default)]
+struct MockChain {
+    latest: Mutex<u64>,
+    hashes: Mutex<HashMap<u64, B256>>,
+    logs: Mutex<Vec<Log>>,
+    log_queries: Mutex<usize>,
+}
+
+impl MockChain {
+    fn new(latest: u64) -> Self {
+        let chain = Self::default();
+        chain.mine_to(latest);
+        chain
+    }
+
+    fn mine_to(&self, latest: u64) {
+        let mut hashes = self.hashes.lock().unwrap();
+        for block in 0..=latest {
+            hashes.entry(block).or_insert_with(random_hash);
+        }
+        *self.latest.lock().unwrap() = latest;
+    }
+
+    fn emit(&self, block_number: u64, commitment_hash: U256) {
+        let event = ZeroXBridge::DepositEvent {
+            assetType: ZeroXBridge::AssetType::ETH,
+            usdVal: U256::from(100),
+            nonce: U256::from(1),
+            leafIndex: U256::from(0),
+            depositId: U256::from(1),
+            token: Address::ZERO,
+            user: Address::from([0xab; 20]),
+            commitmentHash: commitment_hash,
+            newRoot: U256::from_be_bytes(rand::random::<[u8; 32]>()),
+            elementCount: U256::from(block_number),
+        };
+        self.logs.lock().unwrap().push(Log {
+            inner: alloy::primitives::Log {
+                address: CONTRACT.parse().unwrap(),
+                data: event.encode_log_data(),
+            },
+            block_number: Some(block_number),
+            ..Default::default()
+        });
+    }
+
+    /// Replaces `block_number` with a block holding none of its former events
+    fn reorg(&self, block_number: u64) {
+        self.hashes
+            .lock()
+            .unwrap()
+            .insert(block_number, random_hash());
+        self.logs
+            .lock()
+            .unwrap()
+            .retain(|log| log.block_number != Some(block_number));
+    }
+
+    fn log_queries(&self) -> usize {
+        *self.log_queries.lock().unwrap()
+    }
+}
+
+impl TestEthereumProvider for MockChain {
+    fn get_logs(
+        &self,
+        filter: &Filter,
+    ) -> impl Future<Output = Result<Vec<Log>, Box<dyn Error + Send + Sync>>> + Send {
+        *self.log_queries.lock().unwrap() += 1;
+        let from = filter.get_from_block().unwrap_or(0);
+        let to = filter.get_to_block().unwrap_or(u64::MAX);
+        let logs: Vec<Log> = self
+            .logs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|log| (from..=to).contains(&log.block_number.unwrap()))
+            .cloned()
+            .collect();
+        async move { Ok(logs) }
+    }
+
+    fn get_block_number(
+        &self,
+    ) -> impl Future<Output = Result<u64, Box<dyn Error + Send + Sync>>> + Send {
+        let latest = *self.latest.lock().unwrap();
+        async move { Ok(latest) }
+    }
+
+    fn get_block_hash(
+        &self,
+        number: u64,
+    ) -> impl Future<Output = Result<Option<B256>, Box<dyn Error + Send + Sync>>> + Send {
+        let hash = self.hashes.lock().unwrap().get(&number).copied();
+        async move { Ok(hash) }
+    }
+}
+
+fn random_hash() -> B256 {
+    B256::from(rand::random::<[u8; 32]>())
+}
+
+// Shifted below 2^251 so the commitment is a felt252 and the deposit is not flagged
+fn random_commitment() -> U256 {
+    U256::from_be_bytes(rand::random::<[u8; 32]>()) >> 5
+}
+
+async fn fetch(
+    pool: &sqlx::PgPool,
+    network: &NetworkId,
+    chain: &MockChain,
+) -> Vec<Log<ZeroXBridge::DepositEvent>> {
+    let mut pool = pool.clone();
+    fetch_l1_deposit_events_with_provider(
+        &mut pool,
+        network,
+        0,
+        CONTRACT,
+        &LimitsConfig::default(),
+        CONFIRMATIONS,
+        chain,
+    )
+    .await
+    .unwrap()
+}
+
+async fn deposit(pool: &sqlx::PgPool, commitment_hash: U256) -> (String, Option<i64>) {
+    let row = sqlx::query!(
+        "SELECT status, l1_block_number FROM deposits WHERE commitment_hash = $1",
+        commitment_hash_hex(&commitment_hash.to_be_bytes())
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    (row.status, row.l1_block_number)
+}
+
+#[tokio::test]
+async fn test_one_block_reorg_is_rolled_back_and_fetched_again() {
+    let app = create_test_app().await;
+    let network = unique_network("reorg");
+    let chain = MockChain::new(20);
+    let (kept, dropped, replacement) = (
+        random_commitment(),
+        random_commitment(),
+        random_commitment(),
+    );
+    chain.emit(14, kept);
+    chain.emit(15, dropped);
+
+    assert_eq!(fetch(&app.db, &network, &chain).await.len(), 2);
+    let tracked = get_last_processed_block(&app.db, &network, BLOCK_TRACKER_KEY)
+        .await
+        .unwrap();
+    assert_eq!(tracked, Some(15));
+
+    // Block 15 is replaced by one holding a different deposit
+    chain.reorg(15);
+    chain.emit(15, replacement);
+    chain.mine_to(21);
+
+    let logs = fetch(&app.db, &network, &chain).await;
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].data().commitmentHash, replacement);
+
+    assert_eq!(
+        deposit(&app.db, kept).await,
+        ("PENDING_TREE_INCLUSION".to_string(), Some(14))
+    );
+    assert_eq!(
+        deposit(&app.db, dropped).await,
+        ("REORGED".to_string(), Some(15))
+    );
+    assert_eq!(
+        deposit(&app.db, replacement).await,
+        ("PENDING_TREE_INCLUSION".to_string(), Some(15))
+    );
+    let tracked = get_last_processed_block(&app.db, &network, BLOCK_TRACKER_KEY)
+        .await
+        .unwrap();
+    assert_eq!(tracked, Some(16));
+}
+
+#[tokio::test]
+async fn test_reorged_deposit_included_again_is_revived() {
+    let app = create_test_app().await;
+    let network = unique_network("reorg");
+    let chain = MockChain::new(20);
+    let commitment = random_commitment();
+    chain.emit(15, commitment);
+    fetch(&app.db, &network, &chain).await;
+
+    // The deposit transaction lands one block later on the new chain
+    chain.reorg(15);
+    chain.mine_to(21);
+    chain.emit(16, commitment);
+    fetch(&app.db, &network, &chain).await;
+
+    assert_eq!(
+        deposit(&app.db, commitment).await,
+        ("PENDING_TREE_INCLUSION".to_string(), Some(16))
+    );
+}
+
+#[tokio::test]
+async fn test_blocks_without_enough_confirmations_are_not_fetched() {
+    let app = create_test_app().await;
+    let network = unique_network("reorg");
+    let chain = MockChain::new(CONFIRMATIONS - 1);
+    assert!(fetch(&app.db, &network, &chain).await.is_empty());
+
+    chain.mine_to(20);
+    fetch(&app.db, &network, &chain).await;
+    assert_eq!(chain.log_queries(), 1);
+
+    // Nothing new is final yet
+    fetch(&app.db, &network, &chain).await;
+    assert_eq!(chain.log_queries(), 1);
+}
@@ -1,10 +1,13 @@
 use anyhow::Result;
+use async_trait::async_trait;
 use mockall::predicate::*;
 use mockall::*;
+use sqlx::types::BigDecimal;
 use starknet::core::types::{EmittedEvent, EventFilter, EventsPage, Felt};
 
+use zeroxbridge_sequencer::config::NetworkId;
 use zeroxbridge_sequencer::events::fetch_l2_events;
-use zeroxbridge_sequencer::events::l2_event_watcher::TestProvider;
+use zeroxbridge_sequencer::events::l2_event_watcher::{TestProvider, BLOCK_TRACKER_KEY};
 
 #[path = "utils.rs"]
 mod utils;
@@ -25,12 +28,13 @@ mock! {
     }
 }
 
+#[async_trait]
 impl TestProvider for MockStarknetProvider {
-    fn block_number(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    async fn block_number(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
         self.block_number()
     }
 
-    fn get_events(
+    async fn get_events(
         &self,
         filter: EventFilter,
         continuation_token: Option<String>,
@@ -44,8 +48,56 @@ impl TestProvider for MockStarknetProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sqlx::PgPool;
+    use starknet::core::types::BlockId;
+    use tokio::sync::Mutex;
     use utils::create_test_app;
 
+    // Every test shares the L2 block tracker row, so they must not interleave
+    static TRACKER_LOCK: Mutex<()> = Mutex::const_new(());
+
+    async fn reset_tracker(db: &PgPool) -> Result<()> {
+        let network = NetworkId::default();
+        sqlx::query!(
+            "DELETE FROM block_trackers WHERE network = $1 AND key = $2",
+            network.as_str(),
+            BLOCK_TRACKER_KEY
+        )
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+
+    async fn tracked_block(db: &PgPool) -> Result<i64> {
+        let network = NetworkId::default();
+        let row = sqlx::query!(
+            "SELECT last_block FROM block_trackers WHERE network = $1 AND key = $2",
+            network.as_str(),
+            BLOCK_TRACKER_KEY
+        )
+        .fetch_one(db)
+        .await?;
+        Ok(row.last_block)
+    }
+
+    // Commitment hashes unique to one test run, since withdrawals are never cleaned up
+    fn unique_commitment() -> String {
+        format!("0x{:x}", uuid::Uuid::new_v4().as_u128())
+    }
+
+    fn burn_event_with_nonce(block_number: u64, commitment: &str, nonce: u64) -> EmittedEvent {
+        let mut event = create_test_burn_event(
+            block_number,
+            "0x789",
+            "0x1234567890abcdef",
+            "0x500",
+            "0x0",
+            commitment,
+        );
+        event.data.push(Felt::from(nonce));
+        event
+    }
+
     // Helper function to create a test event
     fn create_test_burn_event(
         block_number: u64,
@@ -75,7 +127,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_commitment_hash_decoding() -> Result<()> {
+        let _guard = TRACKER_LOCK.lock().await;
         let app = create_test_app().await;
+        reset_tracker(&app.db).await?;
         let mut mock_provider = MockStarknetProvider::new();
 
         // Mock block number response
@@ -95,7 +149,14 @@ mod tests {
             })
         });
 
-        let result = fetch_l2_events(&app.config, &app.db, 90, &mock_provider).await?;
+        let result = fetch_l2_events(
+            &app.config,
+            &app.db,
+            &NetworkId::default(),
+            90,
+            &mock_provider,
+        )
+        .await?;
 
         assert_eq!(result.burn_events.len(), 2);
         assert_eq!(result.burn_events[0].commitment_hash, "0xabc");
@@ -110,7 +171,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_block_index_tracking() -> Result<()> {
+        let _guard = TRACKER_LOCK.lock().await;
         let app = create_test_app().await;
+        reset_tracker(&app.db).await?;
         let mut mock_provider = MockStarknetProvider::new();
 
         mock_provider.expect_block_number().returning(|| Ok(100));
@@ -134,26 +197,65 @@ mod tests {
                 })
             });
 
-        // First call: should process blocks 90-95
-        let result = fetch_l2_events(&app.config, &app.db, 90, &mock_provider).await?;
+        // First call: should process blocks 90-100
+        let result = fetch_l2_events(
+            &app.config,
+            &app.db,
+            &NetworkId::default(),
+            90,
+            &mock_provider,
+        )
+        .await?;
         assert_eq!(result.burn_events.len(), 1);
 
-        // Verify block tracker was updated
-        let last_block = sqlx::query!(
-            "SELECT last_block FROM block_trackers WHERE key = 'l2_events_last_block'"
+        // The tracker advances to the last scanned block, not the last event
+        assert_eq!(result.burn_events[0].block_number, 95);
+        assert_eq!(tracked_block(&app.db).await?, 100);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_events_without_block_number_are_skipped() -> Result<()> {
+        let _guard = TRACKER_LOCK.lock().await;
+        let app = create_test_app().await;
+        reset_tracker(&app.db).await?;
+        let mut mock_provider = MockStarknetProvider::new();
+
+        mock_provider.expect_block_number().returning(|| Ok(100));
+
+        let commitment = unique_commitment();
+        let mut pending = burn_event_with_nonce(95, &unique_commitment(), 1);
+        pending.block_number = None;
+        let test_events = vec![pending, burn_event_with_nonce(96, &commitment, 2)];
+        mock_provider.expect_get_events().returning(move |_, _, _| {
+            Ok(EventsPage {
+                events: test_events.clone(),
+                continuation_token: None,
+            })
+        });
+
+        let result = fetch_l2_events(
+            &app.config,
+            &app.db,
+            &NetworkId::default(),
+            90,
+            &mock_provider,
         )
-        .fetch_one(&app.db)
         .await?;
 
-        assert_eq!(result.burn_events[0].block_number, 95);
-        assert_eq!(last_block.last_block, 95);
+        assert_eq!(result.burn_events.len(), 1);
+        assert_eq!(result.burn_events[0].commitment_hash, commitment);
+        assert_eq!(result.burn_events[0].block_number, 96);
 
         Ok(())
     }
 
     #[tokio::test]
     async fn test_large_amount_handling() -> Result<()> {
+        let _guard = TRACKER_LOCK.lock().await;
         let app = create_test_app().await;
+        reset_tracker(&app.db).await?;
         let mut mock_provider = MockStarknetProvider::new();
 
         mock_provider.expect_block_number().returning(|| Ok(100));
@@ -174,7 +276,14 @@ mod tests {
             })
         });
 
-        let result = fetch_l2_events(&app.config, &app.db, 92, &mock_provider).await?;
+        let result = fetch_l2_events(
+            &app.config,
+            &app.db,
+            &NetworkId::default(),
+            92,
+            &mock_provider,
+        )
+        .await?;
 
         assert_eq!(result.burn_events.len(), 1);
         assert_eq!(result.burn_events[0].commitment_hash, "0xabc");
@@ -185,4 +294,153 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_multi_page_events_are_all_ingested() -> Result<()> {
+        let _guard = TRACKER_LOCK.lock().await;
+        let app = create_test_app().await;
+        reset_tracker(&app.db).await?;
+        let mut mock_provider = MockStarknetProvider::new();
+
+        mock_provider.expect_block_number().returning(|| Ok(200));
+
+        let first = unique_commitment();
+        let second = unique_commitment();
+        let first_page = vec![burn_event_with_nonce(150, &first, 1)];
+        let second_page = vec![burn_event_with_nonce(160, &second, 2)];
+
+        mock_provider
+            .expect_get_events()
+            .with(always(), eq(None), always())
+            .times(1)
+            .returning(move |_, _, _| {
+                Ok(EventsPage {
+                    events: first_page.clone(),
+                    continuation_token: Some("page-2".to_string()),
+                })
+            });
+        mock_provider
+            .expect_get_events()
+            .with(always(), eq(Some("page-2".to_string())), always())
+            .times(1)
+            .returning(move |_, _, _| {
+                Ok(EventsPage {
+                    events: second_page.clone(),
+                    continuation_token: None,
+                })
+            });
+
+        let result = fetch_l2_events(
+            &app.config,
+            &app.db,
+            &NetworkId::default(),
+            100,
+            &mock_provider,
+        )
+        .await?;
+
+        assert_eq!(result.burn_events.len(), 2);
+        assert_eq!(result.burn_events[1].nonce.as_deref(), Some("0x2"));
+        assert_eq!(tracked_block(&app.db).await?, 200);
+
+        let rows = sqlx::query!(
+            "SELECT commitment_hash, amount, nonce, status FROM withdrawals WHERE commitment_hash = ANY($1) ORDER BY nonce",
+            &[first.clone(), second.clone()]
+        )
+        .fetch_all(&app.db)
+        .await?;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].commitment_hash, first);
+        assert_eq!(rows[0].amount, BigDecimal::from(0x500));
+        assert_eq!(rows[1].nonce, Some(2));
+        assert!(rows.iter().all(|row| row.status == "pending"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_depth_limits_scanned_blocks() -> Result<()> {
+        let _guard = TRACKER_LOCK.lock().await;
+        let app = create_test_app().await;
+        reset_tracker(&app.db).await?;
+        let mut config = app.config.clone();
+        config.starknet.confirmations = 10;
+        let mut mock_provider = MockStarknetProvider::new();
+
+        mock_provider.expect_block_number().returning(|| Ok(100));
+        mock_provider
+            .expect_get_events()
+            .withf(|filter, _, _| filter.to_block == Some(BlockId::Number(90)))
+            .times(1)
+            .returning(|_, _, _| {
+                Ok(EventsPage {
+                    events: vec![],
+                    continuation_token: None,
+                })
+            });
+
+        fetch_l2_events(&config, &app.db, &NetworkId::default(), 50, &mock_provider).await?;
+        assert_eq!(tracked_block(&app.db).await?, 90);
+
+        // Nothing new is confirmed yet, so the provider is not queried again
+        fetch_l2_events(&config, &app.db, &NetworkId::default(), 50, &mock_provider).await?;
+        assert_eq!(tracked_block(&app.db).await?, 90);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reingesting_events_is_idempotent() -> Result<()> {
+        let _guard = TRACKER_LOCK.lock().await;
+        let app = create_test_app().await;
+        let mut mock_provider = MockStarknetProvider::new();
+
+        let commitment = unique_commitment();
+
+        // A withdrawal already created through the API for the same commitment
+        let api_id = sqlx::query_scalar!(
+            "INSERT INTO withdrawals (stark_pub_key, amount, l1_token, commitment_hash, status) VALUES ('0x1234567890abcdef', 1280, '0xtoken', $1, 'processing') RETURNING id",
+            commitment
+        )
+        .fetch_one(&app.db)
+        .await?;
+
+        mock_provider.expect_block_number().returning(|| Ok(100));
+        let events = vec![burn_event_with_nonce(95, &commitment, 7)];
+        mock_provider
+            .expect_get_events()
+            .times(2)
+            .returning(move |_, _, _| {
+                Ok(EventsPage {
+                    events: events.clone(),
+                    continuation_token: None,
+                })
+            });
+
+        for _ in 0..2 {
+            reset_tracker(&app.db).await?;
+            fetch_l2_events(
+                &app.config,
+                &app.db,
+                &NetworkId::default(),
+                90,
+                &mock_provider,
+            )
+            .await?;
+        }
+
+        let rows = sqlx::query!(
+            "SELECT id, l1_token, nonce, status FROM withdrawals WHERE commitment_hash = $1",
+            commitment
+        )
+        .fetch_all(&app.db)
+        .await?;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, api_id);
+        assert_eq!(rows[0].l1_token, "0xtoken");
+        assert_eq!(rows[0].nonce, Some(7));
+        assert_eq!(rows[0].status, "processing");
+
+        Ok(())
+    }
 }
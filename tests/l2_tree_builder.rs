@@ -0,0 +1,104 @@
+#[path = "utils.rs"]
+mod utils;
+
+use sqlx::{types::BigDecimal, PgPool};
+use utils::{create_test_app, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{
+    fetch_withdrawal_by_id, upsert_withdrawal, Withdrawal, WITHDRAWAL_STATUS_INCLUDED,
+};
+use zeroxbridge_sequencer::tree_builder::l2_client::parse_withdrawal_commitment;
+use zeroxbridge_sequencer::tree_builder::{
+    L2TreeBuilderClient, L2TreeBuilderConfig, WithdrawalInclusionProof,
+};
+
+fn client(pool: &PgPool, network: &NetworkId) -> L2TreeBuilderClient {
+    L2TreeBuilderClient::new(
+        pool.clone(),
+        network.clone(),
+        L2TreeBuilderConfig {
+            batch_size: 1,
+            ..L2TreeBuilderConfig::default()
+        },
+    )
+}
+
+/// Records a pending withdrawal whose commitment is a felt252 written without its
+/// leading zeros, as L2 events write them
+async fn withdraw(pool: &PgPool, network: &NetworkId) -> i32 {
+    let commitment_hash = format!(
+        "0x{}{}",
+        Uuid::new_v4().simple(),
+        &Uuid::new_v4().simple().to_string()[..28]
+    );
+    upsert_withdrawal(
+        pool,
+        network,
+        "0xl2tree",
+        &BigDecimal::from(1000),
+        "",
+        &commitment_hash,
+        None,
+    )
+    .await
+    .unwrap()
+}
+
+async fn withdrawal(pool: &PgPool, id: i32) -> Withdrawal {
+    fetch_withdrawal_by_id(pool, id).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn test_stored_proofs_verify_after_a_round_trip() {
+    let app = create_test_app().await;
+    let network = unique_network("l2-tree");
+    let builder = client(&app.db, &network);
+
+    for expected_index in 0..3 {
+        let id = withdraw(&app.db, &network).await;
+        assert_eq!(builder.process_pending_withdrawals().await.unwrap(), 1);
+
+        let withdrawal = withdrawal(&app.db, id).await;
+        assert_eq!(withdrawal.status, WITHDRAWAL_STATUS_INCLUDED);
+        assert_eq!(withdrawal.leaf_index, Some(expected_index));
+        let proof: WithdrawalInclusionProof =
+            serde_json::from_value(withdrawal.proof.unwrap()).unwrap();
+        assert_eq!(proof.leaf_index, expected_index);
+        assert_eq!(withdrawal.merkle_root.as_deref(), Some(proof.root.as_str()));
+
+        let tree = builder.tree();
+        let tree = tree.read().await;
+        assert_eq!(
+            format!("0x{}", hex::encode(tree.get_root().await.unwrap())),
+            proof.root
+        );
+        let leaf = parse_withdrawal_commitment(&withdrawal.commitment_hash).unwrap();
+        assert!(tree.verify_proof(proof.into_proof(), leaf).await.unwrap());
+    }
+
+    // Everything pending is included
+    assert_eq!(builder.process_pending_withdrawals().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_restarted_builder_continues_the_tree() {
+    let app = create_test_app().await;
+    let network = unique_network("l2-tree");
+    let first = client(&app.db, &network);
+    let mut included = Vec::new();
+    for _ in 0..2 {
+        included.push(withdraw(&app.db, &network).await);
+        first.process_pending_withdrawals().await.unwrap();
+    }
+
+    let restarted = client(&app.db, &network);
+    assert_eq!(restarted.rebuild_tree_on_startup().await.unwrap(), 2);
+    let last_root = withdrawal(&app.db, included[1]).await.merkle_root.unwrap();
+    let root = restarted.tree().read().await.get_root().await.unwrap();
+    assert_eq!(format!("0x{}", hex::encode(root)), last_root);
+
+    let id = withdraw(&app.db, &network).await;
+    assert_eq!(restarted.process_pending_withdrawals().await.unwrap(), 1);
+    assert_eq!(withdrawal(&app.db, id).await.leaf_index, Some(2));
+}
@@ -0,0 +1,163 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use sqlx::PgPool;
+use tower::ServiceExt;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router;
+use zeroxbridge_sequencer::api::types::{LatestMerkleRootsResponse, MerkleRootsResponse};
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{get_root_at, insert_merkle_root, RootSource};
+
+async fn setup() -> (Router, PgPool) {
+    let app = create_test_app().await;
+    (create_router(app.db.clone()), app.db.clone())
+}
+
+// Tree sizes are shared by every test run, so pick one nobody else uses
+fn unique_count() -> i64 {
+    (Uuid::new_v4().as_u128() % 1_000_000_000_000) as i64 + 1_000_000
+}
+
+fn random_root() -> String {
+    format!("0x{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+async fn record(pool: &PgPool, count: i64, root: &str, source: RootSource) {
+    let block_number = match source {
+        RootSource::Local => None,
+        RootSource::OnChain => Some(123),
+    };
+    let mut conn = pool.acquire().await.unwrap();
+    insert_merkle_root(
+        &mut conn,
+        &NetworkId::default(),
+        count,
+        root,
+        block_number,
+        source,
+    )
+    .await
+    .unwrap();
+}
+
+async fn get(router: Router, uri: &str) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_matching_local_and_onchain_roots() {
+    let (router, pool) = setup().await;
+    let count = unique_count();
+    let root = random_root();
+    record(&pool, count, &root, RootSource::Local).await;
+    record(
+        &pool,
+        count,
+        &root.to_uppercase().replace("0X", "0x"),
+        RootSource::OnChain,
+    )
+    .await;
+
+    let (status, body) = get(router, &format!("/merkle/roots?count={}", count)).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: MerkleRootsResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(response.elements_count, count);
+    assert_eq!(response.local.unwrap().root, root);
+    assert_eq!(response.onchain.unwrap().block_number, Some(123));
+    assert_eq!(response.matches, Some(true));
+}
+
+#[tokio::test]
+async fn test_diverging_roots_are_reported() {
+    let (router, pool) = setup().await;
+    let count = unique_count();
+    record(&pool, count, &random_root(), RootSource::Local).await;
+    record(&pool, count, &random_root(), RootSource::OnChain).await;
+
+    let (status, body) = get(router, &format!("/merkle/roots?count={}", count)).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: MerkleRootsResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(response.matches, Some(false));
+}
+
+#[tokio::test]
+async fn test_root_known_to_one_source_only() {
+    let (router, pool) = setup().await;
+    let count = unique_count();
+    let root = random_root();
+    record(&pool, count, &root, RootSource::Local).await;
+
+    let (status, body) = get(router, &format!("/merkle/roots?count={}", count)).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: MerkleRootsResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(response.local.unwrap().root, root);
+    assert!(response.onchain.is_none());
+    assert_eq!(response.matches, None);
+}
+
+#[tokio::test]
+async fn test_unknown_count_is_not_found() {
+    let (router, _pool) = setup().await;
+
+    let (status, _) = get(
+        router.clone(),
+        &format!("/merkle/roots?count={}", unique_count()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = get(router, "/merkle/roots?count=-1").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_recording_a_root_twice_replaces_it() {
+    let (_router, pool) = setup().await;
+    let count = unique_count();
+    let root = random_root();
+    record(&pool, count, &random_root(), RootSource::OnChain).await;
+    record(&pool, count, &root, RootSource::OnChain).await;
+
+    let roots = get_root_at(&pool, &NetworkId::default(), count)
+        .await
+        .unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].root, root);
+    assert_eq!(roots[0].source, "onchain");
+}
+
+#[tokio::test]
+async fn test_latest_roots_per_source() {
+    let (router, pool) = setup().await;
+    let root = random_root();
+    // No other test records a tree this large
+    record(&pool, i64::MAX, &root, RootSource::Local).await;
+
+    let (status, body) = get(router, "/merkle/roots/latest").await;
+    assert_eq!(status, StatusCode::OK);
+    let response: LatestMerkleRootsResponse = serde_json::from_slice(&body).unwrap();
+
+    let local = response.local.unwrap();
+    assert_eq!(local.elements_count, i64::MAX);
+    assert_eq!(local.root, root);
+}
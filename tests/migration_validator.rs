@@ -0,0 +1,116 @@
+#[path = "utils.rs"]
+mod utils;
+
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::PgPool;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::db::migrations::{DatabaseMigrationValidator, MigrationError};
+
+const MIGRATIONS: [(&str, &str); 2] = [
+    ("1_create_first.sql", "CREATE TABLE first (id INT);"),
+    ("2_create_second.sql", "CREATE TABLE second (id INT);"),
+];
+
+/// Connects to a new, empty schema, so migrations run against it never touch the
+/// tables of the test database
+async fn isolated_pool() -> (PgPool, String) {
+    let database_url = create_test_app().await.config.database.get_db_url();
+    let schema = format!("migrations_{}", Uuid::new_v4().simple());
+
+    let admin = PgPool::connect(&database_url).await.unwrap();
+    sqlx::query(&format!("CREATE SCHEMA {}", schema))
+        .execute(&admin)
+        .await
+        .unwrap();
+
+    let options = database_url
+        .parse::<PgConnectOptions>()
+        .unwrap()
+        .options([("search_path", schema.as_str())]);
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .unwrap();
+    (pool, schema)
+}
+
+async fn drop_schema(pool: PgPool, schema: &str) {
+    sqlx::query(&format!("DROP SCHEMA {} CASCADE", schema))
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+/// Writes the first `count` migrations to a new directory
+fn migrations_dir(count: usize) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for (name, sql) in &MIGRATIONS[..count] {
+        fs::write(dir.path().join(name), sql).unwrap();
+    }
+    dir
+}
+
+async fn migrator(dir: &Path) -> Migrator {
+    Migrator::new(dir).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_unapplied_migration_is_reported() {
+    let (pool, schema) = isolated_pool().await;
+    let applied = migrations_dir(1);
+    migrator(applied.path()).await.run(&pool).await.unwrap();
+    let all = migrations_dir(2);
+
+    let result =
+        DatabaseMigrationValidator::check_against(&pool, &migrator(all.path()).await).await;
+
+    match result {
+        Err(MigrationError::MissingMigrations(missing)) => {
+            assert_eq!(missing, vec!["2_create_second"]);
+        }
+        other => panic!("expected missing migrations, got {:?}", other),
+    }
+    drop_schema(pool, &schema).await;
+}
+
+#[tokio::test]
+async fn test_never_migrated_database_misses_every_migration() {
+    let (pool, schema) = isolated_pool().await;
+    let all = migrations_dir(2);
+
+    let result =
+        DatabaseMigrationValidator::check_against(&pool, &migrator(all.path()).await).await;
+
+    match result {
+        Err(MigrationError::MissingMigrations(missing)) => {
+            assert_eq!(missing, vec!["1_create_first", "2_create_second"]);
+        }
+        other => panic!("expected missing migrations, got {:?}", other),
+    }
+    drop_schema(pool, &schema).await;
+}
+
+#[tokio::test]
+async fn test_fully_migrated_database_passes() {
+    let (pool, schema) = isolated_pool().await;
+    let all = migrations_dir(2);
+    let migrator = migrator(all.path()).await;
+    migrator.run(&pool).await.unwrap();
+
+    DatabaseMigrationValidator::check_against(&pool, &migrator)
+        .await
+        .unwrap();
+    drop_schema(pool, &schema).await;
+}
+
+#[tokio::test]
+async fn test_embedded_migrations_are_applied_to_the_test_database() {
+    let app = create_test_app().await;
+    DatabaseMigrationValidator::check(&app.db).await.unwrap();
+}
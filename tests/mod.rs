@@ -1,14 +1,71 @@
+// Each test file also builds as its own binary, declaring the shared helpers itself
+#![allow(clippy::duplicate_mod)]
+
+pub mod admin_auth;
+pub mod amount_limits;
+pub mod api_error;
+pub mod block_tracker_api;
+pub mod bridge_stats;
 pub mod compute_hash;
 pub mod compute_hash_api;
+pub mod config_reload;
+pub mod db_pool;
 pub mod deposit_api;
+pub mod deposit_cancellation;
+pub mod deposit_expiry;
+pub mod deposit_hash_backfill;
+pub mod deposit_l1_verification;
+pub mod deposit_lifecycle;
+pub mod deposit_stats;
+pub mod deposit_status_cas;
+pub mod deposit_status_sse;
+pub mod deposit_status_ws;
+pub mod deposit_timings;
+pub mod event_watcher;
+pub mod hash_freshness;
 pub mod herodotus_api;
+pub mod herodotus_state_proof;
 pub mod integration_proof_submission;
+pub mod invalid_commitments;
+pub mod janitor;
 pub mod l1_events_logs;
+pub mod l1_reorg;
 pub mod l2_event_watcher;
+pub mod l2_tree_builder;
+pub mod merkle_roots;
+pub mod migration_validator;
+pub mod multi_network;
+pub mod openapi;
 pub mod poseidon_test;
+pub mod proof_artifacts_api;
+pub mod proof_checkpoints;
+pub mod proof_claims;
 pub mod proof_submission_integration_test;
 pub mod proof_submission_test;
+pub mod proof_submitter;
+pub mod proof_submitter_grpc;
+pub mod rate_limiter;
+pub mod relay_costs;
+pub mod relay_queues;
+pub mod relay_retries;
+pub mod relayer_resubmission;
+pub mod request_limits;
 pub mod scarb_build;
+pub mod sdk_client;
+pub mod service_controls;
+pub mod simulation;
 pub mod starknet_relayer_test;
+pub mod status_notifications;
+pub mod telemetry;
+pub mod transaction_signer;
+pub mod tree_api;
+pub mod tree_builder_dedup;
+pub mod tree_builder_event_index;
+pub mod tree_grpc;
+pub mod tree_rebuild;
 pub mod utils;
+pub mod webhooks;
 pub mod withdrawal_api;
+pub mod withdrawal_nonce_gaps;
+pub mod withdrawal_signatures;
+pub mod withdrawal_status;
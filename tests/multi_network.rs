@@ -0,0 +1,173 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::{append_on_l1, create_test_app, random_commitment, unique_network};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::{AppConfig, NetworkConfig, NetworkId};
+use zeroxbridge_sequencer::db::database::{
+    claim_deposits_for_proof, get_last_processed_block, update_last_processed_block, upsert_deposit,
+};
+use zeroxbridge_sequencer::events::l1_event_watcher::BLOCK_TRACKER_KEY;
+use zeroxbridge_sequencer::tree_builder::{TreeBuilderClient, TreeBuilderConfig};
+
+async fn create_deposit(pool: &PgPool, network: &NetworkId, status: &str) -> String {
+    let commitment_hash = random_commitment();
+    upsert_deposit(
+        pool,
+        network,
+        "0xmultinetwork",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+        status,
+        None,
+    )
+    .await
+    .unwrap();
+    commitment_hash
+}
+
+async fn leaf_index(pool: &PgPool, commitment_hash: &str) -> Option<i64> {
+    sqlx::query_scalar!(
+        "SELECT leaf_index FROM deposits WHERE commitment_hash = $1",
+        commitment_hash
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+fn with_networks(config: &AppConfig, names: &[&NetworkId]) -> AppConfig {
+    let mut config = config.clone();
+    config.networks = names
+        .iter()
+        .map(|name| NetworkConfig {
+            name: (*name).clone(),
+            contracts: config.contracts.clone(),
+            ethereum: config.ethereum.clone(),
+            starknet: config.starknet.clone(),
+        })
+        .collect();
+    config
+}
+
+async fn get(router: Router, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_each_network_builds_its_own_tree() {
+    let app = create_test_app().await;
+    let (net_a, net_b) = (unique_network("net"), unique_network("net"));
+    let a_first = create_deposit(&app.db, &net_a, "PENDING_TREE_INCLUSION").await;
+    let b_first = create_deposit(&app.db, &net_b, "PENDING_TREE_INCLUSION").await;
+    let a_second = create_deposit(&app.db, &net_a, "PENDING_TREE_INCLUSION").await;
+    append_on_l1(&app.db, &net_a, &a_first, 0).await;
+    append_on_l1(&app.db, &net_b, &b_first, 0).await;
+    append_on_l1(&app.db, &net_a, &a_second, 1).await;
+
+    let builder_a =
+        TreeBuilderClient::new(app.db.clone(), net_a.clone(), TreeBuilderConfig::default());
+    assert_eq!(builder_a.process_pending_deposits().await.unwrap(), 2);
+    assert_eq!(leaf_index(&app.db, &a_first).await, Some(0));
+    assert_eq!(leaf_index(&app.db, &a_second).await, Some(1));
+    assert_eq!(leaf_index(&app.db, &b_first).await, None);
+
+    // The other network's tree starts from its own first leaf
+    let builder_b = TreeBuilderClient::new(app.db.clone(), net_b, TreeBuilderConfig::default());
+    assert_eq!(builder_b.process_pending_deposits().await.unwrap(), 1);
+    assert_eq!(leaf_index(&app.db, &b_first).await, Some(0));
+}
+
+#[tokio::test]
+async fn test_block_trackers_are_kept_per_network() {
+    let app = create_test_app().await;
+    let (net_a, net_b) = (unique_network("net"), unique_network("net"));
+
+    update_last_processed_block(&app.db, &net_a, BLOCK_TRACKER_KEY, 100)
+        .await
+        .unwrap();
+    update_last_processed_block(&app.db, &net_b, BLOCK_TRACKER_KEY, 7)
+        .await
+        .unwrap();
+
+    for (network, expected) in [
+        (net_a, Some(100)),
+        (net_b, Some(7)),
+        (unique_network("net"), None),
+    ] {
+        let tracked = get_last_processed_block(&app.db, &network, BLOCK_TRACKER_KEY)
+            .await
+            .unwrap();
+        assert_eq!(tracked, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_proof_workers_only_claim_their_network() {
+    let app = create_test_app().await;
+    let (net_a, net_b) = (unique_network("net"), unique_network("net"));
+    let commitment_a = create_deposit(&app.db, &net_a, "PENDING_PROOF_GENERATION").await;
+    let commitment_b = create_deposit(&app.db, &net_b, "PENDING_PROOF_GENERATION").await;
+
+    let claimed = claim_deposits_for_proof(&app.db, &net_a, "worker-a", 10, 3)
+        .await
+        .unwrap();
+    let claimed: Vec<_> = claimed.iter().map(|d| d.commitment_hash.as_str()).collect();
+    assert_eq!(claimed, vec![commitment_a.as_str()]);
+
+    let claimed = claim_deposits_for_proof(&app.db, &net_b, "worker-b", 10, 3)
+        .await
+        .unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].commitment_hash, commitment_b);
+    assert_eq!(claimed[0].network, net_b.as_str());
+}
+
+#[tokio::test]
+async fn test_api_reads_the_requested_network() {
+    let app = create_test_app().await;
+    let (net_a, net_b) = (unique_network("net"), unique_network("net"));
+    let commitment_b = create_deposit(&app.db, &net_b, "pending").await;
+    let router = create_router_with_config(
+        app.db.clone(),
+        &with_networks(&app.config, &[&net_a, &net_b]),
+    );
+
+    let (status, body) = get(router.clone(), "/deposits?stark_pub_key=0xmultinetwork").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.as_array().unwrap().is_empty());
+
+    let uri = format!("/deposits?stark_pub_key=0xmultinetwork&network={}", net_b);
+    let (status, body) = get(router.clone(), &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let deposits = body.as_array().unwrap();
+    assert_eq!(deposits.len(), 1);
+    assert_eq!(deposits[0]["commitment_hash"], commitment_b);
+    assert_eq!(deposits[0]["network"], net_b.as_str());
+
+    let (status, body) = get(
+        router,
+        "/deposits?stark_pub_key=0xmultinetwork&network=unknown",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "unknown_network");
+}
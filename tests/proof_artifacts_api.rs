@@ -0,0 +1,241 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use proof_pipeline::pipeline::CalldataArtifacts;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::{types::BigDecimal, PgPool};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use utils::{create_test_app, random_commitment};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::ArtifactStoreKind;
+use zeroxbridge_sequencer::db::database::{insert_deposit, update_deposit_status};
+use zeroxbridge_sequencer::proof_client::artifact_store::{LocalArtifactStore, ProofArtifactStore};
+use zeroxbridge_sequencer::proof_client::service::DEPOSIT_STATUS_READY_FOR_RELAY;
+
+const PROOF_BYTES: usize = 5 * 1024 * 1024;
+
+/// Router serving the artifacts saved under `artifacts_dir`
+async fn setup(artifacts_dir: &Path) -> (Router, PgPool) {
+    let app = create_test_app().await;
+    let mut config = app.config.clone();
+    config.proof.artifact_store = ArtifactStoreKind::Local;
+    config.proof.artifacts_dir = artifacts_dir.to_path_buf();
+    (
+        create_router_with_config(app.db.clone(), &config),
+        app.db.clone(),
+    )
+}
+
+async fn create_deposit(pool: &PgPool, status: &str) -> i32 {
+    let commitment_hash = random_commitment();
+    let id = insert_deposit(
+        pool,
+        "0xartifacts",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+    )
+    .await
+    .unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    update_deposit_status(&mut conn, id, status).await.unwrap();
+    id
+}
+
+/// Saves pipeline output with a proof of `PROOF_BYTES` bytes for deposit `id`, returning
+/// the proof
+async fn save_artifacts(artifacts_dir: &Path, id: i32) -> Vec<u8> {
+    let pipeline = TempDir::new().unwrap();
+    let calldata_dir = pipeline.path().join("calldata");
+    fs::create_dir_all(&calldata_dir).unwrap();
+    fs::write(calldata_dir.join("initial"), "0x1 0x2").unwrap();
+    fs::write(calldata_dir.join("step1"), "0x3").unwrap();
+    fs::write(calldata_dir.join("final"), "0x4").unwrap();
+    fs::write(calldata_dir.join("fact.txt"), "0xfact").unwrap();
+    let proof: Vec<u8> = (0..PROOF_BYTES).map(|i| (i * 31 % 251) as u8).collect();
+    let proof_path = pipeline.path().join("proof.json");
+    fs::write(&proof_path, &proof).unwrap();
+
+    let artifacts = CalldataArtifacts::from_calldata_dir(calldata_dir, proof_path).unwrap();
+    LocalArtifactStore::new(artifacts_dir)
+        .save(id, &artifacts)
+        .await
+        .unwrap();
+    proof
+}
+
+async fn download(
+    router: &Router,
+    uri: String,
+    headers: &[(header::HeaderName, String)],
+) -> axum::response::Response {
+    let mut request = Request::builder().uri(uri);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    router
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn body_bytes(response: axum::response::Response) -> Vec<u8> {
+    axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap()
+        .to_vec()
+}
+
+fn header_str(response: &axum::response::Response, name: header::HeaderName) -> &str {
+    response.headers()[name].to_str().unwrap()
+}
+
+#[tokio::test]
+async fn test_proof_is_downloaded_whole_and_by_range() {
+    let dir = TempDir::new().unwrap();
+    let (router, pool) = setup(dir.path()).await;
+    let id = create_deposit(&pool, DEPOSIT_STATUS_READY_FOR_RELAY).await;
+    let proof = save_artifacts(dir.path(), id).await;
+    let uri = format!("/proofs/{}/artifacts/proof.json", id);
+
+    let response = download(&router, uri.clone(), &[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        header_str(&response, header::CONTENT_TYPE),
+        "application/json"
+    );
+    assert_eq!(
+        header_str(&response, header::CONTENT_LENGTH),
+        PROOF_BYTES.to_string()
+    );
+    assert_eq!(header_str(&response, header::ACCEPT_RANGES), "bytes");
+    let etag = header_str(&response, header::ETAG).to_string();
+    assert_eq!(etag, format!("\"{}\"", hex::encode(Sha256::digest(&proof))));
+    assert!(body_bytes(response).await == proof);
+
+    // Resuming after the first half
+    let half = PROOF_BYTES / 2;
+    let response = download(
+        &router,
+        uri.clone(),
+        &[
+            (header::RANGE, format!("bytes={}-", half)),
+            (header::IF_RANGE, etag),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        header_str(&response, header::CONTENT_RANGE),
+        format!("bytes {}-{}/{}", half, PROOF_BYTES - 1, PROOF_BYTES)
+    );
+    assert_eq!(
+        header_str(&response, header::CONTENT_LENGTH),
+        (PROOF_BYTES - half).to_string()
+    );
+    assert!(body_bytes(response).await == proof[half..]);
+
+    // A copy of another version of the artifact is downloaded again whole
+    let response = download(
+        &router,
+        uri.clone(),
+        &[
+            (header::RANGE, format!("bytes={}-", half)),
+            (header::IF_RANGE, "\"stale\"".to_string()),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body_bytes(response).await.len(), PROOF_BYTES);
+
+    let response = download(
+        &router,
+        uri,
+        &[(header::RANGE, format!("bytes={}-", PROOF_BYTES))],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(
+        header_str(&response, header::CONTENT_RANGE),
+        format!("bytes */{}", PROOF_BYTES)
+    );
+}
+
+#[tokio::test]
+async fn test_calldata_and_fact_hash_are_downloaded() {
+    let dir = TempDir::new().unwrap();
+    let (router, pool) = setup(dir.path()).await;
+    let id = create_deposit(&pool, DEPOSIT_STATUS_READY_FOR_RELAY).await;
+    save_artifacts(dir.path(), id).await;
+
+    for (name, expected) in [
+        ("calldata/initial", "0x1 0x2"),
+        ("calldata/step1", "0x3"),
+        ("calldata/final", "0x4"),
+        ("fact_hash.txt", "0xfact"),
+    ] {
+        let response = download(&router, format!("/proofs/{}/artifacts/{}", id, name), &[]).await;
+        assert_eq!(response.status(), StatusCode::OK, "{}", name);
+        assert_eq!(
+            header_str(&response, header::CONTENT_TYPE),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(body_bytes(response).await, expected.as_bytes(), "{}", name);
+    }
+}
+
+#[tokio::test]
+async fn test_unknown_deposit_or_artifact_is_not_found() {
+    let dir = TempDir::new().unwrap();
+    let (router, pool) = setup(dir.path()).await;
+    let id = create_deposit(&pool, DEPOSIT_STATUS_READY_FOR_RELAY).await;
+    save_artifacts(dir.path(), id).await;
+    // Proved, but its artifacts are not in the store
+    let missing = create_deposit(&pool, DEPOSIT_STATUS_READY_FOR_RELAY).await;
+
+    for (uri, code) in [
+        (
+            "/proofs/-1/artifacts/proof.json".to_string(),
+            "deposit_not_found",
+        ),
+        (
+            format!("/proofs/{}/artifacts/proof.json", missing),
+            "artifact_not_found",
+        ),
+        (
+            format!("/proofs/{}/artifacts/calldata/step2", id),
+            "artifact_not_found",
+        ),
+        (
+            format!("/proofs/{}/artifacts/artifact_hashes.json", id),
+            "artifact_not_found",
+        ),
+    ] {
+        let response = download(&router, uri.clone(), &[]).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", uri);
+        let body: Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(body["error"]["code"], code, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_unproved_deposit_conflicts_with_its_status() {
+    let dir = TempDir::new().unwrap();
+    let (router, pool) = setup(dir.path()).await;
+    let id = create_deposit(&pool, "PROOF_IN_PROGRESS").await;
+
+    let response = download(&router, format!("/proofs/{}/artifacts/proof.json", id), &[]).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body: Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+    assert_eq!(body["error"]["code"], "proof_not_ready");
+    assert_eq!(body["error"]["details"]["status"], "PROOF_IN_PROGRESS");
+}
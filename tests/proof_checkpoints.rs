@@ -0,0 +1,227 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use proof_pipeline::pipeline::CalldataArtifacts;
+use sqlx::{types::BigDecimal, PgPool};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::db::database::{insert_deposit, Deposit};
+use zeroxbridge_sequencer::proof_client::artifact_store::{
+    ArtifactLocation, ArtifactStoreError, LocalArtifactStore, ProofArtifactStore,
+};
+use zeroxbridge_sequencer::proof_client::checkpoint::{ProofStage, CHECKPOINT_FILE};
+use zeroxbridge_sequencer::proof_client::service::{
+    DepositProver, ProofGenerationError, ProofStages, StoneDepositProver,
+};
+
+/// Writes placeholder outputs and records which stages ran
+#[derive(Default)]
+struct RecordingStages {
+    ran: Mutex<Vec<ProofStage>>,
+    fail_calldata: AtomicBool,
+}
+
+impl RecordingStages {
+    fn failing_calldata() -> Self {
+        Self {
+            fail_calldata: AtomicBool::new(true),
+            ..Self::default()
+        }
+    }
+
+    /// Stages run since the last call
+    fn take_ran(&self) -> Vec<ProofStage> {
+        std::mem::take(&mut *self.ran.lock().unwrap())
+    }
+
+    fn record(&self, stage: ProofStage) {
+        self.ran.lock().unwrap().push(stage);
+    }
+}
+
+#[async_trait]
+impl ProofStages for RecordingStages {
+    async fn cairo_inputs(
+        &self,
+        deposit: &Deposit,
+        work_dir: &Path,
+    ) -> Result<PathBuf, ProofGenerationError> {
+        self.record(ProofStage::CairoInputs);
+        let path = work_dir.join("input.cairo1.json");
+        fs::write(&path, format!("{{\"data\": [[{}]]}}", deposit.id))?;
+        Ok(path)
+    }
+
+    async fn build(&self, work_dir: &Path) -> Result<PathBuf, ProofGenerationError> {
+        self.record(ProofStage::Build);
+        let path = work_dir.join("program.sierra.json");
+        fs::write(&path, "{}")?;
+        Ok(path)
+    }
+
+    async fn prove(
+        &self,
+        _sierra_path: &Path,
+        _inputs_path: &Path,
+        work_dir: &Path,
+    ) -> Result<PathBuf, ProofGenerationError> {
+        self.record(ProofStage::Prove);
+        let target_dir = work_dir.join("target");
+        fs::create_dir_all(&target_dir)?;
+        let path = target_dir.join("proof.json");
+        fs::write(&path, "{\"proof\": true}")?;
+        Ok(path)
+    }
+
+    async fn calldata(
+        &self,
+        _proof_path: &Path,
+        work_dir: &Path,
+    ) -> Result<PathBuf, ProofGenerationError> {
+        self.record(ProofStage::Calldata);
+        if self.fail_calldata.swap(false, Ordering::SeqCst) {
+            return Err(ProofGenerationError::StonePipeline(
+                "swiftness killed by OOM".to_string(),
+            ));
+        }
+
+        let calldata_dir = work_dir.join("calldata");
+        fs::create_dir_all(&calldata_dir)?;
+        fs::write(calldata_dir.join("initial"), "0x1 0x2")?;
+        fs::write(calldata_dir.join("fact.txt"), "0xfact")?;
+        Ok(calldata_dir)
+    }
+}
+
+/// Local store that leaves checkpoints to the prover's sidecar file
+struct StoreWithoutCheckpoints(LocalArtifactStore);
+
+#[async_trait]
+impl ProofArtifactStore for StoreWithoutCheckpoints {
+    async fn save(
+        &self,
+        deposit_id: i32,
+        artifacts: &CalldataArtifacts,
+    ) -> Result<ArtifactLocation, ArtifactStoreError> {
+        self.0.save(deposit_id, artifacts).await
+    }
+
+    async fn load(&self, deposit_id: i32) -> Result<Option<CalldataArtifacts>, ArtifactStoreError> {
+        self.0.load(deposit_id).await
+    }
+}
+
+async fn create_deposit(pool: &PgPool) -> Deposit {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    let id = insert_deposit(
+        pool,
+        "0xcheckpoint",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+    )
+    .await
+    .unwrap();
+    fetch_deposit(pool, id).await
+}
+
+async fn fetch_deposit(pool: &PgPool, id: i32) -> Deposit {
+    sqlx::query_as!(Deposit, "SELECT * FROM deposits WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_retry_resumes_after_the_last_completed_stage() {
+    let app = create_test_app().await;
+    let work_dir = TempDir::new().unwrap();
+    let artifacts_dir = TempDir::new().unwrap();
+    let store = Arc::new(LocalArtifactStore::new(artifacts_dir.path()));
+    let stages = Arc::new(RecordingStages::failing_calldata());
+    let prover = StoneDepositProver::with_stages(
+        app.db.clone(),
+        stages.clone(),
+        store.clone(),
+        work_dir.path(),
+    );
+    let deposit = create_deposit(&app.db).await;
+
+    assert!(prover.prove(&deposit).await.is_err());
+    assert_eq!(stages.take_ran(), ProofStage::ALL.to_vec());
+    let failed = fetch_deposit(&app.db, deposit.id).await;
+    assert_eq!(failed.proof_stage.as_deref(), Some("prove"));
+    assert!(store.load(deposit.id).await.unwrap().is_none());
+
+    prover.prove(&deposit).await.unwrap();
+    assert_eq!(stages.take_ran(), vec![ProofStage::Calldata]);
+    let proved = fetch_deposit(&app.db, deposit.id).await;
+    assert_eq!(proved.proof_stage.as_deref(), Some("calldata"));
+
+    let artifacts = store.load(deposit.id).await.unwrap().unwrap();
+    assert_eq!(artifacts.fact_hash.as_deref(), Some("0xfact"));
+    // The local store keeps the checkpoint next to the artifacts
+    assert!(artifacts_dir
+        .path()
+        .join(format!("deposit_{}", deposit.id))
+        .join(CHECKPOINT_FILE)
+        .exists());
+}
+
+#[tokio::test]
+async fn test_changed_output_reruns_its_stage_and_everything_after() {
+    let app = create_test_app().await;
+    let work_dir = TempDir::new().unwrap();
+    let artifacts_dir = TempDir::new().unwrap();
+    let store = Arc::new(StoreWithoutCheckpoints(LocalArtifactStore::new(
+        artifacts_dir.path(),
+    )));
+    let stages = Arc::new(RecordingStages::default());
+    let prover =
+        StoneDepositProver::with_stages(app.db.clone(), stages.clone(), store, work_dir.path());
+    let deposit = create_deposit(&app.db).await;
+
+    prover.prove(&deposit).await.unwrap();
+    assert_eq!(stages.take_ran(), ProofStage::ALL.to_vec());
+
+    // Without checkpoint support in the store, the checkpoint is a sidecar file
+    let deposit_dir = work_dir.path().join(format!("deposit_{}", deposit.id));
+    assert!(deposit_dir.join(CHECKPOINT_FILE).exists());
+
+    prover.prove(&deposit).await.unwrap();
+    assert!(stages.take_ran().is_empty());
+
+    fs::write(deposit_dir.join("target/proof.json"), "{\"proof\": false}").unwrap();
+    prover.prove(&deposit).await.unwrap();
+    assert_eq!(
+        stages.take_ran(),
+        vec![ProofStage::Prove, ProofStage::Calldata]
+    );
+
+    fs::write(deposit_dir.join("input.cairo1.json"), "").unwrap();
+    prover.prove(&deposit).await.unwrap();
+    assert_eq!(stages.take_ran(), ProofStage::ALL.to_vec());
+}
+
+#[tokio::test]
+async fn test_regenerate_ignores_checkpoints() {
+    let app = create_test_app().await;
+    let work_dir = TempDir::new().unwrap();
+    let artifacts_dir = TempDir::new().unwrap();
+    let store = Arc::new(LocalArtifactStore::new(artifacts_dir.path()));
+    let stages = Arc::new(RecordingStages::default());
+    let prover =
+        StoneDepositProver::with_stages(app.db.clone(), stages.clone(), store, work_dir.path());
+    let deposit = create_deposit(&app.db).await;
+
+    prover.prove(&deposit).await.unwrap();
+    stages.take_ran();
+
+    prover.regenerate(&deposit).await.unwrap();
+    assert_eq!(stages.take_ran(), ProofStage::ALL.to_vec());
+}
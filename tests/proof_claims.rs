@@ -0,0 +1,271 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use sqlx::{types::BigDecimal, PgPool};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::config::{NetworkId, QueueConfig};
+use zeroxbridge_sequencer::db::database::{
+    claim_deposits_for_proof, heartbeat_deposit_claim, insert_deposit, release_deposit_claim,
+    update_deposit_status, Deposit, DEPOSIT_STATUS_PENDING_PROOF_GENERATION,
+    DEPOSIT_STATUS_PROOF_IN_PROGRESS,
+};
+use zeroxbridge_sequencer::proof_client::service::{
+    DepositProver, ProofClientService, ProofGenerationError, DEPOSIT_STATUS_READY_FOR_RELAY,
+};
+
+// Workers claim every deposit waiting on a proof, so tests claiming them must not
+// interleave
+static CLAIMS_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Records how often each deposit was proved, taking long enough for workers to overlap
+#[derive(Default)]
+struct CountingProver {
+    proved: StdMutex<HashMap<i32, usize>>,
+}
+
+#[async_trait]
+impl DepositProver for CountingProver {
+    async fn prove(&self, deposit: &Deposit) -> Result<(), ProofGenerationError> {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        *self.proved.lock().unwrap().entry(deposit.id).or_default() += 1;
+        Ok(())
+    }
+}
+
+/// Records how many deposits were being proved at the same time at most
+#[derive(Default)]
+struct ConcurrencyProver {
+    running: AtomicUsize,
+    max_running: AtomicUsize,
+}
+
+#[async_trait]
+impl DepositProver for ConcurrencyProver {
+    async fn prove(&self, _deposit: &Deposit) -> Result<(), ProofGenerationError> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_running.fetch_max(running, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+struct FailingProver;
+
+#[async_trait]
+impl DepositProver for FailingProver {
+    async fn prove(&self, _deposit: &Deposit) -> Result<(), ProofGenerationError> {
+        Err(ProofGenerationError::StonePipeline(
+            "prover killed by OOM".to_string(),
+        ))
+    }
+}
+
+fn queue_config() -> QueueConfig {
+    QueueConfig {
+        process_interval_sec: 1,
+        wait_time_seconds: 1,
+        max_retries: 3,
+        initial_retry_delay_sec: 1,
+        retry_delay_seconds: 1,
+        merkle_update_confirmations: 1,
+        deposit_expiry_hours: 72,
+    }
+}
+
+/// Creates deposits waiting on a proof, failing leftovers of earlier runs so only these
+/// are claimed
+async fn pending_proof_deposits(pool: &PgPool, count: usize) -> Vec<i32> {
+    sqlx::query!(
+        "UPDATE deposits SET status = 'FAILED' WHERE status IN ($1, $2)",
+        DEPOSIT_STATUS_PENDING_PROOF_GENERATION,
+        DEPOSIT_STATUS_PROOF_IN_PROGRESS
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    let mut ids = Vec::new();
+    for _ in 0..count {
+        let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+        let id = insert_deposit(pool, "0xclaims", &BigDecimal::from(1000), &commitment_hash)
+            .await
+            .unwrap();
+        update_deposit_status(&mut conn, id, DEPOSIT_STATUS_PENDING_PROOF_GENERATION)
+            .await
+            .unwrap();
+        ids.push(id);
+    }
+    ids
+}
+
+async fn deposit(pool: &PgPool, id: i32) -> Deposit {
+    sqlx::query_as!(Deposit, "SELECT * FROM deposits WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_two_workers_prove_each_deposit_once() {
+    let _guard = CLAIMS_LOCK.lock().await;
+    let app = create_test_app().await;
+    let ids = pending_proof_deposits(&app.db, 8).await;
+
+    let first_prover = Arc::new(CountingProver::default());
+    let second_prover = Arc::new(CountingProver::default());
+    let first = ProofClientService::with_prover(
+        app.db.clone(),
+        NetworkId::default(),
+        queue_config(),
+        "worker-a",
+        first_prover.clone(),
+    );
+    let second = ProofClientService::with_prover(
+        app.db.clone(),
+        NetworkId::default(),
+        queue_config(),
+        "worker-b",
+        second_prover.clone(),
+    );
+
+    let (first_processed, second_processed) = tokio::join!(
+        first.process_pending_deposits(),
+        second.process_pending_deposits()
+    );
+    assert_eq!(
+        first_processed.unwrap() + second_processed.unwrap(),
+        ids.len()
+    );
+
+    let first_proved = first_prover.proved.lock().unwrap().clone();
+    let second_proved = second_prover.proved.lock().unwrap().clone();
+    for id in &ids {
+        let runs = first_proved.get(id).unwrap_or(&0) + second_proved.get(id).unwrap_or(&0);
+        assert_eq!(runs, 1, "deposit {} was proved {} times", id, runs);
+
+        let deposit = deposit(&app.db, *id).await;
+        assert_eq!(deposit.status, DEPOSIT_STATUS_READY_FOR_RELAY);
+        assert_eq!(deposit.claimed_by, None);
+    }
+}
+
+#[tokio::test]
+async fn test_claimed_deposits_are_skipped_by_other_workers() {
+    let _guard = CLAIMS_LOCK.lock().await;
+    let app = create_test_app().await;
+    let ids = pending_proof_deposits(&app.db, 2).await;
+
+    let claimed = claim_deposits_for_proof(&app.db, &NetworkId::default(), "worker-a", 10, 3)
+        .await
+        .unwrap();
+    let mut claimed_ids: Vec<_> = claimed.iter().map(|deposit| deposit.id).collect();
+    claimed_ids.sort();
+    assert_eq!(claimed_ids, ids);
+    assert!(claimed.iter().all(|deposit| {
+        deposit.status == DEPOSIT_STATUS_PROOF_IN_PROGRESS
+            && deposit.claimed_by.as_deref() == Some("worker-a")
+            && deposit.claimed_at.is_some()
+    }));
+
+    assert!(
+        claim_deposits_for_proof(&app.db, &NetworkId::default(), "worker-b", 10, 3)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Only the owner can refresh or release its claim
+    assert!(heartbeat_deposit_claim(&app.db, ids[0], "worker-a")
+        .await
+        .unwrap());
+    assert!(!heartbeat_deposit_claim(&app.db, ids[0], "worker-b")
+        .await
+        .unwrap());
+    let mut conn = app.db.acquire().await.unwrap();
+    assert!(!release_deposit_claim(&mut conn, ids[0], "worker-b")
+        .await
+        .unwrap());
+    assert!(release_deposit_claim(&mut conn, ids[0], "worker-a")
+        .await
+        .unwrap());
+
+    let released = deposit(&app.db, ids[0]).await;
+    assert_eq!(released.status, DEPOSIT_STATUS_PENDING_PROOF_GENERATION);
+    assert_eq!(released.retry_count, 1);
+    assert_eq!(released.claimed_by, None);
+
+    let reclaimed = claim_deposits_for_proof(&app.db, &NetworkId::default(), "worker-b", 10, 3)
+        .await
+        .unwrap();
+    assert_eq!(reclaimed.len(), 1);
+    assert_eq!(reclaimed[0].id, ids[0]);
+}
+
+#[tokio::test]
+async fn test_failed_proof_releases_the_claim() {
+    let _guard = CLAIMS_LOCK.lock().await;
+    let app = create_test_app().await;
+    let ids = pending_proof_deposits(&app.db, 1).await;
+    let service = ProofClientService::with_prover(
+        app.db.clone(),
+        NetworkId::default(),
+        queue_config(),
+        "worker-a",
+        Arc::new(FailingProver),
+    );
+
+    assert_eq!(service.process_pending_deposits().await.unwrap(), 0);
+
+    // Released for a later cycle rather than retried straight away
+    let released = deposit(&app.db, ids[0]).await;
+    assert_eq!(released.status, DEPOSIT_STATUS_PENDING_PROOF_GENERATION);
+    assert_eq!(released.retry_count, 1);
+    assert_eq!(released.claimed_by, None);
+
+    // The last attempt allowed by max_retries marks it as failed
+    service.process_pending_deposits().await.unwrap();
+    service.process_pending_deposits().await.unwrap();
+    let failed = deposit(&app.db, ids[0]).await;
+    assert_eq!(failed.status, "FAILED");
+    assert_eq!(failed.claimed_by, None);
+}
+
+#[tokio::test]
+async fn test_deposits_are_proved_concurrently_up_to_the_cap() {
+    let _guard = CLAIMS_LOCK.lock().await;
+    let app = create_test_app().await;
+    let prover = Arc::new(ConcurrencyProver::default());
+    let service = ProofClientService::with_prover(
+        app.db.clone(),
+        NetworkId::default(),
+        queue_config(),
+        "worker-a",
+        prover.clone(),
+    )
+    .with_max_parallel_proofs(3);
+
+    let ids = pending_proof_deposits(&app.db, 3).await;
+    assert_eq!(service.process_pending_deposits().await.unwrap(), 3);
+    assert_eq!(prover.max_running.load(Ordering::SeqCst), 3);
+    for id in &ids {
+        assert_eq!(
+            deposit(&app.db, *id).await.status,
+            DEPOSIT_STATUS_READY_FOR_RELAY
+        );
+    }
+
+    // Deposits beyond the cap wait for a running proof to finish
+    prover.max_running.store(0, Ordering::SeqCst);
+    pending_proof_deposits(&app.db, 5).await;
+    assert_eq!(service.process_pending_deposits().await.unwrap(), 5);
+    assert_eq!(prover.max_running.load(Ordering::SeqCst), 3);
+}
@@ -23,13 +23,29 @@ fn create_test_config() -> AppConfig {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             server_url: "http://127.0.0.1:4000".to_string(),
+            admin_token: None,
+            admin_addresses: vec![],
+            require_l1_verification: false,
+            verify_withdrawal_signatures: false,
+            rate_limit: RateLimitConfig::default(),
+            stats_cache_ttl_sec: 5,
+            max_body_bytes: 1024 * 1024,
+            request_timeout_ms: 30_000,
+            enable_docs: true,
+            check_hash_freshness: false,
+            hash_timestamp_skew_sec: 600,
+            max_event_streams: 1000,
         },
         database: DatabaseConfig {
             max_connections: 10,
+            url: None,
+            connection_timeout_secs: 30,
+            idle_timeout_secs: 600,
         },
         ethereum: EthereumConfig {
             chain_id: 1,
             confirmations: 3,
+            rpc_url: None,
         },
         starknet: StarknetConfig {
             chain_id: "0x534e5f4d41494e".to_string(),
@@ -42,11 +58,21 @@ fn create_test_config() -> AppConfig {
             max_retries: Some(3),
             retry_delay_ms: Some(1000),
             transaction_timeout_ms: Some(30000),
+            max_calls_per_tx: None,
+            enable_dry_run: false,
+            max_fee_per_tx: None,
+            confirmations: 0,
+            rpc_url: None,
+            nonce: StarknetNonceConfig::default(),
+            signer: SignerConfig::default(),
         },
         relayer: RelayerConfig {
             max_retries: 5,
             retry_delay_seconds: 10,
             gas_limit: 500000,
+            verify_effects: false,
+            gas_price: GasPriceConfig::default(),
+            domain_separator: format!("0x{}", "11".repeat(32)),
         },
         queue: QueueConfig {
             process_interval_sec: 5,
@@ -55,7 +81,16 @@ fn create_test_config() -> AppConfig {
             initial_retry_delay_sec: 10,
             retry_delay_seconds: 15,
             merkle_update_confirmations: 5,
+            deposit_expiry_hours: 72,
         },
+        l1_queue: L1QueueConfig::default(),
+        prover: ProverConfig::default(),
+        proof: ProofConfig::default(),
+        janitor: JanitorConfig::default(),
+        limits: LimitsConfig::default(),
+        grpc: GrpcConfig::default(),
+        simulation: SimulationConfig::default(),
+        webhooks: WebhookConfig::default(),
         merkle: MerkleConfig {
             tree_depth: 32,
             cache_size: 1000,
@@ -64,13 +99,16 @@ fn create_test_config() -> AppConfig {
             level: "debug".to_string(),
             file: "test.log".to_string(),
         },
+        telemetry: None,
         oracle: OracleConfig {
             tolerance_percent: Some(0.01),
             polling_interval_seconds: 60,
         },
         herodotus: HerodotusConfig {
             herodotus_endpoint: "https://test.example.com".to_string(),
+            ..HerodotusConfig::default()
         },
+        networks: Vec::new(),
     }
 }
 
@@ -146,7 +184,7 @@ fn test_hex_conversion_edge_cases() {
 
 #[test]
 fn test_stage_progression() {
-    let stages = vec![
+    let stages = [
         "processing",
         "initial_submitted",
         "step1_submitted",
@@ -156,7 +194,7 @@ fn test_stage_progression() {
     ];
 
     // Test stage parsing for step numbers
-    for (_i, stage) in stages.iter().enumerate() {
+    for stage in stages.iter() {
         if stage.starts_with("step") && stage.ends_with("_submitted") {
             let step_num: Option<u32> = stage
                 .strip_prefix("step")
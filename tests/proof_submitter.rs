@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+use zeroxbridge_sequencer::proof_submitter::{run, SubmissionReport, SubmitterArgs};
+use zeroxbridge_sequencer::relayer::proof_submission::ProofSubmissionError;
+
+fn fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/calldata")
+}
+
+fn dry_run_args(calldata_dir: &Path, report_path: Option<PathBuf>) -> SubmitterArgs {
+    SubmitterArgs {
+        calldata_dir: calldata_dir.to_path_buf(),
+        job_id: 42,
+        layout: "recursive_with_poseidon".to_string(),
+        hasher: "keccak_160_lsb".to_string(),
+        stone_version: "stone6".to_string(),
+        memory_verification: "true".to_string(),
+        // Never read in dry-run mode
+        config_path: PathBuf::from("does-not-exist.toml"),
+        dry_run: true,
+        report_path,
+    }
+}
+
+fn copy_fixture(to: &Path) {
+    for entry in fs::read_dir(fixture_dir()).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_dry_run_validates_fixture_and_writes_report() {
+    let out = tempdir().unwrap();
+    let report_path = out.path().join("reports/report.json");
+
+    let report = run(dry_run_args(&fixture_dir(), Some(report_path.clone())))
+        .await
+        .unwrap();
+
+    let stages: Vec<_> = report
+        .stages
+        .iter()
+        .map(|s| (s.stage.as_str(), s.calldata_len))
+        .collect();
+    // job_id and the four proof parameters precede the initial calldata
+    assert_eq!(
+        stages,
+        [("initial", 11), ("step1", 4), ("step2", 3), ("final", 5)]
+    );
+    assert!(report.dry_run);
+    assert!(!report.fact_registered);
+    assert!(report.stages.iter().all(|s| s.tx_hash.is_none()));
+    assert_eq!(
+        report.fact_hash.as_deref(),
+        Some("0x3d7e7a4bd8bcd5a1b7f1e6c1f0a9b2c3d4e5f60718293a4b5c6d7e8f9a0b1c2")
+    );
+
+    let written: SubmissionReport =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(written, report);
+}
+
+#[tokio::test]
+async fn test_dry_run_rejects_missing_final() {
+    let dir = tempdir().unwrap();
+    copy_fixture(dir.path());
+    fs::remove_file(dir.path().join("final")).unwrap();
+
+    let err = run(dry_run_args(dir.path(), None)).await.unwrap_err();
+
+    assert!(matches!(err, ProofSubmissionError::CalldataFileMissing(name) if name == "final"));
+}
+
+#[tokio::test]
+async fn test_dry_run_rejects_invalid_felt() {
+    let dir = tempdir().unwrap();
+    copy_fixture(dir.path());
+    fs::write(dir.path().join("step2"), "0x20 not-a-felt").unwrap();
+
+    let err = run(dry_run_args(dir.path(), None)).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        ProofSubmissionError::InvalidCalldataFormat(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_dry_run_rejects_corrupt_proof() {
+    let dir = tempdir().unwrap();
+    copy_fixture(dir.path());
+    fs::write(dir.path().join("proof.json"), "{ truncated").unwrap();
+
+    let err = run(dry_run_args(dir.path(), None)).await.unwrap_err();
+
+    assert!(matches!(err, ProofSubmissionError::Json(_)));
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+use tokio::net::TcpListener;
+use tonic::transport::Channel;
+use tonic::Code;
+use zeroxbridge_sequencer::proof_submitter::grpc::proto::proof_submitter_client::ProofSubmitterClient;
+use zeroxbridge_sequencer::proof_submitter::grpc::proto::SubmitProofRequest;
+use zeroxbridge_sequencer::proof_submitter::grpc::serve_with_listener;
+
+fn fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/calldata")
+}
+
+fn copy_fixture(to: &Path) {
+    for entry in fs::read_dir(fixture_dir()).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+    }
+}
+
+/// Starts a server on a free local port and connects a client to it
+async fn client() -> ProofSubmitterClient<Channel> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Never read in dry-run mode
+    tokio::spawn(serve_with_listener(
+        listener,
+        PathBuf::from("does-not-exist.toml"),
+    ));
+
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    ProofSubmitterClient::new(channel)
+}
+
+fn dry_run_request(calldata_dir: &Path) -> SubmitProofRequest {
+    SubmitProofRequest {
+        calldata_dir: calldata_dir.display().to_string(),
+        job_id: 42,
+        dry_run: true,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_dry_run_over_grpc_reports_stages() {
+    let mut client = client().await;
+
+    let response = client
+        .submit_proof(dry_run_request(&fixture_dir()))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let stages: Vec<_> = response
+        .stages
+        .iter()
+        .map(|s| (s.stage.as_str(), s.calldata_len))
+        .collect();
+    // Same calldata as the CLI with its default proof parameters
+    assert_eq!(
+        stages,
+        [("initial", 11), ("step1", 4), ("step2", 3), ("final", 5)]
+    );
+    assert_eq!(response.job_id, 42);
+    assert!(response.dry_run);
+    assert!(!response.fact_registered);
+    assert!(response.stages.iter().all(|s| s.tx_hash.is_none()));
+    assert_eq!(
+        response.fact_hash.as_deref(),
+        Some("0x3d7e7a4bd8bcd5a1b7f1e6c1f0a9b2c3d4e5f60718293a4b5c6d7e8f9a0b1c2")
+    );
+}
+
+#[tokio::test]
+async fn test_missing_calldata_file_is_not_found() {
+    let dir = tempdir().unwrap();
+    copy_fixture(dir.path());
+    fs::remove_file(dir.path().join("final")).unwrap();
+    let mut client = client().await;
+
+    let status = client
+        .submit_proof(dry_run_request(dir.path()))
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::NotFound);
+    assert!(status.message().contains("final"));
+}
+
+#[tokio::test]
+async fn test_invalid_calldata_is_an_invalid_argument() {
+    let dir = tempdir().unwrap();
+    copy_fixture(dir.path());
+    fs::write(dir.path().join("step2"), "0x20 not-a-felt").unwrap();
+    let mut client = client().await;
+
+    let status = client
+        .submit_proof(dry_run_request(dir.path()))
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
@@ -0,0 +1,183 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::Value;
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::{create_test_app, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{
+    get_relay_costs_summary, insert_relay_cost, RelayCost, RelayCostSummary,
+};
+
+/// Records a relay of `fee` on `day` for a new L2 transaction
+async fn record_cost(pool: &PgPool, network: &NetworkId, fee: i64, unit: &str, day: u32) {
+    let l2_transaction_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO l2_transactions (stark_pub_key, amount, token_address, network)
+        VALUES ('0xrelaycosts', 1000, '0x0', $1)
+        RETURNING id
+        "#,
+        network.as_str()
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+
+    let cost = RelayCost {
+        l2_transaction_id,
+        tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+        account_address: "0xacc".to_string(),
+        actual_fee: BigDecimal::from(fee),
+        fee_unit: unit.to_string(),
+        steps: 4200,
+        l1_gas: 0,
+        l1_data_gas: 192,
+    };
+    insert_relay_cost(pool, network, &cost).await.unwrap();
+    // Recording the same transaction again keeps the first record
+    insert_relay_cost(
+        pool,
+        network,
+        &RelayCost {
+            actual_fee: BigDecimal::from(1),
+            ..cost
+        },
+    )
+    .await
+    .unwrap();
+
+    let created_at = Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap();
+    sqlx::query!(
+        "UPDATE relay_costs SET created_at = $2 WHERE l2_transaction_id = $1",
+        l2_transaction_id,
+        created_at
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn summary(pool: &PgPool, network: &NetworkId) -> Vec<RelayCostSummary> {
+    let from = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+    get_relay_costs_summary(pool, from, from + Duration::days(3))
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|row| row.network == network.as_str())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_relay_costs_are_aggregated_per_day_and_unit() {
+    let app = create_test_app().await;
+    let network = unique_network("costs");
+    record_cost(&app.db, &network, 100, "FRI", 1).await;
+    record_cost(&app.db, &network, 300, "FRI", 1).await;
+    record_cost(&app.db, &network, 50, "WEI", 1).await;
+    record_cost(&app.db, &network, 700, "FRI", 2).await;
+    // Outside of the period
+    record_cost(&app.db, &network, 900, "FRI", 5).await;
+
+    let rows = summary(&app.db, &network).await;
+
+    let totals: Vec<_> = rows
+        .iter()
+        .map(|row| {
+            (
+                row.day.to_string(),
+                row.fee_unit.as_str(),
+                row.transactions,
+                row.total_fee.clone(),
+                row.average_fee.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        totals,
+        vec![
+            (
+                "2025-03-01".to_string(),
+                "FRI",
+                2,
+                BigDecimal::from(400),
+                BigDecimal::from(200)
+            ),
+            (
+                "2025-03-01".to_string(),
+                "WEI",
+                1,
+                BigDecimal::from(50),
+                BigDecimal::from(50)
+            ),
+            (
+                "2025-03-02".to_string(),
+                "FRI",
+                1,
+                BigDecimal::from(700),
+                BigDecimal::from(700)
+            ),
+        ]
+    );
+}
+
+async fn get(pool: &PgPool, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = create_router(pool.clone()).oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_relay_costs_endpoint_returns_the_summary() {
+    let app = create_test_app().await;
+    let network = unique_network("costs");
+    record_cost(&app.db, &network, 100, "FRI", 1).await;
+    record_cost(&app.db, &network, 200, "FRI", 1).await;
+
+    let (status, body) = get(
+        &app.db,
+        "/stats/relay-costs?from=2025-03-01T00:00:00Z&to=2025-03-02T00:00:00Z",
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let row = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|row| row["network"] == network.as_str())
+        .unwrap();
+    assert_eq!(row["day"], "2025-03-01");
+    assert_eq!(row["fee_unit"], "FRI");
+    assert_eq!(row["transactions"], 2);
+    assert_eq!(row["total_fee"], "300");
+}
+
+#[tokio::test]
+async fn test_relay_costs_endpoint_rejects_empty_period() {
+    let app = create_test_app().await;
+
+    let (status, body) = get(
+        &app.db,
+        "/stats/relay-costs?from=2025-03-02T00:00:00Z&to=2025-03-01T00:00:00Z",
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_range");
+}
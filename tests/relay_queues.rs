@@ -0,0 +1,287 @@
+#[path = "utils.rs"]
+mod utils;
+
+use serde_json::{json, Value};
+use sqlx::{types::BigDecimal, PgPool};
+use utils::{append_on_l1, create_test_app, random_commitment, unique_network};
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{insert_withdrawal_v2, upsert_deposit};
+use zeroxbridge_sequencer::queue::l1_queue::WithdrawalQueueProcessor;
+use zeroxbridge_sequencer::queue::l2_queue::{L2QueueProcessor, L2Transaction};
+use zeroxbridge_sequencer::tree_builder::{TreeBuilderClient, TreeBuilderConfig};
+
+async fn create_deposit(pool: &PgPool, network: &NetworkId, status: &str) -> (i32, String) {
+    let commitment_hash = random_commitment();
+    upsert_deposit(
+        pool,
+        network,
+        "0xrelayqueue",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+        status,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let id = sqlx::query_scalar!(
+        "SELECT id FROM deposits WHERE commitment_hash = $1",
+        commitment_hash
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    (id, commitment_hash)
+}
+
+/// Creates a deposit included in `tree_builder`'s tree whose proof is generated
+async fn proven_deposit(
+    pool: &PgPool,
+    network: &NetworkId,
+    tree_builder: &TreeBuilderClient,
+) -> (i32, String) {
+    let (id, commitment_hash) = create_deposit(pool, network, "PENDING_TREE_INCLUSION").await;
+    let index = tree_builder.tree().read().await.leaf_count().await.unwrap() as i64;
+    append_on_l1(pool, network, &commitment_hash, index).await;
+    tree_builder.process_pending_deposits().await.unwrap();
+    set_deposit_status(pool, id, "READY_FOR_RELAY").await;
+    (id, commitment_hash)
+}
+
+async fn set_deposit_status(pool: &PgPool, id: i32, status: &str) {
+    sqlx::query!("UPDATE deposits SET status = $2 WHERE id = $1", id, status)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+async fn deposit_status(pool: &PgPool, id: i32) -> String {
+    sqlx::query_scalar!("SELECT status FROM deposits WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+/// Reads the L2 transactions the Starknet relayer would pick up next
+async fn relayer_fetch(pool: &PgPool, network: &NetworkId) -> Vec<L2Transaction> {
+    sqlx::query_as!(
+        L2Transaction,
+        r#"
+        SELECT * FROM l2_transactions
+        WHERE network = $1 AND status = 'ready_for_relay'
+        ORDER BY created_at ASC
+        "#,
+        network.as_str()
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+/// Creates a withdrawal whose state proof arrived, or that never got one
+async fn ready_withdrawal(
+    pool: &PgPool,
+    network: &NetworkId,
+    state_proof: Option<Value>,
+) -> (i32, String) {
+    let commitment_hash = random_commitment();
+    let mut tx = pool.begin().await.unwrap();
+    let id = insert_withdrawal_v2(
+        &mut tx,
+        network,
+        "0xrelayqueue",
+        &BigDecimal::from(1000),
+        "0x0000000000000000000000000000000000000000",
+        &commitment_hash,
+        "0xl1hash",
+        1,
+    )
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+
+    sqlx::query!(
+        "UPDATE withdrawals SET status = 'ready_for_relay', state_proof = $2 WHERE id = $1",
+        id,
+        state_proof
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    (id, commitment_hash)
+}
+
+async fn withdrawal_status(pool: &PgPool, id: i32) -> String {
+    sqlx::query_scalar!("SELECT status FROM withdrawals WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_proven_deposit_is_handed_to_the_starknet_relayer() {
+    let app = create_test_app().await;
+    let network = unique_network("relay");
+    let tree_builder = TreeBuilderClient::new(
+        app.db.clone(),
+        network.clone(),
+        TreeBuilderConfig::default(),
+    );
+    let (id, commitment_hash) = proven_deposit(&app.db, &network, &tree_builder).await;
+    let processor = L2QueueProcessor::new(
+        app.db.clone(),
+        network.clone(),
+        app.config.queue.clone(),
+        tree_builder.tree(),
+    );
+
+    assert_eq!(processor.process_ready_deposits().await.unwrap(), 1);
+    assert_eq!(deposit_status(&app.db, id).await, "QUEUED_FOR_RELAY");
+
+    let transactions = relayer_fetch(&app.db, &network).await;
+    assert_eq!(transactions.len(), 1);
+    let transaction = &transactions[0];
+    assert_eq!(transaction.deposit_id, Some(id));
+    assert_eq!(transaction.stark_pub_key, "0xrelayqueue");
+    assert_eq!(transaction.amount, BigDecimal::from(1000));
+
+    let root = tree_builder.tree().read().await.get_root().await.unwrap();
+    let proof_data: Value =
+        serde_json::from_str(transaction.proof_data.as_deref().unwrap()).unwrap();
+    assert!(proof_data["proof"].is_array());
+    assert_eq!(
+        proof_data["merkle_root"],
+        format!("0x{}", hex::encode(root))
+    );
+    assert_eq!(proof_data["commitment_hash"], commitment_hash);
+    // The first MMR element
+    assert_eq!(proof_data["leaf_index"], 1);
+    for placeholder in ["eth_address", "r", "s", "y_parity"] {
+        assert!(
+            !proof_data[placeholder].is_null(),
+            "{} is missing",
+            placeholder
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_deposit_is_never_queued_twice() {
+    let app = create_test_app().await;
+    let network = unique_network("relay");
+    let tree_builder = TreeBuilderClient::new(
+        app.db.clone(),
+        network.clone(),
+        TreeBuilderConfig::default(),
+    );
+    let (id, _) = proven_deposit(&app.db, &network, &tree_builder).await;
+    let processor = L2QueueProcessor::new(
+        app.db.clone(),
+        network.clone(),
+        app.config.queue.clone(),
+        tree_builder.tree(),
+    );
+
+    assert_eq!(processor.process_ready_deposits().await.unwrap(), 1);
+    assert_eq!(processor.process_ready_deposits().await.unwrap(), 0);
+
+    // A deposit put back by hand is only moved along again
+    set_deposit_status(&app.db, id, "READY_FOR_RELAY").await;
+    assert_eq!(processor.process_ready_deposits().await.unwrap(), 0);
+    assert_eq!(deposit_status(&app.db, id).await, "QUEUED_FOR_RELAY");
+    assert_eq!(relayer_fetch(&app.db, &network).await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_l2_transaction_must_reference_an_existing_deposit() {
+    let app = create_test_app().await;
+    let network = unique_network("relay");
+
+    let err = sqlx::query!(
+        r#"
+        INSERT INTO l2_transactions
+            (deposit_id, network, stark_pub_key, amount, token_address, status)
+        VALUES ($1, $2, '0x123', 1000, '0x456', 'ready_for_relay')
+        "#,
+        i32::MAX,
+        network.as_str()
+    )
+    .execute(&app.db)
+    .await
+    .unwrap_err();
+
+    let err = err.as_database_error().unwrap();
+    assert_eq!(err.code().as_deref(), Some("23503"));
+    assert!(relayer_fetch(&app.db, &network).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_deposit_missing_from_the_tree_waits() {
+    let app = create_test_app().await;
+    let network = unique_network("relay");
+    let (id, _) = create_deposit(&app.db, &network, "READY_FOR_RELAY").await;
+    let tree_builder = TreeBuilderClient::new(
+        app.db.clone(),
+        network.clone(),
+        TreeBuilderConfig::default(),
+    );
+    let processor = L2QueueProcessor::new(
+        app.db.clone(),
+        network.clone(),
+        app.config.queue.clone(),
+        tree_builder.tree(),
+    );
+
+    assert_eq!(processor.process_ready_deposits().await.unwrap(), 0);
+    assert_eq!(deposit_status(&app.db, id).await, "READY_FOR_RELAY");
+    assert!(relayer_fetch(&app.db, &network).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_withdrawal_is_handed_to_the_ethereum_relayer() {
+    let app = create_test_app().await;
+    let network = unique_network("relay");
+    let state_proof = json!({ "queryStatus": "DONE", "proof": { "storageProof": ["0xabc"] } });
+    let (id, commitment_hash) = ready_withdrawal(&app.db, &network, Some(state_proof)).await;
+    let processor =
+        WithdrawalQueueProcessor::new(app.db.clone(), network.clone(), app.config.queue.clone());
+
+    assert_eq!(processor.process_ready_withdrawals().await.unwrap(), 1);
+    assert_eq!(processor.process_ready_withdrawals().await.unwrap(), 0);
+    assert_eq!(withdrawal_status(&app.db, id).await, "queued_for_relay");
+
+    // Same selection as the Ethereum relayer
+    let proofs = sqlx::query!(
+        r#"
+        SELECT w.id, dp.proof_params, dp.proof_data
+        FROM withdrawals w
+        JOIN withdrawal_proofs dp ON w.id = dp.withdrawal_id
+        WHERE w.network = $1 AND w.status = 'queued_for_relay' AND dp.status = 'ready'
+        "#,
+        network.as_str()
+    )
+    .fetch_all(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(proofs.len(), 1);
+    assert_eq!(proofs[0].id, id);
+    assert_eq!(
+        proofs[0].proof_params.as_deref(),
+        Some(hex::decode(&commitment_hash[2..]).unwrap().as_slice())
+    );
+    let proof = proofs[0].proof_data.as_deref().unwrap();
+    assert_eq!(proof.len(), 32);
+    assert_eq!(&proof[30..], &[0x0a, 0xbc]);
+}
+
+#[tokio::test]
+async fn test_withdrawal_without_state_proof_fails() {
+    let app = create_test_app().await;
+    let network = unique_network("relay");
+    let (id, _) = ready_withdrawal(&app.db, &network, None).await;
+    let processor =
+        WithdrawalQueueProcessor::new(app.db.clone(), network.clone(), app.config.queue.clone());
+
+    assert_eq!(processor.process_ready_withdrawals().await.unwrap(), 0);
+    assert_eq!(withdrawal_status(&app.db, id).await, "failed");
+}
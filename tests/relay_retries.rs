@@ -0,0 +1,234 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use starknet::core::types::{Call, Felt};
+use std::collections::HashMap;
+use utils::create_test_app;
+use zeroxbridge_sequencer::queue::l2_queue::L2Transaction;
+use zeroxbridge_sequencer::relayer::starknet_relayer::{
+    prepare_batched_transactions, relay_transactions, BatchedTransactionRelay, ProcessingReport,
+    StarknetRelayerError, TransactionRelay,
+};
+
+#[derive(Clone, Copy)]
+enum Failure {
+    ProviderTimeout,
+    Revert,
+    Unconfirmed,
+}
+
+impl Failure {
+    fn error(self) -> StarknetRelayerError {
+        match self {
+            Failure::ProviderTimeout => {
+                StarknetRelayerError::TimeoutError("provider timed out".to_string())
+            }
+            Failure::Revert => {
+                StarknetRelayerError::TransactionFailed("execution reverted".to_string())
+            }
+            Failure::Unconfirmed => StarknetRelayerError::Unconfirmed(Felt::ONE),
+        }
+    }
+}
+
+/// Relays every transaction, except those failing as registered. In batches, their
+/// resume fails instead.
+struct MockRelay {
+    failures: HashMap<i64, Failure>,
+}
+
+#[async_trait]
+impl TransactionRelay for MockRelay {
+    async fn relay_transaction(&self, tx: &mut L2Transaction) -> Result<(), StarknetRelayerError> {
+        match self.failures.get(&tx.id) {
+            None => Ok(()),
+            Some(failure) => Err(failure.error()),
+        }
+    }
+}
+
+#[async_trait]
+impl BatchedTransactionRelay for MockRelay {
+    async fn resume_transaction(&self, tx: &L2Transaction) -> Result<bool, StarknetRelayerError> {
+        match self.failures.get(&tx.id) {
+            None => Ok(false),
+            Some(failure) => Err(failure.error()),
+        }
+    }
+
+    fn build_calls(
+        &self,
+        _tx: &L2Transaction,
+        _proof_data: &str,
+    ) -> Result<Vec<Call>, StarknetRelayerError> {
+        Ok(Vec::new())
+    }
+}
+
+async fn insert_ready_transaction(pool: &PgPool) -> i64 {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO l2_transactions (stark_pub_key, amount, token_address, status, proof_data)
+        VALUES ('0xretries', 1000, '0xtoken', 'ready_for_relay', 'proof')
+        RETURNING id
+        "#
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+async fn fetch_transaction(pool: &PgPool, id: i64) -> L2Transaction {
+    sqlx::query_as!(
+        L2Transaction,
+        "SELECT * FROM l2_transactions WHERE id = $1",
+        id
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_timeouts_are_retried_and_reverts_fail() {
+    let app = create_test_app().await;
+    let relayed = insert_ready_transaction(&app.db).await;
+    let timed_out = insert_ready_transaction(&app.db).await;
+    let reverted = insert_ready_transaction(&app.db).await;
+    let relay = MockRelay {
+        failures: HashMap::from([
+            (timed_out, Failure::ProviderTimeout),
+            (reverted, Failure::Revert),
+        ]),
+    };
+
+    let mut transactions = Vec::new();
+    for id in [relayed, timed_out, reverted] {
+        transactions.push(fetch_transaction(&app.db, id).await);
+    }
+    let report = relay_transactions(&app.db, &relay, transactions, 3)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report,
+        ProcessingReport {
+            succeeded: vec![relayed],
+            retried: vec![timed_out],
+            failed: vec![(
+                reverted,
+                "Transaction failed: execution reverted".to_string()
+            )],
+            awaiting_confirmation: vec![],
+        }
+    );
+
+    let retried = fetch_transaction(&app.db, timed_out).await;
+    assert_eq!(retried.status, "ready_for_relay");
+    assert_eq!(retried.retry_count, 1);
+    assert_eq!(
+        retried.error.as_deref(),
+        Some("Timeout error: provider timed out")
+    );
+
+    let failed = fetch_transaction(&app.db, reverted).await;
+    assert_eq!(failed.status, "failed");
+    assert_eq!(failed.retry_count, 0);
+}
+
+#[tokio::test]
+async fn test_transient_failures_fail_once_retries_are_used_up() {
+    let app = create_test_app().await;
+    let id = insert_ready_transaction(&app.db).await;
+    let relay = MockRelay {
+        failures: HashMap::from([(id, Failure::ProviderTimeout)]),
+    };
+
+    for retry_count in 1..3 {
+        let tx = fetch_transaction(&app.db, id).await;
+        let report = relay_transactions(&app.db, &relay, vec![tx], 3)
+            .await
+            .unwrap();
+        assert_eq!(report.retried, vec![id]);
+        assert_eq!(
+            fetch_transaction(&app.db, id).await.retry_count,
+            retry_count
+        );
+    }
+
+    // The third failing cycle is the last
+    let tx = fetch_transaction(&app.db, id).await;
+    let report = relay_transactions(&app.db, &relay, vec![tx], 3)
+        .await
+        .unwrap();
+    assert!(report.retried.is_empty());
+    assert_eq!(report.failed.len(), 1);
+    let failed = fetch_transaction(&app.db, id).await;
+    assert_eq!(failed.status, "failed");
+    assert_eq!(failed.retry_count, 2);
+}
+
+#[tokio::test]
+async fn test_unconfirmed_transactions_are_left_processing() {
+    let app = create_test_app().await;
+    let id = insert_ready_transaction(&app.db).await;
+    sqlx::query!(
+        "UPDATE l2_transactions SET status = 'processing' WHERE id = $1",
+        id
+    )
+    .execute(&app.db)
+    .await
+    .unwrap();
+    let relay = MockRelay {
+        failures: HashMap::from([(id, Failure::Unconfirmed)]),
+    };
+
+    let tx = fetch_transaction(&app.db, id).await;
+    let report = relay_transactions(&app.db, &relay, vec![tx], 3)
+        .await
+        .unwrap();
+
+    assert_eq!(report.awaiting_confirmation, vec![id]);
+    assert!(report.retried.is_empty());
+    assert!(report.failed.is_empty());
+    // Neither queued for another submission nor charged a retry
+    let pending = fetch_transaction(&app.db, id).await;
+    assert_eq!(pending.status, "processing");
+    assert_eq!(pending.retry_count, 0);
+}
+
+#[tokio::test]
+async fn test_failed_resume_only_holds_back_its_own_transaction() {
+    let app = create_test_app().await;
+    let ready = insert_ready_transaction(&app.db).await;
+    let unresumable = insert_ready_transaction(&app.db).await;
+    let relay = MockRelay {
+        failures: HashMap::from([(unresumable, Failure::ProviderTimeout)]),
+    };
+
+    let transactions = vec![
+        fetch_transaction(&app.db, ready).await,
+        fetch_transaction(&app.db, unresumable).await,
+    ];
+    let mut report = ProcessingReport::default();
+    let items = prepare_batched_transactions(&app.db, &relay, &transactions, 3, &mut report)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        items.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+        vec![ready]
+    );
+    assert_eq!(report.retried, vec![unresumable]);
+    assert!(report.failed.is_empty());
+
+    // Nothing is marked processing before its batch is sent
+    let batched = fetch_transaction(&app.db, ready).await;
+    assert_eq!(batched.status, "ready_for_relay");
+    assert_eq!(batched.retry_count, 0);
+    let retried = fetch_transaction(&app.db, unresumable).await;
+    assert_eq!(retried.status, "ready_for_relay");
+    assert_eq!(retried.retry_count, 1);
+}
@@ -0,0 +1,332 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use starknet::core::types::{Call, Felt, InvokeTransactionReceipt, StarknetError};
+use starknet::providers::ProviderError;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use utils::create_test_app;
+use zeroxbridge_sequencer::db::database::{
+    fetch_pending_submitted_tx_hash, resolve_submitted_tx_hashes, SUBMITTED_TX_CONFIRMED,
+    SUBMITTED_TX_FAILED,
+};
+use zeroxbridge_sequencer::relayer::nonce_manager::NonceSource;
+use zeroxbridge_sequencer::relayer::starknet_relayer::StarknetRelayerError;
+use zeroxbridge_sequencer::relayer::submission::{
+    resume_submission, submit_recorded, InvokeSubmitter, PreparedInvoke, Resumed,
+};
+
+/// Node holding every sent transaction until told to accept or revert them.
+///
+/// The account's nonce only moves when a test says so, and with `db` set every
+/// broadcast notes whether its hash was already recorded.
+#[derive(Default)]
+struct MockNode {
+    sent: Mutex<Vec<Felt>>,
+    nonce: AtomicU64,
+    accepting: AtomicBool,
+    reverting: AtomicBool,
+    rejecting: AtomicBool,
+    db: Option<PgPool>,
+    recorded_before_broadcast: Mutex<Vec<bool>>,
+}
+
+impl MockNode {
+    fn sent(&self) -> Vec<Felt> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl NonceSource for MockNode {
+    async fn fetch_nonce(&self, _account_address: Felt) -> Result<Felt, ProviderError> {
+        Ok(Felt::from(self.nonce.load(Ordering::SeqCst)))
+    }
+}
+
+#[async_trait]
+impl InvokeSubmitter for MockNode {
+    fn account_address(&self) -> Felt {
+        Felt::ONE
+    }
+
+    async fn prepare<'a>(
+        &'a self,
+        _calls: &[Call],
+        _nonce: Felt,
+        _gas_price_multiplier: f64,
+    ) -> Result<PreparedInvoke<'a>, StarknetRelayerError> {
+        let tx_hash = Felt::from(0x1000 + self.sent().len() as u64);
+
+        Ok(PreparedInvoke::new(tx_hash, async move {
+            if let Some(db) = &self.db {
+                let recorded = sqlx::query_scalar!(
+                    "SELECT EXISTS (SELECT 1 FROM submitted_tx_hashes WHERE tx_hash = $1)",
+                    format!("{:#x}", tx_hash)
+                )
+                .fetch_one(db)
+                .await
+                .unwrap()
+                .unwrap_or(false);
+                self.recorded_before_broadcast
+                    .lock()
+                    .unwrap()
+                    .push(recorded);
+            }
+            if self.rejecting.load(Ordering::SeqCst) {
+                return Err(StarknetRelayerError::Provider(
+                    ProviderError::StarknetError(StarknetError::InvalidTransactionNonce),
+                ));
+            }
+            self.sent.lock().unwrap().push(tx_hash);
+            Ok(())
+        }))
+    }
+
+    async fn receipt(
+        &self,
+        tx_hash: Felt,
+    ) -> Result<Option<InvokeTransactionReceipt>, StarknetRelayerError> {
+        if !self.sent().contains(&tx_hash) {
+            return Ok(None);
+        }
+        if self.reverting.load(Ordering::SeqCst) {
+            return Err(StarknetRelayerError::TransactionFailed(
+                "Withdrawal already processed".to_string(),
+            ));
+        }
+        Ok(self
+            .accepting
+            .load(Ordering::SeqCst)
+            .then(|| receipt(tx_hash)))
+    }
+}
+
+fn receipt(tx_hash: Felt) -> InvokeTransactionReceipt {
+    serde_json::from_value(serde_json::json!({
+        "type": "INVOKE",
+        "transaction_hash": format!("{:#x}", tx_hash),
+        "actual_fee": { "amount": "0x1", "unit": "FRI" },
+        "finality_status": "ACCEPTED_ON_L2",
+        "messages_sent": [],
+        "events": [],
+        "execution_resources": {
+            "steps": 1,
+            "data_availability": { "l1_gas": 0, "l1_data_gas": 0 }
+        },
+        "execution_status": "SUCCEEDED"
+    }))
+    .unwrap()
+}
+
+/// Creates an L2 transaction the relayer has picked up
+async fn create_l2_transaction(pool: &PgPool) -> i64 {
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO l2_transactions (stark_pub_key, amount, token_address, status)
+        VALUES ('0xresubmission', 1000, '0x0', 'processing')
+        RETURNING id
+        "#
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+fn calls(l2_transaction_id: i64) -> Vec<Call> {
+    vec![Call {
+        to: Felt::ONE,
+        selector: Felt::TWO,
+        calldata: vec![Felt::from(l2_transaction_id)],
+    }]
+}
+
+async fn resume(pool: &PgPool, node: &MockNode, l2_transaction_id: i64) -> Resumed {
+    resume_submission(
+        pool,
+        node,
+        l2_transaction_id,
+        Duration::from_millis(200),
+        Duration::from_millis(5),
+    )
+    .await
+    .unwrap()
+}
+
+async fn submission_status(pool: &PgPool, tx_hash: Felt) -> String {
+    sqlx::query_scalar!(
+        "SELECT status FROM submitted_tx_hashes WHERE tx_hash = $1",
+        format!("{:#x}", tx_hash)
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_restarted_relayer_waits_for_the_transaction_it_sent() {
+    let app = create_test_app().await;
+    let node = MockNode::default();
+    let id = create_l2_transaction(&app.db).await;
+
+    // The relayer sends the transaction, then stops before it is accepted
+    let submission = submit_recorded(&app.db, &node, None, &[id], calls(id))
+        .await
+        .unwrap();
+    let pending = fetch_pending_submitted_tx_hash(&app.db, id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(pending.tx_hash, format!("{:#x}", submission.tx_hash));
+    assert_eq!(pending.nonce.as_deref(), Some("0x0"));
+
+    // Once restarted, it picks the recorded hash up instead of sending again
+    node.accepting.store(true, Ordering::SeqCst);
+    let Resumed::Confirmed(tx_hash, _) = resume(&app.db, &node, id).await else {
+        panic!("expected the sent transaction to be confirmed");
+    };
+    assert_eq!(tx_hash, submission.tx_hash);
+    assert_eq!(node.sent(), vec![submission.tx_hash]);
+
+    let resolved = resolve_submitted_tx_hashes(&app.db, &[id], SUBMITTED_TX_CONFIRMED)
+        .await
+        .unwrap();
+    assert_eq!(resolved, 1);
+    assert!(matches!(resume(&app.db, &node, id).await, Resumed::NotSent));
+    assert_eq!(node.sent().len(), 1);
+}
+
+#[tokio::test]
+async fn test_hash_is_recorded_before_the_transaction_is_broadcast() {
+    let app = create_test_app().await;
+    let node = MockNode {
+        db: Some(app.db.clone()),
+        ..MockNode::default()
+    };
+    let id = create_l2_transaction(&app.db).await;
+
+    submit_recorded(&app.db, &node, None, &[id], calls(id))
+        .await
+        .unwrap();
+
+    assert_eq!(*node.recorded_before_broadcast.lock().unwrap(), vec![true]);
+}
+
+#[tokio::test]
+async fn test_rejected_broadcast_is_marked_failed() {
+    let app = create_test_app().await;
+    let node = MockNode::default();
+    node.rejecting.store(true, Ordering::SeqCst);
+    let id = create_l2_transaction(&app.db).await;
+
+    let result = submit_recorded(&app.db, &node, None, &[id], calls(id)).await;
+
+    assert!(result.is_err());
+    assert!(node.sent().is_empty());
+    assert_eq!(
+        submission_status(&app.db, Felt::from(0x1000u64)).await,
+        SUBMITTED_TX_FAILED
+    );
+    assert!(matches!(resume(&app.db, &node, id).await, Resumed::NotSent));
+}
+
+#[tokio::test]
+async fn test_unaccepted_submission_is_not_sent_again_while_its_nonce_is_free() {
+    let app = create_test_app().await;
+    let node = MockNode::default();
+    let id = create_l2_transaction(&app.db).await;
+    let submission = submit_recorded(&app.db, &node, None, &[id], calls(id))
+        .await
+        .unwrap();
+
+    // Not accepted within the timeout, but nothing else took nonce 0 either
+    match resume(&app.db, &node, id).await {
+        Resumed::Pending(tx_hash) => assert_eq!(tx_hash, submission.tx_hash),
+        other => panic!("expected the submission to stay pending, got {:?}", other),
+    }
+    assert!(fetch_pending_submitted_tx_hash(&app.db, id)
+        .await
+        .unwrap()
+        .is_some());
+
+    // It is still picked up once the node includes it
+    node.accepting.store(true, Ordering::SeqCst);
+    assert!(matches!(
+        resume(&app.db, &node, id).await,
+        Resumed::Confirmed(..)
+    ));
+    assert_eq!(node.sent().len(), 1);
+}
+
+#[tokio::test]
+async fn test_submission_whose_nonce_was_taken_is_sent_again() {
+    let app = create_test_app().await;
+    let node = MockNode::default();
+    let id = create_l2_transaction(&app.db).await;
+    let submission = submit_recorded(&app.db, &node, None, &[id], calls(id))
+        .await
+        .unwrap();
+
+    // Another transaction of the account was included with nonce 0
+    node.nonce.store(1, Ordering::SeqCst);
+    assert!(matches!(resume(&app.db, &node, id).await, Resumed::NotSent));
+    assert_eq!(
+        submission_status(&app.db, submission.tx_hash).await,
+        SUBMITTED_TX_FAILED
+    );
+}
+
+#[tokio::test]
+async fn test_reverted_submission_is_sent_again() {
+    let app = create_test_app().await;
+    let node = MockNode::default();
+    let id = create_l2_transaction(&app.db).await;
+    let first = submit_recorded(&app.db, &node, None, &[id], calls(id))
+        .await
+        .unwrap();
+
+    node.reverting.store(true, Ordering::SeqCst);
+    assert!(matches!(resume(&app.db, &node, id).await, Resumed::NotSent));
+
+    assert_eq!(
+        submission_status(&app.db, first.tx_hash).await,
+        SUBMITTED_TX_FAILED
+    );
+    assert!(fetch_pending_submitted_tx_hash(&app.db, id)
+        .await
+        .unwrap()
+        .is_none());
+
+    let second = submit_recorded(&app.db, &node, None, &[id], calls(id))
+        .await
+        .unwrap();
+    assert_ne!(second.tx_hash, first.tx_hash);
+    assert_eq!(node.sent().len(), 2);
+}
+
+#[tokio::test]
+async fn test_batch_submission_is_recorded_for_every_transaction() {
+    let app = create_test_app().await;
+    let node = MockNode::default();
+    let ids = [
+        create_l2_transaction(&app.db).await,
+        create_l2_transaction(&app.db).await,
+    ];
+    let batch = ids.iter().flat_map(|id| calls(*id)).collect();
+
+    let submission = submit_recorded(&app.db, &node, None, &ids, batch)
+        .await
+        .unwrap();
+
+    node.accepting.store(true, Ordering::SeqCst);
+    for id in ids {
+        let Resumed::Confirmed(tx_hash, _) = resume(&app.db, &node, id).await else {
+            panic!("expected the batch to be confirmed");
+        };
+        assert_eq!(tx_hash, submission.tx_hash);
+    }
+    assert_eq!(node.sent().len(), 1);
+}
@@ -0,0 +1,74 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, Request, StatusCode},
+    response::Response,
+    Router,
+};
+use futures_util::stream::{self, StreamExt};
+use serde_json::json;
+use std::convert::Infallible;
+use tower::ServiceExt;
+use utils::create_test_app;
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+
+async fn router(max_body_bytes: usize, request_timeout_ms: u64) -> Router {
+    let app = create_test_app().await;
+    let mut config = app.config.clone();
+    config.server.max_body_bytes = max_body_bytes;
+    config.server.request_timeout_ms = request_timeout_ms;
+    create_router_with_config(app.db.clone(), &config)
+}
+
+async fn error_code(response: Response) -> String {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    parsed["error"]["code"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_body_over_configured_limit_returns_413() {
+    let router = router(1024, 30_000).await;
+
+    let payload = json!({
+        "stark_pubkey": "0x".to_string() + &"a".repeat(2048),
+        "usd_val": 1000,
+        "nonce": 1,
+        "timestamp": 1640995200
+    })
+    .to_string();
+    let request = Request::builder()
+        .method("POST")
+        .uri("/deposit")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::CONTENT_LENGTH, payload.len())
+        .body(Body::from(payload))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(error_code(response).await, "payload_too_large");
+}
+
+#[tokio::test]
+async fn test_slow_request_returns_408() {
+    let router = router(1024 * 1024, 50).await;
+
+    // The client sends the start of the body and then stalls
+    let chunks = stream::once(async { Ok::<_, Infallible>(Bytes::from_static(b"{\"stark")) })
+        .chain(stream::pending());
+    let request = Request::builder()
+        .method("POST")
+        .uri("/compute-hash")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from_stream(chunks))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    assert_eq!(error_code(response).await, "request_timeout");
+}
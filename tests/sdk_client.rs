@@ -0,0 +1,201 @@
+#[path = "utils.rs"]
+mod utils;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use utils::{create_test_app, random_commitment};
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::{create_router_with_tree_state, AppState, TreeState};
+use zeroxbridge_sequencer::api::types::{
+    CreateWithdrawalRequest, DepositRequest, HashRequest, NonceType,
+};
+use zeroxbridge_sequencer::sdk::{ClientConfig, SdkError, SequencerClient};
+
+fn deposit_request(commitment_hash: &str) -> DepositRequest {
+    DepositRequest {
+        stark_pub_key: "0x123".to_string(),
+        amount: 1000,
+        commitment_hash: commitment_hash.to_string(),
+    }
+}
+
+/// Serves the API on an ephemeral port, returning its base URL
+async fn serve(app: &Arc<AppState>, tree_state: TreeState) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = create_router_with_tree_state(app.db.clone(), tree_state);
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+async fn client() -> SequencerClient {
+    let app = create_test_app().await;
+    SequencerClient::new(serve(&app, TreeState::default()).await)
+}
+
+fn assert_api_error<T: std::fmt::Debug>(result: Result<T, SdkError>, status: u16, code: &str) {
+    match result.unwrap_err() {
+        SdkError::Api {
+            status: actual,
+            code: actual_code,
+            ..
+        } => {
+            assert_eq!(actual, status);
+            assert_eq!(actual_code, code);
+        }
+        e => panic!("expected an API error, got {:?}", e),
+    }
+}
+
+#[tokio::test]
+async fn test_created_deposit_status_is_fetched() {
+    let client = client().await;
+    let commitment_hash = random_commitment();
+
+    let created = client
+        .create_deposit(&deposit_request(&commitment_hash))
+        .await
+        .unwrap();
+    assert_eq!(created.status, "pending");
+
+    let deposit = client.get_deposit_status(&commitment_hash).await.unwrap();
+    assert_eq!(deposit.id, created.deposit_id);
+    assert_eq!(deposit.commitment_hash, commitment_hash);
+    assert_eq!(deposit.status, "pending");
+}
+
+#[tokio::test]
+async fn test_api_errors_are_mapped_from_the_error_body() {
+    let client = client().await;
+    let commitment_hash = random_commitment();
+    client
+        .create_deposit(&deposit_request(&commitment_hash))
+        .await
+        .unwrap();
+
+    let duplicate = client
+        .create_deposit(&deposit_request(&commitment_hash))
+        .await;
+    assert_api_error(duplicate, 409, "duplicate_commitment");
+
+    let missing = client.get_deposit_status(&random_commitment()).await;
+    assert_api_error(missing, 404, "deposit_not_found");
+}
+
+#[tokio::test]
+async fn test_network_is_sent_with_requests() {
+    let app = create_test_app().await;
+    let config = ClientConfig {
+        network: Some("unknown".to_string()),
+        ..ClientConfig::default()
+    };
+    let client = SequencerClient::with_config(serve(&app, TreeState::default()).await, config);
+
+    let result = client
+        .create_deposit(&deposit_request(&random_commitment()))
+        .await;
+    assert_api_error(result, 400, "unknown_network");
+}
+
+#[tokio::test]
+async fn test_commitment_hash_is_computed() {
+    let client = client().await;
+    let request = HashRequest {
+        stark_pubkey: format!("0x{}", "01".repeat(32)),
+        usd_val: 1000,
+        nonce: 42,
+        timestamp: 1640995200,
+    };
+
+    let response = client.compute_commitment_hash(&request).await.unwrap();
+    assert!(response.commitment_hash.starts_with("0x"));
+    assert_eq!(response.commitment_hash.len(), 66);
+    assert_eq!(response.input_data.usd_val, 1000);
+    assert_eq!(response.input_data.nonce, 42);
+}
+
+#[tokio::test]
+async fn test_next_nonce_is_fetched() {
+    let client = client().await;
+    let stark_pub_key = format!("0x{:0>64}", Uuid::new_v4().simple().to_string());
+
+    let response = client
+        .get_next_nonce(&stark_pub_key, NonceType::Withdrawal)
+        .await
+        .unwrap();
+    assert_eq!(response.stark_pub_key, stark_pub_key);
+    assert_eq!(response.nonce_type, NonceType::Withdrawal);
+    assert_eq!(response.nonce, 1);
+}
+
+#[tokio::test]
+async fn test_merkle_proof_is_fetched() {
+    let app = create_test_app().await;
+    let tree_state = TreeState::default();
+    tree_state
+        .tree
+        .write()
+        .await
+        .build_merkle(vec![[7u8; 32]])
+        .await
+        .unwrap();
+    let client = SequencerClient::new(serve(&app, tree_state).await);
+
+    let commitment_hash = format!("0x{}", "07".repeat(32));
+    let proof = client.get_merkle_proof(&commitment_hash).await.unwrap();
+    assert_eq!(proof.commitment_hash, commitment_hash);
+    // Indexes are MMR element indexes, which start at 1
+    assert_eq!(proof.leaf_index, 1);
+    assert_eq!(proof.elements_count, 1);
+
+    let missing = client.get_merkle_proof(&random_commitment()).await;
+    assert_api_error(missing, 404, "leaf_not_found");
+}
+
+#[tokio::test]
+async fn test_withdrawal_is_created() {
+    let client = client().await;
+    let request = CreateWithdrawalRequest {
+        stark_pub_key: format!("0x{:064x}", 0xabc123),
+        amount: 5000,
+        commitment_hash: random_commitment(),
+        l1_token: "0xtoken123".to_string(),
+        signature: None,
+        timestamp: None,
+    };
+
+    let response = client.create_withdrawal(&request).await.unwrap();
+    assert!(response.withdrawal_id > 0);
+
+    let duplicate = client.create_withdrawal(&request).await;
+    assert_api_error(duplicate, 409, "duplicate_commitment");
+}
+
+#[tokio::test]
+async fn test_unreachable_sequencer_is_retried() {
+    // Nothing listens on the port once the listener is dropped
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let config = ClientConfig {
+        max_retries: 2,
+        retry_delay: Duration::from_millis(50),
+        ..ClientConfig::default()
+    };
+    let client = SequencerClient::with_config(format!("http://{}", addr), config);
+
+    let started = Instant::now();
+    let result = client.get_deposit_status(&random_commitment()).await;
+    assert!(matches!(result, Err(SdkError::Http(_))));
+    // Waited 50ms, then 100ms before giving up
+    assert!(started.elapsed() >= Duration::from_millis(150));
+}
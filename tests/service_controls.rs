@@ -0,0 +1,172 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode, Uri},
+    Router,
+};
+use ethers::signers::LocalWallet;
+use ethers::types::H256;
+use serde_json::{json, Value};
+use sqlx::{types::BigDecimal, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+use utils::{create_test_app, unique_network, ADMIN_KEYS};
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::auth::{admin_request_hash, ADMIN_SIGNATURE_HEADERS};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::QueueConfig;
+use zeroxbridge_sequencer::db::database::{
+    upsert_deposit, Deposit, DEPOSIT_STATUS_PENDING_PROOF_GENERATION,
+};
+use zeroxbridge_sequencer::db::service_controls::PAUSE_CACHE_TTL;
+use zeroxbridge_sequencer::proof_client::service::{
+    DepositProver, ProofClientService, ProofGenerationError, DEPOSIT_STATUS_READY_FOR_RELAY,
+};
+
+struct InstantProver;
+
+#[async_trait]
+impl DepositProver for InstantProver {
+    async fn prove(&self, _deposit: &Deposit) -> Result<(), ProofGenerationError> {
+        Ok(())
+    }
+}
+
+async fn send(router: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+/// POSTs to `/admin/services/{service}/{action}` with the admins' signatures
+async fn control_service(router: &Router, service: &str, action: &str) -> (StatusCode, Value) {
+    let method = Method::POST;
+    let uri: Uri = format!("/admin/services/{}/{}", service, action)
+        .parse()
+        .unwrap();
+    let body = json!({ "updated_by": "oncall", "reason": "bad L2 upgrade" }).to_string();
+    let hash = H256::from(admin_request_hash(&method, &uri, body.as_bytes()));
+
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    for (header, key) in ADMIN_SIGNATURE_HEADERS.iter().zip(ADMIN_KEYS) {
+        let wallet: LocalWallet = key.parse().unwrap();
+        let signature = wallet.sign_hash(hash).unwrap();
+        request = request.header(*header, format!("0x{}", signature));
+    }
+
+    send(router, request.body(Body::from(body)).unwrap()).await
+}
+
+/// State `GET /health` reports for `service`
+async fn health_state(router: &Router, service: &str) -> (String, Value) {
+    let request = Request::builder()
+        .uri("/health")
+        .body(Body::empty())
+        .unwrap();
+    let (status, body) = send(router, request).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let service = body["services"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["service"] == service)
+        .unwrap()
+        .clone();
+    (body["status"].as_str().unwrap().to_string(), service)
+}
+
+async fn deposit_status(pool: &PgPool, commitment_hash: &str) -> String {
+    sqlx::query_scalar!(
+        "SELECT status FROM deposits WHERE commitment_hash = $1",
+        commitment_hash
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_paused_proof_client_proves_nothing_until_resumed() {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    let network = unique_network("pause");
+
+    let (status, body) = control_service(&router, "proof_client", "pause").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["paused"], true);
+    assert_eq!(body["updated_by"], "oncall");
+
+    let (overall, proof_client) = health_state(&router, "proof_client").await;
+    assert_eq!(overall, "paused");
+    assert_eq!(proof_client["state"], "paused");
+    assert_eq!(proof_client["reason"], "bad L2 upgrade");
+
+    let proof_client = ProofClientService::with_prover(
+        app.db.clone(),
+        network.clone(),
+        QueueConfig {
+            process_interval_sec: 1,
+            ..app.config.queue.clone()
+        },
+        "pause-worker",
+        Arc::new(InstantProver),
+    );
+    let handle = tokio::spawn(async move { proof_client.start().await });
+
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    upsert_deposit(
+        &app.db,
+        &network,
+        "0xpause",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+        DEPOSIT_STATUS_PENDING_PROOF_GENERATION,
+        None,
+    )
+    .await
+    .unwrap();
+
+    tokio::time::sleep(PAUSE_CACHE_TTL + Duration::from_secs(1)).await;
+    assert_eq!(
+        deposit_status(&app.db, &commitment_hash).await,
+        DEPOSIT_STATUS_PENDING_PROOF_GENERATION
+    );
+
+    let (status, body) = control_service(&router, "proof_client", "resume").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["paused"], false);
+    let (_, proof_client) = health_state(&router, "proof_client").await;
+    assert_eq!(proof_client["state"], "running");
+
+    let mut proved = false;
+    for _ in 0..50 {
+        if deposit_status(&app.db, &commitment_hash).await == DEPOSIT_STATUS_READY_FOR_RELAY {
+            proved = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    handle.abort();
+    assert!(proved, "the deposit was not proved once resumed");
+}
+
+#[tokio::test]
+async fn test_unknown_service_is_not_found() {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+
+    let (status, body) = control_service(&router, "oracle", "pause").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "unknown_service");
+}
@@ -0,0 +1,194 @@
+#![cfg(feature = "simulation")]
+
+#[path = "utils.rs"]
+mod utils;
+
+use alloy::primitives::U256;
+use alloy::rpc::types::Log;
+use alloy::sol_types::SolEvent;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use utils::{create_test_app, random_commitment, unique_network};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::{AppConfig, NetworkConfig, NetworkId, SimulationConfig};
+use zeroxbridge_sequencer::events::l1_event_watcher::ZeroXBridge;
+use zeroxbridge_sequencer::proof_client::artifact_store::LocalArtifactStore;
+use zeroxbridge_sequencer::proof_client::service::{ProofClientService, StoneDepositProver};
+use zeroxbridge_sequencer::queue::l1_queue::L1QueueProcessor;
+use zeroxbridge_sequencer::queue::l2_queue::L2QueueProcessor;
+use zeroxbridge_sequencer::simulation::{backend_from_config, EventsFixture};
+use zeroxbridge_sequencer::tree_builder::{TreeBuilderClient, TreeBuilderConfig};
+
+const L1_CONTRACT: &str = "0x00000000000000000000000000000000000000aa";
+const DEPOSIT_BLOCK: u64 = 100;
+const LATEST_BLOCK: u64 = 110;
+
+fn network_config(config: &AppConfig, name: &NetworkId) -> NetworkConfig {
+    let mut contracts = config.contracts.clone();
+    contracts.l1_contract_address = L1_CONTRACT.to_string();
+    NetworkConfig {
+        name: name.clone(),
+        contracts,
+        ethereum: config.ethereum.clone(),
+        starknet: config.starknet.clone(),
+    }
+}
+
+/// Chain on which `commitment_hash` was appended as the first deposit at `DEPOSIT_BLOCK`
+fn deposit_fixture(commitment_hash: &str) -> EventsFixture {
+    let commitment = U256::from_str_radix(commitment_hash.trim_start_matches("0x"), 16).unwrap();
+    let event = ZeroXBridge::DepositHashAppended {
+        index: U256::ZERO,
+        commitmentHash: commitment,
+        rootHash: commitment,
+        elementsCount: U256::from(1),
+    };
+    EventsFixture {
+        latest_block: LATEST_BLOCK,
+        logs: vec![Log {
+            inner: alloy::primitives::Log {
+                address: L1_CONTRACT.parse().unwrap(),
+                data: event.encode_log_data(),
+            },
+            block_number: Some(DEPOSIT_BLOCK),
+            ..Default::default()
+        }],
+    }
+}
+
+async fn deposit_status(pool: &sqlx::PgPool, id: i64) -> String {
+    sqlx::query_scalar!("SELECT status FROM deposits WHERE id = $1", id as i32)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_deposit_runs_through_the_simulated_pipeline() {
+    let app = create_test_app().await;
+    let name = unique_network("sim");
+    let network = network_config(&app.config, &name);
+    let mut config = app.config.clone();
+    config.networks = vec![network.clone()];
+
+    // The deposit enters through the API like any other
+    let commitment_hash = random_commitment();
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/deposit?network={}", name))
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "stark_pub_key": "0x123",
+                "amount": 1000,
+                "commitment_hash": commitment_hash,
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = create_router_with_config(app.db.clone(), &config)
+        .oneshot(request)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let deposit_id = body["deposit_id"].as_i64().unwrap();
+
+    let dir = TempDir::new().unwrap();
+    let fixture_path = dir.path().join("events.json");
+    std::fs::write(
+        &fixture_path,
+        serde_json::to_string(&deposit_fixture(&commitment_hash)).unwrap(),
+    )
+    .unwrap();
+    let backend = backend_from_config(&SimulationConfig {
+        enabled: true,
+        relay_delay_ms: 0,
+        events_fixture: Some(fixture_path),
+    })
+    .unwrap();
+
+    // L1: the commitment is read from the fixture and confirmed against its latest block
+    let mut pool = app.db.clone();
+    for mut watcher in backend.l1_event_watchers(&network, 0, &config.limits) {
+        watcher.poll(&mut pool).await.unwrap();
+    }
+    let mut l1_queue = config.l1_queue.clone();
+    l1_queue.confirmation_blocks = 3;
+    let l1_processor = L1QueueProcessor::new(
+        app.db.clone(),
+        name.clone(),
+        l1_queue,
+        config.queue.max_retries,
+        backend.l1_block_source(&network),
+    );
+    assert_eq!(l1_processor.process_deposits().await.unwrap(), 1);
+
+    let tree_builder =
+        TreeBuilderClient::new(app.db.clone(), name.clone(), TreeBuilderConfig::default());
+    assert_eq!(tree_builder.process_pending_deposits().await.unwrap(), 1);
+
+    // Proving writes synthetic artifacts instead of running Scarb and Stone
+    let stages = backend
+        .proof_stages(
+            app.db.clone(),
+            config.prover.clone(),
+            dir.path().join("cairo"),
+        )
+        .unwrap();
+    let prover = StoneDepositProver::with_stages(
+        app.db.clone(),
+        stages,
+        Arc::new(LocalArtifactStore::new(dir.path().join("artifacts"))),
+        dir.path().join("work"),
+    );
+    let proof_client = ProofClientService::with_prover(
+        app.db.clone(),
+        name.clone(),
+        config.queue.clone(),
+        "simulation",
+        Arc::new(prover),
+    );
+    assert_eq!(proof_client.process_pending_deposits().await.unwrap(), 1);
+
+    let l2_processor = L2QueueProcessor::new(
+        app.db.clone(),
+        name.clone(),
+        config.queue.clone(),
+        tree_builder.tree(),
+    );
+    assert_eq!(l2_processor.process_ready_deposits().await.unwrap(), 1);
+    assert_eq!(
+        deposit_status(&app.db, deposit_id).await,
+        "QUEUED_FOR_RELAY"
+    );
+
+    // L2: the relay only logs its calls and completes the transaction
+    let relayer = backend
+        .starknet_relayer(app.db.clone(), &network, &config.relayer, None)
+        .await
+        .unwrap();
+    let report = relayer.process_pending_transactions().await.unwrap();
+    assert_eq!(report.succeeded.len(), 1);
+
+    let transaction = sqlx::query!(
+        "SELECT status, tx_hash FROM l2_transactions WHERE deposit_id = $1",
+        deposit_id as i32
+    )
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(transaction.status, "completed");
+    assert!(transaction
+        .tx_hash
+        .is_some_and(|hash| hash.starts_with("0x")));
+}
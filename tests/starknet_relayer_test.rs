@@ -1,10 +1,15 @@
+#[path = "utils.rs"]
+mod utils;
+
 #[cfg(test)]
 mod tests {
+    use crate::utils::create_test_app;
     use mockall::mock;
     use mockall::predicate::*;
-    use sqlx::{Pool, Postgres};
+    use sqlx::{types::BigDecimal, Pool, Postgres};
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
+    use zeroxbridge_sequencer::config::{NetworkId, SignerConfig, StarknetNonceConfig};
     use zeroxbridge_sequencer::queue::l2_queue::L2Transaction;
     use zeroxbridge_sequencer::relayer::starknet_relayer::StarknetRelayer;
     use zeroxbridge_sequencer::relayer::starknet_relayer::StarknetRelayerConfig;
@@ -19,14 +24,7 @@ mod tests {
 
     // Helper function to create a test database pool
     async fn create_test_db_pool() -> Pool<Postgres> {
-        let database_url = std::env::var("DATABASE_URL")
-            .expect("DATABASE_URL environment variable must be set for tests");
-
-        sqlx::postgres::PgPoolOptions::new()
-            .max_connections(5)
-            .connect(&database_url)
-            .await
-            .expect("Failed to connect to database")
+        create_test_app().await.db.clone()
     }
 
     // Helper function to create sample L2Transaction
@@ -34,7 +32,7 @@ mod tests {
         L2Transaction {
             id: 1,
             stark_pub_key: "0x1234567890".to_string(),
-            amount: 1000000000000000000,
+            amount: BigDecimal::from(1_000_000_000_000_000_000u64),
             token_address: "0xabcdef1234567890".to_string(),
             status: "ready_for_relay".to_string(),
             created_at: chrono::Utc::now(),
@@ -49,6 +47,8 @@ mod tests {
             }"#
                 .to_string(),
             ),
+            network: NetworkId::default().to_string(),
+            deposit_id: None,
         }
     }
 
@@ -56,11 +56,18 @@ mod tests {
         StarknetRelayerConfig {
             bridge_contract_address: "0x1234567890abcdef".to_string(),
             rpc_url: "http://localhost:8545".to_string(),
+            chain_id: "0x534e5f4d41494e".to_string(),
             private_key: "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890"
                 .to_string(),
+            signer: SignerConfig::default(),
             max_retries: 3,
             retry_delay_ms: 1000,
             transaction_timeout_ms: 30000,
+            max_calls_per_tx: 1,
+            enable_dry_run: false,
+            verify_effects: false,
+            max_fee_per_tx: None,
+            nonce: StarknetNonceConfig::default(),
             account_address: "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890"
                 .to_string(),
         }
@@ -72,7 +79,7 @@ mod tests {
         let pool = create_test_db_pool().await;
         // Create relayer config
 
-        let mock_provider = MockStarknetProvider::new();
+        let _mock_provider = MockStarknetProvider::new();
         // configure mock_provider expectations...
 
         // Insert a test transaction
@@ -103,7 +110,7 @@ mod tests {
         .await
         .expect("Failed to insert test transaction");
 
-        let relayer = StarknetRelayer::new(pool.clone(), config)
+        let relayer = StarknetRelayer::new(pool.clone(), NetworkId::default(), config)
             .await
             .expect("Failed to create relayer");
 
@@ -128,7 +135,7 @@ mod tests {
         let config = create_sample_config();
         let pool = create_test_db_pool().await;
 
-        let relayer = StarknetRelayer::new(pool.clone(), config)
+        let relayer = StarknetRelayer::new(pool.clone(), NetworkId::default(), config)
             .await
             .expect("Failed to create relayer");
         // Create a mock provider
@@ -174,7 +181,7 @@ mod tests {
         let mut mock_provider = MockStarknetProvider::new();
         // configure mock_provider expectations...
 
-        let mock_relayer = StarknetRelayer::new(pool.clone(), config)
+        let mock_relayer = StarknetRelayer::new(pool.clone(), NetworkId::default(), config)
             .await
             .expect("Failed to create relayer");
 
@@ -224,7 +231,7 @@ mod tests {
         let pool = create_test_db_pool().await;
 
         // Create relayer config
-        let mock_relayer = StarknetRelayer::new(pool.clone(), config)
+        let mock_relayer = StarknetRelayer::new(pool.clone(), NetworkId::default(), config)
             .await
             .expect("Failed to create relayer");
 
@@ -0,0 +1,121 @@
+#[path = "utils.rs"]
+mod utils;
+
+use sqlx::{types::BigDecimal, PgPool};
+use std::time::{Duration, Instant};
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::db::database::{insert_deposit, update_deposit_status};
+use zeroxbridge_sequencer::events::status_notifications::{
+    StatusWakeup, Wakeup, DEPOSITS_STATUS_CHANNEL, L2_TRANSACTIONS_STATUS_CHANNEL,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A status no other test moves rows to, so only this test's notifications match
+fn unique_status() -> String {
+    format!("NOTIFY_{}", Uuid::new_v4().simple())
+}
+
+async fn create_deposit(pool: &PgPool) -> i32 {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    insert_deposit(pool, "0xnotify", &BigDecimal::from(1000), &commitment_hash)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_status_change_wakes_the_loop_before_the_poll_interval() {
+    let app = create_test_app().await;
+    let status = unique_status();
+    let id = create_deposit(&app.db).await;
+    let mut wakeup = StatusWakeup::listen(&app.db, DEPOSITS_STATUS_CHANNEL, &status).await;
+
+    let pool = app.db.clone();
+    let moved_status = status.clone();
+    tokio::spawn(async move {
+        let mut conn = pool.acquire().await.unwrap();
+        update_deposit_status(&mut conn, id, &moved_status)
+            .await
+            .unwrap();
+    });
+
+    let started = Instant::now();
+    assert_eq!(
+        wakeup.wait(POLL_INTERVAL).await,
+        Wakeup::Notified(vec![i64::from(id)])
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_burst_of_status_changes_is_coalesced() {
+    let app = create_test_app().await;
+    let status = unique_status();
+    let mut ids = Vec::new();
+    for _ in 0..5 {
+        ids.push(create_deposit(&app.db).await);
+    }
+    let mut wakeup = StatusWakeup::listen(&app.db, DEPOSITS_STATUS_CHANNEL, &status).await;
+
+    // Notifications are delivered together when the transaction commits
+    let mut tx = app.db.begin().await.unwrap();
+    for id in &ids {
+        update_deposit_status(&mut tx, *id, &status).await.unwrap();
+    }
+    tx.commit().await.unwrap();
+
+    let expected = ids.iter().map(|id| i64::from(*id)).collect();
+    assert_eq!(wakeup.wait(POLL_INTERVAL).await, Wakeup::Notified(expected));
+    // Nothing is left over to wake the loop a second time
+    assert_eq!(wakeup.wait(Duration::from_millis(300)).await, Wakeup::Tick);
+}
+
+#[tokio::test]
+async fn test_ignored_transitions_wait_for_the_poll() {
+    let app = create_test_app().await;
+    let status = unique_status();
+    let retrying = format!("{}_RETRYING", status);
+    let mut wakeup = StatusWakeup::listen(&app.db, L2_TRANSACTIONS_STATUS_CHANNEL, &status)
+        .await
+        .ignoring_transitions_from(&retrying);
+
+    let retried = sqlx::query_scalar!(
+        r#"
+        INSERT INTO l2_transactions (stark_pub_key, amount, token_address, status)
+        VALUES ('0xnotify', 1000, '0xtoken', $1)
+        RETURNING id
+        "#,
+        retrying
+    )
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE l2_transactions SET status = $2 WHERE id = $1",
+        retried,
+        status
+    )
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    assert_eq!(wakeup.wait(Duration::from_millis(300)).await, Wakeup::Tick);
+
+    // Newly inserted rows have no previous status and always wake the loop
+    let inserted = sqlx::query_scalar!(
+        r#"
+        INSERT INTO l2_transactions (stark_pub_key, amount, token_address, status)
+        VALUES ('0xnotify', 1000, '0xtoken', $1)
+        RETURNING id
+        "#,
+        status
+    )
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(
+        wakeup.wait(POLL_INTERVAL).await,
+        Wakeup::Notified(vec![inserted])
+    );
+}
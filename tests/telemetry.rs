@@ -0,0 +1,173 @@
+#[path = "utils.rs"]
+mod utils;
+
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use mockito::{mock, Matcher};
+use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+use sqlx::types::BigDecimal;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use utils::{create_test_app, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::config::{
+    NetworkId, SignerConfig, StarknetNonceConfig, TelemetryConfig,
+};
+use zeroxbridge_sequencer::db::database::{
+    upsert_deposit, Deposit, DEPOSIT_STATUS_PENDING_PROOF_GENERATION,
+};
+use zeroxbridge_sequencer::proof_client::service::{
+    DepositProver, ProofClientService, ProofGenerationError,
+};
+use zeroxbridge_sequencer::queue::l2_queue::L2Transaction;
+use zeroxbridge_sequencer::relayer::starknet_relayer::{StarknetRelayer, StarknetRelayerConfig};
+use zeroxbridge_sequencer::telemetry::{telemetry_layer, tracer_provider_builder};
+
+const SN_MAIN_HEX: &str = "0x534e5f4d41494e";
+
+/// Prints exported spans to stdout, keeping their names to check
+#[derive(Debug)]
+struct RecordingExporter {
+    names: Arc<Mutex<Vec<String>>>,
+    stdout: opentelemetry_stdout::SpanExporter,
+}
+
+impl SpanExporter for RecordingExporter {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        self.names
+            .lock()
+            .unwrap()
+            .extend(batch.iter().map(|span| span.name.to_string()));
+        self.stdout.export(batch)
+    }
+}
+
+struct InstantProver;
+
+#[async_trait]
+impl DepositProver for InstantProver {
+    async fn prove(&self, _deposit: &Deposit) -> Result<(), ProofGenerationError> {
+        Ok(())
+    }
+}
+
+fn relayer_config(rpc_url: String) -> StarknetRelayerConfig {
+    StarknetRelayerConfig {
+        bridge_contract_address: "0x1234".to_string(),
+        rpc_url,
+        chain_id: SN_MAIN_HEX.to_string(),
+        account_address: "0xabcdef".to_string(),
+        private_key: "0x1234567890abcdef".to_string(),
+        signer: SignerConfig::default(),
+        max_retries: 1,
+        retry_delay_ms: 10,
+        transaction_timeout_ms: 1000,
+        max_calls_per_tx: 1,
+        enable_dry_run: false,
+        verify_effects: false,
+        max_fee_per_tx: None,
+        nonce: StarknetNonceConfig {
+            managed: false,
+            ..StarknetNonceConfig::default()
+        },
+    }
+}
+
+fn l2_transaction(network: &NetworkId) -> L2Transaction {
+    L2Transaction {
+        id: 1,
+        stark_pub_key: "0xtelemetry".to_string(),
+        amount: BigDecimal::from(1000),
+        token_address: "0x0".to_string(),
+        status: "processing".to_string(),
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+        retry_count: 0,
+        tx_hash: None,
+        error: None,
+        proof_data: None,
+        network: network.to_string(),
+        deposit_id: None,
+    }
+}
+
+// Spans are recorded by the subscriber of the test's thread, so everything has to run
+// on it
+#[tokio::test(flavor = "current_thread")]
+async fn test_instrumented_spans_are_exported_by_name() {
+    let app = create_test_app().await;
+    let network = unique_network("telemetry");
+
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let telemetry = TelemetryConfig {
+        otlp_endpoint: "http://localhost:4317".to_string(),
+        service_name: "zeroxbridge-sequencer-test".to_string(),
+        sampling_ratio: 1.0,
+    };
+    let provider = tracer_provider_builder(&telemetry)
+        .with_simple_exporter(RecordingExporter {
+            names: names.clone(),
+            stdout: opentelemetry_stdout::SpanExporter::default(),
+        })
+        .build();
+    let _subscriber = tracing_subscriber::registry()
+        .with(telemetry_layer(&provider))
+        .set_default();
+
+    upsert_deposit(
+        &app.db,
+        &network,
+        "0xtelemetry",
+        &BigDecimal::from(1000),
+        &format!("0x{}", Uuid::new_v4().simple()),
+        DEPOSIT_STATUS_PENDING_PROOF_GENERATION,
+        None,
+    )
+    .await
+    .unwrap();
+    let proof_client = ProofClientService::with_prover(
+        app.db.clone(),
+        network.clone(),
+        app.config.queue.clone(),
+        "telemetry-worker",
+        Arc::new(InstantProver),
+    );
+    assert_eq!(proof_client.process_pending_deposits().await.unwrap(), 1);
+
+    let _chain_id = mock("POST", "/telemetry-rpc")
+        .match_body(Matcher::Regex("starknet_chainId".to_string()))
+        .with_header("content-type", "application/json")
+        .with_body(format!(
+            r#"{{"jsonrpc":"2.0","id":1,"result":"{}"}}"#,
+            SN_MAIN_HEX
+        ))
+        .create();
+    let relayer = StarknetRelayer::new(
+        app.db.clone(),
+        network.clone(),
+        relayer_config(format!("{}/telemetry-rpc", mockito::server_url())),
+    )
+    .await
+    .unwrap();
+    // Fails before anything is sent, the span is exported all the same
+    assert!(relayer
+        .relay_to_starknet(&l2_transaction(&network), "not a proof")
+        .await
+        .is_err());
+
+    for result in provider.force_flush() {
+        result.unwrap();
+    }
+    let names = names.lock().unwrap().clone();
+    assert!(
+        names.contains(&"process_single_deposit".to_string()),
+        "{:?}",
+        names
+    );
+    assert!(
+        names.contains(&"relay_to_starknet".to_string()),
+        "{:?}",
+        names
+    );
+}
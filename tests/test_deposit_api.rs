@@ -1,8 +1,9 @@
-#[path = "utils.rs"]
-mod utils;
+#[path = "harness/mod.rs"]
+mod harness;
 
+use harness::utils::random_commitment;
 use serde_json::json;
-use zeroxbridge_sequencer::api::routes::create_router;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -10,13 +11,13 @@ mod tests {
         body::Body,
         http::{Request, StatusCode},
     };
+    use harness::Harness;
     use tower::ServiceExt;
-    use utils::create_test_app;
 
     #[tokio::test]
     async fn test_fetch_user_latest_deposit_handler_success() {
-        let app = create_test_app().await;
-        let router = create_router(app.db.clone());
+        let harness = Harness::new().launch().await;
+        let router = harness.router();
 
         let stark_pub_key = "0x1111111111111111111111111111111111111111";
 
@@ -29,7 +30,7 @@ mod tests {
                 json!({
                     "stark_pub_key": stark_pub_key.to_string(),
                     "amount": 500,
-                    "commitment_hash": stark_pub_key.to_string()
+                    "commitment_hash": random_commitment()
                 })
                 .to_string(),
             ))
@@ -51,8 +52,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_fetch_user_latest_deposit_with_user_address_handler() {
-        let app = create_test_app().await;
-        let router = create_router(app.db.clone());
+        let harness = Harness::new().launch().await;
+        let router = harness.router();
 
         let stark_pub_key = "0x1111111111111111111111111111111111111111";
         let user_address = "0x1111111111111111111111111111111111111111";
@@ -66,7 +67,7 @@ mod tests {
                 json!({
                     "stark_pub_key": stark_pub_key.to_string(),
                     "amount": 500,
-                    "commitment_hash": user_address.to_string()
+                    "commitment_hash": random_commitment()
                 })
                 .to_string(),
             ))
@@ -88,8 +89,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_user_address_not_found_handler() {
-        let app = create_test_app().await;
-        let router = create_router(app.db.clone());
+        let harness = Harness::new().launch().await;
+        let router = harness.router();
         let user_address = "0x1111111111111111111111111111111111111qqqq";
 
         let request = Request::builder()
@@ -106,8 +107,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_user_address_for_all_deposits() {
-        let app = create_test_app().await;
-        let router = create_router(app.db.clone());
+        let harness = Harness::new().launch().await;
+        let router = harness.router();
         let user_address = "0x1111111111111111111111111111111111111111";
 
         let request = Request::builder()
@@ -0,0 +1,149 @@
+use serde_json::{json, Value};
+use starknet::core::types::Felt;
+use starknet::signers::{Signer, SigningKey};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixListener;
+use zeroxbridge_sequencer::relayer::signer::{
+    AccountSigner, ExternalSigner, LocalSigner, SignerError, TransactionSigner,
+};
+
+const PRIVATE_KEY: &str = "0x0139fe4d6f02e666e86a6f58e65060f115cd3c185bd9e98bd829636931458f79";
+const ACCOUNT_ADDRESS: &str = "0x4a1b2c3d";
+const TX_HASH: &str = "0x06fea80189363a786037ed3e7ba546dad0ef7de49fccae0e31eb658b7dd4ea76";
+
+fn address() -> Felt {
+    Felt::from_hex(ACCOUNT_ADDRESS).unwrap()
+}
+
+fn tx_hash() -> Felt {
+    Felt::from_hex(TX_HASH).unwrap()
+}
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_secret_scalar(Felt::from_hex(PRIVATE_KEY).unwrap())
+}
+
+/// Serves the external signer protocol for `key` on a socket in `dir`, answering every
+/// request with `error` instead when one is given
+fn serve_signer(dir: &Path, key: SigningKey, error: Option<(i64, &'static str)>) -> PathBuf {
+    let path = dir.join("signer.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(read).read_line(&mut line).await.unwrap();
+
+            let request: Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(request["jsonrpc"], "2.0");
+            assert_eq!(
+                Felt::from_hex(request["params"]["address"].as_str().unwrap()).unwrap(),
+                address()
+            );
+            let result = match request["method"].as_str().unwrap() {
+                "starknet_signHash" => {
+                    let hash = Felt::from_hex(request["params"]["hash"].as_str().unwrap());
+                    let signature = key.sign(&hash.unwrap()).unwrap();
+                    json!({ "r": signature.r, "s": signature.s })
+                }
+                "starknet_getPublicKey" => json!(key.verifying_key().scalar()),
+                method => panic!("unexpected method {}", method),
+            };
+            let response = match error {
+                Some((code, message)) => json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": { "code": code, "message": message },
+                }),
+                None => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+            };
+            write
+                .write_all(format!("{}\n", response).as_bytes())
+                .await
+                .unwrap();
+        }
+    });
+    path
+}
+
+async fn assert_valid_signature(signer: &dyn TransactionSigner) {
+    let signature = signer.sign_hash(tx_hash()).await.unwrap();
+    let public_key = signer.public_key().await.unwrap();
+    assert!(starknet_crypto::verify(&public_key, &tx_hash(), &signature.r, &signature.s).unwrap());
+}
+
+#[tokio::test]
+async fn test_local_signer_signatures_verify_against_its_public_key() {
+    let signer = LocalSigner::from_hex(PRIVATE_KEY, address()).unwrap();
+
+    assert_eq!(signer.address(), address());
+    assert_eq!(
+        signer.public_key().await.unwrap(),
+        signing_key().verifying_key().scalar()
+    );
+    assert_valid_signature(&signer).await;
+}
+
+#[test]
+fn test_local_signer_rejects_invalid_keys() {
+    for key in ["0xnothex", "0x0"] {
+        assert!(matches!(
+            LocalSigner::from_hex(key, address()),
+            Err(SignerError::InvalidPrivateKey(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_external_signer_round_trips_signatures() {
+    let dir = TempDir::new().unwrap();
+    let signer = ExternalSigner::new(serve_signer(dir.path(), signing_key(), None), address());
+    let local = LocalSigner::new(signing_key(), address());
+
+    assert_valid_signature(&signer).await;
+    // Signatures are deterministic, so the daemon's are the ones signed locally
+    let signature = signer.sign_hash(tx_hash()).await.unwrap();
+    let expected = local.sign_hash(tx_hash()).await.unwrap();
+    assert_eq!((signature.r, signature.s), (expected.r, expected.s));
+    assert_eq!(
+        signer.public_key().await.unwrap(),
+        local.public_key().await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_external_signer_errors_are_returned() {
+    let dir = TempDir::new().unwrap();
+    let socket_path = serve_signer(dir.path(), signing_key(), Some((-32000, "key locked")));
+    let signer = ExternalSigner::new(socket_path, address());
+
+    match signer.sign_hash(tx_hash()).await {
+        Err(SignerError::External { code, message }) => {
+            assert_eq!(code, -32000);
+            assert_eq!(message, "key locked");
+        }
+        other => panic!("expected an external signer error, got {:?}", other),
+    }
+
+    let missing = ExternalSigner::new(dir.path().join("missing.sock"), address());
+    assert!(matches!(
+        missing.sign_hash(tx_hash()).await,
+        Err(SignerError::Io(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_accounts_sign_through_the_configured_backend() {
+    let dir = TempDir::new().unwrap();
+    let external = ExternalSigner::new(serve_signer(dir.path(), signing_key(), None), address());
+    let signer = AccountSigner(Arc::new(external));
+
+    let signature = Signer::sign_hash(&signer, &tx_hash()).await.unwrap();
+    let public_key = signer.get_public_key().await.unwrap();
+    assert_eq!(public_key.scalar(), signing_key().verifying_key().scalar());
+    assert!(public_key.verify(&tx_hash(), &signature).unwrap());
+}
@@ -0,0 +1,113 @@
+#[path = "utils.rs"]
+mod utils;
+
+use sqlx::{types::BigDecimal, PgPool};
+use utils::{append_on_l1, create_test_app, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::upsert_deposit;
+use zeroxbridge_sequencer::tree_builder::{TreeBuilderClient, TreeBuilderConfig};
+
+fn random_commitment() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn client(pool: &PgPool, network: &NetworkId) -> TreeBuilderClient {
+    TreeBuilderClient::new(pool.clone(), network.clone(), TreeBuilderConfig::default())
+}
+
+async fn queue(pool: &PgPool, network: &NetworkId, commitment_hash: &str) {
+    upsert_deposit(
+        pool,
+        network,
+        "0xdedup",
+        &BigDecimal::from(1000),
+        commitment_hash,
+        "PENDING_TREE_INCLUSION",
+        None,
+    )
+    .await
+    .unwrap();
+}
+
+async fn status(pool: &PgPool, commitment_hash: &str) -> String {
+    sqlx::query_scalar!(
+        "SELECT status FROM deposits WHERE commitment_hash = $1",
+        commitment_hash
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+async fn leaf_count(client: &TreeBuilderClient) -> usize {
+    client.tree().read().await.leaf_count().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_duplicate_in_the_queue_is_appended_once() {
+    let app = create_test_app().await;
+    let network = unique_network("dedup");
+    let commitment = random_commitment();
+    // Distinct rows for the unique index, the same leaf for the tree
+    let original = format!("0x{}", commitment);
+    let duplicate = commitment.to_uppercase();
+    append_on_l1(&app.db, &network, &original, 0).await;
+    queue(&app.db, &network, &original).await;
+    queue(&app.db, &network, &duplicate).await;
+
+    let builder = client(&app.db, &network);
+    builder.rebuild_tree_on_startup().await.unwrap();
+    assert_eq!(builder.process_pending_deposits().await.unwrap(), 1);
+
+    assert_eq!(leaf_count(&builder).await, 1);
+    assert_eq!(status(&app.db, &original).await, "PENDING_PROOF_GENERATION");
+    assert_eq!(status(&app.db, &duplicate).await, "DUPLICATE_COMMITMENT");
+    // Flagged deposits leave the queue
+    assert_eq!(builder.process_pending_deposits().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_commitments_of_the_rebuilt_tree_are_not_appended_again() {
+    let app = create_test_app().await;
+    let network = unique_network("dedup");
+    let commitment = random_commitment();
+    let original = format!("0x{}", commitment);
+    append_on_l1(&app.db, &network, &original, 0).await;
+    queue(&app.db, &network, &original).await;
+    client(&app.db, &network)
+        .process_pending_deposits()
+        .await
+        .unwrap();
+
+    let restarted = client(&app.db, &network);
+    assert_eq!(restarted.rebuild_tree_on_startup().await.unwrap(), 1);
+    queue(&app.db, &network, &commitment).await;
+
+    assert_eq!(restarted.process_pending_deposits().await.unwrap(), 0);
+    assert_eq!(leaf_count(&restarted).await, 1);
+    assert_eq!(status(&app.db, &commitment).await, "DUPLICATE_COMMITMENT");
+}
+
+#[tokio::test]
+async fn test_commitment_included_by_another_builder_is_flagged() {
+    let app = create_test_app().await;
+    let network = unique_network("dedup");
+    let commitment = random_commitment();
+    let original = format!("0x{}", commitment);
+    append_on_l1(&app.db, &network, &original, 0).await;
+    queue(&app.db, &network, &original).await;
+    // Started before the deposit was included, so only the database knows about it
+    let stale = client(&app.db, &network);
+    stale.rebuild_tree_on_startup().await.unwrap();
+    client(&app.db, &network)
+        .process_pending_deposits()
+        .await
+        .unwrap();
+
+    queue(&app.db, &network, &commitment).await;
+
+    assert_eq!(stale.process_pending_deposits().await.unwrap(), 0);
+    assert_eq!(leaf_count(&stale).await, 0);
+    assert_eq!(status(&app.db, &commitment).await, "DUPLICATE_COMMITMENT");
+}
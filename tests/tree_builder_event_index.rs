@@ -0,0 +1,111 @@
+#[path = "utils.rs"]
+mod utils;
+
+use sqlx::{types::BigDecimal, PgPool};
+use utils::{append_on_l1, create_test_app, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::upsert_deposit;
+use zeroxbridge_sequencer::tree_builder::{TreeBuilderClient, TreeBuilderConfig};
+
+fn commitment_hash() -> String {
+    format!("0x{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn client(pool: &PgPool, network: &NetworkId) -> TreeBuilderClient {
+    TreeBuilderClient::new(pool.clone(), network.clone(), TreeBuilderConfig::default())
+}
+
+async fn queue(pool: &PgPool, network: &NetworkId) -> String {
+    let commitment_hash = commitment_hash();
+    upsert_deposit(
+        pool,
+        network,
+        "0xeventindex",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+        "PENDING_TREE_INCLUSION",
+        None,
+    )
+    .await
+    .unwrap();
+    commitment_hash
+}
+
+async fn leaf_index(pool: &PgPool, commitment_hash: &str) -> Option<i64> {
+    sqlx::query_scalar!(
+        "SELECT leaf_index FROM deposits WHERE commitment_hash = $1",
+        commitment_hash
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_leaves_follow_the_event_index_not_the_submission_order() {
+    let app = create_test_app().await;
+    let network = unique_network("event-index");
+    let first_submitted = queue(&app.db, &network).await;
+    let second_submitted = queue(&app.db, &network).await;
+    append_on_l1(&app.db, &network, &second_submitted, 0).await;
+    append_on_l1(&app.db, &network, &first_submitted, 1).await;
+
+    let builder = client(&app.db, &network);
+    assert_eq!(builder.process_pending_deposits().await.unwrap(), 2);
+
+    assert_eq!(leaf_index(&app.db, &second_submitted).await, Some(0));
+    assert_eq!(leaf_index(&app.db, &first_submitted).await, Some(1));
+}
+
+#[tokio::test]
+async fn test_deposits_past_a_gap_wait_for_it_to_fill() {
+    let app = create_test_app().await;
+    let network = unique_network("event-index");
+    let commitments = [
+        queue(&app.db, &network).await,
+        queue(&app.db, &network).await,
+        queue(&app.db, &network).await,
+    ];
+    // The events of indexes 1 and 2 are ingested before the one of index 0
+    append_on_l1(&app.db, &network, &commitments[2], 2).await;
+    append_on_l1(&app.db, &network, &commitments[1], 1).await;
+
+    let builder = client(&app.db, &network);
+    assert_eq!(builder.process_pending_deposits().await.unwrap(), 0);
+    assert_eq!(builder.tree().read().await.leaf_count().await.unwrap(), 0);
+    assert_eq!(leaf_index(&app.db, &commitments[1]).await, None);
+
+    append_on_l1(&app.db, &network, &commitments[0], 0).await;
+
+    assert_eq!(builder.process_pending_deposits().await.unwrap(), 3);
+    for (index, commitment_hash) in commitments.iter().enumerate() {
+        assert_eq!(
+            leaf_index(&app.db, commitment_hash).await,
+            Some(index as i64)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_deposits_without_an_event_stay_queued() {
+    let app = create_test_app().await;
+    let network = unique_network("event-index");
+    let unseen = queue(&app.db, &network).await;
+    let seen = queue(&app.db, &network).await;
+    append_on_l1(&app.db, &network, &seen, 0).await;
+
+    let builder = client(&app.db, &network);
+    assert_eq!(builder.process_pending_deposits().await.unwrap(), 1);
+    assert_eq!(leaf_index(&app.db, &seen).await, Some(0));
+    assert_eq!(leaf_index(&app.db, &unseen).await, None);
+
+    let status: String = sqlx::query_scalar!(
+        "SELECT status FROM deposits WHERE commitment_hash = $1",
+        unseen
+    )
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(status, "PENDING_TREE_INCLUSION");
+}
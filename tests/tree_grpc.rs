@@ -0,0 +1,208 @@
+#![cfg(feature = "grpc")]
+
+#[path = "utils.rs"]
+mod utils;
+
+use sqlx::{types::BigDecimal, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tonic::transport::Channel;
+use tonic::{Code, Streaming};
+use utils::{create_test_app, unique_network};
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::TreeState;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{
+    insert_deposit_hash_event, upsert_deposit, DepositHashAppended,
+};
+use zeroxbridge_sequencer::tree_builder::grpc::proto::tree_service_client::TreeServiceClient;
+use zeroxbridge_sequencer::tree_builder::grpc::proto::{
+    GetProofAtCountRequest, GetProofRequest, GetRootRequest, RootResponse, WatchRootsRequest,
+};
+use zeroxbridge_sequencer::tree_builder::grpc::{serve_with_listener, TreeGrpcService};
+use zeroxbridge_sequencer::tree_builder::{TreeBuilderClient, TreeBuilderConfig};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Starts the tree builder of `network` and a gRPC server on a free local port serving
+/// its tree, and connects a client to it
+async fn start(
+    pool: &PgPool,
+    network: &NetworkId,
+) -> (Arc<TreeBuilderClient>, TreeServiceClient<Channel>) {
+    let builder = Arc::new(TreeBuilderClient::new(
+        pool.clone(),
+        network.clone(),
+        TreeBuilderConfig {
+            process_interval_sec: POLL_INTERVAL.as_secs(),
+            ..TreeBuilderConfig::default()
+        },
+    ));
+    let tree_state =
+        TreeState::new(builder.tree(), builder.proof_cache()).with_roots(builder.root_feed());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_with_listener(
+        listener,
+        TreeGrpcService::new(vec![(network.clone(), tree_state)]),
+    ));
+
+    let running = builder.clone();
+    tokio::spawn(async move { running.start().await });
+
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    (builder, TreeServiceClient::new(channel))
+}
+
+/// Queues a deposit for tree inclusion whose L1 event appended it at `index`
+async fn deposit(pool: &PgPool, network: &NetworkId, index: i64) -> String {
+    let commitment_hash = format!("0x{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let event = DepositHashAppended {
+        id: 0,
+        index,
+        commitment_hash: hex::decode(&commitment_hash[2..]).unwrap(),
+        root_hash: vec![0; 32],
+        elements_count: index + 1,
+        block_number: 100 + index,
+        created_at: None,
+        updated_at: None,
+    };
+    insert_deposit_hash_event(pool, network, &event)
+        .await
+        .unwrap();
+    upsert_deposit(
+        pool,
+        network,
+        "0xgrpc",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+        "PENDING_TREE_INCLUSION",
+        None,
+    )
+    .await
+    .unwrap();
+    commitment_hash
+}
+
+async fn next_root(roots: &mut Streaming<RootResponse>) -> RootResponse {
+    // The tree builder is woken by the deposit, at the latest after one poll
+    tokio::time::timeout(2 * POLL_INTERVAL, roots.message())
+        .await
+        .expect("no root within the poll interval")
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_proofs_and_roots_follow_the_tree_builder() {
+    let app = create_test_app().await;
+    let network = unique_network("grpc");
+    let (builder, mut client) = start(&app.db, &network).await;
+    let mut roots = client
+        .watch_roots(WatchRootsRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+
+    let first = deposit(&app.db, &network, 0).await;
+    let root = next_root(&mut roots).await;
+    assert_eq!(root.leaf_count, 1);
+
+    let proof = client
+        .get_proof(GetProofRequest {
+            network: network.as_str().to_string(),
+            commitment_hash: first.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let (leaf_index, merkle_root) = sqlx::query!(
+        "SELECT leaf_index, merkle_root FROM deposits WHERE commitment_hash = $1",
+        first
+    )
+    .fetch_one(&app.db)
+    .await
+    .map(|row| (row.leaf_index, row.merkle_root))
+    .unwrap();
+    assert_eq!(leaf_index, Some(0));
+    assert_eq!(merkle_root.as_deref(), Some(proof.root.as_str()));
+    assert_eq!(proof.root, root.root);
+    assert_eq!(proof.commitment_hash, first);
+    assert_eq!(proof.elements_count, root.elements_count);
+
+    deposit(&app.db, &network, 1).await;
+    let newer = next_root(&mut roots).await;
+    assert_eq!(newer.leaf_count, 2);
+    let current = client
+        .get_root(GetRootRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(current, newer);
+
+    // Proofs against the earlier root stay available
+    let earlier = client
+        .get_proof_at_count(GetProofAtCountRequest {
+            network: String::new(),
+            commitment_hash: first,
+            elements_count: root.elements_count,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(earlier, proof);
+
+    builder.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_errors_map_to_grpc_codes() {
+    let app = create_test_app().await;
+    let network = unique_network("grpc");
+    let (builder, mut client) = start(&app.db, &network).await;
+    let mut roots = client
+        .watch_roots(WatchRootsRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    let commitment_hash = deposit(&app.db, &network, 0).await;
+    let root = next_root(&mut roots).await;
+
+    let proof = |commitment_hash: &str| GetProofRequest {
+        network: String::new(),
+        commitment_hash: commitment_hash.to_string(),
+    };
+    let code = client.get_proof(proof("0x1234")).await.unwrap_err().code();
+    assert_eq!(code, Code::InvalidArgument);
+    let missing = format!("0x{}", "ab".repeat(32));
+    let code = client.get_proof(proof(&missing)).await.unwrap_err().code();
+    assert_eq!(code, Code::NotFound);
+
+    let code = client
+        .get_proof_at_count(GetProofAtCountRequest {
+            network: String::new(),
+            commitment_hash,
+            elements_count: root.elements_count + 1,
+        })
+        .await
+        .unwrap_err()
+        .code();
+    assert_eq!(code, Code::OutOfRange);
+
+    let code = client
+        .get_root(GetRootRequest {
+            network: "unknown".to_string(),
+        })
+        .await
+        .unwrap_err()
+        .code();
+    assert_eq!(code, Code::InvalidArgument);
+
+    builder.stop().await.unwrap();
+}
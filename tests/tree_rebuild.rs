@@ -0,0 +1,253 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode, Uri},
+};
+use ethers::signers::LocalWallet;
+use ethers::types::H256;
+use serde_json::{json, Value};
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::{append_on_l1, create_test_app, unique_network, ADMIN_KEYS};
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::auth::{admin_request_hash, ADMIN_SIGNATURE_HEADERS};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{
+    fetch_tree_rebuild_checkpoint, get_root_at, upsert_deposit,
+};
+use zeroxbridge_sequencer::tree_builder::{
+    rebuild_tree, DiscrepancyField, TreeBuilderClient, TreeBuilderConfig, TreeRebuild,
+    TreeRebuildOptions,
+};
+
+const CORRUPTED_ROOT: &str = "0xbad";
+
+/// Includes `count` new deposits in `network`'s tree, returning their ids in leaf order
+async fn seed_tree(pool: &PgPool, network: &NetworkId, count: usize) -> Vec<i32> {
+    let mut ids = Vec::new();
+    for index in 0..count {
+        let commitment_hash = format!("0x{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        upsert_deposit(
+            pool,
+            network,
+            "0xrebuild",
+            &BigDecimal::from(1000),
+            &commitment_hash,
+            "PENDING_TREE_INCLUSION",
+            None,
+        )
+        .await
+        .unwrap();
+        append_on_l1(pool, network, &commitment_hash, index as i64).await;
+        let id = sqlx::query_scalar!(
+            "SELECT id FROM deposits WHERE commitment_hash = $1",
+            commitment_hash
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        ids.push(id);
+    }
+
+    let builder =
+        TreeBuilderClient::new(pool.clone(), network.clone(), TreeBuilderConfig::default());
+    assert_eq!(builder.process_pending_deposits().await.unwrap(), count);
+    ids
+}
+
+async fn merkle_root(pool: &PgPool, id: i32) -> Option<String> {
+    sqlx::query_scalar!("SELECT merkle_root FROM deposits WHERE id = $1", id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn corrupt(pool: &PgPool, id: i32) {
+    sqlx::query!(
+        "UPDATE deposits SET merkle_root = $2 WHERE id = $1",
+        id,
+        CORRUPTED_ROOT
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+fn options(fix: bool, batch_size: i64) -> TreeRebuildOptions {
+    TreeRebuildOptions { batch_size, fix }
+}
+
+#[tokio::test]
+async fn test_corrupted_root_is_reported_without_fix() {
+    let app = create_test_app().await;
+    let network = unique_network("rebuild");
+    let ids = seed_tree(&app.db, &network, 3).await;
+    let expected_root = merkle_root(&app.db, ids[1]).await.unwrap();
+    corrupt(&app.db, ids[1]).await;
+
+    let report = rebuild_tree(&app.db, &network, options(false, 2))
+        .await
+        .unwrap();
+
+    assert_eq!(report.leaves_checked, 3);
+    assert!(!report.fix);
+    assert_eq!(report.discrepancies.len(), 1);
+    let discrepancy = &report.discrepancies[0];
+    assert_eq!(discrepancy.deposit_id, ids[1]);
+    assert_eq!(discrepancy.field, DiscrepancyField::MerkleRoot);
+    assert_eq!(discrepancy.stored.as_deref(), Some(CORRUPTED_ROOT));
+    assert_eq!(discrepancy.recomputed, expected_root);
+    // Read-only: the row keeps its corrupted root
+    assert_eq!(
+        merkle_root(&app.db, ids[1]).await.as_deref(),
+        Some(CORRUPTED_ROOT)
+    );
+    assert_eq!(report.root, merkle_root(&app.db, ids[2]).await);
+}
+
+#[tokio::test]
+async fn test_corrupted_root_is_repaired_with_fix() {
+    let app = create_test_app().await;
+    let network = unique_network("rebuild");
+    let ids = seed_tree(&app.db, &network, 3).await;
+    let expected_root = merkle_root(&app.db, ids[1]).await.unwrap();
+    corrupt(&app.db, ids[1]).await;
+    // A lost root history entry is restored too
+    sqlx::query!(
+        "DELETE FROM merkle_roots WHERE network = $1 AND elements_count = 3",
+        network.as_str()
+    )
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let report = rebuild_tree(&app.db, &network, options(true, 2))
+        .await
+        .unwrap();
+
+    let fields: Vec<_> = report
+        .discrepancies
+        .iter()
+        .map(|discrepancy| (discrepancy.deposit_id, discrepancy.field))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            (ids[1], DiscrepancyField::MerkleRoot),
+            (ids[2], DiscrepancyField::LocalRoot),
+        ]
+    );
+    assert_eq!(merkle_root(&app.db, ids[1]).await, Some(expected_root));
+    let roots = get_root_at(&app.db, &network, 3).await.unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(Some(roots[0].root.clone()), report.root);
+
+    let report = rebuild_tree(&app.db, &network, options(true, 2))
+        .await
+        .unwrap();
+    assert!(report.discrepancies.is_empty());
+}
+
+#[tokio::test]
+async fn test_interrupted_rebuild_resumes_from_checkpoint() {
+    let app = create_test_app().await;
+    let network = unique_network("rebuild");
+    let ids = seed_tree(&app.db, &network, 3).await;
+    corrupt(&app.db, ids[0]).await;
+
+    let mut rebuild = TreeRebuild::resume(app.db.clone(), network.clone(), options(false, 1))
+        .await
+        .unwrap();
+    assert!(rebuild.step().await.unwrap());
+    drop(rebuild);
+
+    let checkpoint = fetch_tree_rebuild_checkpoint(&app.db, &network)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(checkpoint.leaves_checked, 1);
+    corrupt(&app.db, ids[1]).await;
+    // Only a rebuild ignoring the checkpoint would check the first deposit again
+    sqlx::query!(
+        "UPDATE deposits SET merkle_root = NULL WHERE id = $1",
+        ids[0]
+    )
+    .execute(&app.db)
+    .await
+    .unwrap();
+
+    let report = rebuild_tree(&app.db, &network, options(false, 1))
+        .await
+        .unwrap();
+    assert_eq!(report.leaves_checked, 3);
+    let found: Vec<_> = report
+        .discrepancies
+        .iter()
+        .map(|discrepancy| (discrepancy.deposit_id, discrepancy.stored.as_deref()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (ids[0], Some(CORRUPTED_ROOT)),
+            (ids[1], Some(CORRUPTED_ROOT))
+        ]
+    );
+    assert!(fetch_tree_rebuild_checkpoint(&app.db, &network)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+fn admin_request(body: Value) -> Request<Body> {
+    let uri = Uri::from_static("/admin/tree/rebuild");
+    let body = body.to_string();
+    let hash = H256::from(admin_request_hash(&Method::POST, &uri, body.as_bytes()));
+
+    let mut request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    for (header, key) in ADMIN_SIGNATURE_HEADERS.iter().zip(ADMIN_KEYS) {
+        let wallet: LocalWallet = key.parse().unwrap();
+        let signature = wallet.sign_hash(hash).unwrap();
+        request = request.header(*header, format!("0x{}", signature));
+    }
+    request.body(Body::from(body)).unwrap()
+}
+
+#[tokio::test]
+async fn test_rebuild_endpoint_rejects_empty_batches() {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+
+    let response = router
+        .oneshot(admin_request(json!({ "fix": true, "batch_size": 0 })))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "invalid_batch_size");
+}
+
+#[tokio::test]
+async fn test_rebuild_endpoint_requires_admin_signatures() {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/admin/tree/rebuild")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "fix": true }).to_string()))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -1,30 +1,79 @@
-use dotenv::dotenv;
+// Shared by every test binary, each using only some of the helpers
+#![allow(dead_code)]
+
+#[path = "harness/postgres.rs"]
+pub mod postgres;
+
 use sqlx::postgres::PgPoolOptions;
-use std::sync::Arc;
+use sqlx::PgPool;
+use std::sync::{mpsc, Arc, OnceLock};
+use std::thread;
+use uuid::Uuid;
 use zeroxbridge_sequencer::api::routes::AppState;
 use zeroxbridge_sequencer::config::{
-    AppConfig, ContractConfig, Contracts, DatabaseConfig, EthereumConfig, HerodotusConfig,
-    LoggingConfig, MerkleConfig, OracleConfig, QueueConfig, RelayerConfig, ServerConfig,
-    StarknetConfig,
+    AppConfig, ContractConfig, Contracts, DatabaseConfig, EthereumConfig, GasPriceConfig,
+    GrpcConfig, HerodotusConfig, JanitorConfig, L1QueueConfig, LimitsConfig, LoggingConfig,
+    MerkleConfig, NetworkId, OracleConfig, ProofConfig, ProverConfig, QueueConfig, RateLimitConfig,
+    RelayerConfig, ServerConfig, SignerConfig, SimulationConfig, StarknetConfig,
+    StarknetNonceConfig, WebhookConfig,
 };
+use zeroxbridge_sequencer::db::database::{insert_deposit_hash_event, DepositHashAppended};
+use zeroxbridge_sequencer::db::migrations::MIGRATOR;
+use zeroxbridge_sequencer::utils::parse_commitment_hash;
+
+use postgres::TestDatabase;
+
+// Keys of two of the admin addresses configured in `create_test_config`
+pub const ADMIN_KEYS: [&str; 2] = [
+    "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+];
 
+/// Connects to the test binary's shared database, with `create_test_config()` pointing
+/// at it
 pub async fn create_test_app() -> Arc<AppState> {
-    dotenv().ok();
-    let configuration = create_test_config();
-    let database_url = configuration.database.get_db_url();
+    let mut configuration = create_test_config();
+    configuration.database.url = Some(shared_database_url().to_string());
 
     let pool = PgPoolOptions::new()
         .max_connections(5)
-        .connect(&database_url)
+        .connect(&configuration.database.get_db_url())
         .await
         .expect("Failed to connect to test database");
 
-    let state = Arc::new(AppState {
+    Arc::new(AppState {
         db: pool.clone(),
         config: configuration.clone(),
-    });
+    })
+}
+
+/// URL of the Postgres shared by the tests of a binary, started in a harness container
+/// and migrated by the first test asking for it, so the suite needs nothing but Docker
+fn shared_database_url() -> &'static str {
+    static URL: OnceLock<String> = OnceLock::new();
 
-    state
+    URL.get_or_init(|| {
+        // Every test runs on its own runtime, so the container is started on a separate
+        // one rather than on the first test's
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let url = runtime.block_on(async {
+                let database = TestDatabase::start().await;
+                let pool = PgPool::connect(database.url())
+                    .await
+                    .expect("Failed to connect to the test database");
+                MIGRATOR
+                    .run(&pool)
+                    .await
+                    .expect("Failed to migrate the test database");
+                pool.close().await;
+                database.keep_until_exit()
+            });
+            sender.send(url).unwrap();
+        });
+        receiver.recv().expect("Failed to start the test database")
+    })
 }
 
 // Helper function to create test config
@@ -40,11 +89,33 @@ pub fn create_test_config() -> AppConfig {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             server_url: "http://localhost:8080".to_string(),
+            admin_token: Some("test-admin-token".to_string()),
+            admin_addresses: vec![
+                "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string(),
+                "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+                "0xdECfa5e071dec157f8CE4b98175A6eE5bD779398".to_string(),
+            ],
+            require_l1_verification: false,
+            verify_withdrawal_signatures: false,
+            rate_limit: RateLimitConfig::default(),
+            stats_cache_ttl_sec: 5,
+            max_body_bytes: 1024 * 1024,
+            request_timeout_ms: 30_000,
+            enable_docs: true,
+            check_hash_freshness: false,
+            hash_timestamp_skew_sec: 600,
+            max_event_streams: 1000,
+        },
+        database: DatabaseConfig {
+            max_connections: 5,
+            url: None,
+            connection_timeout_secs: 30,
+            idle_timeout_secs: 600,
         },
-        database: DatabaseConfig { max_connections: 5 },
         ethereum: EthereumConfig {
             chain_id: 11155111, // Sepolia testnet
             confirmations: 1,
+            rpc_url: None,
         },
         starknet: StarknetConfig {
             chain_id: "0x534e5f4d41494e".to_string(),
@@ -57,11 +128,21 @@ pub fn create_test_config() -> AppConfig {
             max_retries: Some(5),
             retry_delay_ms: Some(5000),
             transaction_timeout_ms: Some(300000),
+            max_calls_per_tx: None,
+            enable_dry_run: false,
+            max_fee_per_tx: None,
+            confirmations: 0,
+            rpc_url: None,
+            nonce: StarknetNonceConfig::default(),
+            signer: SignerConfig::default(),
         },
         relayer: RelayerConfig {
             max_retries: 3,
             retry_delay_seconds: 60,
             gas_limit: 300000,
+            verify_effects: false,
+            gas_price: GasPriceConfig::default(),
+            domain_separator: format!("0x{}", "11".repeat(32)),
         },
         queue: QueueConfig {
             process_interval_sec: 60,
@@ -70,7 +151,16 @@ pub fn create_test_config() -> AppConfig {
             initial_retry_delay_sec: 60,
             retry_delay_seconds: 60,
             merkle_update_confirmations: 1,
+            deposit_expiry_hours: 72,
         },
+        l1_queue: L1QueueConfig::default(),
+        prover: ProverConfig::default(),
+        proof: ProofConfig::default(),
+        janitor: JanitorConfig::default(),
+        limits: LimitsConfig::default(),
+        grpc: GrpcConfig::default(),
+        simulation: SimulationConfig::default(),
+        webhooks: WebhookConfig::default(),
         merkle: MerkleConfig {
             tree_depth: 32,
             cache_size: 1000,
@@ -79,12 +169,43 @@ pub fn create_test_config() -> AppConfig {
             level: "info".to_string(),
             file: "logs/zeroxbridge.log".to_string(),
         },
+        telemetry: None,
         oracle: OracleConfig {
             tolerance_percent: Some(0.01), // 1% tolerance
             polling_interval_seconds: 60,
         },
         herodotus: HerodotusConfig {
             herodotus_endpoint: "https://herodotus.example.com/api".to_string(),
+            ..HerodotusConfig::default()
         },
+        networks: Vec::new(),
     }
 }
+
+// Networks are named per test run, so rows and trackers of earlier runs never match
+pub fn unique_network(prefix: &str) -> NetworkId {
+    NetworkId::new(format!("{}-{}", prefix, Uuid::new_v4().simple()))
+}
+
+pub fn random_commitment() -> String {
+    // Zero-padded so the hash stays below the Stark prime. Uuid's own formatting ignores
+    // the fill, hence the string.
+    format!("0x{:0>64}", Uuid::new_v4().simple().to_string())
+}
+
+/// Records the `DepositHashAppended` event that appended `commitment_hash` at `index`
+pub async fn append_on_l1(pool: &PgPool, network: &NetworkId, commitment_hash: &str, index: i64) {
+    let event = DepositHashAppended {
+        id: 0,
+        index,
+        commitment_hash: parse_commitment_hash(commitment_hash).unwrap().to_vec(),
+        root_hash: vec![0; 32],
+        elements_count: index + 1,
+        block_number: 100 + index,
+        created_at: None,
+        updated_at: None,
+    };
+    insert_deposit_hash_event(pool, network, &event)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,379 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, Method, Request, StatusCode, Uri},
+    routing::post,
+    Router,
+};
+use ethers::signers::LocalWallet;
+use ethers::types::H256;
+use serde_json::{json, Value};
+use sqlx::{types::BigDecimal, PgPool};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+use utils::{create_test_app, random_commitment, ADMIN_KEYS};
+use zeroxbridge_sequencer::api::auth::{admin_request_hash, ADMIN_SIGNATURE_HEADERS};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::WebhookConfig;
+use zeroxbridge_sequencer::db::database::{
+    fetch_webhook_deliveries, insert_deposit, WebhookDelivery,
+};
+use zeroxbridge_sequencer::webhooks::{
+    verify_signature, WebhookDispatcher, DELIVERY_HEADER, SIGNATURE_HEADER,
+};
+
+const SECRET: &str = "integration-test-secret";
+
+// The dispatcher sends the due deliveries of every active webhook, so tests running it
+// must not interleave
+static DISPATCH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Delivery received by the test webhook
+struct Received {
+    delivery: String,
+    signature: String,
+    body: Bytes,
+}
+
+impl Received {
+    fn payload(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap()
+    }
+}
+
+/// Webhook recording every delivery, answering those about a commitment hash in
+/// `failures` with a 500 until its count runs out
+#[derive(Clone, Default)]
+struct Receiver {
+    received: Arc<Mutex<Vec<Received>>>,
+    failures: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Receiver {
+    fn fail(&self, commitment_hash: &str, times: usize) {
+        self.failures
+            .lock()
+            .unwrap()
+            .insert(commitment_hash.to_string(), times);
+    }
+
+    /// Deliveries received about `commitment_hash`, in the order they arrived
+    fn about(&self, commitment_hash: &str) -> Vec<(String, String, Bytes)> {
+        self.received
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|received| received.payload()["commitment_hash"] == commitment_hash)
+            .map(|received| {
+                (
+                    received.delivery.clone(),
+                    received.signature.clone(),
+                    received.body.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let received = Received {
+        delivery: header(DELIVERY_HEADER),
+        signature: header(SIGNATURE_HEADER),
+        body,
+    };
+    let commitment_hash = received.payload()["commitment_hash"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    receiver.received.lock().unwrap().push(received);
+
+    match receiver.failures.lock().unwrap().get_mut(&commitment_hash) {
+        Some(left) if *left > 0 => {
+            *left -= 1;
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        _ => StatusCode::OK,
+    }
+}
+
+/// Serves `receiver` on an ephemeral port, returning the URL to register
+async fn serve(receiver: Receiver) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = Router::new()
+        .route("/webhook", post(receive))
+        .with_state(receiver);
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{}/webhook", addr)
+}
+
+fn admin_request(method: Method, uri: &str, body: Option<Value>) -> Request<Body> {
+    let uri: Uri = uri.parse().unwrap();
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let hash = H256::from(admin_request_hash(&method, &uri, body.as_bytes()));
+
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    for (header, key) in ADMIN_SIGNATURE_HEADERS.iter().zip(ADMIN_KEYS) {
+        let wallet: LocalWallet = key.parse().unwrap();
+        let signature = wallet.sign_hash(hash).unwrap();
+        request = request.header(*header, format!("0x{}", signature));
+    }
+    request.body(Body::from(body)).unwrap()
+}
+
+async fn send(router: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+/// Deactivates the webhooks of earlier runs and interrupted tests, so only the test's
+/// own webhook has due deliveries
+async fn deactivate_webhooks(pool: &PgPool) {
+    sqlx::query!("UPDATE webhooks SET active = FALSE")
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// Registers a webhook at `url` for deposit status changes through the admin API
+async fn register(router: &Router, url: &str) -> i32 {
+    let (status, body) = send(
+        router,
+        admin_request(
+            Method::POST,
+            "/admin/webhooks",
+            Some(json!({
+                "url": url,
+                "secret": SECRET,
+                "event_types": ["deposit.status_changed"],
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("secret").is_none());
+    body["id"].as_i64().unwrap() as i32
+}
+
+async fn create_deposit(pool: &PgPool) -> (i32, String) {
+    let commitment_hash = random_commitment();
+    let id = insert_deposit(
+        pool,
+        "0xwebhooks",
+        &BigDecimal::from(1000),
+        &commitment_hash,
+    )
+    .await
+    .unwrap();
+    (id, commitment_hash)
+}
+
+/// Delivery of the creation of the deposit with `commitment_hash`
+async fn creation_delivery(
+    pool: &PgPool,
+    webhook_id: i32,
+    commitment_hash: &str,
+) -> WebhookDelivery {
+    fetch_webhook_deliveries(pool, webhook_id, 1_000)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|delivery| {
+            delivery.payload["commitment_hash"] == commitment_hash
+                && delivery.payload["old_status"].is_null()
+        })
+        .unwrap()
+}
+
+fn dispatcher(pool: &PgPool, max_attempts: u32) -> WebhookDispatcher {
+    WebhookDispatcher::new(
+        pool.clone(),
+        WebhookConfig {
+            max_attempts,
+            retry_backoff_ms: 0,
+            ..WebhookConfig::default()
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_status_changes_are_delivered_signed() {
+    let _guard = DISPATCH_LOCK.lock().await;
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    deactivate_webhooks(&app.db).await;
+    let receiver = Receiver::default();
+    let webhook_id = register(&router, &serve(receiver.clone()).await).await;
+
+    let (status, webhooks) =
+        send(&router, admin_request(Method::GET, "/admin/webhooks", None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(webhooks
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|webhook| webhook["id"] == webhook_id && webhook["active"] == true));
+
+    let (deposit_id, commitment_hash) = create_deposit(&app.db).await;
+    sqlx::query!(
+        "UPDATE deposits SET status = 'PENDING_TREE_INCLUSION' WHERE id = $1",
+        deposit_id
+    )
+    .execute(&app.db)
+    .await
+    .unwrap();
+    dispatcher(&app.db, 3).dispatch_due().await.unwrap();
+
+    let received = receiver.about(&commitment_hash);
+    assert_eq!(received.len(), 2);
+    for (_, signature, body) in &received {
+        assert!(verify_signature(SECRET, body, signature));
+    }
+    let created: Value = serde_json::from_slice(&received[0].2).unwrap();
+    assert_eq!(created["event"], "deposit.status_changed");
+    assert_eq!(created["entity_type"], "deposit");
+    assert_eq!(created["id"], deposit_id);
+    assert_eq!(created["old_status"], Value::Null);
+    assert_eq!(created["new_status"], "pending");
+    assert!(created["timestamp"].is_string());
+    let changed: Value = serde_json::from_slice(&received[1].2).unwrap();
+    assert_eq!(changed["old_status"], "pending");
+    assert_eq!(changed["new_status"], "PENDING_TREE_INCLUSION");
+
+    let (status, deleted) = send(
+        &router,
+        admin_request(
+            Method::DELETE,
+            &format!("/admin/webhooks/{}", webhook_id),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(deleted["id"], webhook_id);
+}
+
+#[tokio::test]
+async fn test_failed_delivery_is_retried_and_not_redelivered() {
+    let _guard = DISPATCH_LOCK.lock().await;
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    deactivate_webhooks(&app.db).await;
+    let receiver = Receiver::default();
+    let webhook_id = register(&router, &serve(receiver.clone()).await).await;
+    let dispatcher = dispatcher(&app.db, 3);
+
+    let (_, commitment_hash) = create_deposit(&app.db).await;
+    receiver.fail(&commitment_hash, 1);
+    let delivery = creation_delivery(&app.db, webhook_id, &commitment_hash).await;
+
+    let report = dispatcher.dispatch_due().await.unwrap();
+    assert!(report.retried.contains(&delivery.id));
+    let retried = creation_delivery(&app.db, webhook_id, &commitment_hash).await;
+    assert_eq!(retried.status, "pending");
+    assert_eq!(retried.attempts, 1);
+    assert_eq!(retried.response_status, Some(500));
+
+    let report = dispatcher.dispatch_due().await.unwrap();
+    assert!(report.delivered.contains(&delivery.id));
+    let report = dispatcher.dispatch_due().await.unwrap();
+    assert!(!report.delivered.contains(&delivery.id));
+
+    // Both attempts carried the same delivery id and none followed the success
+    let received = receiver.about(&commitment_hash);
+    assert_eq!(received.len(), 2);
+    assert!(received
+        .iter()
+        .all(|(id, _, _)| *id == delivery.id.to_string()));
+
+    let (status, deliveries) = send(
+        &router,
+        admin_request(
+            Method::GET,
+            &format!("/admin/webhooks/{}/deliveries", webhook_id),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let delivered = deliveries
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|row| row["id"] == delivery.id)
+        .unwrap();
+    assert_eq!(delivered["status"], "delivered");
+    assert_eq!(delivered["attempts"], 2);
+    assert_eq!(delivered["response_status"], 200);
+    assert_eq!(delivered["last_error"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_delivery_fails_after_max_attempts() {
+    let _guard = DISPATCH_LOCK.lock().await;
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    deactivate_webhooks(&app.db).await;
+    let receiver = Receiver::default();
+    let webhook_id = register(&router, &serve(receiver.clone()).await).await;
+    let dispatcher = dispatcher(&app.db, 2);
+
+    let (_, commitment_hash) = create_deposit(&app.db).await;
+    receiver.fail(&commitment_hash, usize::MAX);
+    let delivery = creation_delivery(&app.db, webhook_id, &commitment_hash).await;
+
+    let report = dispatcher.dispatch_due().await.unwrap();
+    assert!(report.retried.contains(&delivery.id));
+    let report = dispatcher.dispatch_due().await.unwrap();
+    assert!(report.failed.contains(&delivery.id));
+    dispatcher.dispatch_due().await.unwrap();
+
+    let failed = creation_delivery(&app.db, webhook_id, &commitment_hash).await;
+    assert_eq!(failed.status, "failed");
+    assert_eq!(failed.attempts, 2);
+    assert!(failed.last_error.unwrap().contains("500"));
+    assert_eq!(receiver.about(&commitment_hash).len(), 2);
+}
+
+#[tokio::test]
+async fn test_unknown_event_type_is_rejected() {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+
+    let (status, body) = send(
+        &router,
+        admin_request(
+            Method::POST,
+            "/admin/webhooks",
+            Some(json!({
+                "url": "https://example.com/webhook",
+                "secret": SECRET,
+                "event_types": ["deposit.created"],
+            })),
+        ),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_event_type");
+}
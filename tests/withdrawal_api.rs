@@ -1,7 +1,6 @@
 #[path = "utils.rs"]
 mod utils;
 
-use std::usize;
 
 use axum::{
     body::Body,
@@ -10,7 +9,44 @@ use axum::{
 use serde_json::json;
 use tower::ServiceExt;
 use utils::create_test_app;
-use zeroxbridge_sequencer::api::routes::create_router;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::{create_router, create_router_with_config};
+use zeroxbridge_sequencer::utils::BurnData;
+
+fn random_commitment() -> String {
+    format!("0x{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+async fn assert_commitment_rejected(commitment_hash: &str, reason: &str) {
+    let app = create_test_app().await;
+    let router = create_router(app.db.clone());
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/withdrawals")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "stark_pub_key": format!("0x{:064x}", 0xabc123),
+                "amount": 5000,
+                "commitment_hash": commitment_hash,
+                "l1_token": "0xtoken123"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(parsed["error"]["code"], "invalid_commitment_hash");
+    let message = parsed["error"]["message"].as_str().unwrap();
+    assert!(message.contains(reason), "unexpected message: {}", message);
+}
 
 #[tokio::test]
 async fn test_post_valid_withdrawal() {
@@ -23,9 +59,9 @@ async fn test_post_valid_withdrawal() {
         .header("content-type", "application/json")
         .body(Body::from(
             json!({
-                "stark_pub_key": "0xabc123",
+                "stark_pub_key": "0x0000000000000000000000000000000000000000000000000000000000abc123",
                 "amount": 5000,
-                "commitment_hash": "0xcommitment123",
+                "commitment_hash": random_commitment(),
                 "l1_token": "0xtoken123"  // ADDED: New required field
             })
             .to_string(),
@@ -43,6 +79,58 @@ async fn test_post_valid_withdrawal() {
     assert!(parsed.get("withdrawal_id").is_some());
 }
 
+#[tokio::test]
+async fn test_withdrawal_l1_hash_is_eip712_typed_data_hash() {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    let stark_pub_key = format!("0x{:0>64}", Uuid::new_v4().simple().to_string());
+
+    let before = chrono::Utc::now().timestamp() as u64;
+    let request = Request::builder()
+        .method("POST")
+        .uri("/withdrawals")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "stark_pub_key": stark_pub_key,
+                "amount": 5000,
+                "commitment_hash": random_commitment(),
+                "l1_token": "0xtoken123"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let after = chrono::Utc::now().timestamp() as u64;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let id = parsed["withdrawal_id"].as_i64().unwrap() as i32;
+    let row = sqlx::query!("SELECT l1_hash, nonce FROM withdrawals WHERE id = $1", id)
+        .fetch_one(&app.db)
+        .await
+        .unwrap();
+
+    // The handler stamps the burn with the current time, which lies within the request
+    let domain_separator = BurnData::hex_to_bytes32(&app.config.relayer.domain_separator).unwrap();
+    let nonce = row.nonce.unwrap() as u64;
+    let expected: Vec<String> = (before..=after)
+        .map(|time_stamp| {
+            let burn = BurnData::new(stark_pub_key.clone(), 5000, nonce, time_stamp);
+            format!("0x{}", hex::encode(burn.eip712_hash(&domain_separator)))
+        })
+        .collect();
+    let l1_hash = row.l1_hash.unwrap();
+    assert!(
+        expected.contains(&l1_hash),
+        "unexpected l1_hash {}",
+        l1_hash
+    );
+}
+
 #[tokio::test]
 async fn test_post_invalid_withdrawal() {
     let app = create_test_app().await;
@@ -80,6 +168,53 @@ async fn test_post_invalid_withdrawal() {
     );
 }
 
+#[tokio::test]
+async fn test_post_withdrawal_with_unpadded_stark_key() {
+    let app = create_test_app().await;
+    let router = create_router(app.db.clone());
+
+    // The same felt as in test_post_valid_withdrawal, without its leading zeros
+    let request = Request::builder()
+        .method("POST")
+        .uri("/withdrawals")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "stark_pub_key": "0xabc123",
+                "amount": 5000,
+                "commitment_hash": random_commitment(),
+                "l1_token": "0xtoken123"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(parsed["error"]["code"], "invalid_stark_key");
+}
+
+#[tokio::test]
+async fn test_withdrawal_commitment_hash_without_prefix() {
+    assert_commitment_rejected(&"ab".repeat(33), "start with 0x").await;
+}
+
+#[tokio::test]
+async fn test_withdrawal_commitment_hash_with_wrong_length() {
+    assert_commitment_rejected(&format!("0x{}", "ab".repeat(31)), "66 characters").await;
+    assert_commitment_rejected(&format!("0x{}", "ab".repeat(33)), "66 characters").await;
+}
+
+#[tokio::test]
+async fn test_withdrawal_commitment_hash_with_non_hex_digits() {
+    assert_commitment_rejected(&format!("0x{}zz", "ab".repeat(31)), "hex digits").await;
+}
+
 #[tokio::test]
 async fn test_get_pending_withdrawals() {
     let app = create_test_app().await;
@@ -92,9 +227,9 @@ async fn test_get_pending_withdrawals() {
         .header("content-type", "application/json")
         .body(Body::from(
             json!({
-                "stark_pub_key": "0xtest123",
+                "stark_pub_key": "0x0000000000000000000000000000000000000000000000000000000000123456",
                 "amount": 500,
-                "commitment_hash": "0xcommitment456",
+                "commitment_hash": random_commitment(),
                 "l1_token": "0xtoken789"  // ADDED: New required field
             })
             .to_string(),
@@ -0,0 +1,162 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode, Uri},
+    Router,
+};
+use ethers::signers::LocalWallet;
+use ethers::types::H256;
+use serde_json::{json, Value};
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::{create_test_app, ADMIN_KEYS};
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::auth::{admin_request_hash, ADMIN_SIGNATURE_HEADERS};
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::config::{JanitorConfig, NetworkId};
+use zeroxbridge_sequencer::db::database::{
+    check_nonce_gap, get_and_increment_withdrawal_nonce, insert_withdrawal_v2,
+    update_withdrawal_status, WithdrawalNonceGap, WITHDRAWAL_STATUS_TOMBSTONE,
+};
+use zeroxbridge_sequencer::maintenance::Janitor;
+
+/// Creates a withdrawal of `stark_pub_key` with the next nonce, returning its id
+async fn create_withdrawal(pool: &PgPool, stark_pub_key: &str) -> i32 {
+    let mut tx = pool.begin().await.unwrap();
+    let nonce = get_and_increment_withdrawal_nonce(&mut tx, stark_pub_key)
+        .await
+        .unwrap();
+    let id = insert_withdrawal_v2(
+        &mut tx,
+        &NetworkId::default(),
+        stark_pub_key,
+        &BigDecimal::from(1000),
+        "0xtoken",
+        &format!("0x{}", Uuid::new_v4().simple()),
+        "0xl1hash",
+        nonce,
+    )
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+    id
+}
+
+/// Nonce gaps the janitor alerts on with `threshold`
+async fn alerted_gaps(pool: &PgPool, threshold: u64) -> Vec<WithdrawalNonceGap> {
+    let config = JanitorConfig {
+        clean_proof_dirs: false,
+        reset_stale_processing: false,
+        vacuum_dead_letters: false,
+        expire_unseen_deposits: false,
+        check_withdrawal_nonce_gaps: true,
+        withdrawal_nonce_gap_threshold: threshold,
+        ..JanitorConfig::default()
+    };
+    Janitor::new(pool.clone(), config)
+        .run_once()
+        .await
+        .unwrap()
+        .withdrawal_nonce_gaps
+}
+
+async fn fill_nonce_gap(router: &Router, stark_pub_key: &str) -> (StatusCode, Value) {
+    let method = Method::POST;
+    let uri = Uri::from_static("/admin/withdrawals/fill-nonce-gap");
+    let body = json!({ "stark_pub_key": stark_pub_key }).to_string();
+    let hash = H256::from(admin_request_hash(&method, &uri, body.as_bytes()));
+
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    for (header, key) in ADMIN_SIGNATURE_HEADERS.iter().zip(ADMIN_KEYS) {
+        let wallet: LocalWallet = key.parse().unwrap();
+        let signature = wallet.sign_hash(hash).unwrap();
+        request = request.header(*header, format!("0x{}", signature));
+    }
+
+    let response = router
+        .clone()
+        .oneshot(request.body(Body::from(body)).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_nonce_of_failed_withdrawal_is_detected_and_filled() {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    let stark_pub_key = format!("0x{}", Uuid::new_v4().simple());
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        ids.push(create_withdrawal(&app.db, &stark_pub_key).await);
+    }
+    assert_eq!(
+        check_nonce_gap(&app.db, &stark_pub_key).await.unwrap(),
+        None
+    );
+
+    let mut conn = app.db.acquire().await.unwrap();
+    update_withdrawal_status(&mut conn, ids[1], "failed", Some("relay reverted"))
+        .await
+        .unwrap();
+    assert_eq!(
+        check_nonce_gap(&app.db, &stark_pub_key).await.unwrap(),
+        Some(2)
+    );
+
+    // Nonce 3 waits on nonce 2
+    let gap = WithdrawalNonceGap {
+        stark_pub_key: stark_pub_key.clone(),
+        nonce: 2,
+        waiting: 1,
+    };
+    assert!(alerted_gaps(&app.db, 0).await.contains(&gap));
+    assert!(!alerted_gaps(&app.db, 1).await.contains(&gap));
+
+    let (status, body) = fill_nonce_gap(&router, &stark_pub_key).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["nonce"], 2);
+    let tombstone = sqlx::query!(
+        "SELECT stark_pub_key, amount, nonce, status FROM withdrawals WHERE id = $1",
+        body["withdrawal_id"].as_i64().unwrap() as i32
+    )
+    .fetch_one(&app.db)
+    .await
+    .unwrap();
+    assert_eq!(tombstone.stark_pub_key, stark_pub_key);
+    assert_eq!(tombstone.amount, BigDecimal::from(0));
+    assert_eq!(tombstone.nonce, Some(2));
+    assert_eq!(tombstone.status, WITHDRAWAL_STATUS_TOMBSTONE);
+
+    assert_eq!(
+        check_nonce_gap(&app.db, &stark_pub_key).await.unwrap(),
+        None
+    );
+    assert!(!alerted_gaps(&app.db, 0).await.contains(&gap));
+}
+
+#[tokio::test]
+async fn test_key_without_gap_is_not_filled() {
+    let app = create_test_app().await;
+    let router = create_router_with_config(app.db.clone(), &app.config);
+    let stark_pub_key = format!("0x{}", Uuid::new_v4().simple());
+    create_withdrawal(&app.db, &stark_pub_key).await;
+
+    let (status, body) = fill_nonce_gap(&router, &stark_pub_key).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["error"]["code"], "no_nonce_gap");
+
+    let unknown = format!("0x{}", Uuid::new_v4().simple());
+    let (status, _) = fill_nonce_gap(&router, &unknown).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
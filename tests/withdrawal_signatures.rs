@@ -0,0 +1,154 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+use starknet_crypto::{get_public_key, rfc6979_generate_k, sign, Felt};
+use tower::ServiceExt;
+use utils::{create_test_app, random_commitment};
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router_with_config;
+use zeroxbridge_sequencer::utils::BurnData;
+
+/// Order of the Stark curve
+const EC_ORDER: Felt =
+    Felt::from_hex_unchecked("0x0800000000000010ffffffffffffffffb781126dcae7b2321e66a241adc64d2f");
+const HALF_EC_ORDER: Felt =
+    Felt::from_hex_unchecked("0x4000000000000087fffffffffffffffdbc08936e573d9190f335120d6e32697");
+const TIMESTAMP: u64 = 1_700_000_000;
+
+/// A Stark key of its own, so its first withdrawal takes nonce 1
+struct Signer {
+    private_key: Felt,
+    stark_pub_key: String,
+}
+
+impl Signer {
+    fn new() -> Self {
+        let private_key = Felt::from_hex(&format!("0x{}", Uuid::new_v4().simple())).unwrap();
+        let public_key = get_public_key(&private_key);
+        Self {
+            private_key,
+            stark_pub_key: format!("0x{}", hex::encode(public_key.to_bytes_be())),
+        }
+    }
+
+    /// Signs the burn of `amount` with `nonce`, returning `(r, low s, high s)`
+    fn sign(&self, domain_separator: &[u8; 32], amount: u64, nonce: u64) -> (Felt, Felt, Felt) {
+        let burn = BurnData::new(self.stark_pub_key.clone(), amount, nonce, TIMESTAMP);
+        let message = burn.signing_hash(domain_separator);
+        let k = rfc6979_generate_k(&message, &self.private_key, None);
+        let signature = sign(&self.private_key, &message, &k).unwrap();
+
+        let flipped = EC_ORDER - signature.s;
+        if signature.s > HALF_EC_ORDER {
+            (signature.r, flipped, signature.s)
+        } else {
+            (signature.r, signature.s, flipped)
+        }
+    }
+}
+
+async fn setup() -> (Router, [u8; 32]) {
+    let app = create_test_app().await;
+    let mut config = app.config.clone();
+    config.server.verify_withdrawal_signatures = true;
+    let domain_separator = BurnData::hex_to_bytes32(&config.relayer.domain_separator).unwrap();
+    (
+        create_router_with_config(app.db.clone(), &config),
+        domain_separator,
+    )
+}
+
+async fn post_withdrawal(router: Router, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/withdrawals")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn withdrawal(signer: &Signer, amount: u64, signature: Option<(Felt, Felt)>) -> Value {
+    let mut body = json!({
+        "stark_pub_key": signer.stark_pub_key,
+        "amount": amount,
+        "commitment_hash": random_commitment(),
+        "l1_token": "0xtoken123",
+        "timestamp": TIMESTAMP,
+    });
+    if let Some((r, s)) = signature {
+        body["signature"] = json!({
+            "r": format!("0x{}", hex::encode(r.to_bytes_be())),
+            "s": format!("0x{}", hex::encode(s.to_bytes_be())),
+        });
+    }
+    body
+}
+
+#[tokio::test]
+async fn test_signed_withdrawal_is_accepted() {
+    let (router, domain_separator) = setup().await;
+    let signer = Signer::new();
+    let (r, s, _) = signer.sign(&domain_separator, 5000, 1);
+
+    let (status, body) = post_withdrawal(router, withdrawal(&signer, 5000, Some((r, s)))).await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["withdrawal_id"].as_i64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_tampered_withdrawal_is_rejected_without_using_the_nonce() {
+    let (router, domain_separator) = setup().await;
+    let signer = Signer::new();
+    let (r, s, _) = signer.sign(&domain_separator, 5000, 1);
+
+    // Signed for 5000, submitted for more
+    let (status, body) =
+        post_withdrawal(router.clone(), withdrawal(&signer, 9000, Some((r, s)))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"]["code"], "invalid_signature");
+
+    // The rejected request did not take nonce 1
+    let (status, _) = post_withdrawal(router, withdrawal(&signer, 5000, Some((r, s)))).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_high_s_signature_is_rejected() {
+    let (router, domain_separator) = setup().await;
+    let signer = Signer::new();
+    let (r, _, high_s) = signer.sign(&domain_separator, 5000, 1);
+
+    let (status, body) =
+        post_withdrawal(router, withdrawal(&signer, 5000, Some((r, high_s)))).await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"]["code"], "invalid_signature");
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("half the curve order"));
+}
+
+#[tokio::test]
+async fn test_unsigned_withdrawal_is_rejected() {
+    let (router, _) = setup().await;
+    let signer = Signer::new();
+
+    let (status, body) = post_withdrawal(router, withdrawal(&signer, 5000, None)).await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"]["code"], "missing_signature");
+}
@@ -0,0 +1,128 @@
+#[path = "utils.rs"]
+mod utils;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use sqlx::{types::BigDecimal, PgPool};
+use tower::ServiceExt;
+use utils::create_test_app;
+use uuid::Uuid;
+use zeroxbridge_sequencer::api::routes::create_router;
+use zeroxbridge_sequencer::api::types::WithdrawalStatusResponse;
+use zeroxbridge_sequencer::config::NetworkId;
+use zeroxbridge_sequencer::db::database::{insert_withdrawal_v2, update_withdrawal_status};
+
+async fn setup() -> (Router, PgPool) {
+    let app = create_test_app().await;
+    (create_router(app.db.clone()), app.db.clone())
+}
+
+async fn create_withdrawal(pool: &PgPool) -> (i32, String) {
+    let commitment_hash = format!("0x{}", Uuid::new_v4().simple());
+    let mut tx = pool.begin().await.unwrap();
+    let id = insert_withdrawal_v2(
+        &mut tx,
+        &NetworkId::default(),
+        "0xstatus123",
+        &BigDecimal::from(1000),
+        "0xtoken",
+        &commitment_hash,
+        "0xl1hash",
+        1,
+    )
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+    (id, commitment_hash)
+}
+
+async fn get(router: Router, uri: &str) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_withdrawal_timeline_follows_status_changes() {
+    let (router, pool) = setup().await;
+    let (id, commitment_hash) = create_withdrawal(&pool).await;
+
+    let mut conn = pool.acquire().await.unwrap();
+    for status in [
+        "PENDING_TREE_INCLUSION",
+        "PENDING_PROOF_GENERATION",
+        "ready_for_relay",
+    ] {
+        update_withdrawal_status(&mut conn, id, status, None)
+            .await
+            .unwrap();
+    }
+    update_withdrawal_status(&mut conn, id, "failed", Some("L1 relay reverted"))
+        .await
+        .unwrap();
+
+    let (status, body) = get(router.clone(), &format!("/withdrawals/{}", id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: WithdrawalStatusResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(response.withdrawal.id, id);
+    assert_eq!(response.withdrawal.status, "failed");
+    assert!(response.proof.is_none());
+
+    let stages: Vec<&str> = response.timeline.iter().map(|e| e.stage.as_str()).collect();
+    assert_eq!(
+        stages,
+        vec![
+            "created",
+            "tree_inclusion",
+            "proof_generation",
+            "l1_relay",
+            "failed"
+        ]
+    );
+    assert!(response
+        .timeline
+        .windows(2)
+        .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+    assert_eq!(
+        response.timeline.last().unwrap().error.as_deref(),
+        Some("L1 relay reverted")
+    );
+
+    let (status, body) = get(
+        router,
+        &format!(
+            "/withdrawals/by-hash/{}?include_proof=true",
+            commitment_hash
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let by_hash: WithdrawalStatusResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(by_hash.withdrawal.id, id);
+    assert_eq!(by_hash.timeline.len(), 5);
+}
+
+#[tokio::test]
+async fn test_unknown_withdrawal_returns_404() {
+    let (router, _) = setup().await;
+
+    let (status, body) = get(router.clone(), "/withdrawals/2147483647").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(parsed["error"]["code"], "withdrawal_not_found");
+
+    let (status, _) = get(router, "/withdrawals/by-hash/0xdoesnotexist").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}